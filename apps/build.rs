@@ -54,6 +54,18 @@ fn main() {
     // Tell Cargo that if the given file changes, to rerun this build script.
     println!("cargo:rerun-if-changed={}", PROTO_SRC);
 
+    // Compile the tx gateway service
+    tonic_build::configure()
+        .protoc_arg("--experimental_allow_proto3_optional")
+        .compile(&[format!("{}/gateway.proto", PROTO_SRC)], &[PROTO_SRC])
+        .unwrap();
+
+    // Compile the client of remote signers
+    tonic_build::configure()
+        .build_server(false)
+        .compile(&[format!("{}/signer.proto", PROTO_SRC)], &[PROTO_SRC])
+        .unwrap();
+
     // Tell Cargo to build when the `NAMADA_DEV` env var changes
     println!("cargo:rerun-if-env-changed=NAMADA_DEV");
     // Enable "dev" feature if `NAMADA_DEV` is trueish
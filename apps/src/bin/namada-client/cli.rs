@@ -55,6 +55,15 @@ pub async fn main() -> Result<()> {
                     tx::submit_ibc_transfer::<HttpClient>(&client, ctx, args)
                         .await?;
                 }
+                Sub::AddToEthBridgePool(AddToEthBridgePool(args)) => {
+                    wait_until_node_is_synched(&args.tx.ledger_address).await;
+                    let client =
+                        HttpClient::new(args.tx.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    tx::submit_bridge_pool_tx::<HttpClient>(&client, ctx, args)
+                        .await?;
+                }
                 Sub::TxUpdateVp(TxUpdateVp(args)) => {
                     wait_until_node_is_synched(&args.tx.ledger_address).await;
                     let client =
@@ -64,6 +73,17 @@ pub async fn main() -> Result<()> {
                     tx::submit_update_vp::<HttpClient>(&client, &mut ctx, args)
                         .await?;
                 }
+                Sub::TxUpdateSigners(TxUpdateSigners(args)) => {
+                    wait_until_node_is_synched(&args.tx.ledger_address).await;
+                    let client =
+                        HttpClient::new(args.tx.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    tx::submit_update_signers::<HttpClient>(
+                        &client, &mut ctx, args,
+                    )
+                    .await?;
+                }
                 Sub::TxInitAccount(TxInitAccount(args)) => {
                     wait_until_node_is_synched(&args.tx.ledger_address).await;
                     let client =
@@ -139,6 +159,17 @@ pub async fn main() -> Result<()> {
                     tx::submit_unbond::<HttpClient>(&client, &mut ctx, args)
                         .await?;
                 }
+                Sub::Redelegate(Redelegate(args)) => {
+                    wait_until_node_is_synched(&args.tx.ledger_address).await;
+                    let client =
+                        HttpClient::new(args.tx.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    tx::submit_redelegate::<HttpClient>(
+                        &client, &mut ctx, args,
+                    )
+                    .await?;
+                }
                 Sub::Withdraw(Withdraw(args)) => {
                     wait_until_node_is_synched(&args.tx.ledger_address).await;
                     let client =
@@ -148,6 +179,28 @@ pub async fn main() -> Result<()> {
                     tx::submit_withdraw::<HttpClient>(&client, ctx, args)
                         .await?;
                 }
+                Sub::ClaimRewards(ClaimRewards(args)) => {
+                    wait_until_node_is_synched(&args.tx.ledger_address).await;
+                    let client =
+                        HttpClient::new(args.tx.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    tx::submit_claim_rewards::<HttpClient>(
+                        &client, &mut ctx, args,
+                    )
+                    .await?;
+                }
+                Sub::TxChangeConsensusKey(TxChangeConsensusKey(args)) => {
+                    wait_until_node_is_synched(&args.tx.ledger_address).await;
+                    let client =
+                        HttpClient::new(args.tx.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    tx::submit_change_consensus_key::<HttpClient>(
+                        &client, ctx, args,
+                    )
+                    .await?;
+                }
                 // Ledger queries
                 Sub::QueryEpoch(QueryEpoch(args)) => {
                     wait_until_node_is_synched(&args.ledger_address).await;
@@ -169,6 +222,25 @@ pub async fn main() -> Result<()> {
                     )
                     .await;
                 }
+                Sub::QueryBridgePool(QueryBridgePool(args)) => {
+                    wait_until_node_is_synched(&args.query.ledger_address)
+                        .await;
+                    let client =
+                        HttpClient::new(args.query.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    rpc::query_bridge_pool(&client, &mut ctx.wallet, args)
+                        .await;
+                }
+                Sub::QueryErc20(QueryErc20(args)) => {
+                    wait_until_node_is_synched(&args.query.ledger_address)
+                        .await;
+                    let client =
+                        HttpClient::new(args.query.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    rpc::query_erc20(&client, &mut ctx.wallet, args).await;
+                }
                 Sub::QueryConversions(QueryConversions(args)) => {
                     wait_until_node_is_synched(&args.query.ledger_address)
                         .await;
@@ -243,6 +315,15 @@ pub async fn main() -> Result<()> {
                     let args = args.to_sdk(&mut ctx);
                     rpc::query_slashes(&client, &mut ctx.wallet, args).await;
                 }
+                Sub::QueryRewards(QueryRewards(args)) => {
+                    wait_until_node_is_synched(&args.query.ledger_address)
+                        .await;
+                    let client =
+                        HttpClient::new(args.query.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    rpc::query_rewards(&client, args).await;
+                }
                 Sub::QueryDelegations(QueryDelegations(args)) => {
                     wait_until_node_is_synched(&args.query.ledger_address)
                         .await;
@@ -298,6 +379,15 @@ pub async fn main() -> Result<()> {
                     let args = args.to_sdk(&mut ctx);
                     rpc::query_proposal_result(&client, args).await;
                 }
+                Sub::VerifyOfflineProposal(VerifyOfflineProposal(args)) => {
+                    wait_until_node_is_synched(&args.query.ledger_address)
+                        .await;
+                    let client =
+                        HttpClient::new(args.query.ledger_address.clone())
+                            .unwrap();
+                    let args = args.to_sdk(&mut ctx);
+                    rpc::verify_offline_proposal(&client, args).await;
+                }
                 Sub::QueryProtocolParameters(QueryProtocolParameters(args)) => {
                     wait_until_node_is_synched(&args.query.ledger_address)
                         .await;
@@ -6,11 +6,9 @@ use std::io::{self, Write};
 use borsh::BorshSerialize;
 use color_eyre::eyre::Result;
 use itertools::sorted;
-use masp_primitives::zip32::ExtendedFullViewingKey;
-use namada::ledger::masp::find_valid_diversifier;
 use namada::ledger::wallet::{DecryptionError, FindKeyError};
 use namada::types::key::*;
-use namada::types::masp::{MaspValue, PaymentAddress};
+use namada::types::masp::MaspValue;
 use namada_apps::cli;
 use namada_apps::cli::args::CliToSdk;
 use namada_apps::cli::{args, cmds, Context};
@@ -34,6 +32,9 @@ pub fn main() -> Result<()> {
             cmds::WalletKey::Export(cmds::Export(args)) => {
                 key_export(ctx, args)
             }
+            cmds::WalletKey::Migrate(cmds::KeyMigrate(args)) => {
+                key_migrate(ctx, args)
+            }
         },
         cmds::NamadaWallet::Address(sub) => match sub {
             cmds::WalletAddress::Gen(cmds::AddressGen(args)) => {
@@ -70,6 +71,12 @@ pub fn main() -> Result<()> {
             cmds::WalletMasp::FindAddrKey(cmds::MaspFindAddrKey(args)) => {
                 address_key_find(ctx, args)
             }
+            cmds::WalletMasp::ExportViewingKey(cmds::MaspExportViewingKey(
+                args,
+            )) => viewing_key_export(ctx, args),
+            cmds::WalletMasp::ImportViewingKey(cmds::MaspImportViewingKey(
+                args,
+            )) => viewing_key_import(ctx, args),
         },
     }
     Ok(())
@@ -221,7 +228,7 @@ fn spending_key_gen(
     );
 }
 
-/// Generate a shielded payment address from the given key.
+/// Generate shielded payment addresses from the given key.
 fn payment_address_gen(
     ctx: Context,
     args::MaspPayAddrGen {
@@ -229,29 +236,80 @@ fn payment_address_gen(
         alias_force,
         viewing_key,
         pin,
+        count,
     }: args::MaspPayAddrGen,
 ) {
     let alias = alias.to_lowercase();
-    let viewing_key = ExtendedFullViewingKey::from(viewing_key).fvk.vk;
-    let (div, _g_d) = find_valid_diversifier(&mut OsRng);
-    let payment_addr = viewing_key
-        .to_payment_address(div)
-        .expect("a PaymentAddress");
     let mut wallet = ctx.wallet;
-    let alias = wallet
-        .insert_payment_addr(
+    let generated =
+        wallet.gen_payment_addrs(alias, &viewing_key, count, pin, alias_force);
+    if generated.is_empty() {
+        eprintln!("Payment address not added");
+        cli::safe_exit(1);
+    }
+    namada_apps::wallet::save(&wallet)
+        .unwrap_or_else(|err| eprintln!("{}", err));
+    for (alias, payment_addr) in generated {
+        println!(
+            "Successfully generated a payment address with the following \
+             alias: {}",
             alias,
-            PaymentAddress::from(payment_addr).pinned(pin),
-            alias_force,
-        )
-        .unwrap_or_else(|| {
-            eprintln!("Payment address not added");
-            cli::safe_exit(1);
-        });
+        );
+        println!("  {}", payment_addr);
+    }
+}
+
+/// Export a viewing key to a file.
+fn viewing_key_export(
+    ctx: Context,
+    args::MaspViewingKeyExport { alias }: args::MaspViewingKeyExport,
+) {
+    let alias = alias.to_lowercase();
+    let mut wallet = ctx.wallet;
+    let encoded = wallet.export_viewing_key(&alias).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        cli::safe_exit(1)
+    });
+    let file_name = format!("viewing_key_{}", alias);
+    let mut file = File::create(&file_name).unwrap();
+    file.write_all(encoded.as_bytes()).unwrap();
+    println!("Exported to file {}", file_name);
+}
+
+/// Import a viewing key from an exported file.
+fn viewing_key_import(
+    ctx: Context,
+    args::MaspViewingKeyImport {
+        alias,
+        alias_force,
+        file_path,
+    }: args::MaspViewingKeyImport,
+) {
+    let alias = alias.to_lowercase();
+    let encoded = std::fs::read_to_string(&file_path).unwrap_or_else(|err| {
+        eprintln!(
+            "Couldn't read the viewing key file {}: {}",
+            file_path.to_string_lossy(),
+            err
+        );
+        cli::safe_exit(1)
+    });
+    let mut wallet = ctx.wallet;
+    let alias = match wallet.import_viewing_key(alias, &encoded, alias_force) {
+        Ok(Some(alias)) => alias,
+        Ok(None) => {
+            eprintln!("Viewing key not added");
+            cli::safe_exit(1)
+        }
+        Err(err) => {
+            eprintln!("Invalid viewing key: {}", err);
+            cli::safe_exit(1)
+        }
+    };
     namada_apps::wallet::save(&wallet)
         .unwrap_or_else(|err| eprintln!("{}", err));
     println!(
-        "Successfully generated a payment address with the following alias: {}",
+        "Successfully imported a viewing key with the following alias: {}",
         alias,
     );
 }
@@ -324,33 +382,37 @@ fn key_and_address_restore(
         alias_force,
         unsafe_dont_encrypt,
         derivation_path,
+        count,
     }: args::KeyAndAddressRestore,
 ) {
     let mut wallet = ctx.wallet;
     let encryption_password =
         read_and_confirm_encryption_password(unsafe_dont_encrypt);
-    let (alias, _key) = wallet
-        .derive_key_from_user_mnemonic_code(
+    let keys = wallet
+        .derive_keys_from_user_mnemonic_code(
             scheme,
             alias,
             alias_force,
             derivation_path,
+            count,
             encryption_password,
         )
         .unwrap_or_else(|err| {
             eprintln!("{}", err);
             cli::safe_exit(1)
-        })
-        .unwrap_or_else(|| {
-            println!("No changes are persisted. Exiting.");
-            cli::safe_exit(0);
         });
+    if keys.is_empty() {
+        println!("No changes are persisted. Exiting.");
+        cli::safe_exit(0);
+    }
     namada_apps::wallet::save(&wallet)
         .unwrap_or_else(|err| eprintln!("{}", err));
-    println!(
-        "Successfully added a key and an address with alias: \"{}\"",
-        alias
-    );
+    for (alias, _key) in keys {
+        println!(
+            "Successfully added a key and an address with alias: \"{}\"",
+            alias
+        );
+    }
 }
 
 /// Generate a new keypair and derive implicit address from it and store them in
@@ -427,6 +489,9 @@ fn key_find(
             let pkh: PublicKeyHash = (&keypair.ref_to()).into();
             println!("Public key hash: {}", pkh);
             println!("Public key: {}", keypair.ref_to());
+            if let common::PublicKey::Secp256k1(pk) = keypair.ref_to() {
+                println!("Ethereum address: {}", pk.eth_address());
+            }
             if unsafe_show_secret {
                 println!("Secret key: {}", keypair);
             }
@@ -457,7 +522,9 @@ fn key_list(
         let mut w = stdout.lock();
         writeln!(w, "Known keys:").unwrap();
         for (alias, (stored_keypair, pkh)) in known_keys {
-            let encrypted = if stored_keypair.is_encrypted() {
+            let encrypted = if stored_keypair.is_legacy() {
+                "encrypted in a legacy format, see `key migrate`"
+            } else if stored_keypair.is_encrypted() {
                 "encrypted"
             } else {
                 "not encrypted"
@@ -470,6 +537,14 @@ fn key_list(
                 Ok(keypair) => {
                     writeln!(w, "    Public key: {}", keypair.ref_to())
                         .unwrap();
+                    if let common::PublicKey::Secp256k1(pk) = keypair.ref_to() {
+                        writeln!(
+                            w,
+                            "    Ethereum address: {}",
+                            pk.eth_address()
+                        )
+                        .unwrap();
+                    }
                     if unsafe_show_secret {
                         writeln!(w, "    Secret key: {}", keypair).unwrap();
                     }
@@ -507,6 +582,33 @@ fn key_export(ctx: Context, args::KeyExport { alias }: args::KeyExport) {
         })
 }
 
+/// Migrate the keys of the wallet to the current encryption format.
+fn key_migrate(
+    ctx: Context,
+    args::KeyMigrate {
+        unsafe_dont_encrypt,
+    }: args::KeyMigrate,
+) {
+    let mut wallet = ctx.wallet;
+    let migrated = match wallet.migrate_keys(!unsafe_dont_encrypt) {
+        Ok(migrated) => migrated,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("Action cancelled, no changes persisted.");
+            cli::safe_exit(1)
+        }
+    };
+    if migrated.is_empty() {
+        println!("All the keys are already in the current format.");
+        return;
+    }
+    namada_apps::wallet::save(&wallet)
+        .unwrap_or_else(|err| eprintln!("{}", err));
+    for alias in migrated {
+        println!("Migrated the key \"{}\".", alias);
+    }
+}
+
 /// List all known addresses.
 fn address_list(ctx: Context) {
     let wallet = ctx.wallet;
@@ -154,7 +154,9 @@ pub mod cmds {
                 .subcommand(TxCustom::def().display_order(1))
                 .subcommand(TxTransfer::def().display_order(1))
                 .subcommand(TxIbcTransfer::def().display_order(1))
+                .subcommand(AddToEthBridgePool::def().display_order(1))
                 .subcommand(TxUpdateVp::def().display_order(1))
+                .subcommand(TxUpdateSigners::def().display_order(1))
                 .subcommand(TxInitAccount::def().display_order(1))
                 .subcommand(TxRevealPk::def().display_order(1))
                 // Proposal transactions
@@ -164,22 +166,29 @@ pub mod cmds {
                 .subcommand(TxInitValidator::def().display_order(2))
                 .subcommand(Bond::def().display_order(2))
                 .subcommand(Unbond::def().display_order(2))
+                .subcommand(Redelegate::def().display_order(2))
                 .subcommand(Withdraw::def().display_order(2))
+                .subcommand(ClaimRewards::def().display_order(2))
+                .subcommand(TxChangeConsensusKey::def().display_order(2))
                 // Queries
                 .subcommand(QueryEpoch::def().display_order(3))
                 .subcommand(QueryTransfers::def().display_order(3))
+                .subcommand(QueryBridgePool::def().display_order(3))
+                .subcommand(QueryErc20::def().display_order(3))
                 .subcommand(QueryConversions::def().display_order(3))
                 .subcommand(QueryBlock::def().display_order(3))
                 .subcommand(QueryBalance::def().display_order(3))
                 .subcommand(QueryBonds::def().display_order(3))
                 .subcommand(QueryBondedStake::def().display_order(3))
                 .subcommand(QuerySlashes::def().display_order(3))
+                .subcommand(QueryRewards::def().display_order(3))
                 .subcommand(QueryDelegations::def().display_order(3))
                 .subcommand(QueryFindValidator::def().display_order(3))
                 .subcommand(QueryResult::def().display_order(3))
                 .subcommand(QueryRawBytes::def().display_order(3))
                 .subcommand(QueryProposal::def().display_order(3))
                 .subcommand(QueryProposalResult::def().display_order(3))
+                .subcommand(VerifyOfflineProposal::def().display_order(3))
                 .subcommand(QueryProtocolParameters::def().display_order(3))
                 // Utils
                 .subcommand(Utils::def().display_order(5))
@@ -190,7 +199,11 @@ pub mod cmds {
             let tx_custom = Self::parse_with_ctx(matches, TxCustom);
             let tx_transfer = Self::parse_with_ctx(matches, TxTransfer);
             let tx_ibc_transfer = Self::parse_with_ctx(matches, TxIbcTransfer);
+            let add_to_eth_bridge_pool =
+                Self::parse_with_ctx(matches, AddToEthBridgePool);
             let tx_update_vp = Self::parse_with_ctx(matches, TxUpdateVp);
+            let tx_update_signers =
+                Self::parse_with_ctx(matches, TxUpdateSigners);
             let tx_init_account = Self::parse_with_ctx(matches, TxInitAccount);
             let tx_init_validator =
                 Self::parse_with_ctx(matches, TxInitValidator);
@@ -201,9 +214,16 @@ pub mod cmds {
                 Self::parse_with_ctx(matches, TxVoteProposal);
             let bond = Self::parse_with_ctx(matches, Bond);
             let unbond = Self::parse_with_ctx(matches, Unbond);
+            let redelegate = Self::parse_with_ctx(matches, Redelegate);
             let withdraw = Self::parse_with_ctx(matches, Withdraw);
+            let claim_rewards = Self::parse_with_ctx(matches, ClaimRewards);
+            let tx_change_consensus_key =
+                Self::parse_with_ctx(matches, TxChangeConsensusKey);
             let query_epoch = Self::parse_with_ctx(matches, QueryEpoch);
             let query_transfers = Self::parse_with_ctx(matches, QueryTransfers);
+            let query_bridge_pool =
+                Self::parse_with_ctx(matches, QueryBridgePool);
+            let query_erc20 = Self::parse_with_ctx(matches, QueryErc20);
             let query_conversions =
                 Self::parse_with_ctx(matches, QueryConversions);
             let query_block = Self::parse_with_ctx(matches, QueryBlock);
@@ -212,6 +232,7 @@ pub mod cmds {
             let query_bonded_stake =
                 Self::parse_with_ctx(matches, QueryBondedStake);
             let query_slashes = Self::parse_with_ctx(matches, QuerySlashes);
+            let query_rewards = Self::parse_with_ctx(matches, QueryRewards);
             let query_delegations =
                 Self::parse_with_ctx(matches, QueryDelegations);
             let query_find_validator =
@@ -221,13 +242,17 @@ pub mod cmds {
             let query_proposal = Self::parse_with_ctx(matches, QueryProposal);
             let query_proposal_result =
                 Self::parse_with_ctx(matches, QueryProposalResult);
+            let verify_offline_proposal =
+                Self::parse_with_ctx(matches, VerifyOfflineProposal);
             let query_protocol_parameters =
                 Self::parse_with_ctx(matches, QueryProtocolParameters);
             let utils = SubCmd::parse(matches).map(Self::WithoutContext);
             tx_custom
                 .or(tx_transfer)
                 .or(tx_ibc_transfer)
+                .or(add_to_eth_bridge_pool)
                 .or(tx_update_vp)
+                .or(tx_update_signers)
                 .or(tx_init_account)
                 .or(tx_reveal_pk)
                 .or(tx_init_proposal)
@@ -235,21 +260,28 @@ pub mod cmds {
                 .or(tx_init_validator)
                 .or(bond)
                 .or(unbond)
+                .or(redelegate)
                 .or(withdraw)
+                .or(claim_rewards)
+                .or(tx_change_consensus_key)
                 .or(query_epoch)
                 .or(query_transfers)
+                .or(query_bridge_pool)
+                .or(query_erc20)
                 .or(query_conversions)
                 .or(query_block)
                 .or(query_balance)
                 .or(query_bonds)
                 .or(query_bonded_stake)
                 .or(query_slashes)
+                .or(query_rewards)
                 .or(query_delegations)
                 .or(query_find_validator)
                 .or(query_result)
                 .or(query_raw_bytes)
                 .or(query_proposal)
                 .or(query_proposal_result)
+                .or(verify_offline_proposal)
                 .or(query_protocol_parameters)
                 .or(utils)
         }
@@ -290,8 +322,10 @@ pub mod cmds {
         TxCustom(TxCustom),
         TxTransfer(TxTransfer),
         TxIbcTransfer(TxIbcTransfer),
+        AddToEthBridgePool(AddToEthBridgePool),
         QueryResult(QueryResult),
         TxUpdateVp(TxUpdateVp),
+        TxUpdateSigners(TxUpdateSigners),
         TxInitAccount(TxInitAccount),
         TxInitValidator(TxInitValidator),
         TxInitProposal(TxInitProposal),
@@ -299,9 +333,14 @@ pub mod cmds {
         TxRevealPk(TxRevealPk),
         Bond(Bond),
         Unbond(Unbond),
+        Redelegate(Redelegate),
         Withdraw(Withdraw),
+        ClaimRewards(ClaimRewards),
+        TxChangeConsensusKey(TxChangeConsensusKey),
         QueryEpoch(QueryEpoch),
         QueryTransfers(QueryTransfers),
+        QueryBridgePool(QueryBridgePool),
+        QueryErc20(QueryErc20),
         QueryConversions(QueryConversions),
         QueryBlock(QueryBlock),
         QueryBalance(QueryBalance),
@@ -309,11 +348,13 @@ pub mod cmds {
         QueryBondedStake(QueryBondedStake),
         QueryCommissionRate(QueryCommissionRate),
         QuerySlashes(QuerySlashes),
+        QueryRewards(QueryRewards),
         QueryDelegations(QueryDelegations),
         QueryFindValidator(QueryFindValidator),
         QueryRawBytes(QueryRawBytes),
         QueryProposal(QueryProposal),
         QueryProposalResult(QueryProposalResult),
+        VerifyOfflineProposal(VerifyOfflineProposal),
         QueryProtocolParameters(QueryProtocolParameters),
     }
 
@@ -369,6 +410,7 @@ pub mod cmds {
         Find(KeyFind),
         List(KeyList),
         Export(Export),
+        Migrate(KeyMigrate),
     }
 
     impl SubCmd for WalletKey {
@@ -381,7 +423,13 @@ pub mod cmds {
                 let lookup = SubCmd::parse(matches).map(Self::Find);
                 let list = SubCmd::parse(matches).map(Self::List);
                 let export = SubCmd::parse(matches).map(Self::Export);
-                generate.or(restore).or(lookup).or(list).or(export)
+                let migrate = SubCmd::parse(matches).map(Self::Migrate);
+                generate
+                    .or(restore)
+                    .or(lookup)
+                    .or(list)
+                    .or(export)
+                    .or(migrate)
             })
         }
 
@@ -397,6 +445,7 @@ pub mod cmds {
                 .subcommand(KeyFind::def())
                 .subcommand(KeyList::def())
                 .subcommand(Export::def())
+                .subcommand(KeyMigrate::def())
         }
     }
 
@@ -506,6 +555,31 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct KeyMigrate(pub args::KeyMigrate);
+
+    impl SubCmd for KeyMigrate {
+        const CMD: &'static str = "migrate";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::KeyMigrate::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Migrates the keys of the wallet to the current \
+                     encryption format, re-encrypting the keys encrypted in \
+                     the legacy format with Argon2id derived keys, and \
+                     encrypting the keys stored unencrypted. The password of \
+                     every key is prompted for.",
+                )
+                .add_args::<args::KeyMigrate>()
+        }
+    }
+
     #[allow(clippy::large_enum_variant)]
     #[derive(Clone, Debug)]
     pub enum WalletMasp {
@@ -515,6 +589,8 @@ pub mod cmds {
         ListPayAddrs(MaspListPayAddrs),
         ListKeys(MaspListKeys),
         FindAddrKey(MaspFindAddrKey),
+        ExportViewingKey(MaspExportViewingKey),
+        ImportViewingKey(MaspImportViewingKey),
     }
 
     impl SubCmd for WalletMasp {
@@ -528,7 +604,18 @@ pub mod cmds {
                 let listpa = SubCmd::parse(matches).map(Self::ListPayAddrs);
                 let listsk = SubCmd::parse(matches).map(Self::ListKeys);
                 let findak = SubCmd::parse(matches).map(Self::FindAddrKey);
-                gensk.or(genpa).or(addak).or(listpa).or(listsk).or(findak)
+                let exportvk =
+                    SubCmd::parse(matches).map(Self::ExportViewingKey);
+                let importvk =
+                    SubCmd::parse(matches).map(Self::ImportViewingKey);
+                gensk
+                    .or(genpa)
+                    .or(addak)
+                    .or(listpa)
+                    .or(listsk)
+                    .or(findak)
+                    .or(exportvk)
+                    .or(importvk)
             })
         }
 
@@ -546,6 +633,53 @@ pub mod cmds {
                 .subcommand(MaspListPayAddrs::def())
                 .subcommand(MaspListKeys::def())
                 .subcommand(MaspFindAddrKey::def())
+                .subcommand(MaspExportViewingKey::def())
+                .subcommand(MaspImportViewingKey::def())
+        }
+    }
+
+    /// Export a viewing key, e.g. for an auditor
+    #[derive(Clone, Debug)]
+    pub struct MaspExportViewingKey(pub args::MaspViewingKeyExport);
+
+    impl SubCmd for MaspExportViewingKey {
+        const CMD: &'static str = "export-viewing-key";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::MaspViewingKeyExport::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Exports the viewing key of the given alias to a file, \
+                     which can be imported in another wallet to view the \
+                     shielded transfers of the key without being able to \
+                     spend from it.",
+                )
+                .add_args::<args::MaspViewingKeyExport>()
+        }
+    }
+
+    /// Import an exported viewing key
+    #[derive(Clone, Debug)]
+    pub struct MaspImportViewingKey(pub args::MaspViewingKeyImport);
+
+    impl SubCmd for MaspImportViewingKey {
+        const CMD: &'static str = "import-viewing-key";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::MaspViewingKeyImport::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Imports a viewing key from an exported file")
+                .add_args::<args::MaspViewingKeyImport>()
         }
     }
 
@@ -664,7 +798,8 @@ pub mod cmds {
         fn def() -> App {
             App::new(Self::CMD)
                 .about(
-                    "Generates a payment address from the given spending key",
+                    "Generates payment addresses with distinct diversifiers \
+                     from the given viewing key",
                 )
                 .add_args::<args::MaspPayAddrGen<args::CliTypes>>()
         }
@@ -1063,6 +1198,35 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct VerifyOfflineProposal(
+        pub args::VerifyOfflineProposal<args::CliTypes>,
+    );
+
+    impl SubCmd for VerifyOfflineProposal {
+        const CMD: &'static str = "verify-offline-proposal";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                VerifyOfflineProposal(args::VerifyOfflineProposal::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Verify the signatures of an offline proposal and of the \
+                     offline votes on it.",
+                )
+                .add_args::<args::VerifyOfflineProposal<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryProtocolParameters(
         pub args::QueryProtocolParameters<args::CliTypes>,
@@ -1146,6 +1310,29 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct AddToEthBridgePool(pub args::EthereumBridgePool<args::CliTypes>);
+
+    impl SubCmd for AddToEthBridgePool {
+        const CMD: &'static str = "add-erc20-transfer";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                AddToEthBridgePool(args::EthereumBridgePool::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transfer of wrapped ERC20 tokens to \
+                     Ethereum, which is pending in the bridge pool until it is \
+                     relayed.",
+                )
+                .add_args::<args::EthereumBridgePool<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxUpdateVp(pub args::TxUpdateVp<args::CliTypes>);
 
@@ -1168,6 +1355,28 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateSigners(pub args::TxUpdateSigners<args::CliTypes>);
+
+    impl SubCmd for TxUpdateSigners {
+        const CMD: &'static str = "update-signers";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxUpdateSigners(args::TxUpdateSigners::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to update the signers of a \
+                     multisig account.",
+                )
+                .add_args::<args::TxUpdateSigners<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxInitAccount(pub args::TxInitAccount<args::CliTypes>);
 
@@ -1250,6 +1459,27 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct Redelegate(pub args::Redelegate<args::CliTypes>);
+
+    impl SubCmd for Redelegate {
+        const CMD: &'static str = "redelegate";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Redelegate(args::Redelegate::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Redelegate bonded tokens from one validator to another.",
+                )
+                .add_args::<args::Redelegate<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct Withdraw(pub args::Withdraw<args::CliTypes>);
 
@@ -1269,6 +1499,49 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct ClaimRewards(pub args::ClaimRewards<args::CliTypes>);
+
+    impl SubCmd for ClaimRewards {
+        const CMD: &'static str = "claim-rewards";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| ClaimRewards(args::ClaimRewards::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Claim the PoS rewards of a bond.")
+                .add_args::<args::ClaimRewards<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxChangeConsensusKey(
+        pub args::TxChangeConsensusKey<args::CliTypes>,
+    );
+
+    impl SubCmd for TxChangeConsensusKey {
+        const CMD: &'static str = "change-consensus-key";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxChangeConsensusKey(args::TxChangeConsensusKey::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Rotate the consensus key of a validator, effective from \
+                     the pipeline epoch.",
+                )
+                .add_args::<args::TxChangeConsensusKey<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryEpoch(pub args::Query<args::CliTypes>);
 
@@ -1402,6 +1675,47 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QueryBridgePool(pub args::QueryBridgePool<args::CliTypes>);
+
+    impl SubCmd for QueryBridgePool {
+        const CMD: &'static str = "query-bridge-pool";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryBridgePool(args::QueryBridgePool::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query the transfers to Ethereum pending in the pool.")
+                .add_args::<args::QueryBridgePool<args::CliTypes>>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryErc20(pub args::QueryErc20<args::CliTypes>);
+
+    impl SubCmd for QueryErc20 {
+        const CMD: &'static str = "query-erc20";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryErc20(args::QueryErc20::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the balance of the token wrapping an ERC20 \
+                     transferred from Ethereum.",
+                )
+                .add_args::<args::QueryErc20<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryCommissionRate(
         pub args::QueryCommissionRate<args::CliTypes>,
@@ -1445,6 +1759,28 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QueryRewards(pub args::QueryRewards<args::CliTypes>);
+
+    impl SubCmd for QueryRewards {
+        const CMD: &'static str = "rewards";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryRewards(args::QueryRewards::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query the PoS rewards of a bond that can be claimed.")
+                .add_args::<args::QueryRewards<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryDelegations(pub args::QueryDelegations<args::CliTypes>);
 
@@ -1767,6 +2103,7 @@ pub mod args {
     pub use namada::ledger::args::*;
     use namada::types::address::Address;
     use namada::types::chain::{ChainId, ChainIdPrefix};
+    use namada::types::ethereum_events::EthAddress;
     use namada::types::key::*;
     use namada::types::masp::MaspValue;
     use namada::types::storage::{self, BlockHeight, Epoch};
@@ -1787,16 +2124,25 @@ pub mod args {
     pub const TX_VOTE_PROPOSAL: &str = "tx_vote_proposal.wasm";
     pub const TX_REVEAL_PK: &str = "tx_reveal_pk.wasm";
     pub const TX_UPDATE_VP_WASM: &str = "tx_update_vp.wasm";
+    pub const TX_UPDATE_SIGNERS_WASM: &str = "tx_update_signers.wasm";
     pub const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
     pub const TX_IBC_WASM: &str = "tx_ibc.wasm";
     pub const VP_USER_WASM: &str = "vp_user.wasm";
+    pub const VP_MULTISIG_WASM: &str = "vp_multisig.wasm";
     pub const TX_BOND_WASM: &str = "tx_bond.wasm";
     pub const TX_UNBOND_WASM: &str = "tx_unbond.wasm";
+    pub const TX_REDELEGATE_WASM: &str = "tx_redelegate.wasm";
     pub const TX_WITHDRAW_WASM: &str = "tx_withdraw.wasm";
+    pub const TX_CLAIM_REWARDS_WASM: &str = "tx_claim_rewards.wasm";
     pub const TX_CHANGE_COMMISSION_WASM: &str =
         "tx_change_validator_commission.wasm";
+    pub const TX_CHANGE_CONSENSUS_KEY_WASM: &str =
+        "tx_change_consensus_key.wasm";
     pub const TX_UNJAIL_VALIDATOR_WASM: &str = "tx_unjail_validator.wasm";
+    pub const TX_BRIDGE_POOL_WASM: &str = "tx_bridge_pool.wasm";
 
+    pub const ACK_FEE: ArgDefault<token::Amount> =
+        arg_default("ack-fee", DefaultFn(|| token::Amount::from(0)));
     pub const ADDRESS: Arg<WalletAddress> = arg("address");
     pub const ALIAS_OPT: ArgOpt<String> = ALIAS.opt();
     pub const ALIAS: Arg<String> = arg("alias");
@@ -1814,6 +2160,10 @@ pub mod args {
     );
     pub const BLOCK_HEIGHT: Arg<BlockHeight> = arg("block-height");
     // pub const BLOCK_HEIGHT_OPT: ArgOpt<BlockHeight> = arg_opt("height");
+    pub const BRIDGE_GAS_AMOUNT: ArgDefault<token::Amount> =
+        arg_default("bridge-gas-amount", DefaultFn(|| token::Amount::from(0)));
+    pub const BRIDGE_GAS_PAYER: ArgOpt<WalletAddress> =
+        arg_opt("bridge-gas-payer");
     pub const BROADCAST_ONLY: ArgFlag = flag("broadcast-only");
     pub const CHAIN_ID: Arg<ChainId> = arg("chain-id");
     pub const CHAIN_ID_OPT: ArgOpt<ChainId> = CHAIN_ID.opt();
@@ -1822,24 +2172,39 @@ pub mod args {
     pub const CODE_PATH: Arg<PathBuf> = arg("code-path");
     pub const CODE_PATH_OPT: ArgOpt<PathBuf> = CODE_PATH.opt();
     pub const COMMISSION_RATE: Arg<Decimal> = arg("commission-rate");
+    pub const COSIGNERS: ArgMulti<WalletPublicKey> = arg_multi("cosigners");
     pub const CONSENSUS_TIMEOUT_COMMIT: ArgDefault<Timeout> = arg_default(
         "consensus-timeout-commit",
         DefaultFn(|| Timeout::from_str("1s").unwrap()),
     );
     pub const DATA_PATH_OPT: ArgOpt<PathBuf> = arg_opt("data-path");
     pub const DATA_PATH: Arg<PathBuf> = arg("data-path");
+    pub const DESTINATION_VALIDATOR: Arg<WalletAddress> =
+        arg("destination-validator");
     pub const DECRYPT: ArgFlag = flag("decrypt");
     pub const DONT_ARCHIVE: ArgFlag = flag("dont-archive");
     pub const DRY_RUN_TX: ArgFlag = flag("dry-run");
     pub const DUMP_TX: ArgFlag = flag("dump-tx");
+    pub const EIP191: ArgFlag = flag("eip191");
     pub const EPOCH: ArgOpt<Epoch> = arg_opt("epoch");
+    pub const ERC20: Arg<EthAddress> = arg("erc20");
+    pub const ETH_ADDRESS: Arg<EthAddress> = arg("ethereum-address");
     pub const EXPIRATION_OPT: ArgOpt<DateTimeUtc> = arg_opt("expiration");
+    pub const EXPIRATION_HEIGHT_OPT: ArgOpt<BlockHeight> =
+        arg_opt("expiration-height");
+    pub const FEE_PAYER_OPT: ArgOpt<WalletAddress> = arg_opt("fee-payer");
+    pub const FEE_SOURCE: ArgDefault<FeeSource> =
+        arg_default("fee-source", DefaultFn(|| FeeSource::Auto));
+    pub const FEE_SPONSOR_OPT: ArgOpt<WalletKeypair> = arg_opt("fee-sponsor");
     pub const FORCE: ArgFlag = flag("force");
     pub const DONT_PREFETCH_WASM: ArgFlag = flag("dont-prefetch-wasm");
-    pub const GAS_AMOUNT: ArgDefault<token::Amount> =
-        arg_default("gas-amount", DefaultFn(|| token::Amount::from(0)));
+    pub const GAS_AMOUNT: ArgDefault<token::DenominatedAmount> = arg_default(
+        "gas-amount",
+        DefaultFn(|| token::DenominatedAmount::native(token::Amount::from(0))),
+    );
     pub const GAS_LIMIT: ArgDefault<token::Amount> =
-        arg_default("gas-limit", DefaultFn(|| token::Amount::from(0)));
+        arg_default("gas-limit", DefaultFn(|| token::Amount::whole(1_000)));
+    pub const GAS_PRICE_OPT: ArgOpt<token::Amount> = arg_opt("gas-price");
     pub const GAS_TOKEN: ArgDefaultFromCtx<WalletAddress> =
         arg_default_from_ctx("gas-token", DefaultFn(|| "NAM".parse().unwrap()));
     pub const GENESIS_PATH: Arg<PathBuf> = arg("genesis-path");
@@ -1849,6 +2214,8 @@ pub mod args {
     pub const HD_WALLET_DERIVATION_PATH: Arg<String> = arg("hd-path");
     pub const HD_WALLET_DERIVATION_PATH_OPT: ArgOpt<String> =
         HD_WALLET_DERIVATION_PATH.opt();
+    pub const HD_WALLET_KEY_COUNT: ArgDefault<u32> =
+        arg_default("count", DefaultFn(|| 1));
     pub const HISTORIC: ArgFlag = flag("historic");
     pub const LEDGER_ADDRESS_ABOUT: &str =
         "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
@@ -1864,13 +2231,17 @@ pub mod args {
     pub const MASP_VALUE: Arg<MaspValue> = arg("value");
     pub const MAX_COMMISSION_RATE_CHANGE: Arg<Decimal> =
         arg("max-commission-rate-change");
+    pub const MEMO_OPT: ArgOpt<String> = arg_opt("memo");
     pub const MODE: ArgOpt<String> = arg_opt("mode");
     pub const NET_ADDRESS: Arg<SocketAddr> = arg("net-address");
     pub const NAMADA_START_TIME: ArgOpt<DateTimeUtc> = arg_opt("time");
     pub const NO_CONVERSIONS: ArgFlag = flag("no-conversions");
+    pub const NONCE_OPT: ArgOpt<u64> = arg_opt("nonce");
     pub const OUT_FILE_PATH_OPT: ArgOpt<PathBuf> = arg_opt("out-file-path");
     pub const OWNER: Arg<WalletAddress> = arg("owner");
     pub const OWNER_OPT: ArgOpt<WalletAddress> = OWNER.opt();
+    pub const PAYMENT_ADDRESS_COUNT: ArgDefault<u32> =
+        arg_default("count", DefaultFn(|| 1));
     pub const PIN: ArgFlag = flag("pin");
     pub const PORT_ID: ArgDefault<PortId> = arg_default(
         "port-id",
@@ -1880,6 +2251,7 @@ pub mod args {
     pub const PROTOCOL_KEY: ArgOpt<WalletPublicKey> = arg_opt("protocol-key");
     pub const PRE_GENESIS_PATH: ArgOpt<PathBuf> = arg_opt("pre-genesis-path");
     pub const PUBLIC_KEY: Arg<WalletPublicKey> = arg("public-key");
+    pub const PUBLIC_KEYS: ArgMulti<WalletPublicKey> = arg_multi("public-keys");
     pub const PROPOSAL_ID: Arg<u64> = arg("proposal-id");
     pub const PROPOSAL_ID_OPT: ArgOpt<u64> = arg_opt("proposal-id");
     pub const PROPOSAL_VOTE_PGF_OPT: ArgOpt<String> = arg_opt("pgf");
@@ -1891,6 +2263,10 @@ pub mod args {
     pub const RAW_PUBLIC_KEY_OPT: ArgOpt<common::PublicKey> =
         arg_opt("public-key");
     pub const RECEIVER: Arg<String> = arg("receiver");
+    pub const RECV_FEE: ArgDefault<token::Amount> =
+        arg_default("recv-fee", DefaultFn(|| token::Amount::from(0)));
+    pub const RELAYER_FEE_TOKEN: ArgOpt<WalletAddress> =
+        arg_opt("relayer-fee-token");
     pub const SCHEME: ArgDefault<SchemeType> =
         arg_default("scheme", DefaultFn(|| SchemeType::Ed25519));
     pub const SIGNER: ArgOpt<WalletAddress> = arg_opt("signer");
@@ -1898,15 +2274,21 @@ pub mod args {
     pub const SIGNING_KEY: Arg<WalletKeypair> = arg("signing-key");
     pub const SOURCE: Arg<WalletAddress> = arg("source");
     pub const SOURCE_OPT: ArgOpt<WalletAddress> = SOURCE.opt();
+    pub const SOURCE_VALIDATOR: Arg<WalletAddress> = arg("source-validator");
     pub const STORAGE_KEY: Arg<storage::Key> = arg("storage-key");
     pub const SUB_PREFIX: ArgOpt<String> = arg_opt("sub-prefix");
     pub const SUSPEND_ACTION: ArgFlag = flag("suspend");
     pub const TENDERMINT_TX_INDEX: ArgFlag = flag("tx-index");
+    pub const THRESHOLD: Arg<u8> = arg("threshold");
+    pub const THRESHOLD_OPT: ArgOpt<u8> = THRESHOLD.opt();
+    pub const TIMEOUT_FEE: ArgDefault<token::Amount> =
+        arg_default("timeout-fee", DefaultFn(|| token::Amount::from(0)));
     pub const TIMEOUT_HEIGHT: ArgOpt<u64> = arg_opt("timeout-height");
     pub const TIMEOUT_SEC_OFFSET: ArgOpt<u64> = arg_opt("timeout-sec-offset");
     pub const TM_ADDRESS: Arg<String> = arg("tm-address");
     pub const TOKEN_OPT: ArgOpt<WalletAddress> = TOKEN.opt();
     pub const TOKEN: Arg<WalletAddress> = arg("token");
+    pub const TRANSFER_AMOUNT: Arg<token::DenominatedAmount> = arg("amount");
     pub const TRANSFER_SOURCE: Arg<WalletTransferSource> = arg("source");
     pub const TRANSFER_TARGET: Arg<WalletTransferTarget> = arg("target");
     pub const TX_HASH: Arg<String> = arg("tx-hash");
@@ -1918,10 +2300,14 @@ pub mod args {
         arg_opt("account-key");
     pub const VALIDATOR_CONSENSUS_KEY: ArgOpt<WalletKeypair> =
         arg_opt("consensus-key");
+    pub const VALIDATOR_NEW_CONSENSUS_KEY: Arg<WalletPublicKey> =
+        arg("consensus-key");
     pub const VALIDATOR_CODE_PATH: ArgOpt<PathBuf> =
         arg_opt("validator-code-path");
     pub const VALUE: ArgOpt<String> = arg_opt("value");
+    pub const VANITY_PREFIX: ArgOpt<String> = arg_opt("vanity-prefix");
     pub const VIEWING_KEY: Arg<WalletViewingKey> = arg("key");
+    pub const VIEWING_KEY_FILE: Arg<PathBuf> = arg("file");
     pub const WALLET_ALIAS_FORCE: ArgFlag = flag("wallet-alias-force");
     pub const WASM_CHECKSUMS_PATH: Arg<PathBuf> = arg("wasm-checksums-path");
     pub const WASM_DIR: ArgOpt<PathBuf> = arg_opt("wasm-dir");
@@ -2171,6 +2557,7 @@ pub mod args {
                 amount: self.amount,
                 native_token: ctx.native_token.clone(),
                 tx_code_path: self.tx_code_path.to_path_buf(),
+                fee_source: self.fee_source,
             }
         }
     }
@@ -2182,8 +2569,9 @@ pub mod args {
             let target = TRANSFER_TARGET.parse(matches);
             let token = TOKEN.parse(matches);
             let sub_prefix = SUB_PREFIX.parse(matches);
-            let amount = AMOUNT.parse(matches);
+            let amount = TRANSFER_AMOUNT.parse(matches);
             let tx_code_path = PathBuf::from(TX_TRANSFER_WASM);
+            let fee_source = FEE_SOURCE.parse(matches);
             Self {
                 tx,
                 source,
@@ -2193,6 +2581,7 @@ pub mod args {
                 amount,
                 native_token: (),
                 tx_code_path,
+                fee_source,
             }
         }
 
@@ -2208,7 +2597,18 @@ pub mod args {
                 ))
                 .arg(TOKEN.def().about("The transfer token."))
                 .arg(SUB_PREFIX.def().about("The token's sub prefix."))
-                .arg(AMOUNT.def().about("The amount to transfer in decimal."))
+                .arg(TRANSFER_AMOUNT.def().about(
+                    "The amount to transfer in decimal, optionally followed \
+                     by the symbol of the token, e.g. \"1.5 NAM\".",
+                ))
+                .arg(FEE_SOURCE.def().about(
+                    "The funds paying the fee of a transfer from a spending \
+                     key with a transparent fee payer: \"transparent\" for \
+                     the balance of the fee payer, \"shielded\" for funds \
+                     unshielded from the spending key, or \"auto\" for the \
+                     balance of the fee payer if it covers the fee and \
+                     unshielded funds otherwise.",
+                ))
         }
     }
 
@@ -2225,6 +2625,13 @@ pub mod args {
                 channel_id: self.channel_id,
                 timeout_height: self.timeout_height,
                 timeout_sec_offset: self.timeout_sec_offset,
+                recv_fee: self.recv_fee,
+                ack_fee: self.ack_fee,
+                timeout_fee: self.timeout_fee,
+                relayer_fee_token: self
+                    .relayer_fee_token
+                    .map(|token| ctx.get(&token)),
+                native_token: ctx.native_token.clone(),
                 tx_code_path: self.tx_code_path.to_path_buf(),
             }
         }
@@ -2242,6 +2649,10 @@ pub mod args {
             let channel_id = CHANNEL_ID.parse(matches);
             let timeout_height = TIMEOUT_HEIGHT.parse(matches);
             let timeout_sec_offset = TIMEOUT_SEC_OFFSET.parse(matches);
+            let recv_fee = RECV_FEE.parse(matches);
+            let ack_fee = ACK_FEE.parse(matches);
+            let timeout_fee = TIMEOUT_FEE.parse(matches);
+            let relayer_fee_token = RELAYER_FEE_TOKEN.parse(matches);
             let tx_code_path = PathBuf::from(TX_IBC_WASM);
             Self {
                 tx,
@@ -2254,6 +2665,11 @@ pub mod args {
                 channel_id,
                 timeout_height,
                 timeout_sec_offset,
+                recv_fee,
+                ack_fee,
+                timeout_fee,
+                relayer_fee_token,
+                native_token: (),
                 tx_code_path,
             }
         }
@@ -2267,7 +2683,10 @@ pub mod args {
                 .arg(RECEIVER.def().about(
                     "The receiver address on the destination chain as string.",
                 ))
-                .arg(TOKEN.def().about("The transfer token."))
+                .arg(TOKEN.def().about(
+                    "The transfer token, or the IBC token address of a token \
+                     received over IBC.",
+                ))
                 .arg(SUB_PREFIX.def().about("The token's sub prefix."))
                 .arg(AMOUNT.def().about("The amount to transfer in decimal."))
                 .arg(PORT_ID.def().about("The port ID."))
@@ -2278,6 +2697,90 @@ pub mod args {
                         .about("The timeout height of the destination chain."),
                 )
                 .arg(TIMEOUT_SEC_OFFSET.def().about("The timeout as seconds."))
+                .arg(RECV_FEE.def().about(
+                    "The fee paid to the relayer of the packet to the \
+                     destination chain.",
+                ))
+                .arg(ACK_FEE.def().about(
+                    "The fee paid to the relayer of the acknowledgement of \
+                     the packet.",
+                ))
+                .arg(TIMEOUT_FEE.def().about(
+                    "The fee paid to the relayer of the timeout of the packet.",
+                ))
+                .arg(RELAYER_FEE_TOKEN.def().about(
+                    "The token of the relayer fees. Defaults to the native \
+                     token.",
+                ))
+        }
+    }
+
+    impl CliToSdk<EthereumBridgePool<SdkTypes>> for EthereumBridgePool<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> EthereumBridgePool<SdkTypes> {
+            EthereumBridgePool::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                asset: self.asset,
+                recipient: self.recipient,
+                sender: ctx.get(&self.sender),
+                amount: self.amount,
+                gas_amount: self.gas_amount,
+                gas_payer: ctx.get(&self.gas_payer),
+                native_token: ctx.native_token.clone(),
+                tx_code_path: self.tx_code_path,
+            }
+        }
+    }
+
+    impl Args for EthereumBridgePool<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let asset = ERC20.parse(matches);
+            let recipient = ETH_ADDRESS.parse(matches);
+            let sender = SOURCE.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let gas_amount = BRIDGE_GAS_AMOUNT.parse(matches);
+            let gas_payer = BRIDGE_GAS_PAYER
+                .parse(matches)
+                .unwrap_or_else(|| sender.clone());
+            let tx_code_path = PathBuf::from(TX_BRIDGE_POOL_WASM);
+            Self {
+                tx,
+                asset,
+                recipient,
+                sender,
+                amount,
+                gas_amount,
+                gas_payer,
+                native_token: (),
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(
+                    ERC20
+                        .def()
+                        .about("The Ethereum address of the ERC20 token."),
+                )
+                .arg(
+                    ETH_ADDRESS
+                        .def()
+                        .about("The Ethereum address receiving the tokens."),
+                )
+                .arg(SOURCE.def().about(
+                    "The account address sending the wrapped tokens. The \
+                     source's key is used to produce the signature.",
+                ))
+                .arg(AMOUNT.def().about("The amount to transfer in decimal."))
+                .arg(BRIDGE_GAS_AMOUNT.def().about(
+                    "The gas fee paid in the native token to the relayer of \
+                     the transfer. Defaults to 0.",
+                ))
+                .arg(BRIDGE_GAS_PAYER.def().about(
+                    "The account address paying the gas fee of the relayer. \
+                     Defaults to the source.",
+                ))
         }
     }
 
@@ -2289,6 +2792,13 @@ pub mod args {
                 vp_code_path: self.vp_code_path.to_path_buf(),
                 tx_code_path: self.tx_code_path.to_path_buf(),
                 public_key: ctx.get_cached(&self.public_key),
+                cosigners: self
+                    .cosigners
+                    .iter()
+                    .map(|pk| ctx.get_cached(pk))
+                    .collect(),
+                threshold: self.threshold,
+                vanity_prefix: self.vanity_prefix,
             }
         }
     }
@@ -2297,16 +2807,27 @@ pub mod args {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
             let source = SOURCE.parse(matches);
+            let threshold = THRESHOLD_OPT.parse(matches);
+            let default_vp = if threshold.is_some() {
+                VP_MULTISIG_WASM
+            } else {
+                VP_USER_WASM
+            };
             let vp_code_path = CODE_PATH_OPT
                 .parse(matches)
-                .unwrap_or_else(|| PathBuf::from(VP_USER_WASM));
+                .unwrap_or_else(|| PathBuf::from(default_vp));
             let tx_code_path = PathBuf::from(TX_INIT_ACCOUNT_WASM);
             let public_key = PUBLIC_KEY.parse(matches);
+            let cosigners = COSIGNERS.parse(matches);
+            let vanity_prefix = VANITY_PREFIX.parse(matches);
             Self {
                 tx,
                 source,
                 vp_code_path,
                 public_key,
+                cosigners,
+                threshold,
+                vanity_prefix,
                 tx_code_path,
             }
         }
@@ -2319,12 +2840,28 @@ pub mod args {
                 .arg(CODE_PATH_OPT.def().about(
                     "The path to the validity predicate WASM code to be used \
                      for the new account. Uses the default user VP if none \
-                     specified.",
+                     specified, or the multisig VP if a threshold is given.",
                 ))
                 .arg(PUBLIC_KEY.def().about(
                     "A public key to be used for the new account in \
                      hexadecimal encoding.",
                 ))
+                .arg(COSIGNERS.def().about(
+                    "The public keys of the other signers of a multisig \
+                     account. Requires a threshold.",
+                ))
+                .arg(THRESHOLD_OPT.def().about(
+                    "The number of signatures required to authorize the \
+                     transactions of the new account, which is then a \
+                     multisig account.",
+                ))
+                .arg(VANITY_PREFIX.def().about(
+                    "A prefix that the address of the new account must start \
+                     with, e.g. \"atest1v4ehgw36xy\". Every address of an \
+                     established account starts with \"atest1v4ehgw36\". \
+                     Each additional character makes finding the address \
+                     about 32 times slower.",
+                ))
         }
     }
 
@@ -2462,6 +2999,54 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<TxUpdateSigners<SdkTypes>> for TxUpdateSigners<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> TxUpdateSigners<SdkTypes> {
+            TxUpdateSigners::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                tx_code_path: self.tx_code_path,
+                addr: ctx.get(&self.addr),
+                public_keys: self
+                    .public_keys
+                    .iter()
+                    .map(|pk| ctx.get_cached(pk))
+                    .collect(),
+                threshold: self.threshold,
+            }
+        }
+    }
+
+    impl Args for TxUpdateSigners<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let addr = ADDRESS.parse(matches);
+            let public_keys = PUBLIC_KEYS.parse(matches);
+            let threshold = THRESHOLD.parse(matches);
+            let tx_code_path = PathBuf::from(TX_UPDATE_SIGNERS_WASM);
+            Self {
+                tx,
+                tx_code_path,
+                addr,
+                public_keys,
+                threshold,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(ADDRESS.def().about("The multisig account's address."))
+                .arg(
+                    PUBLIC_KEYS
+                        .def()
+                        .about("The public keys of the new signers.")
+                        .required(true),
+                )
+                .arg(THRESHOLD.def().about(
+                    "The new number of signatures required to authorize the \
+                     transactions of the account.",
+                ))
+        }
+    }
+
     impl CliToSdk<Bond<SdkTypes>> for Bond<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> Bond<SdkTypes> {
             Bond::<SdkTypes> {
@@ -2546,6 +3131,58 @@ pub mod args {
                 ))
         }
     }
+
+    impl CliToSdk<Redelegate<SdkTypes>> for Redelegate<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> Redelegate<SdkTypes> {
+            Redelegate::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                src_validator: ctx.get(&self.src_validator),
+                dest_validator: ctx.get(&self.dest_validator),
+                owner: ctx.get(&self.owner),
+                amount: self.amount,
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for Redelegate<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let src_validator = SOURCE_VALIDATOR.parse(matches);
+            let dest_validator = DESTINATION_VALIDATOR.parse(matches);
+            let owner = OWNER.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let tx_code_path = PathBuf::from(TX_REDELEGATE_WASM);
+            Self {
+                tx,
+                src_validator,
+                dest_validator,
+                owner,
+                amount,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(
+                    SOURCE_VALIDATOR
+                        .def()
+                        .about("Source validator address of the bond."),
+                )
+                .arg(
+                    DESTINATION_VALIDATOR
+                        .def()
+                        .about("Destination validator address."),
+                )
+                .arg(OWNER.def().about("Delegator address of the bond."))
+                .arg(
+                    AMOUNT
+                        .def()
+                        .about("Amount of tokens to redelegate from the bond."),
+                )
+        }
+    }
     #[derive(Clone, Debug)]
     pub struct InitProposal<C: NamadaTypes = SdkTypes> {
         /// Common tx arguments
@@ -2823,6 +3460,44 @@ pub mod args {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct VerifyOfflineProposal<C: NamadaTypes = SdkTypes> {
+        /// Common query args
+        pub query: Query<C>,
+        /// The folder containing the proposal and votes
+        pub proposal_folder: PathBuf,
+    }
+
+    impl CliToSdk<VerifyOfflineProposal<SdkTypes>>
+        for VerifyOfflineProposal<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> VerifyOfflineProposal<SdkTypes> {
+            VerifyOfflineProposal::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+                proposal_folder: self.proposal_folder,
+            }
+        }
+    }
+
+    impl Args for VerifyOfflineProposal<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let proposal_folder = DATA_PATH.parse(matches);
+
+            Self {
+                query,
+                proposal_folder,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>().arg(DATA_PATH.def().about(
+                "The path to the folder containing the proposal json and \
+                 votes",
+            ))
+        }
+    }
+
     impl CliToSdk<QueryProtocolParameters<SdkTypes>>
         for QueryProtocolParameters<CliTypes>
     {
@@ -2884,6 +3559,42 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<ClaimRewards<SdkTypes>> for ClaimRewards<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> ClaimRewards<SdkTypes> {
+            ClaimRewards::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                validator: ctx.get(&self.validator),
+                source: self.source.map(|x| ctx.get(&x)),
+                tx_code_path: self.tx_code_path.to_path_buf(),
+            }
+        }
+    }
+
+    impl Args for ClaimRewards<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let source = SOURCE_OPT.parse(matches);
+            let tx_code_path = PathBuf::from(TX_CLAIM_REWARDS_WASM);
+            Self {
+                tx,
+                validator,
+                source,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(SOURCE_OPT.def().about(
+                    "Source address for claiming the rewards of delegations. \
+                     For claiming the rewards of self-bonds, the validator is \
+                     also the source.",
+                ))
+        }
+    }
+
     impl CliToSdk<QueryConversions<SdkTypes>> for QueryConversions<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> QueryConversions<SdkTypes> {
             QueryConversions::<SdkTypes> {
@@ -3007,6 +3718,62 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<QueryBridgePool<SdkTypes>> for QueryBridgePool<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryBridgePool<SdkTypes> {
+            QueryBridgePool::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+            }
+        }
+    }
+
+    impl Args for QueryBridgePool<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            Self { query }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>()
+        }
+    }
+
+    impl CliToSdk<QueryErc20<SdkTypes>> for QueryErc20<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryErc20<SdkTypes> {
+            QueryErc20::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+                asset: self.asset,
+                owner: ctx.get(&self.owner),
+            }
+        }
+    }
+
+    impl Args for QueryErc20<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let asset = ERC20.parse(matches);
+            let owner = OWNER.parse(matches);
+            Self {
+                query,
+                asset,
+                owner,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>()
+                .arg(
+                    ERC20
+                        .def()
+                        .about("The Ethereum address of the ERC20 token."),
+                )
+                .arg(
+                    OWNER
+                        .def()
+                        .about("The account address whose balance to query."),
+                )
+        }
+    }
+
     impl CliToSdk<QueryBonds<SdkTypes>> for QueryBonds<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> QueryBonds<SdkTypes> {
             QueryBonds::<SdkTypes> {
@@ -3118,6 +3885,45 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<TxChangeConsensusKey<SdkTypes>>
+        for TxChangeConsensusKey<CliTypes>
+    {
+        fn to_sdk(self, ctx: &mut Context) -> TxChangeConsensusKey<SdkTypes> {
+            TxChangeConsensusKey::<SdkTypes> {
+                tx: self.tx.to_sdk(ctx),
+                validator: ctx.get(&self.validator),
+                consensus_key: ctx.get_cached(&self.consensus_key),
+                tx_code_path: self.tx_code_path,
+            }
+        }
+    }
+
+    impl Args for TxChangeConsensusKey<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let consensus_key = VALIDATOR_NEW_CONSENSUS_KEY.parse(matches);
+            let tx_code_path = PathBuf::from(TX_CHANGE_CONSENSUS_KEY_WASM);
+            Self {
+                tx,
+                validator,
+                consensus_key,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(VALIDATOR.def().about(
+                    "The validator's address whose consensus key to change.",
+                ))
+                .arg(VALIDATOR_NEW_CONSENSUS_KEY.def().about(
+                    "The new consensus key, which must not have been used by \
+                     any validator before.",
+                ))
+        }
+    }
+
     impl CliToSdk<TxUnjailValidator<SdkTypes>> for TxUnjailValidator<CliTypes> {
         fn to_sdk(self, ctx: &mut Context) -> TxUnjailValidator<SdkTypes> {
             TxUnjailValidator {
@@ -3214,6 +4020,38 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<QueryRewards<SdkTypes>> for QueryRewards<CliTypes> {
+        fn to_sdk(self, ctx: &mut Context) -> QueryRewards<SdkTypes> {
+            QueryRewards::<SdkTypes> {
+                query: self.query.to_sdk(ctx),
+                validator: ctx.get(&self.validator),
+                source: self.source.map(|x| ctx.get(&x)),
+            }
+        }
+    }
+
+    impl Args for QueryRewards<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let source = SOURCE_OPT.parse(matches);
+            Self {
+                query,
+                validator,
+                source,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query<CliTypes>>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(SOURCE_OPT.def().about(
+                    "Source address of a delegation. For self-bonds, the \
+                     validator is also the source.",
+                ))
+        }
+    }
+
     impl Args for QueryDelegations<CliTypes> {
         fn parse(matches: &ArgMatches) -> Self {
             let query = Query::parse(matches);
@@ -3316,11 +4154,19 @@ pub mod args {
                 fee_amount: self.fee_amount,
                 fee_token: ctx.get(&self.fee_token),
                 gas_limit: self.gas_limit,
+                gas_price: self.gas_price,
                 signing_key: self.signing_key.map(|x| ctx.get_cached(&x)),
                 signer: self.signer.map(|x| ctx.get(&x)),
+                fee_sponsor: self.fee_sponsor.map(|x| ctx.get_cached(&x)),
+                fee_unshield: self.fee_unshield,
+                fee_payer: self.fee_payer.map(|x| ctx.get(&x)),
                 tx_reveal_code_path: self.tx_reveal_code_path,
                 password: self.password,
                 expiration: self.expiration,
+                expiration_height: self.expiration_height,
+                nonce: self.nonce,
+                memo: self.memo,
+                eip191: self.eip191,
                 chain_id: self.chain_id,
             }
         }
@@ -3358,7 +4204,9 @@ pub mod args {
                 "Override the alias without confirmation if it already exists.",
             ))
             .arg(GAS_AMOUNT.def().about(
-                "The amount being paid for the inclusion of this transaction",
+                "The amount being paid for the inclusion of this transaction, \
+                 in decimal, optionally followed by the symbol of the gas \
+                 token.",
             ))
             .arg(GAS_TOKEN.def().about("The token for paying the gas"))
             .arg(
@@ -3366,12 +4214,33 @@ pub mod args {
                     "The maximum amount of gas needed to run transaction",
                 ),
             )
+            .arg(GAS_PRICE_OPT.def().about(
+                "The optional tip offered per unit of gas on top of the fee, \
+                 to prioritize the transaction when the network is congested.",
+            ))
             .arg(EXPIRATION_OPT.def().about(
                 "The expiration datetime of the transaction, after which the \
                  tx won't be accepted anymore. All of these examples are \
                  equivalent:\n2012-12-12T12:12:12Z\n2012-12-12 \
                  12:12:12Z\n2012-  12-12T12:  12:12Z",
             ))
+            .arg(EXPIRATION_HEIGHT_OPT.def().about(
+                "The last block height at which the transaction may be \
+                 included in a block.",
+            ))
+            .arg(NONCE_OPT.def().about(
+                "The nonce of the transaction, which must be the next one of \
+                 the fee payer to get the transaction accepted.",
+            ))
+            .arg(MEMO_OPT.def().about(
+                "An optional memo attached to the transaction, such as the \
+                 tag required by an exchange to credit a deposit.",
+            ))
+            .arg(EIP191.def().about(
+                "Sign the transaction over the EIP-191 personal messages of \
+                 its hashes, as Ethereum wallets such as Metamask do. \
+                 Requires a secp256k1 signing key.",
+            ))
             .arg(
                 SIGNING_KEY_OPT
                     .def()
@@ -3391,6 +4260,16 @@ pub mod args {
                     )
                     .conflicts_with(SIGNING_KEY_OPT.name),
             )
+            .arg(FEE_SPONSOR_OPT.def().about(
+                "Have the fee of the transaction paid by the implicit account \
+                 of the key for the given public key, public key hash or \
+                 alias from your wallet, which also signs the wrapper.",
+            ))
+            .arg(FEE_PAYER_OPT.def().about(
+                "Have the fee of the transaction paid by the given \
+                 established account, e.g. a multisig. The key of the \
+                 account must be in your wallet to authorize it.",
+            ))
         }
 
         fn parse(matches: &ArgMatches) -> Self {
@@ -3403,10 +4282,20 @@ pub mod args {
             let wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
             let fee_amount = GAS_AMOUNT.parse(matches);
             let fee_token = GAS_TOKEN.parse(matches);
-            let gas_limit = GAS_LIMIT.parse(matches).into();
+            let gas_limit = GAS_LIMIT
+                .parse(matches)
+                .checked_to_u64()
+                .expect("The gas limit should fit in a u64");
+            let gas_price = GAS_PRICE_OPT.parse(matches);
             let expiration = EXPIRATION_OPT.parse(matches);
+            let expiration_height = EXPIRATION_HEIGHT_OPT.parse(matches);
+            let nonce = NONCE_OPT.parse(matches);
+            let memo = MEMO_OPT.parse(matches);
+            let eip191 = EIP191.parse(matches);
             let signing_key = SIGNING_KEY_OPT.parse(matches);
             let signer = SIGNER.parse(matches);
+            let fee_sponsor = FEE_SPONSOR_OPT.parse(matches);
+            let fee_payer = FEE_PAYER_OPT.parse(matches);
             let tx_reveal_code_path = PathBuf::from(TX_REVEAL_PK);
             let chain_id = CHAIN_ID_OPT.parse(matches);
             let password = None;
@@ -3421,9 +4310,17 @@ pub mod args {
                 fee_amount,
                 fee_token,
                 gas_limit,
+                gas_price,
                 expiration,
+                expiration_height,
+                nonce,
+                memo,
+                eip191,
                 signing_key,
                 signer,
+                fee_sponsor,
+                fee_unshield: None,
+                fee_payer,
                 tx_reveal_code_path,
                 password,
                 chain_id,
@@ -3521,6 +4418,7 @@ pub mod args {
                 alias_force: self.alias_force,
                 viewing_key: ctx.get_cached(&self.viewing_key),
                 pin: self.pin,
+                count: self.count,
             }
         }
     }
@@ -3531,11 +4429,13 @@ pub mod args {
             let alias_force = ALIAS_FORCE.parse(matches);
             let viewing_key = VIEWING_KEY.parse(matches);
             let pin = PIN.parse(matches);
+            let count = PAYMENT_ADDRESS_COUNT.parse(matches);
             Self {
                 alias,
                 alias_force,
                 viewing_key,
                 pin,
+                count,
             }
         }
 
@@ -3553,6 +4453,53 @@ pub mod args {
                 "Require that the single transaction to this address be \
                  pinned.",
             ))
+            .arg(PAYMENT_ADDRESS_COUNT.def().about(
+                "The number of payment addresses to generate, e.g. one for \
+                 every customer or invoice. When more than one, the index of \
+                 each address is appended to the alias.",
+            ))
+        }
+    }
+
+    impl Args for MaspViewingKeyExport {
+        fn parse(matches: &ArgMatches) -> Self {
+            let alias = ALIAS.parse(matches);
+            Self { alias }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(ALIAS.def().about(
+                "The alias of the viewing key or of the spending key whose \
+                 viewing key you wish to export.",
+            ))
+        }
+    }
+
+    impl Args for MaspViewingKeyImport {
+        fn parse(matches: &ArgMatches) -> Self {
+            let alias = ALIAS.parse(matches);
+            let alias_force = ALIAS_FORCE.parse(matches);
+            let file_path = VIEWING_KEY_FILE.parse(matches);
+            Self {
+                alias,
+                alias_force,
+                file_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                ALIAS
+                    .def()
+                    .about("An alias to be associated with the viewing key."),
+            )
+            .arg(ALIAS_FORCE.def().about(
+                "Override the alias without confirmation if it already exists.",
+            ))
+            .arg(VIEWING_KEY_FILE.def().about(
+                "The file of the viewing key, as exported by \
+                 `export-viewing-key`.",
+            ))
         }
     }
 
@@ -3563,12 +4510,14 @@ pub mod args {
             let alias_force = ALIAS_FORCE.parse(matches);
             let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
             let derivation_path = HD_WALLET_DERIVATION_PATH_OPT.parse(matches);
+            let count = HD_WALLET_KEY_COUNT.parse(matches);
             Self {
                 scheme,
                 alias,
                 alias_force,
                 unsafe_dont_encrypt,
                 derivation_path,
+                count,
             }
         }
 
@@ -3599,6 +4548,13 @@ pub mod args {
                  indexes. If none is specified, the scheme default path is \
                  used.",
             ))
+            .arg(HD_WALLET_KEY_COUNT.def().about(
+                "The number of keys to restore from the mnemonic code, at \
+                 consecutive address indexes starting from the derivation \
+                 path, to restore a whole wallet at once. The keys after the \
+                 first one have their alias suffixed by their index. \
+                 Defaults to 1.",
+            ))
         }
     }
 
@@ -3764,6 +4720,22 @@ pub mod args {
         }
     }
 
+    impl Args for KeyMigrate {
+        fn parse(matches: &ArgMatches) -> Self {
+            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
+            Self {
+                unsafe_dont_encrypt,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(UNSAFE_DONT_ENCRYPT.def().about(
+                "UNSAFE: Leave the keys that are stored unencrypted as they \
+                 are.",
+            ))
+        }
+    }
+
     impl Args for AddressOrAliasFind {
         fn parse(matches: &ArgMatches) -> Self {
             let alias = ALIAS_OPT.parse(matches);
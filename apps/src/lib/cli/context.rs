@@ -11,6 +11,7 @@ use namada::ledger::masp::ShieldedContext;
 use namada::ledger::wallet::store::AddressVpType;
 use namada::ledger::wallet::Wallet;
 use namada::types::address::Address;
+use namada::types::address_book::{AddressBook, AddressResolver, EnvAliases};
 use namada::types::chain::ChainId;
 use namada::types::key::*;
 use namada::types::masp::*;
@@ -27,9 +28,11 @@ use crate::wasm_loader;
 const ENV_VAR_CHAIN_ID: &str = "NAMADA_CHAIN_ID";
 /// Env. var to set wasm directory
 pub const ENV_VAR_WASM_DIR: &str = "NAMADA_WASM_DIR";
+/// The file name of the address book in the base directory
+pub const ADDRESS_BOOK_FILE_NAME: &str = "address_book.toml";
 
 /// A raw address (bech32m encoding) or an alias of an address that may be found
-/// in the wallet
+/// in the environment, the wallet or the address book
 pub type WalletAddress = FromContext<Address>;
 
 /// A raw extended spending key (bech32m encoding) or an alias of an extended
@@ -79,6 +82,9 @@ pub struct Context {
     pub shielded: ShieldedContext<CLIShieldedUtils>,
     /// Native token's address
     pub native_token: Address,
+    /// The aliases of addresses shared by all the chains in the base
+    /// directory, in addition to the aliases of the wallet
+    pub address_book: AddressBook,
 }
 
 impl Context {
@@ -106,6 +112,9 @@ impl Context {
             &chain_dir,
             default_genesis,
         );
+        let address_book = AddressBook::load_or_default(
+            global_args.base_dir.join(ADDRESS_BOOK_FILE_NAME),
+        )?;
 
         // If the WASM dir specified, put it in the config
         match global_args.wasm_dir.as_ref() {
@@ -126,9 +135,24 @@ impl Context {
             config,
             shielded: CLIShieldedUtils::new(chain_dir),
             native_token,
+            address_book,
         })
     }
 
+    /// Get the resolver of the aliases of addresses, which are overridden by
+    /// the environment first, then looked-up in the wallet and finally in the
+    /// address book of the current chain.
+    pub fn address_resolver(&self) -> impl AddressResolver + '_ {
+        (
+            EnvAliases,
+            (
+                &self.wallet,
+                self.address_book
+                    .on_chain(&self.global_config.default_chain_id),
+            ),
+        )
+    }
+
     /// Parse and/or look-up the value from the context.
     pub fn get<T>(&self, from_context: &FromContext<T>) -> T
     where
@@ -352,16 +376,11 @@ impl ArgFromContext for Address {
         ctx: &Context,
         raw: impl AsRef<str>,
     ) -> Result<Self, String> {
-        let raw = raw.as_ref();
-        // An address can be either raw (bech32m encoding)
-        FromStr::from_str(raw)
-            // Or it can be an alias that may be found in the wallet
-            .or_else(|_| {
-                ctx.wallet
-                    .find_address(raw)
-                    .cloned()
-                    .ok_or_else(|| format!("Unknown address {}", raw))
-            })
+        // An address can be either raw (bech32m encoding) or an alias that
+        // may be found in the environment, the wallet or the address book
+        ctx.address_resolver()
+            .resolve(raw.as_ref())
+            .map_err(|err| err.to_string())
     }
 }
 
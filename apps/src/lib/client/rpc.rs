@@ -33,12 +33,14 @@ use namada::ledger::pos::{
 };
 use namada::ledger::queries::RPC;
 use namada::ledger::rpc::{
-    enriched_bonds_and_unbonds, query_epoch, TxResponse,
+    enriched_bonds_and_unbonds, query_epoch, query_epoch_clock,
+    query_token_metadata, TxResponse,
 };
 use namada::ledger::storage::ConversionState;
 use namada::ledger::wallet::{AddressVpType, Wallet};
 use namada::proof_of_stake::types::WeightedValidator;
 use namada::types::address::{masp, Address};
+use namada::types::eth_bridge_pool;
 use namada::types::governance::{
     OfflineProposal, OfflineVote, ProposalVote, VotePower, VoteType,
 };
@@ -46,6 +48,7 @@ use namada::types::hash::Hash;
 use namada::types::key::*;
 use namada::types::masp::{BalanceOwner, ExtendedViewingKey, PaymentAddress};
 use namada::types::storage::{BlockHeight, BlockResults, Epoch, Key, KeySeg};
+use namada::types::time::DateTimeUtc;
 use namada::types::{storage, token};
 
 use crate::cli::{self, args};
@@ -119,7 +122,7 @@ pub async fn query_transfers<
         || Either::Right(wallet.get_addresses().into_values().collect()),
         Either::Left,
     );
-    let _ = shielded.load();
+    let _ = shielded.load().await;
     // Obtain the effects of all shielded and transparent transactions
     let transfers = shielded
         .query_tx_deltas(
@@ -224,6 +227,59 @@ pub async fn query_transfers<
     }
 }
 
+/// Query the transfers to Ethereum pending in the bridge pool
+pub async fn query_bridge_pool<C: namada::ledger::queries::Client + Sync>(
+    client: &C,
+    wallet: &mut Wallet<CliWalletUtils>,
+    _args: args::QueryBridgePool,
+) {
+    let prefix = eth_bridge_pool::pending_transfers_prefix();
+    let pending = query_storage_prefix::<C, eth_bridge_pool::PendingTransfer>(
+        client, &prefix,
+    )
+    .await;
+    let mut found_any = false;
+    for (_key, pending) in pending.into_iter().flatten() {
+        found_any = true;
+        let eth_bridge_pool::PendingTransfer { transfer, gas_fee } = &pending;
+        println!("Pending transfer {}:", pending.hash());
+        println!(
+            "  {} of ERC20 {} from {} to {}",
+            transfer.amount,
+            transfer.asset,
+            lookup_alias(wallet, &transfer.sender),
+            transfer.recipient
+        );
+        println!(
+            "  Gas fee: {} paid by {}",
+            gas_fee.amount,
+            lookup_alias(wallet, &gas_fee.payer)
+        );
+    }
+    if !found_any {
+        println!("No transfers pending in the bridge pool");
+    }
+}
+
+/// Query the balance of the token wrapping an ERC20
+pub async fn query_erc20<C: namada::ledger::queries::Client + Sync>(
+    client: &C,
+    wallet: &mut Wallet<CliWalletUtils>,
+    args: args::QueryErc20,
+) {
+    let token = args.asset.wrapped_token();
+    let balance = get_token_balance(client, &token, &args.owner)
+        .await
+        .unwrap_or_default();
+    println!(
+        "{} of the ERC20 {} wrapped by {}: {}",
+        lookup_alias(wallet, &args.owner),
+        args.asset,
+        token,
+        balance
+    );
+}
+
 /// Query the raw bytes of given storage key
 pub async fn query_raw_bytes<C: namada::ledger::queries::Client + Sync>(
     client: &C,
@@ -299,16 +355,20 @@ pub async fn query_transparent_balance<
                 None => token::balance_key(&token, &owner.address().unwrap()),
             };
             let token_alias = lookup_alias(wallet, &token);
+            let metadata = query_token_metadata(client, &token).await;
             match query_storage_value::<C, token::Amount>(client, &key).await {
-                Some(balance) => match &args.sub_prefix {
-                    Some(sub_prefix) => {
-                        println!(
-                            "{} with {}: {}",
-                            token_alias, sub_prefix, balance
-                        );
+                Some(balance) => {
+                    let balance = format_amount(metadata.as_ref(), &balance);
+                    match &args.sub_prefix {
+                        Some(sub_prefix) => {
+                            println!(
+                                "{} with {}: {}",
+                                token_alias, sub_prefix, balance
+                            );
+                        }
+                        None => println!("{}: {}", token_alias, balance),
                     }
-                    None => println!("{}: {}", token_alias, balance),
-                },
+                }
                 None => {
                     println!("No {} balance found for {}", token_alias, owner)
                 }
@@ -322,10 +382,12 @@ pub async fn query_transparent_balance<
                     query_storage_prefix::<C, token::Amount>(client, &prefix)
                         .await;
                 if let Some(balances) = balances {
+                    let metadata = query_token_metadata(client, &token).await;
                     print_balances(
                         wallet,
                         balances,
                         &token,
+                        metadata.as_ref(),
                         owner.address().as_ref(),
                     );
                 }
@@ -336,7 +398,14 @@ pub async fn query_transparent_balance<
             let balances =
                 query_storage_prefix::<C, token::Amount>(client, &prefix).await;
             if let Some(balances) = balances {
-                print_balances(wallet, balances, &token, None);
+                let metadata = query_token_metadata(client, &token).await;
+                print_balances(
+                    wallet,
+                    balances,
+                    &token,
+                    metadata.as_ref(),
+                    None,
+                );
             }
         }
         (None, None) => {
@@ -346,7 +415,14 @@ pub async fn query_transparent_balance<
                     query_storage_prefix::<C, token::Amount>(client, &key)
                         .await;
                 if let Some(balances) = balances {
-                    print_balances(wallet, balances, &token, None);
+                    let metadata = query_token_metadata(client, &token).await;
+                    print_balances(
+                        wallet,
+                        balances,
+                        &token,
+                        metadata.as_ref(),
+                        None,
+                    );
                 }
             }
         }
@@ -381,7 +457,7 @@ pub async fn query_pinned_balance<
         .values()
         .map(|fvk| ExtendedFullViewingKey::from(*fvk).fvk.vk)
         .collect();
-    let _ = shielded.load();
+    let _ = shielded.load().await;
     // Print the token balances by payment address
     for owner in owners {
         let mut balance = Err(PinnedBalanceError::InvalidViewingKey);
@@ -483,6 +559,7 @@ fn print_balances(
     wallet: &Wallet<CliWalletUtils>,
     balances: impl Iterator<Item = (storage::Key, token::Amount)>,
     token: &Address,
+    metadata: Option<&token::TokenMetadata>,
     target: Option<&Address>,
 ) {
     let stdout = io::stdout();
@@ -499,7 +576,7 @@ fn print_balances(
                     format!(
                         "with {}: {}, owned by {}",
                         sub_prefix,
-                        balance,
+                        format_amount(metadata, &balance),
                         lookup_alias(wallet, owner)
                     ),
                 )),
@@ -508,7 +585,7 @@ fn print_balances(
                         owner.clone(),
                         format!(
                             ": {}, owned by {}",
-                            balance,
+                            format_amount(metadata, &balance),
                             lookup_alias(wallet, owner)
                         ),
                     )
@@ -538,6 +615,18 @@ fn print_balances(
     }
 }
 
+/// Format a raw amount of a token with the decimal places of its metadata, or
+/// the default decimal places of amounts if it has none
+fn format_amount(
+    metadata: Option<&token::TokenMetadata>,
+    amount: &token::Amount,
+) -> String {
+    match metadata {
+        Some(metadata) => metadata.format_amount(amount),
+        None => amount.to_string(),
+    }
+}
+
 /// Query Proposals
 pub async fn query_proposal<C: namada::ledger::queries::Client + Sync>(
     client: &C,
@@ -706,14 +795,11 @@ pub async fn query_shielded_balance<
         Some(viewing_key) => vec![viewing_key],
         None => wallet.get_viewing_keys().values().copied().collect(),
     };
-    let _ = shielded.load();
     let fvks: Vec<_> = viewing_keys
         .iter()
         .map(|fvk| ExtendedFullViewingKey::from(*fvk).fvk.vk)
         .collect();
-    shielded.fetch(client, &[], &fvks).await;
-    // Save the update state so that future fetches can be short-circuited
-    let _ = shielded.save();
+    shielded.sync(client, &[], &fvks).await;
     // The epoch is required to identify timestamped tokens
     let epoch = query_and_print_epoch(client).await;
     // Map addresses to token names
@@ -752,7 +838,12 @@ pub async fn query_shielded_balance<
             } else {
                 let asset_value =
                     token::Amount::from(balance[&asset_type] as u64);
-                println!("{}: {}", token_alias, asset_value);
+                let metadata = query_token_metadata(client, &token).await;
+                println!(
+                    "{}: {}",
+                    token_alias,
+                    format_amount(metadata.as_ref(), &asset_value)
+                );
             }
         }
         // Here the user wants to know the balance of all tokens across users
@@ -1003,61 +1094,8 @@ pub async fn query_proposal_result<
             if args.offline {
                 match args.proposal_folder {
                     Some(path) => {
-                        let mut dir = fs::read_dir(&path)
-                            .await
-                            .expect("Should be able to read the directory.");
-                        let mut files = HashSet::new();
-                        let mut is_proposal_present = false;
-
-                        while let Some(entry) = dir.next().await {
-                            match entry {
-                                Ok(entry) => match entry.file_type().await {
-                                    Ok(entry_stat) => {
-                                        if entry_stat.is_file() {
-                                            if entry.file_name().eq(&"proposal")
-                                            {
-                                                is_proposal_present = true
-                                            } else if entry
-                                                .file_name()
-                                                .to_string_lossy()
-                                                .starts_with("proposal-vote-")
-                                            {
-                                                // Folder may contain other
-                                                // files than just the proposal
-                                                // and the votes
-                                                files.insert(entry.path());
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "Can't read entry type: {}.",
-                                            e
-                                        );
-                                        cli::safe_exit(1)
-                                    }
-                                },
-                                Err(e) => {
-                                    eprintln!("Can't read entry: {}.", e);
-                                    cli::safe_exit(1)
-                                }
-                            }
-                        }
-
-                        if !is_proposal_present {
-                            eprintln!(
-                                "The folder must contain the offline proposal \
-                                 in a file named \"proposal\""
-                            );
-                            cli::safe_exit(1)
-                        }
-
-                        let file = File::open(path.join("proposal"))
-                            .expect("Proposal file must exist.");
-                        let proposal: OfflineProposal =
-                            serde_json::from_reader(file).expect(
-                                "JSON was not well-formatted for proposal.",
-                            );
+                        let (proposal, files) =
+                            read_offline_proposal_folder(&path).await;
 
                         let public_key =
                             get_public_key(client, &proposal.address)
@@ -1236,11 +1274,26 @@ pub async fn query_and_print_unbonds<
     if !not_yet_withdrawable.is_empty() {
         println!("Current epoch: {current_epoch}.")
     }
+    let clock = if not_yet_withdrawable.is_empty() {
+        None
+    } else {
+        query_epoch_clock(client).await
+    };
+    let now = DateTimeUtc::now();
     for (withdraw_epoch, amount) in not_yet_withdrawable {
-        println!(
-            "Amount {amount} withdrawable starting from epoch \
-             {withdraw_epoch}."
-        );
+        match &clock {
+            Some(clock) => {
+                let secs = clock.duration_until_epoch(withdraw_epoch, now);
+                println!(
+                    "Amount {amount} withdrawable starting from epoch \
+                     {withdraw_epoch} (in about {secs} seconds)."
+                );
+            }
+            None => println!(
+                "Amount {amount} withdrawable starting from epoch \
+                 {withdraw_epoch}."
+            ),
+        }
     }
 }
 
@@ -1535,6 +1588,24 @@ pub async fn query_slashes<C: namada::ledger::queries::Client + Sync>(
     }
 }
 
+/// Query the PoS rewards of a bond that can be claimed
+pub async fn query_rewards<C: namada::ledger::queries::Client + Sync>(
+    client: &C,
+    args: args::QueryRewards,
+) {
+    let rewards = namada::ledger::rpc::query_rewards(
+        client,
+        &args.source,
+        &args.validator,
+    )
+    .await;
+    let source = args.source.as_ref().unwrap_or(&args.validator);
+    println!(
+        "Rewards of {} from validator {} that can be claimed: {} NAM",
+        source, args.validator, rewards
+    );
+}
+
 pub async fn query_delegations<C: namada::ledger::queries::Client + Sync>(
     client: &C,
     _wallet: &mut Wallet<CliWalletUtils>,
@@ -1836,6 +1907,129 @@ pub async fn get_proposal_votes<C: namada::ledger::queries::Client + Sync>(
     namada::ledger::rpc::get_proposal_votes(client, epoch, proposal_id).await
 }
 
+/// Read the offline proposal and the paths of the offline votes on it from
+/// the folder. The proposal is in a file named "proposal" and each vote in a
+/// file whose name starts with "proposal-vote-".
+async fn read_offline_proposal_folder(
+    path: &std::path::Path,
+) -> (OfflineProposal, HashSet<PathBuf>) {
+    let mut dir = fs::read_dir(path)
+        .await
+        .expect("Should be able to read the directory.");
+    let mut files = HashSet::new();
+    let mut is_proposal_present = false;
+
+    while let Some(entry) = dir.next().await {
+        match entry {
+            Ok(entry) => match entry.file_type().await {
+                Ok(entry_stat) => {
+                    if entry_stat.is_file() {
+                        if entry.file_name().eq(&"proposal") {
+                            is_proposal_present = true
+                        } else if entry
+                            .file_name()
+                            .to_string_lossy()
+                            .starts_with("proposal-vote-")
+                        {
+                            // Folder may contain other files than just the
+                            // proposal and the votes
+                            files.insert(entry.path());
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Can't read entry type: {}.", e);
+                    cli::safe_exit(1)
+                }
+            },
+            Err(e) => {
+                eprintln!("Can't read entry: {}.", e);
+                cli::safe_exit(1)
+            }
+        }
+    }
+
+    if !is_proposal_present {
+        eprintln!(
+            "The folder must contain the offline proposal in a file named \
+             \"proposal\""
+        );
+        cli::safe_exit(1)
+    }
+
+    let file =
+        File::open(path.join("proposal")).expect("Proposal file must exist.");
+    let proposal: OfflineProposal = serde_json::from_reader(file)
+        .expect("JSON was not well-formatted for proposal.");
+    (proposal, files)
+}
+
+/// Verify the signatures of an offline proposal and of the offline votes on
+/// it against the public keys of their signers on chain, without submitting
+/// any tx
+pub async fn verify_offline_proposal<
+    C: namada::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    args: args::VerifyOfflineProposal,
+) {
+    let (proposal, files) =
+        read_offline_proposal_folder(&args.proposal_folder).await;
+    let is_valid_proposal =
+        match get_public_key(client, &proposal.address).await {
+            Some(public_key) => proposal.check_signature(&public_key),
+            None => false,
+        };
+    println!(
+        "Proposal by {}, to be tallied at epoch {}: {}",
+        proposal.author,
+        proposal.tally_epoch,
+        if is_valid_proposal {
+            "valid signature"
+        } else {
+            "invalid signature"
+        }
+    );
+
+    let mut files: Vec<_> = files.into_iter().collect();
+    files.sort();
+    let mut valid_votes = 0;
+    for path in &files {
+        let file = File::open(path).expect("Vote file must exist.");
+        let vote: OfflineVote = match serde_json::from_reader(file) {
+            Ok(vote) => vote,
+            Err(_) => {
+                println!("{:4}{}: not a vote", "", path.display());
+                continue;
+            }
+        };
+        let status = if !vote.is_for(&proposal) {
+            "vote on another proposal"
+        } else {
+            match get_public_key(client, &vote.address).await {
+                Some(public_key) if vote.check_signature(&public_key) => {
+                    valid_votes += 1;
+                    "valid signature"
+                }
+                Some(_) => "invalid signature",
+                None => "no public key revealed on chain",
+            }
+        };
+        println!(
+            "{:4}{}: {} voted {}, {}",
+            "",
+            path.display(),
+            vote.address,
+            vote.vote,
+            status
+        );
+    }
+    println!("{} of {} votes are valid", valid_votes, files.len());
+    if !is_valid_proposal {
+        cli::safe_exit(1)
+    }
+}
+
 pub async fn get_proposal_offline_votes<
     C: namada::ledger::queries::Client + Sync,
 >(
@@ -1845,10 +2039,9 @@ pub async fn get_proposal_offline_votes<
 ) -> Votes {
     // let validators = get_all_validators(client, proposal.tally_epoch).await;
 
-    let proposal_hash = proposal.compute_hash();
-
     let mut yay_validators: HashMap<Address, (VotePower, ProposalVote)> =
         HashMap::new();
+    let mut nay_validators: HashMap<Address, VotePower> = HashMap::new();
     let mut delegators: HashMap<
         Address,
         HashMap<Address, (VotePower, ProposalVote)>,
@@ -1864,18 +2057,19 @@ pub async fn get_proposal_offline_votes<
             .await
             .expect("Public key should exist.");
 
-        if !proposal_vote.proposal_hash.eq(&proposal_hash)
+        if !proposal_vote.is_for(&proposal)
             || !proposal_vote.check_signature(&public_key)
         {
             continue;
         }
 
-        if proposal_vote.vote.is_yay()
-            // && validators.contains(&proposal_vote.address)
-            && unwrap_client_response::<C,bool>(
-                RPC.vp().pos().is_validator(client, &proposal_vote.address).await,
-            )
-        {
+        // && validators.contains(&proposal_vote.address)
+        if unwrap_client_response::<C, bool>(
+            RPC.vp()
+                .pos()
+                .is_validator(client, &proposal_vote.address)
+                .await,
+        ) {
             let amount: VotePower = get_validator_stake(
                 client,
                 proposal.tally_epoch,
@@ -1884,10 +2078,14 @@ pub async fn get_proposal_offline_votes<
             .await
             .unwrap_or_default()
             .into();
-            yay_validators.insert(
-                proposal_vote.address,
-                (amount, ProposalVote::Yay(VoteType::Default)),
-            );
+            if proposal_vote.vote.is_yay() {
+                yay_validators.insert(
+                    proposal_vote.address,
+                    (amount, ProposalVote::Yay(VoteType::Default)),
+                );
+            } else {
+                nay_validators.insert(proposal_vote.address, amount);
+            }
         } else if is_delegator_at(
             client,
             &proposal_vote.address,
@@ -2016,6 +2214,7 @@ pub async fn get_proposal_offline_votes<
 
     Votes {
         yay_validators,
+        nay_validators,
         delegators,
     }
 }
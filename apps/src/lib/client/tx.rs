@@ -71,6 +71,20 @@ pub async fn submit_update_vp<C: namada::ledger::queries::Client + Sync>(
     tx::submit_update_vp::<C, _>(client, &mut ctx.wallet, args).await
 }
 
+pub async fn submit_update_signers<
+    C: namada::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    ctx: &mut Context,
+    mut args: args::TxUpdateSigners,
+) -> Result<(), tx::Error> {
+    args.tx.chain_id = args
+        .tx
+        .chain_id
+        .or_else(|| Some(ctx.config.ledger.chain_id.clone()));
+    tx::submit_update_signers::<C, _>(client, &mut ctx.wallet, args).await
+}
+
 pub async fn submit_init_account<C: namada::ledger::queries::Client + Sync>(
     client: &C,
     ctx: &mut Context,
@@ -468,6 +482,20 @@ pub async fn submit_ibc_transfer<C: namada::ledger::queries::Client + Sync>(
     tx::submit_ibc_transfer::<C, _>(client, &mut ctx.wallet, args).await
 }
 
+pub async fn submit_bridge_pool_tx<
+    C: namada::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    mut ctx: Context,
+    mut args: args::EthereumBridgePool,
+) -> Result<(), tx::Error> {
+    args.tx.chain_id = args
+        .tx
+        .chain_id
+        .or_else(|| Some(ctx.config.ledger.chain_id.clone()));
+    tx::submit_bridge_pool_tx::<C, _>(client, &mut ctx.wallet, args).await
+}
+
 pub async fn submit_init_proposal<C: namada::ledger::queries::Client + Sync>(
     client: &C,
     mut ctx: Context,
@@ -1013,6 +1041,18 @@ pub async fn submit_unbond<C: namada::ledger::queries::Client + Sync>(
     tx::submit_unbond::<C, _>(client, &mut ctx.wallet, args).await
 }
 
+pub async fn submit_redelegate<C: namada::ledger::queries::Client + Sync>(
+    client: &C,
+    ctx: &mut Context,
+    mut args: args::Redelegate,
+) -> Result<(), tx::Error> {
+    args.tx.chain_id = args
+        .tx
+        .chain_id
+        .or_else(|| Some(ctx.config.ledger.chain_id.clone()));
+    tx::submit_redelegate::<C, _>(client, &mut ctx.wallet, args).await
+}
+
 pub async fn submit_withdraw<C: namada::ledger::queries::Client + Sync>(
     client: &C,
     mut ctx: Context,
@@ -1025,6 +1065,18 @@ pub async fn submit_withdraw<C: namada::ledger::queries::Client + Sync>(
     tx::submit_withdraw::<C, _>(client, &mut ctx.wallet, args).await
 }
 
+pub async fn submit_claim_rewards<C: namada::ledger::queries::Client + Sync>(
+    client: &C,
+    ctx: &mut Context,
+    mut args: args::ClaimRewards,
+) -> Result<(), tx::Error> {
+    args.tx.chain_id = args
+        .tx
+        .chain_id
+        .or_else(|| Some(ctx.config.ledger.chain_id.clone()));
+    tx::submit_claim_rewards::<C, _>(client, &mut ctx.wallet, args).await
+}
+
 pub async fn submit_validator_commission_change<
     C: namada::ledger::queries::Client + Sync,
 >(
@@ -1044,6 +1096,20 @@ pub async fn submit_validator_commission_change<
     .await
 }
 
+pub async fn submit_change_consensus_key<
+    C: namada::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    mut ctx: Context,
+    mut args: args::TxChangeConsensusKey,
+) -> Result<(), tx::Error> {
+    args.tx.chain_id = args
+        .tx
+        .chain_id
+        .or_else(|| Some(ctx.config.ledger.chain_id.clone()));
+    tx::submit_change_consensus_key::<C, _>(client, &mut ctx.wallet, args).await
+}
+
 pub async fn submit_unjail_validator<
     C: namada::ledger::queries::Client + Sync,
 >(
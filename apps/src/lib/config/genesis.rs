@@ -1,6 +1,6 @@
 //! The parameters used for the chain's genesis
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 #[cfg(not(feature = "dev"))]
 use std::path::Path;
 
@@ -8,6 +8,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use derivative::Derivative;
 #[cfg(not(feature = "mainnet"))]
 use namada::core::ledger::testnet_pow;
+use namada::ledger::gas::GasCostTable;
 use namada::ledger::governance::parameters::GovParams;
 use namada::ledger::parameters::EpochDuration;
 use namada::ledger::pos::{GenesisValidator, PosParams};
@@ -33,14 +34,17 @@ pub mod genesis_config {
     use eyre::Context;
     #[cfg(not(feature = "mainnet"))]
     use namada::core::ledger::testnet_pow;
+    use namada::ledger::gas::GasCostTable;
     use namada::ledger::governance::parameters::GovParams;
     use namada::ledger::parameters::EpochDuration;
     use namada::ledger::pos::{GenesisValidator, PosParams};
     use namada::types::address::Address;
     use namada::types::chain::ProposalBytes;
+    use namada::types::hash::Hash;
     use namada::types::key::dkg_session_keys::DkgPublicKey;
     use namada::types::key::*;
     use namada::types::time::Rfc3339String;
+    use namada::types::transaction::DEFAULT_GAS_LIMIT_RESOLUTION;
     use namada::types::{storage, token};
     use rust_decimal::Decimal;
     use serde::{Deserialize, Serialize};
@@ -147,6 +151,9 @@ pub mod genesis_config {
         // Maximum size of proposal in kibibytes (KiB)
         // XXX: u64 doesn't work with toml-rs!
         pub max_proposal_code_size: u64,
+        // Gas budget of the execution of a proposal code. Defaults to the
+        // budget of `GovParams::default()` when not set.
+        pub max_proposal_code_gas: Option<u64>,
         // Minimum proposal period length in epochs
         // XXX: u64 doesn't work with toml-rs!
         pub min_proposal_period: u64,
@@ -159,6 +166,10 @@ pub mod genesis_config {
         // Minimum number of epoch between end and grace epoch
         // XXX: u64 doesn't work with toml-rs!
         pub min_proposal_grace_epochs: u64,
+        // Minimum fraction of the total voting power voting on a rejected
+        // proposal for its funds to be refunded. Defaults to the quorum of
+        // `GovParams::default()` when not set.
+        pub min_proposal_quorum: Option<Decimal>,
     }
 
     /// Validator pre-genesis configuration can be created with client utils
@@ -212,6 +223,15 @@ pub mod genesis_config {
         // Initial balances held by accounts defined elsewhere.
         // XXX: u64 doesn't work with toml-rs!
         pub balances: Option<HashMap<String, u64>>,
+        // Symbol of the token. (default: the name of the token)
+        pub symbol: Option<String>,
+        // Number of decimal places of the display of amounts. (default: 6)
+        pub decimals: Option<u8>,
+        // SHA-256 hash of the icon of the token, if any.
+        pub icon_hash: Option<HexString>,
+        // Alias of an established account or address of the hook of the
+        // token, whose VP validates its balance changes, if any.
+        pub hook: Option<String>,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -267,6 +287,24 @@ pub mod genesis_config {
         pub pos_gain_p: Decimal,
         /// PoS gain d
         pub pos_gain_d: Decimal,
+        /// Resolution of the gas limits of wrapper txs. Defaults to
+        /// `DEFAULT_GAS_LIMIT_RESOLUTION` when not set.
+        pub gas_limit_resolution: Option<u64>,
+        /// Addresses of the tokens that wrapper tx fees may be paid in, each
+        /// mapped to the amount of the token worth one unit of the native
+        /// token. `None` value or an empty table disables whitelisting.
+        pub fee_tokens: Option<HashMap<String, Decimal>>,
+        /// Costs of the operations metered in txs and VPs. Defaults to the
+        /// default `GasCostTable` when not set.
+        pub gas_cost_table: Option<GasCostTable>,
+        /// Minimum price of a unit of gas. Defaults to zero when not set.
+        pub min_gas_price: Option<token::Amount>,
+        /// Max cumulative gas declared by the wrapper txs of a block.
+        /// Defaults to no limit when not set.
+        pub max_block_gas: Option<u64>,
+        /// Max size in bytes of a single tx. Defaults to no limit when not
+        /// set.
+        pub max_tx_bytes: Option<u64>,
         #[cfg(not(feature = "mainnet"))]
         /// Fix wrapper tx fees
         pub wrapper_tx_fees: Option<token::Amount>,
@@ -390,6 +428,7 @@ pub mod genesis_config {
     }
 
     fn load_token(
+        name: &str,
         config: &TokenAccountConfig,
         wasm: &HashMap<String, WasmConfig>,
         validators: &HashMap<String, Validator>,
@@ -398,6 +437,44 @@ pub mod genesis_config {
     ) -> TokenAccount {
         let token_vp_name = config.vp.as_ref().unwrap();
         let token_vp_config = wasm.get(token_vp_name).unwrap();
+        let decimals =
+            config.decimals.unwrap_or(token::MAX_DECIMAL_PLACES as u8);
+        if decimals > token::MAX_METADATA_DECIMALS {
+            eprintln!(
+                "The token {} may have at most {} decimal places",
+                name,
+                token::MAX_METADATA_DECIMALS
+            );
+            cli::safe_exit(1);
+        }
+        let metadata = token::TokenMetadata {
+            symbol: config.symbol.clone().unwrap_or_else(|| name.to_owned()),
+            decimals,
+            icon_hash: config.icon_hash.as_ref().map(|icon_hash| {
+                Hash(icon_hash.to_sha256_bytes().unwrap_or_else(|err| {
+                    eprintln!(
+                        "Invalid icon hash of the token {}: {}",
+                        name, err
+                    );
+                    cli::safe_exit(1);
+                }))
+            }),
+        };
+        let hook = config.hook.as_ref().map(|alias_or_address| {
+            Address::decode(alias_or_address).unwrap_or_else(|decode_err| {
+                match established_accounts.get(alias_or_address) {
+                    Some(established) => established.address.clone(),
+                    None => {
+                        eprintln!(
+                            "The hook {} of the token {} is unknown alias \
+                             and not a valid address: {}",
+                            alias_or_address, name, decode_err
+                        );
+                        cli::safe_exit(1)
+                    }
+                }
+            })
+        });
 
         TokenAccount {
             address: Address::decode(config.address.as_ref().unwrap()).unwrap(),
@@ -470,6 +547,8 @@ pub mod genesis_config {
                     )
                 })
                 .collect(),
+            metadata,
+            hook,
         }
     }
 
@@ -568,11 +647,11 @@ pub mod genesis_config {
             .iter()
             .map(|(name, cfg)| (name.clone(), load_implicit(cfg)))
             .collect();
-        #[allow(clippy::iter_kv_map)]
         let token_accounts = token
             .iter()
-            .map(|(_name, cfg)| {
+            .map(|(name, cfg)| {
                 load_token(
+                    name,
                     cfg,
                     &wasm,
                     &validators,
@@ -594,6 +673,35 @@ pub mod genesis_config {
             .to_sha256_bytes()
             .unwrap();
 
+        let fee_tokens = parameters
+            .fee_tokens
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(address, rate)| {
+                let address = Address::decode(&address).unwrap_or_else(|_| {
+                    eprintln!("Invalid fee token address {}", address);
+                    cli::safe_exit(1);
+                });
+                if rate <= Decimal::ZERO {
+                    eprintln!(
+                        "The conversion rate of the fee token {} must be \
+                         positive",
+                        address
+                    );
+                    cli::safe_exit(1);
+                }
+                (address, rate)
+            })
+            .collect();
+
+        let gas_limit_resolution = parameters
+            .gas_limit_resolution
+            .unwrap_or(DEFAULT_GAS_LIMIT_RESOLUTION);
+        if gas_limit_resolution == 0 {
+            eprintln!("The gas limit resolution must be non-zero");
+            cli::safe_exit(1);
+        }
+
         let min_duration: i64 =
             60 * 60 * 24 * 365 / (parameters.epochs_per_year as i64);
         let parameters = Parameters {
@@ -619,24 +727,36 @@ pub mod genesis_config {
             pos_gain_d: parameters.pos_gain_d,
             staked_ratio: Decimal::ZERO,
             pos_inflation_amount: 0,
+            gas_limit_resolution,
+            fee_tokens,
+            gas_cost_table: parameters.gas_cost_table.unwrap_or_default(),
+            min_gas_price: parameters.min_gas_price.unwrap_or_default(),
+            max_block_gas: parameters.max_block_gas.unwrap_or(u64::MAX),
+            max_tx_bytes: parameters.max_tx_bytes.unwrap_or(u64::MAX),
             wrapper_tx_fees: parameters.wrapper_tx_fees,
         };
 
         let GovernanceParamsConfig {
             min_proposal_fund,
             max_proposal_code_size,
+            max_proposal_code_gas,
             min_proposal_period,
             max_proposal_content_size,
             min_proposal_grace_epochs,
             max_proposal_period,
+            min_proposal_quorum,
         } = gov_params;
         let gov_params = GovParams {
             min_proposal_fund,
             max_proposal_code_size,
+            max_proposal_code_gas: max_proposal_code_gas
+                .unwrap_or(GovParams::default().max_proposal_code_gas),
             min_proposal_period,
             max_proposal_content_size,
             min_proposal_grace_epochs,
             max_proposal_period,
+            min_proposal_quorum: min_proposal_quorum
+                .unwrap_or(GovParams::default().min_proposal_quorum),
         };
 
         let PosParamsConfig {
@@ -809,6 +929,11 @@ pub struct TokenAccount {
     /// Accounts' balances of this token
     #[derivative(PartialOrd = "ignore", Ord = "ignore")]
     pub balances: HashMap<Address, token::Amount>,
+    /// The metadata of this token
+    #[derivative(PartialOrd = "ignore", Ord = "ignore")]
+    pub metadata: token::TokenMetadata,
+    /// The hook of this token, if any
+    pub hook: Option<Address>,
 }
 
 #[derive(
@@ -867,6 +992,18 @@ pub struct Parameters {
     pub staked_ratio: Decimal,
     /// PoS inflation amount from the last epoch (read + write for every epoch)
     pub pos_inflation_amount: u64,
+    /// Resolution of the gas limits of wrapper txs
+    pub gas_limit_resolution: u64,
+    /// Whitelisted fee tokens with their conversion rates to the native token
+    pub fee_tokens: BTreeMap<Address, Decimal>,
+    /// Costs of the operations metered in txs and VPs
+    pub gas_cost_table: GasCostTable,
+    /// Minimum price of a unit of gas
+    pub min_gas_price: token::Amount,
+    /// Max cumulative gas declared by the wrapper txs of a block
+    pub max_block_gas: u64,
+    /// Max size in bytes of a single tx
+    pub max_tx_bytes: u64,
     /// Fixed Wrapper tx fees
     #[cfg(not(feature = "mainnet"))]
     pub wrapper_tx_fees: Option<token::Amount>,
@@ -884,6 +1021,7 @@ pub fn genesis(num_validators: u64) -> Genesis {
     use namada::types::address::{
         self, apfel, btc, dot, eth, kartoffel, nam, schnitzel,
     };
+    use namada::types::transaction::DEFAULT_GAS_LIMIT_RESOLUTION;
     use rust_decimal_macros::dec;
 
     use crate::wallet;
@@ -966,6 +1104,12 @@ pub fn genesis(num_validators: u64) -> Genesis {
         pos_gain_d: dec!(0.1),
         staked_ratio: dec!(0.0),
         pos_inflation_amount: 0,
+        gas_limit_resolution: DEFAULT_GAS_LIMIT_RESOLUTION,
+        fee_tokens: BTreeMap::new(),
+        gas_cost_table: GasCostTable::default(),
+        min_gas_price: token::Amount::default(),
+        max_block_gas: u64::MAX,
+        max_tx_bytes: u64::MAX,
         wrapper_tx_fees: Some(token::Amount::whole(0)),
     };
     let albert = EstablishedAccount {
@@ -1041,12 +1185,14 @@ pub fn genesis(num_validators: u64) -> Genesis {
         .collect()
     }
     let token_accounts = tokens()
-        .into_keys()
-        .map(|address| TokenAccount {
+        .into_iter()
+        .map(|(address, symbol)| TokenAccount {
             address,
             vp_code_path: vp_token_path.into(),
             vp_sha256: Default::default(),
             balances: balances.clone(),
+            metadata: token::TokenMetadata::new(symbol),
+            hook: None,
         })
         .collect();
     Genesis {
@@ -14,6 +14,7 @@ use directories::ProjectDirs;
 use namada::types::chain::ChainId;
 use namada::types::storage::BlockHeight;
 use namada::types::time::Rfc3339String;
+use namada::types::token;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -90,6 +91,15 @@ pub struct ActionAtHeight {
     pub action: Action,
 }
 
+/// A remote signer, e.g. in an HSM or a KMS, holding a secret key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSigner {
+    /// The URI of the gRPC service of the signer.
+    pub address: String,
+    /// The identifier of the key in the signer.
+    pub key_id: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Ledger {
     pub genesis_time: Rfc3339String,
@@ -114,6 +124,47 @@ pub struct Shell {
     /// When set, will limit the how many block heights in the past can the
     /// storage be queried for reading values.
     pub storage_read_past_height_limit: Option<u64>,
+    /// The minimum gas price in the native token that wrappers must offer to
+    /// be accepted into the mempool, converted into their fee tokens. When not
+    /// set, wrappers offering no tip are accepted.
+    pub mempool_min_gas_price: Option<token::Amount>,
+    /// The percentage of the block space reserved for vote extension
+    /// protocol txs in the blocks proposed by this node. When not set,
+    /// defaults to 10%.
+    pub vote_extension_block_space_pct: Option<u64>,
+    /// When set, the node records its last block space allocation decisions,
+    /// up to this many, which can be queried to diagnose rejected proposals.
+    pub alloc_replay_log_capacity: Option<usize>,
+    /// When set, the node records the wrapper txs pending in its mempool, up
+    /// to this many, and serves them to its RPC clients. This exposes the
+    /// mempool of the node, so it should not be set on a public node.
+    pub mempool_view_capacity: Option<usize>,
+    /// When set, a validator node attaches to its votes the prices of the fee
+    /// tokens found in this JSON file, which maps the addresses of the tokens
+    /// to their prices. The file is read again for every vote, such that it
+    /// can be updated by an external price feed. Only used with ABCI++.
+    pub oracle_price_feed: Option<PathBuf>,
+    /// When set, a validator node votes on the events of the contract of the
+    /// Ethereum bridge found in this JSON file, which lists the events
+    /// observed by an Ethereum node. The file is read again at every block,
+    /// such that it can be updated as new events are observed.
+    pub ethereum_events_feed: Option<PathBuf>,
+    /// When set, the node serves a gRPC gateway at this address, which
+    /// accepts protobuf encoded txs and submits them to the ledger.
+    pub grpc_gateway_address: Option<SocketAddr>,
+    /// When set, a validator node signs its protocol txs with the protocol
+    /// key held by this remote signer, rather than the one in its wallet.
+    pub protocol_remote_signer: Option<RemoteSigner>,
+    /// When set, the node creates a state sync snapshot of its storage at the
+    /// start of every epoch that is a multiple of this interval, and serves
+    /// its snapshots to the nodes joining the network.
+    pub snapshot_epoch_interval: Option<u64>,
+    /// The number of the most recent state sync snapshots that are kept.
+    /// When not set, defaults to 2.
+    pub snapshots_to_keep: Option<u64>,
+    /// When set, the node prunes the diffs and the Merkle tree stores of its
+    /// storage that are only needed to read the state of old heights.
+    pub pruning: Option<Pruning>,
     /// Use the [`Ledger::db_dir()`] method to read the value.
     db_dir: PathBuf,
     /// Use the [`Ledger::tendermint_dir()`] method to read the value.
@@ -122,6 +173,19 @@ pub struct Shell {
     pub action_at_height: Option<ActionAtHeight>,
 }
 
+/// The history of the storage retained by a node that prunes it. The node
+/// always retains the history of the last unbonding period, which is needed
+/// to process the evidence of misbehaviour, and of the heights that the
+/// storage can be queried for, e.g. for IBC proofs, as limited by
+/// `storage_read_past_height_limit`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pruning {
+    /// When set, the history of this many of the last heights is retained.
+    pub keep_last_heights: Option<u64>,
+    /// When set, the history of this many of the last epochs is retained.
+    pub keep_epochs: Option<u64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Tendermint {
     pub rpc_address: SocketAddr,
@@ -150,6 +214,10 @@ pub struct Tendermint {
     pub instrumentation_namespace: String,
     /// Toggle to enable tx indexing
     pub tx_index: bool,
+    /// When set, Tendermint signs consensus messages through the remote
+    /// signer, e.g. tmkms, that it listens for at this address, rather than
+    /// with the consensus key on disk.
+    pub priv_validator_laddr: Option<TendermintAddress>,
 }
 
 impl Ledger {
@@ -172,6 +240,17 @@ impl Ledger {
                 tx_wasm_compilation_cache_bytes: None,
                 // Default corresponds to 1 hour of past blocks at 1 block/sec
                 storage_read_past_height_limit: Some(3600),
+                mempool_min_gas_price: None,
+                vote_extension_block_space_pct: None,
+                alloc_replay_log_capacity: None,
+                mempool_view_capacity: None,
+                oracle_price_feed: None,
+                ethereum_events_feed: None,
+                grpc_gateway_address: None,
+                protocol_remote_signer: None,
+                snapshot_epoch_interval: None,
+                snapshots_to_keep: None,
+                pruning: None,
                 db_dir: DB_DIR.into(),
                 tendermint_dir: TENDERMINT_DIR.into(),
                 action_at_height: None,
@@ -207,6 +286,7 @@ impl Ledger {
                 ),
                 instrumentation_namespace: "namadan_tm".to_string(),
                 tx_index: false,
+                priv_validator_laddr: None,
             },
         }
     }
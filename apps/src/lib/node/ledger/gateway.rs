@@ -0,0 +1,477 @@
+//! A gRPC gateway for the submission of txs by integrators that do not
+//! link this crate, e.g. exchanges and custodians. The gateway accepts
+//! protobuf encoded txs, translates them to their Borsh encoding, which
+//! is what their hashes and signatures are computed over, and submits
+//! them to the mempool of the ledger.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use namada::ledger::rpc::{self, TxEventQuery};
+use namada::proto::{
+    Code, Commitment, Compression, Data, Header, Memo, Section, SignMode,
+    Signature, Tx,
+};
+use namada::types::chain::ChainId;
+use namada::types::hash::Hash;
+use namada::types::storage::{BlockHeight, Epoch};
+use namada::types::time::DateTimeUtc;
+use namada::types::transaction::{
+    Fee, GasLimit, TxType, WrapperTx, WRAPPER_WIRE_VERSION,
+};
+use thiserror::Error;
+use tonic::{Request, Response, Status};
+
+use self::proto::tx_gateway_server::{TxGateway, TxGatewayServer};
+use crate::facade::tendermint_rpc::{Client, HttpClient};
+
+/// The protobuf messages and service of the gateway
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("gateway");
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Missing field {0}")]
+    MissingField(&'static str),
+    #[error("Invalid field {0}: {1}")]
+    InvalidField(&'static str, String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Parse the given field from its string encoding
+fn parse<T>(field: &'static str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|err: T::Err| Error::InvalidField(field, err.to_string()))
+}
+
+/// Decode the given field from its Borsh encoding
+fn borsh<T: BorshDeserialize>(field: &'static str, bytes: &[u8]) -> Result<T> {
+    T::try_from_slice(bytes)
+        .map_err(|err| Error::InvalidField(field, err.to_string()))
+}
+
+/// Decode the given hash field
+fn hash(field: &'static str, bytes: &[u8]) -> Result<Hash> {
+    Hash::try_from(bytes)
+        .map_err(|err| Error::InvalidField(field, err.to_string()))
+}
+
+/// Decode the given salt field
+fn salt(field: &'static str, bytes: &[u8]) -> Result<[u8; 8]> {
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidField(field, "expected 8 bytes".into()))
+}
+
+/// Decode the given compression field
+fn compression(value: i32) -> Result<Compression> {
+    match proto::Compression::from_i32(value) {
+        Some(proto::Compression::None) => Ok(Compression::None),
+        Some(proto::Compression::Zstd) => Ok(Compression::Zstd),
+        None => Err(Error::InvalidField(
+            "compression",
+            format!("unknown scheme {}", value),
+        )),
+    }
+}
+
+/// Translate a protobuf encoded tx to the internal one
+pub fn decode_tx(tx: proto::Tx) -> Result<Tx> {
+    let header = tx.header.ok_or(Error::MissingField("header"))?;
+    Ok(Tx {
+        header: decode_header(header)?,
+        sections: tx
+            .sections
+            .into_iter()
+            .map(decode_section)
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// Translate a protobuf encoded tx header to the internal one
+fn decode_header(header: proto::Header) -> Result<Header> {
+    let tx_type = match header.tx_type.ok_or(Error::MissingField("tx_type"))? {
+        proto::header::TxType::Raw(_) => TxType::Raw,
+        proto::header::TxType::Wrapper(wrapper) => {
+            TxType::Wrapper(Box::new(decode_wrapper(wrapper)?))
+        }
+    };
+    Ok(Header {
+        version: u8::try_from(header.version)
+            .map_err(|err| Error::InvalidField("version", err.to_string()))?,
+        chain_id: ChainId(header.chain_id),
+        expiration: header
+            .expiration
+            .map(|expiration| parse::<DateTimeUtc>("expiration", &expiration))
+            .transpose()?,
+        timestamp: parse("timestamp", &header.timestamp)?,
+        code_hash: hash("code_hash", &header.code_hash)?,
+        data_hash: hash("data_hash", &header.data_hash)?,
+        tx_type,
+        batch: header
+            .batch
+            .into_iter()
+            .map(|hashes| {
+                Ok(namada::proto::InnerTxHashes {
+                    code_hash: hash("batch", &hashes.code_hash)?,
+                    data_hash: hash("batch", &hashes.data_hash)?,
+                })
+            })
+            .collect::<Result<_>>()?,
+        memo_hash: header
+            .memo_hash
+            .map(|memo_hash| hash("memo_hash", &memo_hash))
+            .transpose()?,
+    })
+}
+
+/// Translate a protobuf encoded wrapper tx to the internal one
+fn decode_wrapper(wrapper: proto::WrapperTx) -> Result<WrapperTx> {
+    let fee = wrapper.fee.ok_or(Error::MissingField("fee"))?;
+    Ok(WrapperTx {
+        wire_version: WRAPPER_WIRE_VERSION,
+        fee: Fee {
+            amount: parse("fee", &fee.amount)?,
+            token: parse("fee", &fee.token)?,
+        },
+        pk: parse("pk", &wrapper.pk)?,
+        epoch: Epoch(wrapper.epoch),
+        gas_limit: GasLimit::from_multiplier(wrapper.gas_limit),
+        unshield: wrapper
+            .unshield
+            .map(|unshield| hash("unshield", &unshield))
+            .transpose()?,
+        gas_price: wrapper
+            .gas_price
+            .map(|gas_price| parse("gas_price", &gas_price))
+            .transpose()?,
+        expiration_height: wrapper.expiration_height.map(BlockHeight),
+        nonce: wrapper.nonce,
+        sponsor: wrapper
+            .sponsor
+            .map(|sponsor| parse("sponsor", &sponsor))
+            .transpose()?,
+        fee_payer_address: wrapper
+            .fee_payer_address
+            .map(|address| parse("fee_payer_address", &address))
+            .transpose()?,
+        #[cfg(not(feature = "mainnet"))]
+        pow_solution: wrapper
+            .pow_solution
+            .map(|solution| borsh("pow_solution", &solution))
+            .transpose()?,
+    })
+}
+
+/// Translate a protobuf encoded code section to the internal one
+fn decode_code(code: proto::Code) -> Result<Code> {
+    Ok(Code {
+        salt: salt("salt", &code.salt)?,
+        compression: compression(code.compression)?,
+        code: match code.code.ok_or(Error::MissingField("code"))? {
+            proto::code::Code::Id(bytes) => Commitment::Id(bytes),
+            proto::code::Code::Hash(bytes) => {
+                Commitment::Hash(hash("code", &bytes)?)
+            }
+        },
+    })
+}
+
+/// Translate a protobuf encoded signature section to the internal one
+fn decode_signature(sig: proto::Signature) -> Result<Signature> {
+    let signatures = sig
+        .signatures
+        .into_iter()
+        .map(|signer| {
+            Ok((
+                parse("signatures", &signer.pk)?,
+                borsh("signatures", &signer.signature)?,
+            ))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    Ok(Signature::from_parts(
+        salt("salt", &sig.salt)?,
+        hash("target", &sig.target)?,
+        signatures,
+        match (sig.text, sig.eip191) {
+            (Some(_), true) => {
+                return Err(Error::InvalidField(
+                    "eip191",
+                    "EIP-191 signatures are never textual".to_string(),
+                ));
+            }
+            (Some(text), false) => SignMode::Textual(text),
+            (None, true) => SignMode::Eip191,
+            (None, false) => SignMode::Hash,
+        },
+    ))
+}
+
+/// Translate a protobuf encoded section to the internal one
+fn decode_section(section: proto::Section) -> Result<Section> {
+    use proto::section::Section as ProtoSection;
+
+    Ok(
+        match section.section.ok_or(Error::MissingField("section"))? {
+            ProtoSection::Data(data) => Section::Data(Data {
+                salt: salt("salt", &data.salt)?,
+                compression: compression(data.compression)?,
+                data: data.data,
+            }),
+            ProtoSection::ExtraData(code) => {
+                Section::ExtraData(decode_code(code)?)
+            }
+            ProtoSection::Code(code) => Section::Code(decode_code(code)?),
+            ProtoSection::Signature(sig) => {
+                Section::Signature(decode_signature(sig)?)
+            }
+            ProtoSection::Ciphertext(bytes) => {
+                Section::Ciphertext(borsh("ciphertext", &bytes)?)
+            }
+            ProtoSection::MaspTx(bytes) => {
+                Section::MaspTx(borsh("masp_tx", &bytes)?)
+            }
+            ProtoSection::Memo(memo) => Section::Memo(Memo {
+                salt: salt("salt", &memo.salt)?,
+                memo: memo.memo,
+            }),
+        },
+    )
+}
+
+/// The gateway service, which submits txs to the ledger via the RPC of
+/// Tendermint
+pub struct Gateway {
+    client: HttpClient,
+}
+
+#[tonic::async_trait]
+impl TxGateway for Gateway {
+    async fn submit_tx(
+        &self,
+        request: Request<proto::Tx>,
+    ) -> std::result::Result<Response<proto::SubmitTxResponse>, Status> {
+        let tx = decode_tx(request.into_inner())
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        let wrapper_hash = tx.header_hash();
+        let inner_hash = tx.clone().update_header(TxType::Raw).header_hash();
+        let response = self
+            .client
+            .broadcast_tx_sync(tx.to_bytes().into())
+            .await
+            .map_err(|err| Status::unavailable(err.to_string()))?;
+        Ok(Response::new(proto::SubmitTxResponse {
+            wrapper_hash: wrapper_hash.to_string(),
+            inner_hash: inner_hash.to_string(),
+            code: response.code.into(),
+            log: response.log.to_string(),
+        }))
+    }
+
+    async fn query_tx(
+        &self,
+        request: Request<proto::QueryTxRequest>,
+    ) -> std::result::Result<Response<proto::TxResponse>, Status> {
+        let request = request.into_inner();
+        let query = if request.accepted {
+            TxEventQuery::Accepted(&request.hash)
+        } else {
+            TxEventQuery::Applied(&request.hash)
+        };
+        let response = rpc::query_tx_response(&self.client, query)
+            .await
+            .map_err(|err| Status::not_found(err.to_string()))?;
+        Ok(Response::new(proto::TxResponse {
+            info: response.info,
+            log: response.log,
+            height: response.height,
+            hash: response.hash,
+            code: response.code,
+            gas_used: response.gas_used,
+            initialized_accounts: response
+                .initialized_accounts
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            memo: response.memo,
+        }))
+    }
+}
+
+/// Serve the gateway at the given address until an abort signal is
+/// received, submitting txs to the Tendermint RPC at `rpc_address`.
+pub async fn run(
+    address: SocketAddr,
+    rpc_address: &str,
+    abort_recv: tokio::sync::oneshot::Receiver<()>,
+) {
+    let gateway = Gateway {
+        client: HttpClient::new(format!("http://{}", rpc_address).as_str())
+            .unwrap(),
+    };
+    tracing::info!("Starting the tx gateway at {}.", address);
+    let res = tonic::transport::Server::builder()
+        .add_service(TxGatewayServer::new(gateway))
+        .serve_with_shutdown(address, async {
+            let _ = abort_recv.await;
+        })
+        .await;
+    if let Err(err) = res {
+        tracing::error!("The tx gateway unexpectedly shut down: {}", err);
+    }
+    tracing::info!("Shutting down the tx gateway...");
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use namada::types::address::nam;
+    use namada::types::key::RefTo;
+    use namada::types::token::Amount;
+
+    use super::*;
+    use crate::wallet::defaults;
+
+    /// Test that the translation of a protobuf encoded wrapper tx yields
+    /// the same hashes and signatures as the tx it encodes
+    #[test]
+    fn test_decode_tx() {
+        let keypair = defaults::daewon_keypair();
+        let mut tx = Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount: Amount::from(100u64),
+                token: nam(),
+            },
+            &keypair,
+            Epoch(1),
+            GasLimit::from_multiplier(1),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        ))));
+        tx.header.chain_id = ChainId("test-chain".to_string());
+        tx.set_data(Data::new(b"data".to_vec()));
+        tx.set_code(Code::new(b"code".to_vec()));
+        tx.set_memo(Memo::new("deposit".to_string()));
+        let header_hash = tx.header_hash();
+        let mut sig = Signature::from_parts(
+            [7; 8],
+            header_hash,
+            BTreeMap::new(),
+            SignMode::Hash,
+        );
+        sig.sign(&keypair);
+        let signature = sig.signatures[&keypair.ref_to()].clone();
+        tx.add_section(Section::Signature(sig));
+
+        let sections = tx
+            .sections
+            .iter()
+            .map(|section| {
+                let section = match section {
+                    Section::Data(data) => {
+                        proto::section::Section::Data(proto::Data {
+                            salt: data.salt.to_vec(),
+                            compression: proto::Compression::None as i32,
+                            data: data.data.clone(),
+                        })
+                    }
+                    Section::Code(code) => {
+                        proto::section::Section::Code(proto::Code {
+                            salt: code.salt.to_vec(),
+                            compression: proto::Compression::None as i32,
+                            code: Some(proto::code::Code::Id(b"code".to_vec())),
+                        })
+                    }
+                    Section::Memo(memo) => {
+                        proto::section::Section::Memo(proto::Memo {
+                            salt: memo.salt.to_vec(),
+                            memo: memo.memo.clone(),
+                        })
+                    }
+                    Section::Signature(_) => {
+                        proto::section::Section::Signature(proto::Signature {
+                            salt: vec![7; 8],
+                            target: header_hash.0.to_vec(),
+                            signatures: vec![proto::SignerSignature {
+                                pk: keypair.ref_to().to_string(),
+                                signature: signature.try_to_vec().unwrap(),
+                            }],
+                            text: None,
+                            eip191: false,
+                        })
+                    }
+                    _ => unreachable!(),
+                };
+                proto::Section {
+                    section: Some(section),
+                }
+            })
+            .collect();
+        let encoded = proto::Tx {
+            header: Some(proto::Header {
+                version: tx.header.version.into(),
+                chain_id: "test-chain".to_string(),
+                expiration: None,
+                timestamp: tx.header.timestamp.0.to_rfc3339(),
+                code_hash: tx.header.code_hash.0.to_vec(),
+                data_hash: tx.header.data_hash.0.to_vec(),
+                tx_type: Some(proto::header::TxType::Wrapper(
+                    proto::WrapperTx {
+                        fee: Some(proto::Fee {
+                            amount: Amount::from(100u64).to_string(),
+                            token: nam().to_string(),
+                        }),
+                        pk: keypair.ref_to().to_string(),
+                        epoch: 1,
+                        gas_limit: 1,
+                        ..Default::default()
+                    },
+                )),
+                batch: vec![],
+                memo_hash: tx.header.memo_hash.map(|hash| hash.0.to_vec()),
+            }),
+            sections,
+        };
+
+        let decoded = decode_tx(encoded).expect("Test failed");
+        assert_eq!(decoded.header_hash(), header_hash);
+        assert_eq!(decoded.memo(), Some("deposit".to_string()));
+        for (decoded, section) in decoded.sections.iter().zip(&tx.sections) {
+            assert_eq!(decoded.get_hash(), section.get_hash());
+        }
+        decoded
+            .verify_signature(&keypair.ref_to(), &header_hash)
+            .expect("Test failed");
+    }
+
+    /// Test that missing and malformed fields are reported
+    #[test]
+    fn test_decode_invalid_tx() {
+        assert!(matches!(
+            decode_tx(proto::Tx::default()),
+            Err(Error::MissingField("header"))
+        ));
+        let section = proto::Section {
+            section: Some(proto::section::Section::Memo(proto::Memo {
+                salt: vec![0; 7],
+                memo: String::new(),
+            })),
+        };
+        assert!(matches!(
+            decode_section(section),
+            Err(Error::InvalidField("salt", _))
+        ));
+    }
+}
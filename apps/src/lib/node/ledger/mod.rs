@@ -1,5 +1,7 @@
 mod abortable;
 mod broadcaster;
+mod gateway;
+mod remote_signer;
 mod shell;
 mod shims;
 pub mod storage;
@@ -131,7 +133,9 @@ impl Shell {
             }
             Request::Commit(_) => {
                 tracing::debug!("Request Commit");
-                Ok(Response::Commit(self.commit()))
+                let response = self.commit();
+                self.create_snapshot_if_due();
+                Ok(Response::Commit(response))
             }
             Request::Flush(_) => Ok(Response::Flush(Default::default())),
             Request::Echo(msg) => Ok(Response::Echo(response::Echo {
@@ -147,16 +151,18 @@ impl Shell {
                 Ok(Response::CheckTx(self.mempool_validate(&tx.tx, r#type)))
             }
             Request::ListSnapshots(_) => {
-                Ok(Response::ListSnapshots(Default::default()))
+                Ok(Response::ListSnapshots(self.list_snapshots()))
             }
-            Request::OfferSnapshot(_) => {
-                Ok(Response::OfferSnapshot(Default::default()))
+            Request::OfferSnapshot(req) => {
+                tracing::debug!("Request OfferSnapshot");
+                Ok(Response::OfferSnapshot(self.offer_snapshot(req)))
             }
-            Request::LoadSnapshotChunk(_) => {
-                Ok(Response::LoadSnapshotChunk(Default::default()))
+            Request::LoadSnapshotChunk(req) => {
+                Ok(Response::LoadSnapshotChunk(self.load_snapshot_chunk(req)))
             }
-            Request::ApplySnapshotChunk(_) => {
-                Ok(Response::ApplySnapshotChunk(Default::default()))
+            Request::ApplySnapshotChunk(req) => {
+                tracing::debug!("Request ApplySnapshotChunk");
+                Ok(Response::ApplySnapshotChunk(self.apply_snapshot_chunk(req)))
             }
         }
     }
@@ -216,6 +222,8 @@ pub fn dump_db(
 ) {
     use namada::ledger::storage::DB;
 
+    use crate::node::ledger::storage::DBBackend;
+
     let chain_id = config.chain_id;
     let db_path = config.shell.db_dir(&chain_id);
 
@@ -235,6 +243,8 @@ pub fn rollback(config: config::Ledger) -> Result<(), shell::Error> {
 ///   - A shell which contains an ABCI server, for talking to the Tendermint
 ///     node.
 ///   - A [`Broadcaster`], for the ledger to submit txs to Tendermint's mempool.
+///   - Optionally, a gRPC gateway for integrators to submit protobuf encoded
+///     txs.
 ///
 /// All must be alive for correct functioning.
 async fn run_aux(config: config::Ledger, wasm_dir: PathBuf) {
@@ -247,6 +257,9 @@ async fn run_aux(config: config::Ledger, wasm_dir: PathBuf) {
     // Start Tendermint node
     let tendermint_node = start_tendermint(&mut spawner, &config);
 
+    // Start the tx gateway, if enabled
+    let gateway = start_gateway(&mut spawner, &config);
+
     // Start ABCI server and broadcaster (the latter only if we are a validator
     // node)
     let (abci, broadcaster, shell_handler) = start_abci_broadcaster_shell(
@@ -260,10 +273,10 @@ async fn run_aux(config: config::Ledger, wasm_dir: PathBuf) {
     let aborted = spawner.wait_for_abort().await.child_terminated();
 
     // Wait for all managed tasks to finish.
-    let res = tokio::try_join!(tendermint_node, abci, broadcaster);
+    let res = tokio::try_join!(tendermint_node, abci, broadcaster, gateway);
 
     match res {
-        Ok((tendermint_res, abci_res, _)) => {
+        Ok((tendermint_res, abci_res, _, _)) => {
             // we ignore errors on user-initiated shutdown
             if aborted {
                 if let Err(err) = tendermint_res {
@@ -447,8 +460,9 @@ fn start_abci_broadcaster_shell(
     };
 
     // Setup DB cache, it must outlive the DB instance that's in the shell
-    let db_cache =
-        rocksdb::Cache::new_lru_cache(db_block_cache_size_bytes as usize);
+    let db_cache = <storage::PersistentDB as storage::DBBackend>::new_cache(
+        db_block_cache_size_bytes,
+    );
 
     // Construct our ABCI application.
     let tendermint_mode = config.tendermint.tendermint_mode.clone();
@@ -622,6 +636,31 @@ fn start_tendermint(
         })
 }
 
+/// Launches the gRPC tx gateway, if an address to serve it at is configured,
+/// submitting txs to the RPC of the Tendermint node.
+fn start_gateway(
+    spawner: &mut AbortableSpawner,
+    config: &config::Ledger,
+) -> task::JoinHandle<()> {
+    let address = match config.shell.grpc_gateway_address {
+        Some(address) => address,
+        None => return spawn_dummy_task(()),
+    };
+    let rpc_address = config.tendermint.rpc_address.to_string();
+    let (gw_abort_send, gw_abort_recv) = tokio::sync::oneshot::channel::<()>();
+
+    spawner
+        .spawn_abortable("Gateway", move |aborter| async move {
+            gateway::run(address, &rpc_address, gw_abort_recv).await;
+            tracing::info!("Gateway is no longer running.");
+
+            drop(aborter);
+        })
+        .with_cleanup(async move {
+            let _ = gw_abort_send.send(());
+        })
+}
+
 /// Spawn a dummy asynchronous task into the runtime,
 /// which will resolve instantly.
 fn spawn_dummy_task<T: Send + 'static>(ready: T) -> task::JoinHandle<T> {
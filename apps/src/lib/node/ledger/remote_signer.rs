@@ -0,0 +1,106 @@
+//! A client of remote signers, such as tmkms-style signing services keeping
+//! the protocol key of a validator in an HSM or a KMS, through which the
+//! shell signs protocol txs without ever holding the key.
+
+use borsh::BorshDeserialize;
+use namada::types::key::signer::{Signer, SignerError};
+use namada::types::key::{common, ParsePublicKeyError, SigScheme};
+use once_cell::sync::OnceCell;
+use tokio::runtime::Handle;
+use tonic::transport::{Channel, Endpoint};
+
+use self::proto::remote_signer_client::RemoteSignerClient;
+use crate::config;
+
+/// The protobuf messages and service of remote signers
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("signer");
+}
+
+/// A signer delegating to a remote signer over gRPC
+#[derive(Debug)]
+pub struct RemoteSigner {
+    client: RemoteSignerClient<Channel>,
+    key_id: String,
+    /// The public key of the key held by the signer, fetched on first use
+    public_key: OnceCell<common::PublicKey>,
+    /// The runtime driving the connection to the signer
+    handle: Handle,
+}
+
+impl RemoteSigner {
+    /// Make a client of the given remote signer, which only connects to it
+    /// when first used. It must be made from within a Tokio runtime, but
+    /// used outside of it, as signing blocks on the signer's response.
+    pub fn connect_lazy(
+        config: &config::RemoteSigner,
+    ) -> Result<Self, SignerError> {
+        let channel = Endpoint::from_shared(config.address.clone())
+            .map_err(|err| SignerError::Remote(err.to_string()))?
+            .connect_lazy();
+        Ok(Self {
+            client: RemoteSignerClient::new(channel),
+            key_id: config.key_id.clone(),
+            public_key: OnceCell::new(),
+            handle: Handle::current(),
+        })
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> Result<common::PublicKey, SignerError> {
+        self.public_key
+            .get_or_try_init(|| {
+                let mut client = self.client.clone();
+                let request = proto::PublicKeyRequest {
+                    key_id: self.key_id.clone(),
+                };
+                let response = self
+                    .handle
+                    .block_on(client.get_public_key(request))
+                    .map_err(|status| {
+                        SignerError::Remote(status.message().to_string())
+                    })?;
+                response.into_inner().pk.parse().map_err(
+                    |err: ParsePublicKeyError| {
+                        SignerError::Remote(format!(
+                            "Invalid public key: {}",
+                            err
+                        ))
+                    },
+                )
+            })
+            .cloned()
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<common::Signature, SignerError> {
+        let mut client = self.client.clone();
+        let request = proto::SignRequest {
+            key_id: self.key_id.clone(),
+            data: data.to_vec(),
+        };
+        let response =
+            self.handle
+                .block_on(client.sign(request))
+                .map_err(|status| {
+                    SignerError::Remote(status.message().to_string())
+                })?;
+        let signature =
+            common::Signature::try_from_slice(&response.into_inner().signature)
+                .map_err(|err| {
+                    SignerError::Remote(format!("Invalid signature: {}", err))
+                })?;
+        // Check that the signer did sign with the expected key, as the ledger
+        // would otherwise reject the txs it signs
+        common::SigScheme::verify_signature_raw(
+            &self.public_key()?,
+            data,
+            &signature,
+        )
+        .map_err(|err| {
+            SignerError::Remote(format!("Invalid signature: {}", err))
+        })?;
+        Ok(signature)
+    }
+}
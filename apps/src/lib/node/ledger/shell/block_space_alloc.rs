@@ -16,12 +16,33 @@
 //! In the current implementation, we allocate space for transactions
 //! in the following order of preference:
 //!
-//! - First, we allot space for DKG encrypted txs. We allow DKG encrypted txs to
-//!   take up at most 1/3 of the total block space.
+//! - First, we allot space for DKG encrypted txs. By default, we allow DKG
+//!   encrypted txs to take up at most 1/3 of the total block space, though this
+//!   fraction can be changed with a [`BlockSpaceSplit`]. At the same time, we
+//!   may set aside a configurable fraction of the block space for vote
+//!   extension protocol txs, which no other kind of tx can use up. A small
+//!   fraction of the space of encrypted txs makes up a priority lane, for txs
+//!   that shouldn't be starved during fee market congestion. Whatever part of
+//!   the priority lane is left unused is given back to the remaining encrypted
+//!   txs.
 //! - Next, we allot space for DKG decrypted txs. Decrypted txs take up as much
-//!   space as needed. We will see, shortly, why in practice this is fine.
+//!   space as needed, short of the share of the block guaranteed to protocol
+//!   txs by the [`BlockSpaceSplit`]. We will see, shortly, why in practice
+//!   this is fine.
 //! - Finally, we allot space for protocol txs. Protocol txs get half of the
-//!   remaining block space allotted to them.
+//!   remaining block space allotted to them. Vote extension protocol txs that
+//!   don't fit in their reserved space may use this space too.
+//! - Lastly, we rebalance the allocator, such that protocol txs may also use
+//!   up any space left unused by the other bins, e.g. the part of the vote
+//!   extension reservation that wasn't needed.
+//!
+//! Depending on the [`EncryptedTxsSpace`] strategy of a block height,
+//! encrypted txs may also be withheld altogether, in which case the
+//! whole block is left to decrypted and protocol txs.
+//!
+//! On top of block space, we keep track of the cumulative gas declared
+//! by the encrypted txs of a block, which may not exceed the max block
+//! gas chain parameter.
 //!
 //! Since at some fixed height `H` decrypted txs only take up as
 //! much space as the encrypted txs from height `H - 1`, and we
@@ -47,13 +68,12 @@ pub mod states;
 // reserved for decrypted txs, given the invariants of the state
 // machine
 
-// TODO: refactor our measure of space to also reflect gas costs.
-// the total gas of all chosen txs cannot exceed the configured max
-// gas per block, otherwise a proposal will be rejected!
-
 use std::marker::PhantomData;
+#[cfg(feature = "profiling")]
+use std::time::Instant;
 
 use namada::core::ledger::storage::{self, WlStorage};
+use namada::ledger::alloc_log::AllocBin;
 use namada::proof_of_stake::pos_queries::PosQueries;
 
 #[allow(unused_imports)]
@@ -64,13 +84,208 @@ use crate::facade::tendermint_proto::abci::RequestPrepareProposal;
 pub enum AllocFailure {
     /// The transaction can only be included in an upcoming block.
     ///
-    /// We return the space left in the tx bin for logging purposes.
-    Rejected { bin_space_left: u64 },
+    /// We return the space needed by the tx, as well as the space
+    /// left in the tx bin, for logging purposes.
+    Rejected { needed: u64, available: u64 },
     /// The transaction would overflow the allotted bin space,
     /// therefore it needs to be handled separately.
     ///
     /// We return the size of the tx bin for logging purposes.
     OverflowsBin { bin_size: u64 },
+    /// The gas declared by the transaction exceeds the gas left
+    /// in the block.
+    ///
+    /// We return the gas left in the block for logging purposes.
+    OverflowsBlockGas { gas_left: u64 },
+    /// The transaction is larger than the max size of a single
+    /// transaction, regardless of the space left in its bin.
+    ///
+    /// We return the max size of a transaction for logging purposes.
+    OverMaxTxSize { max_tx_bytes: u64 },
+}
+
+/// Kinds of block space that may be set aside, ahead of time,
+/// by the proposer of a block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SpaceReserve {
+    /// Space reserved for protocol transactions.
+    Protocol,
+    /// Space reserved for evidence of validator misbehavior.
+    Evidence,
+    /// Space reserved as the byte equivalent of some gas budget.
+    GasEquivalent,
+}
+
+/// Block space set aside for each kind of [`SpaceReserve`],
+/// measured in bytes.
+#[derive(Debug, Copy, Clone, Default)]
+struct SpaceReserves {
+    /// Space reserved for protocol transactions.
+    protocol: u64,
+    /// Space reserved for evidence of validator misbehavior.
+    evidence: u64,
+    /// Space reserved as the byte equivalent of some gas budget.
+    gas_equivalent: u64,
+}
+
+impl SpaceReserves {
+    /// Return the total space set aside by all reserves.
+    #[inline]
+    fn total(&self) -> u64 {
+        self.protocol
+            .saturating_add(self.evidence)
+            .saturating_add(self.gas_equivalent)
+    }
+}
+
+/// Split of the block space between each major kind of tx.
+///
+/// The split is expressed in percentages of the block space, and
+/// can be changed by governance.
+/// Decrypted txs get whatever space is not taken up by encrypted
+/// txs nor guaranteed to protocol txs, so their percentage merely
+/// ensures that they always fit the encrypted txs of the previous
+/// height.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BlockSpaceSplit {
+    /// Fraction of the block space allotted to DKG encrypted txs.
+    encrypted: threshold::Threshold,
+    /// Fraction of the block space guaranteed to protocol txs.
+    protocol: threshold::Threshold,
+}
+
+impl Default for BlockSpaceSplit {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            encrypted: threshold::ONE_THIRD,
+            protocol: threshold::Threshold::from_percentage(0),
+        }
+    }
+}
+
+impl BlockSpaceSplit {
+    /// Validate and build a new [`BlockSpaceSplit`] from the percentages
+    /// of the block space allotted to encrypted, decrypted and protocol
+    /// txs.
+    pub fn from_percentages(
+        encrypted: u64,
+        decrypted: u64,
+        protocol: u64,
+    ) -> Result<Self, String> {
+        let total = encrypted
+            .checked_add(decrypted)
+            .and_then(|total| total.checked_add(protocol));
+        if total != Some(100) {
+            return Err(format!(
+                "The block space split percentages must add up to 100, but \
+                 got {encrypted}% encrypted, {decrypted}% decrypted and \
+                 {protocol}% protocol txs"
+            ));
+        }
+        if encrypted > decrypted {
+            return Err(format!(
+                "The block space allotted to decrypted txs ({decrypted}%) \
+                 must be at least as large as that of encrypted txs \
+                 ({encrypted}%)"
+            ));
+        }
+        Ok(Self {
+            encrypted: threshold::Threshold::from_percentage(encrypted),
+            protocol: threshold::Threshold::from_percentage(protocol),
+        })
+    }
+
+    /// Read the [`BlockSpaceSplit`] from the chain parameters, falling
+    /// back to the default split if governance hasn't set one.
+    ///
+    /// Proposers and validators must both read the split from here,
+    /// such that they agree on the space allotted to each kind of tx.
+    /// Invalid splits in storage are logged and ignored.
+    pub fn from_storage<D, H>(storage: &WlStorage<D, H>) -> Self
+    where
+        D: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: 'static + storage::StorageHasher,
+    {
+        let split =
+            namada::ledger::parameters::read_block_space_split_parameter(
+                storage,
+            )
+            .expect("Must be able to read block space split parameter");
+        let (encrypted, decrypted, protocol) = match split {
+            Some(split) => split,
+            None => return Self::default(),
+        };
+        Self::from_percentages(encrypted, decrypted, protocol)
+            .map_err(|err| {
+                tracing::error!(
+                    "Ignoring the block space split set by governance: {err}"
+                );
+            })
+            .unwrap_or_default()
+    }
+
+    /// Return the fraction of the block space allotted to DKG
+    /// encrypted txs.
+    #[inline]
+    pub fn encrypted(&self) -> threshold::Threshold {
+        self.encrypted
+    }
+
+    /// Return the fraction of the block space guaranteed to
+    /// protocol txs.
+    #[inline]
+    pub fn protocol(&self) -> threshold::Threshold {
+        self.protocol
+    }
+}
+
+/// Strategy used to allot block space to DKG encrypted txs, at the
+/// block height currently being decided.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EncryptedTxsSpace {
+    /// DKG encrypted txs are allotted their share of the block space,
+    /// as set by the [`BlockSpaceSplit`], such that the remaining space
+    /// can hold their decrypted counterparts at the next height.
+    Shared,
+    /// DKG encrypted txs are not allotted any block space, which is
+    /// entirely dedicated to decrypted and protocol txs.
+    ///
+    /// This is the case at the second and third block height offsets
+    /// within an epoch, and at every height of chains where governance
+    /// disabled encrypted txs, e.g. because they don't run Ferveo.
+    Withheld,
+}
+
+impl EncryptedTxsSpace {
+    /// Choose the [`EncryptedTxsSpace`] strategy of the block height
+    /// currently being decided, from the protocol state.
+    pub fn from_storage<D, H>(storage: &WlStorage<D, H>) -> Self
+    where
+        D: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: 'static + storage::StorageHasher,
+    {
+        let enabled =
+            namada::ledger::parameters::read_encrypted_txs_enabled_parameter(
+                storage,
+            )
+            .expect("Must be able to read encrypted txs enabled parameter")
+            .unwrap_or(true);
+        let pos_queries = storage.pos_queries();
+        let is_2nd_height_off = pos_queries.is_deciding_offset_within_epoch(1);
+        let is_3rd_height_off = pos_queries.is_deciding_offset_within_epoch(2);
+        if !enabled || is_2nd_height_off || is_3rd_height_off {
+            Self::Withheld
+        } else {
+            Self::Shared
+        }
+    }
+
+    /// Check if DKG encrypted txs may be included in a block.
+    #[inline]
+    pub fn allows_encrypted_txs(&self) -> bool {
+        matches!(self, Self::Shared)
+    }
 }
 
 /// Allotted space for a batch of transactions in some proposed block,
@@ -81,6 +296,7 @@ pub enum AllocFailure {
 ///   - DKG encrypted transactions.
 ///   - DKG decrypted transactions.
 ///   - Protocol transactions.
+///   - Vote extension protocol transactions.
 #[derive(Debug, Default)]
 pub struct BlockSpaceAllocator<State> {
     /// The current state of the [`BlockSpaceAllocator`] state machine.
@@ -92,8 +308,46 @@ pub struct BlockSpaceAllocator<State> {
     protocol_txs: TxBin,
     /// The current space utilized by DKG encrypted transactions.
     encrypted_txs: TxBin,
+    /// The space reserved for, and utilized by, priority DKG encrypted
+    /// transactions, carved out of the space of encrypted transactions.
+    priority_txs: TxBin,
     /// The current space utilized by DKG decrypted transactions.
     decrypted_txs: TxBin,
+    /// The cumulative gas declared by DKG encrypted transactions.
+    encrypted_gas: GasBin,
+    /// The space reserved for, and utilized by, vote extension
+    /// protocol transactions.
+    vote_extension_txs: TxBin,
+    /// The space guaranteed to protocol transactions, which DKG
+    /// decrypted transactions may not use up.
+    protocol_share: u64,
+    /// Block space set aside by the proposer.
+    reserves: SpaceReserves,
+    /// Points in time at which transactions were successfully
+    /// allocated, along with the total space utilized at that time.
+    #[cfg(feature = "profiling")]
+    fill_timeline: Vec<(Instant, u64)>,
+}
+
+// NOTE: we can't derive `Clone`, since that would require the
+// (uninhabited) `State` type to also implement `Clone`
+impl<State> Clone for BlockSpaceAllocator<State> {
+    fn clone(&self) -> Self {
+        Self {
+            _state: PhantomData,
+            block: self.block,
+            protocol_txs: self.protocol_txs,
+            encrypted_txs: self.encrypted_txs,
+            priority_txs: self.priority_txs,
+            decrypted_txs: self.decrypted_txs,
+            encrypted_gas: self.encrypted_gas,
+            vote_extension_txs: self.vote_extension_txs,
+            protocol_share: self.protocol_share,
+            reserves: self.reserves,
+            #[cfg(feature = "profiling")]
+            fill_timeline: self.fill_timeline.clone(),
+        }
+    }
 }
 
 impl<D, H, M> From<&WlStorage<D, H>>
@@ -104,27 +358,134 @@ where
 {
     #[inline]
     fn from(storage: &WlStorage<D, H>) -> Self {
-        Self::init(storage.pos_queries().get_max_proposal_bytes().get())
+        Self::from_storage(storage)
     }
 }
 
 impl<M> BlockSpaceAllocator<states::BuildingEncryptedTxBatch<M>> {
+    /// Construct a new [`BlockSpaceAllocator`] from the parameters
+    /// in storage.
+    ///
+    /// The block space is divided according to the [`BlockSpaceSplit`]
+    /// of the chain parameters.
+    pub fn from_storage<D, H>(storage: &WlStorage<D, H>) -> Self
+    where
+        D: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: 'static + storage::StorageHasher,
+    {
+        let split = BlockSpaceSplit::from_storage(storage);
+        let max_block_gas =
+            namada::ledger::parameters::read_max_block_gas_parameter(storage)
+                .expect("Must be able to read max block gas parameter");
+        let max_tx_bytes =
+            namada::ledger::parameters::read_max_tx_bytes_parameter(storage)
+                .expect("Must be able to read max tx bytes parameter");
+        Self::init_with_split(
+            storage.pos_queries().get_max_proposal_bytes().get(),
+            split,
+        )
+        .with_max_block_gas(max_block_gas)
+        .with_max_tx_bytes(max_tx_bytes)
+    }
+
     /// Construct a new [`BlockSpaceAllocator`], with an upper bound
     /// on the max size of all txs in a block defined by Tendermint.
     #[inline]
     pub fn init(tendermint_max_block_space_in_bytes: u64) -> Self {
+        Self::init_with_split(
+            tendermint_max_block_space_in_bytes,
+            BlockSpaceSplit::default(),
+        )
+    }
+
+    /// Construct a new [`BlockSpaceAllocator`], with an upper bound
+    /// on the max size of all txs in a block defined by Tendermint,
+    /// dividing the block space according to `split`.
+    #[inline]
+    pub fn init_with_split(
+        tendermint_max_block_space_in_bytes: u64,
+        split: BlockSpaceSplit,
+    ) -> Self {
         let max = tendermint_max_block_space_in_bytes;
         Self {
             _state: PhantomData,
             block: TxBin::init(max),
             protocol_txs: TxBin::default(),
-            encrypted_txs: TxBin::init_over_ratio(max, threshold::ONE_THIRD),
+            encrypted_txs: TxBin::init_over_ratio(max, split.encrypted),
+            priority_txs: TxBin::default(),
             decrypted_txs: TxBin::default(),
+            encrypted_gas: GasBin::init(u64::MAX),
+            vote_extension_txs: TxBin::default(),
+            protocol_share: split.protocol.over(max),
+            reserves: SpaceReserves::default(),
+            #[cfg(feature = "profiling")]
+            fill_timeline: Vec::new(),
         }
     }
+
+    /// Bound the cumulative gas declared by the encrypted txs of the
+    /// block to `max_block_gas`.
+    #[inline]
+    pub fn with_max_block_gas(mut self, max_block_gas: u64) -> Self {
+        self.encrypted_gas = GasBin::init(max_block_gas);
+        self
+    }
+
+    /// Bound the size of each of the encrypted txs of the block to
+    /// `max_tx_bytes`.
+    #[inline]
+    pub fn with_max_tx_bytes(mut self, max_tx_bytes: u64) -> Self {
+        self.encrypted_txs.set_max_tx_bytes(max_tx_bytes);
+        self.priority_txs.set_max_tx_bytes(max_tx_bytes);
+        self
+    }
+
+    /// Reserve the fraction `frac` of the space of encrypted txs for
+    /// priority encrypted txs, replacing any previous reservation.
+    #[inline]
+    pub fn with_priority_space(mut self, frac: threshold::Threshold) -> Self {
+        let encrypted_space = self.encrypted_txs.allotted_space_in_bytes
+            + self.priority_txs.allotted_space_in_bytes;
+        let reserved = frac.over(encrypted_space);
+        self.priority_txs.allotted_space_in_bytes = reserved;
+        self.encrypted_txs.allotted_space_in_bytes = encrypted_space - reserved;
+        self
+    }
+
+    /// Reserve the fraction `frac` of the block space for vote extension
+    /// protocol txs, such that large batches of decrypted txs can never
+    /// crowd them out of a block.
+    ///
+    /// The reservation is capped at the space not allotted to encrypted
+    /// txs, and replaces any previous reservation.
+    #[inline]
+    pub fn with_vote_extension_space(
+        mut self,
+        frac: threshold::Threshold,
+    ) -> Self {
+        self.vote_extension_txs = TxBin::default();
+        let reserved = frac
+            .over(self.block.allotted_space_in_bytes)
+            .min(self.uninitialized_space_in_bytes());
+        self.vote_extension_txs = TxBin::init(reserved);
+        self
+    }
 }
 
 impl<State> BlockSpaceAllocator<State> {
+    /// Fork this [`BlockSpaceAllocator`], at its current state.
+    ///
+    /// The returned allocator is a deep copy of the original one,
+    /// preserving the space allotted to and utilized by each of
+    /// its [`TxBin`] instances. Filling up the fork has no effect
+    /// on the original allocator, which makes it possible to
+    /// compare different block proposal strategies over the same
+    /// starting state.
+    #[inline]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
     /// Return the amount of space left to initialize in all
     /// [`TxBin`] instances.
     ///
@@ -135,9 +496,164 @@ impl<State> BlockSpaceAllocator<State> {
     fn uninitialized_space_in_bytes(&self) -> u64 {
         let total_bin_space = self.protocol_txs.allotted_space_in_bytes
             + self.encrypted_txs.allotted_space_in_bytes
-            + self.decrypted_txs.allotted_space_in_bytes;
+            + self.priority_txs.allotted_space_in_bytes
+            + self.decrypted_txs.allotted_space_in_bytes
+            + self.vote_extension_txs.allotted_space_in_bytes;
         self.block.allotted_space_in_bytes - total_bin_space
     }
+
+    /// Return the space currently utilized by DKG encrypted txs.
+    #[inline]
+    pub fn encrypted_used(&self) -> u64 {
+        self.encrypted_txs.occupied_space_in_bytes
+    }
+
+    /// Return the space currently utilized by priority DKG encrypted
+    /// txs, within the space reserved for them.
+    #[inline]
+    pub fn priority_used(&self) -> u64 {
+        self.priority_txs.occupied_space_in_bytes
+    }
+
+    /// Return the gas currently declared by DKG encrypted txs.
+    #[inline]
+    pub fn encrypted_gas_used(&self) -> u64 {
+        self.encrypted_gas.used_gas
+    }
+
+    /// Return the space currently utilized by DKG decrypted txs.
+    #[inline]
+    pub fn decrypted_used(&self) -> u64 {
+        self.decrypted_txs.occupied_space_in_bytes
+    }
+
+    /// Return the space currently utilized by protocol txs.
+    #[inline]
+    pub fn protocol_used(&self) -> u64 {
+        self.protocol_txs.occupied_space_in_bytes
+    }
+
+    /// Return the space currently utilized by vote extension protocol
+    /// txs, within the space reserved for them.
+    #[inline]
+    pub fn vote_extension_used(&self) -> u64 {
+        self.vote_extension_txs.occupied_space_in_bytes
+    }
+
+    /// Return the space left in the given [`AllocBin`], as
+    /// recorded in the allocator replay log.
+    #[inline]
+    pub fn space_left_in(&self, bin: AllocBin) -> u64 {
+        match bin {
+            AllocBin::Block => self.free_space_in_bytes(),
+            AllocBin::Encrypted => self.encrypted_txs.space_left_in_bytes(),
+            AllocBin::Priority => self.priority_txs.space_left_in_bytes(),
+            AllocBin::Decrypted => self.decrypted_txs.space_left_in_bytes(),
+            AllocBin::Protocol => self.protocol_txs.space_left_in_bytes(),
+        }
+    }
+
+    /// Set aside `bytes` worth of block space for the given
+    /// kind of [`SpaceReserve`], replacing any previous
+    /// reservation of the same kind.
+    #[inline]
+    pub fn reserve_space(&mut self, reserve: SpaceReserve, bytes: u64) {
+        let reserved = match reserve {
+            SpaceReserve::Protocol => &mut self.reserves.protocol,
+            SpaceReserve::Evidence => &mut self.reserves.evidence,
+            SpaceReserve::GasEquivalent => &mut self.reserves.gas_equivalent,
+        };
+        *reserved = bytes;
+    }
+
+    /// Return the amount of block space that is still free,
+    /// once all active [`SpaceReserve`] instances have been
+    /// accounted for.
+    ///
+    /// This is the amount of space proposers should rely on
+    /// when deciding whether to include more transactions.
+    #[inline]
+    pub fn effective_free_space(&self) -> u64 {
+        self.free_space_in_bytes()
+            .saturating_sub(self.reserves.total())
+    }
+
+    /// Return the amount of block space not utilized by any
+    /// [`TxBin`] instance.
+    #[inline]
+    fn free_space_in_bytes(&self) -> u64 {
+        self.block
+            .allotted_space_in_bytes
+            .saturating_sub(self.occupied_space_in_bytes())
+    }
+
+    /// Cast this [`BlockSpaceAllocator`] to some other state,
+    /// preserving the state of all of its [`TxBin`] instances.
+    #[inline]
+    fn cast_state<Next>(self) -> BlockSpaceAllocator<Next> {
+        let Self {
+            block,
+            protocol_txs,
+            encrypted_txs,
+            priority_txs,
+            decrypted_txs,
+            encrypted_gas,
+            vote_extension_txs,
+            protocol_share,
+            reserves,
+            #[cfg(feature = "profiling")]
+            fill_timeline,
+            ..
+        } = self;
+
+        BlockSpaceAllocator {
+            _state: PhantomData,
+            block,
+            protocol_txs,
+            encrypted_txs,
+            priority_txs,
+            decrypted_txs,
+            encrypted_gas,
+            vote_extension_txs,
+            protocol_share,
+            reserves,
+            #[cfg(feature = "profiling")]
+            fill_timeline,
+        }
+    }
+
+    /// Return the amount of space currently utilized by all
+    /// [`TxBin`] instances.
+    #[inline]
+    fn occupied_space_in_bytes(&self) -> u64 {
+        self.protocol_txs.occupied_space_in_bytes
+            + self.encrypted_txs.occupied_space_in_bytes
+            + self.priority_txs.occupied_space_in_bytes
+            + self.decrypted_txs.occupied_space_in_bytes
+            + self.vote_extension_txs.occupied_space_in_bytes
+    }
+
+    /// Record a new point in the fill timeline of this
+    /// [`BlockSpaceAllocator`], following a successful allocation.
+    ///
+    /// This is a no-op unless the `profiling` feature is enabled.
+    #[inline]
+    fn record_fill(&mut self) {
+        #[cfg(feature = "profiling")]
+        {
+            let used_bytes = self.occupied_space_in_bytes();
+            self.fill_timeline.push((Instant::now(), used_bytes));
+        }
+    }
+
+    /// Return the points in time at which transactions were
+    /// successfully allocated, along with the total space
+    /// utilized at each of these points.
+    #[cfg(feature = "profiling")]
+    #[inline]
+    pub fn fill_timeline(&self) -> &[(Instant, u64)] {
+        &self.fill_timeline
+    }
 }
 
 /// Allotted space for a batch of transactions of the same kind in some
@@ -148,6 +664,8 @@ pub struct TxBin {
     occupied_space_in_bytes: u64,
     /// The maximum space the batch of transactions may occupy.
     allotted_space_in_bytes: u64,
+    /// The maximum size of a single transaction of the batch, if any.
+    max_tx_bytes: Option<u64>,
 }
 
 impl TxBin {
@@ -159,6 +677,7 @@ impl TxBin {
         Self {
             allotted_space_in_bytes,
             occupied_space_in_bytes: 0,
+            max_tx_bytes: None,
         }
     }
 
@@ -174,9 +693,17 @@ impl TxBin {
         Self {
             allotted_space_in_bytes: max_bytes,
             occupied_space_in_bytes: 0,
+            max_tx_bytes: None,
         }
     }
 
+    /// Bound the size of each transaction dumped into this [`TxBin`]
+    /// to `max_tx_bytes`.
+    #[inline]
+    pub fn set_max_tx_bytes(&mut self, max_tx_bytes: u64) {
+        self.max_tx_bytes = Some(max_tx_bytes);
+    }
+
     /// Shrink the allotted space of this [`TxBin`] to whatever
     /// space is currently being utilized.
     #[inline]
@@ -187,9 +714,14 @@ impl TxBin {
     /// Try to dump a new transaction into this [`TxBin`].
     ///
     /// Signal the caller if the tx is larger than its max
-    /// allotted bin space.
+    /// allotted bin space, or than the max size of a tx.
     pub fn try_dump(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
         let tx_len = tx.len() as u64;
+        if let Some(max_tx_bytes) = self.max_tx_bytes {
+            if tx_len > max_tx_bytes {
+                return Err(AllocFailure::OverMaxTxSize { max_tx_bytes });
+            }
+        }
         if tx_len > self.allotted_space_in_bytes {
             let bin_size = self.allotted_space_in_bytes;
             return Err(AllocFailure::OverflowsBin { bin_size });
@@ -199,10 +731,59 @@ impl TxBin {
             self.occupied_space_in_bytes = occupied;
             Ok(())
         } else {
-            let bin_space_left = self.space_left_in_bytes();
-            Err(AllocFailure::Rejected { bin_space_left })
+            Err(AllocFailure::Rejected {
+                needed: tx_len,
+                available: self.space_left_in_bytes(),
+            })
+        }
+    }
+}
+
+/// Gas budget for a batch of transactions in some proposed block.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct GasBin {
+    /// The cumulative gas declared by the batch of transactions.
+    used_gas: u64,
+    /// The maximum gas the batch of transactions may declare.
+    allotted_gas: u64,
+}
+
+impl GasBin {
+    /// Construct a new [`GasBin`], with a capacity of `max_gas`.
+    #[inline]
+    pub fn init(max_gas: u64) -> Self {
+        Self {
+            used_gas: 0,
+            allotted_gas: max_gas,
+        }
+    }
+
+    /// Return the amount of gas left in this [`GasBin`].
+    #[inline]
+    pub fn gas_left(&self) -> u64 {
+        self.allotted_gas - self.used_gas
+    }
+
+    /// Check if `gas` fits in this [`GasBin`], without
+    /// dumping it.
+    #[inline]
+    pub fn check(&self, gas: u64) -> Result<(), AllocFailure> {
+        let gas_left = self.gas_left();
+        if gas <= gas_left {
+            Ok(())
+        } else {
+            Err(AllocFailure::OverflowsBlockGas { gas_left })
         }
     }
+
+    /// Try to dump the gas declared by a new transaction
+    /// into this [`GasBin`].
+    #[inline]
+    pub fn try_dump(&mut self, gas: u64) -> Result<(), AllocFailure> {
+        self.check(gas)?;
+        self.used_gas += gas;
+        Ok(())
+    }
 }
 
 pub mod threshold {
@@ -211,7 +792,7 @@ pub mod threshold {
     use num_rational::Ratio;
 
     /// Threshold over a portion of block space.
-    #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
     pub struct Threshold(Ratio<u64>);
 
     impl Threshold {
@@ -222,6 +803,11 @@ pub mod threshold {
             Self(Ratio::new_raw(numer, denom))
         }
 
+        /// Return a new [`Threshold`] of `pct` percent, capped at 100.
+        pub const fn from_percentage(pct: u64) -> Self {
+            Self::new(pct, 100)
+        }
+
         /// Return a [`Threshold`] over some free space.
         pub fn over(self, free_space_in_bytes: u64) -> u64 {
             (self.0 * free_space_in_bytes).to_integer()
@@ -230,6 +816,9 @@ pub mod threshold {
 
     /// Divide free space in three.
     pub const ONE_THIRD: Threshold = Threshold::new(1, 3);
+
+    /// Divide free space in ten.
+    pub const ONE_TENTH: Threshold = Threshold::new(1, 10);
 }
 
 #[cfg(test)]
@@ -237,11 +826,14 @@ mod tests {
     use std::cell::RefCell;
 
     use assert_matches::assert_matches;
+    use namada::core::ledger::storage::testing::TestWlStorage;
+    use namada::ledger::parameters::update_block_space_split_parameter;
     use proptest::prelude::*;
 
     use super::states::{
-        BuildingEncryptedTxBatch, NextState, TryAlloc, WithEncryptedTxs,
-        WithoutEncryptedTxs,
+        AllocatorPhase, BuildingEncryptedTxBatch, NextState, TransitionInfo,
+        TryAlloc, TryAllocPriority, TryAllocVoteExtension, TryAllocWithGas,
+        TryNextState, WithEncryptedTxs, WithoutEncryptedTxs,
     };
     use super::*;
     use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
@@ -313,6 +905,337 @@ mod tests {
         );
     }
 
+    /// Check that the fill timeline records a point for each
+    /// successful allocation.
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn test_fill_timeline_records_successful_allocs() {
+        let mut alloc = BsaWrapperTxs::init(60);
+
+        assert!(alloc.try_alloc(&[0; 10]).is_ok());
+        assert!(alloc.try_alloc(&[0; 10]).is_ok());
+        // this tx overflows the encrypted txs bin
+        assert!(alloc.try_alloc(&[0; 30]).is_err());
+
+        let mut alloc = alloc.next_state();
+        assert!(alloc.try_alloc(&[0; 5]).is_ok());
+
+        let timeline = alloc.fill_timeline();
+        assert_eq!(timeline.len(), 3);
+        let used: Vec<_> = timeline.iter().map(|(_, used)| *used).collect();
+        assert_eq!(used, vec![10, 20, 25]);
+    }
+
+    /// Check that filling up a forked allocator doesn't affect
+    /// the original allocator.
+    #[test]
+    fn test_fork_is_independent_of_original() {
+        let mut alloc = BsaWrapperTxs::init(60);
+        assert!(alloc.try_alloc(&[0; 5]).is_ok());
+
+        let mut fork = alloc.fork();
+        assert_eq!(fork.encrypted_txs.occupied_space_in_bytes, 5);
+        assert!(fork.try_alloc(&[0; 15]).is_ok());
+
+        assert_eq!(fork.encrypted_txs.occupied_space_in_bytes, 20);
+        assert_eq!(alloc.encrypted_txs.occupied_space_in_bytes, 5);
+        assert_eq!(
+            alloc.encrypted_txs.space_left_in_bytes(),
+            BsaWrapperTxs::init(60).encrypted_txs.space_left_in_bytes() - 5
+        );
+    }
+
+    /// Check that the per-bin usage getters report the space
+    /// utilized by each kind of tx.
+    #[test]
+    fn test_per_bin_usage() {
+        let mut alloc = BsaWrapperTxs::init(60);
+        assert!(alloc.try_alloc(&[0; 7]).is_ok());
+        assert!(alloc.try_alloc(&[0; 4]).is_ok());
+        assert_eq!(alloc.encrypted_used(), 11);
+        assert_eq!(alloc.decrypted_used(), 0);
+        assert_eq!(alloc.protocol_used(), 0);
+
+        let mut alloc = alloc.next_state();
+        assert!(alloc.try_alloc(&[0; 3]).is_ok());
+        let mut alloc = alloc.next_state();
+        assert!(alloc.try_alloc(&[0; 2]).is_ok());
+        assert_eq!(alloc.encrypted_used(), 11);
+        assert_eq!(alloc.decrypted_used(), 3);
+        assert_eq!(alloc.protocol_used(), 2);
+    }
+
+    /// Check that only the protocol txs batch allocator reports
+    /// being in the protocol phase.
+    #[test]
+    fn test_is_protocol_phase() {
+        let alloc = BsaWrapperTxs::init(60);
+        assert!(!alloc.is_protocol_phase());
+        let alloc = alloc.next_state();
+        assert!(!alloc.is_protocol_phase());
+        let alloc = alloc.next_state();
+        assert!(alloc.is_protocol_phase());
+    }
+
+    /// Check that we can find out why a state transition produced
+    /// a tiny protocol txs bin.
+    #[test]
+    fn test_transition_info_on_tiny_protocol_bin() {
+        const BLOCK_SIZE: u64 = 60;
+
+        let mut alloc = BsaWrapperTxs::init(BLOCK_SIZE);
+        assert!(alloc.try_alloc(&[0; 18]).is_ok());
+
+        let (mut alloc, info) = alloc.try_next_state();
+        assert_eq!(
+            info,
+            TransitionInfo {
+                prior_free: BLOCK_SIZE - 18,
+                new_bin_size: BLOCK_SIZE - 18,
+            }
+        );
+
+        // almost fill up the block with decrypted txs
+        assert!(alloc.try_alloc(&[0; 40]).is_ok());
+
+        let (alloc, info) = alloc.try_next_state();
+        assert_eq!(
+            info,
+            TransitionInfo {
+                prior_free: 2,
+                new_bin_size: 2,
+            }
+        );
+        assert_eq!(alloc.protocol_txs.allotted_space_in_bytes, 2);
+    }
+
+    /// Check that decrypted txs can't use up the space reserved for
+    /// vote extension protocol txs, and that vote extensions spill
+    /// over into the space of the remaining protocol txs.
+    #[test]
+    fn test_vote_extension_space_is_reserved() {
+        const BLOCK_SIZE: u64 = 60;
+
+        let mut alloc = BsaWrapperTxs::init(BLOCK_SIZE)
+            .with_vote_extension_space(threshold::ONE_TENTH);
+        assert_eq!(alloc.vote_extension_txs.allotted_space_in_bytes, 6);
+        assert!(alloc.try_alloc(&[0; 18]).is_ok());
+
+        // decrypted txs can't take the reserved space
+        let mut alloc = alloc.next_state();
+        assert_eq!(
+            alloc.decrypted_txs.allotted_space_in_bytes,
+            BLOCK_SIZE - 18 - 6
+        );
+        assert!(alloc.try_alloc(&[0; 36]).is_ok());
+        assert_matches!(
+            alloc.try_alloc(&[0; 1]),
+            Err(AllocFailure::Rejected { .. })
+        );
+
+        // the reserved space is still available for vote extensions
+        let mut alloc = alloc.next_state();
+        assert_eq!(alloc.protocol_txs.allotted_space_in_bytes, 0);
+        assert!(alloc.try_alloc_vote_extension(&[0; 6]).is_ok());
+        assert_eq!(alloc.vote_extension_used(), 6);
+        assert!(alloc.try_alloc_vote_extension(&[0; 1]).is_err());
+
+        // vote extensions that don't fit in the reserved space use the
+        // space of the remaining protocol txs
+        let mut alloc = BsaWrapperTxs::init(BLOCK_SIZE)
+            .with_vote_extension_space(threshold::ONE_TENTH)
+            .next_state()
+            .next_state();
+        assert!(alloc.try_alloc_vote_extension(&[0; 4]).is_ok());
+        assert!(alloc.try_alloc_vote_extension(&[0; 4]).is_ok());
+        assert_eq!(alloc.vote_extension_used(), 4);
+        assert_eq!(alloc.protocol_used(), 4);
+    }
+
+    /// Check that encrypted txs are rejected once the gas they declare
+    /// exceeds the max block gas, without using up any block space.
+    #[test]
+    fn test_encrypted_txs_block_gas() {
+        let mut alloc = BsaWrapperTxs::init(60).with_max_block_gas(10);
+        assert!(alloc.try_alloc_with_gas(&[0; 5], 6).is_ok());
+        assert_matches!(
+            alloc.try_alloc_with_gas(&[0; 5], 5),
+            Err(AllocFailure::OverflowsBlockGas { gas_left: 4 })
+        );
+        assert_eq!(alloc.encrypted_gas_used(), 6);
+        assert_eq!(alloc.encrypted_txs.occupied_space_in_bytes, 5);
+        assert!(alloc.try_alloc_with_gas(&[0; 5], 4).is_ok());
+        assert_eq!(alloc.encrypted_gas_used(), 10);
+    }
+
+    /// Check that rebalancing the allocator hands the space left unused
+    /// by the other bins over to protocol txs.
+    #[test]
+    fn test_rebalance_into_protocol_txs() {
+        const BLOCK_SIZE: u64 = 60;
+
+        let mut alloc = BsaWrapperTxs::init(BLOCK_SIZE)
+            .with_vote_extension_space(threshold::ONE_TENTH);
+        assert!(alloc.try_alloc(&[0; 10]).is_ok());
+        let mut alloc = alloc.next_state();
+        assert!(alloc.try_alloc(&[0; 10]).is_ok());
+
+        // the protocol bin excludes the vote extension reservation
+        let mut alloc = alloc.next_state();
+        assert_eq!(alloc.protocol_txs.allotted_space_in_bytes, 34);
+        assert!(alloc.try_alloc_vote_extension(&[0; 2]).is_ok());
+        assert!(alloc.try_alloc(&[0; 30]).is_ok());
+
+        // the unused vote extension reservation is released
+        let (mut alloc, info) = alloc.try_next_state();
+        assert_eq!(
+            info,
+            TransitionInfo {
+                prior_free: 8,
+                new_bin_size: 38,
+            }
+        );
+        assert!(alloc.is_protocol_phase());
+        assert!(alloc.try_alloc(&[0; 8]).is_ok());
+        assert_eq!(alloc.free_space_in_bytes(), 0);
+        assert_matches!(
+            alloc.try_alloc(&[0; 1]),
+            Err(AllocFailure::Rejected { .. })
+        );
+    }
+
+    /// Check that priority encrypted txs are allocated in their own lane,
+    /// and that the unused space of the lane is released to the remaining
+    /// encrypted txs.
+    #[test]
+    fn test_priority_lane() {
+        const BLOCK_SIZE: u64 = 300;
+
+        let mut alloc = BsaWrapperTxs::init(BLOCK_SIZE)
+            .with_priority_space(threshold::ONE_TENTH);
+        assert_eq!(alloc.priority_txs.allotted_space_in_bytes, 10);
+        assert_eq!(alloc.encrypted_txs.allotted_space_in_bytes, 90);
+
+        // priority txs don't spill over into the space of other txs
+        assert!(alloc.try_alloc_priority(&[0; 4], 1).is_ok());
+        assert_matches!(
+            alloc.try_alloc_priority(&[0; 7], 1),
+            Err(AllocFailure::Rejected {
+                needed: 7,
+                available: 6
+            })
+        );
+        assert_eq!(alloc.priority_used(), 4);
+        assert_eq!(alloc.encrypted_gas_used(), 1);
+
+        // the unused space of the lane is given back
+        alloc.release_priority_space();
+        assert_eq!(alloc.priority_txs.allotted_space_in_bytes, 4);
+        assert_eq!(alloc.encrypted_txs.allotted_space_in_bytes, 96);
+        assert!(alloc.try_alloc(&[0; 96]).is_ok());
+        assert_eq!(alloc.uninitialized_space_in_bytes(), 200);
+    }
+
+    /// Check that encrypted txs larger than the max tx size are rejected,
+    /// even if they fit in their bin.
+    #[test]
+    fn test_encrypted_txs_max_tx_bytes() {
+        let mut alloc = BsaWrapperTxs::init(60).with_max_tx_bytes(5);
+        assert!(alloc.try_alloc(&[0; 5]).is_ok());
+        assert_matches!(
+            alloc.try_alloc(&[0; 6]),
+            Err(AllocFailure::OverMaxTxSize { max_tx_bytes: 5 })
+        );
+        assert_eq!(alloc.encrypted_used(), 5);
+
+        // the cap doesn't apply to decrypted txs
+        let mut alloc = alloc.next_state();
+        assert!(alloc.try_alloc(&[0; 6]).is_ok());
+    }
+
+    /// Check that invalid block space splits are rejected.
+    #[test]
+    fn test_block_space_split_validation() {
+        assert!(BlockSpaceSplit::from_percentages(20, 50, 30).is_ok());
+        assert!(BlockSpaceSplit::from_percentages(20, 50, 20).is_err());
+        assert!(BlockSpaceSplit::from_percentages(50, 20, 30).is_err());
+        assert!(BlockSpaceSplit::from_percentages(u64::MAX, 1, 0).is_err());
+    }
+
+    /// Check that a [`BlockSpaceSplit`] is applied when the allocator
+    /// is initialized, and that decrypted txs can't use up the share
+    /// of the block guaranteed to protocol txs.
+    #[test]
+    fn test_block_space_split_is_applied() {
+        const BLOCK_SIZE: u64 = 100;
+
+        let split = BlockSpaceSplit::from_percentages(20, 50, 30).unwrap();
+        let mut alloc = BsaWrapperTxs::init_with_split(BLOCK_SIZE, split);
+        assert_eq!(alloc.encrypted_txs.allotted_space_in_bytes, 20);
+        assert!(alloc.try_alloc(&[0; 10]).is_ok());
+
+        let mut alloc = alloc.next_state();
+        assert_eq!(alloc.decrypted_txs.allotted_space_in_bytes, 60);
+        assert!(alloc.try_alloc(&[0; 60]).is_ok());
+
+        let alloc = alloc.next_state();
+        assert_eq!(alloc.protocol_txs.allotted_space_in_bytes, 30);
+    }
+
+    /// Check that the [`BlockSpaceSplit`] is read from the chain
+    /// parameters, and that invalid splits in storage are ignored.
+    #[test]
+    fn test_block_space_split_from_storage() {
+        let mut wl_storage = TestWlStorage::default();
+        assert_eq!(
+            BlockSpaceSplit::from_storage(&wl_storage),
+            BlockSpaceSplit::default()
+        );
+
+        update_block_space_split_parameter(&mut wl_storage, &(20, 50, 30))
+            .unwrap();
+        assert_eq!(
+            BlockSpaceSplit::from_storage(&wl_storage),
+            BlockSpaceSplit::from_percentages(20, 50, 30).unwrap()
+        );
+
+        update_block_space_split_parameter(&mut wl_storage, &(50, 20, 30))
+            .unwrap();
+        assert_eq!(
+            BlockSpaceSplit::from_storage(&wl_storage),
+            BlockSpaceSplit::default()
+        );
+    }
+
+    /// Check that reserving block space reduces the effective
+    /// free space of an allocator accordingly.
+    #[test]
+    fn test_reserves_reduce_effective_free_space() {
+        let mut alloc = BsaWrapperTxs::init(60);
+        assert!(alloc.try_alloc(&[0; 10]).is_ok());
+        let mut alloc = alloc.next_state();
+        assert_eq!(alloc.effective_free_space(), 50);
+
+        alloc.reserve_space(SpaceReserve::Protocol, 10);
+        assert_eq!(alloc.effective_free_space(), 40);
+        alloc.reserve_space(SpaceReserve::Evidence, 5);
+        assert_eq!(alloc.effective_free_space(), 35);
+        alloc.reserve_space(SpaceReserve::GasEquivalent, 15);
+        assert_eq!(alloc.effective_free_space(), 20);
+
+        // replacing a reservation doesn't stack with the old one
+        alloc.reserve_space(SpaceReserve::Protocol, 0);
+        assert_eq!(alloc.effective_free_space(), 30);
+
+        // allocating txs also reduces the effective free space
+        assert!(alloc.try_alloc(&[0; 10]).is_ok());
+        assert_eq!(alloc.effective_free_space(), 20);
+
+        // reserves never make the effective free space underflow
+        alloc.reserve_space(SpaceReserve::Evidence, u64::MAX);
+        assert_eq!(alloc.effective_free_space(), 0);
+    }
+
     // Test that we cannot include encrypted txs in a block
     // when the state invariants banish them from inclusion.
     #[test]
@@ -324,6 +1247,32 @@ mod tests {
         );
     }
 
+    /// Test that withholding the space of encrypted txs dedicates
+    /// the whole block to decrypted and protocol txs.
+    #[test]
+    fn test_withheld_encrypted_space() {
+        const BLOCK_SIZE: u64 = 60;
+        let whole_block = threshold::Threshold::from_percentage(100);
+
+        // the space of encrypted txs caps the vote extension reservation
+        let alloc = BsaNoWrapperTxs::init(BLOCK_SIZE)
+            .with_vote_extension_space(whole_block);
+        assert_eq!(alloc.vote_extension_txs.allotted_space_in_bytes, 40);
+
+        let alloc = BsaNoWrapperTxs::init(BLOCK_SIZE)
+            .withhold_encrypted_space()
+            .with_vote_extension_space(whole_block);
+        assert_eq!(
+            alloc.vote_extension_txs.allotted_space_in_bytes,
+            BLOCK_SIZE
+        );
+
+        let alloc = BsaNoWrapperTxs::init(BLOCK_SIZE)
+            .withhold_encrypted_space()
+            .next_state();
+        assert_eq!(alloc.decrypted_txs.allotted_space_in_bytes, BLOCK_SIZE);
+    }
+
     proptest! {
         /// Check if we reject a tx when its respective bin
         /// capacity has been reached on a [`BlockSpaceAllocator`].
@@ -345,6 +1294,106 @@ mod tests {
         fn test_tx_dump_doesnt_fill_up_bin(args in arb_transactions()) {
             proptest_tx_dump_doesnt_fill_up_bin(args)
         }
+
+        /// Test that the invariants of the [`BlockSpaceAllocator`] hold
+        /// across all of its states, for arbitrary sequences of txs.
+        #[test]
+        fn test_allocator_invariants(
+            args in arb_transactions(),
+            vote_extension_pct in 0..=100u64,
+        ) {
+            proptest_allocator_invariants(args, vote_extension_pct)
+        }
+    }
+
+    /// Implementation of [`test_allocator_invariants`].
+    fn proptest_allocator_invariants(args: PropTx, vote_extension_pct: u64) {
+        let PropTx {
+            tendermint_max_block_space_in_bytes,
+            protocol_txs,
+            encrypted_txs,
+            decrypted_txs,
+        } = args;
+
+        let mut alloc =
+            BsaWrapperTxs::init(tendermint_max_block_space_in_bytes)
+                .with_vote_extension_space(
+                    threshold::Threshold::from_percentage(vote_extension_pct),
+                );
+        assert_alloc_invariants(&alloc);
+        fill_and_check(&mut alloc, &encrypted_txs);
+
+        let (mut alloc, info) = alloc.try_next_state();
+        assert_transition_invariants(&alloc, info);
+        fill_and_check(&mut alloc, &decrypted_txs);
+
+        let (mut alloc, info) = alloc.try_next_state();
+        assert_transition_invariants(&alloc, info);
+        for tx in &protocol_txs {
+            check_rejection(alloc.try_alloc_vote_extension(tx), tx);
+            assert_alloc_invariants(&alloc);
+        }
+
+        let (mut alloc, info) = alloc.try_next_state();
+        assert_transition_invariants(&alloc, info);
+        assert_eq!(alloc.uninitialized_space_in_bytes(), 0);
+        fill_and_check(&mut alloc, &protocol_txs);
+    }
+
+    /// Try to allocate all the given txs, checking the invariants
+    /// of the [`BlockSpaceAllocator`] after each allocation.
+    fn fill_and_check<State>(
+        alloc: &mut BlockSpaceAllocator<State>,
+        txs: &[Vec<u8>],
+    ) where
+        BlockSpaceAllocator<State>: TryAlloc,
+    {
+        for tx in txs {
+            check_rejection(alloc.try_alloc(tx), tx);
+            assert_alloc_invariants(alloc);
+        }
+    }
+
+    /// Check that the diagnostics of a rejected tx are consistent.
+    fn check_rejection(result: Result<(), AllocFailure>, tx: &[u8]) {
+        if let Err(AllocFailure::Rejected { needed, available }) = result {
+            assert_eq!(needed, tx.len() as u64);
+            assert!(needed > available);
+        }
+    }
+
+    /// Check that no more space than is available in the block is
+    /// allotted to or utilized by the bins of a [`BlockSpaceAllocator`],
+    /// such that bins never overlap.
+    fn assert_alloc_invariants<State>(alloc: &BlockSpaceAllocator<State>) {
+        let bins = [
+            alloc.protocol_txs,
+            alloc.encrypted_txs,
+            alloc.priority_txs,
+            alloc.decrypted_txs,
+            alloc.vote_extension_txs,
+        ];
+        for bin in bins {
+            assert!(bin.occupied_space_in_bytes <= bin.allotted_space_in_bytes);
+        }
+        let allotted: u64 =
+            bins.iter().map(|bin| bin.allotted_space_in_bytes).sum();
+        assert!(allotted <= alloc.block.allotted_space_in_bytes);
+        assert!(
+            alloc.occupied_space_in_bytes()
+                <= alloc.block.allotted_space_in_bytes
+        );
+    }
+
+    /// Check the invariants of a [`BlockSpaceAllocator`] right after
+    /// a state transition.
+    fn assert_transition_invariants<State>(
+        alloc: &BlockSpaceAllocator<State>,
+        info: TransitionInfo,
+    ) {
+        assert_alloc_invariants(alloc);
+        assert_eq!(info.prior_free, alloc.free_space_in_bytes());
+        assert!(info.new_bin_size <= alloc.block.allotted_space_in_bytes);
     }
 
     /// Implementation of [`test_reject_tx_on_bin_cap_reached`].
@@ -11,17 +11,28 @@
 //!    This state supports two modes of operation, which you can
 //!    think of as two sub-states:
 //!   * [`WithoutEncryptedTxs`] - When this mode is active, no encrypted txs are
-//!     included in a block proposal.
+//!     included in a block proposal, and their space may be withheld, such
+//!     that the whole block is left to decrypted and protocol txs.
 //!   * [`WithEncryptedTxs`] - When this mode is active, we are able to include
-//!     encrypted txs in a block proposal.
+//!     encrypted txs in a block proposal. Priority encrypted txs are allocated
+//!     first, in the space reserved for them, after which the unused part of
+//!     that space is released to the remaining encrypted txs.
 //! 2. [`BuildingDecryptedTxBatch`] - the second state. In
 //!    this state, we populate a block with DKG decrypted txs.
 //! 3. [`BuildingProtocolTxBatch`] - the third state. In
-//!    this state, we populate a block with protocol txs.
+//!    this state, we populate a block with protocol txs. Vote
+//!    extension protocol txs are allocated in the space reserved
+//!    for them, if any, before spilling over into the space of
+//!    the remaining protocol txs.
+//! 4. [`RebalancedProtocolTxBatch`] - the fourth and final state.
+//!    In this state, the capacity left unused by the bins of the
+//!    previous states is redistributed into the protocol tx bin,
+//!    before the block proposal is finalized.
 
 mod decrypted_txs;
 mod encrypted_txs;
 mod protocol_txs;
+mod rebalanced_protocol_txs;
 
 use super::{AllocFailure, BlockSpaceAllocator};
 
@@ -51,6 +62,14 @@ pub enum BuildingDecryptedTxBatch {}
 /// [`crate::node::ledger::shell::prepare_proposal::block_space_alloc::states`].
 pub enum BuildingProtocolTxBatch {}
 
+/// The leader of the current Tendermint round is filling up
+/// the capacity left unused by the other batches of txs with
+/// Namada protocol transactions.
+///
+/// For more info, read the module docs of
+/// [`crate::node::ledger::shell::prepare_proposal::block_space_alloc::states`].
+pub enum RebalancedProtocolTxBatch {}
+
 /// The leader of the current Tendermint round is building
 /// a new batch of DKG encrypted transactions.
 ///
@@ -82,6 +101,66 @@ pub trait TryAlloc {
     fn try_alloc(&mut self, tx: &[u8]) -> Result<(), AllocFailure>;
 }
 
+/// Try to allocate a new transaction declaring some amount of gas on a
+/// [`BlockSpaceAllocator`] state.
+///
+/// For more info, read the module docs of
+/// [`crate::node::ledger::shell::prepare_proposal::block_space_alloc::states`].
+pub trait TryAllocWithGas {
+    /// Try to allocate space for a new transaction, as well as the
+    /// `gas` it declares. Nothing is allocated if either of them
+    /// doesn't fit in the block.
+    fn try_alloc_with_gas(
+        &mut self,
+        tx: &[u8],
+        gas: u64,
+    ) -> Result<(), AllocFailure>;
+}
+
+/// Try to allocate a new priority encrypted transaction on a
+/// [`BlockSpaceAllocator`] state.
+///
+/// For more info, read the module docs of
+/// [`crate::node::ledger::shell::prepare_proposal::block_space_alloc::states`].
+pub trait TryAllocPriority {
+    /// Try to allocate space for a new priority transaction, as well
+    /// as the `gas` it declares, in the space reserved for priority
+    /// transactions.
+    fn try_alloc_priority(
+        &mut self,
+        tx: &[u8],
+        gas: u64,
+    ) -> Result<(), AllocFailure>;
+
+    /// Give the unused space reserved for priority transactions back
+    /// to the remaining encrypted transactions.
+    fn release_priority_space(&mut self);
+}
+
+/// Try to allocate a new vote extension protocol transaction on a
+/// [`BlockSpaceAllocator`] state.
+///
+/// For more info, read the module docs of
+/// [`crate::node::ledger::shell::prepare_proposal::block_space_alloc::states`].
+pub trait TryAllocVoteExtension {
+    /// Try to allocate space for a new vote extension protocol transaction.
+    fn try_alloc_vote_extension(
+        &mut self,
+        tx: &[u8],
+    ) -> Result<(), AllocFailure>;
+}
+
+/// Query the phase of block construction a [`BlockSpaceAllocator`]
+/// state is in, at runtime.
+///
+/// For more info, read the module docs of
+/// [`crate::node::ledger::shell::prepare_proposal::block_space_alloc::states`].
+pub trait AllocatorPhase {
+    /// Check if we are done building batches of encrypted and
+    /// decrypted txs, and are now building a batch of protocol txs.
+    fn is_protocol_phase(&self) -> bool;
+}
+
 /// Represents a state transition in the [`BlockSpaceAllocator`] state machine.
 ///
 /// This trait should not be used directly. Instead, consider using one of
@@ -117,3 +196,28 @@ pub trait NextState: NextStateImpl {
 }
 
 impl<S> NextState for S where S: NextStateImpl {}
+
+/// Diagnostics about a transition in the [`BlockSpaceAllocator`]
+/// state machine, measured in bytes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TransitionInfo {
+    /// The block space that was free prior to the transition.
+    pub prior_free: u64,
+    /// The space allotted to the bin of the new state.
+    pub new_bin_size: u64,
+}
+
+/// Extension of [`NextStateImpl`], to transition to a new state
+/// while reporting how the space of the new state's bin was
+/// derived.
+///
+/// This is useful to log why some bin ended up with little
+/// or no space, e.g. during congested rounds.
+///
+/// For more info, read the module docs of
+/// [`crate::node::ledger::shell::prepare_proposal::block_space_alloc::states`].
+pub trait TryNextState: NextStateImpl {
+    /// Transition to the next state in the [`BlockSpaceAllocator`] state
+    /// machine, returning diagnostics about the transition.
+    fn try_next_state(self) -> (Self::Next, TransitionInfo);
+}
@@ -1,14 +1,22 @@
-use std::marker::PhantomData;
-
 use super::super::{AllocFailure, BlockSpaceAllocator, TxBin};
 use super::{
-    BuildingDecryptedTxBatch, BuildingProtocolTxBatch, NextStateImpl, TryAlloc,
+    AllocatorPhase, BuildingDecryptedTxBatch, BuildingProtocolTxBatch,
+    NextStateImpl, TransitionInfo, TryAlloc, TryNextState,
 };
 
 impl TryAlloc for BlockSpaceAllocator<BuildingDecryptedTxBatch> {
     #[inline]
     fn try_alloc(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
-        self.decrypted_txs.try_dump(tx)
+        self.decrypted_txs.try_dump(tx)?;
+        self.record_fill();
+        Ok(())
+    }
+}
+
+impl AllocatorPhase for BlockSpaceAllocator<BuildingDecryptedTxBatch> {
+    #[inline]
+    fn is_protocol_phase(&self) -> bool {
+        false
     }
 }
 
@@ -23,21 +31,19 @@ impl NextStateImpl for BlockSpaceAllocator<BuildingDecryptedTxBatch> {
         let remaining_free_space = self.uninitialized_space_in_bytes();
         self.protocol_txs = TxBin::init(remaining_free_space);
 
-        // cast state
-        let Self {
-            block,
-            protocol_txs,
-            encrypted_txs,
-            decrypted_txs,
-            ..
-        } = self;
+        self.cast_state()
+    }
+}
 
-        BlockSpaceAllocator {
-            _state: PhantomData,
-            block,
-            protocol_txs,
-            encrypted_txs,
-            decrypted_txs,
-        }
+impl TryNextState for BlockSpaceAllocator<BuildingDecryptedTxBatch> {
+    #[inline]
+    fn try_next_state(self) -> (Self::Next, TransitionInfo) {
+        let prior_free = self.free_space_in_bytes();
+        let next = self.next_state_impl();
+        let info = TransitionInfo {
+            prior_free,
+            new_bin_size: next.protocol_txs.allotted_space_in_bytes,
+        };
+        (next, info)
     }
 }
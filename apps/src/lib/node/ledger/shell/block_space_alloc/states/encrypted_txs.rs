@@ -1,9 +1,10 @@
-use std::marker::PhantomData;
+use namada::ledger::alloc_log::AllocBin;
 
 use super::super::{AllocFailure, BlockSpaceAllocator, TxBin};
 use super::{
-    BuildingDecryptedTxBatch, BuildingEncryptedTxBatch,
-    EncryptedTxBatchAllocator, NextStateImpl, TryAlloc, WithEncryptedTxs,
+    AllocatorPhase, BuildingDecryptedTxBatch, BuildingEncryptedTxBatch,
+    EncryptedTxBatchAllocator, NextStateImpl, TransitionInfo, TryAlloc,
+    TryAllocPriority, TryAllocWithGas, TryNextState, WithEncryptedTxs,
     WithoutEncryptedTxs,
 };
 
@@ -12,7 +13,47 @@ impl TryAlloc
 {
     #[inline]
     fn try_alloc(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
-        self.encrypted_txs.try_dump(tx)
+        self.encrypted_txs.try_dump(tx)?;
+        self.record_fill();
+        Ok(())
+    }
+}
+
+impl TryAllocWithGas
+    for BlockSpaceAllocator<BuildingEncryptedTxBatch<WithEncryptedTxs>>
+{
+    #[inline]
+    fn try_alloc_with_gas(
+        &mut self,
+        tx: &[u8],
+        gas: u64,
+    ) -> Result<(), AllocFailure> {
+        self.encrypted_gas.check(gas)?;
+        self.try_alloc(tx)?;
+        self.encrypted_gas.try_dump(gas)
+    }
+}
+
+impl TryAllocPriority
+    for BlockSpaceAllocator<BuildingEncryptedTxBatch<WithEncryptedTxs>>
+{
+    #[inline]
+    fn try_alloc_priority(
+        &mut self,
+        tx: &[u8],
+        gas: u64,
+    ) -> Result<(), AllocFailure> {
+        self.encrypted_gas.check(gas)?;
+        self.priority_txs.try_dump(tx)?;
+        self.record_fill();
+        self.encrypted_gas.try_dump(gas)
+    }
+
+    #[inline]
+    fn release_priority_space(&mut self) {
+        let unused_space = self.priority_txs.space_left_in_bytes();
+        self.priority_txs.shrink_to_fit();
+        self.encrypted_txs.allotted_space_in_bytes += unused_space;
     }
 }
 
@@ -31,8 +72,52 @@ impl TryAlloc
     for BlockSpaceAllocator<BuildingEncryptedTxBatch<WithoutEncryptedTxs>>
 {
     #[inline]
-    fn try_alloc(&mut self, _tx: &[u8]) -> Result<(), AllocFailure> {
-        Err(AllocFailure::Rejected { bin_space_left: 0 })
+    fn try_alloc(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
+        Err(AllocFailure::Rejected {
+            needed: tx.len() as u64,
+            available: 0,
+        })
+    }
+}
+
+impl TryAllocWithGas
+    for BlockSpaceAllocator<BuildingEncryptedTxBatch<WithoutEncryptedTxs>>
+{
+    #[inline]
+    fn try_alloc_with_gas(
+        &mut self,
+        tx: &[u8],
+        _gas: u64,
+    ) -> Result<(), AllocFailure> {
+        self.try_alloc(tx)
+    }
+}
+
+impl TryAllocPriority
+    for BlockSpaceAllocator<BuildingEncryptedTxBatch<WithoutEncryptedTxs>>
+{
+    #[inline]
+    fn try_alloc_priority(
+        &mut self,
+        tx: &[u8],
+        _gas: u64,
+    ) -> Result<(), AllocFailure> {
+        self.try_alloc(tx)
+    }
+
+    #[inline]
+    fn release_priority_space(&mut self) {}
+}
+
+impl BlockSpaceAllocator<BuildingEncryptedTxBatch<WithoutEncryptedTxs>> {
+    /// Release the space allotted to DKG encrypted txs, including their
+    /// priority lane, such that the whole block is left to decrypted and
+    /// protocol txs.
+    #[inline]
+    pub fn withhold_encrypted_space(mut self) -> Self {
+        self.encrypted_txs = TxBin::default();
+        self.priority_txs = TxBin::default();
+        self
     }
 }
 
@@ -47,33 +132,62 @@ impl NextStateImpl
     }
 }
 
+impl<Mode> AllocatorPhase
+    for BlockSpaceAllocator<BuildingEncryptedTxBatch<Mode>>
+{
+    #[inline]
+    fn is_protocol_phase(&self) -> bool {
+        false
+    }
+}
+
+impl<Mode> TryNextState for BlockSpaceAllocator<BuildingEncryptedTxBatch<Mode>>
+where
+    Self: NextStateImpl<Next = BlockSpaceAllocator<BuildingDecryptedTxBatch>>,
+{
+    #[inline]
+    fn try_next_state(self) -> (Self::Next, TransitionInfo) {
+        let prior_free = self.free_space_in_bytes();
+        let next = self.next_state_impl();
+        let info = TransitionInfo {
+            prior_free,
+            new_bin_size: next.decrypted_txs.allotted_space_in_bytes,
+        };
+        (next, info)
+    }
+}
+
 #[inline]
 fn next_state<Mode>(
     mut alloc: BlockSpaceAllocator<BuildingEncryptedTxBatch<Mode>>,
 ) -> BlockSpaceAllocator<BuildingDecryptedTxBatch> {
     alloc.encrypted_txs.shrink_to_fit();
+    alloc.priority_txs.shrink_to_fit();
 
     // decrypted txs can use as much space as they need - which
-    // in practice will only be, at most, 1/3 of the block space
-    // used by encrypted txs at the prev height
-    let remaining_free_space = alloc.uninitialized_space_in_bytes();
+    // in practice will only be, at most, the block space used by
+    // encrypted txs at the prev height - except for the share of
+    // the block guaranteed to protocol txs
+    let remaining_free_space = alloc
+        .uninitialized_space_in_bytes()
+        .saturating_sub(alloc.protocol_share);
     alloc.decrypted_txs = TxBin::init(remaining_free_space);
 
-    // cast state
-    let BlockSpaceAllocator {
-        block,
-        protocol_txs,
-        encrypted_txs,
-        decrypted_txs,
-        ..
-    } = alloc;
+    alloc.cast_state()
+}
 
-    BlockSpaceAllocator {
-        _state: PhantomData,
-        block,
-        protocol_txs,
-        encrypted_txs,
-        decrypted_txs,
+impl EncryptedTxBatchAllocator {
+    /// Return the space left in the given [`AllocBin`].
+    #[inline]
+    pub fn space_left_in(&self, bin: AllocBin) -> u64 {
+        match self {
+            EncryptedTxBatchAllocator::WithEncryptedTxs(state) => {
+                state.space_left_in(bin)
+            }
+            EncryptedTxBatchAllocator::WithoutEncryptedTxs(state) => {
+                state.space_left_in(bin)
+            }
+        }
     }
 }
 
@@ -93,6 +207,54 @@ impl TryAlloc for EncryptedTxBatchAllocator {
     }
 }
 
+impl TryAllocWithGas for EncryptedTxBatchAllocator {
+    #[inline]
+    fn try_alloc_with_gas(
+        &mut self,
+        tx: &[u8],
+        gas: u64,
+    ) -> Result<(), AllocFailure> {
+        match self {
+            EncryptedTxBatchAllocator::WithEncryptedTxs(state) => {
+                state.try_alloc_with_gas(tx, gas)
+            }
+            EncryptedTxBatchAllocator::WithoutEncryptedTxs(state) => {
+                state.try_alloc_with_gas(tx, gas)
+            }
+        }
+    }
+}
+
+impl TryAllocPriority for EncryptedTxBatchAllocator {
+    #[inline]
+    fn try_alloc_priority(
+        &mut self,
+        tx: &[u8],
+        gas: u64,
+    ) -> Result<(), AllocFailure> {
+        match self {
+            EncryptedTxBatchAllocator::WithEncryptedTxs(state) => {
+                state.try_alloc_priority(tx, gas)
+            }
+            EncryptedTxBatchAllocator::WithoutEncryptedTxs(state) => {
+                state.try_alloc_priority(tx, gas)
+            }
+        }
+    }
+
+    #[inline]
+    fn release_priority_space(&mut self) {
+        match self {
+            EncryptedTxBatchAllocator::WithEncryptedTxs(state) => {
+                state.release_priority_space()
+            }
+            EncryptedTxBatchAllocator::WithoutEncryptedTxs(state) => {
+                state.release_priority_space()
+            }
+        }
+    }
+}
+
 impl NextStateImpl for EncryptedTxBatchAllocator {
     type Next = BlockSpaceAllocator<BuildingDecryptedTxBatch>;
 
@@ -108,3 +270,24 @@ impl NextStateImpl for EncryptedTxBatchAllocator {
         }
     }
 }
+
+impl TryNextState for EncryptedTxBatchAllocator {
+    #[inline]
+    fn try_next_state(self) -> (Self::Next, TransitionInfo) {
+        match self {
+            EncryptedTxBatchAllocator::WithEncryptedTxs(state) => {
+                state.try_next_state()
+            }
+            EncryptedTxBatchAllocator::WithoutEncryptedTxs(state) => {
+                state.try_next_state()
+            }
+        }
+    }
+}
+
+impl AllocatorPhase for EncryptedTxBatchAllocator {
+    #[inline]
+    fn is_protocol_phase(&self) -> bool {
+        false
+    }
+}
@@ -1,9 +1,70 @@
 use super::super::{AllocFailure, BlockSpaceAllocator};
-use super::{BuildingProtocolTxBatch, TryAlloc};
+use super::{
+    AllocatorPhase, BuildingProtocolTxBatch, NextStateImpl,
+    RebalancedProtocolTxBatch, TransitionInfo, TryAlloc, TryAllocVoteExtension,
+    TryNextState,
+};
 
 impl TryAlloc for BlockSpaceAllocator<BuildingProtocolTxBatch> {
     #[inline]
     fn try_alloc(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
-        self.protocol_txs.try_dump(tx)
+        self.protocol_txs.try_dump(tx)?;
+        self.record_fill();
+        Ok(())
+    }
+}
+
+impl TryAllocVoteExtension for BlockSpaceAllocator<BuildingProtocolTxBatch> {
+    #[inline]
+    fn try_alloc_vote_extension(
+        &mut self,
+        tx: &[u8],
+    ) -> Result<(), AllocFailure> {
+        // fall back to the space of the remaining protocol txs
+        // once the reserved space runs out
+        if self.vote_extension_txs.try_dump(tx).is_err() {
+            self.protocol_txs.try_dump(tx)?;
+        }
+        self.record_fill();
+        Ok(())
+    }
+}
+
+impl AllocatorPhase for BlockSpaceAllocator<BuildingProtocolTxBatch> {
+    #[inline]
+    fn is_protocol_phase(&self) -> bool {
+        true
+    }
+}
+
+impl NextStateImpl for BlockSpaceAllocator<BuildingProtocolTxBatch> {
+    type Next = BlockSpaceAllocator<RebalancedProtocolTxBatch>;
+
+    #[inline]
+    fn next_state_impl(mut self) -> Self::Next {
+        // release the space left unused in every other bin, e.g.
+        // the reservation of vote extensions that didn't show up
+        self.encrypted_txs.shrink_to_fit();
+        self.decrypted_txs.shrink_to_fit();
+        self.vote_extension_txs.shrink_to_fit();
+
+        // and hand it over to protocol txs
+        let unused_space = self.uninitialized_space_in_bytes();
+        self.protocol_txs.allotted_space_in_bytes += unused_space;
+
+        self.cast_state()
+    }
+}
+
+impl TryNextState for BlockSpaceAllocator<BuildingProtocolTxBatch> {
+    #[inline]
+    fn try_next_state(self) -> (Self::Next, TransitionInfo) {
+        let prior_free = self.free_space_in_bytes();
+        let next = self.next_state_impl();
+        let info = TransitionInfo {
+            prior_free,
+            new_bin_size: next.protocol_txs.allotted_space_in_bytes,
+        };
+        (next, info)
     }
 }
@@ -0,0 +1,18 @@
+use super::super::{AllocFailure, BlockSpaceAllocator};
+use super::{AllocatorPhase, RebalancedProtocolTxBatch, TryAlloc};
+
+impl TryAlloc for BlockSpaceAllocator<RebalancedProtocolTxBatch> {
+    #[inline]
+    fn try_alloc(&mut self, tx: &[u8]) -> Result<(), AllocFailure> {
+        self.protocol_txs.try_dump(tx)?;
+        self.record_fill();
+        Ok(())
+    }
+}
+
+impl AllocatorPhase for BlockSpaceAllocator<RebalancedProtocolTxBatch> {
+    #[inline]
+    fn is_protocol_phase(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,272 @@
+//! The votes of the validators on the events of the contract of the bridge
+//! to Ethereum, which are tallied in the storage described in
+//! [`namada::ledger::eth_bridge::storage`].
+//!
+//! A validator node reads the events observed on Ethereum from the feed
+//! given in its config and, at every block, votes on the events that it
+//! didn't vote on yet in a protocol tx. Once an event is seen by more than 2/3
+//! of the stake, the protocol acts on it: the locked ERC20 tokens are minted
+//! as wrapped tokens, and the transfers of the bridge pool relayed to Ethereum
+//! are removed from the pool.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use namada::ledger::eth_bridge::storage as bridge_storage;
+use namada::ledger::storage::{DBIter, StorageHasher, DB};
+use namada::proof_of_stake::pos_queries::PosQueries;
+use namada::types::ethereum_events::EthereumEvent;
+use namada::types::token;
+use namada::types::transaction::protocol::{
+    EthereumEventsVote, ProtocolTxType,
+};
+
+use super::{Shell, ShellMode};
+
+/// Read the events of the contract of the bridge observed on Ethereum from a
+/// feed file, which lists them in JSON
+pub fn read_events_feed(path: &Path) -> Option<Vec<EthereumEvent>> {
+    let feed = std::fs::read_to_string(path)
+        .map_err(|err| {
+            tracing::error!(
+                "Failed to read the Ethereum events feed {}: {}",
+                path.to_string_lossy(),
+                err
+            )
+        })
+        .ok()?;
+    serde_json::from_str(&feed)
+        .map_err(|err| {
+            tracing::error!(
+                "Failed to decode the Ethereum events feed {}: {}",
+                path.to_string_lossy(),
+                err
+            )
+        })
+        .ok()
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// If this node is a validator with a feed of Ethereum events, vote on
+    /// the events of the feed that it didn't vote on yet and broadcast the
+    /// vote in a protocol tx
+    pub(super) fn broadcast_eth_events_vote(&self) {
+        let (validator, broadcast_sender, signer) = match &self.mode {
+            ShellMode::Validator {
+                data,
+                broadcast_sender,
+                protocol_signer,
+            } => (&data.address, broadcast_sender, protocol_signer.as_ref()),
+            _ => return,
+        };
+        let events = match self
+            .ethereum_events_feed
+            .as_ref()
+            .and_then(|path| read_events_feed(path))
+        {
+            Some(events) => events,
+            None => return,
+        };
+        let events: Vec<EthereumEvent> = events
+            .into_iter()
+            .filter(|event| {
+                let hash = event.hash();
+                matches!(
+                    bridge_storage::is_eth_msg_seen(&self.wl_storage, &hash),
+                    Ok(false)
+                ) && matches!(
+                    bridge_storage::has_voted_eth_msg(
+                        &self.wl_storage,
+                        &hash,
+                        validator
+                    ),
+                    Ok(false)
+                )
+            })
+            .collect();
+        if events.is_empty() {
+            return;
+        }
+        let tx = EthereumEventsVote::new(validator.clone(), events, signer)
+            .and_then(|vote| {
+                ProtocolTxType::EthereumEvents(vote)
+                    .sign(signer, self.chain_id.clone())
+            });
+        match tx {
+            Ok(tx) => {
+                if let Err(err) = broadcast_sender.send(tx.to_bytes()) {
+                    tracing::error!(
+                        "Failed to broadcast the vote on the Ethereum events: \
+                         {}",
+                        err
+                    );
+                }
+            }
+            Err(err) => tracing::error!(
+                "Failed to sign the vote on the Ethereum events: {}",
+                err
+            ),
+        }
+    }
+
+    /// Check that the Ethereum events were voted on by a consensus validator
+    /// of the epoch of the last committed block, and that none of them was
+    /// already voted on by this validator or seen by more than 2/3 of the
+    /// stake. Returns the stake of the validator.
+    pub(super) fn validate_eth_events_vote(
+        &self,
+        vote: &EthereumEventsVote,
+    ) -> Result<token::Amount, String> {
+        if vote.events.is_empty() {
+            return Err(format!(
+                "Validator {} voted on no Ethereum events",
+                vote.validator
+            ));
+        }
+        let epoch = self.last_block_epoch()?;
+        let (stake, protocol_pk) = self
+            .wl_storage
+            .pos_queries()
+            .get_validator_from_address(&vote.validator, Some(epoch))
+            .map_err(|err| err.to_string())?;
+        let mut hashes = HashSet::new();
+        for event in &vote.events {
+            let hash = event.hash();
+            if !hashes.insert(hash) {
+                return Err(format!(
+                    "Validator {} voted twice on the Ethereum event {}",
+                    vote.validator, hash
+                ));
+            }
+            if bridge_storage::is_eth_msg_seen(&self.wl_storage, &hash)
+                .map_err(|err| err.to_string())?
+            {
+                return Err(format!(
+                    "The Ethereum event {} was already seen",
+                    hash
+                ));
+            }
+            if bridge_storage::has_voted_eth_msg(
+                &self.wl_storage,
+                &hash,
+                &vote.validator,
+            )
+            .map_err(|err| err.to_string())?
+            {
+                return Err(format!(
+                    "Validator {} already voted on the Ethereum event {}",
+                    vote.validator, hash
+                ));
+            }
+        }
+        vote.verify(&protocol_pk).map_err(|err| {
+            format!(
+                "Invalid signature of the Ethereum events vote of validator \
+                 {}: {}",
+                vote.validator, err
+            )
+        })?;
+        Ok(stake)
+    }
+
+    /// Record a vote on Ethereum events, acting on the events that it makes
+    /// seen by more than 2/3 of the stake. Returns the number of events acted
+    /// on.
+    pub(super) fn apply_eth_events_vote(
+        &mut self,
+        vote: &EthereumEventsVote,
+    ) -> Result<usize, String> {
+        let stake = self.validate_eth_events_vote(vote)?;
+        let epoch = self.last_block_epoch()?;
+        let total_stake = self
+            .wl_storage
+            .pos_queries()
+            .get_total_voting_power(Some(epoch));
+        let mut acted_on = 0;
+        for event in &vote.events {
+            let seen = bridge_storage::vote_eth_msg(
+                &mut self.wl_storage,
+                event,
+                &vote.validator,
+                stake,
+                total_stake,
+            )
+            .map_err(|err| err.to_string())?;
+            if seen {
+                bridge_storage::act_on_eth_msg(&mut self.wl_storage, event)
+                    .map_err(|err| err.to_string())?;
+                acted_on += 1;
+            }
+        }
+        Ok(acted_on)
+    }
+}
+
+#[cfg(test)]
+mod test_eth_events {
+    use namada::ledger::storage_api::token as token_api;
+    use namada::types::ethereum_events::{EthAddress, TransferToNamada};
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils;
+    use crate::wallet::defaults;
+
+    /// Vote on the given events with the protocol key of the genesis
+    /// validator
+    fn vote(events: Vec<EthereumEvent>) -> EthereumEventsVote {
+        EthereumEventsVote::new(
+            defaults::validator_address(),
+            events,
+            &defaults::validator_keys().0,
+        )
+        .unwrap()
+    }
+
+    /// Test that the votes are validated, and that the wrapped tokens of a
+    /// transfer to Namada are minted once it is seen
+    #[test]
+    fn test_eth_events_vote() {
+        let (mut shell, _) = test_utils::setup(1);
+        let receiver = defaults::albert_address();
+        let asset = EthAddress([1; 20]);
+        let event = EthereumEvent::TransfersToNamada {
+            nonce: 0,
+            transfers: vec![TransferToNamada {
+                asset,
+                amount: token::Amount::whole(100),
+                receiver: receiver.clone(),
+            }],
+        };
+
+        // a vote must be on some events, once per event
+        assert!(shell.validate_eth_events_vote(&vote(vec![])).is_err());
+        assert!(shell
+            .validate_eth_events_vote(&vote(vec![event.clone(), event.clone()]))
+            .is_err());
+
+        // the vote must be signed with the protocol key of the validator
+        let mut forged = vote(vec![event.clone()]);
+        forged.sig = vote(vec![]).sig;
+        assert!(shell.validate_eth_events_vote(&forged).is_err());
+
+        // the only validator holds all the stake, so its vote is a quorum
+        let valid = vote(vec![event.clone()]);
+        assert_eq!(shell.apply_eth_events_vote(&valid), Ok(1));
+        assert_eq!(
+            token_api::read_balance(
+                &shell.wl_storage,
+                &asset.wrapped_token(),
+                &receiver
+            )
+            .unwrap(),
+            token::Amount::whole(100)
+        );
+
+        // the event is only acted on once
+        assert!(shell.validate_eth_events_vote(&valid).is_err());
+    }
+}
@@ -3,13 +3,15 @@
 use std::collections::HashMap;
 
 use data_encoding::HEXUPPER;
+use namada::ledger::eth_bridge::storage as bridge_storage;
+use namada::ledger::oracle::{self, SignedOraclePrices};
 use namada::ledger::parameters::storage as params_storage;
 use namada::ledger::pos::types::{decimal_mult_u64, into_tm_voting_power};
 use namada::ledger::pos::{namada_proof_of_stake, staking_token_address};
 use namada::ledger::storage::EPOCH_SWITCH_BLOCKS_DELAY;
 use namada::ledger::storage_api::token::credit_tokens;
 use namada::ledger::storage_api::{StorageRead, StorageWrite};
-use namada::ledger::{inflation, protocol, replay_protection};
+use namada::ledger::{inflation, migrations, protocol, replay_protection};
 use namada::proof_of_stake::{
     delegator_rewards_products_handle, find_validator_by_raw_hash,
     read_last_block_proposer_address, read_pos_params, read_total_stake,
@@ -18,9 +20,12 @@ use namada::proof_of_stake::{
     write_last_block_proposer_address,
 };
 use namada::types::address::Address;
+use namada::types::event::{attributes, FeeRefund, IbcPacket, TxApplied};
 use namada::types::key::tm_raw_hash_to_string;
 use namada::types::storage::{BlockHash, BlockResults, Epoch, Header};
 use namada::types::token::{total_supply_key, Amount};
+use namada::types::transaction::protocol as protocol_tx;
+use namada::types::transaction::TxResultDetails;
 use rust_decimal::prelude::Decimal;
 
 use super::governance::execute_governance_proposals;
@@ -85,11 +90,23 @@ where
             self.wl_storage.storage.update_epoch_blocks_delay
         );
 
+        // Migrate the layout of the storage in the block at the agreed
+        // height of every migration, before any tx is applied
+        migrations::run(&mut self.wl_storage, &migrations::all(), height)?;
+        self.wl_storage.commit_tx();
+
         if new_epoch {
             namada::ledger::storage::update_allowed_conversions(
                 &mut self.wl_storage,
             )?;
 
+            // Apply the changes of the PoS parameters scheduled by governance
+            // for this epoch, before new changes are scheduled
+            namada_proof_of_stake::apply_pending_max_validator_slots(
+                &mut self.wl_storage,
+                current_epoch,
+            )?;
+
             let _proposals_result =
                 execute_governance_proposals(self, &mut response)?;
 
@@ -104,6 +121,9 @@ where
                 &namada_proof_of_stake::consensus_validator_set_handle(),
                 &namada_proof_of_stake::below_capacity_validator_set_handle(),
             )?;
+
+            // Sign the validator set of the next epoch for the bridge
+            self.broadcast_valset_update();
         }
 
         // Invariant: This has to be applied after
@@ -114,6 +134,11 @@ where
         }
 
         let wrapper_fees = self.get_wrapper_tx_fees();
+        let fee_tokens = self.get_fee_tokens();
+        let gas_limit_resolution = self.get_gas_limit_resolution();
+        // Meter the txs of this block with the current gas costs, which may
+        // have been updated by governance
+        self.gas_meter.set_gas_costs(self.get_gas_cost_table());
         let mut stats = InternalStats::default();
 
         // Tracks the accepted transactions
@@ -130,10 +155,12 @@ where
                 continue;
             };
             let tx_length = processed_tx.tx.len();
+            // The tx is no longer pending in the mempool
+            self.mempool_view.remove(&hash::Hash(tx.header_hash().0));
             // If [`process_proposal`] rejected a Tx due to invalid signature,
             // emit an event here and move on to next tx.
-            if ErrorCodes::from_u32(processed_tx.result.code).unwrap()
-                == ErrorCodes::InvalidSig
+            if ResultCode::from_u32(processed_tx.result.code).unwrap()
+                == ResultCode::InvalidSig
             {
                 let mut tx_event = match tx.header().tx_type {
                     TxType::Wrapper(_) | TxType::Protocol(_) => {
@@ -149,10 +176,11 @@ where
                         continue;
                     }
                 };
-                tx_event["code"] = processed_tx.result.code.to_string();
-                tx_event["info"] =
-                    format!("Tx rejected: {}", &processed_tx.result.info);
-                tx_event["gas_used"] = "0".into();
+                set_rejected_tx_result(
+                    &mut tx_event,
+                    ResultCode::InvalidSig,
+                    format!("Tx rejected: {}", &processed_tx.result.info),
+                );
                 response.events.push(tx_event);
                 continue;
             }
@@ -169,14 +197,14 @@ where
             let tx_type = tx.header();
             // If [`process_proposal`] rejected a Tx, emit an event here and
             // move on to next tx
-            if ErrorCodes::from_u32(processed_tx.result.code).unwrap()
-                != ErrorCodes::Ok
-            {
+            let code = ResultCode::from_u32(processed_tx.result.code).unwrap();
+            if code != ResultCode::Ok {
                 let mut tx_event = Event::new_tx_event(&tx, height.0);
-                tx_event["code"] = processed_tx.result.code.to_string();
-                tx_event["info"] =
-                    format!("Tx rejected: {}", &processed_tx.result.info);
-                tx_event["gas_used"] = "0".into();
+                set_rejected_tx_result(
+                    &mut tx_event,
+                    code,
+                    format!("Tx rejected: {}", &processed_tx.result.info),
+                );
                 response.events.push(tx_event);
                 // if the rejected tx was decrypted, remove it
                 // from the queue of txs to be processed and remove the hash
@@ -202,6 +230,8 @@ where
                 continue;
             }
 
+            // The wrapper of a decrypted tx, whose unused fee is refunded
+            let mut refunded_wrapper = None;
             let (mut tx_event, tx_unsigned_hash) = match &tx_type.tx_type {
                 TxType::Wrapper(wrapper) => {
                     stats.increment_wrapper_txs();
@@ -219,14 +249,31 @@ where
                         .write(&wrapper_tx_hash_key, vec![])
                         .expect("Error while writing tx hash to storage");
 
-                    let inner_tx_hash_key = replay_protection::get_tx_hash_key(
-                        &tx.clone().update_header(TxType::Raw).header_hash(),
-                    );
+                    let inner_tx_hash =
+                        tx.clone().update_header(TxType::Raw).header_hash();
+                    // The inner tx can no longer be replaced in the mempool
+                    self.pending_inner_txs.remove(&inner_tx_hash);
+                    let inner_tx_hash_key =
+                        replay_protection::get_tx_hash_key(&inner_tx_hash);
                     self.wl_storage
                         .storage
                         .write(&inner_tx_hash_key, vec![])
                         .expect("Error while writing tx hash to storage");
 
+                    // Advance the nonce of the fee payer
+                    if let Some(nonce) = wrapper.nonce {
+                        let nonce_key = replay_protection::get_nonce_key(
+                            &wrapper.fee_payer(),
+                        );
+                        self.wl_storage
+                            .storage
+                            .write(
+                                &nonce_key,
+                                (nonce + 1).try_to_vec().unwrap(),
+                            )
+                            .expect("Error while writing nonce to storage");
+                    }
+
                     #[cfg(not(feature = "mainnet"))]
                     let has_valid_pow =
                         self.invalidate_pow_solution_if_valid(wrapper);
@@ -239,6 +286,43 @@ where
                             address::masp()
                         };
 
+                    // The fee token may have been removed from the whitelist
+                    // since the proposal, in which case no fees can be paid
+                    let fees = namada::ledger::parameters::convert_fee_amount(
+                        &fee_tokens,
+                        &self.wl_storage.storage.native_token,
+                        &wrapper.fee.token,
+                        wrapper_fees,
+                    )
+                    .and_then(|fees| {
+                        fees.checked_add(wrapper.tip(gas_limit_resolution)?)
+                    });
+
+                    // Unshield the funds to pay the fee with, if requested.
+                    // If the unshielding fails, the fee is still charged
+                    // from the transparent balance of the fee payer.
+                    let mut unshielded = false;
+                    if let (Some(unshield), Some(fees)) =
+                        (&wrapper.unshield, fees)
+                    {
+                        match self.run_fee_unshielding(
+                            &tx,
+                            unshield,
+                            &fee_payer,
+                            &wrapper.fee.token,
+                            fees,
+                        ) {
+                            Ok(()) => unshielded = true,
+                            Err(msg) => tracing::info!(
+                                "Fee unshielding of wrapper tx {} failed, \
+                                 charging the fee from the transparent \
+                                 balance: {}",
+                                tx_event[attributes::HASH],
+                                msg
+                            ),
+                        }
+                    }
+
                     let balance_key =
                         token::balance_key(&wrapper.fee.token, &fee_payer);
                     let balance: token::Amount = self
@@ -247,15 +331,16 @@ where
                         .expect("must be able to read")
                         .unwrap_or_default();
 
-                    match balance.checked_sub(wrapper_fees) {
-                        Some(amount) => {
-                            self.wl_storage
-                                .storage
-                                .write(
-                                    &balance_key,
-                                    amount.try_to_vec().unwrap(),
-                                )
-                                .unwrap();
+                    // The fee charged to the fee payer, off which the unused
+                    // gas is refunded
+                    let charged = match fees.filter(|fees| *fees <= balance) {
+                        Some(fees) => {
+                            self.write_fee_payer_balance(
+                                &balance_key,
+                                balance - fees,
+                                unshielded,
+                            );
+                            fees
                         }
                         None => {
                             #[cfg(not(feature = "mainnet"))]
@@ -264,26 +349,27 @@ where
                             let reject = true;
                             if reject {
                                 // Burn remaining funds
-                                self.wl_storage
-                                    .storage
-                                    .write(
-                                        &balance_key,
-                                        Amount::from(0).try_to_vec().unwrap(),
-                                    )
-                                    .unwrap();
-                                tx_event["info"] =
-                                    "Insufficient balance for fee".into();
-                                tx_event["code"] = ErrorCodes::InvalidTx.into();
-                                tx_event["gas_used"] = "0".to_string();
+                                self.write_fee_payer_balance(
+                                    &balance_key,
+                                    Amount::from(0),
+                                    unshielded,
+                                );
+                                set_rejected_tx_result(
+                                    &mut tx_event,
+                                    ResultCode::InvalidTx,
+                                    "Insufficient balance for fee".into(),
+                                );
 
                                 response.events.push(tx_event);
                                 continue;
                             }
+                            Amount::default()
                         }
-                    }
+                    };
 
                     self.wl_storage.storage.tx_queue.push(TxInQueue {
                         tx: processed_tx.clone(),
+                        fee: charged,
                         #[cfg(not(feature = "mainnet"))]
                         has_valid_pow,
                     });
@@ -291,33 +377,54 @@ where
                 }
                 TxType::Decrypted(inner) => {
                     // We remove the corresponding wrapper tx from the queue
-                    let wrapper_hash = self
+                    let queued_wrapper = self
                         .wl_storage
                         .storage
                         .tx_queue
                         .pop()
-                        .expect("Missing wrapper tx in queue")
-                        .tx
-                        .clone()
-                        .update_header(TxType::Raw)
-                        .header_hash();
+                        .expect("Missing wrapper tx in queue");
+                    let mut wrapper_tx = queued_wrapper.tx;
+                    let wrapper = wrapper_tx.header().wrapper();
+                    // The decrypted tx may not consume more gas than what was
+                    // declared in its wrapper
+                    if let Some(wrapper) = &wrapper {
+                        let gas_limit = wrapper
+                            .gas_limit
+                            .checked_raw(self.get_gas_limit_resolution())
+                            .unwrap_or(u64::MAX);
+                        self.gas_meter.set_transaction_gas_limit(gas_limit);
+                    }
+                    let wrapper_hash =
+                        wrapper_tx.update_header(TxType::Raw).header_hash();
+                    refunded_wrapper =
+                        wrapper.map(|wrapper| (wrapper, queued_wrapper.fee));
                     let mut event = Event::new_tx_event(&tx, height.0);
 
                     match inner {
                         DecryptedTx::Decrypted { has_valid_pow: _ } => {
-                            if let Some(code_sec) = tx
-                                .get_section(tx.code_sechash())
-                                .and_then(Section::code_sec)
-                            {
-                                stats.increment_tx_type(
-                                    code_sec.code.hash().to_string(),
+                            let code_hashes =
+                                std::iter::once(tx.code_sechash()).chain(
+                                    tx.header
+                                        .batch
+                                        .iter()
+                                        .map(|hashes| &hashes.code_hash),
                                 );
+                            for code_hash in code_hashes {
+                                if let Some(code_sec) = tx
+                                    .get_section(code_hash)
+                                    .and_then(Section::code_sec)
+                                {
+                                    stats.increment_tx_type(
+                                        code_sec.code_hash().to_string(),
+                                    );
+                                }
                             }
                         }
                         DecryptedTx::Undecryptable => {
-                            event["log"] =
+                            event[attributes::LOG] =
                                 "Transaction could not be decrypted.".into();
-                            event["code"] = ErrorCodes::Undecryptable.into();
+                            event[attributes::CODE] =
+                                ResultCode::Undecryptable.into();
                         }
                     }
                     (event, Some(wrapper_hash))
@@ -329,16 +436,45 @@ where
                     );
                     continue;
                 }
-                TxType::Protocol(_) => {
-                    tracing::error!(
-                        "Internal logic error: FinalizeBlock received a \
-                         TxType::Protocol transaction"
-                    );
+                TxType::Protocol(protocol_tx) => {
+                    let tx_event = match &protocol_tx.tx {
+                        ProtocolTxType::OraclePrices(reports) => self
+                            .apply_oracle_prices(
+                                &tx, reports, tx_index, height,
+                            ),
+                        ProtocolTxType::ValidatorSetUpdate(update) => self
+                            .apply_valset_update(&tx, update, tx_index, height),
+                        ProtocolTxType::EthereumEvents(vote) => {
+                            let applied =
+                                self.apply_eth_events_vote(vote).map(|acted| {
+                                    format!(
+                                        "Recorded the vote of validator {} \
+                                         on {} Ethereum events, {} of which \
+                                         were acted on",
+                                        vote.validator,
+                                        vote.events.len(),
+                                        acted
+                                    )
+                                });
+                            self.protocol_tx_event(
+                                &tx, tx_index, height, applied,
+                            )
+                        }
+                        _ => {
+                            tracing::error!(
+                                "Internal logic error: FinalizeBlock \
+                                 received an unsupported TxType::Protocol \
+                                 transaction"
+                            );
+                            continue;
+                        }
+                    };
+                    response.events.push(tx_event);
                     continue;
                 }
             };
 
-            match protocol::apply_tx(
+            let gas_used = match protocol::apply_tx(
                 tx.clone(),
                 tx_length,
                 TxIndex(
@@ -355,61 +491,67 @@ where
             .map_err(Error::TxApply)
             {
                 Ok(result) => {
-                    if result.is_accepted() {
+                    let (code, initialized_accounts) = if result.is_accepted() {
                         tracing::trace!(
                             "all VPs accepted transaction {} storage \
                              modification {:#?}",
-                            tx_event["hash"],
+                            tx_event[attributes::HASH],
                             result
                         );
                         stats.increment_successful_txs();
                         self.wl_storage.commit_tx();
-                        if !tx_event.contains_key("code") {
-                            tx_event["code"] = ErrorCodes::Ok.into();
-                            self.wl_storage
-                                .storage
-                                .block
-                                .results
-                                .accept(tx_index);
-                        }
+                        let code = match tx_event.get(attributes::CODE) {
+                            Some(code) => code
+                                .parse()
+                                .ok()
+                                .and_then(ResultCode::from_u32)
+                                .expect("The tx code should be valid"),
+                            None => {
+                                self.wl_storage
+                                    .storage
+                                    .block
+                                    .results
+                                    .accept(tx_index);
+                                ResultCode::Ok
+                            }
+                        };
                         for ibc_event in &result.ibc_events {
-                            // Add the IBC event besides the tx_event
-                            let event = Event::from(ibc_event.clone());
+                            // Add the IBC event besides the tx_event, typed
+                            // if it's the event of a packet
+                            let event =
+                                match IbcPacket::from_ibc_event(ibc_event) {
+                                    Some(packet) => Event::from(packet),
+                                    None => Event::from(ibc_event.clone()),
+                                };
                             response.events.push(event);
                         }
-                        match serde_json::to_string(
-                            &result.initialized_accounts,
-                        ) {
-                            Ok(initialized_accounts) => {
-                                tx_event["initialized_accounts"] =
-                                    initialized_accounts;
-                            }
-                            Err(err) => {
-                                tracing::error!(
-                                    "Failed to serialize the initialized \
-                                     accounts: {}",
-                                    err
-                                );
-                            }
-                        }
+                        (code, result.initialized_accounts.clone())
                     } else {
                         tracing::trace!(
                             "some VPs rejected transaction {} storage \
                              modification {:#?}",
-                            tx_event["hash"],
+                            tx_event[attributes::HASH],
                             result.vps_result.rejected_vps
                         );
                         stats.increment_rejected_txs();
                         self.wl_storage.drop_tx();
-                        tx_event["code"] = ErrorCodes::InvalidTx.into();
-                    }
-                    tx_event["gas_used"] = result.gas_used.to_string();
-                    tx_event["info"] = result.to_string();
+                        (ResultCode::InvalidTx, vec![])
+                    };
+                    tx_event.extend(&TxApplied {
+                        hash: tx_event[attributes::HASH].clone(),
+                        height,
+                        code,
+                        gas_used: result.gas_used,
+                        info: result.to_string(),
+                        initialized_accounts,
+                        details: TxResultDetails::from(&result),
+                    });
+                    result.gas_used
                 }
                 Err(msg) => {
                     tracing::info!(
                         "Transaction {} failed with: {}",
-                        tx_event["hash"],
+                        tx_event[attributes::HASH],
                         msg
                     );
                     stats.increment_errored_txs();
@@ -433,15 +575,26 @@ where
                     }
 
                     self.wl_storage.drop_tx();
-                    tx_event["gas_used"] = self
-                        .gas_meter
-                        .get_current_transaction_gas()
-                        .to_string();
-                    tx_event["info"] = msg.to_string();
-                    tx_event["code"] = ErrorCodes::WasmRuntimeError.into();
+                    let gas_used = self.gas_meter.abort_transaction();
+                    tx_event.extend(&TxApplied {
+                        hash: tx_event[attributes::HASH].clone(),
+                        height,
+                        code: ResultCode::WasmRuntimeError,
+                        gas_used,
+                        info: msg.to_string(),
+                        initialized_accounts: vec![],
+                        details: TxResultDetails::error(gas_used, &msg),
+                    });
+                    gas_used
                 }
-            }
+            };
+
+            // Refund the fee paid for the gas left unused by a decrypted tx
+            let refund_event = refunded_wrapper.and_then(|(wrapper, fee)| {
+                self.refund_unused_fee(&wrapper, fee, gas_used, &tx_event)
+            });
             response.events.push(tx_event);
+            response.events.extend(refund_event);
         }
 
         stats.set_tx_cache_size(
@@ -519,12 +672,203 @@ where
             .finalize_transaction()
             .map_err(|_| Error::GasOverflow)?;
 
+        // Vote on the Ethereum events not voted on by this validator, as of
+        // the txs of this block
+        self.broadcast_eth_events_vote();
+
         self.event_log_mut().log_events(response.events.clone());
         tracing::debug!("End finalize_block {height} of epoch {current_epoch}");
 
         Ok(response)
     }
 
+    /// Execute the MASP transaction section of the given wrapper `tx` with
+    /// the `unshield` hash, unshielding `amount` of `token` to the
+    /// `fee_payer`. The unshielding is committed to the write log only if
+    /// all the VPs accept it.
+    fn run_fee_unshielding(
+        &mut self,
+        tx: &Tx,
+        unshield: &hash::Hash,
+        fee_payer: &Address,
+        token: &Address,
+        amount: Amount,
+    ) -> std::result::Result<(), String> {
+        let unshield_tx =
+            self.fee_unshielding_tx(tx, unshield, fee_payer, token, amount)?;
+        apply_fee_unshielding(
+            unshield_tx,
+            &mut self.wl_storage.write_log,
+            &self.wl_storage.storage,
+            &mut self.vp_wasm_cache,
+            &mut self.tx_wasm_cache,
+        )?;
+        self.wl_storage.commit_tx();
+        Ok(())
+    }
+
+    /// Write the balance of the fee payer of a wrapper tx after charging its
+    /// fee. If the fee was paid with `unshielded` funds, the balance must be
+    /// written through the write log, where the unshielding was committed,
+    /// lest it be overwritten when the block is committed.
+    fn write_fee_payer_balance(
+        &mut self,
+        balance_key: &Key,
+        balance: Amount,
+        unshielded: bool,
+    ) {
+        if unshielded {
+            self.wl_storage
+                .write(balance_key, balance)
+                .expect("must be able to write");
+            self.wl_storage.commit_tx();
+        } else {
+            self.wl_storage
+                .storage
+                .write(balance_key, balance.try_to_vec().unwrap())
+                .unwrap();
+        }
+    }
+
+    /// Write to storage the prices of the fee tokens aggregated from the
+    /// reports of the validators carried by a protocol tx, and return the
+    /// event of the tx.
+    fn apply_oracle_prices(
+        &mut self,
+        tx: &Tx,
+        reports: &[SignedOraclePrices],
+        tx_index: usize,
+        height: BlockHeight,
+    ) -> Event {
+        let applied =
+            self.aggregate_oracle_prices(reports).and_then(|prices| {
+                oracle::write_prices(&mut self.wl_storage, &prices)
+                    .map(|()| {
+                        format!(
+                            "Applied the oracle prices of {} tokens",
+                            prices.len()
+                        )
+                    })
+                    .map_err(|err| err.to_string())
+            });
+        self.protocol_tx_event(tx, tx_index, height, applied)
+    }
+
+    /// Record the signature of the validator set of the next epoch carried
+    /// by a protocol tx, and return the event of the tx.
+    fn apply_valset_update(
+        &mut self,
+        tx: &Tx,
+        update: &protocol_tx::ValidatorSetUpdate,
+        tx_index: usize,
+        height: BlockHeight,
+    ) -> Event {
+        let applied = self.validate_valset_update(update).and_then(|stake| {
+            bridge_storage::write_valset_update(
+                &mut self.wl_storage,
+                update,
+                stake,
+            )
+            .map(|()| {
+                format!(
+                    "Recorded the signature of the validator set of epoch {} \
+                     by validator {}",
+                    update.epoch, update.validator
+                )
+            })
+            .map_err(|err| err.to_string())
+        });
+        self.protocol_tx_event(tx, tx_index, height, applied)
+    }
+
+    /// Commit the changes of a protocol tx applied by the shell, or drop them
+    /// if it failed, and return the event of the tx.
+    fn protocol_tx_event(
+        &mut self,
+        tx: &Tx,
+        tx_index: usize,
+        height: BlockHeight,
+        applied: std::result::Result<String, String>,
+    ) -> Event {
+        let mut tx_event = Event::new_tx_event(tx, height.0);
+        let (code, info, details) = match applied {
+            Ok(info) => {
+                self.wl_storage.commit_tx();
+                self.wl_storage.storage.block.results.accept(tx_index);
+                (ResultCode::Ok, info, TxResultDetails::default())
+            }
+            Err(err) => {
+                tracing::error!("Failed to apply a protocol tx: {}", err);
+                self.wl_storage.drop_tx();
+                let details = TxResultDetails::error(0, &err);
+                (ResultCode::InvalidTx, err, details)
+            }
+        };
+        tx_event.extend(&TxApplied {
+            hash: tx_event[attributes::HASH].clone(),
+            height,
+            code,
+            gas_used: 0,
+            info,
+            initialized_accounts: vec![],
+            details,
+        });
+        tx_event
+    }
+
+    /// Refund to its payer the part of the `fee` charged for a `wrapper` that
+    /// covered the gas left unused by its decrypted tx, whose `tx_event` is
+    /// given. Returns the event of the refund, if there was anything to
+    /// refund.
+    fn refund_unused_fee(
+        &mut self,
+        wrapper: &namada::types::transaction::WrapperTx,
+        fee: Amount,
+        gas_used: u64,
+        tx_event: &Event,
+    ) -> Option<Event> {
+        let resolution = self.get_gas_limit_resolution();
+        let gas_limit = wrapper.gas_limit.checked_raw(resolution)?;
+        let unused_gas =
+            u128::from(wrapper.gas_limit.refund_amount(gas_used, resolution));
+        // The fee covers the whole gas limit, refund the unused share of it
+        let refund = u128::from(fee)
+            .checked_mul(unused_gas)?
+            .checked_div(u128::from(gas_limit))?;
+        let refund = Amount::from_micro(refund)?;
+        if refund == Amount::default() {
+            return None;
+        }
+
+        let fee_payer = if wrapper.pk != address::masp_tx_key().ref_to() {
+            wrapper.fee_payer()
+        } else {
+            address::masp()
+        };
+        let balance_key = token::balance_key(&wrapper.fee.token, &fee_payer);
+        let balance: Amount = self
+            .wl_storage
+            .read(&balance_key)
+            .expect("must be able to read")
+            .unwrap_or_default();
+        let balance = balance.checked_add(refund)?;
+        self.wl_storage
+            .write(&balance_key, balance)
+            .expect("must be able to write");
+        self.wl_storage.commit_tx();
+
+        Some(
+            FeeRefund {
+                hash: tx_event[attributes::HASH].clone(),
+                height: tx_event[attributes::HEIGHT].parse().ok()?,
+                payer: fee_payer,
+                token: wrapper.fee.token.clone(),
+                amount: refund,
+            }
+            .into(),
+        )
+    }
+
     /// Sets the metadata necessary for a new block, including
     /// the hash, height, validator changes, and evidence of
     /// byzantine behavior. Applies slashes if necessary.
@@ -890,6 +1234,21 @@ fn pos_votes_from_abci(
         .collect()
 }
 
+/// Set the result of a tx that was rejected before its application on its
+/// event
+fn set_rejected_tx_result(
+    tx_event: &mut Event,
+    code: ResultCode,
+    info: String,
+) {
+    let details = TxResultDetails::error(0, &info);
+    tx_event[attributes::CODE] = code.into();
+    tx_event[attributes::INFO] = info;
+    tx_event[attributes::GAS_USED] = "0".into();
+    tx_event[attributes::DETAILS] = serde_json::to_string(&details)
+        .expect("Serializing the result details shouldn't fail");
+}
+
 /// We test the failure cases of [`finalize_block`]. The happy flows
 /// are covered by the e2e tests.
 #[cfg(test)]
@@ -898,6 +1257,8 @@ mod test_finalize_block {
     use std::str::FromStr;
 
     use data_encoding::HEXUPPER;
+    use namada::ledger::events::EventType;
+    use namada::ledger::governance::storage as gov_storage;
     use namada::ledger::parameters::EpochDuration;
     use namada::ledger::storage_api;
     use namada::proof_of_stake::btree_set::BTreeSetShims;
@@ -917,7 +1278,10 @@ mod test_finalize_block {
         validator_slashes_handle, validator_state_handle, write_pos_params,
     };
     use namada::proto::{Code, Data, Section, Signature};
-    use namada::types::governance::ProposalVote;
+    use namada::types::event::ProposalDeposit;
+    use namada::types::governance::{
+        DepositOutcome, ParameterChange, ProposalVote,
+    };
     use namada::types::key::tm_consensus_key_raw_hash;
     use namada::types::storage::Epoch;
     use namada::types::time::DurationSecs;
@@ -925,7 +1289,10 @@ mod test_finalize_block {
     use namada::types::transaction::governance::{
         InitProposalData, ProposalType, VoteProposalData,
     };
-    use namada::types::transaction::{Fee, WrapperTx, MIN_FEE};
+    use namada::types::transaction::{
+        Fee, GasLimit, WrapperTx, MIN_FEE, WRAPPER_WIRE_VERSION,
+    };
+    use namada_test_utils::tx_data::TxWriteData;
     use namada_test_utils::TestWasms;
     use rust_decimal_macros::dec;
     use test_log::test;
@@ -970,7 +1337,7 @@ mod test_finalize_block {
                     },
                     &keypair,
                     Epoch(0),
-                    0.into(),
+                    GasLimit::from_multiplier(0),
                     #[cfg(not(feature = "mainnet"))]
                     None,
                 ))));
@@ -1030,6 +1397,74 @@ mod test_finalize_block {
         assert_eq!(counter, 3);
     }
 
+    /// Check that the fee of a wrapper tx whose fee unshielding fails is
+    /// charged from the transparent balance of the fee payer instead
+    #[test]
+    fn test_failed_fee_unshielding_charges_balance() {
+        let (mut shell, _) = setup(1);
+        let keypair = gen_keypair();
+
+        let initial_balance = Amount::whole(1000);
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&keypair.ref_to()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, initial_balance.try_to_vec().unwrap())
+            .unwrap();
+
+        // The wrapper requests the unshielding of a missing MASP tx
+        let mut wrapper = Tx::new(TxType::Wrapper(Box::new(
+            WrapperTx::new(
+                Fee {
+                    amount: MIN_FEE.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_unshield(hash::Hash([1; 32])),
+        )));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            &keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+
+        let processed_tx = ProcessedTx {
+            tx: wrapper.to_bytes(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+        let event = &shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")[0];
+        let code = event.attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ResultCode::Ok));
+
+        // The wrapper was queued and its fee paid from the balance
+        assert_eq!(shell.iter_tx_queue().count(), 1);
+        let balance: Amount = shell
+            .wl_storage
+            .read(&balance_key)
+            .expect("Test failed")
+            .expect("Test failed");
+        assert!(balance < initial_balance);
+    }
+
     /// Check that if a decrypted tx was rejected by [`process_proposal`],
     /// check that the correct event is returned. Check that it is still
     /// removed from the queue of txs to be included in the next block
@@ -1045,7 +1480,7 @@ mod test_finalize_block {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1064,7 +1499,7 @@ mod test_finalize_block {
         let processed_tx = ProcessedTx {
             tx: outer_tx.to_bytes(),
             result: TxResult {
-                code: ErrorCodes::InvalidTx.into(),
+                code: ResultCode::InvalidTx.into(),
                 info: "".into(),
             },
         };
@@ -1079,7 +1514,7 @@ mod test_finalize_block {
         {
             assert_eq!(event.event_type.to_string(), String::from("applied"));
             let code = event.attributes.get("code").expect("Test failed");
-            assert_eq!(code, &String::from(ErrorCodes::InvalidTx));
+            assert_eq!(code, &String::from(ResultCode::InvalidTx));
         }
         // check that the corresponding wrapper tx was removed from the queue
         assert!(shell.wl_storage.storage.tx_queue.is_empty());
@@ -1094,13 +1529,20 @@ mod test_finalize_block {
         let keypair = crate::wallet::defaults::daewon_keypair();
         // not valid tx bytes
         let wrapper = WrapperTx {
+            wire_version: WRAPPER_WIRE_VERSION,
             fee: Fee {
                 amount: 0.into(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
             pk: keypair.ref_to(),
             epoch: Epoch(0),
-            gas_limit: 0.into(),
+            gas_limit: GasLimit::from_multiplier(0),
+            unshield: None,
+            gas_price: None,
+            expiration_height: None,
+            nonce: None,
+            sponsor: None,
+            fee_payer_address: None,
             #[cfg(not(feature = "mainnet"))]
             pow_solution: None,
         };
@@ -1108,7 +1550,7 @@ mod test_finalize_block {
             tx: Tx::new(TxType::Decrypted(DecryptedTx::Undecryptable))
                 .to_bytes(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         };
@@ -1126,7 +1568,7 @@ mod test_finalize_block {
         {
             assert_eq!(event.event_type.to_string(), String::from("applied"));
             let code = event.attributes.get("code").expect("Test failed");
-            assert_eq!(code, &String::from(ErrorCodes::Undecryptable));
+            assert_eq!(code, &String::from(ResultCode::Undecryptable));
             let log = event.attributes.get("log").expect("Test failed");
             assert!(log.contains("Transaction could not be decrypted."))
         }
@@ -1166,7 +1608,8 @@ mod test_finalize_block {
                     },
                     &keypair,
                     Epoch(0),
-                    0.into(),
+                    // enough gas to run the decrypted tx
+                    GasLimit::from_multiplier(1_000),
                     #[cfg(not(feature = "mainnet"))]
                     None,
                 ))));
@@ -1188,7 +1631,7 @@ mod test_finalize_block {
             processed_txs.push(ProcessedTx {
                 tx: outer_tx.to_bytes(),
                 result: TxResult {
-                    code: ErrorCodes::Ok.into(),
+                    code: ResultCode::Ok.into(),
                     info: "".into(),
                 },
             });
@@ -1203,7 +1646,7 @@ mod test_finalize_block {
                     },
                     &keypair,
                     Epoch(0),
-                    0.into(),
+                    GasLimit::from_multiplier(0),
                     #[cfg(not(feature = "mainnet"))]
                     None,
                 ))));
@@ -1223,7 +1666,7 @@ mod test_finalize_block {
             processed_txs.push(ProcessedTx {
                 tx: wrapper_tx.to_bytes(),
                 result: TxResult {
-                    code: ErrorCodes::Ok.into(),
+                    code: ResultCode::Ok.into(),
                     info: "".into(),
                 },
             });
@@ -1238,6 +1681,7 @@ mod test_finalize_block {
             })
             .expect("Test failed")
             .iter()
+            .filter(|event| event.event_type != EventType::FeeRefund)
             .enumerate()
         {
             if index < 2 {
@@ -1248,7 +1692,7 @@ mod test_finalize_block {
                 );
                 let code =
                     event.attributes.get("code").expect("Test failed").as_str();
-                assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+                assert_eq!(code, String::from(ResultCode::Ok).as_str());
             } else {
                 // these should be accepted decrypted txs
                 assert_eq!(
@@ -1257,7 +1701,7 @@ mod test_finalize_block {
                 );
                 let code =
                     event.attributes.get("code").expect("Test failed").as_str();
-                assert_eq!(code, String::from(ErrorCodes::Ok).as_str());
+                assert_eq!(code, String::from(ResultCode::Ok).as_str());
             }
         }
 
@@ -1275,6 +1719,198 @@ mod test_finalize_block {
         assert_eq!(counter, 2);
     }
 
+    /// Test that the part of the fee charged for a wrapper covering the gas
+    /// left unused by its decrypted tx is refunded to its fee payer
+    #[test]
+    fn test_unused_fee_refunded() {
+        let (mut shell, _) = setup(1);
+        let keypair = gen_keypair();
+        let fee_payer = Address::from(&keypair.ref_to());
+        let initial_balance = Amount::whole(1000);
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &fee_payer,
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(&balance_key, initial_balance.try_to_vec().unwrap())
+            .unwrap();
+
+        let mut outer_tx = Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount: MIN_FEE.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            GasLimit::from_multiplier(1_000),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        ))));
+        outer_tx.header.chain_id = shell.chain_id.clone();
+        outer_tx.set_code(Code::new(TestWasms::TxNoOp.read_bytes()));
+        outer_tx.set_data(Data::new(
+            "Decrypted transaction data".as_bytes().to_owned(),
+        ));
+        outer_tx.add_section(Section::Signature(Signature::new(
+            &outer_tx.header_hash(),
+            &keypair,
+        )));
+        outer_tx.encrypt(&Default::default());
+
+        // Charge the fee of the wrapper in a first block
+        let processed_tx = ProcessedTx {
+            tx: outer_tx.to_bytes(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+        let event = &shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")[0];
+        let code = event.attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ResultCode::Ok));
+        let balance: Amount = shell
+            .wl_storage
+            .read(&balance_key)
+            .expect("Test failed")
+            .expect("Test failed");
+        let fee = initial_balance - balance;
+        assert_ne!(fee, Amount::default());
+        let queued = shell.iter_tx_queue().next().expect("Test failed");
+        assert_eq!(queued.fee, fee);
+        shell.wl_storage.commit_block().unwrap();
+
+        // The refund doesn't depend on the fee parameters at the time of the
+        // decrypted tx
+        shell
+            .wl_storage
+            .write(&params_storage::get_wrapper_tx_fees_key(), fee + fee)
+            .expect("Test failed");
+
+        outer_tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted {
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow: false,
+        }));
+        outer_tx.decrypt(<EllipticCurve as PairingEngine>::G2Affine::prime_subgroup_generator())
+            .expect("Test failed");
+        let processed_tx = ProcessedTx {
+            tx: outer_tx.to_bytes(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let events = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        assert_eq!(events.len(), 2);
+        let code = events[0].attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ResultCode::Ok));
+
+        // check that the refund was credited to the fee payer
+        let refund_event = &events[1];
+        assert_eq!(
+            refund_event.event_type.to_string(),
+            String::from("fee_refund")
+        );
+        let refund: FeeRefund = refund_event.parse().expect("Test failed");
+        assert_eq!(refund.hash, events[0]["hash"]);
+        assert_eq!(refund.payer, fee_payer);
+        let gas_used: u64 = events[0]["gas_used"].parse().expect("Test failed");
+        let resolution = shell.get_gas_limit_resolution();
+        let gas_limit = GasLimit::from_multiplier(1_000);
+        let unused_gas =
+            u128::from(gas_limit.refund_amount(gas_used, resolution));
+        let raw_gas_limit = gas_limit.checked_raw(resolution).unwrap();
+        let expected = u128::from(fee) * unused_gas / u128::from(raw_gas_limit);
+        let expected = Amount::from_micro(expected).expect("Test failed");
+        assert_ne!(expected, Amount::default());
+        assert_eq!(refund.amount, expected);
+        let balance: Amount = shell
+            .wl_storage
+            .read(&balance_key)
+            .expect("Test failed")
+            .expect("Test failed");
+        assert_eq!(balance, initial_balance - fee + expected);
+    }
+
+    /// Test that the inner txs of a batch are applied atomically: if one of
+    /// them fails, the modifications of the others are dropped too
+    #[test]
+    fn test_batch_is_atomic() {
+        let (mut shell, _) = setup(1);
+        let keypair = gen_keypair();
+
+        let written_key = Key::parse("batched").expect("Test failed");
+        let mut outer_tx = Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount: MIN_FEE.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            GasLimit::from_multiplier(1_000),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        ))));
+        outer_tx.header.chain_id = shell.chain_id.clone();
+        outer_tx.set_code(Code::new(TestWasms::TxWriteStorageKey.read_bytes()));
+        outer_tx.set_data(Data::new(
+            TxWriteData {
+                key: written_key.clone(),
+                value: "value".as_bytes().to_owned(),
+            }
+            .try_to_vec()
+            .expect("Test failed"),
+        ));
+        // The second inner tx has no valid code and fails
+        outer_tx.add_inner_tx(
+            Code::new("wasm_code".as_bytes().to_owned()),
+            Data::new("transaction data".as_bytes().to_owned()),
+        );
+        outer_tx.encrypt(&Default::default());
+        shell.enqueue_tx(outer_tx.clone());
+        outer_tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted {
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow: false,
+        }));
+        outer_tx.decrypt(<EllipticCurve as PairingEngine>::G2Affine::prime_subgroup_generator())
+            .expect("Test failed");
+        let processed_tx = ProcessedTx {
+            tx: outer_tx.to_bytes(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let event = &shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")[0];
+        let code = event.attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ResultCode::WasmRuntimeError));
+        assert!(
+            shell
+                .wl_storage
+                .read_bytes(&written_key)
+                .expect("Test failed")
+                .is_none()
+        );
+    }
+
     /// Test that the finalize block handler never commits changes directly to
     /// the DB.
     #[test]
@@ -1380,7 +2016,7 @@ mod test_finalize_block {
         let votes = vec![VoteInfo {
             validator: Some(Validator {
                 address: proposer_address.clone(),
-                power: u64::from(val_stake) as i64,
+                power: val_stake.checked_to_u64().unwrap() as i64,
             }),
             signed_last_block: true,
         }];
@@ -1408,6 +2044,224 @@ mod test_finalize_block {
         }
     }
 
+    /// Pass and execute a proposal with the given parameter changes
+    fn execute_parameter_changes(
+        shell: &mut TestShell,
+        changes: Vec<ParameterChange>,
+    ) {
+        let validator = shell.mode.get_validator_address().unwrap().clone();
+        let proposal_id = 0;
+        shell.proposal_data.insert(proposal_id);
+
+        let proposal = InitProposalData {
+            id: Some(proposal_id),
+            content: vec![],
+            author: validator.clone(),
+            voting_start_epoch: Epoch::default(),
+            voting_end_epoch: Epoch::default().next(),
+            grace_epoch: Epoch::default().next(),
+            r#type: ProposalType::ParameterChange(changes),
+        };
+        storage_api::governance::init_proposal(&mut shell.wl_storage, proposal)
+            .unwrap();
+        let vote = VoteProposalData {
+            id: proposal_id,
+            vote: ProposalVote::Yay(
+                namada::types::governance::VoteType::Default,
+            ),
+            voter: validator,
+            delegations: vec![],
+        };
+        storage_api::governance::vote_proposal(&mut shell.wl_storage, vote)
+            .unwrap();
+
+        let mut response = shim::response::FinalizeBlock::default();
+        execute_governance_proposals(shell, &mut response).unwrap();
+    }
+
+    /// Test that the parameter changes of a passed proposal are written to
+    /// storage when the proposal is executed
+    #[test]
+    fn test_parameter_change_proposal() {
+        let (mut shell, _) = setup(1);
+        let max_validator_slots =
+            namada_proof_of_stake::read_pos_params(&shell.wl_storage)
+                .unwrap()
+                .max_validator_slots;
+
+        execute_parameter_changes(
+            &mut shell,
+            vec![
+                ParameterChange::GasLimitResolution(5_000),
+                ParameterChange::MinGasPrice(Amount::from(3)),
+                ParameterChange::EpochDuration {
+                    min_num_of_blocks: 7,
+                    min_duration: 60,
+                },
+                ParameterChange::PosMaxValidatorSlots(42),
+            ],
+        );
+
+        assert_eq!(
+            namada::ledger::parameters::read_gas_limit_resolution_parameter(
+                &shell.wl_storage
+            )
+            .unwrap(),
+            5_000
+        );
+        assert_eq!(
+            namada::ledger::parameters::read_min_gas_price_parameter(
+                &shell.wl_storage
+            )
+            .unwrap(),
+            Amount::from(3)
+        );
+        assert_eq!(
+            namada::ledger::parameters::read_epoch_duration_parameter(
+                &shell.wl_storage
+            )
+            .unwrap(),
+            EpochDuration {
+                min_num_of_blocks: 7,
+                min_duration: DurationSecs(60),
+            }
+        );
+        // The max validator slots only change at the pipeline epoch
+        let pos_params =
+            namada_proof_of_stake::read_pos_params(&shell.wl_storage).unwrap();
+        assert_eq!(pos_params.max_validator_slots, max_validator_slots);
+    }
+
+    /// Test that a new maximum number of consensus validators passed by
+    /// governance takes effect at the pipeline epoch
+    #[test]
+    fn test_max_validator_slots_change() {
+        let (mut shell, _) = setup(1);
+        let params = read_pos_params(&shell.wl_storage).unwrap();
+        let max_validator_slots = params.max_validator_slots;
+
+        let validator = shell.mode.get_validator_address().unwrap().clone();
+        let pkh = get_pkh_from_address(
+            &shell.wl_storage,
+            &params,
+            validator,
+            Epoch::default(),
+        );
+        next_block_for_inflation(&mut shell, pkh.clone(), vec![], None);
+        let votes = get_default_true_votes(&shell.wl_storage, Epoch::default());
+
+        let current_epoch = shell.wl_storage.storage.block.epoch;
+        execute_parameter_changes(
+            &mut shell,
+            vec![ParameterChange::PosMaxValidatorSlots(42)],
+        );
+        let pipeline_epoch = current_epoch + params.pipeline_len;
+
+        // Unchanged in the epochs before the pipeline epoch
+        while shell.wl_storage.storage.block.epoch.next() < pipeline_epoch {
+            advance_epoch(&mut shell, &pkh, &votes, None);
+            let params = read_pos_params(&shell.wl_storage).unwrap();
+            assert_eq!(params.max_validator_slots, max_validator_slots);
+        }
+
+        let epoch = advance_epoch(&mut shell, &pkh, &votes, None);
+        assert_eq!(epoch, pipeline_epoch);
+        let params = read_pos_params(&shell.wl_storage).unwrap();
+        assert_eq!(params.max_validator_slots, 42);
+        let pending =
+            namada_proof_of_stake::pending_max_validator_slots_handle()
+                .get(&shell.wl_storage, &pipeline_epoch)
+                .unwrap();
+        assert!(pending.is_none());
+    }
+
+    /// Test that the funds of a rejected proposal are burned if too few
+    /// voted on it, and refunded to its author otherwise
+    #[test]
+    fn test_proposal_deposit_outcome() {
+        let (mut shell, _) = setup(1);
+        let validator = shell.mode.get_validator_address().unwrap().clone();
+        let native_token = shell.wl_storage.storage.native_token.clone();
+        let read_balance = |shell: &TestShell| {
+            storage_api::token::read_balance(
+                &shell.wl_storage,
+                &native_token,
+                &validator,
+            )
+            .unwrap()
+        };
+        let read_supply = |shell: &TestShell| {
+            storage_api::token::read_total_supply(
+                &shell.wl_storage,
+                &native_token,
+            )
+            .unwrap()
+        };
+
+        // The first proposal isn't voted on and the second one is rejected
+        // by the only validator
+        for (proposal_id, vote) in [(0, None), (1, Some(ProposalVote::Nay))] {
+            shell.proposal_data.insert(proposal_id);
+            let proposal = InitProposalData {
+                id: Some(proposal_id),
+                content: vec![],
+                author: validator.clone(),
+                voting_start_epoch: Epoch::default(),
+                voting_end_epoch: Epoch::default().next(),
+                grace_epoch: Epoch::default().next(),
+                r#type: ProposalType::Default(None),
+            };
+            storage_api::governance::init_proposal(
+                &mut shell.wl_storage,
+                proposal,
+            )
+            .unwrap();
+            if let Some(vote) = vote {
+                let vote = VoteProposalData {
+                    id: proposal_id,
+                    vote,
+                    voter: validator.clone(),
+                    delegations: vec![],
+                };
+                storage_api::governance::vote_proposal(
+                    &mut shell.wl_storage,
+                    vote,
+                )
+                .unwrap();
+            }
+        }
+        let funds: Amount = shell
+            .wl_storage
+            .read(&gov_storage::get_funds_key(0))
+            .unwrap()
+            .unwrap();
+        let balance = read_balance(&shell);
+        let supply = read_supply(&shell);
+
+        let mut response = shim::response::FinalizeBlock::default();
+        execute_governance_proposals(&mut shell, &mut response).unwrap();
+
+        let mut outcomes: Vec<_> = response
+            .events
+            .iter()
+            .filter(|event| event.event_type == EventType::ProposalDeposit)
+            .map(|event| {
+                let deposit: ProposalDeposit = event.parse().unwrap();
+                (deposit.proposal_id, deposit.outcome, deposit.amount)
+            })
+            .collect();
+        outcomes.sort_by_key(|(proposal_id, _, _)| *proposal_id);
+        assert_eq!(
+            outcomes,
+            vec![
+                (0, DepositOutcome::Burned, funds),
+                (1, DepositOutcome::Refunded, funds),
+            ]
+        );
+        assert_eq!(read_balance(&shell), balance + funds);
+        assert_eq!(read_supply(&shell), supply - funds);
+    }
+
     /// A unit test for PoS inflationary rewards
     #[test]
     fn test_inflation_accounting() {
@@ -1458,28 +2312,28 @@ mod test_finalize_block {
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh1.clone(),
-                    power: u64::from(val1.bonded_stake) as i64,
+                    power: val1.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: true,
             },
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh2.clone(),
-                    power: u64::from(val2.bonded_stake) as i64,
+                    power: val2.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: true,
             },
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh3.clone(),
-                    power: u64::from(val3.bonded_stake) as i64,
+                    power: val3.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: true,
             },
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh4.clone(),
-                    power: u64::from(val4.bonded_stake) as i64,
+                    power: val4.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: true,
             },
@@ -1567,28 +2421,28 @@ mod test_finalize_block {
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh1.clone(),
-                    power: u64::from(val1.bonded_stake) as i64,
+                    power: val1.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: true,
             },
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh2,
-                    power: u64::from(val2.bonded_stake) as i64,
+                    power: val2.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: true,
             },
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh3,
-                    power: u64::from(val3.bonded_stake) as i64,
+                    power: val3.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: true,
             },
             VoteInfo {
                 validator: Some(Validator {
                     address: pkh4,
-                    power: u64::from(val4.bonded_stake) as i64,
+                    power: val4.bonded_stake.checked_to_u64().unwrap() as i64,
                 }),
                 signed_last_block: false,
             },
@@ -1732,7 +2586,7 @@ mod test_finalize_block {
                 },
                 &keypair,
                 Epoch(0),
-                0.into(),
+                GasLimit::from_multiplier(0),
                 #[cfg(not(feature = "mainnet"))]
                 None,
             ))));
@@ -1762,33 +2616,33 @@ mod test_finalize_block {
         let processed_tx = ProcessedTx {
             tx: decrypted_tx.to_bytes(),
             result: TxResult {
-                code: ErrorCodes::Ok.into(),
+                code: ResultCode::Ok.into(),
                 info: "".into(),
             },
         };
         shell.enqueue_tx(wrapper_tx);
 
-        let _event = &shell
+        let event = &shell
             .finalize_block(FinalizeBlock {
                 txs: vec![processed_tx],
                 ..Default::default()
             })
             .expect("Test failed")[0];
 
-        // FIXME: uncomment when proper gas metering is in place
-        // // Check inner tx hash has been removed from storage
-        // assert_eq!(event.event_type.to_string(), String::from("applied"));
-        // let code = event.attributes.get("code").expect("Test
-        // failed").as_str(); assert_eq!(code,
-        // String::from(ErrorCodes::WasmRuntimeError).as_str());
+        // The wrapper declared no gas, so the decrypted tx runs out of it.
+        // Check inner tx hash has been removed from storage
+        assert_eq!(event.event_type.to_string(), String::from("applied"));
+        let code = event.attributes.get("code").expect("Test failed").as_str();
+        assert_eq!(code, String::from(ResultCode::WasmRuntimeError).as_str());
 
-        // assert!(
-        //     !shell
-        //         .storage
-        //         .has_key(&inner_hash_key)
-        //         .expect("Test failed")
-        //         .0
-        // )
+        assert!(
+            !shell
+                .wl_storage
+                .storage
+                .has_key(&inner_hash_key)
+                .expect("Test failed")
+                .0
+        )
     }
 
     #[test]
@@ -2964,10 +3818,11 @@ mod test_finalize_block {
                     val.address.clone(),
                     epoch,
                 );
+                let power = val.bonded_stake.checked_to_u64().unwrap() as i64;
                 VoteInfo {
                     validator: Some(Validator {
                         address: pkh,
-                        power: u64::from(val.bonded_stake) as i64,
+                        power,
                     }),
                     signed_last_block: true,
                 }
@@ -7,15 +7,22 @@ use namada::ledger::governance::{
 use namada::ledger::native_vp::governance::utils::{
     compute_tally, get_proposal_votes, ProposalEvent,
 };
+use namada::ledger::parameters::{self, EpochDuration};
 use namada::ledger::protocol;
 use namada::ledger::storage::types::encode;
 use namada::ledger::storage::{DBIter, StorageHasher, DB};
-use namada::ledger::storage_api::{token, StorageWrite};
-use namada::proof_of_stake::read_total_stake;
+use namada::ledger::storage_api::{self, token, StorageWrite};
+use namada::proof_of_stake::{
+    read_total_stake, schedule_max_validator_slots, write_pos_params,
+};
 use namada::proto::{Code, Data};
 use namada::types::address::Address;
-use namada::types::governance::{Council, Tally, TallyResult, VotePower};
+use namada::types::event::ProposalDeposit;
+use namada::types::governance::{
+    Council, DepositOutcome, ParameterChange, Tally, TallyResult, VotePower,
+};
 use namada::types::storage::Epoch;
+use namada::types::time::DurationSecs;
 
 use super::*;
 
@@ -34,6 +41,10 @@ where
     H: StorageHasher + Sync + 'static,
 {
     let mut proposals_result = ProposalsResult::default();
+    let min_quorum_key = gov_storage::get_min_proposal_quorum_key();
+    let min_quorum = shell
+        .read_storage_key::<Decimal>(&min_quorum_key)
+        .unwrap_or_default();
 
     for id in std::mem::take(&mut shell.proposal_data) {
         let proposal_funds_key = gov_storage::get_funds_key(id);
@@ -68,13 +79,13 @@ where
         let total_stake =
             read_total_stake(&shell.wl_storage, &params, proposal_end_epoch)
                 .map_err(|msg| Error::BadProposal(id, msg.to_string()))?;
-        let total_stake = VotePower::from(u64::from(total_stake));
-        let tally_result = compute_tally(votes, total_stake, &proposal_type)
-            .map_err(|msg| Error::BadProposal(id, msg.to_string()))?
-            .result;
+        let total_stake = VotePower::from(total_stake);
+        let proposal_result = compute_tally(votes, total_stake, &proposal_type)
+            .map_err(|msg| Error::BadProposal(id, msg.to_string()))?;
+        let has_quorum = proposal_result.has_quorum(min_quorum);
 
         // Execute proposal if succesful
-        let transfer_address = match tally_result {
+        let deposit_outcome = match proposal_result.result {
             TallyResult::Passed(tally) => {
                 let (successful_execution, proposal_event) = match tally {
                     Tally::Default => execute_default_proposal(shell, id),
@@ -82,24 +93,22 @@ where
                         execute_pgf_proposal(id, council)
                     }
                     Tally::ETHBridge => execute_eth_proposal(id),
+                    Tally::ParameterChange => {
+                        execute_parameter_change_proposal(
+                            shell,
+                            id,
+                            &proposal_type,
+                        )
+                    }
                 };
 
                 response.events.push(proposal_event);
                 if successful_execution {
                     proposals_result.passed.push(id);
-                    shell
-                        .read_storage_key::<Address>(
-                            &gov_storage::get_author_key(id),
-                        )
-                        .ok_or_else(|| {
-                            Error::BadProposal(
-                                id,
-                                "Invalid proposal author.".to_string(),
-                            )
-                        })?
+                    DepositOutcome::Refunded
                 } else {
                     proposals_result.rejected.push(id);
-                    slash_fund_address
+                    DepositOutcome::Slashed
                 }
             }
             TallyResult::Rejected => {
@@ -114,22 +123,67 @@ where
                 response.events.push(proposal_event);
                 proposals_result.rejected.push(id);
 
-                slash_fund_address
+                // The funds of a proposal that too few voted on are burned,
+                // to deter spam
+                if has_quorum {
+                    DepositOutcome::Refunded
+                } else {
+                    DepositOutcome::Burned
+                }
             }
         };
 
         let native_token = shell.wl_storage.storage.native_token.clone();
-        // transfer proposal locked funds
-        token::transfer(
-            &mut shell.wl_storage,
-            &native_token,
-            &gov_address,
-            &transfer_address,
-            funds,
-        )
-        .expect(
-            "Must be able to transfer governance locked funds after proposal \
-             has been tallied",
+        // refund, slash or burn proposal locked funds
+        match deposit_outcome {
+            DepositOutcome::Refunded | DepositOutcome::Slashed => {
+                let transfer_address =
+                    if deposit_outcome == DepositOutcome::Refunded {
+                        shell
+                            .read_storage_key::<Address>(
+                                &gov_storage::get_author_key(id),
+                            )
+                            .ok_or_else(|| {
+                                Error::BadProposal(
+                                    id,
+                                    "Invalid proposal author.".to_string(),
+                                )
+                            })?
+                    } else {
+                        slash_fund_address
+                    };
+                token::transfer(
+                    &mut shell.wl_storage,
+                    &native_token,
+                    &gov_address,
+                    &transfer_address,
+                    funds,
+                )
+                .expect(
+                    "Must be able to transfer governance locked funds after \
+                     proposal has been tallied",
+                );
+            }
+            DepositOutcome::Burned => {
+                token::burn_tokens(
+                    &mut shell.wl_storage,
+                    &native_token,
+                    &gov_address,
+                    funds,
+                )
+                .expect(
+                    "Must be able to burn governance locked funds after \
+                     proposal has been tallied",
+                );
+            }
+        }
+        response.events.push(
+            ProposalDeposit {
+                proposal_id: id,
+                outcome: deposit_outcome,
+                amount: funds,
+            }
+            .into(),
         );
     }
 
@@ -161,13 +215,23 @@ where
                 .wl_storage
                 .write(&pending_execution_key, ())
                 .expect("Should be able to write to storage.");
+            // The proposal code is metered like any tx, within the gas budget
+            // of the governance parameters, so that all the validators
+            // execute it to the same outcome
+            let mut gas_meter = BlockGasMeter::default();
+            gas_meter.set_gas_costs(shell.get_gas_cost_table());
+            if let Some(gas_budget) = shell.read_storage_key::<u64>(
+                &gov_storage::get_max_proposal_code_gas_key(),
+            ) {
+                gas_meter.set_transaction_gas_limit(gas_budget);
+            }
             let tx_result = protocol::apply_tx(
                 tx,
                 0, /*  this is used to compute the fee
                     * based on the code size. We dont
                     * need it here. */
                 TxIndex::default(),
-                &mut BlockGasMeter::default(),
+                &mut gas_meter,
                 &mut shell.wl_storage.write_log,
                 &shell.wl_storage.storage,
                 &mut shell.vp_wasm_cache,
@@ -180,6 +244,11 @@ where
                 .expect("Should be able to delete the storage.");
             match tx_result {
                 Ok(tx_result) if tx_result.is_accepted() => {
+                    tracing::info!(
+                        "Executed the code of proposal {} with {} gas",
+                        id,
+                        tx_result.gas_used
+                    );
                     shell.wl_storage.commit_tx();
                     (
                         tx_result.is_accepted(),
@@ -193,7 +262,12 @@ where
                         .into(),
                     )
                 }
-                _ => {
+                result => {
+                    tracing::info!(
+                        "Executing the code of proposal {} failed: {:?}",
+                        id,
+                        result.map(|tx_result| tx_result.vps_result)
+                    );
                     shell.wl_storage.drop_tx();
                     (
                         false,
@@ -255,3 +329,98 @@ fn execute_eth_proposal(id: u64) -> (bool, Event) {
         .into(),
     )
 }
+
+fn execute_parameter_change_proposal<D, H>(
+    shell: &mut Shell<D, H>,
+    id: u64,
+    proposal_type: &ProposalType,
+) -> (bool, Event)
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let changes = match proposal_type {
+        ProposalType::ParameterChange(changes) => changes.as_slice(),
+        _ => &[],
+    };
+    let result = changes
+        .iter()
+        .try_for_each(|change| apply_parameter_change(shell, change));
+    let successful_execution = match result {
+        Ok(()) => {
+            shell.wl_storage.commit_tx();
+            true
+        }
+        Err(err) => {
+            tracing::error!(
+                "Applying the parameter changes of proposal {} failed: {}",
+                id,
+                err
+            );
+            shell.wl_storage.drop_tx();
+            false
+        }
+    };
+    (
+        successful_execution,
+        ProposalEvent::new(
+            EventType::Proposal.to_string(),
+            TallyResult::Passed(Tally::ParameterChange),
+            id,
+            false,
+            successful_execution,
+        )
+        .into(),
+    )
+}
+
+/// Write the new value of a protocol parameter to storage, or schedule it for
+/// the pipeline epoch in case of the maximum number of consensus validators
+fn apply_parameter_change<D, H>(
+    shell: &mut Shell<D, H>,
+    change: &ParameterChange,
+) -> storage_api::Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let storage = &mut shell.wl_storage;
+    match change {
+        ParameterChange::GasLimitResolution(resolution) => {
+            parameters::update_gas_limit_resolution_parameter(
+                storage, resolution,
+            )
+        }
+        ParameterChange::WrapperTxFees(fees) => {
+            parameters::update_wrapper_tx_fees_parameter(storage, fees)
+        }
+        ParameterChange::MinGasPrice(price) => {
+            parameters::update_min_gas_price_parameter(storage, price)
+        }
+        ParameterChange::EpochDuration {
+            min_num_of_blocks,
+            min_duration,
+        } => parameters::update_epoch_parameter(
+            storage,
+            &EpochDuration {
+                min_num_of_blocks: *min_num_of_blocks,
+                min_duration: DurationSecs(*min_duration),
+            },
+        ),
+        ParameterChange::PosMaxValidatorSlots(slots) => {
+            // The validator sets are updated at the pipeline epoch
+            let current_epoch = storage.storage.block.epoch;
+            schedule_max_validator_slots(storage, *slots, current_epoch)
+        }
+        ParameterChange::PosMaxInflationRate(rate) => {
+            let mut params = read_pos_params(storage)?;
+            params.max_inflation_rate = *rate;
+            write_pos_params(storage, params)
+        }
+        ParameterChange::PosTargetStakedRatio(ratio) => {
+            let mut params = read_pos_params(storage)?;
+            params.target_staked_ratio = *ratio;
+            write_pos_params(storage, params)
+        }
+    }
+}
@@ -7,7 +7,7 @@ use namada::core::ledger::testnet_pow;
 use namada::ledger::parameters::{self, Parameters};
 use namada::ledger::pos::{into_tm_voting_power, staking_token_address};
 use namada::ledger::storage_api::token::{
-    credit_tokens, read_balance, read_total_supply,
+    credit_tokens, read_balance, read_total_supply, write_hook, write_metadata,
 };
 use namada::ledger::storage_api::{ResultExt, StorageRead, StorageWrite};
 use namada::types::hash::Hash as CodeHash;
@@ -87,6 +87,12 @@ where
             pos_gain_d,
             staked_ratio,
             pos_inflation_amount,
+            gas_limit_resolution,
+            fee_tokens,
+            gas_cost_table,
+            min_gas_price,
+            max_block_gas,
+            max_tx_bytes,
             wrapper_tx_fees,
         } = genesis.parameters;
         #[cfg(not(feature = "mainnet"))]
@@ -179,6 +185,12 @@ where
             pos_gain_d,
             staked_ratio,
             pos_inflation_amount,
+            gas_limit_resolution,
+            fee_tokens,
+            gas_cost_table,
+            min_gas_price,
+            max_block_gas,
+            max_tx_bytes,
             #[cfg(not(feature = "mainnet"))]
             faucet_account,
             #[cfg(not(feature = "mainnet"))]
@@ -194,6 +206,10 @@ where
             .init_storage(&mut self.wl_storage)
             .expect("Initializing governance parameters must not fail");
 
+        // A new chain starts with the latest storage layout
+        migrations::init_version(&mut self.wl_storage, &migrations::all())
+            .expect("Initializing the storage version must not fail");
+
         // Depends on parameters being initialized
         self.wl_storage
             .storage
@@ -271,11 +287,14 @@ where
         }
 
         // Initialize genesis token accounts
+        let masp_rewards = address::masp_rewards();
         for genesis::TokenAccount {
             address,
             vp_code_path,
             vp_sha256,
             balances,
+            metadata,
+            hook,
         } in genesis.token_accounts
         {
             let vp_code_hash =
@@ -302,6 +321,18 @@ where
             self.wl_storage
                 .write_bytes(&Key::validity_predicate(&address), vp_code_hash)
                 .unwrap();
+            write_metadata(&mut self.wl_storage, &address, &metadata).unwrap();
+            if let Some(hook) = hook {
+                write_hook(&mut self.wl_storage, &address, &hook).unwrap();
+            }
+            if let Some(rate) = masp_rewards.get(&address) {
+                parameters::update_masp_reward_rate_parameter(
+                    &mut self.wl_storage,
+                    &address,
+                    *rate,
+                )
+                .unwrap();
+            }
 
             for (owner, amount) in balances {
                 credit_tokens(&mut self.wl_storage, &address, &owner, amount)
@@ -6,15 +6,20 @@
 //! (unless we can simply overwrite them in the next block).
 //! More info in <https://github.com/anoma/namada/issues/362>.
 mod block_space_alloc;
+mod eth_events;
 mod finalize_block;
 mod governance;
 mod init_chain;
+mod oracle;
 mod prepare_proposal;
 mod process_proposal;
 mod queries;
+mod replace_by_fee;
+mod snapshots;
 mod stats;
+mod valset_update;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::mem;
 use std::path::{Path, PathBuf};
@@ -22,9 +27,13 @@ use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use namada::ledger::alloc_log::{
+    AllocBin, AllocDecision, AllocReplayLog, AllocRole,
+};
 use namada::ledger::events::log::EventLog;
 use namada::ledger::events::Event;
-use namada::ledger::gas::BlockGasMeter;
+use namada::ledger::gas::{BlockGasMeter, GasCostTable};
+use namada::ledger::mempool_view::{MempoolView, PendingWrapper};
 use namada::ledger::pos::namada_proof_of_stake::types::{
     ConsensusValidator, ValidatorSetUpdate,
 };
@@ -33,27 +42,30 @@ use namada::ledger::storage::{
     DBIter, Sha256Hasher, Storage, StorageHasher, TempWlStorage, WlStorage, DB,
 };
 use namada::ledger::storage_api::{self, StorageRead, StorageWrite};
+use namada::ledger::tx::TX_TRANSFER_WASM;
 use namada::ledger::{ibc, pos, protocol, replay_protection};
+use namada::proof_of_stake::pos_queries::PosQueries;
 use namada::proof_of_stake::{self, process_slashes, read_pos_params, slash};
-use namada::proto::{self, Section, Tx};
+use namada::proto::{self, Code, Data, Section, Tx};
 use namada::types::address::{masp, masp_tx_key, Address};
 use namada::types::chain::ChainId;
 use namada::types::internal::TxInQueue;
+use namada::types::key::signer::Signer;
 use namada::types::key::*;
-use namada::types::storage::{BlockHeight, Key, TxIndex};
+use namada::types::storage::{BlockHeight, Epoch, Key, TxIndex};
 use namada::types::time::{DateTimeUtc, TimeZone, Utc};
 use namada::types::token::{self};
+use namada::types::transaction::protocol::ProtocolTxType;
 #[cfg(not(feature = "mainnet"))]
 use namada::types::transaction::MIN_FEE;
 use namada::types::transaction::{
     hash_tx, verify_decrypted_correctly, AffineCurve, DecryptedTx,
-    EllipticCurve, PairingEngine, TxType,
+    EllipticCurve, PairingEngine, ResultCode, TxType,
 };
 use namada::types::{address, hash};
 use namada::vm::wasm::{TxCache, VpCache};
 use namada::vm::WasmCacheRwAccess;
-use num_derive::{FromPrimitive, ToPrimitive};
-use num_traits::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
 use thiserror::Error;
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -67,6 +79,7 @@ use crate::facade::tendermint_proto::abci::{
 use crate::facade::tendermint_proto::crypto::public_key;
 use crate::facade::tendermint_proto::google::protobuf::Timestamp;
 use crate::facade::tower_abci::{request, response};
+use crate::node::ledger::remote_signer::RemoteSigner;
 use crate::node::ledger::shims::abcipp_shim_types::shim;
 use crate::node::ledger::shims::abcipp_shim_types::shim::response::TxResult;
 use crate::node::ledger::{storage, tendermint_node};
@@ -125,57 +138,6 @@ impl From<Error> for TxResult {
     }
 }
 
-/// The different error codes that the ledger may
-/// send back to a client indicating the status
-/// of their submitted tx
-#[derive(Debug, Copy, Clone, FromPrimitive, ToPrimitive, PartialEq)]
-pub enum ErrorCodes {
-    Ok = 0,
-    InvalidDecryptedChainId = 1,
-    ExpiredDecryptedTx = 2,
-    WasmRuntimeError = 3,
-    InvalidTx = 4,
-    InvalidSig = 5,
-    InvalidOrder = 6,
-    ExtraTxs = 7,
-    Undecryptable = 8,
-    AllocationError = 9,
-    ReplayTx = 10,
-    InvalidChainId = 11,
-    ExpiredTx = 12,
-}
-
-impl ErrorCodes {
-    /// Checks if the given [`ErrorCodes`] value is a protocol level error,
-    /// that can be recovered from at the finalize block stage.
-    pub const fn is_recoverable(&self) -> bool {
-        use ErrorCodes::*;
-        // NOTE: pattern match on all `ErrorCodes` variants, in order
-        // to catch potential bugs when adding new codes
-        match self {
-            Ok
-            | InvalidDecryptedChainId
-            | ExpiredDecryptedTx
-            | WasmRuntimeError => true,
-            InvalidTx | InvalidSig | InvalidOrder | ExtraTxs
-            | Undecryptable | AllocationError | ReplayTx | InvalidChainId
-            | ExpiredTx => false,
-        }
-    }
-}
-
-impl From<ErrorCodes> for u32 {
-    fn from(code: ErrorCodes) -> u32 {
-        code.to_u32().unwrap()
-    }
-}
-
-impl From<ErrorCodes> for String {
-    fn from(code: ErrorCodes) -> String {
-        u32::from(code).to_string()
-    }
-}
-
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub fn reset(config: config::Ledger) -> Result<()> {
@@ -192,6 +154,8 @@ pub fn reset(config: config::Ledger) -> Result<()> {
 }
 
 pub fn rollback(config: config::Ledger) -> Result<()> {
+    use crate::node::ledger::storage::DBBackend;
+
     // Rollback Tendermint state
     tracing::info!("Rollback Tendermint state");
     let tendermint_block_height =
@@ -213,6 +177,8 @@ pub(super) enum ShellMode {
     Validator {
         data: ValidatorData,
         broadcast_sender: UnboundedSender<Vec<u8>>,
+        /// The signer of protocol txs, holding the protocol key
+        protocol_signer: Box<dyn Signer>,
     },
     Full,
     Seed,
@@ -227,6 +193,16 @@ impl ShellMode {
             _ => None,
         }
     }
+
+    /// Get the signer of protocol txs if ledger is in validator mode
+    pub fn get_protocol_signer(&self) -> Option<&dyn Signer> {
+        match &self {
+            ShellMode::Validator {
+                protocol_signer, ..
+            } => Some(protocol_signer.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -270,10 +246,41 @@ where
     /// limit the how many block heights in the past can the storage be
     /// queried for reading values.
     storage_read_past_height_limit: Option<u64>,
+    /// Taken from config `mempool_min_gas_price`. The minimum gas price
+    /// that wrappers must offer to be accepted into the mempool.
+    mempool_min_gas_price: token::Amount,
+    /// Taken from config `vote_extension_block_space_pct`. The fraction of
+    /// the block space reserved for vote extension protocol txs.
+    vote_extension_block_space: block_space_alloc::threshold::Threshold,
     /// Proposal execution tracking
     pub proposal_data: HashSet<u64>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
     event_log: EventLog,
+    /// Taken from config `alloc_replay_log_capacity`. Log of block space
+    /// allocation decisions, if enabled.
+    alloc_replay_log: AllocReplayLog,
+    /// Taken from config `mempool_view_capacity`. View of the wrapper txs
+    /// pending in the mempool, if enabled.
+    mempool_view: MempoolView,
+    /// The wrappers of the inner txs pending in the mempool, which may be
+    /// replaced by wrappers offering a higher fee.
+    pending_inner_txs: replace_by_fee::PendingInnerTxs,
+    /// Taken from config `oracle_price_feed`. The file with the prices of the
+    /// fee tokens that this validator attaches to its votes, if any.
+    #[cfg(feature = "abcipp")]
+    oracle_price_feed: Option<PathBuf>,
+    /// Taken from config `ethereum_events_feed`. The file with the events of
+    /// the Ethereum bridge that this validator votes on, if any.
+    ethereum_events_feed: Option<PathBuf>,
+    /// Taken from config `snapshot_epoch_interval` and `snapshots_to_keep`.
+    /// The state sync snapshots created and served by the node, if enabled.
+    snapshots: Option<snapshots::SnapshotConfig>,
+    /// The restoration of the storage from a state sync snapshot offered by
+    /// Tendermint, with the app hash that it trusts for the snapshot
+    snapshot_restore: Option<(storage::snapshots::SnapshotRestore, Vec<u8>)>,
+    /// Taken from config `pruning`. The history of the storage that is
+    /// retained, if it's pruned.
+    pruning: Option<config::Pruning>,
 }
 
 impl<D, H> Shell<D, H>
@@ -294,10 +301,30 @@ where
     ) -> Self {
         let chain_id = config.chain_id;
         let db_path = config.shell.db_dir(&chain_id);
+        let snapshots =
+            snapshots::SnapshotConfig::new(&config.shell, &chain_id);
         let base_dir = config.shell.base_dir;
         let mode = config.tendermint.tendermint_mode;
         let storage_read_past_height_limit =
             config.shell.storage_read_past_height_limit;
+        let pruning = config.shell.pruning.clone();
+        let mempool_min_gas_price =
+            config.shell.mempool_min_gas_price.unwrap_or_default();
+        let vote_extension_block_space = config
+            .shell
+            .vote_extension_block_space_pct
+            .map(block_space_alloc::threshold::Threshold::from_percentage)
+            .unwrap_or(block_space_alloc::threshold::ONE_TENTH);
+        let alloc_replay_log = config
+            .shell
+            .alloc_replay_log_capacity
+            .map(AllocReplayLog::new)
+            .unwrap_or_default();
+        let mempool_view = config
+            .shell
+            .mempool_view_capacity
+            .map(MempoolView::new)
+            .unwrap_or_default();
         if !Path::new(&base_dir).is_dir() {
             std::fs::create_dir(&base_dir)
                 .expect("Creating directory for Namada should not fail");
@@ -321,6 +348,17 @@ where
             base_dir.join(chain_id.as_str()).join("vp_wasm_cache");
         let tx_wasm_cache_dir =
             base_dir.join(chain_id.as_str()).join("tx_wasm_cache");
+        // protocol txs are signed by the remote signer if there is one, or
+        // else by the protocol key in the wallet
+        let protocol_signer = |data: &ValidatorData| -> Box<dyn Signer> {
+            match &config.shell.protocol_remote_signer {
+                Some(remote) => Box::new(
+                    RemoteSigner::connect_lazy(remote)
+                        .expect("Invalid remote signer of protocol txs"),
+                ),
+                None => Box::new(data.keys.protocol_keypair.clone()),
+            }
+        };
         // load in keys and address from wallet if mode is set to `Validator`
         let mode = match mode {
             TendermintMode::Validator => {
@@ -345,6 +383,7 @@ where
                         .map(|data| ShellMode::Validator {
                             data: data.clone(),
                             broadcast_sender,
+                            protocol_signer: protocol_signer(data),
                         })
                         .expect(
                             "Validator data should have been stored in the \
@@ -355,15 +394,16 @@ where
                 {
                     let validator_keys =
                         crate::wallet::defaults::validator_keys();
-                    ShellMode::Validator {
-                        data: crate::wallet::ValidatorData {
-                            address: crate::wallet::defaults::validator_address(
-                            ),
-                            keys: crate::wallet::ValidatorKeys {
-                                protocol_keypair: validator_keys.0,
-                                dkg_keypair: Some(validator_keys.1),
-                            },
+                    let data = crate::wallet::ValidatorData {
+                        address: crate::wallet::defaults::validator_address(),
+                        keys: crate::wallet::ValidatorKeys {
+                            protocol_keypair: validator_keys.0,
+                            dkg_keypair: Some(validator_keys.1),
                         },
+                    };
+                    ShellMode::Validator {
+                        protocol_signer: protocol_signer(&data),
+                        data,
                         broadcast_sender,
                     }
                 }
@@ -393,9 +433,20 @@ where
                 tx_wasm_compilation_cache as usize,
             ),
             storage_read_past_height_limit,
+            mempool_min_gas_price,
+            vote_extension_block_space,
             proposal_data: HashSet::new(),
             // TODO: config event log params
             event_log: EventLog::default(),
+            alloc_replay_log,
+            mempool_view,
+            pending_inner_txs: Default::default(),
+            #[cfg(feature = "abcipp")]
+            oracle_price_feed: config.shell.oracle_price_feed,
+            ethereum_events_feed: config.shell.ethereum_events_feed,
+            snapshots,
+            snapshot_restore: None,
+            pruning,
         }
     }
 
@@ -411,6 +462,31 @@ where
         &mut self.event_log
     }
 
+    /// Record a block space allocation decision taken at the current
+    /// decision height, if the [`AllocReplayLog`] is enabled.
+    pub fn record_alloc_decision<E: std::fmt::Debug>(
+        &self,
+        role: AllocRole,
+        tx_bytes: &[u8],
+        bin: AllocBin,
+        result: &Result<(), E>,
+        space_left: u64,
+    ) {
+        if !self.alloc_replay_log.is_enabled() {
+            return;
+        }
+        let height =
+            self.wl_storage.pos_queries().get_current_decision_height();
+        self.alloc_replay_log.record(AllocDecision {
+            height,
+            role,
+            tx_hash: hash::Hash::sha256(tx_bytes),
+            bin,
+            rejection: result.as_ref().err().map(|err| format!("{err:?}")),
+            space_left,
+        });
+    }
+
     /// Iterate over the wrapper txs in order
     #[allow(dead_code)]
     fn iter_tx_queue(&mut self) -> impl Iterator<Item = &TxInQueue> {
@@ -444,6 +520,25 @@ where
         response
     }
 
+    /// The epoch of the last committed block. Unlike the current epoch, this
+    /// doesn't change while a new block that starts an epoch is finalized,
+    /// so it's used to validate the protocol txs the same way in every stage
+    /// of a block.
+    fn last_block_epoch(&self) -> std::result::Result<Epoch, String> {
+        let last_height = self.wl_storage.storage.get_last_block_height();
+        self.wl_storage
+            .storage
+            .block
+            .pred_epochs
+            .get_epoch(last_height)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown epoch of the last block height {}",
+                    last_height
+                )
+            })
+    }
+
     /// Takes the optional tendermint timestamp of the block: if it's Some than
     /// converts it to a [`DateTimeUtc`], otherwise retrieve from self the
     /// time of the last block committed
@@ -633,19 +728,31 @@ where
     #[cfg(feature = "abcipp")]
     pub fn extend_vote(
         &self,
-        _req: request::ExtendVote,
+        req: request::ExtendVote,
     ) -> response::ExtendVote {
-        Default::default()
+        response::ExtendVote {
+            vote_extension: self
+                .sign_oracle_prices(BlockHeight(req.height as u64))
+                .unwrap_or_default(),
+        }
     }
 
     /// INVARIANT: This method must be stateless.
     #[cfg(feature = "abcipp")]
     pub fn verify_vote_extension(
         &self,
-        _req: request::VerifyVoteExtension,
+        req: request::VerifyVoteExtension,
     ) -> response::VerifyVoteExtension {
+        let status = if self.verify_oracle_prices(
+            &req.vote_extension,
+            BlockHeight(req.height as u64),
+        ) {
+            VerifyStatus::Accept
+        } else {
+            VerifyStatus::Reject
+        };
         response::VerifyVoteExtension {
-            status: VerifyStatus::Accept as i32,
+            status: status as i32,
         }
     }
 
@@ -668,9 +775,66 @@ where
             self.wl_storage.storage.get_last_block_height(),
         );
         response.data = root.0;
+        self.prune_history_if_due();
         response
     }
 
+    /// Prune the history of the storage that is no longer retained, if the
+    /// pruning is enabled. This is done at the start of every epoch, as the
+    /// history is pruned by whole epochs.
+    fn prune_history_if_due(&mut self) {
+        let pruning = match &self.pruning {
+            Some(pruning) => pruning,
+            None => return,
+        };
+        let storage = &self.wl_storage.storage;
+        let height = storage.get_last_block_height();
+        let pred_epochs = &storage.block.pred_epochs;
+        if pred_epochs.first_block_heights.last() != Some(&height) {
+            return;
+        }
+        let params = match read_pos_params(&self.wl_storage) {
+            Ok(params) => params,
+            Err(err) => {
+                tracing::error!(
+                    "Failed to read the PoS parameters to prune the storage: \
+                     {}",
+                    err
+                );
+                return;
+            }
+        };
+        // The history of the last unbonding period is always retained
+        let keep_epochs = pruning
+            .keep_epochs
+            .unwrap_or_default()
+            .max(params.withdrawable_epoch_offset());
+        let retained_epoch =
+            Epoch(storage.last_epoch.0.saturating_sub(keep_epochs));
+        let mut retained_height =
+            match pred_epochs.get_start_height_of_epoch(retained_epoch) {
+                Some(height) => height,
+                // The retained epoch predates the known epochs
+                None => return,
+            };
+        let limits = [
+            pruning.keep_last_heights,
+            self.storage_read_past_height_limit,
+        ];
+        for limit in limits.into_iter().flatten() {
+            retained_height = retained_height
+                .min(BlockHeight(height.0.saturating_sub(limit)));
+        }
+        if let Err(err) = self.wl_storage.storage.prune_history(retained_height)
+        {
+            tracing::error!(
+                "Failed to prune the storage before height {}: {}",
+                retained_height,
+                err
+            );
+        }
+    }
+
     /// Checks that neither the wrapper nor the inner transaction have already
     /// been applied. Requires a [`TempWlStorage`] to perform the check during
     /// block construction and validation
@@ -718,6 +882,29 @@ where
             .write(&wrapper_hash_key, ())
             .expect("Couldn't write wrapper tx hash to write log");
 
+        // Check that the wrapper is the next one in the sequence of its fee
+        // payer
+        if let Some((fee_payer, nonce)) = tx
+            .header()
+            .wrapper()
+            .and_then(|wrapper| Some((wrapper.fee_payer(), wrapper.nonce?)))
+        {
+            let next_nonce =
+                replay_protection::read_next_nonce(temp_wl_storage, &fee_payer)
+                    .expect("Error while reading the nonce from storage");
+            if nonce != next_nonce {
+                return Err(Error::ReplayAttempt(format!(
+                    "Wrapper nonce {} doesn't match the next nonce {} of {}",
+                    nonce, next_nonce, fee_payer
+                )));
+            }
+
+            // Write the next nonce to WAL
+            temp_wl_storage
+                .write(&replay_protection::get_nonce_key(&fee_payer), nonce + 1)
+                .expect("Couldn't write wrapper nonce to write log");
+        }
+
         Ok(())
     }
 
@@ -734,23 +921,31 @@ where
     pub fn mempool_validate(
         &self,
         tx_bytes: &[u8],
-        r#_type: MempoolTxType,
+        r#type: MempoolTxType,
     ) -> response::CheckTx {
         let mut response = response::CheckTx::default();
 
         // Tx format check
-        let tx = match Tx::try_from(tx_bytes).map_err(Error::TxDecoding) {
+        let limits = self.get_tx_decode_limits();
+        let tx = match Tx::decode_with_limits(tx_bytes, &limits)
+            .map_err(Error::TxDecoding)
+        {
             Ok(t) => t,
             Err(msg) => {
-                response.code = ErrorCodes::InvalidTx.into();
+                response.code = ResultCode::InvalidTx.into();
                 response.log = msg.to_string();
                 return response;
             }
         };
 
+        // A pending wrapper is recorded again if it passes the recheck
+        if let MempoolTxType::RecheckTransaction = r#type {
+            self.mempool_view.remove(&hash::Hash(tx.header_hash().0));
+        }
+
         // Tx chain id
         if tx.header.chain_id != self.chain_id {
-            response.code = ErrorCodes::InvalidChainId.into();
+            response.code = ResultCode::InvalidChainId.into();
             response.log = format!(
                 "Tx carries a wrong chain id: expected {}, found {}",
                 self.chain_id, tx.header.chain_id
@@ -763,7 +958,7 @@ where
             let last_block_timestamp = self.get_block_timestamp(None);
 
             if last_block_timestamp > exp {
-                response.code = ErrorCodes::ExpiredTx.into();
+                response.code = ResultCode::ExpiredTx.into();
                 response.log = format!(
                     "Tx expired at {:#?}, last committed block time: {:#?}",
                     exp, last_block_timestamp
@@ -776,7 +971,7 @@ where
         let tx_type = match tx.validate_header() {
             Ok(()) => tx.header(),
             Err(msg) => {
-                response.code = ErrorCodes::InvalidSig.into();
+                response.code = ResultCode::InvalidSig.into();
                 response.log = msg.to_string();
                 return response;
             }
@@ -797,7 +992,7 @@ where
                 .expect("Error while checking inner tx hash key in storage")
                 .0
             {
-                response.code = ErrorCodes::ReplayTx.into();
+                response.code = ResultCode::ReplayTx.into();
                 response.log = format!(
                     "Inner transaction hash {} already in storage, replay \
                      attempt",
@@ -808,6 +1003,31 @@ where
 
             let tx =
                 Tx::try_from(tx_bytes).expect("Deserialization shouldn't fail");
+
+            // Fee unshielding check
+            if let Some(unshield) = &wrapper.unshield {
+                if tx
+                    .get_section(unshield)
+                    .and_then(Section::masp_tx)
+                    .is_none()
+                {
+                    response.code = ResultCode::InvalidTx.into();
+                    response.log = format!(
+                        "Missing fee unshielding MASP transaction section {}",
+                        unshield
+                    );
+                    return response;
+                }
+            }
+
+            // Fee payer authorization check
+            if let Err(err) = self.verify_fee_payer_authorization(&tx, &wrapper)
+            {
+                response.code = ResultCode::InvalidSig.into();
+                response.log = err;
+                return response;
+            }
+
             let wrapper_hash = hash::Hash(tx.header_hash().0);
             let wrapper_hash_key =
                 replay_protection::get_tx_hash_key(&wrapper_hash);
@@ -818,7 +1038,7 @@ where
                 .expect("Error while checking wrapper tx hash key in storage")
                 .0
             {
-                response.code = ErrorCodes::ReplayTx.into();
+                response.code = ResultCode::ReplayTx.into();
                 response.log = format!(
                     "Wrapper transaction hash {} already in storage, replay \
                      attempt",
@@ -827,6 +1047,182 @@ where
                 return response;
             }
 
+            // Replace-by-fee check. A wrapper of an inner tx that is pending
+            // under another wrapper must outbid it, and an outbid wrapper is
+            // evicted when it's rechecked.
+            let is_recheck =
+                matches!(r#type, MempoolTxType::RecheckTransaction);
+            let last_height = self.wl_storage.storage.get_last_block_height();
+            match self.pending_inner_txs.get(inner_tx_hash, last_height) {
+                Some((pending_hash, _)) if pending_hash == wrapper_hash => {
+                    // Recorded again if it passes the recheck
+                    self.pending_inner_txs.remove(inner_tx_hash);
+                }
+                Some((pending_hash, _)) if is_recheck => {
+                    response.code = ResultCode::ReplayTx.into();
+                    response.log = format!(
+                        "Wrapper tx replaced by the wrapper {} of the same \
+                         inner tx",
+                        pending_hash
+                    );
+                    return response;
+                }
+                Some((_, pending)) => {
+                    if let Err(err) = wrapper.validate_replacement(&pending) {
+                        response.code = ResultCode::InvalidTx.into();
+                        response.log = err.to_string();
+                        return response;
+                    }
+                }
+                None => {}
+            }
+
+            // Wrapper nonce check. A nonce above the next one may become valid
+            // once the wrappers preceding it are applied.
+            if let Some(nonce) = wrapper.nonce {
+                let fee_payer = wrapper.fee_payer();
+                let next_nonce = replay_protection::read_next_nonce(
+                    &self.wl_storage,
+                    &fee_payer,
+                )
+                .expect("Error while reading the nonce from storage");
+                if nonce < next_nonce {
+                    response.code = ResultCode::ReplayTx.into();
+                    response.log = format!(
+                        "Wrapper nonce {} was already used, the next nonce of \
+                         {} is {}",
+                        nonce, fee_payer, next_nonce
+                    );
+                    return response;
+                }
+            }
+
+            // Wrapper expiration check, against the height of the next block
+            let next_height =
+                self.wl_storage.storage.get_last_block_height() + 1;
+            if wrapper.is_expired_at(next_height) {
+                response.code = ResultCode::ExpiredTx.into();
+                response.log = format!(
+                    "Wrapper tx expired at height {}, next block height: {}",
+                    wrapper.expiration_height.unwrap_or_default(),
+                    next_height
+                );
+                return response;
+            }
+
+            // Tx size check
+            let max_tx_bytes = self.get_max_tx_bytes();
+            if tx_bytes.len() as u64 > max_tx_bytes {
+                response.code = ResultCode::AllocationError.into();
+                response.log = format!(
+                    "The wrapper tx of {} bytes exceeds the max tx size of {} \
+                     bytes",
+                    tx_bytes.len(),
+                    max_tx_bytes
+                );
+                return response;
+            }
+
+            // Memo check
+            let max_memo_len = self.get_max_memo_len();
+            if !tx.validate_memo(max_memo_len) {
+                response.code = ResultCode::InvalidTx.into();
+                response.log = format!(
+                    "The memo of the wrapper tx is missing or exceeds the max \
+                     memo length of {} bytes",
+                    max_memo_len
+                );
+                return response;
+            }
+
+            // Gas limit check
+            let resolution = self.get_gas_limit_resolution();
+            if wrapper.gas_limit.checked_raw(resolution).is_none() {
+                response.code = ResultCode::InvalidTx.into();
+                response.log = format!(
+                    "The gas limit of the wrapper tx overflows at the gas \
+                     limit resolution of {}",
+                    resolution
+                );
+                return response;
+            }
+
+            // Check that the fee token is whitelisted and convert the
+            // minimum gas prices, denominated in the native token, into it
+            let fee_tokens = self.get_fee_tokens();
+            let native_token = &self.wl_storage.storage.native_token;
+            let convert = |amount| {
+                namada::ledger::parameters::convert_fee_amount(
+                    &fee_tokens,
+                    native_token,
+                    &wrapper.fee.token,
+                    amount,
+                )
+            };
+            let gas_prices = convert(self.get_min_gas_price())
+                .zip(convert(self.mempool_min_gas_price));
+            let (min_gas_price, mempool_min_gas_price) = match gas_prices {
+                Some(prices) => prices,
+                None => {
+                    response.code = ResultCode::InvalidTx.into();
+                    response.log = format!(
+                        "Fees cannot be paid in token {}",
+                        wrapper.fee.token
+                    );
+                    return response;
+                }
+            };
+
+            // Minimum fee check
+            if let Err(err) =
+                wrapper.validate_min_gas_price(resolution, min_gas_price)
+            {
+                response.code = ResultCode::InvalidTx.into();
+                response.log = format!(
+                    "The fee of the wrapper tx does not cover its gas limit \
+                     at the minimum gas price of {}: {}",
+                    min_gas_price, err
+                );
+                return response;
+            }
+
+            // Gas price check
+            if wrapper.effective_gas_price() < mempool_min_gas_price {
+                response.code = ResultCode::InvalidTx.into();
+                response.log = format!(
+                    "The gas price of the wrapper tx is lower than the minimum \
+                     of {} accepted by this node",
+                    mempool_min_gas_price
+                );
+                return response;
+            }
+
+            // Convert the fees into the fee token
+            let fees = match convert(self.get_wrapper_tx_fees()) {
+                Some(fees) => fees,
+                None => {
+                    response.code = ResultCode::InvalidTx.into();
+                    response.log = format!(
+                        "Fees cannot be paid in token {}",
+                        wrapper.fee.token
+                    );
+                    return response;
+                }
+            };
+            // Add the tip offered on top of the fees
+            let fees = match wrapper
+                .tip(resolution)
+                .and_then(|tip| fees.checked_add(tip))
+            {
+                Some(fees) => fees,
+                None => {
+                    response.code = ResultCode::InvalidTx.into();
+                    response.log =
+                        "The tip of the wrapper tx overflows".to_string();
+                    return response;
+                }
+            };
+
             // Check balance for fee
             let fee_payer = if wrapper.pk != masp_tx_key().ref_to() {
                 wrapper.fee_payer()
@@ -843,16 +1239,51 @@ where
             #[cfg(feature = "mainnet")]
             let has_valid_pow = false;
 
-            if !has_valid_pow && self.get_wrapper_tx_fees() > balance {
-                response.code = ErrorCodes::InvalidTx.into();
+            // The fee may be paid with funds unshielded before charging it
+            if !has_valid_pow && wrapper.unshield.is_none() && fees > balance {
+                response.code = ResultCode::InvalidTx.into();
                 response.log = String::from(
                     "The given address does not have a sufficient balance to \
                      pay fee",
                 );
                 return response;
             }
+
+            if let Some(replaced) = self.pending_inner_txs.insert(
+                *inner_tx_hash,
+                wrapper_hash,
+                (*wrapper).clone(),
+                self.wl_storage.storage.get_last_block_height(),
+            ) {
+                self.mempool_view.remove(&replaced);
+            }
+            self.mempool_view.insert(PendingWrapper {
+                hash: wrapper_hash,
+                fee: wrapper.fee.clone(),
+                gas_limit: wrapper.gas_limit.clone(),
+                fee_payer,
+                validated_at: self.wl_storage.storage.get_last_block_height(),
+            });
+        } else if let TxType::Protocol(protocol_tx) = tx_type.tx_type {
+            // Only the validator set updates and the votes on the Ethereum
+            // events are broadcast by the validators, the other protocol txs
+            // are built by the proposers
+            let validated = match protocol_tx.tx {
+                ProtocolTxType::ValidatorSetUpdate(update) => {
+                    self.validate_valset_update(&update)
+                }
+                ProtocolTxType::EthereumEvents(vote) => {
+                    self.validate_eth_events_vote(&vote)
+                }
+                _ => Err("Unsupported protocol tx type".to_string()),
+            };
+            if let Err(err) = validated {
+                response.code = ResultCode::InvalidTx.into();
+                response.log = err;
+                return response;
+            }
         } else {
-            response.code = ErrorCodes::InvalidTx.into();
+            response.code = ResultCode::InvalidTx.into();
             response.log = "Unsupported tx type".to_string();
             return response;
         }
@@ -955,6 +1386,160 @@ where
         false
     }
 
+    /// Get the resolution of the gas limits of wrapper txs
+    fn get_gas_limit_resolution(&self) -> u64 {
+        namada::ledger::parameters::read_gas_limit_resolution_parameter(
+            &self.wl_storage,
+        )
+        .expect("Must be able to read gas limit resolution parameter")
+    }
+
+    /// Get the table of the costs of the operations metered in txs and VPs
+    fn get_gas_cost_table(&self) -> GasCostTable {
+        namada::ledger::parameters::read_gas_cost_table_parameter(
+            &self.wl_storage,
+        )
+        .expect("Must be able to read gas cost table parameter")
+    }
+
+    /// Check that the established account paying the fee of the given
+    /// wrapper, if any, authorized it by signing the header of the outer tx
+    /// with the public key that its VP verifies signatures against
+    fn verify_fee_payer_authorization(
+        &self,
+        tx: &Tx,
+        wrapper: &namada::types::transaction::WrapperTx,
+    ) -> std::result::Result<(), String> {
+        let fee_payer = match &wrapper.fee_payer_address {
+            Some(fee_payer) => fee_payer,
+            None => return Ok(()),
+        };
+        if !matches!(fee_payer, Address::Established(_)) {
+            return Err(format!(
+                "The fee payer {} is not an established account",
+                fee_payer
+            ));
+        }
+        let signers =
+            storage_api::key::get_signers(&self.wl_storage, fee_payer)
+                .expect("Must be able to read the signers of the fee payer");
+        let verified = match signers {
+            // A multisig fee payer must be authorized by enough signers
+            Some((pks, threshold)) => {
+                tx.verify_multisig(&pks, threshold, &tx.header_hash())
+            }
+            None => {
+                let pk = storage_api::key::get(&self.wl_storage, fee_payer)
+                    .expect(
+                        "Must be able to read the public key of the fee payer",
+                    )
+                    .ok_or_else(|| {
+                        format!("The fee payer {} has no public key", fee_payer)
+                    })?;
+                tx.verify_signature(&pk, &tx.header_hash())
+            }
+        };
+        verified.map_err(|err| {
+            format!(
+                "The fee payer {} did not authorize the wrapper tx: {}",
+                fee_payer, err
+            )
+        })
+    }
+
+    /// Build the transfer tx that executes the MASP transaction section of
+    /// the given wrapper `tx` with the `unshield` hash, unshielding `amount`
+    /// of `token` to the `fee_payer`
+    fn fee_unshielding_tx(
+        &self,
+        tx: &Tx,
+        unshield: &hash::Hash,
+        fee_payer: &Address,
+        token: &Address,
+        amount: token::Amount,
+    ) -> std::result::Result<Tx, String> {
+        let masp_tx = tx
+            .get_section(unshield)
+            .and_then(Section::masp_tx)
+            .ok_or_else(|| "missing MASP transaction section".to_string())?;
+        let transfer_code_hash = self
+            .wl_storage
+            .read_bytes(&Key::wasm_hash(TX_TRANSFER_WASM))
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| "missing transfer tx code".to_string())
+            .and_then(|bytes| {
+                hash::Hash::try_from(&bytes[..]).map_err(|err| err.to_string())
+            })?;
+        let transfer = token::Transfer {
+            source: address::masp(),
+            target: fee_payer.clone(),
+            token: token.clone(),
+            sub_prefix: None,
+            amount,
+            key: None,
+            shielded: Some(*unshield),
+        };
+
+        let mut unshield_tx =
+            Tx::new(TxType::Decrypted(DecryptedTx::Decrypted {
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow: false,
+            }));
+        unshield_tx.header.chain_id = tx.header.chain_id.clone();
+        unshield_tx.set_code(Code::from_hash(transfer_code_hash));
+        unshield_tx.set_data(Data::new(
+            transfer
+                .try_to_vec()
+                .expect("Encoding transfer shouldn't fail"),
+        ));
+        unshield_tx.add_section(Section::MaspTx(masp_tx));
+        Ok(unshield_tx)
+    }
+
+    /// Get the max size in bytes of a single tx
+    fn get_max_tx_bytes(&self) -> u64 {
+        namada::ledger::parameters::read_max_tx_bytes_parameter(
+            &self.wl_storage,
+        )
+        .expect("Must be able to read max tx bytes parameter")
+    }
+
+    /// Get the max length in bytes of a tx memo, falling back to the
+    /// default one if governance did not set it
+    fn get_max_memo_len(&self) -> u64 {
+        namada::ledger::parameters::read_max_memo_len_parameter(
+            &self.wl_storage,
+        )
+        .expect("Must be able to read max memo length parameter")
+        .unwrap_or(namada::proto::DEFAULT_MAX_MEMO_LEN)
+    }
+
+    /// Get the limits enforced when decoding txs, falling back to the hard
+    /// caps if governance did not tighten them
+    fn get_tx_decode_limits(&self) -> namada::proto::TxDecodeLimits {
+        namada::ledger::parameters::read_tx_decode_limits_parameter(
+            &self.wl_storage,
+        )
+        .expect("Must be able to read tx decode limits parameter")
+        .unwrap_or_default()
+        .capped()
+    }
+
+    /// Get the minimum price of a unit of gas
+    fn get_min_gas_price(&self) -> token::Amount {
+        namada::ledger::parameters::read_min_gas_price_parameter(
+            &self.wl_storage,
+        )
+        .expect("Must be able to read min gas price parameter")
+    }
+
+    /// Get the whitelisted fee tokens, with their conversion rates to the
+    /// native token
+    fn get_fee_tokens(&self) -> BTreeMap<Address, Decimal> {
+        namada::ledger::parameters::read_fee_tokens_parameter(&self.wl_storage)
+            .expect("Must be able to read fee tokens parameter")
+    }
+
     #[cfg(not(feature = "mainnet"))]
     /// Get fixed amount of fees for wrapper tx
     fn get_wrapper_tx_fees(&self) -> token::Amount {
@@ -993,6 +1578,43 @@ where
     }
 }
 
+/// Apply the given fee unshielding tx to the `write_log`. The changes of the
+/// tx are kept in the write log only if all the VPs accept it, otherwise they
+/// are dropped.
+fn apply_fee_unshielding<D, H>(
+    unshield_tx: Tx,
+    write_log: &mut WriteLog,
+    storage: &Storage<D, H>,
+    vp_wasm_cache: &mut VpCache<WasmCacheRwAccess>,
+    tx_wasm_cache: &mut TxCache<WasmCacheRwAccess>,
+) -> std::result::Result<(), String>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let tx_length = unshield_tx.to_bytes().len();
+    match protocol::apply_tx(
+        unshield_tx,
+        tx_length,
+        TxIndex::default(),
+        &mut BlockGasMeter::default(),
+        write_log,
+        storage,
+        vp_wasm_cache,
+        tx_wasm_cache,
+    ) {
+        Ok(result) if result.is_accepted() => Ok(()),
+        Ok(result) => {
+            write_log.drop_tx();
+            Err(result.to_string())
+        }
+        Err(err) => {
+            write_log.drop_tx();
+            Err(err.to_string())
+        }
+    }
+}
+
 /// Helper functions and types for writing unit tests
 /// for the shell
 #[cfg(test)]
@@ -1007,7 +1629,7 @@ mod test_utils {
     use namada::types::hash::Hash;
     use namada::types::key::*;
     use namada::types::storage::{BlockHash, Epoch, Epochs, Header};
-    use namada::types::transaction::{Fee, WrapperTx};
+    use namada::types::transaction::{Fee, GasLimit, WrapperTx};
     use tempfile::tempdir;
     use tokio::sync::mpsc::UnboundedReceiver;
 
@@ -1162,6 +1784,7 @@ mod test_utils {
         pub fn enqueue_tx(&mut self, tx: Tx) {
             self.shell.wl_storage.storage.tx_queue.push(TxInQueue {
                 tx,
+                fee: token::Amount::default(),
                 #[cfg(not(feature = "mainnet"))]
                 has_valid_pow: false,
             });
@@ -1245,7 +1868,7 @@ mod test_utils {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1256,6 +1879,7 @@ mod test_utils {
 
         shell.wl_storage.storage.tx_queue.push(TxInQueue {
             tx: wrapper,
+            fee: token::Amount::default(),
             #[cfg(not(feature = "mainnet"))]
             has_valid_pow: false,
         });
@@ -1293,7 +1917,7 @@ mod test_utils {
 mod test_mempool_validate {
     use namada::proof_of_stake::Epoch;
     use namada::proto::{Code, Data, Section, Signature, Tx};
-    use namada::types::transaction::{Fee, WrapperTx};
+    use namada::types::transaction::{Fee, GasLimit, WrapperTx};
 
     use super::test_utils::TestShell;
     use super::{MempoolTxType, *};
@@ -1313,7 +1937,7 @@ mod test_mempool_validate {
                 },
                 &keypair,
                 Epoch(0),
-                0.into(),
+                GasLimit::from_multiplier(0),
                 #[cfg(not(feature = "mainnet"))]
                 None,
             ))));
@@ -1327,12 +1951,12 @@ mod test_mempool_validate {
             unsigned_wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::InvalidSig));
+        assert_eq!(result.code, u32::from(ResultCode::InvalidSig));
         result = shell.mempool_validate(
             unsigned_wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::InvalidSig));
+        assert_eq!(result.code, u32::from(ResultCode::InvalidSig));
     }
 
     /// Mempool validation must reject wrappers with an invalid signature
@@ -1350,7 +1974,7 @@ mod test_mempool_validate {
                 },
                 &keypair,
                 Epoch(0),
-                0.into(),
+                GasLimit::from_multiplier(0),
                 #[cfg(not(feature = "mainnet"))]
                 None,
             ))));
@@ -1374,12 +1998,12 @@ mod test_mempool_validate {
             invalid_wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::InvalidSig));
+        assert_eq!(result.code, u32::from(ResultCode::InvalidSig));
         result = shell.mempool_validate(
             invalid_wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::InvalidSig));
+        assert_eq!(result.code, u32::from(ResultCode::InvalidSig));
     }
 
     /// Mempool validation must reject non-wrapper txs
@@ -1396,7 +2020,7 @@ mod test_mempool_validate {
             tx.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::InvalidTx));
+        assert_eq!(result.code, u32::from(ResultCode::InvalidTx));
         assert_eq!(result.log, "Unsupported tx type")
     }
 
@@ -1415,7 +2039,7 @@ mod test_mempool_validate {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1443,7 +2067,7 @@ mod test_mempool_validate {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::ReplayTx));
+        assert_eq!(result.code, u32::from(ResultCode::ReplayTx));
         assert_eq!(
             result.log,
             format!(
@@ -1457,7 +2081,7 @@ mod test_mempool_validate {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::ReplayTx));
+        assert_eq!(result.code, u32::from(ResultCode::ReplayTx));
         assert_eq!(
             result.log,
             format!(
@@ -1482,7 +2106,7 @@ mod test_mempool_validate {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::ReplayTx));
+        assert_eq!(result.code, u32::from(ResultCode::ReplayTx));
         assert_eq!(
             result.log,
             format!(
@@ -1495,7 +2119,7 @@ mod test_mempool_validate {
             wrapper.to_bytes().as_ref(),
             MempoolTxType::RecheckTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::ReplayTx));
+        assert_eq!(result.code, u32::from(ResultCode::ReplayTx));
         assert_eq!(
             result.log,
             format!(
@@ -1526,7 +2150,7 @@ mod test_mempool_validate {
             tx.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::InvalidChainId));
+        assert_eq!(result.code, u32::from(ResultCode::InvalidChainId));
         assert_eq!(
             result.log,
             format!(
@@ -1557,6 +2181,521 @@ mod test_mempool_validate {
             tx.to_bytes().as_ref(),
             MempoolTxType::NewTransaction,
         );
-        assert_eq!(result.code, u32::from(ErrorCodes::ExpiredTx));
+        assert_eq!(result.code, u32::from(ResultCode::ExpiredTx));
+    }
+
+    /// Check that a wrapper offering a gas price lower than the mempool
+    /// minimum gets rejected
+    #[test]
+    fn test_gas_price_too_low() {
+        let (mut shell, _) = super::test_utils::setup(1);
+        shell.mempool_min_gas_price = 10.into();
+
+        let keypair = super::test_utils::gen_keypair();
+
+        let mut wrapper = Tx::new(TxType::Wrapper(Box::new(
+            WrapperTx::new(
+                Fee {
+                    amount: 100.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_gas_price(5.into()),
+        )));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            &keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::InvalidTx));
+        assert!(result.log.contains("gas price"));
+    }
+
+    /// Check that the mempool minimum gas price is converted into the fee
+    /// token of a wrapper, and that fees can only be paid in the native token
+    /// with an empty whitelist
+    #[test]
+    fn test_gas_price_in_fee_token() {
+        let (mut shell, _) = super::test_utils::setup(1);
+        shell.mempool_min_gas_price = 10.into();
+
+        let keypair = super::test_utils::gen_keypair();
+        let chain_id = shell.chain_id.clone();
+        let new_wrapper = || {
+            let mut wrapper = Tx::new(TxType::Wrapper(Box::new(
+                WrapperTx::new(
+                    Fee {
+                        amount: 100.into(),
+                        token: address::btc(),
+                    },
+                    &keypair,
+                    Epoch(0),
+                    GasLimit::from_multiplier(0),
+                    #[cfg(not(feature = "mainnet"))]
+                    None,
+                )
+                .with_gas_price(15.into()),
+            )));
+            wrapper.header.chain_id = chain_id.clone();
+            wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+            wrapper
+                .set_data(Data::new("transaction data".as_bytes().to_owned()));
+            wrapper.add_section(Section::Signature(Signature::new(
+                &wrapper.header_hash(),
+                &keypair,
+            )));
+            wrapper.encrypt(&Default::default());
+            wrapper.to_bytes()
+        };
+
+        let wrapper = new_wrapper();
+        let result = shell
+            .mempool_validate(wrapper.as_ref(), MempoolTxType::NewTransaction);
+        assert_eq!(result.code, u32::from(ResultCode::InvalidTx));
+        assert_eq!(
+            result.log,
+            format!("Fees cannot be paid in token {}", address::btc())
+        );
+
+        // At a rate of 2, the gas price of 15 is lower than the minimum of 20
+        let fee_tokens = BTreeMap::from([(address::btc(), Decimal::TWO)]);
+        namada::ledger::parameters::update_fee_tokens_parameter(
+            &mut shell.wl_storage,
+            &fee_tokens,
+        )
+        .expect("Test failed");
+        let wrapper = new_wrapper();
+        let result = shell
+            .mempool_validate(wrapper.as_ref(), MempoolTxType::NewTransaction);
+        assert_eq!(result.code, u32::from(ResultCode::InvalidTx));
+        let min_gas_price = token::Amount::from(20);
+        let expected = format!("minimum of {}", min_gas_price);
+        assert!(result.log.contains(&expected));
+    }
+
+    /// Check that a wrapper whose fee is paid by an established account is
+    /// rejected unless the account authorized it
+    #[test]
+    fn test_unauthorized_fee_payer_address() {
+        let (mut shell, _) = TestShell::new();
+
+        let keypair = super::test_utils::gen_keypair();
+        let fee_payer = address::testing::established_address_1();
+
+        let mut wrapper = Tx::new(TxType::Wrapper(Box::new(
+            WrapperTx::new(
+                Fee {
+                    amount: 100.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_fee_payer_address(fee_payer.clone()),
+        )));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            &keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+
+        // the account has no public key to authorize the wrapper with
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::InvalidSig));
+        assert!(result.log.contains("has no public key"));
+
+        // the account has a public key but didn't sign the wrapper
+        let account_keypair = super::test_utils::gen_keypair();
+        shell
+            .wl_storage
+            .storage
+            .write(
+                &pk_key(&fee_payer),
+                account_keypair.ref_to().try_to_vec().expect("Test failed"),
+            )
+            .expect("Test failed");
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::InvalidSig));
+        assert!(result.log.contains("did not authorize"));
+    }
+
+    /// Check that a wrapper whose fee doesn't cover its gas limit at the
+    /// minimum gas price of the chain gets rejected
+    #[test]
+    fn test_fee_below_min_gas_price() {
+        let (mut shell, _) = super::test_utils::setup(1);
+        shell
+            .wl_storage
+            .storage
+            .write(
+                &namada::ledger::parameters::storage::get_min_gas_price_key(),
+                token::Amount::from(1u64).try_to_vec().expect("Test failed"),
+            )
+            .expect("Test failed");
+
+        let keypair = super::test_utils::gen_keypair();
+
+        let mut wrapper = Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount: 100.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            GasLimit::from_multiplier(1),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            &keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::InvalidTx));
+        assert!(result.log.contains("minimum gas price"));
+    }
+
+    /// Check that a wrapper larger than the max tx size of the chain gets
+    /// rejected
+    #[test]
+    fn test_wrapper_over_max_tx_bytes() {
+        let (mut shell, _) = super::test_utils::setup(1);
+        shell
+            .wl_storage
+            .storage
+            .write(
+                &namada::ledger::parameters::storage::get_max_tx_bytes_key(),
+                10u64.try_to_vec().expect("Test failed"),
+            )
+            .expect("Test failed");
+
+        let keypair = super::test_utils::gen_keypair();
+
+        let mut wrapper = Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount: 100.into(),
+                token: shell.wl_storage.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            GasLimit::from_multiplier(0),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            &keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::AllocationError));
+        assert!(result.log.contains("max tx size"));
+    }
+
+    /// Check that a wrapper that expires before the next block height gets
+    /// rejected
+    #[test]
+    fn test_expired_height_wrapper() {
+        let (shell, _) = TestShell::new();
+        let last_height = shell.wl_storage.storage.get_last_block_height();
+
+        let keypair = super::test_utils::gen_keypair();
+
+        let mut wrapper = Tx::new(TxType::Wrapper(Box::new(
+            WrapperTx::new(
+                Fee {
+                    amount: 100.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_expiration_height(last_height),
+        )));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            &keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::ExpiredTx));
+    }
+
+    /// Check that a wrapper reusing an already consumed nonce of its fee
+    /// payer is rejected by the mempool
+    #[test]
+    fn test_used_nonce_wrapper() {
+        let (mut shell, _) = TestShell::new();
+
+        let keypair = super::test_utils::gen_keypair();
+        let fee_payer = Address::from(&keypair.ref_to());
+        shell
+            .wl_storage
+            .storage
+            .write(
+                &replay_protection::get_nonce_key(&fee_payer),
+                1u64.try_to_vec().expect("Test failed"),
+            )
+            .expect("Test failed");
+
+        let mut wrapper = Tx::new(TxType::Wrapper(Box::new(
+            WrapperTx::new(
+                Fee {
+                    amount: 100.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_nonce(0),
+        )));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            &keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+
+        let result = shell.mempool_validate(
+            wrapper.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::ReplayTx));
+    }
+
+    /// Build an inner tx, to be wrapped
+    fn inner_tx(shell: &TestShell) -> Tx {
+        let mut tx = Tx::new(TxType::Raw);
+        tx.header.chain_id = shell.chain_id.clone();
+        tx.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        tx
+    }
+
+    /// Build a signed wrapper of the given inner tx with the given gas price
+    fn wrapper_with_gas_price(
+        shell: &TestShell,
+        inner: &Tx,
+        keypair: &common::SecretKey,
+        gas_price: u64,
+    ) -> Tx {
+        let mut wrapper = inner.clone();
+        wrapper.update_header(TxType::Wrapper(Box::new(
+            WrapperTx::new(
+                Fee {
+                    amount: 100.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_gas_price(gas_price.into()),
+        )));
+        wrapper.add_section(Section::Signature(Signature::new(
+            &wrapper.header_hash(),
+            keypair,
+        )));
+        wrapper.encrypt(&Default::default());
+        wrapper
+    }
+
+    /// Record the given wrapper as pending in the mempool, as validated at
+    /// the given last committed block height
+    fn set_pending(shell: &TestShell, wrapper: &Tx, height: BlockHeight) {
+        let header = wrapper.header().wrapper().expect("Test failed");
+        shell.pending_inner_txs.insert(
+            wrapper.clone().update_header(TxType::Raw).header_hash(),
+            wrapper.header_hash(),
+            header,
+            height,
+        );
+    }
+
+    /// Check that a wrapper of an inner tx pending under another wrapper is
+    /// rejected unless it raises the gas price by the minimum bump
+    #[test]
+    fn test_underpriced_replacement() {
+        let (shell, _) = TestShell::new();
+        let keypair = super::test_utils::gen_keypair();
+
+        let inner = inner_tx(&shell);
+
+        let pending = wrapper_with_gas_price(&shell, &inner, &keypair, 15);
+        set_pending(
+            &shell,
+            &pending,
+            shell.wl_storage.storage.get_last_block_height(),
+        );
+
+        let underpriced = wrapper_with_gas_price(&shell, &inner, &keypair, 16);
+        let result = shell.mempool_validate(
+            underpriced.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::InvalidTx));
+        assert!(result.log.contains("replace the pending wrapper"));
+    }
+
+    /// Check that a wrapper which was outbid by another wrapper of its inner
+    /// tx is evicted from the mempool on recheck
+    #[test]
+    fn test_replaced_wrapper_recheck() {
+        let (shell, _) = TestShell::new();
+        let keypair = super::test_utils::gen_keypair();
+
+        let inner = inner_tx(&shell);
+
+        let replaced = wrapper_with_gas_price(&shell, &inner, &keypair, 15);
+        let replacement = wrapper_with_gas_price(&shell, &inner, &keypair, 17);
+        set_pending(
+            &shell,
+            &replacement,
+            shell.wl_storage.storage.get_last_block_height(),
+        );
+
+        let result = shell.mempool_validate(
+            replaced.to_bytes().as_ref(),
+            MempoolTxType::RecheckTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::ReplayTx));
+        assert!(result.log.contains("replaced by the wrapper"));
+    }
+
+    /// Check that a wrapper of an inner tx pending under another wrapper is
+    /// accepted if it raises the gas price by the minimum bump, and that it
+    /// then replaces the pending wrapper
+    #[test]
+    fn test_bumped_replacement() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = super::test_utils::gen_keypair();
+
+        // Add balance for fee payment
+        let balance_key = token::balance_key(
+            &shell.wl_storage.storage.native_token,
+            &Address::from(&keypair.ref_to()),
+        );
+        shell
+            .wl_storage
+            .storage
+            .write(
+                &balance_key,
+                token::Amount::whole(1000)
+                    .try_to_vec()
+                    .expect("Test failed"),
+            )
+            .expect("Test failed");
+
+        let inner = inner_tx(&shell);
+        let last_height = shell.wl_storage.storage.get_last_block_height();
+
+        let pending = wrapper_with_gas_price(&shell, &inner, &keypair, 15);
+        let inner_hash =
+            pending.clone().update_header(TxType::Raw).header_hash();
+        set_pending(&shell, &pending, last_height);
+
+        let bumped = wrapper_with_gas_price(&shell, &inner, &keypair, 17);
+        let result = shell.mempool_validate(
+            bumped.to_bytes().as_ref(),
+            MempoolTxType::NewTransaction,
+        );
+        assert_eq!(result.code, u32::from(ResultCode::Ok));
+        let (pending_hash, _) = shell
+            .pending_inner_txs
+            .get(&inner_hash, last_height)
+            .expect("Test failed");
+        assert_eq!(pending_hash, bumped.header_hash());
+    }
+
+    /// Check that the record of a pending wrapper which was not rechecked
+    /// expires, such that it no longer has to be outbid
+    #[test]
+    fn test_expired_pending_wrapper() {
+        let (shell, _) = TestShell::new();
+        let keypair = super::test_utils::gen_keypair();
+
+        let inner = inner_tx(&shell);
+        let recorded_at = BlockHeight(1);
+
+        let pending = wrapper_with_gas_price(&shell, &inner, &keypair, 15);
+        let inner_hash =
+            pending.clone().update_header(TxType::Raw).header_hash();
+        set_pending(&shell, &pending, recorded_at);
+
+        let last_valid_height = BlockHeight(
+            recorded_at.0 + replace_by_fee::PENDING_WRAPPER_EXPIRY_BLOCKS,
+        );
+        let recorded =
+            shell.pending_inner_txs.get(&inner_hash, last_valid_height);
+        assert!(recorded.is_some());
+        let expired = shell
+            .pending_inner_txs
+            .get(&inner_hash, last_valid_height.next_height());
+        assert!(expired.is_none());
+
+        // The expired records are dropped when a wrapper is recorded
+        let mut other_inner = inner_tx(&shell);
+        other_inner.set_data(Data::new("other data".as_bytes().to_owned()));
+        let other = wrapper_with_gas_price(&shell, &other_inner, &keypair, 15);
+        set_pending(&shell, &other, last_valid_height.next_height());
+        let dropped = shell.pending_inner_txs.get(&inner_hash, recorded_at);
+        assert!(dropped.is_none());
     }
 }
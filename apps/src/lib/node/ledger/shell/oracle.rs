@@ -0,0 +1,270 @@
+//! The validation and the aggregation by the shell of the prices of the fee
+//! tokens reported by the validators, as described in
+//! [`namada::ledger::oracle`].
+
+use std::collections::{BTreeMap, HashSet};
+#[cfg(feature = "abcipp")]
+use std::path::Path;
+
+#[cfg(feature = "abcipp")]
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "abcipp")]
+use namada::ledger::oracle::OraclePrices;
+use namada::ledger::oracle::{self, SignedOraclePrices};
+use namada::ledger::storage::{DBIter, StorageHasher, DB};
+use namada::proof_of_stake::pos_queries::PosQueries;
+use namada::types::address::Address;
+use namada::types::storage::{BlockHeight, Epoch};
+use namada::types::token;
+#[cfg(feature = "abcipp")]
+use namada::types::transaction::protocol::ProtocolTxType;
+use rust_decimal::Decimal;
+
+use super::Shell;
+#[cfg(feature = "abcipp")]
+use crate::facade::tendermint_proto::abci::ExtendedCommitInfo;
+#[cfg(feature = "abcipp")]
+use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
+
+/// Read the prices of the fee tokens from a price feed file, which maps the
+/// addresses of the tokens to their prices in JSON
+#[cfg(feature = "abcipp")]
+pub fn read_price_feed(path: &Path) -> Option<BTreeMap<Address, Decimal>> {
+    let feed = std::fs::read_to_string(path)
+        .map_err(|err| {
+            tracing::error!(
+                "Failed to read the price feed {}: {}",
+                path.to_string_lossy(),
+                err
+            )
+        })
+        .ok()?;
+    serde_json::from_str(&feed)
+        .map_err(|err| {
+            tracing::error!(
+                "Failed to decode the price feed {}: {}",
+                path.to_string_lossy(),
+                err
+            )
+        })
+        .ok()
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Sign the prices of the price feed of this validator, to extend its
+    /// precommit of the block at the given height. Returns `None` if the node
+    /// isn't a validator or if it has no price feed.
+    #[cfg(feature = "abcipp")]
+    pub(super) fn sign_oracle_prices(
+        &self,
+        block_height: BlockHeight,
+    ) -> Option<Vec<u8>> {
+        let path = self.oracle_price_feed.as_ref()?;
+        let validator = self.mode.get_validator_address()?.clone();
+        let signer = self.mode.get_protocol_signer()?;
+        let data = OraclePrices {
+            validator,
+            block_height,
+            prices: read_price_feed(path)?,
+        };
+        let signed = SignedOraclePrices::new(data, signer)
+            .map_err(|err| {
+                tracing::error!("Failed to sign the oracle prices: {}", err)
+            })
+            .ok()?;
+        Some(signed.try_to_vec().expect("Encoding prices shouldn't fail"))
+    }
+
+    /// Check the vote extension of a precommit of the block at the given
+    /// height. Validators without a price feed extend their votes with
+    /// nothing.
+    #[cfg(feature = "abcipp")]
+    pub(super) fn verify_oracle_prices(
+        &self,
+        vote_extension: &[u8],
+        block_height: BlockHeight,
+    ) -> bool {
+        if vote_extension.is_empty() {
+            return true;
+        }
+        // The block isn't committed yet, so its epoch may not be known
+        let epoch = self.wl_storage.storage.get_current_epoch().0;
+        SignedOraclePrices::try_from_slice(vote_extension)
+            .map_err(|err| err.to_string())
+            .and_then(|signed| {
+                self.validate_oracle_prices(&signed, block_height, epoch)
+            })
+            .map_err(|err| tracing::info!("Rejected a vote extension: {}", err))
+            .is_ok()
+    }
+
+    /// Build a protocol tx with the prices reported in the vote extensions
+    /// of the last block, if they're reported by more than 2/3 of the voting
+    /// power
+    #[cfg(feature = "abcipp")]
+    pub(super) fn build_oracle_prices_tx(
+        &self,
+        local_last_commit: ExtendedCommitInfo,
+    ) -> Option<TxBytes> {
+        let signer = self.mode.get_protocol_signer()?;
+        let last_height = self.wl_storage.storage.get_last_block_height();
+        let epoch = self.last_block_epoch().ok()?;
+        let mut validators = HashSet::new();
+        let reports: Vec<SignedOraclePrices> = local_last_commit
+            .votes
+            .into_iter()
+            .filter(|vote| !vote.vote_extension.is_empty())
+            .filter_map(|vote| {
+                SignedOraclePrices::try_from_slice(&vote.vote_extension).ok()
+            })
+            .filter(|signed| {
+                self.validate_oracle_prices(signed, last_height, epoch)
+                    .is_ok()
+                    && validators.insert(signed.data.validator.clone())
+            })
+            .collect();
+        if reports.is_empty() {
+            return None;
+        }
+        // Don't propose a tx that would get the proposal rejected
+        if let Err(err) = self.aggregate_oracle_prices(&reports) {
+            tracing::info!("Not proposing the oracle prices: {}", err);
+            return None;
+        }
+        let tx = ProtocolTxType::OraclePrices(reports)
+            .sign(signer, self.chain_id.clone())
+            .map_err(|err| {
+                tracing::error!("Failed to sign the oracle prices tx: {}", err)
+            })
+            .ok()?;
+        Some(tx.to_bytes())
+    }
+
+    /// Aggregate the prices reported in the vote extensions of the last
+    /// block into the prices of the fee tokens. Fails if any report is
+    /// invalid or repeated, or if the reports are made by at most 2/3 of the
+    /// voting power.
+    pub(super) fn aggregate_oracle_prices(
+        &self,
+        reports: &[SignedOraclePrices],
+    ) -> Result<BTreeMap<Address, Decimal>, String> {
+        let last_height = self.wl_storage.storage.get_last_block_height();
+        let epoch = self.last_block_epoch()?;
+        let mut validators = HashSet::new();
+        let mut stakes = Vec::with_capacity(reports.len());
+        for signed in reports {
+            if !validators.insert(&signed.data.validator) {
+                return Err(format!(
+                    "Repeated prices of validator {}",
+                    signed.data.validator
+                ));
+            }
+            let stake =
+                self.validate_oracle_prices(signed, last_height, epoch)?;
+            stakes.push((stake, &signed.data));
+        }
+        let reported: token::Amount = stakes.iter().map(|(s, _)| *s).sum();
+        let total_stake = self
+            .wl_storage
+            .pos_queries()
+            .get_total_voting_power(Some(epoch));
+        if !oracle::is_quorum(reported, total_stake) {
+            return Err(format!(
+                "The prices are reported by a stake of {} out of {}, which \
+                 isn't more than 2/3",
+                reported, total_stake
+            ));
+        }
+        Ok(oracle::aggregate(stakes, total_stake))
+    }
+
+    /// Check that the prices were signed by a consensus validator in the
+    /// given epoch for the precommit of the block at the given height.
+    /// Returns the stake of the validator.
+    fn validate_oracle_prices(
+        &self,
+        signed: &SignedOraclePrices,
+        block_height: BlockHeight,
+        epoch: Epoch,
+    ) -> Result<token::Amount, String> {
+        if signed.data.block_height != block_height {
+            return Err(format!(
+                "The prices of validator {} are reported for the block height \
+                 {}, expected {}",
+                signed.data.validator, signed.data.block_height, block_height
+            ));
+        }
+        let (stake, protocol_pk) = self
+            .wl_storage
+            .pos_queries()
+            .get_validator_from_address(&signed.data.validator, Some(epoch))
+            .map_err(|err| err.to_string())?;
+        signed.verify(&protocol_pk).map_err(|err| {
+            format!(
+                "Invalid signature of the prices of validator {}: {}",
+                signed.data.validator, err
+            )
+        })?;
+        Ok(stake)
+    }
+}
+
+#[cfg(test)]
+mod test_oracle {
+    use namada::ledger::oracle::OraclePrices;
+    use namada::types::address::{btc, nam};
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils;
+    use crate::wallet::defaults;
+
+    /// Sign the given prices with the protocol key of the genesis validator
+    fn signed_prices(
+        block_height: BlockHeight,
+        prices: &[(Address, Decimal)],
+    ) -> SignedOraclePrices {
+        let data = OraclePrices {
+            validator: defaults::validator_address(),
+            block_height,
+            prices: prices.iter().cloned().collect(),
+        };
+        SignedOraclePrices::new(data, &defaults::validator_keys().0).unwrap()
+    }
+
+    /// Test that the prices of the last block are aggregated, and that
+    /// invalid or repeated reports are rejected
+    #[test]
+    fn test_aggregate_oracle_prices() {
+        let (shell, _) = test_utils::setup(1);
+        let last_height = shell.wl_storage.storage.get_last_block_height();
+        let prices = [(nam(), dec!(0.5)), (btc(), dec!(30000))];
+
+        let report = signed_prices(last_height, &prices);
+        assert_eq!(
+            shell.aggregate_oracle_prices(&[report.clone()]),
+            Ok(prices.iter().cloned().collect())
+        );
+
+        // the prices must be reported once per validator
+        assert!(shell
+            .aggregate_oracle_prices(&[report.clone(), report.clone()])
+            .is_err());
+
+        // the prices must be reported for the last block
+        let stale = signed_prices(last_height.next_height(), &prices);
+        assert!(shell.aggregate_oracle_prices(&[stale]).is_err());
+
+        // the prices must be signed with the protocol key of the validator
+        let mut forged = report;
+        forged.data.prices.insert(nam(), dec!(1000));
+        assert!(shell.aggregate_oracle_prices(&[forged]).is_err());
+
+        // the prices must be reported by more than 2/3 of the voting power
+        assert!(shell.aggregate_oracle_prices(&[]).is_err());
+    }
+}
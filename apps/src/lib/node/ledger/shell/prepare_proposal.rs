@@ -1,6 +1,7 @@
 //! Implementation of the [`RequestPrepareProposal`] ABCI++ method for the Shell
 
 use namada::core::hints;
+use namada::ledger::alloc_log::{AllocBin, AllocRole};
 use namada::ledger::storage::{DBIter, StorageHasher, TempWlStorage, DB};
 use namada::proof_of_stake::pos_queries::PosQueries;
 use namada::proto::Tx;
@@ -8,17 +9,22 @@ use namada::types::internal::TxInQueue;
 use namada::types::time::DateTimeUtc;
 use namada::types::transaction::wrapper::wrapper_tx::PairingEngine;
 use namada::types::transaction::{
-    AffineCurve, DecryptedTx, EllipticCurve, TxType,
+    AffineCurve, DecryptedTx, EllipticCurve, TxType, WrapperTx,
 };
 
 use super::super::*;
 #[allow(unused_imports)]
 use super::block_space_alloc;
+#[cfg(feature = "abcipp")]
+use super::block_space_alloc::states::TryAllocVoteExtension;
 use super::block_space_alloc::states::{
     BuildingDecryptedTxBatch, BuildingProtocolTxBatch,
-    EncryptedTxBatchAllocator, NextState, TryAlloc,
+    EncryptedTxBatchAllocator, NextState, TryAlloc, TryAllocPriority,
+    TryAllocWithGas,
+};
+use super::block_space_alloc::{
+    threshold, AllocFailure, BlockSpaceAllocator, EncryptedTxsSpace,
 };
-use super::block_space_alloc::{AllocFailure, BlockSpaceAllocator};
 #[cfg(feature = "abcipp")]
 use crate::facade::tendermint_proto::abci::ExtendedCommitInfo;
 use crate::facade::tendermint_proto::abci::RequestPrepareProposal;
@@ -27,6 +33,10 @@ use crate::facade::tendermint_proto::abci::{tx_record::TxAction, TxRecord};
 use crate::facade::tendermint_proto::google::protobuf::Timestamp;
 use crate::node::ledger::shims::abcipp_shim_types::shim::{response, TxBytes};
 
+/// The max gas limit of a wrapper tx admitted in the priority lane of the
+/// batch of encrypted txs
+const PRIORITY_TX_MAX_GAS: u64 = 1_000_000;
+
 impl<D, H> Shell<D, H>
 where
     D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
@@ -64,7 +74,6 @@ where
             alloc,
             #[cfg(feature = "abcipp")]
             req.local_last_commit,
-            #[cfg(not(feature = "abcipp"))]
             &req.txs,
         );
         txs.append(&mut protocol_txs);
@@ -78,37 +87,40 @@ where
         response::PrepareProposal { txs }
     }
 
-    /// Depending on the current block height offset within the epoch,
-    /// transition state accordingly, return a block space allocator
-    /// with or without encrypted txs.
+    /// Depending on the [`EncryptedTxsSpace`] strategy of the current
+    /// block height, transition state accordingly, return a block space
+    /// allocator with or without encrypted txs.
     ///
     /// # How to determine which path to take in the states DAG
     ///
     /// If we are at the second or third block height offset within an
-    /// epoch, we do not allow encrypted transactions to be included in
-    /// a block, therefore we return an allocator wrapped in an
-    /// [`EncryptedTxBatchAllocator::WithoutEncryptedTxs`] value.
+    /// epoch, or if governance disabled encrypted txs, we do not allow
+    /// encrypted transactions to be included in a block, therefore we
+    /// return an allocator wrapped in an
+    /// [`EncryptedTxBatchAllocator::WithoutEncryptedTxs`] value, whose
+    /// whole block space is left to decrypted and protocol txs.
     /// Otherwise, we return an allocator wrapped in an
     /// [`EncryptedTxBatchAllocator::WithEncryptedTxs`] value.
     #[inline]
     fn get_encrypted_txs_allocator(&self) -> EncryptedTxBatchAllocator {
-        let pos_queries = self.wl_storage.pos_queries();
-
-        let is_2nd_height_off = pos_queries.is_deciding_offset_within_epoch(1);
-        let is_3rd_height_off = pos_queries.is_deciding_offset_within_epoch(2);
+        let strategy = EncryptedTxsSpace::from_storage(&self.wl_storage);
 
-        if hints::unlikely(is_2nd_height_off || is_3rd_height_off) {
+        if hints::unlikely(!strategy.allows_encrypted_txs()) {
             tracing::warn!(
                 proposal_height =
-                    ?pos_queries.get_current_decision_height(),
+                    ?self.wl_storage.pos_queries().get_current_decision_height(),
                 "No mempool txs are being included in the current proposal"
             );
             EncryptedTxBatchAllocator::WithoutEncryptedTxs(
-                (&self.wl_storage).into(),
+                BlockSpaceAllocator::from_storage(&self.wl_storage)
+                    .withhold_encrypted_space()
+                    .with_vote_extension_space(self.vote_extension_block_space),
             )
         } else {
             EncryptedTxBatchAllocator::WithEncryptedTxs(
-                (&self.wl_storage).into(),
+                BlockSpaceAllocator::from_storage(&self.wl_storage)
+                    .with_priority_space(threshold::ONE_TENTH)
+                    .with_vote_extension_space(self.vote_extension_block_space),
             )
         }
     }
@@ -128,30 +140,91 @@ where
             // valid because of mempool check
             TryInto::<DateTimeUtc>::try_into(block_time).ok()
         });
-        let txs = txs
+        let block_height = self.wl_storage.storage.get_last_block_height() + 1;
+        let resolution = self.get_gas_limit_resolution();
+        let mut txs: Vec<_> = txs
             .iter()
             .filter_map(|tx_bytes| {
                 if let Ok(tx) = Tx::try_from(tx_bytes.as_slice()) {
                     // If tx doesn't have an expiration it is valid. If time cannot be
                     // retrieved from block default to last block datetime which has
                     // already been checked by mempool_validate, so it's valid
-                    if let (Some(block_time), Some(exp)) = (block_time.as_ref(), &tx.header.expiration) {
-                        if block_time > exp { return None }
+                    if let (Some(block_time), Some(exp)) =
+                        (block_time.as_ref(), &tx.header.expiration)
+                    {
+                        if block_time > exp {
+                            return None;
+                        }
                     }
-                    if tx.validate_header().is_ok() && tx.header().wrapper().is_some() && self.replay_protection_checks(&tx, tx_bytes.as_slice(), &mut temp_wl_storage).is_ok() {
-                        return Some(tx_bytes.clone());
+                    let wrapper = tx.header().wrapper()?;
+                    if wrapper.is_expired_at(block_height) {
+                        return None;
+                    }
+                    if tx.validate_header().is_ok()
+                        && self
+                            .replay_protection_checks(
+                                &tx,
+                                tx_bytes.as_slice(),
+                                &mut temp_wl_storage,
+                            )
+                            .is_ok()
+                    {
+                        // The gas limit was checked not to overflow by
+                        // mempool_validate
+                        let gas = wrapper
+                            .gas_limit
+                            .checked_raw(resolution)
+                            .unwrap_or(u64::MAX);
+                        return Some((
+                            self.is_priority_tx(wrapper, gas),
+                            wrapper.effective_gas_price(),
+                            gas,
+                            tx_bytes.clone(),
+                        ));
                     }
                 }
                 None
             })
-            .take_while(|tx_bytes| {
-                alloc.try_alloc(&tx_bytes[..])
+            .collect();
+        // Prioritize the wrappers offering the highest gas price, keeping the
+        // mempool order between the ones offering the same price
+        txs.sort_by_key(|(_, gas_price, _, _)| std::cmp::Reverse(*gas_price));
+        // Fill up the priority lane first. Priority txs that don't fit in it
+        // compete with the remaining txs for the rest of the space
+        let (priority_txs, txs): (Vec<_>, Vec<_>) =
+            txs.into_iter().partition(|(priority, _, _, _)| *priority);
+        let (mut priority_txs, deferred_txs): (Vec<_>, Vec<_>) =
+            priority_txs.into_iter().partition(|(_, _, gas, tx_bytes)| {
+                let result = alloc.try_alloc_priority(&tx_bytes[..], *gas);
+                self.record_alloc_decision(
+                    AllocRole::Proposer,
+                    tx_bytes,
+                    AllocBin::Priority,
+                    &result,
+                    alloc.space_left_in(AllocBin::Priority),
+                );
+                result.is_ok()
+            });
+        alloc.release_priority_space();
+        let mut txs: Vec<_> = deferred_txs.into_iter().chain(txs).collect();
+        txs.sort_by_key(|(_, gas_price, _, _)| std::cmp::Reverse(*gas_price));
+        priority_txs.extend(txs.into_iter().take_while(
+            |(_, _, gas, tx_bytes)| {
+                let result = alloc.try_alloc_with_gas(&tx_bytes[..], *gas);
+                self.record_alloc_decision(
+                    AllocRole::Proposer,
+                    tx_bytes,
+                    AllocBin::Encrypted,
+                    &result,
+                    alloc.space_left_in(AllocBin::Encrypted),
+                );
+                result
                     .map_or_else(
                         |status| match status {
-                            AllocFailure::Rejected { bin_space_left } => {
+                            AllocFailure::Rejected { needed, available } => {
                                 tracing::debug!(
-                                    tx_bytes_len = tx_bytes.len(),
-                                    bin_space_left,
+                                    needed,
+                                    available,
                                     proposal_height =
                                         ?pos_queries.get_current_decision_height(),
                                     "Dropping encrypted tx from the current proposal",
@@ -170,16 +243,50 @@ where
                                 );
                                 true
                             }
+                            AllocFailure::OverflowsBlockGas { gas_left } => {
+                                tracing::debug!(
+                                    tx_gas = gas,
+                                    gas_left,
+                                    proposal_height =
+                                        ?pos_queries.get_current_decision_height(),
+                                    "Dropping encrypted tx exceeding the block gas from the current proposal",
+                                );
+                                false
+                            }
+                            AllocFailure::OverMaxTxSize { max_tx_bytes } => {
+                                tracing::warn!(
+                                    tx_bytes_len = tx_bytes.len(),
+                                    max_tx_bytes,
+                                    proposal_height =
+                                        ?pos_queries.get_current_decision_height(),
+                                    "Dropping encrypted tx exceeding the max tx size from the current proposal",
+                                );
+                                true
+                            }
                         },
                         |()| true,
                     )
-            })
+            },
+        ));
+        let txs = priority_txs
+            .into_iter()
+            .map(|(_, _, _, tx_bytes)| tx_bytes)
             .collect();
         let alloc = alloc.next_state();
 
         (txs, alloc)
     }
 
+    /// Check if the given wrapper tx belongs in the priority lane of
+    /// the batch of encrypted txs, i.e. if it pays its fee in the native
+    /// token and declares a `gas` limit low enough for a PoS unbond or
+    /// withdrawal or a governance vote. Only the plaintext metadata of the
+    /// wrapper is looked at, the inner tx stays encrypted.
+    fn is_priority_tx(&self, wrapper: &WrapperTx, gas: u64) -> bool {
+        wrapper.fee.token == self.wl_storage.storage.native_token
+            && gas <= PRIORITY_TX_MAX_GAS
+    }
+
     /// Builds a batch of DKG decrypted transactions.
     // NOTE: we won't have frontrunning protection until V2 of the
     // Anoma protocol; Namada runs V1, therefore this method is
@@ -206,6 +313,7 @@ where
                      tx,
                      #[cfg(not(feature = "mainnet"))]
                      has_valid_pow,
+                     ..
                 }| {
                     let mut tx = tx.clone();
                     match tx.decrypt(privkey).ok()
@@ -230,12 +338,20 @@ where
             )
             // TODO: make sure all decrypted txs are accepted
             .take_while(|tx_bytes| {
-                alloc.try_alloc(&tx_bytes[..]).map_or_else(
+                let result = alloc.try_alloc(&tx_bytes[..]);
+                self.record_alloc_decision(
+                    AllocRole::Proposer,
+                    tx_bytes,
+                    AllocBin::Decrypted,
+                    &result,
+                    alloc.space_left_in(AllocBin::Decrypted),
+                );
+                result.map_or_else(
                     |status| match status {
-                        AllocFailure::Rejected { bin_space_left } => {
+                        AllocFailure::Rejected { needed, available } => {
                             tracing::warn!(
-                                tx_bytes_len = tx_bytes.len(),
-                                bin_space_left,
+                                needed,
+                                available,
                                 proposal_height =
                                     ?pos_queries.get_current_decision_height(),
                                 "Dropping decrypted tx from the current proposal",
@@ -252,6 +368,16 @@ where
                             );
                             true
                         }
+                        AllocFailure::OverflowsBlockGas { .. } => {
+                            unreachable!(
+                                "The gas of decrypted txs is not allocated"
+                            )
+                        }
+                        AllocFailure::OverMaxTxSize { .. } => {
+                            unreachable!(
+                                "The size of decrypted txs is not capped"
+                            )
+                        }
                     },
                     |()| true,
                 )
@@ -265,14 +391,78 @@ where
     /// Builds a batch of protocol transactions.
     fn build_protocol_txs(
         &self,
-        _alloc: BlockSpaceAllocator<BuildingProtocolTxBatch>,
-        #[cfg(feature = "abcipp")] _local_last_commit: Option<
+        alloc: BlockSpaceAllocator<BuildingProtocolTxBatch>,
+        #[cfg(feature = "abcipp")] local_last_commit: Option<
             ExtendedCommitInfo,
         >,
-        #[cfg(not(feature = "abcipp"))] _txs: &[TxBytes],
+        txs: &[TxBytes],
     ) -> Vec<TxBytes> {
-        // no protocol txs are implemented yet
-        vec![]
+        // vote extension protocol txs go first, after which the allocator
+        // is rebalanced to fit as many remaining protocol txs as possible
+        // in the unused block space
+        #[cfg(feature = "abcipp")]
+        let (mut protocol_txs, alloc) =
+            self.build_vote_extension_txs(alloc, local_last_commit);
+        #[cfg(not(feature = "abcipp"))]
+        let mut protocol_txs = vec![];
+        let mut alloc = alloc.next_state();
+
+        // the validator set updates and the votes on the Ethereum events
+        // broadcast by the validators, at most one of each kind per validator
+        let mut signers = HashSet::new();
+        protocol_txs.extend(
+            txs.iter()
+                .filter(|tx_bytes| {
+                    let protocol_tx = match Tx::try_from(tx_bytes.as_slice())
+                        .ok()
+                        .filter(|tx| tx.validate_header().is_ok())
+                        .map(|tx| tx.header().tx_type)
+                    {
+                        Some(TxType::Protocol(protocol_tx)) => protocol_tx.tx,
+                        _ => return false,
+                    };
+                    let kind = std::mem::discriminant(&protocol_tx);
+                    let signer = match protocol_tx {
+                        ProtocolTxType::ValidatorSetUpdate(update) => self
+                            .validate_valset_update(&update)
+                            .map(|_| update.validator),
+                        ProtocolTxType::EthereumEvents(vote) => self
+                            .validate_eth_events_vote(&vote)
+                            .map(|_| vote.validator),
+                        _ => return false,
+                    };
+                    match signer {
+                        Ok(signer) => {
+                            signers.insert((kind, signer))
+                                && alloc.try_alloc(tx_bytes).is_ok()
+                        }
+                        Err(_) => false,
+                    }
+                })
+                .cloned(),
+        );
+        protocol_txs
+    }
+
+    /// Builds the protocol txs of the vote extensions of the last block.
+    #[cfg(feature = "abcipp")]
+    fn build_vote_extension_txs(
+        &self,
+        mut alloc: BlockSpaceAllocator<BuildingProtocolTxBatch>,
+        local_last_commit: Option<ExtendedCommitInfo>,
+    ) -> (Vec<TxBytes>, BlockSpaceAllocator<BuildingProtocolTxBatch>) {
+        let txs = local_last_commit
+            .and_then(|commit| self.build_oracle_prices_tx(commit))
+            .into_iter()
+            .filter(|tx| match alloc.try_alloc_vote_extension(tx) {
+                Ok(()) => true,
+                Err(err) => {
+                    tracing::warn!("Dropping the oracle prices tx: {:?}", err);
+                    false
+                }
+            })
+            .collect();
+        (txs, alloc)
     }
 }
 
@@ -283,7 +473,7 @@ mod test_prepare_proposal {
     use namada::ledger::replay_protection;
     use namada::proof_of_stake::Epoch;
     use namada::proto::{Code, Data, Header, Section, Signature};
-    use namada::types::transaction::{Fee, WrapperTx};
+    use namada::types::transaction::{Fee, GasLimit, WrapperTx};
 
     use super::*;
     use crate::node::ledger::shell::test_utils::{self, gen_keypair};
@@ -320,7 +510,7 @@ mod test_prepare_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -361,7 +551,7 @@ mod test_prepare_proposal {
                 },
                 &keypair,
                 Epoch(0),
-                0.into(),
+                GasLimit::from_multiplier(0),
                 #[cfg(not(feature = "mainnet"))]
                 None,
             ))));
@@ -430,7 +620,7 @@ mod test_prepare_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -482,7 +672,7 @@ mod test_prepare_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -523,7 +713,7 @@ mod test_prepare_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -576,7 +766,7 @@ mod test_prepare_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -599,7 +789,7 @@ mod test_prepare_proposal {
                 },
                 &keypair_2,
                 Epoch(0),
-                0.into(),
+                GasLimit::from_multiplier(0),
                 #[cfg(not(feature = "mainnet"))]
                 None,
             ))));
@@ -627,6 +817,108 @@ mod test_prepare_proposal {
         assert_eq!(received.len(), 1);
     }
 
+    /// Test that wrapper txs are included in the block by decreasing gas
+    /// price, keeping the mempool order between equal gas prices
+    #[test]
+    fn test_wrapper_txs_ordered_by_gas_price() {
+        let (shell, _) = test_utils::setup(1);
+        let keypair = gen_keypair();
+        let gas_prices = [None, Some(5u64), Some(10), Some(5)];
+        let txs = gas_prices
+            .iter()
+            .enumerate()
+            .map(|(i, gas_price)| {
+                let mut wrapper = WrapperTx::new(
+                    Fee {
+                        amount: 0.into(),
+                        token: shell.wl_storage.storage.native_token.clone(),
+                    },
+                    &keypair,
+                    Epoch(0),
+                    GasLimit::from_multiplier(0),
+                    #[cfg(not(feature = "mainnet"))]
+                    None,
+                );
+                if let Some(gas_price) = gas_price {
+                    wrapper = wrapper.with_gas_price((*gas_price).into());
+                }
+                let mut wrapper_tx =
+                    Tx::new(TxType::Wrapper(Box::new(wrapper)));
+                wrapper_tx.header.chain_id = shell.chain_id.clone();
+                wrapper_tx
+                    .set_code(Code::new("wasm_code".as_bytes().to_owned()));
+                wrapper_tx.set_data(Data::new(
+                    format!("transaction data: {}", i).as_bytes().to_owned(),
+                ));
+                wrapper_tx.add_section(Section::Signature(Signature::new(
+                    &wrapper_tx.header_hash(),
+                    &keypair,
+                )));
+                wrapper_tx.encrypt(&Default::default());
+                wrapper_tx
+            })
+            .collect::<Vec<_>>();
+
+        let req = RequestPrepareProposal {
+            txs: txs.iter().map(Tx::to_bytes).collect(),
+            ..Default::default()
+        };
+        let received: Vec<_> = shell
+            .prepare_proposal(req)
+            .txs
+            .into_iter()
+            .map(|tx_bytes| {
+                Tx::try_from(tx_bytes.as_slice())
+                    .expect("Test failed")
+                    .header_hash()
+            })
+            .collect();
+        let expected: Vec<_> = [2, 1, 3, 0]
+            .into_iter()
+            .map(|i| txs[i].header_hash())
+            .collect();
+        assert_eq!(received, expected);
+    }
+
+    /// Test that wrapper transactions expired at the height of the block are
+    /// not included in it
+    #[test]
+    fn test_expired_height_wrapper_tx() {
+        let (shell, _) = test_utils::setup(1);
+        let keypair = gen_keypair();
+        let last_height = shell.wl_storage.storage.get_last_block_height();
+        let mut wrapper_tx = Tx::new(TxType::Wrapper(Box::new(
+            WrapperTx::new(
+                Fee {
+                    amount: 0.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_expiration_height(last_height),
+        )));
+        wrapper_tx.header.chain_id = shell.chain_id.clone();
+        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper_tx
+            .set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper_tx.add_section(Section::Signature(Signature::new(
+            &wrapper_tx.header_hash(),
+            &keypair,
+        )));
+        wrapper_tx.encrypt(&Default::default());
+
+        let req = RequestPrepareProposal {
+            txs: vec![wrapper_tx.to_bytes()],
+            ..Default::default()
+        };
+        let result = shell.prepare_proposal(req);
+        assert_eq!(result.txs.len(), 0);
+    }
+
     /// Test that expired wrapper transactions are not included in the block
     #[test]
     fn test_expired_wrapper_tx() {
@@ -641,7 +933,7 @@ mod test_prepare_proposal {
                 },
                 &keypair,
                 Epoch(0),
-                0.into(),
+                GasLimit::from_multiplier(0),
                 #[cfg(not(feature = "mainnet"))]
                 None,
             ))));
@@ -672,4 +964,78 @@ mod test_prepare_proposal {
         eprintln!("Proposal: {:?}", result.txs);
         assert_eq!(result.txs.len(), 0);
     }
+
+    /// Test that wrapper txs are not included in the block once
+    /// governance disables encrypted txs
+    #[test]
+    fn test_encrypted_txs_disabled() {
+        let (mut shell, _) = test_utils::setup(1);
+        let keypair = gen_keypair();
+        let mut wrapper_tx =
+            Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount: 0.into(),
+                    token: shell.wl_storage.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            ))));
+        wrapper_tx.header.chain_id = shell.chain_id.clone();
+        wrapper_tx.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        wrapper_tx
+            .set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper_tx.add_section(Section::Signature(Signature::new(
+            &wrapper_tx.header_hash(),
+            &keypair,
+        )));
+        wrapper_tx.encrypt(&Default::default());
+
+        let req = RequestPrepareProposal {
+            txs: vec![wrapper_tx.to_bytes()],
+            ..Default::default()
+        };
+        let result = shell.prepare_proposal(req.clone());
+        assert_eq!(result.txs.len(), 1);
+
+        namada::ledger::parameters::update_encrypted_txs_enabled_parameter(
+            &mut shell.wl_storage,
+            false,
+        )
+        .expect("Test failed");
+        assert_eq!(
+            EncryptedTxsSpace::from_storage(&shell.wl_storage),
+            EncryptedTxsSpace::Withheld
+        );
+        let result = shell.prepare_proposal(req);
+        assert_eq!(result.txs.len(), 0);
+    }
+
+    /// Test that wrappers are classified in the priority lane from their
+    /// fee token and gas limit only
+    #[test]
+    fn test_is_priority_tx() {
+        let (shell, _) = test_utils::setup(1);
+        let keypair = gen_keypair();
+        let wrapper = |token: Address| {
+            WrapperTx::new(
+                Fee {
+                    amount: 0.into(),
+                    token,
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+        };
+        let native = wrapper(shell.wl_storage.storage.native_token.clone());
+        assert!(shell.is_priority_tx(&native, PRIORITY_TX_MAX_GAS));
+        assert!(!shell.is_priority_tx(&native, PRIORITY_TX_MAX_GAS + 1));
+        let foreign = wrapper(address::btc());
+        assert!(!shell.is_priority_tx(&foreign, PRIORITY_TX_MAX_GAS));
+    }
 }
@@ -4,6 +4,7 @@
 use data_encoding::HEXUPPER;
 use namada::core::hints;
 use namada::core::ledger::storage::WlStorage;
+use namada::ledger::alloc_log::{AllocBin, AllocRole};
 use namada::ledger::storage::TempWlStorage;
 use namada::proof_of_stake::pos_queries::PosQueries;
 use namada::types::internal::TxInQueue;
@@ -12,7 +13,7 @@ use super::*;
 use crate::facade::tendermint_proto::abci::response_process_proposal::ProposalStatus;
 use crate::facade::tendermint_proto::abci::RequestProcessProposal;
 use crate::node::ledger::shell::block_space_alloc::{
-    threshold, AllocFailure, TxBin,
+    AllocFailure, BlockSpaceSplit, EncryptedTxsSpace, GasBin, TxBin,
 };
 use crate::node::ledger::shims::abcipp_shim_types::shim::response::ProcessProposal;
 use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
@@ -23,6 +24,8 @@ use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
 pub struct ValidationMeta {
     /// Space utilized by encrypted txs.
     pub encrypted_txs_bin: TxBin,
+    /// Gas declared by encrypted txs.
+    pub encrypted_txs_gas: GasBin,
     /// Space utilized by all txs.
     pub txs_bin: TxBin,
     /// Check if the decrypted tx queue has any elements
@@ -43,13 +46,23 @@ where
     fn from(storage: &WlStorage<D, H>) -> Self {
         let max_proposal_bytes =
             storage.pos_queries().get_max_proposal_bytes().get();
-        let encrypted_txs_bin =
-            TxBin::init_over_ratio(max_proposal_bytes, threshold::ONE_THIRD);
+        let split = BlockSpaceSplit::from_storage(storage);
+        let mut encrypted_txs_bin =
+            TxBin::init_over_ratio(max_proposal_bytes, split.encrypted());
+        let max_tx_bytes =
+            namada::ledger::parameters::read_max_tx_bytes_parameter(storage)
+                .expect("Must be able to read max tx bytes parameter");
+        encrypted_txs_bin.set_max_tx_bytes(max_tx_bytes);
         let txs_bin = TxBin::init(max_proposal_bytes);
+        let max_block_gas =
+            namada::ledger::parameters::read_max_block_gas_parameter(storage)
+                .expect("Must be able to read max block gas parameter");
+        let encrypted_txs_gas = GasBin::init(max_block_gas);
         Self {
             decrypted_queue_has_remaining_txs: false,
             has_decrypted_txs: false,
             encrypted_txs_bin,
+            encrypted_txs_gas,
             txs_bin,
         }
     }
@@ -89,7 +102,7 @@ where
         // deserialize properly, that have invalid signatures
         // and that have invalid wasm code to reach FinalizeBlock.
         let invalid_txs = tx_results.iter().any(|res| {
-            let error = ErrorCodes::from_u32(res.code).expect(
+            let error = ResultCode::from_u32(res.code).expect(
                 "All error codes returned from process_single_tx are valid",
             );
             !error.is_recoverable()
@@ -138,6 +151,10 @@ where
         let mut tx_queue_iter = self.wl_storage.storage.tx_queue.iter();
         let mut temp_wl_storage = TempWlStorage::new(&self.wl_storage.storage);
         let mut metadata = ValidationMeta::from(&self.wl_storage);
+        // The wasm caches used to dry run the fee unshielding txs, shared by
+        // all the txs of the proposal
+        let mut vp_wasm_cache = self.vp_wasm_cache.clone();
+        let mut tx_wasm_cache = self.tx_wasm_cache.clone();
         let tx_results = txs
             .iter()
             .map(|tx_bytes| {
@@ -147,9 +164,11 @@ where
                     &mut metadata,
                     &mut temp_wl_storage,
                     block_time,
+                    &mut vp_wasm_cache,
+                    &mut tx_wasm_cache,
                 );
-                let error_code = ErrorCodes::from_u32(result.code).unwrap();
-                if let ErrorCodes::Ok = error_code {
+                let error_code = ResultCode::from_u32(result.code).unwrap();
+                if let ResultCode::Ok = error_code {
                     temp_wl_storage.write_log.commit_tx();
                 } else {
                     tracing::info!(
@@ -197,11 +216,21 @@ where
         metadata: &mut ValidationMeta,
         temp_wl_storage: &mut TempWlStorage<D, H>,
         block_time: DateTimeUtc,
+        vp_wasm_cache: &mut VpCache<WasmCacheRwAccess>,
+        tx_wasm_cache: &mut TxCache<WasmCacheRwAccess>,
     ) -> TxResult {
         // try to allocate space for this tx
-        if let Err(e) = metadata.txs_bin.try_dump(tx_bytes) {
+        let result = metadata.txs_bin.try_dump(tx_bytes);
+        self.record_alloc_decision(
+            AllocRole::Verifier,
+            tx_bytes,
+            AllocBin::Block,
+            &result,
+            metadata.txs_bin.space_left_in_bytes(),
+        );
+        if let Err(e) = result {
             return TxResult {
-                code: ErrorCodes::AllocationError.into(),
+                code: ResultCode::AllocationError.into(),
                 info: match e {
                     AllocFailure::Rejected { .. } => {
                         "No more space left in the block"
@@ -210,12 +239,19 @@ where
                         "The given tx is larger than the max configured \
                          proposal size"
                     }
+                    AllocFailure::OverflowsBlockGas { .. } => {
+                        "No more gas left in the block"
+                    }
+                    AllocFailure::OverMaxTxSize { .. } => {
+                        "The given tx is larger than the max tx size"
+                    }
                 }
                 .into(),
             };
         }
 
-        let maybe_tx = Tx::try_from(tx_bytes).map_or_else(
+        let limits = self.get_tx_decode_limits();
+        let maybe_tx = Tx::decode_with_limits(tx_bytes, &limits).map_or_else(
             |err| {
                 tracing::debug!(
                     ?err,
@@ -223,7 +259,7 @@ where
                      PrepareProposal"
                 );
                 Err(TxResult {
-                    code: ErrorCodes::InvalidTx.into(),
+                    code: ResultCode::InvalidTx.into(),
                     info: "The submitted transaction was not deserializable"
                         .into(),
                 })
@@ -235,7 +271,7 @@ where
                     // This occurs if the wrapper / protocol tx signature is
                     // invalid
                     return Err(TxResult {
-                        code: ErrorCodes::InvalidSig.into(),
+                        code: ResultCode::InvalidSig.into(),
                         info: err.to_string(),
                     });
                 }
@@ -252,23 +288,23 @@ where
 
         if let Err(err) = tx.validate_header() {
             return TxResult {
-                code: ErrorCodes::InvalidSig.into(),
+                code: ResultCode::InvalidSig.into(),
                 info: err.to_string(),
             };
         }
         match tx.header().tx_type {
             // If it is a raw transaction, we do no further validation
             TxType::Raw => TxResult {
-                code: ErrorCodes::InvalidTx.into(),
+                code: ResultCode::InvalidTx.into(),
                 info: "Transaction rejected: Non-encrypted transactions are \
                        not supported"
                     .into(),
             },
-            TxType::Protocol(_) => {
+            TxType::Protocol(protocol_tx) => {
                 // Tx chain id
                 if tx_chain_id != self.chain_id {
                     return TxResult {
-                        code: ErrorCodes::InvalidChainId.into(),
+                        code: ResultCode::InvalidChainId.into(),
                         info: format!(
                             "Tx carries a wrong chain id: expected {}, found \
                              {}",
@@ -281,7 +317,7 @@ where
                 if let Some(exp) = tx_expiration {
                     if block_time > exp {
                         return TxResult {
-                            code: ErrorCodes::ExpiredTx.into(),
+                            code: ResultCode::ExpiredTx.into(),
                             info: format!(
                                 "Tx expired at {:#?}, block time: {:#?}",
                                 exp, block_time
@@ -289,11 +325,40 @@ where
                         };
                     }
                 }
-                TxResult {
-                    code: ErrorCodes::InvalidTx.into(),
-                    info: "Protocol transactions are a fun new feature that \
-                           is coming soon to a blockchain near you. Patience."
-                        .into(),
+                let validated = match &protocol_tx.tx {
+                    ProtocolTxType::OraclePrices(reports) => self
+                        .aggregate_oracle_prices(reports)
+                        .map(|_| ())
+                        .map_err(|err| {
+                            format!("Invalid oracle prices: {}", err)
+                        }),
+                    ProtocolTxType::ValidatorSetUpdate(update) => self
+                        .validate_valset_update(update)
+                        .map(|_| ())
+                        .map_err(|err| {
+                            format!("Invalid validator set update: {}", err)
+                        }),
+                    ProtocolTxType::EthereumEvents(vote) => self
+                        .validate_eth_events_vote(vote)
+                        .map(|_| ())
+                        .map_err(|err| {
+                            format!("Invalid Ethereum events vote: {}", err)
+                        }),
+                    _ => Err("Protocol transactions are a fun new feature \
+                              that is coming soon to a blockchain near you. \
+                              Patience."
+                        .into()),
+                };
+                match validated {
+                    Ok(()) => TxResult {
+                        code: ResultCode::Ok.into(),
+                        info: "Process Proposal accepted this transaction"
+                            .into(),
+                    },
+                    Err(info) => TxResult {
+                        code: ResultCode::InvalidTx.into(),
+                        info,
+                    },
                 }
             }
             TxType::Decrypted(tx_header) => {
@@ -310,7 +375,7 @@ where
                             != inner_tx.header_hash()
                         {
                             TxResult {
-                                code: ErrorCodes::InvalidOrder.into(),
+                                code: ResultCode::InvalidOrder.into(),
                                 info: "Process proposal rejected a decrypted \
                                        transaction that violated the tx order \
                                        determined in the previous block"
@@ -324,7 +389,7 @@ where
                             // Tx chain id
                             if wrapper.tx.header.chain_id != self.chain_id {
                                 return TxResult {
-                                    code: ErrorCodes::InvalidDecryptedChainId
+                                    code: ResultCode::InvalidDecryptedChainId
                                         .into(),
                                     info: format!(
                                         "Decrypted tx carries a wrong chain \
@@ -339,7 +404,7 @@ where
                             if let Some(exp) = wrapper.tx.header.expiration {
                                 if block_time > exp {
                                     return TxResult {
-                                        code: ErrorCodes::ExpiredDecryptedTx
+                                        code: ResultCode::ExpiredDecryptedTx
                                             .into(),
                                         info: format!(
                                             "Decrypted tx expired at {:#?}, \
@@ -350,7 +415,7 @@ where
                                 }
                             }
                             TxResult {
-                                code: ErrorCodes::Ok.into(),
+                                code: ResultCode::Ok.into(),
                                 info: "Process Proposal accepted this \
                                        transaction"
                                     .into(),
@@ -358,7 +423,7 @@ where
                         } else {
                             // Wrong inner tx commitment
                             TxResult {
-                                code: ErrorCodes::InvalidTx.into(),
+                                code: ResultCode::InvalidTx.into(),
                                 info: "The encrypted payload of tx was \
                                        incorrectly marked as un-decryptable"
                                     .into(),
@@ -366,7 +431,7 @@ where
                         }
                     }
                     None => TxResult {
-                        code: ErrorCodes::ExtraTxs.into(),
+                        code: ResultCode::ExtraTxs.into(),
                         info: "Received more decrypted txs than expected"
                             .into(),
                     },
@@ -376,16 +441,24 @@ where
                 // decrypted txs shouldn't show up before wrapper txs
                 if metadata.has_decrypted_txs {
                     return TxResult {
-                        code: ErrorCodes::InvalidTx.into(),
+                        code: ResultCode::InvalidTx.into(),
                         info: "Decrypted txs should not be proposed before \
                                wrapper txs"
                             .into(),
                     };
                 }
                 // try to allocate space for this encrypted tx
-                if let Err(e) = metadata.encrypted_txs_bin.try_dump(tx_bytes) {
+                let result = metadata.encrypted_txs_bin.try_dump(tx_bytes);
+                self.record_alloc_decision(
+                    AllocRole::Verifier,
+                    tx_bytes,
+                    AllocBin::Encrypted,
+                    &result,
+                    metadata.encrypted_txs_bin.space_left_in_bytes(),
+                );
+                if let Err(e) = result {
                     return TxResult {
-                        code: ErrorCodes::AllocationError.into(),
+                        code: ResultCode::AllocationError.into(),
                         info: match e {
                             AllocFailure::Rejected { .. } => {
                                 "No more space left in the block for wrapper \
@@ -395,13 +468,21 @@ where
                                 "The given wrapper tx is larger than 1/3 of \
                                  the available block space"
                             }
+                            AllocFailure::OverflowsBlockGas { .. } => {
+                                "No more gas left in the block for wrapper \
+                                 txs"
+                            }
+                            AllocFailure::OverMaxTxSize { .. } => {
+                                "The given wrapper tx is larger than the max \
+                                 tx size"
+                            }
                         }
                         .into(),
                     };
                 }
                 if hints::unlikely(self.encrypted_txs_not_allowed()) {
                     return TxResult {
-                        code: ErrorCodes::AllocationError.into(),
+                        code: ResultCode::AllocationError.into(),
                         info: "Wrapper txs not allowed at the current block \
                                height"
                             .into(),
@@ -411,7 +492,7 @@ where
                 // ChainId check
                 if tx_chain_id != self.chain_id {
                     return TxResult {
-                        code: ErrorCodes::InvalidChainId.into(),
+                        code: ResultCode::InvalidChainId.into(),
                         info: format!(
                             "Tx carries a wrong chain id: expected {}, found \
                              {}",
@@ -424,7 +505,7 @@ where
                 if let Some(exp) = tx_expiration {
                     if block_time > exp {
                         return TxResult {
-                            code: ErrorCodes::ExpiredTx.into(),
+                            code: ResultCode::ExpiredTx.into(),
                             info: format!(
                                 "Tx expired at {:#?}, block time: {:#?}",
                                 exp, block_time
@@ -433,10 +514,118 @@ where
                     }
                 }
 
+                // Wrapper expiration check
+                let height =
+                    self.wl_storage.storage.get_last_block_height() + 1;
+                if wrapper.is_expired_at(height) {
+                    return TxResult {
+                        code: ResultCode::ExpiredTx.into(),
+                        info: format!(
+                            "Wrapper tx expired at height {}, block height: \
+                             {}",
+                            wrapper.expiration_height.unwrap_or_default(),
+                            height
+                        ),
+                    };
+                }
+
+                // Memo check
+                let max_memo_len = self.get_max_memo_len();
+                if !tx.validate_memo(max_memo_len) {
+                    return TxResult {
+                        code: ResultCode::InvalidTx.into(),
+                        info: format!(
+                            "The memo of the wrapper tx is missing or exceeds \
+                             the max memo length of {} bytes",
+                            max_memo_len
+                        ),
+                    };
+                }
+
+                // Gas limit check
+                let resolution = self.get_gas_limit_resolution();
+                if wrapper.gas_limit.checked_raw(resolution).is_none() {
+                    return TxResult {
+                        code: ResultCode::InvalidTx.into(),
+                        info: format!(
+                            "The gas limit of the wrapper tx overflows at the \
+                             gas limit resolution of {}",
+                            resolution
+                        ),
+                    };
+                }
+
+                // Block gas check
+                let gas = wrapper
+                    .gas_limit
+                    .checked_raw(resolution)
+                    .expect("The gas limit was checked not to overflow");
+                if let Err(AllocFailure::OverflowsBlockGas { gas_left }) =
+                    metadata.encrypted_txs_gas.try_dump(gas)
+                {
+                    return TxResult {
+                        code: ResultCode::AllocationError.into(),
+                        info: format!(
+                            "The gas limit {} of the wrapper tx exceeds the \
+                             gas left in the block {}",
+                            gas, gas_left
+                        ),
+                    };
+                }
+
+                // Fee payer authorization check
+                if let Err(err) =
+                    self.verify_fee_payer_authorization(&tx, &wrapper)
+                {
+                    return TxResult {
+                        code: ResultCode::InvalidSig.into(),
+                        info: err,
+                    };
+                }
+
+                // Check that the fee token is whitelisted and convert the
+                // minimum gas price, denominated in the native token, into it
+                let fee_tokens = self.get_fee_tokens();
+                let native_token = &self.wl_storage.storage.native_token;
+                let convert = |amount| {
+                    namada::ledger::parameters::convert_fee_amount(
+                        &fee_tokens,
+                        native_token,
+                        &wrapper.fee.token,
+                        amount,
+                    )
+                };
+                let min_gas_price = match convert(self.get_min_gas_price()) {
+                    Some(min_gas_price) => min_gas_price,
+                    None => {
+                        return TxResult {
+                            code: ResultCode::InvalidTx.into(),
+                            info: format!(
+                                "Fees cannot be paid in token {}",
+                                wrapper.fee.token
+                            ),
+                        };
+                    }
+                };
+
+                // Minimum fee check
+                if let Err(err) =
+                    wrapper.validate_min_gas_price(resolution, min_gas_price)
+                {
+                    return TxResult {
+                        code: ResultCode::InvalidTx.into(),
+                        info: format!(
+                            "The fee of the wrapper tx does not cover its gas \
+                             limit at the minimum gas price of {}: {}",
+                            min_gas_price, err
+                        ),
+                    };
+                }
+
                 // validate the ciphertext via Ferveo
                 if !tx.validate_ciphertext() {
                     TxResult {
-                        code: ErrorCodes::InvalidTx.into(),
+                        code: ResultCode::InvalidTx.into(),
                         info: format!(
                             "The ciphertext of the wrapped tx {} is invalid",
                             hash_tx(tx_bytes)
@@ -450,11 +639,57 @@ where
                         temp_wl_storage,
                     ) {
                         return TxResult {
-                            code: ErrorCodes::ReplayTx.into(),
+                            code: ResultCode::ReplayTx.into(),
                             info: e.to_string(),
                         };
                     }
 
+                    // Fee unshielding check
+                    if let Some(unshield) = &wrapper.unshield {
+                        if tx
+                            .get_section(unshield)
+                            .and_then(Section::masp_tx)
+                            .is_none()
+                        {
+                            return TxResult {
+                                code: ResultCode::InvalidTx.into(),
+                                info: format!(
+                                    "Missing fee unshielding MASP \
+                                     transaction section {}",
+                                    unshield
+                                ),
+                            };
+                        }
+                    }
+
+                    // Convert the fees into the fee token
+                    let fees = match convert(self.get_wrapper_tx_fees()) {
+                        Some(fees) => fees,
+                        None => {
+                            return TxResult {
+                                code: ResultCode::InvalidTx.into(),
+                                info: format!(
+                                    "Fees cannot be paid in token {}",
+                                    wrapper.fee.token
+                                ),
+                            };
+                        }
+                    };
+                    // Add the tip offered on top of the fees
+                    let fees = match wrapper
+                        .tip(resolution)
+                        .and_then(|tip| fees.checked_add(tip))
+                    {
+                        Some(fees) => fees,
+                        None => {
+                            return TxResult {
+                                code: ResultCode::InvalidTx.into(),
+                                info: "The tip of the wrapper tx overflows"
+                                    .into(),
+                            };
+                        }
+                    };
+
                     // If the public key corresponds to the MASP sentinel
                     // transaction key, then the fee payer is effectively
                     // the MASP, otherwise derive
@@ -464,9 +699,44 @@ where
                     } else {
                         masp()
                     };
+                    // Dry run the fee unshielding, if requested, such that
+                    // the unshielded funds count towards the balance of the
+                    // fee payer
+                    if let Some(unshield) = &wrapper.unshield {
+                        let result = self
+                            .fee_unshielding_tx(
+                                &tx,
+                                unshield,
+                                &fee_payer,
+                                &wrapper.fee.token,
+                                fees,
+                            )
+                            .and_then(|unshield_tx| {
+                                apply_fee_unshielding(
+                                    unshield_tx,
+                                    &mut temp_wl_storage.write_log,
+                                    temp_wl_storage.storage,
+                                    vp_wasm_cache,
+                                    tx_wasm_cache,
+                                )
+                            });
+                        if let Err(msg) = result {
+                            return TxResult {
+                                code: ResultCode::InvalidTx.into(),
+                                info: format!(
+                                    "Fee unshielding failed: {}",
+                                    msg
+                                ),
+                            };
+                        }
+                    }
                     // check that the fee payer has sufficient balance
-                    let balance =
-                        self.get_balance(&wrapper.fee.token, &fee_payer);
+                    let balance = storage_api::token::read_balance(
+                        &*temp_wl_storage,
+                        &wrapper.fee.token,
+                        &fee_payer,
+                    )
+                    .expect("Token balance read in the protocol must not fail");
 
                     // In testnets, tx is allowed to skip fees if it
                     // includes a valid PoW
@@ -475,15 +745,15 @@ where
                     #[cfg(feature = "mainnet")]
                     let has_valid_pow = false;
 
-                    if has_valid_pow || self.get_wrapper_tx_fees() <= balance {
+                    if has_valid_pow || fees <= balance {
                         TxResult {
-                            code: ErrorCodes::Ok.into(),
+                            code: ResultCode::Ok.into(),
                             info: "Process proposal accepted this transaction"
                                 .into(),
                         }
                     } else {
                         TxResult {
-                            code: ErrorCodes::InvalidTx.into(),
+                            code: ResultCode::InvalidTx.into(),
                             info: "The address given does not have sufficient \
                                    balance to pay fee"
                                 .into(),
@@ -504,10 +774,8 @@ where
     /// Checks if it is not possible to include encrypted txs at the current
     /// block height.
     fn encrypted_txs_not_allowed(&self) -> bool {
-        let pos_queries = self.wl_storage.pos_queries();
-        let is_2nd_height_off = pos_queries.is_deciding_offset_within_epoch(1);
-        let is_3rd_height_off = pos_queries.is_deciding_offset_within_epoch(2);
-        is_2nd_height_off || is_3rd_height_off
+        !EncryptedTxsSpace::from_storage(&self.wl_storage)
+            .allows_encrypted_txs()
     }
 }
 
@@ -515,14 +783,19 @@ where
 /// are covered by the e2e tests.
 #[cfg(test)]
 mod test_process_proposal {
-    use namada::ledger::parameters::storage::get_wrapper_tx_fees_key;
+    use namada::ledger::parameters::storage::{
+        get_fee_tokens_key, get_wrapper_tx_fees_key,
+    };
     use namada::proto::{Code, Data, Section, Signature};
+    use namada::types::address::btc;
     use namada::types::hash::Hash;
     use namada::types::key::*;
     use namada::types::storage::Epoch;
     use namada::types::token::Amount;
     use namada::types::transaction::protocol::{ProtocolTx, ProtocolTxType};
-    use namada::types::transaction::{Fee, WrapperTx, MIN_FEE};
+    use namada::types::transaction::{
+        Fee, GasLimit, WrapperTx, MIN_FEE, WRAPPER_WIRE_VERSION,
+    };
 
     use super::*;
     use crate::node::ledger::shell::test_utils::{
@@ -542,7 +815,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -561,7 +834,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::InvalidSig)
+                    u32::from(ResultCode::InvalidSig)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -587,7 +860,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -618,7 +891,7 @@ mod test_process_proposal {
                                       data with a signature.";
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::InvalidSig)
+                    u32::from(ResultCode::InvalidSig)
                 );
                 assert!(
                     response[0].result.info.contains(expected_error),
@@ -651,7 +924,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -673,7 +946,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::InvalidTx)
+                    u32::from(ResultCode::InvalidTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -685,6 +958,60 @@ mod test_process_proposal {
         }
     }
 
+    /// Test that if the fee token of a wrapper tx is not whitelisted,
+    /// [`process_proposal`] rejects that block
+    #[test]
+    fn test_wrapper_fee_token_not_whitelisted() {
+        let (mut shell, _) = test_utils::setup(1);
+        let fee_tokens = BTreeMap::from([(
+            shell.wl_storage.storage.native_token.clone(),
+            Decimal::ONE,
+        )]);
+        shell
+            .wl_storage
+            .write_log
+            .write(&get_fee_tokens_key(), fee_tokens.try_to_vec().unwrap())
+            .unwrap();
+        let keypair = crate::wallet::defaults::daewon_keypair();
+        let mut outer_tx = Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount: 1.into(),
+                token: btc(),
+            },
+            &keypair,
+            Epoch(0),
+            GasLimit::from_multiplier(0),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        ))));
+        outer_tx.header.chain_id = shell.chain_id.clone();
+        outer_tx.set_code(Code::new("wasm_code".as_bytes().to_owned()));
+        outer_tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        outer_tx.add_section(Section::Signature(Signature::new(
+            &outer_tx.header_hash(),
+            &keypair,
+        )));
+        outer_tx.encrypt(&Default::default());
+
+        let request = ProcessProposal {
+            txs: vec![outer_tx.to_bytes()],
+        };
+
+        match shell.process_proposal(request) {
+            Ok(_) => panic!("Test failed"),
+            Err(TestError::RejectProposal(response)) => {
+                assert_eq!(
+                    response[0].result.code,
+                    u32::from(ResultCode::InvalidTx)
+                );
+                assert_eq!(
+                    response[0].result.info,
+                    format!("Fees cannot be paid in token {}", btc()),
+                );
+            }
+        }
+    }
+
     /// Test that if the account submitting the tx does
     /// not have sufficient balance to pay the fee,
     /// [`process_proposal`] rejects the entire block
@@ -718,7 +1045,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -740,7 +1067,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::InvalidTx)
+                    u32::from(ResultCode::InvalidTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -769,7 +1096,7 @@ mod test_process_proposal {
                     },
                     &keypair,
                     Epoch(0),
-                    0.into(),
+                    GasLimit::from_multiplier(0),
                     #[cfg(not(feature = "mainnet"))]
                     None,
                 ))));
@@ -804,7 +1131,7 @@ mod test_process_proposal {
                 panic!("Test failed")
             }
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidOrder));
+        assert_eq!(response.result.code, u32::from(ResultCode::InvalidOrder));
         assert_eq!(
             response.result.info,
             String::from(
@@ -828,7 +1155,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -848,7 +1175,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::InvalidTx)
+                    u32::from(ResultCode::InvalidTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -876,7 +1203,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -902,7 +1229,7 @@ mod test_process_proposal {
         } else {
             panic!("Test failed")
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::Ok));
+        assert_eq!(response.result.code, u32::from(ResultCode::Ok));
     }
 
     /// Test that if a wrapper tx contains garbage bytes
@@ -914,13 +1241,20 @@ mod test_process_proposal {
         let keypair = crate::wallet::defaults::daewon_keypair();
         // not valid tx bytes
         let wrapper = WrapperTx {
+            wire_version: WRAPPER_WIRE_VERSION,
             fee: Fee {
                 amount: 0.into(),
                 token: shell.wl_storage.storage.native_token.clone(),
             },
             pk: keypair.ref_to(),
             epoch: Epoch(0),
-            gas_limit: 0.into(),
+            gas_limit: GasLimit::from_multiplier(0),
+            unshield: None,
+            gas_price: None,
+            expiration_height: None,
+            nonce: None,
+            sponsor: None,
+            fee_payer_address: None,
             #[cfg(not(feature = "mainnet"))]
             pow_solution: None,
         };
@@ -943,7 +1277,7 @@ mod test_process_proposal {
         } else {
             panic!("Test failed")
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::Ok));
+        assert_eq!(response.result.code, u32::from(ResultCode::Ok));
     }
 
     /// Test that if more decrypted txs are submitted to
@@ -973,7 +1307,7 @@ mod test_process_proposal {
         } else {
             panic!("Test failed")
         };
-        assert_eq!(response.result.code, u32::from(ErrorCodes::ExtraTxs));
+        assert_eq!(response.result.code, u32::from(ResultCode::ExtraTxs));
         assert_eq!(
             response.result.info,
             String::from("Received more decrypted txs than expected"),
@@ -999,7 +1333,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::InvalidTx)
+                    u32::from(ResultCode::InvalidTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -1027,7 +1361,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1060,7 +1394,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::ReplayTx)
+                    u32::from(ResultCode::ReplayTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -1099,7 +1433,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1119,10 +1453,10 @@ mod test_process_proposal {
         match shell.process_proposal(request) {
             Ok(_) => panic!("Test failed"),
             Err(TestError::RejectProposal(response)) => {
-                assert_eq!(response[0].result.code, u32::from(ErrorCodes::Ok));
+                assert_eq!(response[0].result.code, u32::from(ResultCode::Ok));
                 assert_eq!(
                     response[1].result.code,
-                    u32::from(ErrorCodes::ReplayTx)
+                    u32::from(ResultCode::ReplayTx)
                 );
                 // The checks happens on the inner hash first, so the tx is
                 // rejected because of this hash, not the
@@ -1157,7 +1491,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1189,7 +1523,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::ReplayTx)
+                    u32::from(ResultCode::ReplayTx)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -1241,7 +1575,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1264,7 +1598,7 @@ mod test_process_proposal {
             },
             &keypair_2,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1281,10 +1615,10 @@ mod test_process_proposal {
         match shell.process_proposal(request) {
             Ok(_) => panic!("Test failed"),
             Err(TestError::RejectProposal(response)) => {
-                assert_eq!(response[0].result.code, u32::from(ErrorCodes::Ok));
+                assert_eq!(response[0].result.code, u32::from(ResultCode::Ok));
                 assert_eq!(
                     response[1].result.code,
-                    u32::from(ErrorCodes::ReplayTx)
+                    u32::from(ResultCode::ReplayTx)
                 );
                 assert_eq!(
                     response[1].result.info,
@@ -1312,7 +1646,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1346,7 +1680,7 @@ mod test_process_proposal {
                 for res in response {
                     assert_eq!(
                         res.result.code,
-                        u32::from(ErrorCodes::InvalidChainId)
+                        u32::from(ResultCode::InvalidChainId)
                     );
                     assert_eq!(
                         res.result.info,
@@ -1376,7 +1710,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1396,6 +1730,7 @@ mod test_process_proposal {
         )));
         let wrapper_in_queue = TxInQueue {
             tx: wrapper,
+            fee: Amount::default(),
             has_valid_pow: false,
         };
         shell.wl_storage.storage.tx_queue.push(wrapper_in_queue);
@@ -1409,7 +1744,7 @@ mod test_process_proposal {
             Ok(response) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::InvalidDecryptedChainId)
+                    u32::from(ResultCode::InvalidDecryptedChainId)
                 );
                 assert_eq!(
                     response[0].result.info,
@@ -1437,7 +1772,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1460,7 +1795,7 @@ mod test_process_proposal {
             Err(TestError::RejectProposal(response)) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::ExpiredTx)
+                    u32::from(ResultCode::ExpiredTx)
                 );
             }
         }
@@ -1480,7 +1815,7 @@ mod test_process_proposal {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -1501,6 +1836,7 @@ mod test_process_proposal {
         )));
         let wrapper_in_queue = TxInQueue {
             tx: wrapper,
+            fee: Amount::default(),
             has_valid_pow: false,
         };
         shell.wl_storage.storage.tx_queue.push(wrapper_in_queue);
@@ -1513,7 +1849,7 @@ mod test_process_proposal {
             Ok(response) => {
                 assert_eq!(
                     response[0].result.code,
-                    u32::from(ErrorCodes::ExpiredDecryptedTx)
+                    u32::from(ResultCode::ExpiredDecryptedTx)
                 );
             }
             Err(_) => panic!("Test failed"),
@@ -28,9 +28,12 @@ where
         let ctx = RequestCtx {
             wl_storage: &self.wl_storage,
             event_log: self.event_log(),
+            alloc_replay_log: &self.alloc_replay_log,
+            mempool_view: &self.mempool_view,
             vp_wasm_cache: self.vp_wasm_cache.read_only(),
             tx_wasm_cache: self.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: self.storage_read_past_height_limit,
+            mempool_min_gas_price: self.mempool_min_gas_price,
         };
 
         // Convert request to domain-type
@@ -0,0 +1,90 @@
+//! Replacement of the wrappers pending in the mempool by wrappers of the
+//! same inner tx that offer a higher fee.
+//!
+//! The shell tracks the wrapper under which each inner tx is pending, such
+//! that a new wrapper of the same inner tx is only accepted in `CheckTx` if
+//! it outbids the pending one, as per
+//! [`WrapperTx::validate_replacement`]. The outbid wrapper is then evicted
+//! from the mempool when it is rechecked after the next block.
+//!
+//! Every wrapper is recorded with the height of the last committed block
+//! when it was validated. The pending wrappers are rechecked, and thus
+//! recorded again, after every block, such that a record which was not
+//! refreshed for [`PENDING_WRAPPER_EXPIRY_BLOCKS`] blocks belongs to a
+//! wrapper that has left the mempool and is dropped.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use namada::types::hash::Hash;
+use namada::types::storage::BlockHeight;
+use namada::types::transaction::WrapperTx;
+
+/// The number of blocks after which the record of a pending wrapper that was
+/// not rechecked expires
+pub const PENDING_WRAPPER_EXPIRY_BLOCKS: u64 = 2;
+
+/// The hash and the header of a pending wrapper, with the height of the last
+/// committed block when it was validated
+type PendingWrapper = (Hash, WrapperTx, BlockHeight);
+
+/// The wrappers of the inner txs pending in the mempool, by the hashes of
+/// the inner txs
+#[derive(Debug, Default)]
+pub struct PendingInnerTxs {
+    wrappers: Mutex<HashMap<Hash, PendingWrapper>>,
+}
+
+impl PendingInnerTxs {
+    /// Get the hash and the header of the wrapper under which the given inner
+    /// tx is pending, if any and if its record has not expired at the given
+    /// last committed block height
+    pub fn get(
+        &self,
+        inner_hash: &Hash,
+        height: BlockHeight,
+    ) -> Option<(Hash, WrapperTx)> {
+        self.lock()
+            .get(inner_hash)
+            .filter(|(_, _, recorded_at)| !is_expired(*recorded_at, height))
+            .map(|(wrapper_hash, wrapper, _)| (*wrapper_hash, wrapper.clone()))
+    }
+
+    /// Record the wrapper under which the given inner tx is pending, as
+    /// validated at the given last committed block height, and drop the
+    /// expired records. Returns the hash of the wrapper that it replaces, if
+    /// any.
+    pub fn insert(
+        &self,
+        inner_hash: Hash,
+        wrapper_hash: Hash,
+        wrapper: WrapperTx,
+        height: BlockHeight,
+    ) -> Option<Hash> {
+        let mut wrappers = self.lock();
+        wrappers
+            .retain(|_, (_, _, recorded_at)| !is_expired(*recorded_at, height));
+        wrappers
+            .insert(inner_hash, (wrapper_hash, wrapper, height))
+            .map(|(replaced, _, _)| replaced)
+            .filter(|replaced| *replaced != wrapper_hash)
+    }
+
+    /// Forget the wrapper of the given inner tx, e.g. once the inner tx was
+    /// included in a block
+    pub fn remove(&self, inner_hash: &Hash) {
+        self.lock().remove(inner_hash);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Hash, PendingWrapper>> {
+        self.wrappers
+            .lock()
+            .expect("The pending inner txs should not be poisoned")
+    }
+}
+
+/// Check if the record of a wrapper validated at the height `recorded_at` has
+/// expired at the given last committed block height
+fn is_expired(recorded_at: BlockHeight, height: BlockHeight) -> bool {
+    height.0.saturating_sub(recorded_at.0) > PENDING_WRAPPER_EXPIRY_BLOCKS
+}
@@ -0,0 +1,270 @@
+//! Implementation of the ABCI state sync hooks, with which the node serves
+//! the snapshots of its storage to the nodes joining the network, and
+//! restores its storage from the snapshot that Tendermint offers to it when
+//! it joins the network.
+
+use data_encoding::HEXLOWER;
+
+use super::*;
+use crate::facade::tendermint_proto::abci::response_apply_snapshot_chunk::Result as ApplyChunkResult;
+use crate::facade::tendermint_proto::abci::response_offer_snapshot::Result as OfferSnapshotResult;
+use crate::facade::tendermint_proto::abci::{
+    RequestApplySnapshotChunk, RequestLoadSnapshotChunk, RequestOfferSnapshot,
+    ResponseApplySnapshotChunk, ResponseListSnapshots,
+    ResponseLoadSnapshotChunk, ResponseOfferSnapshot, Snapshot as AbciSnapshot,
+};
+use crate::node::ledger::storage::snapshots::{
+    self, Snapshot, SnapshotMetadata, SnapshotRestore, SnapshotStore,
+    SNAPSHOT_FORMAT,
+};
+
+/// The number of the most recent snapshots that are kept, unless configured
+/// otherwise
+const DEFAULT_SNAPSHOTS_TO_KEEP: u64 = 2;
+
+/// The snapshots that the node creates and serves
+#[derive(Debug)]
+pub struct SnapshotConfig {
+    /// The stored snapshots
+    store: SnapshotStore,
+    /// A snapshot is created at the start of every epoch that is a multiple
+    /// of this interval
+    epoch_interval: u64,
+    /// The number of the most recent snapshots that are kept
+    to_keep: u64,
+}
+
+impl SnapshotConfig {
+    /// Get the snapshots configuration from the configs
+    /// `snapshot_epoch_interval` and `snapshots_to_keep`, if the snapshots are
+    /// enabled
+    pub fn new(config: &config::Shell, chain_id: &ChainId) -> Option<Self> {
+        let epoch_interval = config.snapshot_epoch_interval?.max(1);
+        Some(Self {
+            store: SnapshotStore::new(
+                config.base_dir.join(chain_id.as_str()).join("snapshots"),
+            ),
+            epoch_interval,
+            to_keep: config
+                .snapshots_to_keep
+                .unwrap_or(DEFAULT_SNAPSHOTS_TO_KEEP)
+                .max(1),
+        })
+    }
+}
+
+impl Shell {
+    /// Create a snapshot of the storage if the last committed block is the
+    /// first block of an epoch that is a multiple of the configured interval,
+    /// and remove the old snapshots. This is done on commit, as the snapshot
+    /// must not include the changes of the next block.
+    pub fn create_snapshot_if_due(&mut self) {
+        let config = match &self.snapshots {
+            Some(config) => config,
+            None => return,
+        };
+        let storage = &self.wl_storage.storage;
+        let height = storage.get_last_block_height();
+        let is_epoch_start =
+            storage.block.pred_epochs.first_block_heights.last()
+                == Some(&height);
+        if !is_epoch_start || storage.last_epoch.0 % config.epoch_interval != 0
+        {
+            return;
+        }
+        match config.store.create(&storage.db, height) {
+            Ok(snapshot) => tracing::info!(
+                "Created a state sync snapshot at height {} with {} chunks",
+                height,
+                snapshot.chunks()
+            ),
+            Err(err) => {
+                tracing::error!(
+                    "Failed to create a state sync snapshot at height {}: {}",
+                    height,
+                    err
+                );
+                return;
+            }
+        }
+        if let Err(err) = config.store.prune(config.to_keep as usize) {
+            tracing::error!("Failed to remove the old snapshots: {}", err);
+        }
+    }
+
+    /// List the snapshots served by the node
+    pub fn list_snapshots(&self) -> ResponseListSnapshots {
+        let snapshots = match &self.snapshots {
+            Some(config) => config.store.list().unwrap_or_else(|err| {
+                tracing::error!("Failed to list the snapshots: {}", err);
+                vec![]
+            }),
+            None => vec![],
+        };
+        ResponseListSnapshots {
+            snapshots: snapshots
+                .into_iter()
+                .map(|snapshot| AbciSnapshot {
+                    height: snapshot.height.0,
+                    format: SNAPSHOT_FORMAT,
+                    chunks: snapshot.chunks(),
+                    hash: snapshot.metadata.hash().0.to_vec(),
+                    metadata: snapshot.metadata.try_to_vec().unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Load a chunk of a snapshot served by the node
+    pub fn load_snapshot_chunk(
+        &self,
+        req: RequestLoadSnapshotChunk,
+    ) -> ResponseLoadSnapshotChunk {
+        let chunk = match &self.snapshots {
+            Some(config) if req.format == SNAPSHOT_FORMAT => config
+                .store
+                .load_chunk(BlockHeight(req.height), req.chunk)
+                .unwrap_or_else(|err| {
+                    tracing::error!(
+                        "Failed to load the chunk {} of the snapshot at \
+                         height {}: {}",
+                        req.chunk,
+                        req.height,
+                        err
+                    );
+                    None
+                }),
+            _ => None,
+        };
+        ResponseLoadSnapshotChunk {
+            chunk: chunk.unwrap_or_default(),
+        }
+    }
+
+    /// Accept to restore the storage from the offered snapshot, if the node
+    /// has no state yet and if the snapshot is valid
+    pub fn offer_snapshot(
+        &mut self,
+        req: RequestOfferSnapshot,
+    ) -> ResponseOfferSnapshot {
+        let result = match req.snapshot {
+            _ if self.wl_storage.storage.get_last_block_height().0 != 0 => {
+                tracing::info!(
+                    "Rejecting the offered snapshot, as the node already has \
+                     a state"
+                );
+                OfferSnapshotResult::Abort
+            }
+            None => OfferSnapshotResult::Reject,
+            Some(snapshot) if snapshot.format != SNAPSHOT_FORMAT => {
+                OfferSnapshotResult::RejectFormat
+            }
+            Some(snapshot) => {
+                match SnapshotMetadata::try_from_slice(&snapshot.metadata) {
+                    Ok(metadata)
+                        if metadata.hash().0[..] == snapshot.hash[..]
+                            && metadata.chunk_hashes.len()
+                                == snapshot.chunks as usize =>
+                    {
+                        tracing::info!(
+                            "Restoring the storage from the snapshot at \
+                             height {}",
+                            snapshot.height
+                        );
+                        self.snapshot_restore = Some((
+                            SnapshotRestore::new(Snapshot {
+                                height: BlockHeight(snapshot.height),
+                                metadata,
+                            }),
+                            req.app_hash,
+                        ));
+                        OfferSnapshotResult::Accept
+                    }
+                    _ => OfferSnapshotResult::Reject,
+                }
+            }
+        };
+        ResponseOfferSnapshot {
+            result: result as i32,
+        }
+    }
+
+    /// Apply a chunk of the snapshot being restored. Once all of its chunks
+    /// are applied, the last state is loaded from the restored storage.
+    pub fn apply_snapshot_chunk(
+        &mut self,
+        req: RequestApplySnapshotChunk,
+    ) -> ResponseApplySnapshotChunk {
+        let (restore, app_hash) = match self.snapshot_restore.as_mut() {
+            Some(restore) => restore,
+            None => {
+                return ResponseApplySnapshotChunk {
+                    result: ApplyChunkResult::Abort as i32,
+                    ..Default::default()
+                };
+            }
+        };
+        let result = match restore.apply_chunk(
+            &mut self.wl_storage.storage.db,
+            req.index,
+            &req.chunk,
+        ) {
+            Ok(false) => ApplyChunkResult::Accept,
+            Ok(true) => {
+                let app_hash = std::mem::take(app_hash);
+                self.snapshot_restore = None;
+                self.load_restored_state(app_hash)
+            }
+            Err(snapshots::Error::ChunkHash(index)) => {
+                tracing::info!(
+                    "Refetching the chunk {} of the snapshot from another \
+                     sender",
+                    index
+                );
+                return ResponseApplySnapshotChunk {
+                    result: ApplyChunkResult::Retry as i32,
+                    refetch_chunks: vec![index],
+                    reject_senders: vec![req.sender],
+                };
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Failed to apply a chunk of the snapshot: {}",
+                    err
+                );
+                ApplyChunkResult::Abort
+            }
+        };
+        ResponseApplySnapshotChunk {
+            result: result as i32,
+            ..Default::default()
+        }
+    }
+
+    /// Load the last state from the storage restored from a snapshot and
+    /// check its merkle root against the app hash trusted by Tendermint
+    fn load_restored_state(&mut self, app_hash: Vec<u8>) -> ApplyChunkResult {
+        if let Err(err) = self.wl_storage.storage.load_last_state() {
+            tracing::error!(
+                "Failed to load the state restored from the snapshot: {}",
+                err
+            );
+            return ApplyChunkResult::Abort;
+        }
+        let root = self.wl_storage.storage.merkle_root();
+        if root.0 != app_hash {
+            tracing::error!(
+                "The merkle root {} of the state restored from the snapshot \
+                 doesn't match the app hash {}",
+                root,
+                HEXLOWER.encode(&app_hash)
+            );
+            return ApplyChunkResult::Abort;
+        }
+        tracing::info!(
+            "Restored the storage from the snapshot at height {}",
+            self.wl_storage.storage.get_last_block_height()
+        );
+        ApplyChunkResult::Accept
+    }
+}
@@ -0,0 +1,199 @@
+//! The signatures by the validators of the validator set of the next epoch,
+//! which are stored for the contract of the bridge, as described in
+//! [`namada::ledger::eth_bridge::storage`].
+//!
+//! At the start of every epoch, each validator signs the hash of the
+//! validator set of the next epoch with its protocol key, and broadcasts it
+//! in a protocol tx. The proposers include these txs in their blocks, and the
+//! signatures are recorded with the stake of their validators.
+
+use namada::ledger::eth_bridge::storage as bridge_storage;
+use namada::ledger::storage::{DBIter, StorageHasher, DB};
+use namada::ledger::storage_api::StorageRead;
+use namada::proof_of_stake::pos_queries::PosQueries;
+use namada::types::hash::Hash;
+use namada::types::storage::Epoch;
+use namada::types::token;
+use namada::types::transaction::protocol::{
+    valset_hash, ProtocolTxType, ValidatorSetUpdate,
+};
+
+use super::{Shell, ShellMode};
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// The hash of the consensus validator set of the given epoch
+    pub(super) fn consensus_valset_hash(&self, epoch: Epoch) -> Hash {
+        let pos_queries = self.wl_storage.pos_queries();
+        valset_hash(
+            pos_queries
+                .get_consensus_validators(Some(epoch))
+                .iter()
+                .map(|validator| {
+                    let (_, protocol_pk) = pos_queries
+                        .get_validator_from_address(
+                            &validator.address,
+                            Some(epoch),
+                        )
+                        .expect("A consensus validator should be found");
+                    (protocol_pk, validator.bonded_stake)
+                }),
+        )
+    }
+
+    /// If this node is a validator, sign the validator set of the epoch after
+    /// the current one and broadcast the signature in a protocol tx
+    pub(super) fn broadcast_valset_update(&self) {
+        let (validator, broadcast_sender, signer) = match &self.mode {
+            ShellMode::Validator {
+                data,
+                broadcast_sender,
+                protocol_signer,
+            } => (&data.address, broadcast_sender, protocol_signer.as_ref()),
+            _ => return,
+        };
+        let epoch = self.wl_storage.storage.get_current_epoch().0.next();
+        let tx = ValidatorSetUpdate::new(
+            validator.clone(),
+            epoch,
+            self.consensus_valset_hash(epoch),
+            signer,
+        )
+        .and_then(|update| {
+            ProtocolTxType::ValidatorSetUpdate(update)
+                .sign(signer, self.chain_id.clone())
+        });
+        match tx {
+            Ok(tx) => {
+                if let Err(err) = broadcast_sender.send(tx.to_bytes()) {
+                    tracing::error!(
+                        "Failed to broadcast the validator set update of \
+                         epoch {}: {}",
+                        epoch,
+                        err
+                    );
+                }
+            }
+            Err(err) => tracing::error!(
+                "Failed to sign the validator set update of epoch {}: {}",
+                epoch,
+                err
+            ),
+        }
+    }
+
+    /// Check that the validator set of the epoch after the one of the last
+    /// committed block was signed by a consensus validator that didn't sign
+    /// it yet. Returns the stake of the validator.
+    pub(super) fn validate_valset_update(
+        &self,
+        update: &ValidatorSetUpdate,
+    ) -> Result<token::Amount, String> {
+        let epoch = self.last_block_epoch()?;
+        if update.epoch != epoch.next() {
+            return Err(format!(
+                "The validator set update is signed for the epoch {}, \
+                 expected {}",
+                update.epoch,
+                epoch.next()
+            ));
+        }
+        let (stake, protocol_pk) = self
+            .wl_storage
+            .pos_queries()
+            .get_validator_from_address(&update.validator, Some(epoch))
+            .map_err(|err| err.to_string())?;
+        let sig_key =
+            bridge_storage::valset_sig_key(update.epoch, &update.validator);
+        if self
+            .wl_storage
+            .has_key(&sig_key)
+            .map_err(|err| err.to_string())?
+        {
+            return Err(format!(
+                "Validator {} already signed the validator set of epoch {}",
+                update.validator, update.epoch
+            ));
+        }
+        if update.valset_hash != self.consensus_valset_hash(update.epoch) {
+            return Err(format!(
+                "Validator {} signed the wrong hash of the validator set of \
+                 epoch {}",
+                update.validator, update.epoch
+            ));
+        }
+        update.verify(&protocol_pk).map_err(|err| {
+            format!(
+                "Invalid signature of the validator set update of validator \
+                 {}: {}",
+                update.validator, err
+            )
+        })?;
+        Ok(stake)
+    }
+}
+
+#[cfg(test)]
+mod test_valset_update {
+    use super::*;
+    use crate::node::ledger::shell::test_utils;
+    use crate::wallet::defaults;
+
+    /// Sign the validator set of the given epoch with the protocol key of the
+    /// genesis validator
+    fn signed_update(valset_hash: Hash, epoch: Epoch) -> ValidatorSetUpdate {
+        ValidatorSetUpdate::new(
+            defaults::validator_address(),
+            epoch,
+            valset_hash,
+            &defaults::validator_keys().0,
+        )
+        .unwrap()
+    }
+
+    /// Test that the validator set updates are validated, and that they are
+    /// only accepted once per validator
+    #[test]
+    fn test_validate_valset_update() {
+        let (mut shell, _) = test_utils::setup(1);
+        let epoch = shell.last_block_epoch().unwrap().next();
+        let valset_hash = shell.consensus_valset_hash(epoch);
+
+        let update = signed_update(valset_hash, epoch);
+        let stake = shell.validate_valset_update(&update).unwrap();
+        assert_eq!(
+            stake,
+            shell.wl_storage.pos_queries().get_total_voting_power(None)
+        );
+
+        // the validator set of another epoch is rejected
+        let stale = signed_update(valset_hash, epoch.next());
+        assert!(shell.validate_valset_update(&stale).is_err());
+
+        // the wrong hash of the validator set is rejected
+        let wrong = signed_update(Hash::default(), epoch);
+        assert!(shell.validate_valset_update(&wrong).is_err());
+
+        // the signature must be made with the protocol key of the validator
+        let mut forged = update.clone();
+        forged.sig = signed_update(valset_hash, epoch.next()).sig;
+        assert!(shell.validate_valset_update(&forged).is_err());
+
+        // the validator set is only signed once by a validator
+        bridge_storage::write_valset_update(
+            &mut shell.wl_storage,
+            &update,
+            stake,
+        )
+        .unwrap();
+        assert!(shell.validate_valset_update(&update).is_err());
+        assert_eq!(
+            bridge_storage::read_valset_signed_stake(&shell.wl_storage, epoch)
+                .unwrap(),
+            stake
+        );
+    }
+}
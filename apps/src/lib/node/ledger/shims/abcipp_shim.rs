@@ -5,6 +5,7 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures::future::FutureExt;
+use namada::ledger::storage::DB;
 use namada::proto::Tx;
 use namada::types::address::Address;
 #[cfg(not(feature = "abcipp"))]
@@ -18,6 +19,7 @@ use tokio::sync::broadcast;
 use tokio::sync::mpsc::UnboundedSender;
 use tower::Service;
 
+use super::super::storage::PersistentDB;
 use super::super::Shell;
 use super::abcipp_shim_types::shim::request::{FinalizeBlock, ProcessedTx};
 #[cfg(not(feature = "abcipp"))]
@@ -56,7 +58,7 @@ impl AbcippShim {
         config: config::Ledger,
         wasm_dir: PathBuf,
         broadcast_sender: UnboundedSender<Vec<u8>>,
-        db_cache: &rocksdb::Cache,
+        db_cache: &<PersistentDB as DB>::Cache,
         vp_wasm_compilation_cache: u64,
         tx_wasm_compilation_cache: u64,
         native_token: Address,
@@ -1,20 +1,39 @@
 //! The storage module handles both the current state in-memory and the stored
 //! state in DB.
+//!
+//! The DB is RocksDB by default, or sled if the node is built without the
+//! `rocksdb` feature and with the `sled` feature.
 
+#[cfg(feature = "rocksdb")]
 mod rocksdb;
+#[cfg(feature = "sled")]
+mod sled;
+pub mod snapshots;
 
 use std::fmt;
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use arse_merkle_tree::blake2b::Blake2bHasher;
 use arse_merkle_tree::traits::Hasher;
 use arse_merkle_tree::H256;
 use blake2b_rs::{Blake2b, Blake2bBuilder};
-use namada::ledger::storage::{Storage, StorageHasher};
+use namada::ledger::storage::{self, DBIter, Storage, StorageHasher, DB};
+use namada::types::storage::BlockHeight;
+
+use self::snapshots::SnapshotEntry;
+
+#[cfg(not(any(feature = "rocksdb", feature = "sled")))]
+compile_error!("Either the `rocksdb` or the `sled` feature must be enabled");
 
 #[derive(Default)]
 pub struct PersistentStorageHasher(Blake2bHasher);
 
+#[cfg(feature = "rocksdb")]
 pub type PersistentDB = rocksdb::RocksDB;
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+pub type PersistentDB = sled::SledDB;
 
 pub type PersistentStorage = Storage<PersistentDB, PersistentStorageHasher>;
 
@@ -44,6 +63,116 @@ impl fmt::Debug for PersistentStorageHasher {
     }
 }
 
+/// A DB backend of the node, which provides the operations of the node on its
+/// DB on top of the ones of the ledger's storage
+pub trait DBBackend: DB + for<'iter> DBIter<'iter> {
+    /// Create the DB's cache with the given capacity in bytes
+    fn new_cache(capacity_bytes: u64) -> Self::Cache;
+
+    /// Iterate over the entries of the DB that are exported in state sync
+    /// snapshots
+    fn iter_snapshot_entries(
+        &self,
+    ) -> Box<dyn Iterator<Item = storage::Result<SnapshotEntry>> + '_>;
+
+    /// Write the entries of a chunk of a state sync snapshot
+    fn write_snapshot_entries(
+        &mut self,
+        entries: Vec<SnapshotEntry>,
+    ) -> storage::Result<()>;
+
+    /// Dump last known block
+    fn dump_last_block(&self, out_file_path: PathBuf, historic: bool);
+
+    /// The latencies of the commits of the batches written to the DB
+    fn commit_metrics(&self) -> CommitMetrics;
+
+    /// Rollback to previous block. Given the inner working of tendermint
+    /// rollback and of the key structure of Namada, calling rollback more than
+    /// once without restarting the chain results in a single rollback.
+    fn rollback(
+        &mut self,
+        tendermint_block_height: BlockHeight,
+    ) -> storage::Result<()>;
+}
+
+/// The latencies of the commits of the batches written to the DB. A batch is
+/// committed by writing it to the DB, which then syncs it to the disk in the
+/// background.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommitMetrics {
+    /// The number of the written batches
+    pub commits: u64,
+    /// The time taken to write the last batch
+    pub last_write: Duration,
+    /// The time taken to sync the last synced batch to the disk
+    pub last_sync: Duration,
+    /// The time that the last write waited for the sync of the previous batch
+    pub last_sync_wait: Duration,
+    /// The total time taken to write the batches
+    pub total_write: Duration,
+    /// The total time that the writes waited for the syncs of the previous
+    /// batches
+    pub total_sync_wait: Duration,
+}
+
+/// The sync to the disk of the last batch written to the DB, which is done in
+/// the background while the next block is processed. The next write waits for
+/// it, such that at most the last batch isn't synced. If the node stops before
+/// it's synced, Tendermint replays the last block on restart.
+#[derive(Debug, Default)]
+pub struct BackgroundSync {
+    pending: Option<JoinHandle<storage::Result<Duration>>>,
+    metrics: CommitMetrics,
+}
+
+impl BackgroundSync {
+    /// Wait for the sync of the last written batch, if it's not done yet
+    pub fn wait(&mut self) -> storage::Result<()> {
+        if let Some(pending) = self.pending.take() {
+            let start = Instant::now();
+            let sync = pending.join().map_err(|_| {
+                storage::Error::DBError(
+                    "The background sync of the DB panicked".to_owned(),
+                )
+            })??;
+            let wait = start.elapsed();
+            self.metrics.last_sync = sync;
+            self.metrics.last_sync_wait = wait;
+            self.metrics.total_sync_wait += wait;
+        }
+        Ok(())
+    }
+
+    /// Record the time taken to write a batch and start syncing it in the
+    /// background with the given function
+    pub fn start(
+        &mut self,
+        write: Duration,
+        sync: impl FnOnce() -> storage::Result<()> + Send + 'static,
+    ) {
+        self.metrics.commits += 1;
+        self.metrics.last_write = write;
+        self.metrics.total_write += write;
+        tracing::debug!(
+            "Wrote a batch to the DB in {:?}, after waiting {:?} for the sync \
+             of the previous batch",
+            write,
+            self.metrics.last_sync_wait
+        );
+        self.pending = Some(thread::spawn(move || {
+            let start = Instant::now();
+            sync()?;
+            Ok(start.elapsed())
+        }));
+    }
+
+    /// The latencies of the commits of the written batches
+    pub fn metrics(&self) -> CommitMetrics {
+        self.metrics
+    }
+}
+
 fn new_blake2b() -> Blake2b {
     Blake2bBuilder::new(32).personal(b"namada storage").build()
 }
@@ -55,7 +184,7 @@ mod tests {
     use itertools::Itertools;
     use namada::ledger::storage::write_log::WriteLog;
     use namada::ledger::storage::{
-        types, update_allowed_conversions, WlStorage,
+        types, update_allowed_conversions, DBIter, WlStorage,
     };
     use namada::ledger::storage_api::{self, StorageWrite};
     use namada::types::chain::ChainId;
@@ -117,6 +246,54 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    /// Test that the writes of a block are only written to the DB on commit,
+    /// and that they're read from the storage before that
+    #[test]
+    fn test_block_writes_batched() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(100))
+            .expect("begin_block failed");
+        let key = Key::parse("key").expect("cannot parse the key string");
+        let deleted_key =
+            Key::parse("deleted").expect("cannot parse the key string");
+        let value_bytes = types::encode(&1_u64);
+        storage
+            .write(&key, value_bytes.clone())
+            .expect("write failed");
+        storage
+            .write(&deleted_key, value_bytes.clone())
+            .expect("write failed");
+        let (_, size_diff) =
+            storage.write(&key, vec![0; 2]).expect("write failed");
+        assert_eq!(size_diff, 2 - value_bytes.len() as i64);
+        let (_, deleted_len) =
+            storage.delete(&deleted_key).expect("delete failed");
+        assert_eq!(deleted_len, value_bytes.len() as i64);
+
+        assert_eq!(storage.db.read_subspace_val(&key).unwrap(), None);
+        let (result, _) = storage.read(&key).expect("read failed");
+        assert_eq!(result, Some(vec![0; 2]));
+
+        storage
+            .commit_block(PersistentDB::batch())
+            .expect("commit failed");
+        assert_eq!(
+            storage.db.read_subspace_val(&key).unwrap(),
+            Some(vec![0; 2])
+        );
+        assert_eq!(storage.db.read_subspace_val(&deleted_key).unwrap(), None);
+        assert_eq!(storage.db.commit_metrics().commits, 1);
+    }
+
     #[test]
     fn test_commit_block() {
         let db_path =
@@ -552,6 +729,59 @@ mod tests {
         assert!(result.is_ok(), "The tree should be restored");
     }
 
+    /// Test the pruning of the history of the storage
+    #[test]
+    fn test_prune_history() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        let key = Key::parse("key").expect("cannot parse the key string");
+        for (value, height) in [1_u64, 6, 11].into_iter().enumerate() {
+            storage
+                .begin_block(BlockHash::default(), BlockHeight(height))
+                .expect("begin_block failed");
+            storage
+                .write(&key, types::encode(&value))
+                .expect("write failed");
+            storage.block.epoch = storage.block.epoch.next();
+            storage
+                .block
+                .pred_epochs
+                .new_epoch(BlockHeight(height), 1000);
+            let batch = PersistentStorage::batch();
+            storage.commit_block(batch).expect("commit failed");
+        }
+
+        // The history is retained from the start of the epoch of the height
+        storage
+            .prune_history(BlockHeight(8))
+            .expect("pruning failed");
+        assert_eq!(
+            storage.db.iter_new_diffs(BlockHeight(1)).count(),
+            0,
+            "The diffs at height 1 should be pruned"
+        );
+        assert!(
+            storage.get_merkle_tree(1.into()).is_err(),
+            "The tree at height 1 should be pruned"
+        );
+        assert_eq!(storage.db.iter_old_diffs(BlockHeight(6)).count(), 1);
+        let (val, _) = storage
+            .read_with_height(&key, BlockHeight(6))
+            .expect("read failed");
+        assert_eq!(val.expect("no value"), types::encode(&1_usize));
+        assert!(
+            storage.get_merkle_tree(6.into()).is_ok(),
+            "The tree at height 6 should be restored"
+        );
+    }
+
     /// Test the prefix iterator with RocksDB.
     #[test]
     fn test_persistent_storage_prefix_iter() {
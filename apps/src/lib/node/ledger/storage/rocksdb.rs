@@ -31,7 +31,8 @@
 use std::fs::File;
 use std::path::Path;
 use std::str::FromStr;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use data_encoding::HEXLOWER;
@@ -52,6 +53,8 @@ use rocksdb::{
     FlushOptions, IteratorMode, Options, ReadOptions, WriteBatch,
 };
 
+use super::snapshots::SnapshotEntry;
+use super::{BackgroundSync, CommitMetrics, DBBackend};
 use crate::config::utils::num_of_threads;
 
 // TODO the DB schema will probably need some kind of versioning
@@ -66,9 +69,16 @@ const DIFFS_CF: &str = "diffs";
 const STATE_CF: &str = "state";
 const BLOCK_CF: &str = "block";
 
+/// The column families that are exported in state sync snapshots. The diffs
+/// are left out, as they're only needed to read the past heights.
+const SNAPSHOT_CFS: [&str; 3] = [STATE_CF, BLOCK_CF, SUBSPACE_CF];
+
 /// RocksDB handle
 #[derive(Debug)]
-pub struct RocksDB(rocksdb::DB);
+pub struct RocksDB {
+    db: Arc<rocksdb::DB>,
+    sync: BackgroundSync,
+}
 
 /// DB Handle for batch writes.
 #[derive(Default)]
@@ -106,6 +116,9 @@ pub fn open(
     db_opts.create_missing_column_families(true);
     db_opts.create_if_missing(true);
     db_opts.set_atomic_flush(true);
+    // The WAL is synced in the background after the batch of a block is
+    // written, rather than on every write
+    db_opts.set_manual_wal_flush(true);
 
     let mut cfs = Vec::new();
     let mut table_opts = BlockBasedOptions::default();
@@ -153,19 +166,23 @@ pub fn open(
     cfs.push(ColumnFamilyDescriptor::new(BLOCK_CF, block_cf_opts));
 
     rocksdb::DB::open_cf_descriptors(&db_opts, path, cfs)
-        .map(RocksDB)
+        .map(|db| RocksDB {
+            db: Arc::new(db),
+            sync: BackgroundSync::default(),
+        })
         .map_err(|e| Error::DBError(e.into_string()))
 }
 
 impl Drop for RocksDB {
     fn drop(&mut self) {
+        self.sync.wait().expect("sync failed");
         self.flush(true).expect("flush failed");
     }
 }
 
 impl RocksDB {
     fn get_column_family(&self, cf_name: &str) -> Result<&ColumnFamily> {
-        self.0
+        self.db
             .cf_handle(cf_name)
             .ok_or(Error::DBError("No {cf_name} column family".to_string()))
     }
@@ -173,7 +190,7 @@ impl RocksDB {
     fn flush(&self, wait: bool) -> Result<()> {
         let mut flush_opts = FlushOptions::default();
         flush_opts.set_wait(wait);
-        self.0
+        self.db
             .flush_opt(&flush_opts)
             .map_err(|e| Error::DBError(e.into_string()))
     }
@@ -196,7 +213,7 @@ impl RocksDB {
                 .map_err(Error::KeyError)?
                 .join(key)
                 .to_string();
-            self.0
+            self.db
                 .put_cf(cf, old_val_key, old_value)
                 .map_err(|e| Error::DBError(e.into_string()))?;
         }
@@ -207,7 +224,7 @@ impl RocksDB {
                 .map_err(Error::KeyError)?
                 .join(key)
                 .to_string();
-            self.0
+            self.db
                 .put_cf(cf, new_val_key, new_value)
                 .map_err(|e| Error::DBError(e.into_string()))?;
         }
@@ -247,14 +264,102 @@ impl RocksDB {
         Ok(())
     }
 
+    /// Write the batch to the DB and sync its WAL to the disk in the
+    /// background, after the sync of the previous batch is done
     fn exec_batch(&mut self, batch: WriteBatch) -> Result<()> {
-        self.0
+        self.sync.wait()?;
+        let start = Instant::now();
+        self.db
             .write(batch)
-            .map_err(|e| Error::DBError(e.into_string()))
+            .map_err(|e| Error::DBError(e.into_string()))?;
+        let db = self.db.clone();
+        self.sync.start(start.elapsed(), move || {
+            db.flush_wal(true)
+                .map_err(|e| Error::DBError(e.into_string()))
+        });
+        Ok(())
+    }
+
+    /// Dump data
+    fn dump_it(
+        &self,
+        cf: &ColumnFamily,
+        prefix: Option<String>,
+        file: &mut File,
+    ) {
+        use std::io::Write;
+
+        let read_opts = make_iter_read_opts(prefix.clone());
+        let iter = if let Some(prefix) = prefix {
+            self.db.iterator_cf_opt(
+                cf,
+                read_opts,
+                IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+            )
+        } else {
+            self.db.iterator_cf_opt(cf, read_opts, IteratorMode::Start)
+        };
+
+        for (key, raw_val, _gas) in PersistentPrefixIterator(
+            PrefixIterator::new(iter, String::default()),
+            // Empty string to prevent prefix stripping, the prefix is
+            // already in the enclosed iterator
+        ) {
+            let val = HEXLOWER.encode(&raw_val);
+            let bytes = format!("\"{key}\" = \"{val}\"\n");
+            file.write_all(bytes.as_bytes())
+                .expect("Unable to write to output file");
+        }
+    }
+}
+
+impl DBBackend for RocksDB {
+    fn new_cache(capacity_bytes: u64) -> Self::Cache {
+        rocksdb::Cache::new_lru_cache(capacity_bytes as usize)
+    }
+
+    fn iter_snapshot_entries(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<SnapshotEntry>> + '_> {
+        Box::new(SNAPSHOT_CFS.into_iter().flat_map(move |cf_name| {
+            let cf = self
+                .get_column_family(cf_name)
+                .expect("{cf_name} column family should exist");
+            self.db
+                .iterator_cf_opt(
+                    cf,
+                    make_iter_read_opts(None),
+                    IteratorMode::Start,
+                )
+                .map(move |result| {
+                    let (key, value) =
+                        result.map_err(|e| Error::DBError(e.into_string()))?;
+                    Ok(SnapshotEntry {
+                        cf: cf_name.to_owned(),
+                        key: key.into_vec(),
+                        value: value.into_vec(),
+                    })
+                })
+        }))
+    }
+
+    fn write_snapshot_entries(
+        &mut self,
+        entries: Vec<SnapshotEntry>,
+    ) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for SnapshotEntry { cf, key, value } in entries {
+            if !SNAPSHOT_CFS.contains(&cf.as_str()) {
+                return Err(Error::DBError(format!(
+                    "Unexpected {cf} column family in a snapshot"
+                )));
+            }
+            batch.put_cf(self.get_column_family(&cf)?, key, value);
+        }
+        self.exec_batch(batch)
     }
 
-    /// Dump last known block
-    pub fn dump_last_block(
+    fn dump_last_block(
         &self,
         out_file_path: std::path::PathBuf,
         historic: bool,
@@ -264,7 +369,7 @@ impl RocksDB {
             .get_column_family(STATE_CF)
             .expect("State column family should exist");
         let height: BlockHeight = types::decode(
-            self.0
+            self.db
                 .get_cf(state_cf, "height")
                 .expect("Unable to read DB")
                 .expect("No block height found"),
@@ -316,45 +421,11 @@ impl RocksDB {
         println!("Done writing to {}", full_path.to_string_lossy());
     }
 
-    /// Dump data
-    fn dump_it(
-        &self,
-        cf: &ColumnFamily,
-        prefix: Option<String>,
-        file: &mut File,
-    ) {
-        use std::io::Write;
-
-        let read_opts = make_iter_read_opts(prefix.clone());
-        let iter = if let Some(prefix) = prefix {
-            self.0.iterator_cf_opt(
-                cf,
-                read_opts,
-                IteratorMode::From(prefix.as_bytes(), Direction::Forward),
-            )
-        } else {
-            self.0.iterator_cf_opt(cf, read_opts, IteratorMode::Start)
-        };
-
-        for (key, raw_val, _gas) in PersistentPrefixIterator(
-            PrefixIterator::new(iter, String::default()),
-            // Empty string to prevent prefix stripping, the prefix is
-            // already in the enclosed iterator
-        ) {
-            let val = HEXLOWER.encode(&raw_val);
-            let bytes = format!("\"{key}\" = \"{val}\"\n");
-            file.write_all(bytes.as_bytes())
-                .expect("Unable to write to output file");
-        }
+    fn commit_metrics(&self) -> CommitMetrics {
+        self.sync.metrics()
     }
 
-    /// Rollback to previous block. Given the inner working of tendermint
-    /// rollback and of the key structure of Namada, calling rollback more than
-    /// once without restarting the chain results in a single rollback.
-    pub fn rollback(
-        &mut self,
-        tendermint_block_height: BlockHeight,
-    ) -> Result<()> {
+    fn rollback(&mut self, tendermint_block_height: BlockHeight) -> Result<()> {
         let last_block = self.read_last_block()?.ok_or(Error::DBError(
             "Missing last block in storage".to_string(),
         ))?;
@@ -392,7 +463,7 @@ impl RocksDB {
         ] {
             let previous_key = format!("pred/{}", metadata_key);
             let previous_value = self
-                .0
+                .db
                 .get_cf(state_cf, previous_key.as_bytes())
                 .map_err(|e| Error::DBError(e.to_string()))?
                 .ok_or(Error::UnknownKey { key: previous_key })?;
@@ -440,7 +511,7 @@ impl RocksDB {
         let prefix = last_block.height.to_string();
         let mut delete_keys = |cf: &ColumnFamily| {
             let read_opts = make_iter_read_opts(Some(prefix.clone()));
-            let iter = self.0.iterator_cf_opt(
+            let iter = self.db.iterator_cf_opt(
                 cf,
                 read_opts,
                 IteratorMode::From(prefix.as_bytes(), Direction::Forward),
@@ -478,7 +549,7 @@ impl DB for RocksDB {
     fn flush(&self, wait: bool) -> Result<()> {
         let mut flush_opts = FlushOptions::default();
         flush_opts.set_wait(wait);
-        self.0
+        self.db
             .flush_opt(&flush_opts)
             .map_err(|e| Error::DBError(e.into_string()))
     }
@@ -487,7 +558,7 @@ impl DB for RocksDB {
         // Block height
         let state_cf = self.get_column_family(STATE_CF)?;
         let height: BlockHeight = match self
-            .0
+            .db
             .get_cf(state_cf, "height")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -503,7 +574,7 @@ impl DB for RocksDB {
         let block_cf = self.get_column_family(BLOCK_CF)?;
         let results_path = format!("results/{}", height.raw());
         let results: BlockResults = match self
-            .0
+            .db
             .get_cf(block_cf, results_path)
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -513,7 +584,7 @@ impl DB for RocksDB {
 
         // Epoch start height and time
         let next_epoch_min_start_height: BlockHeight = match self
-            .0
+            .db
             .get_cf(state_cf, "next_epoch_min_start_height")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -526,7 +597,7 @@ impl DB for RocksDB {
             }
         };
         let next_epoch_min_start_time: DateTimeUtc = match self
-            .0
+            .db
             .get_cf(state_cf, "next_epoch_min_start_time")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -539,7 +610,7 @@ impl DB for RocksDB {
             }
         };
         let update_epoch_blocks_delay: Option<u32> = match self
-            .0
+            .db
             .get_cf(state_cf, "update_epoch_blocks_delay")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -552,7 +623,7 @@ impl DB for RocksDB {
             }
         };
         let tx_queue: TxQueue = match self
-            .0
+            .db
             .get_cf(state_cf, "tx_queue")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -575,7 +646,7 @@ impl DB for RocksDB {
         let mut epoch = None;
         let mut pred_epochs = None;
         let mut address_gen = None;
-        for value in self.0.iterator_cf_opt(
+        for value in self.db.iterator_cf_opt(
             block_cf,
             read_opts,
             IteratorMode::From(prefix.as_bytes(), Direction::Forward),
@@ -698,7 +769,7 @@ impl DB for RocksDB {
         // Epoch start height and time
         let state_cf = self.get_column_family(STATE_CF)?;
         if let Some(current_value) = self
-            .0
+            .db
             .get_cf(state_cf, "next_epoch_min_start_height")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -716,7 +787,7 @@ impl DB for RocksDB {
         );
 
         if let Some(current_value) = self
-            .0
+            .db
             .get_cf(state_cf, "next_epoch_min_start_time")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -733,7 +804,7 @@ impl DB for RocksDB {
             types::encode(&next_epoch_min_start_time),
         );
         if let Some(current_value) = self
-            .0
+            .db
             .get_cf(state_cf, "update_epoch_blocks_delay")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -752,7 +823,7 @@ impl DB for RocksDB {
 
         // Tx queue
         if let Some(pred_tx_queue) = self
-            .0
+            .db
             .get_cf(state_cf, "tx_queue")
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -877,7 +948,7 @@ impl DB for RocksDB {
             .push(&"header".to_owned())
             .map_err(Error::KeyError)?;
         let value = self
-            .0
+            .db
             .get_cf(block_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?;
         match value {
@@ -900,7 +971,7 @@ impl DB for RocksDB {
             .push(&"pred_epochs".to_owned())
             .expect("Cannot obtain a storage key");
         let pred_epochs: Epochs = match self
-            .0
+            .db
             .get_cf(block_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -924,7 +995,7 @@ impl DB for RocksDB {
                 .push(&"root".to_owned())
                 .map_err(Error::KeyError)?;
             let bytes = self
-                .0
+                .db
                 .get_cf(block_cf, root_key.to_string())
                 .map_err(|e| Error::DBError(e.into_string()))?;
             match bytes {
@@ -939,7 +1010,7 @@ impl DB for RocksDB {
                 .push(&"store".to_owned())
                 .map_err(Error::KeyError)?;
             let bytes = self
-                .0
+                .db
                 .get_cf(block_cf, store_key.to_string())
                 .map_err(|e| Error::DBError(e.into_string()))?;
             match bytes {
@@ -954,7 +1025,7 @@ impl DB for RocksDB {
 
     fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>> {
         let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
-        self.0
+        self.db
             .get_cf(subspace_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))
     }
@@ -976,7 +1047,7 @@ impl DB for RocksDB {
 
         // If it has a "new" val, it was written at this height
         match self
-            .0
+            .db
             .get_cf(diffs_cf, new_val_key)
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -990,10 +1061,10 @@ impl DB for RocksDB {
                     .join(key)
                     .to_string();
                 // If it has an "old" val, it was deleted at this height
-                if self.0.key_may_exist_cf(diffs_cf, old_val_key.clone()) {
+                if self.db.key_may_exist_cf(diffs_cf, old_val_key.clone()) {
                     // check if it actually exists
                     if self
-                        .0
+                        .db
                         .get_cf(diffs_cf, old_val_key)
                         .map_err(|e| Error::DBError(e.into_string()))?
                         .is_some()
@@ -1016,7 +1087,7 @@ impl DB for RocksDB {
                 .join(key)
                 .to_string();
             let old_val = self
-                .0
+                .db
                 .get_cf(diffs_cf, old_val_key)
                 .map_err(|e| Error::DBError(e.into_string()))?;
             // If it has an "old" val, it's the one we're looking for
@@ -1030,10 +1101,10 @@ impl DB for RocksDB {
                         .map_err(Error::KeyError)?
                         .join(key)
                         .to_string();
-                    if self.0.key_may_exist_cf(diffs_cf, new_val_key.clone()) {
+                    if self.db.key_may_exist_cf(diffs_cf, new_val_key.clone()) {
                         // check if it actually exists
                         if self
-                            .0
+                            .db
                             .get_cf(diffs_cf, new_val_key)
                             .map_err(|e| Error::DBError(e.into_string()))?
                             .is_some()
@@ -1062,7 +1133,7 @@ impl DB for RocksDB {
         let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
         let value = value.as_ref();
         let size_diff = match self
-            .0
+            .db
             .get_cf(subspace_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -1083,7 +1154,7 @@ impl DB for RocksDB {
         };
 
         // Write the new key-val
-        self.0
+        self.db
             .put_cf(subspace_cf, key.to_string(), value)
             .map_err(|e| Error::DBError(e.into_string()))?;
 
@@ -1099,7 +1170,7 @@ impl DB for RocksDB {
 
         // Check the length of previous value, if any
         let prev_len = match self
-            .0
+            .db
             .get_cf(subspace_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -1112,7 +1183,7 @@ impl DB for RocksDB {
         };
 
         // Delete the key-val
-        self.0
+        self.db
             .delete_cf(subspace_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?;
 
@@ -1137,7 +1208,7 @@ impl DB for RocksDB {
         let value = value.as_ref();
         let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
         let size_diff = match self
-            .0
+            .db
             .get_cf(subspace_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -1181,7 +1252,7 @@ impl DB for RocksDB {
 
         // Check the length of previous value, if any
         let prev_len = match self
-            .0
+            .db
             .get_cf(subspace_cf, key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?
         {
@@ -1238,6 +1309,21 @@ impl DB for RocksDB {
             None => Ok(()),
         }
     }
+
+    fn prune_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        before: BlockHeight,
+    ) -> Result<()> {
+        let diffs_cf = self.get_column_family(DIFFS_CF)?;
+        // The diffs are keyed by their height first, whose encoding maintains
+        // the order of the heights. The range deletion is reclaimed by the
+        // background compaction.
+        batch
+            .0
+            .delete_range_cf(diffs_cf, BlockHeight(0).raw(), before.raw());
+        Ok(())
+    }
 }
 
 impl<'iter> DBIter<'iter> for RocksDB {
@@ -1258,7 +1344,7 @@ impl<'iter> DBIter<'iter> for RocksDB {
             .get_column_family(BLOCK_CF)
             .expect("{BLOCK_CF} column family should exist");
         let read_opts = make_iter_read_opts(Some(prefix.clone()));
-        let iter = self.0.iterator_cf_opt(
+        let iter = self.db.iterator_cf_opt(
             block_cf,
             read_opts,
             IteratorMode::From(prefix.as_bytes(), Direction::Forward),
@@ -1317,7 +1403,7 @@ fn iter_prefix<'a>(
     prefix: String,
 ) -> PersistentPrefixIterator<'a> {
     let read_opts = make_iter_read_opts(Some(prefix.clone()));
-    let iter = db.0.iterator_cf_opt(
+    let iter = db.db.iterator_cf_opt(
         cf,
         read_opts,
         IteratorMode::From(prefix.as_bytes(), Direction::Forward),
@@ -1600,4 +1686,36 @@ mod test {
             db.read_subspace_val(&key).expect("read should succeed");
         assert_eq!(latest_value, None);
     }
+
+    /// Test that the latencies of the commits of the batches are recorded
+    #[test]
+    fn test_commit_metrics() {
+        let dir = tempdir().unwrap();
+        let mut db = open(dir.path(), None).unwrap();
+        assert_eq!(db.commit_metrics(), CommitMetrics::default());
+
+        for height in 1..=2 {
+            let mut batch = RocksDB::batch();
+            db.batch_write_subspace_val(
+                &mut batch,
+                BlockHeight(height),
+                &Key::parse("test").unwrap(),
+                vec![1_u8, 1, 1, 1],
+            )
+            .unwrap();
+            db.exec_batch(batch.0).unwrap();
+        }
+        let metrics = db.commit_metrics();
+        assert_eq!(metrics.commits, 2);
+        assert!(metrics.total_write >= metrics.last_write);
+        assert!(metrics.total_sync_wait >= metrics.last_sync_wait);
+
+        // The last batch is synced before the DB is closed
+        drop(db);
+        let db = open(dir.path(), None).unwrap();
+        assert_eq!(
+            db.read_subspace_val(&Key::parse("test").unwrap()).unwrap(),
+            Some(vec![1_u8, 1, 1, 1])
+        );
+    }
 }
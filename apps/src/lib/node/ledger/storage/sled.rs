@@ -0,0 +1,1230 @@
+//! The persistent storage in sled, a pure-Rust embedded DB, which can be used
+//! instead of RocksDB on the platforms where it's difficult to build.
+//!
+//! The storage tree is the same as the one of the RocksDB storage, in which
+//! each column family is a sled tree.
+
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use data_encoding::HEXLOWER;
+use namada::ledger::storage::types::PrefixIterator;
+use namada::ledger::storage::{
+    types, BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error,
+    MerkleTreeStoresRead, Result, StoreType, DB,
+};
+use namada::types::internal::TxQueue;
+use namada::types::storage::{
+    BlockHeight, BlockResults, Epoch, Epochs, Header, Key, KeySeg,
+    KEY_SEGMENT_SEPARATOR,
+};
+use namada::types::time::DateTimeUtc;
+use sled::transaction::TransactionError;
+use sled::Transactional;
+
+use super::snapshots::SnapshotEntry;
+use super::{BackgroundSync, CommitMetrics, DBBackend};
+
+/// Tree names, matching the column families of the RocksDB storage
+const SUBSPACE_CF: &str = "subspace";
+const DIFFS_CF: &str = "diffs";
+const STATE_CF: &str = "state";
+const BLOCK_CF: &str = "block";
+
+/// The trees that are exported in state sync snapshots. The diffs are left
+/// out, as they're only needed to read the past heights.
+const SNAPSHOT_CFS: [&str; 3] = [STATE_CF, BLOCK_CF, SUBSPACE_CF];
+
+/// sled handle
+#[derive(Debug)]
+pub struct SledDB {
+    db: sled::Db,
+    subspace: sled::Tree,
+    diffs: sled::Tree,
+    state: sled::Tree,
+    block: sled::Tree,
+    sync: BackgroundSync,
+}
+
+/// DB Handle for batch writes, with a batch for each of the trees, which are
+/// applied in a single transaction.
+#[derive(Debug, Default)]
+pub struct SledDBWriteBatch {
+    subspace: sled::Batch,
+    diffs: sled::Batch,
+    state: sled::Batch,
+    block: sled::Batch,
+}
+
+/// Open sled for the DB, with the given capacity of its cache in bytes
+pub fn open(path: impl AsRef<Path>, cache: Option<&u64>) -> Result<SledDB> {
+    let mut config = sled::Config::new().path(path);
+    if let Some(cache) = cache {
+        config = config.cache_capacity(*cache);
+    }
+    open_with_config(config)
+}
+
+/// Open a temporary sled DB, which is removed once it's dropped, to be used
+/// as an in-memory DB in tests
+#[cfg(test)]
+pub fn open_temporary() -> Result<SledDB> {
+    open_with_config(sled::Config::new().temporary(true))
+}
+
+fn open_with_config(config: sled::Config) -> Result<SledDB> {
+    let db = config.open().map_err(db_error)?;
+    let open_tree = |name| db.open_tree(name).map_err(db_error);
+    Ok(SledDB {
+        subspace: open_tree(SUBSPACE_CF)?,
+        diffs: open_tree(DIFFS_CF)?,
+        state: open_tree(STATE_CF)?,
+        block: open_tree(BLOCK_CF)?,
+        db,
+        sync: BackgroundSync::default(),
+    })
+}
+
+impl Drop for SledDB {
+    fn drop(&mut self) {
+        self.sync.wait().expect("sync failed");
+        self.flush(true).expect("flush failed");
+    }
+}
+
+impl SledDB {
+    fn tree(&self, cf_name: &str) -> Result<&sled::Tree> {
+        match cf_name {
+            SUBSPACE_CF => Ok(&self.subspace),
+            DIFFS_CF => Ok(&self.diffs),
+            STATE_CF => Ok(&self.state),
+            BLOCK_CF => Ok(&self.block),
+            _ => Err(Error::DBError(format!("No {cf_name} tree"))),
+        }
+    }
+
+    fn flush(&self, _wait: bool) -> Result<()> {
+        // sled always waits for the flush to complete
+        self.db.flush().map(|_| ()).map_err(db_error)
+    }
+
+    /// Persist the diff of an account subspace key-val under the height where
+    /// it was changed.
+    fn write_subspace_diff(
+        &self,
+        height: BlockHeight,
+        key: &Key,
+        old_value: Option<&[u8]>,
+        new_value: Option<&[u8]>,
+    ) -> Result<()> {
+        if let Some(old_value) = old_value {
+            self.diffs
+                .insert(diff_key(height, key, true)?, old_value)
+                .map_err(db_error)?;
+        }
+        if let Some(new_value) = new_value {
+            self.diffs
+                .insert(diff_key(height, key, false)?, new_value)
+                .map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    /// Persist the diff of an account subspace key-val under the height where
+    /// it was changed in a batch write.
+    fn batch_write_subspace_diff(
+        &self,
+        batch: &mut SledDBWriteBatch,
+        height: BlockHeight,
+        key: &Key,
+        old_value: Option<&[u8]>,
+        new_value: Option<&[u8]>,
+    ) -> Result<()> {
+        if let Some(old_value) = old_value {
+            batch
+                .diffs
+                .insert(diff_key(height, key, true)?.as_bytes(), old_value);
+        }
+        if let Some(new_value) = new_value {
+            batch
+                .diffs
+                .insert(diff_key(height, key, false)?.as_bytes(), new_value);
+        }
+        Ok(())
+    }
+
+    /// Dump data
+    fn dump_it(
+        &self,
+        tree: &sled::Tree,
+        prefix: Option<String>,
+        file: &mut File,
+    ) {
+        use std::io::Write;
+
+        let iter = SledPrefixIterator(PrefixIterator {
+            iter: tree.scan_prefix(prefix.unwrap_or_default()),
+            // Empty string to prevent prefix stripping, the prefix is
+            // already in the enclosed iterator
+            db_prefix: String::default(),
+        });
+        for (key, raw_val, _gas) in iter {
+            let val = HEXLOWER.encode(&raw_val);
+            let bytes = format!("\"{key}\" = \"{val}\"\n");
+            file.write_all(bytes.as_bytes())
+                .expect("Unable to write to output file");
+        }
+    }
+}
+
+impl DBBackend for SledDB {
+    fn new_cache(capacity_bytes: u64) -> Self::Cache {
+        capacity_bytes
+    }
+
+    fn iter_snapshot_entries(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<SnapshotEntry>> + '_> {
+        Box::new(SNAPSHOT_CFS.into_iter().flat_map(move |cf_name| {
+            let tree = self.tree(cf_name).expect("{cf_name} tree should exist");
+            tree.iter().map(move |result| {
+                let (key, value) = result.map_err(db_error)?;
+                Ok(SnapshotEntry {
+                    cf: cf_name.to_owned(),
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                })
+            })
+        }))
+    }
+
+    fn write_snapshot_entries(
+        &mut self,
+        entries: Vec<SnapshotEntry>,
+    ) -> Result<()> {
+        let mut batch = SledDBWriteBatch::default();
+        for SnapshotEntry { cf, key, value } in entries {
+            let tree_batch = match cf.as_str() {
+                STATE_CF => &mut batch.state,
+                BLOCK_CF => &mut batch.block,
+                SUBSPACE_CF => &mut batch.subspace,
+                _ => {
+                    return Err(Error::DBError(format!(
+                        "Unexpected {cf} column family in a snapshot"
+                    )));
+                }
+            };
+            tree_batch.insert(key, value);
+        }
+        self.exec_batch(batch)
+    }
+
+    fn dump_last_block(
+        &self,
+        out_file_path: std::path::PathBuf,
+        historic: bool,
+    ) {
+        // Find the last block height
+        let height: BlockHeight = types::decode(
+            self.state
+                .get("height")
+                .expect("Unable to read DB")
+                .expect("No block height found"),
+        )
+        .expect("Unable to decode block height");
+
+        let full_path = out_file_path
+            .with_file_name(format!(
+                "{}_{height}",
+                out_file_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "dump_db".to_string())
+            ))
+            .with_extension("toml");
+
+        let mut file = File::options()
+            .append(true)
+            .create_new(true)
+            .open(&full_path)
+            .expect("Cannot open the output file");
+
+        println!("Will write to {} ...", full_path.to_string_lossy());
+
+        if historic {
+            // Dump the keys prepended with the selected block height (includes
+            // subspace diff keys)
+            let prefix = height.raw();
+            self.dump_it(&self.diffs, Some(prefix.clone()), &mut file);
+            self.dump_it(&self.block, Some(prefix), &mut file);
+        }
+
+        self.dump_it(&self.subspace, None, &mut file);
+
+        println!("Done writing to {}", full_path.to_string_lossy());
+    }
+
+    fn commit_metrics(&self) -> CommitMetrics {
+        self.sync.metrics()
+    }
+
+    fn rollback(&mut self, tendermint_block_height: BlockHeight) -> Result<()> {
+        let last_block = self.read_last_block()?.ok_or(Error::DBError(
+            "Missing last block in storage".to_string(),
+        ))?;
+        tracing::info!(
+            "Namada last block height: {}, Tendermint last block height: {}",
+            last_block.height,
+            tendermint_block_height
+        );
+
+        // If the block height to which tendermint rolled back matches the
+        // Namada height, there's no need to rollback
+        if tendermint_block_height == last_block.height {
+            tracing::info!(
+                "Namada height already matches the rollback Tendermint \
+                 height, no need to rollback."
+            );
+            return Ok(());
+        }
+
+        let mut batch = SledDBWriteBatch::default();
+        let previous_height =
+            BlockHeight::from(u64::from(last_block.height) - 1);
+
+        // Revert the non-height-prepended metadata storage keys which get
+        // updated with every block. Because of the way we save these
+        // three keys in storage we can only perform one rollback before
+        // restarting the chain
+        tracing::info!("Reverting non-height-prepended metadata keys");
+        batch
+            .state
+            .insert("height".as_bytes(), types::encode(&previous_height));
+        for metadata_key in [
+            "next_epoch_min_start_height",
+            "next_epoch_min_start_time",
+            "tx_queue",
+        ] {
+            let previous_key = format!("pred/{}", metadata_key);
+            let previous_value = self
+                .state
+                .get(previous_key.as_bytes())
+                .map_err(db_error)?
+                .ok_or(Error::UnknownKey { key: previous_key })?;
+
+            batch.state.insert(metadata_key.as_bytes(), previous_value);
+            // NOTE: we cannot restore the "pred/" keys themselves since we
+            // don't have their predecessors in storage, but there's no need to
+            // since we cannot do more than one rollback anyway because of
+            // Tendermint.
+        }
+
+        // Delete block results for the last block
+        tracing::info!("Removing last block results");
+        batch
+            .block
+            .remove(format!("results/{}", last_block.height.raw()).as_bytes());
+
+        tracing::info!("Restoring previous hight subspace diffs");
+        for (key, _value, _gas) in self.iter_prefix(&Key::default()) {
+            // Restore previous height diff if present, otherwise delete the
+            // subspace key
+            match self.read_subspace_val_with_height(
+                &Key::from(key.to_db_key()),
+                previous_height,
+                last_block.height,
+            )? {
+                Some(previous_value) => {
+                    batch.subspace.insert(key.as_bytes(), previous_value)
+                }
+                None => batch.subspace.remove(key.as_bytes()),
+            }
+        }
+
+        tracing::info!("Deleting keys prepended with the last height");
+        let prefix = format!("{}/", last_block.height.raw());
+        // Delete any height-prepended key in subspace diffs and in the block
+        for (tree, tree_batch) in [
+            (&self.diffs, &mut batch.diffs),
+            (&self.block, &mut batch.block),
+        ] {
+            for entry in tree.scan_prefix(&prefix) {
+                let (key, _value) = entry.map_err(db_error)?;
+                tree_batch.remove(key);
+            }
+        }
+
+        // Write the batch and persist changes to disk
+        tracing::info!("Flushing restored state to disk");
+        self.exec_batch(batch)
+    }
+}
+
+impl DB for SledDB {
+    type Cache = u64;
+    type WriteBatch = SledDBWriteBatch;
+
+    fn open(
+        db_path: impl AsRef<std::path::Path>,
+        cache: Option<&Self::Cache>,
+    ) -> Self {
+        open(db_path, cache).expect("cannot open the DB")
+    }
+
+    fn flush(&self, wait: bool) -> Result<()> {
+        self.flush(wait)
+    }
+
+    fn read_last_block(&mut self) -> Result<Option<BlockStateRead>> {
+        // Block height
+        let height: BlockHeight =
+            match self.state.get("height").map_err(db_error)? {
+                Some(bytes) => {
+                    types::decode(bytes).map_err(Error::CodingError)?
+                }
+                None => return Ok(None),
+            };
+
+        // Block results
+        let results_path = format!("results/{}", height.raw());
+        let results: BlockResults =
+            match self.block.get(results_path).map_err(db_error)? {
+                Some(bytes) => {
+                    types::decode(bytes).map_err(Error::CodingError)?
+                }
+                None => return Ok(None),
+            };
+
+        // Epoch start height and time
+        let next_epoch_min_start_height: BlockHeight = match self
+            .state
+            .get("next_epoch_min_start_height")
+            .map_err(db_error)?
+        {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError)?,
+            None => {
+                tracing::error!(
+                    "Couldn't load next epoch start height from the DB"
+                );
+                return Ok(None);
+            }
+        };
+        let next_epoch_min_start_time: DateTimeUtc = match self
+            .state
+            .get("next_epoch_min_start_time")
+            .map_err(db_error)?
+        {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError)?,
+            None => {
+                tracing::error!(
+                    "Couldn't load next epoch start time from the DB"
+                );
+                return Ok(None);
+            }
+        };
+        let update_epoch_blocks_delay: Option<u32> = match self
+            .state
+            .get("update_epoch_blocks_delay")
+            .map_err(db_error)?
+        {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError)?,
+            None => {
+                tracing::error!(
+                    "Couldn't load epoch update block delay from the DB"
+                );
+                return Ok(None);
+            }
+        };
+        let tx_queue: TxQueue =
+            match self.state.get("tx_queue").map_err(db_error)? {
+                Some(bytes) => {
+                    types::decode(bytes).map_err(Error::CodingError)?
+                }
+                None => {
+                    tracing::error!("Couldn't load tx queue from the DB");
+                    return Ok(None);
+                }
+            };
+
+        // Load block data at the height
+        let prefix = format!("{}/", height.raw());
+        let mut merkle_tree_stores = MerkleTreeStoresRead::default();
+        let mut hash = None;
+        let mut time = None;
+        let mut epoch = None;
+        let mut pred_epochs = None;
+        let mut address_gen = None;
+        for value in self.block.scan_prefix(prefix) {
+            let (key, bytes) = value.map_err(db_error)?;
+            let path = &String::from_utf8(key.to_vec()).map_err(|e| {
+                Error::Temporary {
+                    error: format!(
+                        "Cannot convert path from utf8 bytes to string: {}",
+                        e
+                    ),
+                }
+            })?;
+            let segments: Vec<&str> =
+                path.split(KEY_SEGMENT_SEPARATOR).collect();
+            match segments.get(1) {
+                Some(prefix) => match *prefix {
+                    "tree" => match segments.get(2) {
+                        Some(s) => {
+                            let st = StoreType::from_str(s)?;
+                            match segments.get(3) {
+                                Some(&"root") => merkle_tree_stores.set_root(
+                                    &st,
+                                    types::decode(bytes)
+                                        .map_err(Error::CodingError)?,
+                                ),
+                                Some(&"store") => merkle_tree_stores
+                                    .set_store(st.decode_store(bytes)?),
+                                _ => unknown_key_error(path)?,
+                            }
+                        }
+                        None => unknown_key_error(path)?,
+                    },
+                    "header" => {
+                        // the block header doesn't have to be restored
+                    }
+                    "hash" => {
+                        hash = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "time" => {
+                        time = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "epoch" => {
+                        epoch = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "pred_epochs" => {
+                        pred_epochs = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "address_gen" => {
+                        address_gen = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        );
+                    }
+                    _ => unknown_key_error(path)?,
+                },
+                None => unknown_key_error(path)?,
+            }
+        }
+        match (hash, time, epoch, pred_epochs, address_gen) {
+            (
+                Some(hash),
+                Some(time),
+                Some(epoch),
+                Some(pred_epochs),
+                Some(address_gen),
+            ) => Ok(Some(BlockStateRead {
+                merkle_tree_stores,
+                hash,
+                height,
+                time,
+                epoch,
+                pred_epochs,
+                results,
+                next_epoch_min_start_height,
+                next_epoch_min_start_time,
+                update_epoch_blocks_delay,
+                address_gen,
+                tx_queue,
+            })),
+            _ => Err(Error::Temporary {
+                error: "Essential data couldn't be read from the DB"
+                    .to_string(),
+            }),
+        }
+    }
+
+    fn write_block(
+        &mut self,
+        state: BlockStateWrite,
+        batch: &mut Self::WriteBatch,
+        is_full_commit: bool,
+    ) -> Result<()> {
+        let BlockStateWrite {
+            merkle_tree_stores,
+            header,
+            hash,
+            height,
+            time,
+            epoch,
+            pred_epochs,
+            next_epoch_min_start_height,
+            next_epoch_min_start_time,
+            update_epoch_blocks_delay,
+            address_gen,
+            results,
+            tx_queue,
+        }: BlockStateWrite = state;
+
+        // Epoch start height and time, the tx queue and the epoch update
+        // delay, with their predecessor values for rollback
+        for (key, value) in [
+            (
+                "next_epoch_min_start_height",
+                types::encode(&next_epoch_min_start_height),
+            ),
+            (
+                "next_epoch_min_start_time",
+                types::encode(&next_epoch_min_start_time),
+            ),
+            (
+                "update_epoch_blocks_delay",
+                types::encode(&update_epoch_blocks_delay),
+            ),
+            ("tx_queue", types::encode(&tx_queue)),
+        ] {
+            if let Some(current_value) =
+                self.state.get(key).map_err(db_error)?
+            {
+                batch
+                    .state
+                    .insert(format!("pred/{}", key).as_bytes(), current_value);
+            }
+            batch.state.insert(key.as_bytes(), value);
+        }
+
+        let prefix_key = Key::from(height.to_db_key());
+        // Merkle tree
+        {
+            let prefix_key = prefix_key
+                .push(&"tree".to_owned())
+                .map_err(Error::KeyError)?;
+            for st in StoreType::iter() {
+                if *st == StoreType::Base || is_full_commit {
+                    let prefix_key = prefix_key
+                        .push(&st.to_string())
+                        .map_err(Error::KeyError)?;
+                    let root_key = prefix_key
+                        .push(&"root".to_owned())
+                        .map_err(Error::KeyError)?;
+                    batch.block.insert(
+                        root_key.to_string().as_bytes(),
+                        types::encode(merkle_tree_stores.root(st)),
+                    );
+                    let store_key = prefix_key
+                        .push(&"store".to_owned())
+                        .map_err(Error::KeyError)?;
+                    batch.block.insert(
+                        store_key.to_string().as_bytes(),
+                        merkle_tree_stores.store(st).encode(),
+                    );
+                }
+            }
+        }
+        // Block header
+        if let Some(h) = header {
+            let key = prefix_key
+                .push(&"header".to_owned())
+                .map_err(Error::KeyError)?;
+            batch.block.insert(
+                key.to_string().as_bytes(),
+                h.try_to_vec().expect("serialization failed"),
+            );
+        }
+        // Block hash, time, epoch, predecessor epochs and address gen
+        for (key, value) in [
+            ("hash", types::encode(&hash)),
+            ("time", types::encode(&time)),
+            ("epoch", types::encode(&epoch)),
+            ("pred_epochs", types::encode(&pred_epochs)),
+            ("address_gen", types::encode(&address_gen)),
+        ] {
+            let key =
+                prefix_key.push(&key.to_owned()).map_err(Error::KeyError)?;
+            batch.block.insert(key.to_string().as_bytes(), value);
+        }
+        // Block results
+        let results_path = format!("results/{}", height.raw());
+        batch
+            .block
+            .insert(results_path.as_bytes(), types::encode(&results));
+
+        // Block height
+        batch
+            .state
+            .insert("height".as_bytes(), types::encode(&height));
+
+        Ok(())
+    }
+
+    fn read_block_header(&self, height: BlockHeight) -> Result<Option<Header>> {
+        let prefix_key = Key::from(height.to_db_key());
+        let key = prefix_key
+            .push(&"header".to_owned())
+            .map_err(Error::KeyError)?;
+        match self.block.get(key.to_string()).map_err(db_error)? {
+            Some(v) => Ok(Some(
+                Header::try_from_slice(&v[..])
+                    .map_err(Error::BorshCodingError)?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn read_merkle_tree_stores(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<(BlockHeight, MerkleTreeStoresRead)>> {
+        // Get the latest height at which the tree stores were written
+        let height_key = Key::from(height.to_db_key());
+        let key = height_key
+            .push(&"pred_epochs".to_owned())
+            .expect("Cannot obtain a storage key");
+        let pred_epochs: Epochs =
+            match self.block.get(key.to_string()).map_err(db_error)? {
+                Some(b) => types::decode(b).map_err(Error::CodingError)?,
+                None => return Ok(None),
+            };
+        // Read the tree at the first height if no epoch update
+        let stored_height = match pred_epochs.get_epoch_start_height(height) {
+            Some(BlockHeight(0)) | None => BlockHeight(1),
+            Some(h) => h,
+        };
+
+        let tree_key = Key::from(stored_height.to_db_key())
+            .push(&"tree".to_owned())
+            .map_err(Error::KeyError)?;
+        let mut merkle_tree_stores = MerkleTreeStoresRead::default();
+        for st in StoreType::iter() {
+            let prefix_key =
+                tree_key.push(&st.to_string()).map_err(Error::KeyError)?;
+            let root_key = prefix_key
+                .push(&"root".to_owned())
+                .map_err(Error::KeyError)?;
+            match self.block.get(root_key.to_string()).map_err(db_error)? {
+                Some(b) => {
+                    let root = types::decode(b).map_err(Error::CodingError)?;
+                    merkle_tree_stores.set_root(st, root);
+                }
+                None => return Ok(None),
+            }
+
+            let store_key = prefix_key
+                .push(&"store".to_owned())
+                .map_err(Error::KeyError)?;
+            match self.block.get(store_key.to_string()).map_err(db_error)? {
+                Some(b) => {
+                    merkle_tree_stores.set_store(st.decode_store(b)?);
+                }
+                None => return Ok(None),
+            }
+        }
+        Ok(Some((stored_height, merkle_tree_stores)))
+    }
+
+    fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        self.subspace
+            .get(key.to_string())
+            .map(|value| value.map(|value| value.to_vec()))
+            .map_err(db_error)
+    }
+
+    fn read_subspace_val_with_height(
+        &self,
+        key: &Key,
+        height: BlockHeight,
+        last_height: BlockHeight,
+    ) -> Result<Option<Vec<u8>>> {
+        // If it has a "new" val, it was written at this height
+        if let Some(new_val) = self
+            .diffs
+            .get(diff_key(height, key, false)?)
+            .map_err(db_error)?
+        {
+            return Ok(Some(new_val.to_vec()));
+        }
+        // If it has an "old" val, it was deleted at this height
+        if self
+            .diffs
+            .contains_key(diff_key(height, key, true)?)
+            .map_err(db_error)?
+        {
+            return Ok(None);
+        }
+
+        // If the value didn't change at the given height, we try to look for it
+        // at successor heights, up to the `last_height`
+        let mut raw_height = height.0 + 1;
+        loop {
+            let height = BlockHeight(raw_height);
+            // If it has an "old" val, it's the one we're looking for
+            if let Some(old_val) = self
+                .diffs
+                .get(diff_key(height, key, true)?)
+                .map_err(db_error)?
+            {
+                return Ok(Some(old_val.to_vec()));
+            }
+            // Check if the value was created at this height instead, which
+            // would mean that it wasn't present before
+            if self
+                .diffs
+                .contains_key(diff_key(height, key, false)?)
+                .map_err(db_error)?
+            {
+                return Ok(None);
+            }
+
+            if raw_height >= last_height.0 {
+                // Read from latest height
+                return self.read_subspace_val(key);
+            } else {
+                raw_height += 1
+            }
+        }
+    }
+
+    fn write_subspace_val(
+        &mut self,
+        height: BlockHeight,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<i64> {
+        let value = value.as_ref();
+        let size_diff =
+            match self.subspace.get(key.to_string()).map_err(db_error)? {
+                Some(prev_value) => {
+                    let size_diff =
+                        value.len() as i64 - prev_value.len() as i64;
+                    self.write_subspace_diff(
+                        height,
+                        key,
+                        Some(&prev_value),
+                        Some(value),
+                    )?;
+                    size_diff
+                }
+                None => {
+                    self.write_subspace_diff(height, key, None, Some(value))?;
+                    value.len() as i64
+                }
+            };
+
+        // Write the new key-val
+        self.subspace
+            .insert(key.to_string(), value)
+            .map_err(db_error)?;
+
+        Ok(size_diff)
+    }
+
+    fn delete_subspace_val(
+        &mut self,
+        height: BlockHeight,
+        key: &Key,
+    ) -> Result<i64> {
+        // Delete the key-val and check the length of previous value, if any
+        let prev_len = match self
+            .subspace
+            .remove(key.to_string())
+            .map_err(db_error)?
+        {
+            Some(prev_value) => {
+                self.write_subspace_diff(height, key, Some(&prev_value), None)?;
+                prev_value.len() as i64
+            }
+            None => 0,
+        };
+
+        Ok(prev_len)
+    }
+
+    fn batch() -> Self::WriteBatch {
+        SledDBWriteBatch::default()
+    }
+
+    /// Apply the batch to the DB and flush it to the disk in the background,
+    /// after the flush of the previous batch is done
+    fn exec_batch(&mut self, batch: Self::WriteBatch) -> Result<()> {
+        self.sync.wait()?;
+        let start = Instant::now();
+        let SledDBWriteBatch {
+            subspace,
+            diffs,
+            state,
+            block,
+        } = batch;
+        (&self.subspace, &self.diffs, &self.state, &self.block)
+            .transaction(
+                |(subspace_tree, diffs_tree, state_tree, block_tree)| {
+                    subspace_tree.apply_batch(&subspace)?;
+                    diffs_tree.apply_batch(&diffs)?;
+                    state_tree.apply_batch(&state)?;
+                    block_tree.apply_batch(&block)?;
+                    Ok(())
+                },
+            )
+            .map_err(|e: TransactionError| Error::DBError(format!("{e:?}")))?;
+        let db = self.db.clone();
+        self.sync.start(start.elapsed(), move || {
+            db.flush().map(|_| ()).map_err(db_error)
+        });
+        Ok(())
+    }
+
+    fn batch_write_subspace_val(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<i64> {
+        let value = value.as_ref();
+        let size_diff =
+            match self.subspace.get(key.to_string()).map_err(db_error)? {
+                Some(old_value) => {
+                    let size_diff = value.len() as i64 - old_value.len() as i64;
+                    // Persist the previous value
+                    self.batch_write_subspace_diff(
+                        batch,
+                        height,
+                        key,
+                        Some(&old_value),
+                        Some(value),
+                    )?;
+                    size_diff
+                }
+                None => {
+                    self.batch_write_subspace_diff(
+                        batch,
+                        height,
+                        key,
+                        None,
+                        Some(value),
+                    )?;
+                    value.len() as i64
+                }
+            };
+
+        // Write the new key-val
+        batch.subspace.insert(key.to_string().as_bytes(), value);
+
+        Ok(size_diff)
+    }
+
+    fn batch_delete_subspace_val(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        key: &Key,
+    ) -> Result<i64> {
+        // Check the length of previous value, if any
+        let prev_len =
+            match self.subspace.get(key.to_string()).map_err(db_error)? {
+                Some(prev_value) => {
+                    let prev_len = prev_value.len() as i64;
+                    // Persist the previous value
+                    self.batch_write_subspace_diff(
+                        batch,
+                        height,
+                        key,
+                        Some(&prev_value),
+                        None,
+                    )?;
+                    prev_len
+                }
+                None => 0,
+            };
+
+        // Delete the key-val
+        batch.subspace.remove(key.to_string().as_bytes());
+
+        Ok(prev_len)
+    }
+
+    fn prune_merkle_tree_stores(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        epoch: Epoch,
+        pred_epochs: &Epochs,
+    ) -> Result<()> {
+        match pred_epochs.get_start_height_of_epoch(epoch) {
+            Some(height) => {
+                let prefix_key = Key::from(height.to_db_key())
+                    .push(&"tree".to_owned())
+                    .map_err(Error::KeyError)?;
+                for st in StoreType::iter() {
+                    if *st != StoreType::Base {
+                        let prefix_key = prefix_key
+                            .push(&st.to_string())
+                            .map_err(Error::KeyError)?;
+                        let root_key = prefix_key
+                            .push(&"root".to_owned())
+                            .map_err(Error::KeyError)?;
+                        batch.block.remove(root_key.to_string().as_bytes());
+                        let store_key = prefix_key
+                            .push(&"store".to_owned())
+                            .map_err(Error::KeyError)?;
+                        batch.block.remove(store_key.to_string().as_bytes());
+                    }
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn prune_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        before: BlockHeight,
+    ) -> Result<()> {
+        // The diffs are keyed by their height first, whose encoding maintains
+        // the order of the heights
+        for entry in self.diffs.range(BlockHeight(0).raw()..before.raw()) {
+            let (key, _value) = entry.map_err(db_error)?;
+            batch.diffs.remove(key);
+        }
+        Ok(())
+    }
+}
+
+impl<'iter> DBIter<'iter> for SledDB {
+    type PrefixIter = SledPrefixIterator;
+
+    fn iter_optional_prefix(
+        &'iter self,
+        prefix: Option<&Key>,
+    ) -> SledPrefixIterator {
+        let prefix = match prefix {
+            Some(prefix) => prefix.to_string(),
+            None => "".to_string(),
+        };
+        iter_prefix(&self.subspace, "".to_owned(), prefix)
+    }
+
+    fn iter_results(&'iter self) -> SledPrefixIterator {
+        iter_prefix(&self.block, "results/".to_owned(), "results".to_owned())
+    }
+
+    fn iter_old_diffs(&'iter self, height: BlockHeight) -> SledPrefixIterator {
+        iter_diffs_prefix(self, height, true)
+    }
+
+    fn iter_new_diffs(&'iter self, height: BlockHeight) -> SledPrefixIterator {
+        iter_diffs_prefix(self, height, false)
+    }
+}
+
+fn iter_diffs_prefix(
+    db: &SledDB,
+    height: BlockHeight,
+    is_old: bool,
+) -> SledPrefixIterator {
+    let prefix = if is_old { "old" } else { "new" };
+    let db_prefix = format!("{}/{}/", height.0.raw(), prefix);
+    // get keys without a prefix
+    iter_prefix(&db.diffs, db_prefix.clone(), db_prefix)
+}
+
+fn iter_prefix(
+    tree: &sled::Tree,
+    db_prefix: String,
+    prefix: String,
+) -> SledPrefixIterator {
+    SledPrefixIterator(PrefixIterator {
+        iter: tree.scan_prefix(prefix),
+        db_prefix,
+    })
+}
+
+#[derive(Debug)]
+pub struct SledPrefixIterator(PrefixIterator<sled::Iter>);
+
+impl Iterator for SledPrefixIterator {
+    type Item = (String, Vec<u8>, u64);
+
+    /// Returns the next pair and the gas cost
+    fn next(&mut self) -> Option<(String, Vec<u8>, u64)> {
+        match self.0.iter.next() {
+            Some(result) => {
+                let (key, val) =
+                    result.expect("Prefix iterator shouldn't fail");
+                let key = String::from_utf8(key.to_vec())
+                    .expect("Cannot convert from bytes to key string");
+                match key.strip_prefix(&self.0.db_prefix) {
+                    Some(k) => {
+                        let gas = k.len() + val.len();
+                        Some((k.to_owned(), val.to_vec(), gas as _))
+                    }
+                    None => self.next(),
+                }
+            }
+            None => None,
+        }
+    }
+}
+
+impl DBWriteBatch for SledDBWriteBatch {}
+
+/// The key of the old or new value of an account subspace key-val in the
+/// diffs of the given height
+fn diff_key(height: BlockHeight, key: &Key, is_old: bool) -> Result<String> {
+    let prefix = if is_old { "old" } else { "new" };
+    Ok(Key::from(height.to_db_key())
+        .push(&prefix.to_owned())
+        .map_err(Error::KeyError)?
+        .join(key)
+        .to_string())
+}
+
+fn db_error(error: sled::Error) -> Error {
+    Error::DBError(error.to_string())
+}
+
+fn unknown_key_error(key: &str) -> Result<()> {
+    Err(Error::UnknownKey {
+        key: key.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use namada::ledger::storage::{MerkleTree, Sha256Hasher};
+    use namada::types::address::EstablishedAddressGen;
+    use namada::types::storage::BlockHash;
+
+    use super::*;
+
+    /// Test that a block written can be loaded back from DB.
+    #[test]
+    fn test_load_state() {
+        let mut db = open_temporary().unwrap();
+
+        let mut batch = SledDB::batch();
+        let last_height = BlockHeight::default();
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &Key::parse("test").unwrap(),
+            vec![1_u8, 1, 1, 1],
+        )
+        .unwrap();
+
+        let merkle_tree = MerkleTree::<Sha256Hasher>::default();
+        let merkle_tree_stores = merkle_tree.stores();
+        let hash = BlockHash::default();
+        let time = DateTimeUtc::now();
+        let epoch = Epoch::default();
+        let pred_epochs = Epochs::default();
+        let height = BlockHeight::default();
+        let next_epoch_min_start_height = BlockHeight::default();
+        let next_epoch_min_start_time = DateTimeUtc::now();
+        let update_epoch_blocks_delay = None;
+        let address_gen = EstablishedAddressGen::new("whatever");
+        let tx_queue = TxQueue::default();
+        let results = BlockResults::default();
+        let block = BlockStateWrite {
+            merkle_tree_stores,
+            header: None,
+            hash: &hash,
+            height,
+            time,
+            epoch,
+            results: &results,
+            pred_epochs: &pred_epochs,
+            next_epoch_min_start_height,
+            next_epoch_min_start_time,
+            update_epoch_blocks_delay,
+            address_gen: &address_gen,
+            tx_queue: &tx_queue,
+        };
+
+        db.write_block(block, &mut batch, true).unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let _state = db
+            .read_last_block()
+            .expect("Should be able to read last block")
+            .expect("Block should have been written");
+    }
+
+    #[test]
+    fn test_read() {
+        let mut db = open_temporary().unwrap();
+
+        let key = Key::parse("test").unwrap();
+        let batch_key = Key::parse("batch").unwrap();
+
+        let mut batch = SledDB::batch();
+        let last_height = BlockHeight(100);
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &batch_key,
+            vec![1_u8, 1, 1, 1],
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        db.write_subspace_val(last_height, &key, vec![1_u8, 1, 1, 0])
+            .unwrap();
+
+        let mut batch = SledDB::batch();
+        let last_height = BlockHeight(111);
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &batch_key,
+            vec![2_u8, 2, 2, 2],
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        db.write_subspace_val(last_height, &key, vec![2_u8, 2, 2, 0])
+            .unwrap();
+
+        let prev_value = db
+            .read_subspace_val_with_height(
+                &batch_key,
+                BlockHeight(100),
+                last_height,
+            )
+            .expect("read should succeed");
+        assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 1]));
+        let prev_value = db
+            .read_subspace_val_with_height(&key, BlockHeight(100), last_height)
+            .expect("read should succeed");
+        assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 0]));
+
+        let latest_value = db
+            .read_subspace_val(&batch_key)
+            .expect("read should succeed");
+        assert_eq!(latest_value, Some(vec![2_u8, 2, 2, 2]));
+
+        let mut batch = SledDB::batch();
+        let last_height = BlockHeight(222);
+        db.batch_delete_subspace_val(&mut batch, last_height, &batch_key)
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        db.delete_subspace_val(last_height, &key).unwrap();
+
+        let deleted_value = db
+            .read_subspace_val_with_height(
+                &batch_key,
+                BlockHeight(222),
+                last_height,
+            )
+            .expect("read should succeed");
+        assert_eq!(deleted_value, None);
+        let latest_value =
+            db.read_subspace_val(&key).expect("read should succeed");
+        assert_eq!(latest_value, None);
+        assert_eq!(db.iter_old_diffs(BlockHeight(222)).count(), 2);
+    }
+}
@@ -0,0 +1,366 @@
+//! State sync snapshots of the storage, from which the nodes joining the
+//! network restore the state of a recent height rather than replaying all the
+//! blocks.
+//!
+//! A snapshot is created at the first height of an epoch, at which all the
+//! merkle tree stores are written to the DB. It consists of the key-vals of
+//! the DB column families that are needed to load the last state, split into
+//! chunks that are stored in `{dir}/{height}/{chunk index}`, and of its
+//! metadata, stored in `{dir}/{height}/metadata`, with the hashes of the
+//! chunks.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada::ledger::storage;
+use namada::types::hash::Hash;
+use namada::types::storage::BlockHeight;
+use thiserror::Error;
+
+use super::DBBackend;
+
+/// The format of the snapshots, to be bumped on any change of their encoding
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// The default approximate maximum size of the chunks of the snapshots in
+/// bytes
+pub const DEFAULT_CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+/// The name of the file of the metadata of a snapshot
+const METADATA_FILE: &str = "metadata";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Snapshot I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Storage error: {0}")]
+    Storage(#[from] storage::Error),
+    #[error("Failed to decode a snapshot chunk or metadata: {0}")]
+    Decode(io::Error),
+    #[error("The chunk {0} doesn't match its hash in the snapshot metadata")]
+    ChunkHash(u32),
+    #[error("Unexpected chunk {0}, expected the chunk {1}")]
+    ChunkIndex(u32, u32),
+}
+
+/// Result of a function that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A key-val of a column family of the DB
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SnapshotEntry {
+    /// The name of the column family
+    pub cf: String,
+    /// The key
+    pub key: Vec<u8>,
+    /// The value
+    pub value: Vec<u8>,
+}
+
+/// The metadata of a snapshot
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SnapshotMetadata {
+    /// The hashes of the chunks, in order
+    pub chunk_hashes: Vec<Hash>,
+}
+
+impl SnapshotMetadata {
+    /// The hash of the snapshot, which commits to the hashes of its chunks
+    pub fn hash(&self) -> Hash {
+        Hash::sha256(
+            self.try_to_vec()
+                .expect("Encoding snapshot metadata shouldn't fail"),
+        )
+    }
+}
+
+/// A snapshot of the storage at some height
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The height of the last committed block of the storage
+    pub height: BlockHeight,
+    /// The metadata of the snapshot
+    pub metadata: SnapshotMetadata,
+}
+
+impl Snapshot {
+    /// The number of chunks of the snapshot
+    pub fn chunks(&self) -> u32 {
+        self.metadata.chunk_hashes.len() as u32
+    }
+}
+
+/// The snapshots stored in a directory
+#[derive(Clone, Debug)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+    chunk_size: usize,
+}
+
+impl SnapshotStore {
+    /// Open the snapshots stored in the given directory
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self::with_chunk_size(dir, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Open the snapshots stored in the given directory, with the given
+    /// approximate maximum size of the chunks of the new snapshots
+    pub fn with_chunk_size(dir: impl AsRef<Path>, chunk_size: usize) -> Self {
+        Self {
+            dir: dir.as_ref().to_owned(),
+            chunk_size,
+        }
+    }
+
+    /// Create a snapshot of the state of the DB, whose last committed block is
+    /// at the given height
+    pub fn create<D: DBBackend>(
+        &self,
+        db: &D,
+        height: BlockHeight,
+    ) -> Result<Snapshot> {
+        let dir = self.snapshot_dir(height);
+        // The chunks are written to a temporary directory first, such that an
+        // interrupted snapshot isn't listed
+        let tmp_dir = dir.with_extension("tmp");
+        if tmp_dir.exists() {
+            fs::remove_dir_all(&tmp_dir)?;
+        }
+        fs::create_dir_all(&tmp_dir)?;
+
+        let mut chunk_hashes = vec![];
+        let mut chunk = vec![];
+        let mut chunk_len = 0;
+        for entry in db.iter_snapshot_entries() {
+            let entry = entry?;
+            chunk_len += entry.key.len() + entry.value.len();
+            chunk.push(entry);
+            if chunk_len >= self.chunk_size {
+                chunk_hashes.push(write_chunk(
+                    &tmp_dir,
+                    chunk_hashes.len(),
+                    &chunk,
+                )?);
+                chunk.clear();
+                chunk_len = 0;
+            }
+        }
+        if !chunk.is_empty() || chunk_hashes.is_empty() {
+            chunk_hashes.push(write_chunk(
+                &tmp_dir,
+                chunk_hashes.len(),
+                &chunk,
+            )?);
+        }
+
+        let metadata = SnapshotMetadata { chunk_hashes };
+        fs::write(tmp_dir.join(METADATA_FILE), metadata.try_to_vec()?)?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::rename(&tmp_dir, &dir)?;
+        Ok(Snapshot { height, metadata })
+    }
+
+    /// List the stored snapshots, from the most recent one
+    pub fn list(&self) -> Result<Vec<Snapshot>> {
+        if !self.dir.is_dir() {
+            return Ok(vec![]);
+        }
+        let mut snapshots = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            // Skip the temporary directories of the snapshots being created
+            let height = match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.parse().ok())
+            {
+                Some(height) => BlockHeight(height),
+                None => continue,
+            };
+            let metadata = fs::read(path.join(METADATA_FILE))?;
+            let metadata = SnapshotMetadata::try_from_slice(&metadata)
+                .map_err(Error::Decode)?;
+            snapshots.push(Snapshot { height, metadata });
+        }
+        snapshots.sort_by(|a, b| b.height.cmp(&a.height));
+        Ok(snapshots)
+    }
+
+    /// Load a chunk of the snapshot at the given height, if any
+    pub fn load_chunk(
+        &self,
+        height: BlockHeight,
+        index: u32,
+    ) -> Result<Option<Vec<u8>>> {
+        let path = self.snapshot_dir(height).join(index.to_string());
+        match fs::read(path) {
+            Ok(chunk) => Ok(Some(chunk)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Remove all the snapshots but the given number of the most recent ones
+    pub fn prune(&self, keep: usize) -> Result<()> {
+        for snapshot in self.list()?.into_iter().skip(keep) {
+            fs::remove_dir_all(self.snapshot_dir(snapshot.height))?;
+        }
+        Ok(())
+    }
+
+    fn snapshot_dir(&self, height: BlockHeight) -> PathBuf {
+        self.dir.join(height.0.to_string())
+    }
+}
+
+/// Write a chunk of a snapshot to the given directory and return its hash
+fn write_chunk(
+    dir: &Path,
+    index: usize,
+    entries: &[SnapshotEntry],
+) -> Result<Hash> {
+    let chunk = entries.try_to_vec()?;
+    fs::write(dir.join(index.to_string()), &chunk)?;
+    Ok(Hash::sha256(chunk))
+}
+
+/// The restoration of the DB from the chunks of a snapshot, which are applied
+/// in order
+#[derive(Debug)]
+pub struct SnapshotRestore {
+    snapshot: Snapshot,
+    next_chunk: u32,
+}
+
+impl SnapshotRestore {
+    /// Start restoring the DB from the given snapshot
+    pub fn new(snapshot: Snapshot) -> Self {
+        Self {
+            snapshot,
+            next_chunk: 0,
+        }
+    }
+
+    /// Check the given chunk against its hash in the snapshot metadata and
+    /// write its entries to the DB. Returns `true` if it's the last chunk of
+    /// the snapshot.
+    pub fn apply_chunk<D: DBBackend>(
+        &mut self,
+        db: &mut D,
+        index: u32,
+        chunk: &[u8],
+    ) -> Result<bool> {
+        if index != self.next_chunk {
+            return Err(Error::ChunkIndex(index, self.next_chunk));
+        }
+        let hash = self.snapshot.metadata.chunk_hashes.get(index as usize);
+        if hash != Some(&Hash::sha256(chunk)) {
+            return Err(Error::ChunkHash(index));
+        }
+        let entries = Vec::<SnapshotEntry>::try_from_slice(chunk)
+            .map_err(Error::Decode)?;
+        db.write_snapshot_entries(entries)?;
+        self.next_chunk += 1;
+        Ok(self.next_chunk == self.snapshot.chunks())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::ledger::storage::write_log::WriteLog;
+    use namada::ledger::storage::{
+        types, update_allowed_conversions, WlStorage,
+    };
+    use namada::types::address;
+    use namada::types::chain::ChainId;
+    use namada::types::storage::{BlockHash, Key};
+    use tempfile::TempDir;
+
+    use super::super::PersistentStorage;
+    use super::*;
+
+    /// Test that a storage is restored from the chunks of its snapshot
+    #[test]
+    fn test_snapshot_restore() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = PersistentStorage::open(
+            db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(100))
+            .expect("begin_block failed");
+        let key = Key::parse("key").expect("cannot parse the key string");
+        let value_bytes = types::encode(&1_u64);
+        storage
+            .write(&key, value_bytes.clone())
+            .expect("write failed");
+        storage.block.epoch = storage.block.epoch.next();
+        storage.block.pred_epochs.new_epoch(BlockHeight(100), 1000);
+        let mut wl_storage = WlStorage::new(WriteLog::default(), storage);
+        update_allowed_conversions(&mut wl_storage)
+            .expect("update conversions failed");
+        wl_storage.commit_block().expect("commit failed");
+        let root = wl_storage.storage.merkle_root().0;
+
+        let snapshots_dir = TempDir::new()
+            .expect("Unable to create a temporary snapshots directory");
+        let store = SnapshotStore::with_chunk_size(snapshots_dir.path(), 64);
+        let snapshot = store
+            .create(&wl_storage.storage.db, BlockHeight(100))
+            .expect("creating the snapshot failed");
+        assert!(snapshot.chunks() > 1);
+        assert_eq!(store.list().unwrap(), vec![snapshot.clone()]);
+
+        let restored_db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut restored = PersistentStorage::open(
+            restored_db_path.path(),
+            ChainId::default(),
+            address::nam(),
+            None,
+            None,
+        );
+        let mut restore = SnapshotRestore::new(snapshot.clone());
+        // The chunks must match their hashes and be applied in order
+        assert!(matches!(
+            restore.apply_chunk(&mut restored.db, 0, b"invalid"),
+            Err(Error::ChunkHash(0))
+        ));
+        assert!(matches!(
+            restore.apply_chunk(&mut restored.db, 1, b"invalid"),
+            Err(Error::ChunkIndex(1, 0))
+        ));
+        for index in 0..snapshot.chunks() {
+            let chunk = store
+                .load_chunk(BlockHeight(100), index)
+                .unwrap()
+                .expect("missing chunk");
+            let is_last = restore
+                .apply_chunk(&mut restored.db, index, &chunk)
+                .expect("applying the chunk failed");
+            assert_eq!(is_last, index + 1 == snapshot.chunks());
+        }
+
+        restored
+            .load_last_state()
+            .expect("loading the last state failed");
+        let (loaded_root, height) =
+            restored.get_state().expect("no block exists");
+        assert_eq!(loaded_root.0, root);
+        assert_eq!(height, 100);
+        let (val, _) = restored.read(&key).expect("read failed");
+        assert_eq!(val.expect("no value"), value_bytes);
+
+        store.prune(0).expect("pruning the snapshots failed");
+        assert!(store.list().unwrap().is_empty());
+    }
+}
@@ -390,6 +390,10 @@ async fn update_tendermint_config(
     config.instrumentation.namespace =
         tendermint_config.instrumentation_namespace;
 
+    if let Some(laddr) = tendermint_config.priv_validator_laddr {
+        config.priv_validator_laddr = Some(laddr);
+    }
+
     #[cfg(feature = "abciplus")]
     {
         config.consensus.timeout_propose =
@@ -45,6 +45,19 @@ impl WalletUtils for CliWalletUtils {
         }
     }
 
+    fn read_key_decryption_password(alias: &Alias) -> Zeroizing<String> {
+        if env::var("NAMADA_WALLET_PASSWORD_FILE").is_ok()
+            || env::var("NAMADA_WALLET_PASSWORD").is_ok()
+        {
+            return Self::read_decryption_password();
+        }
+        let prompt =
+            format!("Enter the decryption password of the key \"{alias}\": ");
+        rpassword::read_password_from_tty(Some(&prompt))
+            .map(Zeroizing::new)
+            .expect("Failed reading password from tty.")
+    }
+
     fn read_encryption_password() -> Zeroizing<String> {
         let pwd = match env::var("NAMADA_WALLET_PASSWORD_FILE") {
             Ok(path) => Zeroizing::new(
@@ -75,6 +88,11 @@ impl WalletUtils for CliWalletUtils {
         pwd
     }
 
+    fn read_key_encryption_password(alias: &Alias) -> Zeroizing<String> {
+        println!("Encrypting the key \"{alias}\".");
+        Self::read_encryption_password()
+    }
+
     fn read_alias(prompt_msg: &str) -> String {
         print!("Choose an alias for {}: ", prompt_msg);
         io::stdout().flush().unwrap();
@@ -0,0 +1,179 @@
+//! Estimation of the block heights and the times at which the epochs start,
+//! with which clients can e.g. display the countdown of unbonding.
+//!
+//! A new epoch starts at the first block whose height and time are both past
+//! the minimum start height and time of the next epoch, which are set from the
+//! [`EpochDuration`] parameter at the start of each epoch. The estimates
+//! assume that all the blocks take the same time.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+use super::parameters::EpochDuration;
+use crate::types::storage::{BlockHeight, Epoch};
+use crate::types::time::{DateTimeUtc, DurationSecs};
+
+/// The state of the epochs of a chain at some block, from which the upcoming
+/// epochs are estimated
+#[derive(
+    Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct EpochClock {
+    /// The epoch of the block
+    pub epoch: Epoch,
+    /// The height of the block
+    pub height: BlockHeight,
+    /// The time of the block
+    pub time: DateTimeUtc,
+    /// The minimum height of the first block of the next epoch
+    pub next_epoch_min_start_height: BlockHeight,
+    /// The minimum time of the first block of the next epoch
+    pub next_epoch_min_start_time: DateTimeUtc,
+    /// The minimum duration of the epochs
+    pub epoch_duration: EpochDuration,
+    /// The expected time of the blocks, which is treated as one second if
+    /// it's zero
+    pub block_time: DurationSecs,
+}
+
+impl EpochClock {
+    /// The expected time of the blocks, at least one second
+    fn block_time(&self) -> DurationSecs {
+        DurationSecs(self.block_time.0.max(1))
+    }
+
+    /// The number of blocks from the block of the clock until the start of
+    /// the next epoch, at least one
+    fn blocks_until_next_epoch(&self) -> u64 {
+        let min_blocks =
+            self.height.blocks_until(self.next_epoch_min_start_height);
+        let min_duration =
+            self.next_epoch_min_start_time.duration_since(self.time);
+        min_blocks
+            .max(min_duration.div_ceil(self.block_time()))
+            .max(1)
+    }
+
+    /// The number of blocks of the epochs after the next one, at least one
+    fn blocks_per_epoch(&self) -> u64 {
+        self.epoch_duration
+            .min_num_of_blocks
+            .max(self.epoch_duration.min_duration.div_ceil(self.block_time()))
+            .max(1)
+    }
+
+    /// The number of blocks from the block of the clock until the estimated
+    /// start of the given epoch, or `None` if it's not an upcoming epoch or
+    /// if it overflows
+    fn blocks_until_epoch(&self, epoch: Epoch) -> Option<u64> {
+        let epochs_after_next = epoch.checked_sub(self.epoch.next())?.0;
+        epochs_after_next
+            .checked_mul(self.blocks_per_epoch())?
+            .checked_add(self.blocks_until_next_epoch())
+    }
+
+    /// Estimate the height of the first block of the given epoch, or `None`
+    /// if it's not an upcoming epoch
+    pub fn epoch_start_height(&self, epoch: Epoch) -> Option<BlockHeight> {
+        let blocks = self.blocks_until_epoch(epoch)?;
+        self.height.0.checked_add(blocks).map(BlockHeight)
+    }
+
+    /// Estimate the time of the first block of the given epoch, or `None` if
+    /// it's not an upcoming epoch
+    pub fn epoch_start_time(&self, epoch: Epoch) -> Option<DateTimeUtc> {
+        let blocks = self.blocks_until_epoch(epoch)?;
+        let duration = self.block_time().checked_mul(blocks)?;
+        Some(self.time + duration)
+    }
+
+    /// Estimate the epoch of the block at the given height, or `None` if it's
+    /// before the block of the clock
+    pub fn epoch_at_height(&self, height: BlockHeight) -> Option<Epoch> {
+        if height < self.height {
+            return None;
+        }
+        let blocks = self.height.blocks_until(height);
+        let blocks_until_next_epoch = self.blocks_until_next_epoch();
+        if blocks < blocks_until_next_epoch {
+            return Some(self.epoch);
+        }
+        let epochs_after_next =
+            (blocks - blocks_until_next_epoch) / self.blocks_per_epoch();
+        self.epoch.next().checked_add(epochs_after_next)
+    }
+
+    /// Estimate the duration from the given time until the start of the given
+    /// epoch, which is zero if it's not an upcoming epoch or if its estimated
+    /// start is already past
+    pub fn duration_until_epoch(
+        &self,
+        epoch: Epoch,
+        now: DateTimeUtc,
+    ) -> DurationSecs {
+        self.epoch_start_time(epoch)
+            .map(|start| start.duration_since(now))
+            .unwrap_or(DurationSecs(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock() -> EpochClock {
+        let time: DateTimeUtc = "2023-01-01T00:00:00Z".parse().unwrap();
+        EpochClock {
+            epoch: Epoch(3),
+            height: BlockHeight(100),
+            time,
+            next_epoch_min_start_height: BlockHeight(105),
+            next_epoch_min_start_time: time + DurationSecs(60),
+            epoch_duration: EpochDuration {
+                min_num_of_blocks: 10,
+                min_duration: DurationSecs(100),
+            },
+            block_time: DurationSecs(5),
+        }
+    }
+
+    #[test]
+    fn test_epoch_clock_estimates() {
+        let clock = clock();
+        // The next epoch is bound by its min start time, after 12 blocks of
+        // 5 seconds each
+        assert_eq!(clock.epoch_start_height(Epoch(4)), Some(BlockHeight(112)));
+        assert_eq!(
+            clock.epoch_start_time(Epoch(4)),
+            Some(clock.time + DurationSecs(60))
+        );
+        // The following epochs are bound by their min duration, of 20 blocks
+        assert_eq!(clock.epoch_start_height(Epoch(6)), Some(BlockHeight(152)));
+        assert_eq!(
+            clock.epoch_start_time(Epoch(6)),
+            Some(clock.time + DurationSecs(260))
+        );
+        // Past epochs aren't estimated
+        assert_eq!(clock.epoch_start_height(Epoch(3)), None);
+        assert_eq!(clock.epoch_start_time(Epoch(2)), None);
+
+        assert_eq!(clock.epoch_at_height(BlockHeight(99)), None);
+        assert_eq!(clock.epoch_at_height(BlockHeight(111)), Some(Epoch(3)));
+        assert_eq!(clock.epoch_at_height(BlockHeight(112)), Some(Epoch(4)));
+        assert_eq!(clock.epoch_at_height(BlockHeight(151)), Some(Epoch(5)));
+        assert_eq!(clock.epoch_at_height(BlockHeight(152)), Some(Epoch(6)));
+
+        let now = clock.time + DurationSecs(10);
+        assert_eq!(clock.duration_until_epoch(Epoch(4), now), DurationSecs(50));
+        assert_eq!(clock.duration_until_epoch(Epoch(3), now), DurationSecs(0));
+    }
+
+    #[test]
+    fn test_epoch_clock_without_block_time() {
+        // A zero block time is treated as one second
+        let clock = EpochClock {
+            block_time: DurationSecs(0),
+            ..clock()
+        };
+        assert_eq!(clock.epoch_start_height(Epoch(4)), Some(BlockHeight(160)));
+    }
+}
@@ -4,6 +4,7 @@
 use std::convert::TryFrom;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[allow(missing_docs)]
@@ -29,6 +30,11 @@ const TRANSACTION_GAS_LIMIT: u64 = 10_000_000_000;
 /// The minimum gas cost for accessing the storage
 pub const MIN_STORAGE_GAS: u64 = 1;
 
+/// The version of the default gas cost table
+pub const GAS_COST_TABLE_VERSION: u64 = 1;
+/// The default gas cost of a signature section of an applied tx
+const SIGNATURE_VERIFICATION_GAS: u64 = 1_000;
+
 /// Gas module result for functions that may fail
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -38,6 +44,93 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct BlockGasMeter {
     block_gas: u64,
     transaction_gas: u64,
+    /// The gas limit of the current transaction, if lower than the default
+    /// `TRANSACTION_GAS_LIMIT`
+    transaction_gas_limit: Option<u64>,
+    /// The costs of the metered operations
+    gas_costs: GasCostTable,
+}
+
+/// The costs of the operations metered in txs and VPs. The table is a
+/// protocol parameter, so that gas pricing can be updated by governance. All
+/// the validators must apply the same table in a given block.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct GasCostTable {
+    /// The version of the table, to be increased on every update
+    pub version: u64,
+    /// The base cost of applying a tx
+    pub tx_base: u64,
+    /// The cost of each byte of an applied tx
+    pub tx_size_per_byte: u64,
+    /// The cost of each byte read from storage
+    pub storage_read_per_byte: u64,
+    /// The cost of each byte written to storage
+    pub storage_write_per_byte: u64,
+    /// The cost of each signature section of an applied tx
+    pub signature_verification: u64,
+    /// The cost of each unit of gas charged by the instrumented WASM code,
+    /// i.e. of each WASM opcode
+    pub wasm_opcode: u64,
+}
+
+impl Default for GasCostTable {
+    fn default() -> Self {
+        Self {
+            version: GAS_COST_TABLE_VERSION,
+            tx_base: BASE_TRANSACTION_FEE,
+            tx_size_per_byte: 0,
+            storage_read_per_byte: 1,
+            storage_write_per_byte: 1,
+            signature_verification: SIGNATURE_VERIFICATION_GAS,
+            wasm_opcode: 1,
+        }
+    }
+}
+
+impl GasCostTable {
+    /// The gas cost of applying a tx of the given length in bytes
+    pub fn tx_size(&self, bytes_len: u64) -> u64 {
+        bytes_len
+            .saturating_mul(self.tx_size_per_byte)
+            .saturating_add(self.tx_base)
+    }
+
+    /// The gas cost of reading from storage, given the gas of the read in
+    /// bytes
+    pub fn storage_read(&self, bytes_len: u64) -> u64 {
+        bytes_len.saturating_mul(self.storage_read_per_byte)
+    }
+
+    /// The gas cost of writing to storage, given the gas of the write in
+    /// bytes
+    pub fn storage_write(&self, bytes_len: u64) -> u64 {
+        bytes_len.saturating_mul(self.storage_write_per_byte)
+    }
+
+    /// The gas cost of verifying the given number of signatures
+    pub fn signature_verifications(&self, count: u64) -> u64 {
+        count.saturating_mul(self.signature_verification)
+    }
+
+    /// The gas cost of the given WASM gas, as charged by the instrumented
+    /// WASM code
+    pub fn wasm_opcodes(&self, used_gas: u64) -> u64 {
+        used_gas.saturating_mul(self.wasm_opcode)
+    }
 }
 
 /// Gas metering in a validity predicate
@@ -47,6 +140,8 @@ pub struct VpGasMeter {
     initial_gas: u64,
     /// The current gas usage in the VP
     pub current_gas: u64,
+    /// The costs of the metered operations
+    gas_costs: GasCostTable,
 }
 
 /// Gas meter for VPs parallel runs
@@ -68,17 +163,47 @@ impl BlockGasMeter {
             .checked_add(gas)
             .ok_or(Error::GasOverflow)?;
 
-        if self.transaction_gas > TRANSACTION_GAS_LIMIT {
+        if self.transaction_gas > self.get_transaction_gas_limit() {
             return Err(Error::TransactionGasExceededError);
         }
         Ok(())
     }
 
+    /// Set the gas limit of the current transaction, e.g. the one declared in
+    /// its wrapper. The limit is capped at the default transaction gas limit
+    /// and applies until the transaction is finalized or aborted.
+    pub fn set_transaction_gas_limit(&mut self, limit: u64) {
+        self.transaction_gas_limit = Some(limit.min(TRANSACTION_GAS_LIMIT));
+    }
+
+    /// Get the gas limit that applies to the current transaction.
+    pub fn get_transaction_gas_limit(&self) -> u64 {
+        self.transaction_gas_limit.unwrap_or(TRANSACTION_GAS_LIMIT)
+    }
+
+    /// Set the gas cost table to meter the next transactions with
+    pub fn set_gas_costs(&mut self, gas_costs: GasCostTable) {
+        self.gas_costs = gas_costs;
+    }
+
+    /// Get the gas cost table the transactions are metered with
+    pub fn gas_costs(&self) -> &GasCostTable {
+        &self.gas_costs
+    }
+
     /// Add the base transaction fee and the fee per transaction byte that's
     /// charged the moment we try to apply the transaction.
     pub fn add_base_transaction_fee(&mut self, bytes_len: usize) -> Result<()> {
         tracing::trace!("add_base_transaction_fee {}", bytes_len);
-        self.add(BASE_TRANSACTION_FEE)
+        self.add(self.gas_costs.tx_size(bytes_len as u64))
+    }
+
+    /// Add the fee for verifying the given number of signatures
+    pub fn add_signature_verification_fee(
+        &mut self,
+        count: usize,
+    ) -> Result<()> {
+        self.add(self.gas_costs.signature_verifications(count as u64))
     }
 
     /// Add the compiling cost proportionate to the code length
@@ -98,15 +223,28 @@ impl BlockGasMeter {
 
         let transaction_gas = self.transaction_gas;
         self.transaction_gas = 0;
+        self.transaction_gas_limit = None;
         if self.block_gas > BLOCK_GAS_LIMIT {
             return Err(Error::BlockGasExceeded);
         }
         Ok(transaction_gas)
     }
 
+    /// Abort the current transaction, e.g. when it ran out of gas. The gas it
+    /// consumed so far still counts towards the block's total gas. Returns the
+    /// transaction's gas cost and resets the transaction meter.
+    pub fn abort_transaction(&mut self) -> u64 {
+        let transaction_gas = self.transaction_gas;
+        self.block_gas = self.block_gas.saturating_add(transaction_gas);
+        self.transaction_gas = 0;
+        self.transaction_gas_limit = None;
+        transaction_gas
+    }
+
     /// Reset the gas meter.
     pub fn reset(&mut self) {
         self.transaction_gas = 0;
+        self.transaction_gas_limit = None;
         self.block_gas = 0;
     }
 
@@ -128,9 +266,21 @@ impl VpGasMeter {
         Self {
             initial_gas,
             current_gas: 0,
+            gas_costs: GasCostTable::default(),
         }
     }
 
+    /// Meter the VP with the given gas cost table
+    pub fn with_gas_costs(mut self, gas_costs: GasCostTable) -> Self {
+        self.gas_costs = gas_costs;
+        self
+    }
+
+    /// Get the gas cost table the VP is metered with
+    pub fn gas_costs(&self) -> &GasCostTable {
+        &self.gas_costs
+    }
+
     /// Consume gas in a validity predicate. It will return error when the
     /// consumed gas exceeds the transaction gas limit, but the state will still
     /// be updated.
@@ -283,6 +433,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tx_gas_custom_limit() {
+        let mut meter = BlockGasMeter::default();
+        meter.set_transaction_gas_limit(10);
+        meter.add(10).expect("cannot add the gas");
+        assert_matches!(
+            meter.add(1).expect_err("unexpectedly succeeded"),
+            Error::TransactionGasExceededError
+        );
+        assert_eq!(meter.abort_transaction(), 11);
+        assert_eq!(meter.get_current_transaction_gas(), 0);
+
+        // The limit doesn't carry over to the next tx
+        assert_eq!(meter.get_transaction_gas_limit(), TRANSACTION_GAS_LIMIT);
+        meter.add(11).expect("cannot add the gas");
+
+        // The limit can't be raised over the default one
+        meter.set_transaction_gas_limit(u64::MAX);
+        assert_eq!(meter.get_transaction_gas_limit(), TRANSACTION_GAS_LIMIT);
+    }
+
+    #[test]
+    fn test_gas_cost_table() {
+        let mut meter = BlockGasMeter::default();
+        // The default table only charges the base fee for the tx size
+        meter
+            .add_base_transaction_fee(100)
+            .expect("cannot add the gas");
+        assert_eq!(meter.get_current_transaction_gas(), BASE_TRANSACTION_FEE);
+        meter
+            .finalize_transaction()
+            .expect("cannot finalize the tx");
+
+        meter.set_gas_costs(GasCostTable {
+            version: GAS_COST_TABLE_VERSION + 1,
+            tx_size_per_byte: 3,
+            signature_verification: 7,
+            ..GasCostTable::default()
+        });
+        meter
+            .add_base_transaction_fee(100)
+            .expect("cannot add the gas");
+        meter
+            .add_signature_verification_fee(2)
+            .expect("cannot add the gas");
+        assert_eq!(
+            meter.get_current_transaction_gas(),
+            BASE_TRANSACTION_FEE + 300 + 14
+        );
+
+        // The table is kept across txs and blocks
+        meter
+            .finalize_transaction()
+            .expect("cannot finalize the tx");
+        meter.reset();
+        assert_eq!(meter.gas_costs().tx_size_per_byte, 3);
+    }
+
     #[test]
     fn test_block_gas_limit() {
         let mut meter = BlockGasMeter::default();
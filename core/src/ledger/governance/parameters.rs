@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 
 use super::storage as gov_storage;
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
@@ -23,6 +25,8 @@ pub struct GovParams {
     pub min_proposal_fund: u64,
     /// Maximum kibibyte length for proposal code
     pub max_proposal_code_size: u64,
+    /// Gas budget of the execution of a proposal code
+    pub max_proposal_code_gas: u64,
     /// Minimum proposal voting period in epochs
     pub min_proposal_period: u64,
     /// Maximum proposal voting period in epochs
@@ -31,21 +35,27 @@ pub struct GovParams {
     pub max_proposal_content_size: u64,
     /// Minimum epochs between end and grace epochs
     pub min_proposal_grace_epochs: u64,
+    /// Minimum fraction of the total voting power that must vote on a
+    /// rejected proposal for its funds to be refunded rather than burned
+    pub min_proposal_quorum: Decimal,
 }
 
 impl Display for GovParams {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Min. proposal fund: {}\nMax. proposal code size: {}\nMin. \
-             proposal period: {}\nMax. proposal period: {}\nMax. proposal \
-             content size: {}\nMin. proposal grace epochs: {}",
+            "Min. proposal fund: {}\nMax. proposal code size: {}\nMax. \
+             proposal code gas: {}\nMin. proposal period: {}\nMax. proposal \
+             period: {}\nMax. proposal content size: {}\nMin. proposal grace \
+             epochs: {}\nMin. proposal quorum: {}",
             self.min_proposal_fund,
             self.max_proposal_code_size,
+            self.max_proposal_code_gas,
             self.min_proposal_period,
             self.max_proposal_period,
             self.max_proposal_content_size,
-            self.min_proposal_grace_epochs
+            self.min_proposal_grace_epochs,
+            self.min_proposal_quorum
         )
     }
 }
@@ -55,10 +65,12 @@ impl Default for GovParams {
         Self {
             min_proposal_fund: 500,
             max_proposal_code_size: 300_000,
+            max_proposal_code_gas: 1_000_000_000,
             min_proposal_period: 3,
             max_proposal_period: 27,
             max_proposal_content_size: 10_000,
             min_proposal_grace_epochs: 6,
+            min_proposal_quorum: dec!(0.33),
         }
     }
 }
@@ -72,10 +84,12 @@ impl GovParams {
         let Self {
             min_proposal_fund,
             max_proposal_code_size,
+            max_proposal_code_gas,
             min_proposal_period,
             max_proposal_period,
             max_proposal_content_size,
             min_proposal_grace_epochs,
+            min_proposal_quorum,
         } = self;
 
         let min_proposal_fund_key = gov_storage::get_min_proposal_fund_key();
@@ -86,6 +100,10 @@ impl GovParams {
             gov_storage::get_max_proposal_code_size_key();
         storage.write(&max_proposal_code_size_key, max_proposal_code_size)?;
 
+        let max_proposal_code_gas_key =
+            gov_storage::get_max_proposal_code_gas_key();
+        storage.write(&max_proposal_code_gas_key, max_proposal_code_gas)?;
+
         let min_proposal_period_key =
             gov_storage::get_min_proposal_period_key();
         storage.write(&min_proposal_period_key, min_proposal_period)?;
@@ -104,6 +122,10 @@ impl GovParams {
         storage
             .write(&min_proposal_grace_epoch_key, min_proposal_grace_epochs)?;
 
+        let min_proposal_quorum_key =
+            gov_storage::get_min_proposal_quorum_key();
+        storage.write(&min_proposal_quorum_key, min_proposal_quorum)?;
+
         let counter_key = gov_storage::get_counter_key();
         storage.write(&counter_key, u64::MIN)
     }
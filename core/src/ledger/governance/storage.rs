@@ -1,6 +1,6 @@
 use crate::ledger::governance::ADDRESS;
 use crate::types::address::Address;
-use crate::types::storage::{DbKeySeg, Key, KeySeg};
+use crate::types::storage::{DbKeySeg, Key, KeyBuilder};
 
 const PROPOSAL_PREFIX: &str = "proposal";
 const PROPOSAL_VOTE: &str = "vote";
@@ -16,10 +16,12 @@ const PROPOSAL_COMMITTING_EPOCH: &str = "epoch";
 
 const MIN_PROPOSAL_FUND_KEY: &str = "min_fund";
 const MAX_PROPOSAL_CODE_SIZE_KEY: &str = "max_code_size";
+const MAX_PROPOSAL_CODE_GAS_KEY: &str = "max_code_gas";
 const MIN_PROPOSAL_PERIOD_KEY: &str = "min_period";
 const MAX_PROPOSAL_PERIOD_KEY: &str = "max_period";
 const MAX_PROPOSAL_CONTENT_SIZE_KEY: &str = "max_content";
 const MIN_GRACE_EPOCH_KEY: &str = "min_grace_epoch";
+const MIN_PROPOSAL_QUORUM_KEY: &str = "min_quorum";
 const COUNTER_KEY: &str = "counter";
 const PENDING_PROPOSAL: &str = "pending";
 
@@ -218,9 +220,18 @@ pub fn is_max_content_size_key(key: &Key) -> bool {
 pub fn is_max_proposal_code_size_key(key: &Key) -> bool {
     matches!(&key.segments[..], [
              DbKeySeg::AddressSeg(addr),
-             DbKeySeg::StringSeg(max_content_size_param),
+             DbKeySeg::StringSeg(max_code_size_param),
          ] if addr == &ADDRESS
-             && max_content_size_param == MAX_PROPOSAL_CONTENT_SIZE_KEY)
+             && max_code_size_param == MAX_PROPOSAL_CODE_SIZE_KEY)
+}
+
+/// Check if key is a max proposal code gas key
+pub fn is_max_proposal_code_gas_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+             DbKeySeg::AddressSeg(addr),
+             DbKeySeg::StringSeg(max_code_gas_param),
+         ] if addr == &ADDRESS
+             && max_code_gas_param == MAX_PROPOSAL_CODE_GAS_KEY)
 }
 
 /// Check if key is a min proposal period param key
@@ -264,14 +275,25 @@ pub fn is_min_grace_epoch_key(key: &Key) -> bool {
                     && min_grace_epoch_param == MIN_GRACE_EPOCH_KEY)
 }
 
+/// Check if key is a min proposal quorum key
+pub fn is_min_proposal_quorum_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+                    DbKeySeg::AddressSeg(addr),
+                    DbKeySeg::StringSeg(min_quorum_param),
+                ] if addr == &ADDRESS
+                    && min_quorum_param == MIN_PROPOSAL_QUORUM_KEY)
+}
+
 /// Check if key is parameter key
 pub fn is_parameter_key(key: &Key) -> bool {
     is_min_proposal_fund_key(key)
         || is_max_content_size_key(key)
         || is_max_proposal_code_size_key(key)
+        || is_max_proposal_code_gas_key(key)
         || is_min_proposal_period_key(key)
         || is_max_proposal_period_key(key)
         || is_min_grace_epoch_key(key)
+        || is_min_proposal_quorum_key(key)
 }
 
 /// Check if key is start epoch or end epoch key
@@ -281,155 +303,146 @@ pub fn is_start_or_end_epoch_key(key: &Key) -> bool {
 
 /// Get governance prefix key
 pub fn proposal_prefix() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&PROPOSAL_PREFIX.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(PROPOSAL_PREFIX).build()
 }
 
 /// Get key for the minimum proposal fund
 pub fn get_min_proposal_fund_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&MIN_PROPOSAL_FUND_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(MIN_PROPOSAL_FUND_KEY).build()
 }
 
 /// Get maximum proposal code size key
 pub fn get_max_proposal_code_size_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&MAX_PROPOSAL_CODE_SIZE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(MAX_PROPOSAL_CODE_SIZE_KEY)
+        .build()
+}
+
+/// Get maximum proposal code gas key
+pub fn get_max_proposal_code_gas_key() -> Key {
+    Key::builder(&ADDRESS)
+        .name(MAX_PROPOSAL_CODE_GAS_KEY)
+        .build()
 }
 
 /// Get minimum proposal period key
 pub fn get_min_proposal_period_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&MIN_PROPOSAL_PERIOD_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(MIN_PROPOSAL_PERIOD_KEY).build()
 }
 
 /// Get maximum proposal period key
 pub fn get_max_proposal_period_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&MAX_PROPOSAL_PERIOD_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(MAX_PROPOSAL_PERIOD_KEY).build()
 }
 
 /// Get maximum proposal content key
 pub fn get_max_proposal_content_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&MAX_PROPOSAL_CONTENT_SIZE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(MAX_PROPOSAL_CONTENT_SIZE_KEY)
+        .build()
 }
 
 /// Get min grace epoch proposal key
 pub fn get_min_proposal_grace_epoch_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&MIN_GRACE_EPOCH_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(MIN_GRACE_EPOCH_KEY).build()
+}
+
+/// Get the key of the minimum quorum for the funds of a rejected proposal to
+/// be refunded
+pub fn get_min_proposal_quorum_key() -> Key {
+    Key::builder(&ADDRESS).name(MIN_PROPOSAL_QUORUM_KEY).build()
 }
 
 /// Get key of proposal ids counter
 pub fn get_counter_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&COUNTER_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(COUNTER_KEY).build()
 }
 
 /// Get key of proposal content
 pub fn get_content_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_CONTENT.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_CONTENT)
+        .build()
 }
 
 /// Get key of proposal author
 pub fn get_author_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_AUTHOR.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_AUTHOR)
+        .build()
 }
 
 /// Get key of a proposal type
 pub fn get_proposal_type_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_TYPE.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_TYPE)
+        .build()
 }
 
 /// Get key of proposal voting start epoch
 pub fn get_voting_start_epoch_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_START_EPOCH.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_START_EPOCH)
+        .build()
 }
 
 /// Get key of proposal voting end epoch
 pub fn get_voting_end_epoch_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_END_EPOCH.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_END_EPOCH)
+        .build()
 }
 
 /// Get key of proposal funds
 pub fn get_funds_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_FUNDS.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_FUNDS)
+        .build()
 }
 
 /// Get proposal grace epoch key
 pub fn get_grace_epoch_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_GRACE_EPOCH.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_GRACE_EPOCH)
+        .build()
 }
 
 /// Get the proposal committing key prefix
 pub fn get_commiting_proposals_prefix(epoch: u64) -> Key {
-    proposal_prefix()
-        .push(&PROPOSAL_COMMITTING_EPOCH.to_owned())
-        .expect("Cannot obtain a storage key")
-        .push(&epoch.to_string())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .name(PROPOSAL_COMMITTING_EPOCH)
+        .seg(&epoch.to_string())
+        .build()
 }
 
 /// Get proposal code key
 pub fn get_proposal_code_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_CODE.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_CODE)
+        .build()
 }
 
 /// Get the committing proposal key
 pub fn get_committing_proposals_key(id: u64, epoch: u64) -> Key {
-    get_commiting_proposals_prefix(epoch)
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(get_commiting_proposals_prefix(epoch))
+        .seg(&id.to_string())
+        .build()
 }
 
 /// Get proposal vote prefix key
 pub fn get_proposal_vote_prefix_key(id: u64) -> Key {
-    proposal_prefix()
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
-        .push(&PROPOSAL_VOTE.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(proposal_prefix())
+        .seg(&id.to_string())
+        .name(PROPOSAL_VOTE)
+        .build()
 }
 
 /// Get proposal code key
@@ -438,20 +451,18 @@ pub fn get_vote_proposal_key(
     voter_address: Address,
     delegation_address: Address,
 ) -> Key {
-    get_proposal_vote_prefix_key(id)
-        .push(&delegation_address)
-        .expect("Cannot obtain a storage key")
-        .push(&voter_address)
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(get_proposal_vote_prefix_key(id))
+        .seg(&delegation_address)
+        .seg(&voter_address)
+        .build()
 }
 
 /// Get the proposal execution key
 pub fn get_proposal_execution_key(id: u64) -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&PENDING_PROPOSAL.to_owned())
-        .expect("Cannot obtain a storage key")
-        .push(&id.to_string())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(PENDING_PROPOSAL)
+        .seg(&id.to_string())
+        .build()
 }
 
 /// Get proposal id from key
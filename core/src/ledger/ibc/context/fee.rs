@@ -0,0 +1,185 @@
+//! Fees of the relayers of IBC packets (ICS-29)
+//!
+//! A token transfer can be sent with fees for the relayers of its packet,
+//! which are escrowed by the [`InternalAddress::IbcFeeEscrow`] address until
+//! the packet is acknowledged or times out. The relayer of the acknowledgement
+//! is paid the receive and the acknowledgement fees, or the relayer of the
+//! timeout is paid the timeout fee, and the rest is refunded to the payer,
+//! i.e. the sender of the transfer.
+//!
+//! The relayers are paid on this chain, so a relayer is only paid when its
+//! signer is an address of this chain.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::common::IbcCommonContext;
+use super::transfer_mod::TransferModule;
+use crate::ibc::core::ics04_channel::packet::{Packet, Sequence};
+use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::ibc::signer::Signer;
+use crate::ledger::ibc::storage;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::token::{self, Amount};
+
+/// The type URL of a message of a token transfer with the fees of its
+/// relayers, encoded with Borsh
+pub const MSG_TRANSFER_WITH_FEE_TYPE_URL: &str = "/namada.ibc.TransferWithFee";
+
+/// The fees paid to the relayers of a packet
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct PacketFee {
+    /// The token of the fees
+    pub token: Address,
+    /// The fee for relaying the packet to the counterparty chain
+    pub recv_fee: Amount,
+    /// The fee for relaying the acknowledgement of the packet
+    pub ack_fee: Amount,
+    /// The fee for relaying the timeout of the packet
+    pub timeout_fee: Amount,
+}
+
+impl PacketFee {
+    /// The amount escrowed until the packet is acknowledged or times out
+    pub fn total(&self) -> Amount {
+        self.recv_fee + self.ack_fee + self.timeout_fee
+    }
+}
+
+/// A token transfer with the fees for the relayers of its packet
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct MsgTransferWithFee {
+    /// The IBC message of the token transfer, encoded with protobuf as an
+    /// `Any`
+    pub transfer: Vec<u8>,
+    /// The fees, paid by the sender of the transfer
+    pub fee: PacketFee,
+}
+
+/// The fees escrowed for the relayers of a packet, stored until the packet is
+/// acknowledged or times out
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct EscrowedFee {
+    /// The payer, who is refunded the fees which aren't paid
+    pub payer: Address,
+    /// The fees
+    pub fee: PacketFee,
+}
+
+fn fee_escrow_address() -> Address {
+    Address::Internal(InternalAddress::IbcFeeEscrow)
+}
+
+impl<C> TransferModule<C>
+where
+    C: IbcCommonContext,
+{
+    /// Escrow the fees of the relayers of the packet sent to the port and the
+    /// channel with the sequence
+    pub fn escrow_packet_fee(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        payer: Address,
+        fee: PacketFee,
+    ) -> Result<(), String> {
+        let src = token::balance_key(&fee.token, &payer);
+        let dest = token::balance_key(&fee.token, &fee_escrow_address());
+        self.ctx
+            .borrow_mut()
+            .transfer_token(&src, &dest, fee.total())
+            .map_err(|e| format!("Escrowing the fee failed: {:?}", e))?;
+        let escrowed = EscrowedFee { payer, fee };
+        self.ctx
+            .borrow_mut()
+            .write(
+                &storage::fee_key(port_id, channel_id, sequence),
+                escrowed.try_to_vec().expect("encoding shouldn't fail"),
+            )
+            .map_err(|e| format!("Writing the fee failed: {:?}", e))
+    }
+
+    /// Pay the escrowed fees of the packet, if any, to the relayer of its
+    /// acknowledgement or its timeout, and refund the rest to the payer
+    pub(super) fn pay_packet_fee(
+        &mut self,
+        packet: &Packet,
+        relayer: &Signer,
+        is_timeout: bool,
+    ) {
+        let fee_key = storage::fee_key(
+            &packet.port_id_on_a,
+            &packet.chan_id_on_a,
+            packet.sequence,
+        );
+        let escrowed = match self.ctx.borrow().read(&fee_key) {
+            Ok(Some(value)) => EscrowedFee::try_from_slice(&value)
+                .expect("Decoding the escrowed fee shouldn't fail"),
+            Ok(None) => return,
+            Err(e) => panic!("Reading the escrowed fee failed: {:?}", e),
+        };
+        self.ctx
+            .borrow_mut()
+            .delete(&fee_key)
+            .expect("Deleting the escrowed fee shouldn't fail");
+
+        let EscrowedFee { payer, fee } = escrowed;
+        let (paid, mut refunded) = if is_timeout {
+            (fee.timeout_fee, fee.recv_fee + fee.ack_fee)
+        } else {
+            (fee.recv_fee + fee.ack_fee, fee.timeout_fee)
+        };
+        match Address::try_from(relayer.clone()) {
+            Ok(relayer) => self.pay_fee(&fee.token, &relayer, paid),
+            // the relayer without an address on this chain isn't paid
+            Err(_) => refunded += paid,
+        }
+        self.pay_fee(&fee.token, &payer, refunded);
+    }
+
+    /// Pay the amount of the escrowed fee token to the target
+    fn pay_fee(
+        &mut self,
+        fee_token: &Address,
+        target: &Address,
+        amount: Amount,
+    ) {
+        if amount == Amount::default() {
+            return;
+        }
+        let src = token::balance_key(fee_token, &fee_escrow_address());
+        let dest = token::balance_key(fee_token, target);
+        self.ctx
+            .borrow_mut()
+            .transfer_token(&src, &dest, amount)
+            .expect("Paying the escrowed fee shouldn't fail");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::established_address_1;
+
+    #[test]
+    fn test_packet_fee_total() {
+        let fee = PacketFee {
+            token: established_address_1(),
+            recv_fee: Amount::whole(3),
+            ack_fee: Amount::whole(2),
+            timeout_fee: Amount::whole(1),
+        };
+        assert_eq!(fee.total(), Amount::whole(6));
+    }
+}
@@ -0,0 +1,404 @@
+//! Forwarding of the IBC token transfers to another chain
+//!
+//! A token transfer received with a `forward` memo, e.g.
+//! `{"forward":{"receiver":"cosmos1...","port":"transfer","channel":"channel-1"}}`
+//! is received by the [`InternalAddress::IbcForward`] address and sent right
+//! away over the given channel to the receiver of the memo, so that this chain
+//! routes the transfers between the chains that it's connected to.
+//!
+//! The received packet is acknowledged once the transfer is forwarded. When
+//! the forwarded packet fails, i.e. it's acknowledged with an error or times
+//! out, its refunded tokens are sent back to the original sender over the
+//! channel that they were received from.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::common::IbcCommonContext;
+use super::transfer_mod::{into_packet_error, TransferModule};
+use crate::ibc::applications::transfer::acknowledgement::TokenTransferAcknowledgement;
+use crate::ibc::applications::transfer::context::{
+    on_acknowledgement_packet_execute, on_recv_packet_execute,
+    on_timeout_packet_execute,
+};
+use crate::ibc::applications::transfer::denom::{PrefixedDenom, TracePrefix};
+use crate::ibc::applications::transfer::msgs::transfer::MsgTransfer;
+use crate::ibc::applications::transfer::packet::PacketData;
+use crate::ibc::applications::transfer::relay::send_transfer::{
+    send_transfer_execute, send_transfer_validate,
+};
+use crate::ibc::applications::transfer::{
+    is_receiver_chain_source, PORT_ID_STR,
+};
+use crate::ibc::core::ics04_channel::error::PacketError;
+use crate::ibc::core::ics04_channel::handler::ModuleExtras;
+use crate::ibc::core::ics04_channel::msgs::acknowledgement::Acknowledgement;
+use crate::ibc::core::ics04_channel::packet::Packet;
+use crate::ibc::core::ics04_channel::timeout::TimeoutHeight;
+use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::ibc::core::ics24_host::path::SeqSendPath;
+use crate::ibc::signer::Signer;
+use crate::ibc::timestamp::Timestamp;
+use crate::ibc_proto::cosmos::base::v1beta1::Coin;
+use crate::ledger::ibc::storage;
+use crate::tendermint::Time as TmTime;
+use crate::types::address::{Address, InternalAddress};
+
+/// The timeout of a forwarded transfer when the memo doesn't give one
+pub const DEFAULT_FORWARD_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The memo of a token transfer to be forwarded
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardMemo {
+    /// Where to forward the transfer
+    pub forward: ForwardMetadata,
+}
+
+/// Where to forward a token transfer
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardMetadata {
+    /// The receiver on the next chain
+    pub receiver: String,
+    /// The port of this chain to forward the transfer from
+    #[serde(default = "default_forward_port")]
+    pub port: String,
+    /// The channel of this chain to forward the transfer over
+    pub channel: String,
+    /// The timeout of the forwarded transfer in seconds from the time of the
+    /// block
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// A forwarded transfer, stored until its packet is acknowledged or times out
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ForwardedTransfer {
+    /// The port of this chain that the transfer was received on
+    pub port_id: String,
+    /// The channel of this chain that the transfer was received over
+    pub channel_id: String,
+    /// The original sender on the counterparty chain of the channel
+    pub sender: String,
+}
+
+/// The packet data of a token transfer with its memo, which isn't in
+/// [`PacketData`]
+#[derive(Deserialize)]
+struct PacketMemo {
+    #[serde(default)]
+    memo: Option<String>,
+}
+
+fn default_forward_port() -> String {
+    PORT_ID_STR.to_string()
+}
+
+/// Get where to forward the token transfer of the packet data, if any. A memo
+/// that isn't for forwarding is ignored.
+pub fn forward_metadata(packet_data: &[u8]) -> Option<ForwardMetadata> {
+    let memo = serde_json::from_slice::<PacketMemo>(packet_data)
+        .ok()?
+        .memo?;
+    serde_json::from_str::<ForwardMemo>(&memo)
+        .ok()
+        .map(|memo| memo.forward)
+}
+
+/// Get the denom on this chain of the token received with the packet
+pub fn received_denom(packet: &Packet, denom: &PrefixedDenom) -> PrefixedDenom {
+    let mut denom = denom.clone();
+    if is_receiver_chain_source(
+        packet.port_id_on_a.clone(),
+        packet.chan_id_on_a.clone(),
+        &denom,
+    ) {
+        let prefix = TracePrefix::new(
+            packet.port_id_on_a.clone(),
+            packet.chan_id_on_a.clone(),
+        );
+        denom.remove_trace_prefix(&prefix);
+    } else {
+        let prefix = TracePrefix::new(
+            packet.port_id_on_b.clone(),
+            packet.chan_id_on_b.clone(),
+        );
+        denom.add_trace_prefix(prefix);
+    }
+    denom
+}
+
+fn forward_address() -> Address {
+    Address::Internal(InternalAddress::IbcForward)
+}
+
+fn error_ack(message: impl ToString) -> Acknowledgement {
+    TokenTransferAcknowledgement::Error(message.to_string()).into()
+}
+
+impl<C> TransferModule<C>
+where
+    C: IbcCommonContext,
+{
+    /// Receive the token transfer of the packet, and forward it if its memo
+    /// asks for it
+    pub(super) fn recv_packet_execute(
+        &mut self,
+        packet: &Packet,
+    ) -> (ModuleExtras, Acknowledgement) {
+        let forward = match forward_metadata(&packet.data) {
+            Some(forward) => forward,
+            None => return on_recv_packet_execute(self, packet),
+        };
+        let mut data = match serde_json::from_slice::<PacketData>(&packet.data)
+        {
+            Ok(data) => data,
+            Err(e) => {
+                return (
+                    ModuleExtras::empty(),
+                    error_ack(format!(
+                        "Decoding the packet data failed: {}",
+                        e
+                    )),
+                );
+            }
+        };
+        let msg = match self.forward_msg(packet, &data, &forward) {
+            Ok(msg) => msg,
+            Err(e) => return (ModuleExtras::empty(), error_ack(e)),
+        };
+        // check the forwarding before receiving the tokens, so that the
+        // sender is refunded on the failure
+        if let Err(e) = send_transfer_validate(self, msg.clone()) {
+            return (
+                ModuleExtras::empty(),
+                error_ack(format!("The transfer can't be forwarded: {}", e)),
+            );
+        }
+
+        // receive the tokens with the forward address
+        let original_sender = data.sender.clone();
+        data.receiver = Signer::from_str(&forward_address().to_string())
+            .expect("The forward address should be a signer");
+        let mut recv_packet = packet.clone();
+        recv_packet.data = serde_json::to_vec(&data)
+            .expect("Encoding the packet data shouldn't fail");
+        let (extras, ack) = on_recv_packet_execute(self, &recv_packet);
+        let success: Acknowledgement =
+            TokenTransferAcknowledgement::success().into();
+        if ack.as_ref() != success.as_ref() {
+            return (extras, ack);
+        }
+
+        let seq_send_path =
+            SeqSendPath(msg.port_id_on_a.clone(), msg.chan_id_on_a.clone());
+        let sequence = self
+            .ctx
+            .borrow()
+            .get_next_sequence_send(&seq_send_path)
+            .expect("Reading the next sequence shouldn't fail");
+        let forward_key = storage::forward_key(
+            &msg.port_id_on_a,
+            &msg.chan_id_on_a,
+            sequence,
+        );
+        send_transfer_execute(self, msg)
+            .expect("Forwarding a validated transfer shouldn't fail");
+        let forwarded = ForwardedTransfer {
+            port_id: packet.port_id_on_b.to_string(),
+            channel_id: packet.chan_id_on_b.to_string(),
+            sender: original_sender.to_string(),
+        };
+        self.ctx
+            .borrow_mut()
+            .write(
+                &forward_key,
+                forwarded.try_to_vec().expect("encoding shouldn't fail"),
+            )
+            .expect("Writing the forwarded transfer shouldn't fail");
+        (extras, ack)
+    }
+
+    /// Handle the acknowledgement of the packet, sending the refunded tokens
+    /// back to the original sender if the packet was a forwarded transfer
+    pub(super) fn acknowledgement_packet_execute(
+        &mut self,
+        packet: &Packet,
+        acknowledgement: &Acknowledgement,
+        relayer: &Signer,
+    ) -> (ModuleExtras, Result<(), PacketError>) {
+        let (extras, result) = on_acknowledgement_packet_execute(
+            self,
+            packet,
+            acknowledgement,
+            relayer,
+        );
+        if result.is_err() {
+            return (extras, result.map_err(into_packet_error));
+        }
+        let is_refunded = !matches!(
+            serde_json::from_slice::<TokenTransferAcknowledgement>(
+                acknowledgement.as_ref()
+            ),
+            Ok(TokenTransferAcknowledgement::Success(_))
+        );
+        self.finish_forward(packet, is_refunded);
+        (extras, Ok(()))
+    }
+
+    /// Handle the timeout of the packet, sending the refunded tokens back to
+    /// the original sender if the packet was a forwarded transfer
+    pub(super) fn timeout_packet_execute(
+        &mut self,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> (ModuleExtras, Result<(), PacketError>) {
+        let (extras, result) = on_timeout_packet_execute(self, packet, relayer);
+        if result.is_err() {
+            return (extras, result.map_err(into_packet_error));
+        }
+        self.finish_forward(packet, true);
+        (extras, Ok(()))
+    }
+
+    /// Make the message to forward the received token transfer
+    fn forward_msg(
+        &self,
+        packet: &Packet,
+        data: &PacketData,
+        forward: &ForwardMetadata,
+    ) -> Result<MsgTransfer, String> {
+        let port_id = PortId::from_str(&forward.port)
+            .map_err(|e| format!("Invalid forward port: {}", e))?;
+        let channel_id = ChannelId::from_str(&forward.channel)
+            .map_err(|e| format!("Invalid forward channel: {}", e))?;
+        let receiver = Signer::from_str(&forward.receiver)
+            .map_err(|e| format!("Invalid forward receiver: {}", e))?;
+        let timeout = forward
+            .timeout
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_FORWARD_TIMEOUT);
+        let denom = received_denom(packet, &data.token.denom);
+        Ok(MsgTransfer {
+            port_id_on_a: port_id,
+            chan_id_on_a: channel_id,
+            token: Coin {
+                denom: denom.to_string(),
+                amount: data.token.amount.to_string(),
+            },
+            sender: Signer::from_str(&forward_address().to_string())
+                .expect("The forward address should be a signer"),
+            receiver,
+            timeout_height_on_b: TimeoutHeight::Never,
+            timeout_timestamp_on_b: self.timeout_timestamp(timeout)?,
+        })
+    }
+
+    /// Remove the forwarded transfer of the packet, if any, and send its
+    /// refunded tokens back to the original sender
+    fn finish_forward(&mut self, packet: &Packet, is_refunded: bool) {
+        let forward_key = storage::forward_key(
+            &packet.port_id_on_a,
+            &packet.chan_id_on_a,
+            packet.sequence,
+        );
+        let forwarded = match self.ctx.borrow().read(&forward_key) {
+            Ok(Some(value)) => ForwardedTransfer::try_from_slice(&value)
+                .expect("Decoding the forwarded transfer shouldn't fail"),
+            Ok(None) => return,
+            Err(e) => panic!("Reading the forwarded transfer failed: {:?}", e),
+        };
+        self.ctx
+            .borrow_mut()
+            .delete(&forward_key)
+            .expect("Deleting the forwarded transfer shouldn't fail");
+        if !is_refunded {
+            return;
+        }
+        if let Err(e) = self.refund_forward(packet, forwarded) {
+            // the refunded tokens are kept by the forward address
+            self.ctx.borrow().log_string(format!(
+                "Sending back the refunded tokens of the forwarded packet {} \
+                 failed: {}",
+                packet.sequence, e
+            ));
+        }
+    }
+
+    /// Send the refunded tokens of the forwarded packet back to the original
+    /// sender
+    fn refund_forward(
+        &mut self,
+        packet: &Packet,
+        forwarded: ForwardedTransfer,
+    ) -> Result<(), String> {
+        let data = serde_json::from_slice::<PacketData>(&packet.data)
+            .map_err(|e| format!("Decoding the packet data failed: {}", e))?;
+        let msg = MsgTransfer {
+            port_id_on_a: PortId::from_str(&forwarded.port_id)
+                .map_err(|e| e.to_string())?,
+            chan_id_on_a: ChannelId::from_str(&forwarded.channel_id)
+                .map_err(|e| e.to_string())?,
+            token: Coin {
+                denom: data.token.denom.to_string(),
+                amount: data.token.amount.to_string(),
+            },
+            sender: Signer::from_str(&forward_address().to_string())
+                .expect("The forward address should be a signer"),
+            receiver: Signer::from_str(&forwarded.sender)
+                .map_err(|e| e.to_string())?,
+            timeout_height_on_b: TimeoutHeight::Never,
+            timeout_timestamp_on_b: self
+                .timeout_timestamp(DEFAULT_FORWARD_TIMEOUT)?,
+        };
+        send_transfer_validate(self, msg.clone()).map_err(|e| e.to_string())?;
+        send_transfer_execute(self, msg).map_err(|e| e.to_string())
+    }
+
+    /// Get the timestamp of the timeout from the time of the current block
+    fn timeout_timestamp(
+        &self,
+        timeout: Duration,
+    ) -> Result<Timestamp, String> {
+        let ctx = self.ctx.borrow();
+        let height = ctx
+            .get_height()
+            .map_err(|e| format!("Getting the height failed: {:?}", e))?;
+        let header = ctx
+            .get_header(height)
+            .map_err(|e| format!("Getting the header failed: {:?}", e))?
+            .ok_or_else(|| "No header of the current block".to_string())?;
+        let time = TmTime::try_from(header.time)
+            .map_err(|_| "Converting the time failed".to_string())?;
+        (Timestamp::from(time) + timeout).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_metadata() {
+        let data = r#"{
+            "amount": "100",
+            "denom": "transfer/channel-0/uatom",
+            "receiver": "atest1",
+            "sender": "cosmos1",
+            "memo": "{\"forward\":{\"receiver\":\"osmo1\",\"channel\":\"channel-1\"}}"
+        }"#;
+        let forward = forward_metadata(data.as_bytes()).unwrap();
+        assert_eq!(forward.receiver, "osmo1");
+        assert_eq!(forward.port, PORT_ID_STR);
+        assert_eq!(forward.channel, "channel-1");
+        assert_eq!(forward.timeout, None);
+
+        // a memo which isn't for forwarding is ignored
+        let data = r#"{"amount": "100", "memo": "hello"}"#;
+        assert_eq!(forward_metadata(data.as_bytes()), None);
+        let data = r#"{"amount": "100"}"#;
+        assert_eq!(forward_metadata(data.as_bytes()), None);
+    }
+}
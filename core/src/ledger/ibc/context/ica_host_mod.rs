@@ -0,0 +1,759 @@
+//! IBC module for the host of the interchain accounts (ICS-27)
+//!
+//! A controller chain opens an interchain account on this chain with an
+//! ordered channel from one of its controller ports to the host port. The
+//! address of the account is derived from the connection and the controller
+//! port, so that the account is kept when its channel is closed on timeout
+//! and a new one is opened. The controller then sends the txs of the account
+//! over the channel, which can only execute the types of messages that
+//! governance allows for the channel, and only with the account as their
+//! source.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use data_encoding::BASE64;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use super::common::IbcCommonContext;
+use super::transfer_mod::ModuleWrapper;
+use crate::ibc::core::ics04_channel::channel::{Counterparty, Order, State};
+use crate::ibc::core::ics04_channel::error::{ChannelError, PacketError};
+use crate::ibc::core::ics04_channel::handler::ModuleExtras;
+use crate::ibc::core::ics04_channel::msgs::acknowledgement::Acknowledgement;
+use crate::ibc::core::ics04_channel::packet::Packet;
+use crate::ibc::core::ics04_channel::Version;
+use crate::ibc::core::ics24_host::identifier::{
+    ChannelId, ConnectionId, PortChannelId, PortId,
+};
+use crate::ibc::core::ics24_host::path::ChannelEndPath;
+use crate::ibc::core::ics26_routing::context::{Module, ModuleId};
+use crate::ibc::signer::Signer;
+use crate::ibc_proto::google::protobuf::Any;
+use crate::ledger::ibc::storage;
+use crate::ledger::parameters::storage as params_storage;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::Key;
+use crate::types::token;
+use crate::types::transaction::pos::Bond;
+
+/// The module ID of the host of the interchain accounts
+pub const MODULE_ID_STR: &str = "icahost";
+/// The port ID of the host of the interchain accounts
+pub const PORT_ID_STR: &str = "icahost";
+/// The prefix of the port IDs of the controllers of the interchain accounts
+pub const CONTROLLER_PORT_PREFIX: &str = "icacontroller-";
+/// The version of the interchain accounts protocol
+pub const VERSION: &str = "ics27-1";
+/// The encoding of the txs of the interchain accounts
+pub const ENCODING_PROTO3: &str = "proto3";
+/// The type of the txs of the interchain accounts
+pub const TX_TYPE_SDK_MULTI_MSG: &str = "sdk_multi_msg";
+/// The type of the packets that execute a tx
+pub const EXECUTE_TX: &str = "TYPE_EXECUTE_TX";
+/// The type URL of a message to transfer tokens, encoded with Borsh
+pub const MSG_TRANSFER_TYPE_URL: &str = "/namada.token.Transfer";
+/// The type URL of a message to bond tokens, encoded with Borsh
+pub const MSG_BOND_TYPE_URL: &str = "/namada.pos.Bond";
+
+/// The metadata negotiated as the version of the channel of an interchain
+/// account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// The version of the protocol
+    pub version: String,
+    /// The connection of the controller chain
+    pub controller_connection_id: String,
+    /// The connection of this chain
+    pub host_connection_id: String,
+    /// The address of the account, which is set by the host
+    #[serde(default)]
+    pub address: String,
+    /// The encoding of the txs
+    pub encoding: String,
+    /// The type of the txs
+    pub tx_type: String,
+}
+
+/// The data of a packet sent by the controller of an interchain account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchainAccountPacketData {
+    /// The type of the packet
+    #[serde(rename = "type")]
+    pub packet_type: String,
+    /// The tx to execute, encoded with protobuf
+    #[serde(with = "base64_bytes")]
+    pub data: Vec<u8>,
+    /// The memo of the packet
+    #[serde(default)]
+    pub memo: String,
+}
+
+/// The tx of the messages to be executed by an interchain account
+#[derive(Clone, PartialEq, Message)]
+pub struct CosmosTx {
+    /// The messages, executed in order
+    #[prost(message, repeated, tag = "1")]
+    pub messages: Vec<Any>,
+}
+
+/// The acknowledgement of a packet of an interchain account
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum IcaAcknowledgement {
+    Result(String),
+    Error(String),
+}
+
+/// A message that an interchain account is allowed to execute
+enum IcaMessage {
+    Transfer(token::Transfer),
+    Bond(Bond),
+}
+
+/// IBC module for the host of the interchain accounts
+#[derive(Debug)]
+pub struct IcaHostModule<C>
+where
+    C: IbcCommonContext,
+{
+    /// IBC actions
+    pub ctx: Rc<RefCell<C>>,
+}
+
+impl<C> IcaHostModule<C>
+where
+    C: IbcCommonContext,
+{
+    /// Make a new module
+    pub fn new(ctx: Rc<RefCell<C>>) -> Self {
+        Self { ctx }
+    }
+
+    /// Get the module ID
+    pub fn module_id(&self) -> ModuleId {
+        ModuleId::from_str(MODULE_ID_STR).expect("should be parsable")
+    }
+
+    /// Get the port ID of the host
+    pub fn port_id() -> PortId {
+        PortId::from_str(PORT_ID_STR).expect("should be parsable")
+    }
+
+    /// Check the opening of the channel of an interchain account, returning
+    /// the address of the account and the version of the channel
+    fn open_try(
+        &self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(Address, Version), ChannelError> {
+        if order != Order::Ordered {
+            return Err(ica_channel_error(
+                "The channel of an interchain account must be ordered",
+            ));
+        }
+        let controller_port_id = counterparty.port_id();
+        if !controller_port_id
+            .as_str()
+            .starts_with(CONTROLLER_PORT_PREFIX)
+        {
+            return Err(ica_channel_error(format!(
+                "The counterparty port {} isn't a controller port",
+                controller_port_id
+            )));
+        }
+        let conn_id = match connection_hops {
+            [conn_id] => conn_id,
+            _ => {
+                return Err(ica_channel_error(
+                    "The channel of an interchain account must have a single \
+                     connection hop",
+                ));
+            }
+        };
+        let mut metadata: Metadata = serde_json::from_str(
+            &counterparty_version.to_string(),
+        )
+        .map_err(|e| {
+            ica_channel_error(format!(
+                "Decoding the version metadata failed: {}",
+                e
+            ))
+        })?;
+        if metadata.version != VERSION
+            || metadata.encoding != ENCODING_PROTO3
+            || metadata.tx_type != TX_TYPE_SDK_MULTI_MSG
+        {
+            return Err(ica_channel_error(format!(
+                "Unsupported version metadata: {:?}",
+                metadata
+            )));
+        }
+        if metadata.host_connection_id != conn_id.to_string() {
+            return Err(ica_channel_error(format!(
+                "The host connection {} of the metadata isn't the connection \
+                 {} of the channel",
+                metadata.host_connection_id, conn_id
+            )));
+        }
+
+        // only one channel of the account may be active
+        let active_key =
+            storage::ica_active_channel_key(conn_id, controller_port_id);
+        if let Some(channel_id) = self.read_string(&active_key)? {
+            let channel_id = ChannelId::from_str(&channel_id)
+                .map_err(|e| ica_channel_error(e.to_string()))?;
+            let path = ChannelEndPath(Self::port_id(), channel_id.clone());
+            let channel_end = self
+                .ctx
+                .borrow()
+                .channel_end(&path)
+                .map_err(|e| ica_channel_error(e.to_string()))?;
+            if !channel_end.state_matches(&State::Closed) {
+                return Err(ica_channel_error(format!(
+                    "The interchain account already has the active channel {}",
+                    channel_id
+                )));
+            }
+        }
+
+        let account = Address::Internal(InternalAddress::ibc_account_address(
+            conn_id.to_string(),
+            controller_port_id.to_string(),
+        ));
+        metadata.address = account.to_string();
+        let version = serde_json::to_string(&metadata)
+            .expect("Encoding the version metadata shouldn't fail");
+        Ok((account, Version::new(version)))
+    }
+
+    /// Execute the tx of a packet, returning an error without executing any
+    /// of its messages if one of them isn't allowed
+    fn execute_packet(&mut self, packet: &Packet) -> Result<(), String> {
+        let data: InterchainAccountPacketData =
+            serde_json::from_slice(&packet.data).map_err(|e| {
+                format!("Decoding the packet data failed: {}", e)
+            })?;
+        if data.packet_type != EXECUTE_TX {
+            return Err(format!(
+                "Unsupported packet type: {}",
+                data.packet_type
+            ));
+        }
+        let tx = CosmosTx::decode(&data.data[..])
+            .map_err(|e| format!("Decoding the tx failed: {}", e))?;
+
+        let port_channel_id = PortChannelId {
+            port_id: packet.port_id_on_b.clone(),
+            channel_id: packet.chan_id_on_b.clone(),
+        };
+        let account = match self
+            .read_bytes(&storage::ica_account_key(&port_channel_id))?
+        {
+            Some(value) => Address::try_from_slice(&value)
+                .map_err(|e| format!("Decoding the account failed: {}", e))?,
+            None => {
+                return Err(format!(
+                    "No interchain account on the channel {}",
+                    packet.chan_id_on_b
+                ));
+            }
+        };
+        let allowed_key = params_storage::get_ica_host_allow_messages_key(
+            packet.chan_id_on_b.as_str(),
+        );
+        let allowed: Vec<String> = match self.read_bytes(&allowed_key)? {
+            Some(value) => {
+                BorshDeserialize::try_from_slice(&value).map_err(|e| {
+                    format!("Decoding the allowed messages failed: {}", e)
+                })?
+            }
+            None => Vec::new(),
+        };
+
+        let mut messages = Vec::with_capacity(tx.messages.len());
+        // the balances of the account spent by the messages
+        let mut spent: HashMap<Key, token::Amount> = HashMap::new();
+        let native_token =
+            self.ctx.borrow().get_native_token().map_err(|e| {
+                format!("Reading the native token failed: {:?}", e)
+            })?;
+        for msg in tx.messages {
+            if !allowed.contains(&msg.type_url) {
+                return Err(format!(
+                    "The message type {} isn't allowed on the channel {}",
+                    msg.type_url, packet.chan_id_on_b
+                ));
+            }
+            let (message, spent_key, amount) = match msg.type_url.as_str() {
+                MSG_TRANSFER_TYPE_URL => {
+                    let transfer = token::Transfer::try_from_slice(&msg.value)
+                        .map_err(|e| {
+                            format!("Decoding the transfer failed: {}", e)
+                        })?;
+                    if transfer.source != account {
+                        return Err(format!(
+                            "The source of the transfer isn't the account {}",
+                            account
+                        ));
+                    }
+                    if transfer.shielded.is_some() {
+                        return Err(
+                            "A shielded transfer isn't allowed".to_string()
+                        );
+                    }
+                    let key = token::token_balance_key(
+                        &transfer.token,
+                        transfer.sub_prefix.as_ref(),
+                        &account,
+                    );
+                    let amount = transfer.amount;
+                    (IcaMessage::Transfer(transfer), key, amount)
+                }
+                MSG_BOND_TYPE_URL => {
+                    let bond =
+                        Bond::try_from_slice(&msg.value).map_err(|e| {
+                            format!("Decoding the bond failed: {}", e)
+                        })?;
+                    if bond.source.as_ref() != Some(&account) {
+                        return Err(format!(
+                            "The source of the bond isn't the account {}",
+                            account
+                        ));
+                    }
+                    let is_validator = self
+                        .ctx
+                        .borrow()
+                        .is_validator(&bond.validator)
+                        .map_err(|e| {
+                            format!("Reading the validator failed: {:?}", e)
+                        })?;
+                    if !is_validator {
+                        return Err(format!(
+                            "{} isn't a validator",
+                            bond.validator
+                        ));
+                    }
+                    let key = token::balance_key(&native_token, &account);
+                    let amount = bond.amount;
+                    (IcaMessage::Bond(bond), key, amount)
+                }
+                _ => {
+                    return Err(format!(
+                        "Unsupported message type: {}",
+                        msg.type_url
+                    ));
+                }
+            };
+            let balance = match self.read_bytes(&spent_key)? {
+                Some(value) => {
+                    token::Amount::try_from_slice(&value).map_err(|e| {
+                        format!("Decoding the balance failed: {}", e)
+                    })?
+                }
+                None => token::Amount::default(),
+            };
+            add_spent_amount(&mut spent, spent_key, amount, balance, &account)?;
+            messages.push(message);
+        }
+
+        // all the messages are checked, so that they're executed in whole
+        for message in messages {
+            let result = match message {
+                IcaMessage::Transfer(transfer) => {
+                    let src = token::token_balance_key(
+                        &transfer.token,
+                        transfer.sub_prefix.as_ref(),
+                        &transfer.source,
+                    );
+                    let dest = token::token_balance_key(
+                        &transfer.token,
+                        transfer.sub_prefix.as_ref(),
+                        &transfer.target,
+                    );
+                    self.ctx.borrow_mut().transfer_token(
+                        &src,
+                        &dest,
+                        transfer.amount,
+                    )
+                }
+                IcaMessage::Bond(bond) => self.ctx.borrow_mut().bond_tokens(
+                    &account,
+                    &bond.validator,
+                    bond.amount,
+                ),
+            };
+            result.map_err(|e| {
+                format!("Executing the message failed: {:?}", e)
+            })?;
+        }
+        Ok(())
+    }
+
+    fn read_bytes(&self, key: &Key) -> Result<Option<Vec<u8>>, String> {
+        self.ctx
+            .borrow()
+            .read(key)
+            .map_err(|e| format!("Reading the key {} failed: {:?}", key, e))
+    }
+
+    fn read_string(&self, key: &Key) -> Result<Option<String>, ChannelError> {
+        match self.read_bytes(key).map_err(ica_channel_error)? {
+            Some(value) => String::from_utf8(value)
+                .map(Some)
+                .map_err(|e| ica_channel_error(e.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Add the amount spent from a balance of the account by a message to the
+/// total spent by the packet, which may not exceed the balance
+fn add_spent_amount(
+    spent: &mut HashMap<Key, token::Amount>,
+    key: Key,
+    amount: token::Amount,
+    balance: token::Amount,
+    account: &Address,
+) -> Result<(), String> {
+    let total = spent.entry(key).or_default();
+    *total = total.checked_add(amount).ok_or_else(|| {
+        format!("The amount spent by the account {} overflows", account)
+    })?;
+    if *total > balance {
+        return Err(format!(
+            "The account {} has an insufficient balance",
+            account
+        ));
+    }
+    Ok(())
+}
+
+impl<C> ModuleWrapper for IcaHostModule<C>
+where
+    C: IbcCommonContext + Debug,
+{
+    fn as_module(&self) -> &dyn Module {
+        self
+    }
+
+    fn as_module_mut(&mut self) -> &mut dyn Module {
+        self
+    }
+}
+
+impl<C> Module for IcaHostModule<C>
+where
+    C: IbcCommonContext + Debug,
+{
+    #[allow(clippy::too_many_arguments)]
+    fn on_chan_open_init_validate(
+        &self,
+        _order: Order,
+        _connection_hops: &[ConnectionId],
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty: &Counterparty,
+        _version: &Version,
+    ) -> Result<Version, ChannelError> {
+        Err(ica_channel_error(
+            "The channel of an interchain account is opened by its controller",
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_chan_open_init_execute(
+        &mut self,
+        _order: Order,
+        _connection_hops: &[ConnectionId],
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty: &Counterparty,
+        _version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        Err(ica_channel_error(
+            "The channel of an interchain account is opened by its controller",
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_chan_open_try_validate(
+        &self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<Version, ChannelError> {
+        self.open_try(
+            order,
+            connection_hops,
+            counterparty,
+            counterparty_version,
+        )
+        .map(|(_account, version)| version)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_chan_open_try_execute(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        let (account, version) = self.open_try(
+            order,
+            connection_hops,
+            counterparty,
+            counterparty_version,
+        )?;
+        let port_channel_id = PortChannelId {
+            port_id: port_id.clone(),
+            channel_id: channel_id.clone(),
+        };
+        let active_key = storage::ica_active_channel_key(
+            &connection_hops[0],
+            counterparty.port_id(),
+        );
+        let mut ctx = self.ctx.borrow_mut();
+        ctx.write(
+            &storage::ica_account_key(&port_channel_id),
+            account.try_to_vec().expect("encoding shouldn't fail"),
+        )
+        .and_then(|_| {
+            ctx.write(&active_key, channel_id.to_string().into_bytes())
+        })
+        .map_err(|e| {
+            ica_channel_error(format!(
+                "Writing the interchain account failed: {:?}",
+                e
+            ))
+        })?;
+        Ok((ModuleExtras::empty(), version))
+    }
+
+    fn on_chan_open_ack_validate(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty_version: &Version,
+    ) -> Result<(), ChannelError> {
+        Err(ica_channel_error(
+            "The host of the interchain accounts doesn't open channels",
+        ))
+    }
+
+    fn on_chan_open_ack_execute(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty_version: &Version,
+    ) -> Result<ModuleExtras, ChannelError> {
+        Err(ica_channel_error(
+            "The host of the interchain accounts doesn't open channels",
+        ))
+    }
+
+    fn on_chan_open_confirm_validate(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<(), ChannelError> {
+        Ok(())
+    }
+
+    fn on_chan_open_confirm_execute(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        Ok(ModuleExtras::empty())
+    }
+
+    fn on_chan_close_init_validate(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<(), ChannelError> {
+        Err(ica_channel_error(
+            "The channel of an interchain account can't be closed by the host",
+        ))
+    }
+
+    fn on_chan_close_init_execute(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        Err(ica_channel_error(
+            "The channel of an interchain account can't be closed by the host",
+        ))
+    }
+
+    fn on_chan_close_confirm_validate(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<(), ChannelError> {
+        Ok(())
+    }
+
+    fn on_chan_close_confirm_execute(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        Ok(ModuleExtras::empty())
+    }
+
+    fn on_recv_packet_execute(
+        &mut self,
+        packet: &Packet,
+        _relayer: &Signer,
+    ) -> (ModuleExtras, Acknowledgement) {
+        let ack = match self.execute_packet(packet) {
+            Ok(()) => IcaAcknowledgement::Result(BASE64.encode(&[1])),
+            Err(error) => {
+                self.ctx.borrow().log_string(format!(
+                    "Rejected the packet of an interchain account: {}",
+                    error
+                ));
+                IcaAcknowledgement::Error(error)
+            }
+        };
+        let ack = serde_json::to_vec(&ack)
+            .expect("Encoding the acknowledgement shouldn't fail")
+            .try_into()
+            .expect("The acknowledgement shouldn't be empty");
+        (ModuleExtras::empty(), ack)
+    }
+
+    fn on_acknowledgement_packet_validate(
+        &self,
+        _packet: &Packet,
+        _acknowledgement: &Acknowledgement,
+        _relayer: &Signer,
+    ) -> Result<(), PacketError> {
+        Err(ica_packet_error())
+    }
+
+    fn on_acknowledgement_packet_execute(
+        &mut self,
+        _packet: &Packet,
+        _acknowledgement: &Acknowledgement,
+        _relayer: &Signer,
+    ) -> (ModuleExtras, Result<(), PacketError>) {
+        (ModuleExtras::empty(), Err(ica_packet_error()))
+    }
+
+    fn on_timeout_packet_validate(
+        &self,
+        _packet: &Packet,
+        _relayer: &Signer,
+    ) -> Result<(), PacketError> {
+        Err(ica_packet_error())
+    }
+
+    fn on_timeout_packet_execute(
+        &mut self,
+        _packet: &Packet,
+        _relayer: &Signer,
+    ) -> (ModuleExtras, Result<(), PacketError>) {
+        (ModuleExtras::empty(), Err(ica_packet_error()))
+    }
+}
+
+fn ica_channel_error(description: impl ToString) -> ChannelError {
+    ChannelError::AppModule {
+        description: description.to_string(),
+    }
+}
+
+fn ica_packet_error() -> PacketError {
+    PacketError::AppModule {
+        description: "The host of the interchain accounts doesn't send packets"
+            .to_string(),
+    }
+}
+
+/// Serde of the bytes of the packet data in base64, as in the JSON encoding
+/// of protobuf
+mod base64_bytes {
+    use data_encoding::BASE64;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&BASE64.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let encoded = String::deserialize(deserializer)?;
+        BASE64.decode(encoded.as_bytes()).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+
+    /// Test that the messages of a packet can't overflow the amount spent
+    /// from a balance of the account
+    #[test]
+    fn test_add_spent_amount_overflow() {
+        let account = established_address_1();
+        let key = token::balance_key(&established_address_2(), &account);
+        let balance = token::Amount::max();
+        let mut spent = HashMap::new();
+
+        add_spent_amount(&mut spent, key.clone(), balance, balance, &account)
+            .unwrap();
+        let amount = token::Amount::whole(1);
+        let result = add_spent_amount(
+            &mut spent,
+            key.clone(),
+            amount,
+            balance,
+            &account,
+        );
+        assert!(result.is_err());
+        assert_eq!(spent.get(&key), Some(&balance));
+    }
+
+    /// Test that the messages of a packet can't spend more than a balance of
+    /// the account
+    #[test]
+    fn test_add_spent_amount_insufficient_balance() {
+        let account = established_address_1();
+        let key = token::balance_key(&established_address_2(), &account);
+        let balance = token::Amount::whole(10);
+        let amount = token::Amount::whole(6);
+        let mut spent = HashMap::new();
+
+        add_spent_amount(&mut spent, key.clone(), amount, balance, &account)
+            .unwrap();
+        let result =
+            add_spent_amount(&mut spent, key, amount, balance, &account);
+        assert!(result.is_err());
+    }
+}
@@ -2,6 +2,9 @@
 
 pub mod common;
 pub mod execution;
+pub mod fee;
+pub mod forward;
+pub mod ica_host_mod;
 pub mod router;
 pub mod storage;
 pub mod transfer_mod;
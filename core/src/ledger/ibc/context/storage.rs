@@ -6,6 +6,7 @@ pub use ics23::ProofSpec;
 
 use super::super::Error;
 use crate::ledger::storage_api;
+use crate::types::address::Address;
 use crate::types::ibc::IbcEvent;
 use crate::types::storage::{BlockHeight, Header, Key};
 use crate::types::token::Amount;
@@ -59,6 +60,20 @@ pub trait IbcStorageContext {
         amount: Amount,
     ) -> Result<(), Self::Error>;
 
+    /// Get the native token address
+    fn get_native_token(&self) -> Result<Address, Self::Error>;
+
+    /// Check if the address is a validator
+    fn is_validator(&self, address: &Address) -> Result<bool, Self::Error>;
+
+    /// Bond the native tokens of the source to the validator
+    fn bond_tokens(
+        &mut self,
+        source: &Address,
+        validator: &Address,
+        amount: Amount,
+    ) -> Result<(), Self::Error>;
+
     /// Get the current height of this chain
     fn get_height(&self) -> Result<BlockHeight, Self::Error>;
 
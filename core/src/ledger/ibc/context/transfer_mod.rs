@@ -8,16 +8,14 @@ use std::str::FromStr;
 use super::common::IbcCommonContext;
 use crate::ibc::applications::transfer::coin::PrefixedCoin;
 use crate::ibc::applications::transfer::context::{
-    on_acknowledgement_packet_execute, on_acknowledgement_packet_validate,
-    on_chan_close_confirm_execute, on_chan_close_confirm_validate,
-    on_chan_close_init_execute, on_chan_close_init_validate,
-    on_chan_open_ack_execute, on_chan_open_ack_validate,
-    on_chan_open_confirm_execute, on_chan_open_confirm_validate,
-    on_chan_open_init_execute, on_chan_open_init_validate,
-    on_chan_open_try_execute, on_chan_open_try_validate,
-    on_recv_packet_execute, on_timeout_packet_execute,
-    on_timeout_packet_validate, TokenTransferExecutionContext,
-    TokenTransferValidationContext,
+    on_acknowledgement_packet_validate, on_chan_close_confirm_execute,
+    on_chan_close_confirm_validate, on_chan_close_init_execute,
+    on_chan_close_init_validate, on_chan_open_ack_execute,
+    on_chan_open_ack_validate, on_chan_open_confirm_execute,
+    on_chan_open_confirm_validate, on_chan_open_init_execute,
+    on_chan_open_init_validate, on_chan_open_try_execute,
+    on_chan_open_try_validate, on_timeout_packet_validate,
+    TokenTransferExecutionContext, TokenTransferValidationContext,
 };
 use crate::ibc::applications::transfer::denom::PrefixedDenom;
 use crate::ibc::applications::transfer::error::TokenTransferError;
@@ -281,7 +279,7 @@ where
         packet: &Packet,
         _relayer: &Signer,
     ) -> (ModuleExtras, Acknowledgement) {
-        on_recv_packet_execute(self, packet)
+        self.recv_packet_execute(packet)
     }
 
     fn on_acknowledgement_packet_validate(
@@ -305,13 +303,15 @@ where
         acknowledgement: &Acknowledgement,
         relayer: &Signer,
     ) -> (ModuleExtras, Result<(), PacketError>) {
-        let (extras, result) = on_acknowledgement_packet_execute(
-            self,
+        let (extras, result) = self.acknowledgement_packet_execute(
             packet,
             acknowledgement,
             relayer,
         );
-        (extras, result.map_err(into_packet_error))
+        if result.is_ok() {
+            self.pay_packet_fee(packet, relayer, false);
+        }
+        (extras, result)
     }
 
     fn on_timeout_packet_validate(
@@ -328,8 +328,11 @@ where
         packet: &Packet,
         relayer: &Signer,
     ) -> (ModuleExtras, Result<(), PacketError>) {
-        let (extras, result) = on_timeout_packet_execute(self, packet, relayer);
-        (extras, result.map_err(into_packet_error))
+        let (extras, result) = self.timeout_packet_execute(packet, relayer);
+        if result.is_ok() {
+            self.pay_packet_fee(packet, relayer, true);
+        }
+        (extras, result)
     }
 }
 
@@ -604,7 +607,7 @@ fn get_token_amount(
     coin: &PrefixedCoin,
 ) -> Result<(Address, token::Amount), TokenTransferError> {
     let token =
-        Address::decode(coin.denom.base_denom.as_str()).map_err(|_| {
+        storage::token(coin.denom.base_denom.as_str()).map_err(|_| {
             TokenTransferError::InvalidCoin {
                 coin: coin.denom.base_denom.to_string(),
             }
@@ -625,7 +628,7 @@ fn into_channel_error(error: TokenTransferError) -> ChannelError {
     }
 }
 
-fn into_packet_error(error: TokenTransferError) -> PacketError {
+pub(super) fn into_packet_error(error: TokenTransferError) -> PacketError {
     PacketError::AppModule {
         description: error.to_string(),
     }
@@ -9,7 +9,10 @@ use std::fmt::Debug;
 use std::rc::Rc;
 use std::time::Duration;
 
+use borsh::BorshDeserialize;
 pub use context::common::IbcCommonContext;
+use context::fee::{MsgTransferWithFee, MSG_TRANSFER_WITH_FEE_TYPE_URL};
+pub use context::ica_host_mod::IcaHostModule;
 pub use context::storage::{IbcStorageContext, ProofSpec};
 pub use context::transfer_mod::{ModuleWrapper, TransferModule};
 use prost::Message;
@@ -28,11 +31,13 @@ use crate::ibc::core::context::Router;
 use crate::ibc::core::ics04_channel::msgs::PacketMsg;
 use crate::ibc::core::ics23_commitment::specs::ProofSpecs;
 use crate::ibc::core::ics24_host::identifier::{ChainId as IbcChainId, PortId};
+use crate::ibc::core::ics24_host::path::SeqSendPath;
 use crate::ibc::core::ics26_routing::context::{Module, ModuleId};
 use crate::ibc::core::ics26_routing::error::RouterError;
 use crate::ibc::core::ics26_routing::msgs::MsgEnvelope;
 use crate::ibc::core::{execute, validate};
 use crate::ibc_proto::google::protobuf::Any;
+use crate::types::address::Address;
 use crate::types::chain::ChainId;
 
 #[allow(missing_docs)]
@@ -56,6 +61,8 @@ pub enum Error {
     Denom(String),
     #[error("Invalid chain ID: {0}")]
     ChainId(ChainId),
+    #[error("Relayer fee error: {0}")]
+    Fee(String),
 }
 
 /// IBC actions to handle IBC operations
@@ -99,6 +106,16 @@ where
         self.ports.insert(PortId::transfer(), module_id);
     }
 
+    /// Add the host of the interchain accounts route
+    pub fn add_ica_host_route(
+        &mut self,
+        module_id: ModuleId,
+        module: impl ModuleWrapper + 'a,
+    ) {
+        self.modules.insert(module_id.clone(), Rc::new(module));
+        self.ports.insert(IcaHostModule::<C>::port_id(), module_id);
+    }
+
     fn get_route_by_port(&self, port_id: &PortId) -> Option<&dyn Module> {
         self.lookup_module_by_port(port_id)
             .and_then(|id| self.get_route(&id))
@@ -119,17 +136,41 @@ where
             MSG_TRANSFER_TYPE_URL => {
                 let msg =
                     MsgTransfer::try_from(msg).map_err(Error::TokenTransfer)?;
-                let port_id = msg.port_id_on_a.clone();
-                match self.get_route_mut_by_port(&port_id) {
-                    Some(_module) => {
-                        let mut module = TransferModule::new(self.ctx.clone());
-                        // restore the denom if it is hashed
-                        let msg = self.restore_denom(msg)?;
-                        send_transfer_execute(&mut module, msg)
-                            .map_err(Error::TokenTransfer)
-                    }
-                    None => Err(Error::NoModule),
-                }
+                self.execute_transfer(msg)
+            }
+            MSG_TRANSFER_WITH_FEE_TYPE_URL => {
+                let msg = decode_transfer_with_fee(&msg.value)?;
+                let transfer = decode_transfer(&msg.transfer)?;
+                let payer = Address::try_from(transfer.sender.clone())
+                    .map_err(|e| {
+                        Error::Fee(format!("Invalid payer of the fee: {}", e))
+                    })?;
+                let port_id = transfer.port_id_on_a.clone();
+                let channel_id = transfer.chan_id_on_a.clone();
+                // the fee is for the packet sent with the transfer
+                let sequence = self
+                    .ctx
+                    .borrow()
+                    .get_next_sequence_send(&SeqSendPath(
+                        port_id.clone(),
+                        channel_id.clone(),
+                    ))
+                    .map_err(|e| {
+                        Error::Fee(format!(
+                            "Reading the sequence failed: {}",
+                            e
+                        ))
+                    })?;
+                self.execute_transfer(transfer)?;
+                TransferModule::new(self.ctx.clone())
+                    .escrow_packet_fee(
+                        &port_id,
+                        &channel_id,
+                        sequence,
+                        payer,
+                        msg.fee,
+                    )
+                    .map_err(Error::Fee)
             }
             _ => {
                 execute(self, msg.clone()).map_err(Error::Execution)?;
@@ -140,6 +181,21 @@ where
         }
     }
 
+    /// Execute the token transfer
+    fn execute_transfer(&mut self, msg: MsgTransfer) -> Result<(), Error> {
+        let port_id = msg.port_id_on_a.clone();
+        match self.get_route_mut_by_port(&port_id) {
+            Some(_module) => {
+                let mut module = TransferModule::new(self.ctx.clone());
+                // restore the denom if it is hashed
+                let msg = self.restore_denom(msg)?;
+                send_transfer_execute(&mut module, msg)
+                    .map_err(Error::TokenTransfer)
+            }
+            None => Err(Error::NoModule),
+        }
+    }
+
     /// Restore the denom when it is hashed, i.e. the denom is `ibc/{hash}`.
     fn restore_denom(&self, msg: MsgTransfer) -> Result<MsgTransfer, Error> {
         let mut msg = msg;
@@ -209,21 +265,43 @@ where
             MSG_TRANSFER_TYPE_URL => {
                 let msg =
                     MsgTransfer::try_from(msg).map_err(Error::TokenTransfer)?;
-                let port_id = msg.port_id_on_a.clone();
-                match self.get_route_by_port(&port_id) {
-                    Some(_module) => {
-                        let module = TransferModule::new(self.ctx.clone());
-                        // restore the denom if it is hashed
-                        let msg = self.restore_denom(msg)?;
-                        send_transfer_validate(&module, msg)
-                            .map_err(Error::TokenTransfer)
-                    }
-                    None => Err(Error::NoModule),
-                }
+                self.validate_transfer(msg)
+            }
+            // the escrow of the fee is checked with the changed balances
+            MSG_TRANSFER_WITH_FEE_TYPE_URL => {
+                let msg = decode_transfer_with_fee(&msg.value)?;
+                let transfer = decode_transfer(&msg.transfer)?;
+                self.validate_transfer(transfer)
             }
             _ => validate(self, msg).map_err(Error::Validation),
         }
     }
+
+    /// Validate the token transfer
+    fn validate_transfer(&self, msg: MsgTransfer) -> Result<(), Error> {
+        let port_id = msg.port_id_on_a.clone();
+        match self.get_route_by_port(&port_id) {
+            Some(_module) => {
+                let module = TransferModule::new(self.ctx.clone());
+                // restore the denom if it is hashed
+                let msg = self.restore_denom(msg)?;
+                send_transfer_validate(&module, msg)
+                    .map_err(Error::TokenTransfer)
+            }
+            None => Err(Error::NoModule),
+        }
+    }
+}
+
+fn decode_transfer_with_fee(value: &[u8]) -> Result<MsgTransferWithFee, Error> {
+    MsgTransferWithFee::try_from_slice(value).map_err(|e| {
+        Error::Fee(format!("Decoding the transfer with fee failed: {}", e))
+    })
+}
+
+fn decode_transfer(transfer: &[u8]) -> Result<MsgTransfer, Error> {
+    let msg = Any::decode(transfer).map_err(Error::DecodingData)?;
+    MsgTransfer::try_from(msg).map_err(Error::TokenTransfer)
 }
 
 #[derive(Debug, Default)]
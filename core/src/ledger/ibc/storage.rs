@@ -18,11 +18,16 @@ use crate::ibc::core::ics24_host::path::{
 use crate::ibc::core::ics24_host::Path;
 use crate::types::address::{Address, InternalAddress, HASH_HEX_LEN};
 use crate::types::storage::{self, DbKeySeg, Key, KeySeg};
+use crate::types::token;
 
 const CLIENTS_COUNTER: &str = "clients/counter";
 const CONNECTIONS_COUNTER: &str = "connections/counter";
 const CHANNELS_COUNTER: &str = "channelEnds/counter";
 const DENOM: &str = "denom";
+const ICA_ACCOUNTS: &str = "ica/accounts";
+const ICA_ACTIVE_CHANNELS: &str = "ica/activeChannels";
+const FORWARDS: &str = "forwards";
+const FEES: &str = "fees";
 /// Key segment for a multitoken related to IBC
 pub const MULTITOKEN_STORAGE_KEY: &str = "ibc";
 
@@ -458,17 +463,75 @@ pub fn ibc_denom_key(token_hash: impl AsRef<str>) -> Key {
     ibc_key(path).expect("Creating a key for the denom key shouldn't fail")
 }
 
-/// Token address from the denom string
+/// The storage key of the interchain account of a channel of the host port
+pub fn ica_account_key(port_channel_id: &PortChannelId) -> Key {
+    let path = format!(
+        "{}/ports/{}/channels/{}",
+        ICA_ACCOUNTS, port_channel_id.port_id, port_channel_id.channel_id
+    );
+    ibc_key(path)
+        .expect("Creating a key for the interchain account shouldn't fail")
+}
+
+/// The storage key of the host channel of the interchain account that the
+/// controller port owns over the connection
+pub fn ica_active_channel_key(
+    conn_id: &ConnectionId,
+    controller_port_id: &PortId,
+) -> Key {
+    let path = format!(
+        "{}/connections/{}/ports/{}",
+        ICA_ACTIVE_CHANNELS, conn_id, controller_port_id
+    );
+    ibc_key(path).expect(
+        "Creating a key for the interchain account channel shouldn't fail",
+    )
+}
+
+/// The storage key of the transfer that a packet sent to the port and the
+/// channel with the sequence forwards
+pub fn forward_key(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    sequence: Sequence,
+) -> Key {
+    let path = format!(
+        "{}/ports/{}/channels/{}/sequences/{}",
+        FORWARDS, port_id, channel_id, sequence
+    );
+    ibc_key(path).expect("Creating a key for the forward shouldn't fail")
+}
+
+/// The storage key of the fee escrowed for the relayers of a packet sent to
+/// the port and the channel with the sequence
+pub fn fee_key(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    sequence: Sequence,
+) -> Key {
+    let path = format!(
+        "{}/ports/{}/channels/{}/sequences/{}",
+        FEES, port_id, channel_id, sequence
+    );
+    ibc_key(path).expect("Creating a key for the fee shouldn't fail")
+}
+
+/// Token address from the denom string. A base denom which isn't a Namada
+/// address is a token originating from another chain, e.g. `uatom`, whose
+/// address is the IBC token address of the hash of the base denom.
 pub fn token(denom: impl AsRef<str>) -> Result<Address> {
-    let token_str = denom.as_ref().split('/').last().ok_or_else(|| {
-        Error::Denom(format!("No token was specified: {}", denom.as_ref()))
-    })?;
-    Address::decode(token_str).map_err(|e| {
-        Error::Denom(format!(
-            "Invalid token address: token {}, error {}",
-            token_str, e
-        ))
-    })
+    let token_str = match denom.as_ref().split('/').last() {
+        Some(token_str) if !token_str.is_empty() => token_str,
+        _ => {
+            return Err(Error::Denom(format!(
+                "No token was specified: {}",
+                denom.as_ref()
+            )));
+        }
+    };
+    Ok(Address::decode(token_str).unwrap_or_else(|_| {
+        Address::Internal(InternalAddress::IbcToken(calc_hash(token_str)))
+    }))
 }
 
 /// Get the hash of IBC token address from the denom string
@@ -522,6 +585,23 @@ pub fn is_ibc_key(key: &Key) -> bool {
              DbKeySeg::AddressSeg(addr) if *addr == Address::Internal(InternalAddress::Ibc))
 }
 
+/// Returns true if the given key is a balance key of an account operated by
+/// IBC, i.e. an interchain account, the account of the forwarded transfers or
+/// the escrow of the relayer fees
+pub fn is_ibc_account_balance_key(key: &Key) -> bool {
+    matches!(
+        token::is_any_token_or_multitoken_balance_key(key),
+        Some((
+            _,
+            Address::Internal(
+                InternalAddress::IbcAccount(_)
+                    | InternalAddress::IbcForward
+                    | InternalAddress::IbcFeeEscrow
+            )
+        ))
+    )
+}
+
 /// Returns true if the sub prefix is for IBC
 pub fn is_ibc_sub_prefix(sub_prefix: &Key) -> bool {
     matches!(&sub_prefix.segments[0],
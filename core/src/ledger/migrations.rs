@@ -0,0 +1,337 @@
+//! Migrations of the storage layout, which are run when a block is finalized.
+//!
+//! When a protocol upgrade changes the layout of some storage keys, it ships
+//! with a migration step that rewrites the affected keys. Every step is
+//! applied at the start of the block at its agreed height, so that all the
+//! nodes write the migrated keys in the same block. The steps are ordered by
+//! the version of the layout that they migrate to and the version of the
+//! storage is recorded under the parameters' address, such that each step is
+//! applied only once. The steps are registered by the ledger, which knows the
+//! layouts of all the subsystems, while the core of the protocol provides the
+//! migrations of its own layouts.
+
+use borsh::BorshSerialize;
+
+use crate::ledger::storage_api::{
+    self, token, ResultExt, StorageRead, StorageWrite,
+};
+use crate::ledger::{governance, parameters};
+use crate::types::address::{Address, InternalAddress};
+use crate::types::eth_bridge_pool::{
+    is_pending_transfer_key, pending_transfer_key, PendingTransfer,
+};
+use crate::types::storage::{BlockHeight, DbKeySeg, Key, KeySeg};
+use crate::types::token::{
+    is_any_token_or_multitoken_balance_key, is_total_supply_key,
+};
+
+/// The version of the storage layout before any migration
+pub const INITIAL_VERSION: u64 = 0;
+
+/// The address under which the version of the storage layout is recorded
+const ADDRESS: Address = Address::Internal(InternalAddress::Parameters);
+
+const VERSION_KEY_SEGMENT: &str = "storage_version";
+
+/// A step of the migration of the storage layout
+pub struct Migration<S> {
+    /// The version of the storage layout after the step
+    pub version: u64,
+    /// The height of the first block in which the step is applied
+    pub height: BlockHeight,
+    /// The description of the step, which is logged when it's applied
+    pub description: &'static str,
+    /// Rewrite the keys of the previous layout in the storage
+    pub apply: fn(&mut S) -> storage_api::Result<()>,
+}
+
+/// Migrate the amounts stored in the layouts of the core of the protocol
+/// from the legacy encoding of 64-bit amounts: the balances and the total
+/// supplies of all the tokens, the funds of the governance proposals, the
+/// amounts of the parameters and the transfers pending in the bridge pool.
+/// It's idempotent, and returns the number of migrated values.
+pub fn migrate_legacy_amounts<S>(storage: &mut S) -> storage_api::Result<u64>
+where
+    S: StorageRead + StorageWrite,
+{
+    let mut amount_keys = vec![];
+    let mut pending_transfer_keys = vec![];
+    for entry in storage_api::iter_prefix_bytes(storage, &Key::default())? {
+        let (key, _value) = entry?;
+        if is_amount_key(&key) {
+            amount_keys.push(key);
+        } else if is_pending_transfer_key(&key).is_some() {
+            pending_transfer_keys.push(key);
+        }
+    }
+    let mut migrated = 0;
+    for key in amount_keys {
+        if token::migrate_legacy_amount(storage, &key)? {
+            migrated += 1;
+        }
+    }
+    for key in pending_transfer_keys {
+        if migrate_legacy_pending_transfer(storage, &key)? {
+            migrated += 1;
+        }
+    }
+    Ok(migrated)
+}
+
+/// Check if the given key is the key of an amount in the layouts of the core
+/// of the protocol
+fn is_amount_key(key: &Key) -> bool {
+    let is_supply_key = match key.segments.first() {
+        Some(DbKeySeg::AddressSeg(token)) => is_total_supply_key(key, token),
+        _ => false,
+    };
+    is_supply_key
+        || is_any_token_or_multitoken_balance_key(key).is_some()
+        || governance::storage::is_balance_key(key)
+        || governance::storage::is_min_proposal_fund_key(key)
+        || parameters::storage::is_min_gas_price_key(key)
+        || key == &parameters::storage::get_wrapper_tx_fees_key()
+}
+
+/// Migrate the transfer pending in the bridge pool at the given key from its
+/// encoding with the legacy 64-bit amounts, if it isn't migrated yet. The
+/// transfer is moved to the key of the hash of its new encoding. Returns
+/// whether it was migrated.
+fn migrate_legacy_pending_transfer<S>(
+    storage: &mut S,
+    key: &Key,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead + StorageWrite,
+{
+    let bytes = match storage.read_bytes(key)? {
+        Some(bytes) => bytes,
+        None => return Ok(false),
+    };
+    let transfer =
+        PendingTransfer::try_from_slice_compat(&bytes).into_storage_result()?;
+    if transfer.try_to_vec().into_storage_result()? == bytes {
+        return Ok(false);
+    }
+    storage.delete(key)?;
+    storage.write(&pending_transfer_key(&transfer.hash()), transfer)?;
+    Ok(true)
+}
+
+/// Get the key of the version of the storage layout
+pub fn version_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&VERSION_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Read the version of the storage layout
+pub fn read_version<S>(storage: &S) -> storage_api::Result<u64>
+where
+    S: StorageRead,
+{
+    Ok(storage.read(&version_key())?.unwrap_or(INITIAL_VERSION))
+}
+
+/// The version of the layout after all the given migrations
+pub fn latest_version<S>(migrations: &[Migration<S>]) -> u64 {
+    migrations
+        .last()
+        .map(|migration| migration.version)
+        .unwrap_or(INITIAL_VERSION)
+}
+
+/// Record the latest version of the layout in a new storage, to which no
+/// migration applies
+pub fn init_version<S>(
+    storage: &mut S,
+    migrations: &[Migration<S>],
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    storage.write(&version_key(), latest_version(migrations))
+}
+
+/// Apply the migrations to versions greater than the version of the storage
+/// whose heights have been reached at the given block height, in order, and
+/// record the new version. Returns the versions of the applied migrations.
+pub fn run<S>(
+    storage: &mut S,
+    migrations: &[Migration<S>],
+    height: BlockHeight,
+) -> storage_api::Result<Vec<u64>>
+where
+    S: StorageRead + StorageWrite,
+{
+    if migrations.windows(2).any(|pair| {
+        pair[0].version >= pair[1].version || pair[0].height > pair[1].height
+    }) {
+        return Err(storage_api::Error::new_const(
+            "The migrations must be ordered by increasing versions and \
+             heights",
+        ));
+    }
+    let mut version = read_version(storage)?;
+    let mut applied = vec![];
+    for migration in migrations {
+        if migration.version <= version {
+            continue;
+        }
+        if migration.height > height {
+            break;
+        }
+        tracing::info!(
+            "Migrating the storage from version {} to {}: {}",
+            version,
+            migration.version,
+            migration.description
+        );
+        (migration.apply)(storage)?;
+        version = migration.version;
+        storage.write(&version_key(), version)?;
+        applied.push(version);
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshDeserialize;
+
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::address::{self, testing::established_address_1};
+    use crate::types::ethereum_events::EthAddress;
+    use crate::types::hash::Hash;
+    use crate::types::token::{
+        balance_key, token_balance_key, total_supply_key, Amount,
+    };
+
+    fn counter_key() -> Key {
+        Key::parse("counter").unwrap()
+    }
+
+    /// Increment a counter, to check how many times a migration is applied
+    fn increment(storage: &mut TestWlStorage) -> storage_api::Result<()> {
+        let counter: u64 = storage.read(&counter_key())?.unwrap_or_default();
+        storage.write(&counter_key(), counter + 1)
+    }
+
+    fn migration(version: u64, height: u64) -> Migration<TestWlStorage> {
+        Migration {
+            version,
+            height: BlockHeight(height),
+            description: "increment the counter",
+            apply: increment,
+        }
+    }
+
+    /// Test that the migrations are applied once, in order, from the version
+    /// of the storage
+    #[test]
+    fn test_run_migrations() {
+        let mut storage = TestWlStorage::default();
+        assert_eq!(read_version(&storage).unwrap(), INITIAL_VERSION);
+
+        let height = BlockHeight(10);
+        let migrations = vec![migration(1, 5), migration(2, 10)];
+        let applied = run(&mut storage, &migrations[..1], height).unwrap();
+        assert_eq!(applied, vec![1]);
+        assert_eq!(run(&mut storage, &migrations, height).unwrap(), vec![2]);
+        // Running the migrations again is a no-op
+        assert!(run(&mut storage, &migrations, height).unwrap().is_empty());
+        assert_eq!(read_version(&storage).unwrap(), 2);
+        let counter: Option<u64> = storage.read(&counter_key()).unwrap();
+        assert_eq!(counter, Some(2));
+
+        // A new storage starts at the latest version
+        let mut storage = TestWlStorage::default();
+        init_version(&mut storage, &migrations).unwrap();
+        assert!(run(&mut storage, &migrations, height).unwrap().is_empty());
+
+        // The migrations must be ordered
+        let unordered = vec![migration(2, 5), migration(1, 10)];
+        assert!(run(&mut storage, &unordered, height).is_err());
+        let unordered = vec![migration(1, 10), migration(2, 5)];
+        assert!(run(&mut storage, &unordered, height).is_err());
+    }
+
+    /// Test that a migration is only applied once the block height has
+    /// reached its agreed height
+    #[test]
+    fn test_run_migrations_at_height() {
+        let mut storage = TestWlStorage::default();
+        let migrations = vec![migration(1, 5), migration(2, 10)];
+
+        let applied = run(&mut storage, &migrations, BlockHeight(4)).unwrap();
+        assert!(applied.is_empty());
+        assert_eq!(read_version(&storage).unwrap(), INITIAL_VERSION);
+
+        // The later step waits for its own height
+        let applied = run(&mut storage, &migrations, BlockHeight(5)).unwrap();
+        assert_eq!(applied, vec![1]);
+        let applied = run(&mut storage, &migrations, BlockHeight(9)).unwrap();
+        assert!(applied.is_empty());
+        let applied = run(&mut storage, &migrations, BlockHeight(10)).unwrap();
+        assert_eq!(applied, vec![2]);
+        assert_eq!(read_version(&storage).unwrap(), 2);
+    }
+
+    /// Test that the amounts of the core of the protocol are re-encoded from
+    /// the legacy encoding
+    #[test]
+    fn test_migrate_legacy_amounts() {
+        let mut storage = TestWlStorage::default();
+        let owner = established_address_1();
+        let legacy = 42_u64.try_to_vec().unwrap();
+        let sub_prefix = Key::parse("sub").unwrap();
+        let amount_keys = [
+            balance_key(&storage.storage.native_token.clone(), &owner),
+            balance_key(&address::btc(), &owner),
+            token_balance_key(&address::eth(), Some(&sub_prefix), &owner),
+            total_supply_key(&address::btc()),
+            governance::storage::get_funds_key(0),
+            parameters::storage::get_min_gas_price_key(),
+        ];
+        for key in &amount_keys {
+            storage.write_bytes(key, &legacy).unwrap();
+        }
+        // A transfer pending in the bridge pool, keyed by the hash of its
+        // legacy encoding
+        let legacy_transfer = (
+            EthAddress([1; 20]),
+            EthAddress([2; 20]),
+            owner.clone(),
+            42_u64,
+            1_u64,
+            owner.clone(),
+        )
+            .try_to_vec()
+            .unwrap();
+        let legacy_transfer_key =
+            pending_transfer_key(&Hash::sha256(&legacy_transfer));
+        storage
+            .write_bytes(&legacy_transfer_key, &legacy_transfer)
+            .unwrap();
+
+        let migrated = migrate_legacy_amounts(&mut storage).unwrap();
+        assert_eq!(migrated, amount_keys.len() as u64 + 1);
+        for key in &amount_keys {
+            let bytes = storage.read_bytes(key).unwrap().unwrap();
+            let amount = Amount::try_from_slice(&bytes).unwrap();
+            assert_eq!(amount, Amount::from(42));
+        }
+        let transfer =
+            PendingTransfer::try_from_slice_compat(&legacy_transfer).unwrap();
+        assert_eq!(transfer.transfer.amount, Amount::from(42));
+        assert!(!storage.has_key(&legacy_transfer_key).unwrap());
+        let stored: Option<PendingTransfer> = storage
+            .read(&pending_transfer_key(&transfer.hash()))
+            .unwrap();
+        assert_eq!(stored, Some(transfer));
+
+        // Migrating again is a no-op
+        assert_eq!(migrate_legacy_amounts(&mut storage).unwrap(), 0);
+    }
+}
@@ -1,9 +1,12 @@
 //! The ledger modules
 
+pub mod epoch_clock;
 pub mod gas;
 pub mod governance;
 #[cfg(any(feature = "abciplus", feature = "abcipp"))]
 pub mod ibc;
+pub mod migrations;
+pub mod oracle;
 pub mod parameters;
 pub mod replay_protection;
 pub mod slash_fund;
@@ -0,0 +1,256 @@
+//! The native oracle of the prices of the fee tokens.
+//!
+//! With ABCI++, the validators attach their [`SignedOraclePrices`] to their
+//! precommits in `ExtendVote`. The proposer of the next block aggregates the
+//! extensions of the last commit in a protocol tx, which is only valid if the
+//! validators that signed them hold more than 2/3 of the voting power. When
+//! the tx is applied, the stake-weighted median of the reported prices of each
+//! token is written to protocol storage, under the parameters address, which
+//! the txs can't write to without a governance proposal.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::ledger::parameters::storage::ADDRESS;
+use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
+use crate::types::address::Address;
+use crate::types::key::signer::{Signer, SignerError};
+use crate::types::key::{common, SigScheme, VerifySigError};
+use crate::types::storage::{BlockHeight, DbKeySeg, Key, KeySeg};
+use crate::types::token::Amount;
+
+const PRICE_KEY_SEGMENT: &str = "oracle_price";
+
+/// The prices of the fee tokens reported by a validator, as of a block
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct OraclePrices {
+    /// The address of the reporting validator
+    pub validator: Address,
+    /// The height of the block whose precommit carries the report
+    pub block_height: BlockHeight,
+    /// The prices of the tokens
+    pub prices: BTreeMap<Address, Decimal>,
+}
+
+/// The [`OraclePrices`] signed with the protocol key of their validator
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct SignedOraclePrices {
+    /// The reported prices
+    pub data: OraclePrices,
+    /// The signature of the prices
+    pub sig: common::Signature,
+}
+
+impl SignedOraclePrices {
+    /// Sign the prices with the protocol key of their validator
+    pub fn new(
+        data: OraclePrices,
+        signer: &dyn Signer,
+    ) -> Result<Self, SignerError> {
+        let bytes = data.try_to_vec().expect("Encoding prices shouldn't fail");
+        let sig = signer.sign(&bytes)?;
+        Ok(Self { data, sig })
+    }
+
+    /// Check that the prices were signed with the given protocol key
+    pub fn verify(&self, pk: &common::PublicKey) -> Result<(), VerifySigError> {
+        let bytes = self
+            .data
+            .try_to_vec()
+            .expect("Encoding prices shouldn't fail");
+        common::SigScheme::verify_signature_raw(pk, &bytes, &self.sig)
+    }
+}
+
+/// Check if the given stake is more than 2/3 of the total stake
+pub fn is_quorum(stake: Amount, total_stake: Amount) -> bool {
+    match (stake.checked_mul(3), total_stake.checked_mul(2)) {
+        (Some(stake), Some(total_stake)) => stake > total_stake,
+        _ => false,
+    }
+}
+
+/// Aggregate the prices reported by the validators with the given stakes.
+/// The price of a token is the stake-weighted median of its reported prices,
+/// and a token is only priced if the validators reporting it hold more than
+/// 2/3 of the total stake.
+pub fn aggregate<'a>(
+    reports: impl IntoIterator<Item = (Amount, &'a OraclePrices)>,
+    total_stake: Amount,
+) -> BTreeMap<Address, Decimal> {
+    let mut by_token: BTreeMap<&Address, Vec<(Decimal, Amount)>> =
+        BTreeMap::new();
+    for (stake, report) in reports {
+        for (token, price) in &report.prices {
+            by_token.entry(token).or_default().push((*price, stake));
+        }
+    }
+    by_token
+        .into_iter()
+        .filter_map(|(token, mut prices)| {
+            let reported: Amount = prices.iter().map(|(_, stake)| *stake).sum();
+            if !is_quorum(reported, total_stake) {
+                return None;
+            }
+            prices.sort();
+            let mut cumulated = Amount::default();
+            prices
+                .into_iter()
+                .find_map(|(price, stake)| {
+                    cumulated += stake;
+                    (cumulated.checked_mul(2)? >= reported).then_some(price)
+                })
+                .map(|price| (token.clone(), price))
+        })
+        .collect()
+}
+
+/// Get the key of the aggregated price of a token
+pub fn price_key(token: &Address) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PRICE_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+        .push(token)
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Check if a key is the key of the aggregated price of a token. Returns the
+/// address of the token if so.
+pub fn is_price_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(token),
+        ] if addr == &ADDRESS && prefix == PRICE_KEY_SEGMENT => Some(token),
+        _ => None,
+    }
+}
+
+/// Read the last aggregated price of a token, if any
+pub fn read_price<S>(
+    storage: &S,
+    token: &Address,
+) -> storage_api::Result<Option<Decimal>>
+where
+    S: StorageRead,
+{
+    storage.read(&price_key(token))
+}
+
+/// Write the aggregated prices of the tokens
+pub fn write_prices<S>(
+    storage: &mut S,
+    prices: &BTreeMap<Address, Decimal>,
+) -> storage_api::Result<()>
+where
+    S: StorageWrite,
+{
+    for (token, price) in prices {
+        storage.write(&price_key(token), price)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+    use crate::types::address::{btc, nam};
+    use crate::types::key::testing::keypair_1;
+    use crate::types::key::RefTo;
+
+    fn report(prices: &[(Address, Decimal)]) -> OraclePrices {
+        OraclePrices {
+            validator: established_address_1(),
+            block_height: BlockHeight(1),
+            prices: prices.iter().cloned().collect(),
+        }
+    }
+
+    /// Test that the signatures of the prices are checked
+    #[test]
+    fn test_signed_prices() {
+        let keypair = keypair_1();
+        let mut signed =
+            SignedOraclePrices::new(report(&[(nam(), dec!(1.5))]), &keypair)
+                .unwrap();
+        assert!(signed.verify(&keypair.ref_to()).is_ok());
+
+        signed.data.block_height = BlockHeight(2);
+        assert!(signed.verify(&keypair.ref_to()).is_err());
+    }
+
+    /// Test that the prices are the stake-weighted medians of the prices of
+    /// the tokens reported by more than 2/3 of the stake
+    #[test]
+    fn test_aggregate() {
+        let reports = [
+            (
+                Amount::whole(50),
+                report(&[(nam(), dec!(1)), (btc(), dec!(30000))]),
+            ),
+            (Amount::whole(30), report(&[(nam(), dec!(3))])),
+            (Amount::whole(20), report(&[(nam(), dec!(100))])),
+        ];
+        let prices = aggregate(
+            reports.iter().map(|(stake, report)| (*stake, report)),
+            Amount::whole(100),
+        );
+        // the btc price is only reported by half of the stake
+        assert_eq!(prices, BTreeMap::from([(nam(), dec!(1))]));
+
+        let prices = aggregate(
+            reports[1..].iter().map(|(stake, report)| (*stake, report)),
+            Amount::whole(60),
+        );
+        assert_eq!(prices, BTreeMap::from([(nam(), dec!(3))]));
+
+        // 2/3 of the stake is not a quorum
+        let prices = aggregate(
+            reports[..1].iter().map(|(stake, report)| (*stake, report)),
+            Amount::whole(75),
+        );
+        assert!(prices.is_empty());
+    }
+
+    /// Test the keys of the aggregated prices
+    #[test]
+    fn test_price_key() {
+        let token = established_address_2();
+        assert_eq!(is_price_key(&price_key(&token)), Some(&token));
+        assert_eq!(
+            is_price_key(
+                &Key::from(ADDRESS.to_db_key())
+                    .push(&established_address_3())
+                    .unwrap()
+            ),
+            None
+        );
+    }
+}
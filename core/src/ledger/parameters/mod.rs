@@ -1,13 +1,18 @@
 //! Protocol parameters
 pub mod storage;
 
+use std::collections::BTreeMap;
+
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use thiserror::Error;
 
+use super::gas::GasCostTable;
 use super::storage::types;
 use super::storage_api::{self, ResultExt, StorageRead, StorageWrite};
 use crate::ledger::storage::{self as ledger_storage};
+use crate::proto::TxDecodeLimits;
 use crate::types::address::{Address, InternalAddress};
 use crate::types::chain::ProposalBytes;
 use crate::types::hash::Hash;
@@ -52,6 +57,25 @@ pub struct Parameters {
     pub staked_ratio: Decimal,
     /// PoS inflation amount from the last epoch (read + write for every epoch)
     pub pos_inflation_amount: u64,
+    /// The resolution of the gas limits of wrapper txs, which must be
+    /// non-zero
+    pub gas_limit_resolution: u64,
+    /// Whitelisted tokens that wrapper tx fees may be paid in, each mapped to
+    /// its conversion rate, i.e. the amount of the token worth one unit of
+    /// the native token. An empty whitelist allows fees in any token, at par.
+    pub fee_tokens: BTreeMap<Address, Decimal>,
+    /// The versioned costs of the operations metered in txs and VPs
+    pub gas_cost_table: GasCostTable,
+    /// The minimum price of a unit of gas in the native token. The fee of a
+    /// wrapper tx must be at least its gas limit times this price, converted
+    /// into its fee token.
+    pub min_gas_price: token::Amount,
+    /// The max cumulative gas that the wrapper txs included in a block may
+    /// declare in their gas limits
+    pub max_block_gas: u64,
+    /// The max size in bytes of a single tx, regardless of the space left
+    /// in the block
+    pub max_tx_bytes: u64,
     #[cfg(not(feature = "mainnet"))]
     /// Faucet account for free token withdrawal
     pub faucet_account: Option<Address>,
@@ -119,6 +143,12 @@ impl Parameters {
             pos_gain_d,
             staked_ratio,
             pos_inflation_amount,
+            gas_limit_resolution,
+            fee_tokens,
+            gas_cost_table,
+            min_gas_price,
+            max_block_gas,
+            max_tx_bytes,
             #[cfg(not(feature = "mainnet"))]
             faucet_account,
             #[cfg(not(feature = "mainnet"))]
@@ -178,6 +208,24 @@ impl Parameters {
         let pos_inflation_key = storage::get_pos_inflation_amount_key();
         storage.write(&pos_inflation_key, pos_inflation_amount)?;
 
+        let gas_limit_resolution_key = storage::get_gas_limit_resolution_key();
+        storage.write(&gas_limit_resolution_key, gas_limit_resolution)?;
+
+        let fee_tokens_key = storage::get_fee_tokens_key();
+        storage.write(&fee_tokens_key, fee_tokens)?;
+
+        let gas_cost_table_key = storage::get_gas_cost_table_key();
+        storage.write(&gas_cost_table_key, gas_cost_table)?;
+
+        let min_gas_price_key = storage::get_min_gas_price_key();
+        storage.write(&min_gas_price_key, min_gas_price)?;
+
+        let max_block_gas_key = storage::get_max_block_gas_key();
+        storage.write(&max_block_gas_key, max_block_gas)?;
+
+        let max_tx_bytes_key = storage::get_max_tx_bytes_key();
+        storage.write(&max_tx_bytes_key, max_tx_bytes)?;
+
         #[cfg(not(feature = "mainnet"))]
         if let Some(faucet_account) = faucet_account {
             let faucet_account_key = storage::get_faucet_account_key();
@@ -324,6 +372,186 @@ where
     storage.write(&key, value)
 }
 
+/// Update the gas limit resolution parameter in storage. The new resolution
+/// must be non-zero.
+pub fn update_gas_limit_resolution_parameter<S>(
+    storage: &mut S,
+    value: &u64,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    if *value == 0 {
+        return Err(storage_api::Error::new_const(
+            "The gas limit resolution must be non-zero",
+        ));
+    }
+    let key = storage::get_gas_limit_resolution_key();
+    storage.write(&key, value)
+}
+
+/// Update the fee tokens whitelist parameter in storage. The conversion rates
+/// of the new whitelist must be positive.
+pub fn update_fee_tokens_parameter<S>(
+    storage: &mut S,
+    value: &BTreeMap<Address, Decimal>,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    if value.values().any(|rate| *rate <= Decimal::ZERO) {
+        return Err(storage_api::Error::new_const(
+            "The conversion rates of the fee tokens must be positive",
+        ));
+    }
+    let key = storage::get_fee_tokens_key();
+    storage.write(&key, value)
+}
+
+/// Update the gas cost table parameter in storage. The version of the new
+/// table should be higher than the one of the current table.
+pub fn update_gas_cost_table_parameter<S>(
+    storage: &mut S,
+    value: &GasCostTable,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_gas_cost_table_key();
+    storage.write(&key, value)
+}
+
+/// Update the minimum gas price parameter in storage
+pub fn update_min_gas_price_parameter<S>(
+    storage: &mut S,
+    value: &token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_min_gas_price_key();
+    storage.write(&key, value)
+}
+
+/// Update the wrapper tx fees parameter in storage
+pub fn update_wrapper_tx_fees_parameter<S>(
+    storage: &mut S,
+    value: &token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_wrapper_tx_fees_key();
+    storage.write(&key, value)
+}
+
+/// Update the max block gas parameter in storage
+pub fn update_max_block_gas_parameter<S>(
+    storage: &mut S,
+    value: &u64,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_max_block_gas_key();
+    storage.write(&key, value)
+}
+
+/// Update the max tx bytes parameter in storage
+pub fn update_max_tx_bytes_parameter<S>(
+    storage: &mut S,
+    value: &u64,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_max_tx_bytes_key();
+    storage.write(&key, value)
+}
+
+/// Update the block space split parameter in storage. The value holds
+/// the percentages of the block space allotted to encrypted, decrypted
+/// and protocol txs, in this order.
+pub fn update_block_space_split_parameter<S>(
+    storage: &mut S,
+    value: &(u64, u64, u64),
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_block_space_split_key();
+    storage.write(&key, value)
+}
+
+/// Update the parameter that allows DKG encrypted txs to be included in
+/// blocks. Chains without Ferveo may disable them, such that the whole
+/// block is dedicated to decrypted and protocol txs.
+pub fn update_encrypted_txs_enabled_parameter<S>(
+    storage: &mut S,
+    value: bool,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_encrypted_txs_enabled_key();
+    storage.write(&key, value)
+}
+
+/// Update the maximum length of tx memos parameter in storage, measured
+/// in bytes.
+pub fn update_max_memo_len_parameter<S>(
+    storage: &mut S,
+    value: u64,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_max_memo_len_key();
+    storage.write(&key, value)
+}
+
+/// Update the limits enforced when decoding the txs submitted to the
+/// ledger. They can only tighten the hard caps of [`TxDecodeLimits`].
+pub fn update_tx_decode_limits_parameter<S>(
+    storage: &mut S,
+    value: &TxDecodeLimits,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_tx_decode_limits_key();
+    storage.write(&key, value)
+}
+
+/// Update the type URLs of the messages that the interchain accounts may
+/// execute over the channel of the given ID of this chain.
+pub fn update_ica_host_allow_messages_parameter<S>(
+    storage: &mut S,
+    channel_id: &str,
+    value: Vec<String>,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_ica_host_allow_messages_key(channel_id);
+    storage.write(&key, value)
+}
+
+/// Update the rate of the MASP rewards of the given token, i.e. the reward and
+/// the denominator of the fraction of its shielded balance rewarded in every
+/// epoch.
+pub fn update_masp_reward_rate_parameter<S>(
+    storage: &mut S,
+    token: &Address,
+    value: (u64, u64),
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = storage::get_masp_reward_rate_key(token);
+    storage.write(&key, value)
+}
+
 /// Update the implicit VP parameter in storage. Return the gas cost.
 pub fn update_implicit_vp<S>(
     storage: &mut S,
@@ -353,6 +581,174 @@ where
         .into_storage_result()
 }
 
+/// Read the gas limit resolution parameter from store
+pub fn read_gas_limit_resolution_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<u64>
+where
+    S: StorageRead,
+{
+    let gas_limit_resolution_key = storage::get_gas_limit_resolution_key();
+    let gas_limit_resolution = storage.read(&gas_limit_resolution_key)?;
+    gas_limit_resolution
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
+}
+
+/// Read the fee tokens whitelist parameter from store
+pub fn read_fee_tokens_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<BTreeMap<Address, Decimal>>
+where
+    S: StorageRead,
+{
+    let fee_tokens_key = storage::get_fee_tokens_key();
+    let fee_tokens = storage.read(&fee_tokens_key)?;
+    fee_tokens
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
+}
+
+/// Read the gas cost table parameter from store
+pub fn read_gas_cost_table_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<GasCostTable>
+where
+    S: StorageRead,
+{
+    let gas_cost_table_key = storage::get_gas_cost_table_key();
+    let gas_cost_table = storage.read(&gas_cost_table_key)?;
+    gas_cost_table
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
+}
+
+/// Read the minimum gas price parameter from store
+pub fn read_min_gas_price_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead,
+{
+    let min_gas_price_key = storage::get_min_gas_price_key();
+    let min_gas_price = storage.read(&min_gas_price_key)?;
+    min_gas_price
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
+}
+
+/// Read the max block gas parameter from store
+pub fn read_max_block_gas_parameter<S>(storage: &S) -> storage_api::Result<u64>
+where
+    S: StorageRead,
+{
+    let max_block_gas_key = storage::get_max_block_gas_key();
+    let max_block_gas = storage.read(&max_block_gas_key)?;
+    max_block_gas
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
+}
+
+/// Read the max tx bytes parameter from store
+pub fn read_max_tx_bytes_parameter<S>(storage: &S) -> storage_api::Result<u64>
+where
+    S: StorageRead,
+{
+    let max_tx_bytes_key = storage::get_max_tx_bytes_key();
+    let max_tx_bytes = storage.read(&max_tx_bytes_key)?;
+    max_tx_bytes
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()
+}
+
+/// Convert an `amount` of the native token into the equivalent amount of the
+/// given fee `token`, according to the `fee_tokens` whitelist, rounding up.
+/// An empty whitelist only allows the fees to be paid in the native token.
+/// Returns `None` if the token is not whitelisted or the conversion
+/// overflows.
+pub fn convert_fee_amount(
+    fee_tokens: &BTreeMap<Address, Decimal>,
+    native_token: &Address,
+    token: &Address,
+    amount: token::Amount,
+) -> Option<token::Amount> {
+    if fee_tokens.is_empty() {
+        return (token == native_token).then_some(amount);
+    }
+    let rate = fee_tokens.get(token)?;
+    amount
+        .as_dec_unscaled()
+        .checked_mul(*rate)?
+        .ceil()
+        .to_u128()
+        .and_then(token::Amount::from_micro)
+}
+
+/// Read the block space split parameter, if it was set by governance
+pub fn read_block_space_split_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<Option<(u64, u64, u64)>>
+where
+    S: StorageRead,
+{
+    let block_space_split_key = storage::get_block_space_split_key();
+    storage.read(&block_space_split_key)
+}
+
+/// Read the parameter that allows DKG encrypted txs to be included in
+/// blocks, if it was set by governance
+pub fn read_encrypted_txs_enabled_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<Option<bool>>
+where
+    S: StorageRead,
+{
+    let encrypted_txs_enabled_key = storage::get_encrypted_txs_enabled_key();
+    storage.read(&encrypted_txs_enabled_key)
+}
+
+/// Read the maximum length of tx memos parameter, if it was set by
+/// governance
+pub fn read_max_memo_len_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<Option<u64>>
+where
+    S: StorageRead,
+{
+    let max_memo_len_key = storage::get_max_memo_len_key();
+    storage.read(&max_memo_len_key)
+}
+
+/// Read the rates of the MASP rewards of the tokens eligible for the rewards
+pub fn read_masp_reward_rates<S>(
+    storage: &S,
+) -> storage_api::Result<BTreeMap<Address, (u64, u64)>>
+where
+    S: StorageRead,
+{
+    let prefix = storage::get_masp_reward_rates_prefix();
+    let mut rates = BTreeMap::new();
+    for entry in storage_api::iter_prefix::<(u64, u64)>(storage, &prefix)? {
+        let (key, rate) = entry?;
+        if let Some(token) = storage::is_masp_reward_rate_key(&key) {
+            rates.insert(token.clone(), rate);
+        }
+    }
+    Ok(rates)
+}
+
+/// Read the limits enforced when decoding the txs submitted to the ledger,
+/// if they were set by governance
+pub fn read_tx_decode_limits_parameter<S>(
+    storage: &S,
+) -> storage_api::Result<Option<TxDecodeLimits>>
+where
+    S: StorageRead,
+{
+    let tx_decode_limits_key = storage::get_tx_decode_limits_key();
+    storage.read(&tx_decode_limits_key)
+}
+
 #[cfg(not(feature = "mainnet"))]
 /// Read the faucet account's address, if any
 pub fn read_faucet_account_parameter<S>(
@@ -460,6 +856,24 @@ where
         .ok_or(ReadError::ParametersMissing)
         .into_storage_result()?;
 
+    // read gas limit resolution
+    let gas_limit_resolution = read_gas_limit_resolution_parameter(storage)?;
+
+    // read fee tokens
+    let fee_tokens = read_fee_tokens_parameter(storage)?;
+
+    // read gas cost table
+    let gas_cost_table = read_gas_cost_table_parameter(storage)?;
+
+    // read min gas price
+    let min_gas_price = read_min_gas_price_parameter(storage)?;
+
+    // read max block gas
+    let max_block_gas = read_max_block_gas_parameter(storage)?;
+
+    // read max tx bytes
+    let max_tx_bytes = read_max_tx_bytes_parameter(storage)?;
+
     // read faucet account
     #[cfg(not(feature = "mainnet"))]
     let faucet_account = read_faucet_account_parameter(storage)?;
@@ -480,6 +894,12 @@ where
         pos_gain_d,
         staked_ratio,
         pos_inflation_amount,
+        gas_limit_resolution,
+        fee_tokens,
+        gas_cost_table,
+        min_gas_price,
+        max_block_gas,
+        max_tx_bytes,
         #[cfg(not(feature = "mainnet"))]
         faucet_account,
         #[cfg(not(feature = "mainnet"))]
@@ -3,6 +3,7 @@
 use namada_macros::StorageKeys;
 
 use super::ADDRESS;
+use crate::types::address::Address;
 use crate::types::storage::{DbKeySeg, Key};
 
 /// Storage keys for ledger parameters.
@@ -19,8 +20,20 @@ struct Keys {
     tx_whitelist: &'static str,
     vp_whitelist: &'static str,
     max_proposal_bytes: &'static str,
+    gas_limit_resolution: &'static str,
+    fee_tokens: &'static str,
+    gas_cost_table: &'static str,
+    min_gas_price: &'static str,
+    max_block_gas: &'static str,
+    max_tx_bytes: &'static str,
+    block_space_split: &'static str,
+    encrypted_txs_enabled: &'static str,
+    max_memo_len: &'static str,
+    tx_decode_limits: &'static str,
     faucet_account: &'static str,
     wrapper_tx_fees: &'static str,
+    ica_host_allow_messages: &'static str,
+    masp_reward_rates: &'static str,
 }
 
 /// Returns if the key is a parameter key.
@@ -30,6 +43,11 @@ pub fn is_parameter_key(key: &Key) -> bool {
 
 /// Returns if the key is a protocol parameter key.
 pub fn is_protocol_parameter_key(key: &Key) -> bool {
+    if is_ica_host_allow_messages_key(key).is_some()
+        || is_masp_reward_rate_key(key).is_some()
+    {
+        return true;
+    }
     let segment = match &key.segments[..] {
         [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(segment)]
             if addr == &ADDRESS =>
@@ -57,6 +75,38 @@ pub fn is_max_expected_time_per_block_key(key: &Key) -> bool {
     ] if addr == &ADDRESS && max_expected_time_per_block == Keys::VALUES.max_expected_time_per_block)
 }
 
+/// Returns if the key is the key of the messages that the interchain
+/// accounts may execute over a channel. Returns the ID of the channel if so.
+pub fn is_ica_host_allow_messages_key(key: &Key) -> Option<&str> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(channel_id),
+        ] if addr == &ADDRESS
+            && prefix == Keys::VALUES.ica_host_allow_messages =>
+        {
+            Some(channel_id.as_str())
+        }
+        _ => None,
+    }
+}
+
+/// Returns if the key is the key of the rate of the MASP rewards of a token.
+/// Returns the address of the token if so.
+pub fn is_masp_reward_rate_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(token),
+        ] if addr == &ADDRESS && prefix == Keys::VALUES.masp_reward_rates => {
+            Some(token)
+        }
+        _ => None,
+    }
+}
+
 /// Returns if the key is the tx_whitelist key.
 pub fn is_tx_whitelist_key(key: &Key) -> bool {
     matches!(&key.segments[..], [
@@ -129,6 +179,54 @@ pub fn is_max_proposal_bytes_key(key: &Key) -> bool {
     ] if addr == &ADDRESS && max_proposal_bytes == Keys::VALUES.max_proposal_bytes)
 }
 
+/// Returns if the key is the gas limit resolution key.
+pub fn is_gas_limit_resolution_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+        DbKeySeg::AddressSeg(addr),
+        DbKeySeg::StringSeg(gas_limit_resolution),
+    ] if addr == &ADDRESS && gas_limit_resolution == Keys::VALUES.gas_limit_resolution)
+}
+
+/// Returns if the key is the fee tokens key.
+pub fn is_fee_tokens_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+        DbKeySeg::AddressSeg(addr),
+        DbKeySeg::StringSeg(fee_tokens),
+    ] if addr == &ADDRESS && fee_tokens == Keys::VALUES.fee_tokens)
+}
+
+/// Returns if the key is the gas cost table key.
+pub fn is_gas_cost_table_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+        DbKeySeg::AddressSeg(addr),
+        DbKeySeg::StringSeg(gas_cost_table),
+    ] if addr == &ADDRESS && gas_cost_table == Keys::VALUES.gas_cost_table)
+}
+
+/// Returns if the key is the minimum gas price key.
+pub fn is_min_gas_price_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+        DbKeySeg::AddressSeg(addr),
+        DbKeySeg::StringSeg(min_gas_price),
+    ] if addr == &ADDRESS && min_gas_price == Keys::VALUES.min_gas_price)
+}
+
+/// Returns if the key is the max block gas key.
+pub fn is_max_block_gas_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+        DbKeySeg::AddressSeg(addr),
+        DbKeySeg::StringSeg(max_block_gas),
+    ] if addr == &ADDRESS && max_block_gas == Keys::VALUES.max_block_gas)
+}
+
+/// Returns if the key is the max tx bytes key.
+pub fn is_max_tx_bytes_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+        DbKeySeg::AddressSeg(addr),
+        DbKeySeg::StringSeg(max_tx_bytes),
+    ] if addr == &ADDRESS && max_tx_bytes == Keys::VALUES.max_tx_bytes)
+}
+
 /// Storage key used for epoch parameter.
 pub fn get_epoch_duration_storage_key() -> Key {
     Key {
@@ -241,6 +339,106 @@ pub fn get_max_proposal_bytes_key() -> Key {
     }
 }
 
+/// Storage key used for the gas limit resolution.
+pub fn get_gas_limit_resolution_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.gas_limit_resolution.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the fee tokens whitelist.
+pub fn get_fee_tokens_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.fee_tokens.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the gas cost table.
+pub fn get_gas_cost_table_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.gas_cost_table.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the minimum gas price.
+pub fn get_min_gas_price_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.min_gas_price.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the max block gas.
+pub fn get_max_block_gas_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.max_block_gas.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the max size of a single tx.
+pub fn get_max_tx_bytes_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.max_tx_bytes.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the governance override of the block space split.
+pub fn get_block_space_split_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.block_space_split.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the governance toggle of DKG encrypted txs.
+pub fn get_encrypted_txs_enabled_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.encrypted_txs_enabled.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the maximum length of tx memos.
+pub fn get_max_memo_len_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.max_memo_len.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the governance override of the tx decode limits.
+pub fn get_tx_decode_limits_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.tx_decode_limits.to_string()),
+        ],
+    }
+}
+
 /// Storage key used for faucet account.
 pub fn get_faucet_account_key() -> Key {
     Key {
@@ -260,3 +458,40 @@ pub fn get_wrapper_tx_fees_key() -> Key {
         ],
     }
 }
+
+/// Storage key used for the type URLs of the messages that the interchain
+/// accounts may execute over the channel of the given ID of this chain.
+pub fn get_ica_host_allow_messages_key(channel_id: &str) -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(
+                Keys::VALUES.ica_host_allow_messages.to_string(),
+            ),
+            DbKeySeg::StringSeg(channel_id.to_string()),
+        ],
+    }
+}
+
+/// Storage prefix of the rates of the MASP rewards of the tokens.
+pub fn get_masp_reward_rates_prefix() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.masp_reward_rates.to_string()),
+        ],
+    }
+}
+
+/// Storage key used for the rate of the MASP rewards of the given token, i.e.
+/// the reward and the denominator of the fraction of the shielded balance
+/// rewarded in every epoch.
+pub fn get_masp_reward_rate_key(token: &Address) -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(Keys::VALUES.masp_reward_rates.to_string()),
+            DbKeySeg::AddressSeg(token.clone()),
+        ],
+    }
+}
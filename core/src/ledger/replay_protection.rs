@@ -1,5 +1,6 @@
 //! Replay protection storage
 
+use crate::ledger::storage_api::{self, StorageRead};
 use crate::types::address::{Address, InternalAddress};
 use crate::types::hash::Hash;
 use crate::types::storage::{DbKeySeg, Key, KeySeg};
@@ -8,9 +9,14 @@ use crate::types::storage::{DbKeySeg, Key, KeySeg};
 pub const ADDRESS: Address =
     Address::Internal(InternalAddress::ReplayProtection);
 
-/// Check if a key is a replay protection key
+const NONCE_KEY_SEGMENT: &str = "nonce";
+
+/// Check if a key is the key of the hash of an applied tx
 pub fn is_tx_hash_key(key: &Key) -> bool {
-    matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &ADDRESS)
+    matches!(&key.segments[..], [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(_),
+        ] if addr == &ADDRESS)
 }
 
 /// Get the transaction hash key
@@ -19,3 +25,54 @@ pub fn get_tx_hash_key(hash: &Hash) -> Key {
         .push(&hash.to_string())
         .expect("Cannot obtain a valid db key")
 }
+
+/// Check if a key is the key of the next wrapper nonce of an account. Returns
+/// the address of the account if so.
+pub fn is_nonce_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(owner),
+        ] if addr == &ADDRESS && prefix == NONCE_KEY_SEGMENT => Some(owner),
+        _ => None,
+    }
+}
+
+/// Get the key of the nonce that the next wrapper paid by `owner` must carry
+pub fn get_nonce_key(owner: &Address) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&NONCE_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+        .push(&owner.to_db_key())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Read the nonce that the next wrapper paid by `owner` must carry, which
+/// is `0` if no wrapper with a nonce was ever paid by it
+pub fn read_next_nonce<S>(
+    storage: &S,
+    owner: &Address,
+) -> storage_api::Result<u64>
+where
+    S: StorageRead,
+{
+    Ok(storage.read(&get_nonce_key(owner))?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+
+    #[test]
+    fn test_nonce_key() {
+        let owner = address::testing::established_address_1();
+        let key = get_nonce_key(&owner);
+        assert_eq!(is_nonce_key(&key), Some(&owner));
+        assert!(!is_tx_hash_key(&key));
+        let tx_hash_key = get_tx_hash_key(&Hash::default());
+        assert!(is_tx_hash_key(&tx_hash_key));
+        assert_eq!(is_nonce_key(&tx_hash_key), None);
+    }
+}
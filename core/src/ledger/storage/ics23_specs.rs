@@ -45,7 +45,6 @@ pub fn ibc_leaf_spec<H: StorageHasher>() -> LeafOp {
 }
 
 /// Get the proof specs for ibc
-#[allow(dead_code)]
 pub fn ibc_proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
     let spec = arse_merkle_tree::proof_ics23::get_spec(H::hash_op());
     let sub_tree_spec = ProofSpec {
@@ -60,7 +59,6 @@ pub fn ibc_proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
 }
 
 /// Get the proof specs
-#[allow(dead_code)]
 pub fn proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
     let spec = arse_merkle_tree::proof_ics23::get_spec(H::hash_op());
     let sub_tree_spec = ProofSpec {
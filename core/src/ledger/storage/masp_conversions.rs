@@ -39,6 +39,7 @@ where
     };
     use rayon::prelude::ParallelSlice;
 
+    use crate::ledger::parameters;
     use crate::ledger::storage_api::{ResultExt, StorageRead, StorageWrite};
     use crate::types::storage::{self, KeySeg};
     use crate::types::{address, token};
@@ -47,7 +48,9 @@ where
     let masp_addr = address::masp();
     let key_prefix: storage::Key = masp_addr.to_db_key().into();
 
-    let masp_rewards = address::masp_rewards();
+    // The tokens eligible for the rewards with their rates, as set by
+    // governance
+    let masp_rewards = parameters::read_masp_reward_rates(wl_storage)?;
     // The total transparent value of the rewards being distributed
     let mut total_reward = token::Amount::from(0);
 
@@ -110,6 +113,10 @@ where
         .values_mut()
         .enumerate()
         .collect();
+    // Without any shielded asset, there's no conversion to commit to
+    if assets.is_empty() {
+        return Ok(());
+    }
     // ceil(assets.len() / num_threads)
     let notes_per_thread_max = (assets.len() - 1) / num_threads + 1;
     // floor(assets.len() / num_threads)
@@ -120,8 +127,11 @@ where
         .with_min_len(notes_per_thread_min)
         .with_max_len(notes_per_thread_max)
         .map(|(idx, (addr, _epoch, conv, pos))| {
-            // Use transitivity to update conversion
-            *conv += current_convs[addr].clone();
+            // Use transitivity to update conversion. The assets of a token
+            // which is no longer rewarded keep their last conversion.
+            if let Some(current_conv) = current_convs.get(addr) {
+                *conv += current_conv.clone();
+            }
             // Update conversion position to leaf we are about to create
             *pos = idx;
             // The merkle tree need only provide the conversion commitment,
@@ -9,7 +9,10 @@ use arse_merkle_tree::{
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use ics23::commitment_proof::Proof as Ics23Proof;
-use ics23::{CommitmentProof, ExistenceProof, NonExistenceProof};
+use ics23::{
+    CommitmentProof, ExistenceProof, HostFunctionsManager, NonExistenceProof,
+    ProofSpec,
+};
 use thiserror::Error;
 
 use super::traits::{StorageHasher, SubTreeRead, SubTreeWrite};
@@ -46,6 +49,8 @@ pub enum Error {
     Ics23MultiLeaf,
     #[error("A Tendermint proof can only be constructed from an ICS23 proof.")]
     TendermintProof,
+    #[error("Invalid proof: {0}")]
+    InvalidProof(String),
 }
 
 /// Result for functions that may fail
@@ -572,6 +577,119 @@ pub struct Proof {
     pub base_proof: CommitmentProof,
 }
 
+impl Proof {
+    /// Verify that the given value of the key exists in the merkle tree of
+    /// the given root, which is the app hash of the header of the block after
+    /// the height of the proof
+    pub fn verify_membership<H: StorageHasher>(
+        &self,
+        value: impl AsRef<[u8]>,
+        root: &MerkleRoot,
+    ) -> Result<()> {
+        let (store_type, sub_key) = StoreType::sub_key(&self.key)?;
+        let specs = store_proof_specs::<H>(&store_type);
+        let sub_root = match &self.sub_proof.proof {
+            Some(Ics23Proof::Exist(ep)) => existence_root(ep)?,
+            _ => {
+                return Err(Error::InvalidProof(
+                    "Expected an existence proof".into(),
+                ));
+            }
+        };
+        if !ics23::verify_membership::<HostFunctionsManager>(
+            &self.sub_proof,
+            &specs[0],
+            &sub_root,
+            sub_key.to_string().as_bytes(),
+            value.as_ref(),
+        ) {
+            return Err(Error::InvalidProof(format!(
+                "The sub proof of the key {} is invalid",
+                self.key
+            )));
+        }
+        self.verify_base_proof(&store_type, &specs[1], &sub_root, root)
+    }
+
+    /// Verify that the key doesn't exist in the merkle tree of the given root,
+    /// which is the app hash of the header of the block after the height of
+    /// the proof
+    pub fn verify_non_membership<H: StorageHasher>(
+        &self,
+        root: &MerkleRoot,
+    ) -> Result<()> {
+        let (store_type, sub_key) = StoreType::sub_key(&self.key)?;
+        let specs = store_proof_specs::<H>(&store_type);
+        let sub_root = match &self.sub_proof.proof {
+            Some(Ics23Proof::Nonexist(NonExistenceProof {
+                left: Some(ep),
+                ..
+            }))
+            | Some(Ics23Proof::Nonexist(NonExistenceProof {
+                right: Some(ep),
+                ..
+            })) => existence_root(ep)?,
+            _ => {
+                return Err(Error::InvalidProof(
+                    "Expected a non-existence proof".into(),
+                ));
+            }
+        };
+        if !ics23::verify_non_membership::<HostFunctionsManager>(
+            &self.sub_proof,
+            &specs[0],
+            &sub_root,
+            sub_key.to_string().as_bytes(),
+        ) {
+            return Err(Error::InvalidProof(format!(
+                "The sub proof of the key {} is invalid",
+                self.key
+            )));
+        }
+        self.verify_base_proof(&store_type, &specs[1], &sub_root, root)
+    }
+
+    /// Verify that the root of the sub-tree exists in the base tree
+    fn verify_base_proof(
+        &self,
+        store_type: &StoreType,
+        spec: &ProofSpec,
+        sub_root: &[u8],
+        root: &MerkleRoot,
+    ) -> Result<()> {
+        if ics23::verify_membership::<HostFunctionsManager>(
+            &self.base_proof,
+            spec,
+            &root.0,
+            store_type.to_string().as_bytes(),
+            sub_root,
+        ) {
+            Ok(())
+        } else {
+            Err(Error::InvalidProof(format!(
+                "The base proof of the key {} is invalid",
+                self.key
+            )))
+        }
+    }
+}
+
+/// Get the proof specs of the sub-tree and the base tree of the given store
+fn store_proof_specs<H: StorageHasher>(
+    store_type: &StoreType,
+) -> Vec<ProofSpec> {
+    match store_type {
+        StoreType::Ibc => ics23_specs::ibc_proof_specs::<H>(),
+        _ => ics23_specs::proof_specs::<H>(),
+    }
+}
+
+/// Calculate the root of the tree from an existence proof
+fn existence_root(proof: &ExistenceProof) -> Result<Vec<u8>> {
+    ics23::calculate_existence_root::<HostFunctionsManager>(proof)
+        .map_err(|err| Error::InvalidProof(err.to_string()))
+}
+
 #[cfg(any(feature = "tendermint", feature = "tendermint-abcipp"))]
 impl From<Proof> for crate::tendermint::merkle::proof::Proof {
     fn from(
@@ -612,6 +730,35 @@ impl From<Proof> for crate::tendermint::merkle::proof::Proof {
     }
 }
 
+#[cfg(any(feature = "tendermint", feature = "tendermint-abcipp"))]
+impl TryFrom<crate::tendermint::merkle::proof::Proof> for Proof {
+    type Error = Error;
+
+    fn try_from(
+        proof: crate::tendermint::merkle::proof::Proof,
+    ) -> Result<Self> {
+        use prost::Message;
+
+        let [sub_proof_op, base_proof_op]: [_; 2] =
+            proof.ops.try_into().map_err(|_| {
+                Error::InvalidProof(
+                    "Expected the ops of a sub proof and a base proof".into(),
+                )
+            })?;
+        let key = String::from_utf8(sub_proof_op.key)
+            .map_err(|err| Error::InvalidProof(err.to_string()))?;
+        let decode = |data: Vec<u8>| {
+            CommitmentProof::decode(&data[..])
+                .map_err(|err| Error::InvalidProof(err.to_string()))
+        };
+        Ok(Self {
+            key: Key::parse(key)?,
+            sub_proof: decode(sub_proof_op.data)?,
+            base_proof: decode(base_proof_op.data)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ics23::HostFunctionsManager;
@@ -915,4 +1062,54 @@ mod test {
             );
         assert!(basetree_verification_res);
     }
+
+    #[test]
+    fn test_proof_verification() {
+        let mut tree = MerkleTree::<Sha256Hasher>::default();
+
+        let key_prefix: Key =
+            Address::Internal(InternalAddress::Ibc).to_db_key().into();
+        let ibc_key = key_prefix.push(&"test".to_string()).unwrap();
+        let ibc_non_key = key_prefix.push(&"test2".to_string()).unwrap();
+        let key_prefix: Key =
+            Address::Internal(InternalAddress::PoS).to_db_key().into();
+        let pos_key = key_prefix.push(&"test".to_string()).unwrap();
+
+        let ibc_val = [1u8; 8].to_vec();
+        tree.update(&ibc_key, &ibc_val).unwrap();
+        let pos_val = [2u8; 8].to_vec();
+        tree.update(&pos_key, &pos_val).unwrap();
+        let root = tree.root();
+
+        for (key, value) in [(&ibc_key, &ibc_val), (&pos_key, &pos_val)] {
+            let MembershipProof::ICS23(proof) = tree
+                .get_sub_tree_existence_proof(
+                    std::array::from_ref(key),
+                    vec![value],
+                )
+                .unwrap();
+            let proof = tree.get_sub_tree_proof(key, proof).unwrap();
+            proof
+                .verify_membership::<Sha256Hasher>(value, &root)
+                .unwrap();
+            assert!(proof
+                .verify_membership::<Sha256Hasher>([3u8; 8], &root)
+                .is_err());
+            assert!(proof
+                .verify_non_membership::<Sha256Hasher>(&root)
+                .is_err());
+        }
+
+        let proof = tree.get_non_existence_proof(&ibc_non_key).unwrap();
+        proof.verify_non_membership::<Sha256Hasher>(&root).unwrap();
+        assert!(proof
+            .verify_membership::<Sha256Hasher>(&ibc_val, &root)
+            .is_err());
+
+        // The proofs are invalid against another root
+        tree.update(&pos_key, [3u8; 8]).unwrap();
+        assert!(proof
+            .verify_non_membership::<Sha256Hasher>(&tree.root())
+            .is_err());
+    }
 }
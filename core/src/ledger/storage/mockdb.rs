@@ -509,6 +509,15 @@ impl DB for MockDB {
             None => Ok(()),
         }
     }
+
+    fn prune_diffs(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        _before: BlockHeight,
+    ) -> Result<()> {
+        // The diffs aren't stored
+        Ok(())
+    }
 }
 
 impl<'iter> DBIter<'iter> for MockDB {
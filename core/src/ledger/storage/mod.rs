@@ -12,6 +12,8 @@ pub mod write_log;
 
 use core::fmt::Debug;
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 pub use merkle_tree::{
@@ -102,6 +104,45 @@ where
     pub tx_queue: TxQueue,
     /// How many block heights in the past can the storage be queried
     pub storage_read_past_height_limit: Option<u64>,
+    /// The subspace writes (`Some`) and deletions (`None`) of the current
+    /// block that aren't in the DB yet. They are written to the DB in the
+    /// batch of the block on commit, rather than one key at a time.
+    block_writes: BTreeMap<Key, Option<Vec<u8>>>,
+    /// The cache of the values read from the DB in the current block
+    read_cache: Mutex<ReadCache>,
+}
+
+/// A cache of the values read from the DB in the current block, keyed by the
+/// block height and the storage key, such that the validity predicates don't
+/// read the DB again for the keys that the transaction already read. The
+/// cached value of a key is invalidated when the key is written and the whole
+/// cache is cleared on commit.
+#[derive(Debug, Default)]
+pub struct ReadCache {
+    values: HashMap<(BlockHeight, Key), Option<Vec<u8>>>,
+    metrics: ReadCacheMetrics,
+}
+
+/// The hits and misses of the read cache in a block
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReadCacheMetrics {
+    /// The number of the reads served from the cache
+    pub hits: u64,
+    /// The number of the reads from the DB
+    pub misses: u64,
+}
+
+impl ReadCacheMetrics {
+    /// The ratio of the reads that were served from the cache, which is 0 if
+    /// there were no reads
+    pub fn hit_rate(&self) -> f64 {
+        let reads = self.hits + self.misses;
+        if reads == 0 {
+            0.0
+        } else {
+            self.hits as f64 / reads as f64
+        }
+    }
 }
 
 /// Last committed block
@@ -327,6 +368,14 @@ pub trait DB: std::fmt::Debug {
         pruned_epoch: Epoch,
         pred_epochs: &Epochs,
     ) -> Result<()>;
+
+    /// Prune the diffs of the account subspaces of the heights before the
+    /// given one
+    fn prune_diffs(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        before: BlockHeight,
+    ) -> Result<()>;
 }
 
 /// A database prefix iterator.
@@ -408,12 +457,17 @@ where
             tx_queue: TxQueue::default(),
             native_token,
             storage_read_past_height_limit,
+            block_writes: BTreeMap::new(),
+            read_cache: Mutex::default(),
         }
     }
 
     /// Load the full state at the last committed height, if any. Returns the
     /// Merkle root hash and the height of the committed block.
     pub fn load_last_state(&mut self) -> Result<()> {
+        // The DB may have been changed without the storage, e.g. when it's
+        // restored from a snapshot
+        *self.read_cache.get_mut().unwrap() = ReadCache::default();
         if let Some(BlockStateRead {
             merkle_tree_stores,
             hash,
@@ -488,6 +542,23 @@ where
         let is_full_commit =
             self.block.height.0 == 1 || self.last_epoch != self.block.epoch;
 
+        // The Merkle tree is already updated with the block's writes
+        for (key, value) in std::mem::take(&mut self.block_writes) {
+            match value {
+                Some(value) => self.db.batch_write_subspace_val(
+                    &mut batch,
+                    self.block.height,
+                    &key,
+                    value,
+                )?,
+                None => self.db.batch_delete_subspace_val(
+                    &mut batch,
+                    self.block.height,
+                    &key,
+                )?,
+            };
+        }
+
         // For convenience in tests, fill-in a header if it's missing.
         // Normally, the header is added in `FinalizeBlock`.
         #[cfg(any(test, feature = "testing"))]
@@ -536,7 +607,19 @@ where
             // prune old merkle tree stores
             self.prune_merkle_tree_stores(&mut batch)?;
         }
-        self.db.exec_batch(batch)
+        self.db.exec_batch(batch)?;
+
+        // The read cache is scoped to the block
+        let cache = std::mem::take(self.read_cache.get_mut().unwrap());
+        tracing::debug!(
+            "Read cache of the block at height {}: {} hits, {} misses, hit \
+             rate {:.2}",
+            self.block.height,
+            cache.metrics.hits,
+            cache.metrics.misses,
+            cache.metrics.hit_rate()
+        );
+        Ok(())
     }
 
     /// Find the root hash of the merkle tree
@@ -558,7 +641,7 @@ where
             return Ok((None, gas));
         }
 
-        match self.db.read_subspace_val(key)? {
+        match self.read_uncommitted(key)? {
             Some(v) => {
                 let gas = key.len() + v.len();
                 Ok((Some(v), gas as _))
@@ -591,9 +674,43 @@ where
         }
     }
 
+    /// Read a value from the current block's writes or else from the read
+    /// cache or the DB
+    fn read_uncommitted(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self.block_writes.get(key) {
+            return Ok(value.clone());
+        }
+        let cache_key = (self.block.height, key.clone());
+        {
+            let mut cache = self.read_cache.lock().unwrap();
+            if let Some(value) = cache.values.get(&cache_key).cloned() {
+                cache.metrics.hits += 1;
+                return Ok(value);
+            }
+        }
+        // The cache isn't locked while reading the DB
+        let value = self.db.read_subspace_val(key)?;
+        let mut cache = self.read_cache.lock().unwrap();
+        cache.metrics.misses += 1;
+        cache.values.insert(cache_key, value.clone());
+        Ok(value)
+    }
+
+    /// Invalidate the cached read of a key that is written
+    fn invalidate_cached_read(&mut self, key: &Key) {
+        let cache_key = (self.block.height, key.clone());
+        self.read_cache.get_mut().unwrap().values.remove(&cache_key);
+    }
+
+    /// Get the hits and misses of the read cache in the current block
+    pub fn read_cache_metrics(&self) -> ReadCacheMetrics {
+        self.read_cache.lock().unwrap().metrics
+    }
+
     /// WARNING: This only works for values that have been committed to DB.
     /// To be able to see values written or deleted, but not yet committed,
-    /// use the `StorageWithWriteLog`.
+    /// use the `StorageWithWriteLog`. This includes the values written with
+    /// [`Storage::write`] and [`Storage::delete`] in the current block.
     ///
     /// Returns a prefix iterator, ordered by storage keys, and the gas cost.
     pub fn iter_prefix(
@@ -623,8 +740,13 @@ where
 
         let len = value.len();
         let gas = key.len() + len;
-        let size_diff =
-            self.db.write_subspace_val(self.block.height, key, value)?;
+        let prev_len = self
+            .read_uncommitted(key)?
+            .map(|prev_value| prev_value.len())
+            .unwrap_or_default();
+        let size_diff = len as i64 - prev_len as i64;
+        self.block_writes.insert(key.clone(), Some(value.to_vec()));
+        self.invalidate_cached_read(key);
         Ok((gas as _, size_diff))
     }
 
@@ -636,8 +758,12 @@ where
         let mut deleted_bytes_len = 0;
         if self.has_key(key)?.0 {
             self.block.tree.delete(key)?;
-            deleted_bytes_len =
-                self.db.delete_subspace_val(self.block.height, key)?;
+            deleted_bytes_len = self
+                .read_uncommitted(key)?
+                .map(|prev_value| prev_value.len() as i64)
+                .unwrap_or_default();
+            self.block_writes.insert(key.clone(), None);
+            self.invalidate_cached_read(key);
         }
         let gas = key.len() + deleted_bytes_len as usize;
         Ok((gas as _, deleted_bytes_len))
@@ -936,6 +1062,9 @@ where
     ) -> Result<i64> {
         let value = value.as_ref();
         self.block.tree.update(key, value)?;
+        // The batched value supersedes the block's write of the key, if any
+        self.block_writes.remove(key);
+        self.invalidate_cached_read(key);
         self.db
             .batch_write_subspace_val(batch, self.block.height, key, value)
     }
@@ -949,6 +1078,8 @@ where
         key: &Key,
     ) -> Result<i64> {
         self.block.tree.delete(key)?;
+        self.block_writes.remove(key);
+        self.invalidate_cached_read(key);
         self.db
             .batch_delete_subspace_val(batch, self.block.height, key)
     }
@@ -984,6 +1115,39 @@ where
         Ok(())
     }
 
+    /// Prune the diffs and the Merkle tree stores that are only needed to read
+    /// the state of the heights before the given one, which can then no
+    /// longer be read. The diffs are retained from the start of the epoch of
+    /// the given height, from whose Merkle tree stores the trees of the
+    /// following heights are restored.
+    pub fn prune_history(
+        &mut self,
+        retained_height: BlockHeight,
+    ) -> Result<()> {
+        let pred_epochs = &self.block.pred_epochs;
+        let start_height =
+            match pred_epochs.get_epoch_start_height(retained_height) {
+                Some(start_height) => start_height,
+                None => return Ok(()),
+            };
+        let mut batch = D::batch();
+        self.db.prune_diffs(&mut batch, start_height)?;
+        // Prune the stores of all the known epochs before the retained one
+        for height in pred_epochs.first_block_heights() {
+            if *height >= start_height {
+                break;
+            }
+            if let Some(epoch) = pred_epochs.get_epoch(*height) {
+                self.db.prune_merkle_tree_stores(
+                    &mut batch,
+                    epoch,
+                    pred_epochs,
+                )?;
+            }
+        }
+        self.db.exec_batch(batch)
+    }
+
     /// Get the height of the last committed block or 0 if no block has been
     /// committed yet. The first block is at height 1.
     pub fn get_last_block_height(&self) -> BlockHeight {
@@ -1049,6 +1213,8 @@ pub mod testing {
                 tx_queue: TxQueue::default(),
                 native_token: address::nam(),
                 storage_read_past_height_limit: Some(1000),
+                block_writes: BTreeMap::new(),
+                read_cache: Mutex::default(),
             }
         }
     }
@@ -1075,6 +1241,7 @@ mod tests {
     use super::*;
     use crate::ledger::parameters::{self, Parameters};
     use crate::types::time::{self, Duration};
+    use crate::types::transaction::DEFAULT_GAS_LIMIT_RESOLUTION;
 
     prop_compose! {
         /// Setup test input data with arbitrary epoch duration, epoch start
@@ -1156,6 +1323,12 @@ mod tests {
                 pos_gain_d: dec!(0.1),
                 staked_ratio: dec!(0.1),
                 pos_inflation_amount: 0,
+                gas_limit_resolution: DEFAULT_GAS_LIMIT_RESOLUTION,
+                fee_tokens: Default::default(),
+                gas_cost_table: Default::default(),
+                min_gas_price: Default::default(),
+                max_block_gas: u64::MAX,
+                max_tx_bytes: u64::MAX,
                 #[cfg(not(feature = "mainnet"))]
                 faucet_account: None,
                 #[cfg(not(feature = "mainnet"))]
@@ -1278,4 +1451,33 @@ mod tests {
             assert_eq!(wl_storage.storage.block.epoch, epoch_before.next());
         }
     }
+
+    /// Test that the committed values are read from the DB once per block and
+    /// that the cached reads are invalidated on write
+    #[test]
+    fn test_read_cache() {
+        let mut storage = TestStorage::default();
+        let key = Key::parse("key").unwrap();
+        storage.write(&key, vec![1_u8]).unwrap();
+        storage.commit_block(mockdb::MockDB::batch()).unwrap();
+
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .unwrap();
+        for _ in 0..3 {
+            assert_eq!(storage.read(&key).unwrap().0, Some(vec![1_u8]));
+        }
+        assert_eq!(
+            storage.read_cache_metrics(),
+            ReadCacheMetrics { hits: 2, misses: 1 }
+        );
+
+        // The written value is read rather than the cached one
+        storage.write(&key, vec![2_u8]).unwrap();
+        assert_eq!(storage.read(&key).unwrap().0, Some(vec![2_u8]));
+        storage.commit_block(mockdb::MockDB::batch()).unwrap();
+        assert_eq!(storage.read_cache_metrics(), ReadCacheMetrics::default());
+        assert_eq!(storage.read(&key).unwrap().0, Some(vec![2_u8]));
+        assert_eq!(storage.read_cache_metrics().misses, 1);
+    }
 }
@@ -62,7 +62,8 @@ pub enum StorageModification {
 /// The write log storage
 #[derive(Debug, Clone)]
 pub struct WriteLog {
-    /// The generator of established addresses
+    /// The generator of the established addresses of the accounts
+    /// initialized by the current transaction
     address_gen: Option<EstablishedAddressGen>,
     /// All the storage modification accepted by validity predicates are stored
     /// in block write-log, before being committed to the storage
@@ -310,18 +311,21 @@ impl WriteLog {
         Ok(())
     }
 
-    /// Initialize a new account and return the gas cost.
+    /// Initialize a new account for the transaction with the given raw header
+    /// hash and return the gas cost. The address of the account is derived
+    /// from the hash and the number of accounts previously initialized by the
+    /// transaction, see [`EstablishedAddressGen::for_tx`].
     pub fn init_account(
         &mut self,
-        storage_address_gen: &EstablishedAddressGen,
+        tx_hash: &Hash,
         vp_code_hash: Hash,
     ) -> (Address, u64) {
-        // If we've previously generated a new account, we use the local copy of
-        // the generator. Otherwise, we create a new copy from the storage
-        let address_gen =
-            self.address_gen.get_or_insert(storage_address_gen.clone());
-        let addr =
-            address_gen.generate_address("TODO more randomness".as_bytes());
+        // If the transaction has previously initialized an account, we
+        // continue with its generator
+        let address_gen = self
+            .address_gen
+            .get_or_insert_with(|| EstablishedAddressGen::for_tx(tx_hash));
+        let addr = address_gen.generate_address(b"");
         let key = storage::Key::validity_predicate(&addr);
         let gas = (key.len() + vp_code_hash.len()) as _;
         self.tx_write_log
@@ -403,6 +407,7 @@ impl WriteLog {
             HashMap::with_capacity(100),
         );
         self.block_write_log.extend(tx_write_log);
+        self.address_gen = None;
         self.take_ibc_events();
     }
 
@@ -410,6 +415,7 @@ impl WriteLog {
     /// the triggered validity predicates. Starts a new transaction write log.
     pub fn drop_tx(&mut self) {
         self.tx_write_log.clear();
+        self.address_gen = None;
     }
 
     /// Commit the current block's write log to the storage. Starts a new block
@@ -446,9 +452,6 @@ impl WriteLog {
                 StorageModification::Temp { .. } => {}
             }
         }
-        if let Some(address_gen) = self.address_gen.take() {
-            storage.address_gen = address_gen
-        }
         self.block_write_log.clear();
         Ok(())
     }
@@ -600,12 +603,12 @@ mod tests {
     #[test]
     fn test_crud_account() {
         let mut write_log = WriteLog::default();
-        let address_gen = EstablishedAddressGen::new("test");
+        let tx_hash = Hash::sha256("test".as_bytes());
 
         // init
         let init_vp = "initialized".as_bytes().to_vec();
         let vp_hash = Hash::sha256(init_vp);
-        let (addr, gas) = write_log.init_account(&address_gen, vp_hash);
+        let (addr, gas) = write_log.init_account(&tx_hash, vp_hash);
         let vp_key = storage::Key::validity_predicate(&addr);
         assert_eq!(gas, (vp_key.len() + vp_hash.len()) as u64);
 
@@ -625,14 +628,45 @@ mod tests {
         assert_eq!(init_accounts.len(), 1);
     }
 
+    #[test]
+    fn test_init_account_addresses() {
+        let mut write_log = WriteLog::default();
+        let tx_hash = Hash::sha256("test".as_bytes());
+        let vp_hash = Hash::sha256("vp".as_bytes());
+
+        // The addresses only depend on the tx hash and on the order of the
+        // accounts initialized by the tx
+        let mut address_gen = EstablishedAddressGen::for_tx(&tx_hash);
+        let (addr1, _) = write_log.init_account(&tx_hash, vp_hash);
+        let (addr2, _) = write_log.init_account(&tx_hash, vp_hash);
+        assert_ne!(addr1, addr2);
+        assert_eq!(addr1, address_gen.generate_address(b""));
+        assert_eq!(addr2, address_gen.generate_address(b""));
+        write_log.commit_tx();
+
+        // Another tx gets other addresses
+        let other_tx_hash = Hash::sha256("other".as_bytes());
+        let (addr3, _) = write_log.init_account(&other_tx_hash, vp_hash);
+        assert_ne!(addr3, addr1);
+        assert_eq!(
+            addr3,
+            EstablishedAddressGen::for_tx(&other_tx_hash).generate_address(b"")
+        );
+        write_log.drop_tx();
+
+        // Dropping the tx also drops its generator
+        let (addr4, _) = write_log.init_account(&other_tx_hash, vp_hash);
+        assert_eq!(addr4, addr3);
+    }
+
     #[test]
     fn test_update_initialized_account_should_fail() {
         let mut write_log = WriteLog::default();
-        let address_gen = EstablishedAddressGen::new("test");
+        let tx_hash = Hash::sha256("test".as_bytes());
 
         let init_vp = "initialized".as_bytes().to_vec();
         let vp_hash = Hash::sha256(init_vp);
-        let (addr, _) = write_log.init_account(&address_gen, vp_hash);
+        let (addr, _) = write_log.init_account(&tx_hash, vp_hash);
         let vp_key = storage::Key::validity_predicate(&addr);
 
         // update should fail
@@ -647,11 +681,11 @@ mod tests {
     #[test]
     fn test_delete_initialized_account_should_fail() {
         let mut write_log = WriteLog::default();
-        let address_gen = EstablishedAddressGen::new("test");
+        let tx_hash = Hash::sha256("test".as_bytes());
 
         let init_vp = "initialized".as_bytes().to_vec();
         let vp_hash = Hash::sha256(init_vp);
-        let (addr, _) = write_log.init_account(&address_gen, vp_hash);
+        let (addr, _) = write_log.init_account(&tx_hash, vp_hash);
         let vp_key = storage::Key::validity_predicate(&addr);
 
         // delete should fail
@@ -676,7 +710,7 @@ mod tests {
             crate::ledger::storage::testing::TestStorage::default();
         let mut write_log = WriteLog::default();
         let mut batch = crate::ledger::storage::testing::TestStorage::batch();
-        let address_gen = EstablishedAddressGen::new("test");
+        let tx_hash = Hash::sha256("test".as_bytes());
 
         let key1 =
             storage::Key::parse("key1").expect("cannot parse the key string");
@@ -689,7 +723,7 @@ mod tests {
 
         // initialize an account
         let vp1 = Hash::sha256("vp1".as_bytes());
-        let (addr1, _) = write_log.init_account(&address_gen, vp1);
+        let (addr1, _) = write_log.init_account(&tx_hash, vp1);
         write_log.commit_tx();
 
         // write values
@@ -24,3 +24,38 @@ where
     let key = pk_key(&addr);
     storage.write(&key, pk)
 }
+
+/// Get the public keys of the signers of the multisig account at the given
+/// address and the number of their signatures it requires. Returns
+/// `Ok(None)` if the account is not a multisig.
+pub fn get_signers<S>(
+    storage: &S,
+    owner: &Address,
+) -> Result<Option<(Vec<common::PublicKey>, u8)>>
+where
+    S: StorageRead,
+{
+    let threshold: Option<u8> = storage.read(&threshold_key(owner))?;
+    match threshold {
+        Some(threshold) => {
+            let pks = storage.read(&pks_key(owner))?.unwrap_or_default();
+            Ok(Some((pks, threshold)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Set the public keys of the signers of the multisig account at the given
+/// address and the number of their signatures it requires.
+pub fn write_signers<S>(
+    storage: &mut S,
+    owner: &Address,
+    pks: &[common::PublicKey],
+    threshold: u8,
+) -> Result<()>
+where
+    S: StorageWrite,
+{
+    storage.write(&pks_key(owner), pks.to_vec())?;
+    storage.write(&threshold_key(owner), threshold)
+}
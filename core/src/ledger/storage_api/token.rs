@@ -1,12 +1,15 @@
 //! Token storage_api functions
 
-use super::{StorageRead, StorageWrite};
+use super::{ResultExt, StorageRead, StorageWrite};
 use crate::ledger::storage_api;
 use crate::types::address::Address;
+use crate::types::storage::Key;
 use crate::types::token;
 pub use crate::types::token::{
-    balance_key, is_balance_key, is_total_supply_key, total_supply_key, Amount,
-    Change,
+    balance_key, frozen_account_key, frozen_key, hook_key, is_balance_key,
+    is_hook_key, is_metadata_key, is_token_balance_key, is_total_supply_key,
+    metadata_key, mint_cap_key, minted_key, token_balance_key,
+    total_supply_key, Amount, Change, TokenMetadata,
 };
 
 /// Read the balance of a given token and owner.
@@ -36,6 +39,102 @@ where
     Ok(balance)
 }
 
+/// Read the metadata of a given token, if any.
+pub fn read_metadata<S>(
+    storage: &S,
+    token: &Address,
+) -> storage_api::Result<Option<token::TokenMetadata>>
+where
+    S: StorageRead,
+{
+    storage.read(&token::metadata_key(token))
+}
+
+/// Write the metadata of a given token, to be used only by protocol at the
+/// token's initialization. In transactions, this would get rejected by the
+/// default `vp_token`.
+pub fn write_metadata<S>(
+    storage: &mut S,
+    token: &Address,
+    metadata: &token::TokenMetadata,
+) -> storage_api::Result<()>
+where
+    S: StorageWrite,
+{
+    storage.write(&token::metadata_key(token), metadata)
+}
+
+/// Read the address of the hook of a given token, if any.
+pub fn read_hook<S>(
+    storage: &S,
+    token: &Address,
+) -> storage_api::Result<Option<Address>>
+where
+    S: StorageRead,
+{
+    storage.read(&token::hook_key(token))
+}
+
+/// Write the address of the hook of a given token, to be used only by protocol
+/// at the token's initialization. In transactions, this would get rejected by
+/// the default `vp_token`.
+pub fn write_hook<S>(
+    storage: &mut S,
+    token: &Address,
+    hook: &Address,
+) -> storage_api::Result<()>
+where
+    S: StorageWrite,
+{
+    storage.write(&token::hook_key(token), hook)
+}
+
+/// Read the mint cap of a given token set by its hook, if any.
+pub fn read_mint_cap<S>(
+    storage: &S,
+    hook: &Address,
+    token: &Address,
+) -> storage_api::Result<Option<token::Amount>>
+where
+    S: StorageRead,
+{
+    storage.read(&token::mint_cap_key(hook, token))
+}
+
+/// Read the supply of a given token minted through IBC, as tracked by its
+/// hook.
+pub fn read_minted<S>(
+    storage: &S,
+    hook: &Address,
+    token: &Address,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead,
+{
+    let minted = storage.read(&token::minted_key(hook, token))?;
+    Ok(minted.unwrap_or_default())
+}
+
+/// Check if the transfers of a given token from and to a given owner are
+/// frozen by its hook, either for all the owners or for this one only.
+pub fn is_frozen<S>(
+    storage: &S,
+    hook: &Address,
+    token: &Address,
+    owner: &Address,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    let frozen: Option<bool> = storage.read(&token::frozen_key(hook, token))?;
+    if frozen.unwrap_or_default() {
+        return Ok(true);
+    }
+    let frozen: Option<bool> =
+        storage.read(&token::frozen_account_key(hook, token, owner))?;
+    Ok(frozen.unwrap_or_default())
+}
+
 /// Transfer `token` from `src` to `dest`. Returns an `Err` if `src` has
 /// insufficient balance or if the transfer the `dest` would overflow (This can
 /// only happen if the total supply does't fit in `token::Amount`).
@@ -46,18 +145,47 @@ pub fn transfer<S>(
     dest: &Address,
     amount: token::Amount,
 ) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    multitoken_transfer(storage, token, None, src, dest, amount)
+}
+
+/// Transfer `token` from `src` to `dest`, either from the token's own
+/// balances or, with a sub prefix, from one of its multitoken balances.
+/// Returns an `Err` if `src` has insufficient balance, if the transfer the
+/// `dest` would overflow or if the transfer is frozen by the token's hook.
+pub fn multitoken_transfer<S>(
+    storage: &mut S,
+    token: &Address,
+    sub_prefix: Option<&Key>,
+    src: &Address,
+    dest: &Address,
+    amount: token::Amount,
+) -> storage_api::Result<()>
 where
     S: StorageRead + StorageWrite,
 {
     if amount.is_zero() {
         return Ok(());
     }
-    let src_key = token::balance_key(token, src);
-    let src_balance = read_balance(storage, token, src)?;
+    if let Some(hook) = read_hook(storage, token)? {
+        if is_frozen(storage, &hook, token, src)?
+            || is_frozen(storage, &hook, token, dest)?
+        {
+            return Err(storage_api::Error::new_const(
+                "The transfer is frozen by the token's hook",
+            ));
+        }
+    }
+    let src_key = token::token_balance_key(token, sub_prefix, src);
+    let src_balance: token::Amount =
+        storage.read(&src_key)?.unwrap_or_default();
     match src_balance.checked_sub(amount) {
         Some(new_src_balance) => {
-            let dest_key = token::balance_key(token, dest);
-            let dest_balance = read_balance(storage, token, dest)?;
+            let dest_key = token::token_balance_key(token, sub_prefix, dest);
+            let dest_balance: token::Amount =
+                storage.read(&dest_key)?.unwrap_or_default();
             match dest_balance.checked_add(amount) {
                 Some(new_dest_balance) => {
                     storage.write(&src_key, new_src_balance)?;
@@ -102,3 +230,50 @@ where
     storage.write(&balance_key, new_balance)?;
     storage.write(&total_supply_key, new_supply)
 }
+
+/// Burn tokens of an account, to be used only by protocol. In transactions,
+/// this would get rejected by the default `vp_token`.
+pub fn burn_tokens<S>(
+    storage: &mut S,
+    token: &Address,
+    src: &Address,
+    amount: token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let balance_key = token::balance_key(token, src);
+    let cur_balance = read_balance(storage, token, src)?;
+    let new_balance = cur_balance.checked_sub(amount).ok_or_else(|| {
+        storage_api::Error::new_const("Insufficient balance to burn")
+    })?;
+
+    let total_supply_key = token::total_supply_key(token);
+    let cur_supply = read_total_supply(storage, token)?;
+    let new_supply = cur_supply.checked_sub(amount).ok_or_else(|| {
+        storage_api::Error::new_const("Token total supply underflow")
+    })?;
+
+    storage.write(&balance_key, new_balance)?;
+    storage.write(&total_supply_key, new_supply)
+}
+
+/// Migrate the amount stored at the given key from the legacy encoding of
+/// 64-bit amounts, if it isn't migrated yet. Returns whether it was migrated.
+pub fn migrate_legacy_amount<S>(
+    storage: &mut S,
+    key: &Key,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead + StorageWrite,
+{
+    match storage.read_bytes(key)? {
+        Some(bytes) if bytes.len() == token::LegacyAmount::ENCODED_LEN => {
+            let amount = token::Amount::try_from_slice_compat(&bytes)
+                .into_storage_result()?;
+            storage.write(key, amount)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
@@ -4,8 +4,9 @@ pub mod generated;
 mod types;
 
 pub use types::{
-    Code, Commitment, Data, Dkg, Error, Header, MaspBuilder, Section,
-    Signature, Tx, TxError,
+    Code, Commitment, Compression, Data, Dkg, Error, Header, InnerTxHashes,
+    MaspBuilder, Memo, Section, SignMode, Signature, Tx, TxDecodeLimits,
+    TxError, DEFAULT_MAX_MEMO_LEN, TX_VERSION,
 };
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
 
 #[cfg(feature = "ferveo-tpke")]
@@ -21,6 +22,7 @@ use super::generated::types;
 use crate::tendermint_proto::abci::ResponseDeliverTx;
 use crate::types::address::Address;
 use crate::types::chain::ChainId;
+use crate::types::key::signer::{Signer, SignerError};
 use crate::types::key::*;
 use crate::types::storage::Epoch;
 use crate::types::time::DateTimeUtc;
@@ -50,10 +52,151 @@ pub enum Error {
     NoTimestampError,
     #[error("Timestamp is invalid: {0}")]
     InvalidTimestamp(prost_types::TimestampError),
+    #[error("Error decompressing a transaction section: {0}")]
+    SectionDecompressionError(std::io::Error),
+    #[error("Unsupported transaction version {0}")]
+    UnsupportedTxVersion(u8),
+    #[error("The transaction of {len} bytes exceeds the max of {max} bytes")]
+    TxTooLarge { len: u64, max: u64 },
+    #[error(
+        "The transaction holds {count} sections, more than the max of {max}"
+    )]
+    TooManySections { count: u64, max: u64 },
+    #[error(
+        "The section {index} of {len} bytes exceeds the max section size of \
+         {max} bytes"
+    )]
+    SectionTooLarge { index: usize, len: u64, max: u64 },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The max size of the bytes of a section once decompressed, which guards
+/// against decompression bombs
+pub const MAX_DECOMPRESSED_SECTION_LEN: usize = 32 * 1024 * 1024;
+
+/// The version of the layout of the txs produced by this code, which is
+/// embedded in their header:
+///
+/// - version 0 txs do not batch inner txs
+/// - version 1 txs do not carry a memo
+/// - version 2 txs start with this version byte
+///
+/// The layout of txs of versions 0 and 1 holds no version byte, they are
+/// recognized by [`Tx::decode_versioned`].
+pub const TX_VERSION: u8 = 2;
+
+/// The limits enforced when decoding a transaction, which guard against
+/// transactions that are costly to decode and check.
+///
+/// The default limits, which [`Tx::try_from`] enforces, are hard caps.
+/// Governance may tighten them, in which case the ledger enforces the
+/// tighter limits on the transactions submitted to it.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct TxDecodeLimits {
+    /// The max number of sections of a transaction
+    pub max_sections: u64,
+    /// The max size in bytes of a single section, as encoded
+    pub max_section_len: u64,
+    /// The max size in bytes of a whole transaction, as encoded
+    pub max_tx_len: u64,
+}
+
+impl Default for TxDecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_sections: 1024,
+            max_section_len: MAX_DECOMPRESSED_SECTION_LEN as u64,
+            max_tx_len: 2 * MAX_DECOMPRESSED_SECTION_LEN as u64,
+        }
+    }
+}
+
+impl TxDecodeLimits {
+    /// Get the limits that are the tightest among these and the hard caps
+    pub fn capped(&self) -> Self {
+        let caps = Self::default();
+        Self {
+            max_sections: self.max_sections.min(caps.max_sections),
+            max_section_len: self.max_section_len.min(caps.max_section_len),
+            max_tx_len: self.max_tx_len.min(caps.max_tx_len),
+        }
+    }
+}
+
+/// The max length of a tx memo in bytes, unless overridden by governance
+pub const DEFAULT_MAX_MEMO_LEN: u64 = 256;
+
+/// The zstd compression level used for tx sections
+#[cfg(feature = "compression")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// The compression scheme applied to the bytes of a section.
+///
+/// Sections are hashed over their decompressed bytes, such that the
+/// compression of a section changes neither its hash nor the signatures
+/// over it. Only the size of the section, which is what gets accounted
+/// for in gas and block space, is affected.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum Compression {
+    /// The bytes are stored as they are
+    #[default]
+    None,
+    /// The bytes are compressed with zstd
+    Zstd,
+}
+
+impl Compression {
+    /// Compress the given bytes with this scheme
+    #[cfg(feature = "compression")]
+    pub fn compress(&self, bytes: Vec<u8>) -> Vec<u8> {
+        match self {
+            Self::None => bytes,
+            Self::Zstd => zstd::bulk::compress(&bytes, ZSTD_LEVEL)
+                .expect("unable to compress section bytes"),
+        }
+    }
+
+    /// Decompress the given bytes, which were compressed with this scheme
+    pub fn decompress(&self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            #[cfg(feature = "compression")]
+            Self::Zstd => {
+                zstd::bulk::decompress(bytes, MAX_DECOMPRESSED_SECTION_LEN)
+            }
+            #[cfg(not(feature = "compression"))]
+            Self::Zstd => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "zstd compressed sections are not supported",
+            )),
+        }
+    }
+}
+
 /// A section representing transaction data
 #[derive(
     Clone,
@@ -66,6 +209,8 @@ pub type Result<T> = std::result::Result<T, Error>;
 )]
 pub struct Data {
     pub salt: [u8; 8],
+    /// The compression scheme applied to the bytes below
+    pub compression: Compression,
     pub data: Vec<u8>,
 }
 
@@ -74,14 +219,81 @@ impl Data {
     pub fn new(data: Vec<u8>) -> Self {
         Self {
             salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
+            compression: Compression::None,
             data,
         }
     }
 
+    /// Make a new data section with the given bytes, compressed with zstd
+    #[cfg(feature = "compression")]
+    pub fn new_compressed(data: Vec<u8>) -> Self {
+        Self {
+            salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
+            compression: Compression::Zstd,
+            data: Compression::Zstd.compress(data),
+        }
+    }
+
+    /// Get the decompressed bytes of this data section
+    pub fn decompressed_data(&self) -> std::io::Result<Vec<u8>> {
+        self.compression.decompress(&self.data)
+    }
+
+    /// Get a copy of this data section with its bytes decompressed
+    pub fn decompressed(&self) -> std::io::Result<Self> {
+        Ok(Self {
+            salt: self.salt,
+            compression: Compression::None,
+            data: self.decompressed_data()?,
+        })
+    }
+
     /// Hash this data section
+    pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
+        // Sections that cannot be decompressed are hashed as they are,
+        // such that they can never match the hash they were committed to
+        let data = match self.compression {
+            Compression::None => None,
+            _ => self.decompressed_data().ok(),
+        };
+        hasher.update(
+            (self.salt, data.as_ref().unwrap_or(&self.data))
+                .try_to_vec()
+                .expect("unable to serialize data section"),
+        );
+        hasher
+    }
+}
+
+/// A section holding a free-form memo attached to a transaction, such as
+/// the tag exchanges use to credit deposits
+#[derive(
+    Clone,
+    Debug,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Memo {
+    pub salt: [u8; 8],
+    pub memo: String,
+}
+
+impl Memo {
+    /// Make a new memo section with the given text
+    pub fn new(memo: String) -> Self {
+        Self {
+            salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
+            memo,
+        }
+    }
+
+    /// Hash this memo section
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
         hasher.update(
-            self.try_to_vec().expect("unable to serialize data section"),
+            self.try_to_vec().expect("unable to serialize memo section"),
         );
         hasher
     }
@@ -162,6 +374,9 @@ impl Commitment {
 pub struct Code {
     /// Additional random data
     pub salt: [u8; 8],
+    /// The compression scheme applied to the code bytes, if the code is
+    /// not just committed to by its hash
+    pub compression: Compression,
     /// Actual transaction code
     pub code: Commitment,
 }
@@ -171,27 +386,118 @@ impl Code {
     pub fn new(code: Vec<u8>) -> Self {
         Self {
             salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
+            compression: Compression::None,
             code: Commitment::Id(code),
         }
     }
 
+    /// Make a new code section with the given bytes, compressed with zstd
+    #[cfg(feature = "compression")]
+    pub fn new_compressed(code: Vec<u8>) -> Self {
+        Self {
+            salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
+            compression: Compression::Zstd,
+            code: Commitment::Id(Compression::Zstd.compress(code)),
+        }
+    }
+
     /// Make a new code section with the given hash
     pub fn from_hash(hash: crate::types::hash::Hash) -> Self {
         Self {
             salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
+            compression: Compression::None,
             code: Commitment::Hash(hash),
         }
     }
 
+    /// Get a copy of this code section with its bytes decompressed
+    pub fn decompressed(&self) -> std::io::Result<Self> {
+        let code = match &self.code {
+            Commitment::Id(code) => {
+                Commitment::Id(self.compression.decompress(code)?)
+            }
+            Commitment::Hash(hash) => Commitment::Hash(*hash),
+        };
+        Ok(Self {
+            salt: self.salt,
+            compression: Compression::None,
+            code,
+        })
+    }
+
+    /// Get the decompressed code bytes of this section, if it holds any
+    pub fn decompressed_code(&self) -> Option<Vec<u8>> {
+        match &self.code {
+            Commitment::Id(code) => self.compression.decompress(code).ok(),
+            Commitment::Hash(_) => None,
+        }
+    }
+
+    /// Get the SHA-256 hash of the decompressed code of this section
+    pub fn code_hash(&self) -> crate::types::hash::Hash {
+        match (&self.code, self.compression) {
+            (Commitment::Id(_), Compression::Zstd) => self
+                .decompressed_code()
+                .map(|code| hash_tx(&code))
+                // Sections that cannot be decompressed are hashed as they
+                // are, such that they can never match their commitment
+                .unwrap_or_else(|| self.code.hash()),
+            _ => self.code.hash(),
+        }
+    }
+
     /// Hash this code section
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
         hasher.update(self.salt);
-        hasher.update(self.code.hash());
+        hasher.update(self.code_hash());
         hasher
     }
 }
 
-/// A section representing the signature over another section
+/// Error representing the case where signature sections over different
+/// hashes, or in different modes, were aggregated
+#[derive(Error, Debug)]
+#[error("Cannot aggregate signatures over {0} with signatures over {1}")]
+pub struct MismatchedTargetError(
+    crate::types::hash::Hash,
+    crate::types::hash::Hash,
+);
+
+/// The payload signed over by the signatures of a signature section
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum SignMode {
+    /// The signatures are made over the raw bytes of the target hash
+    Hash,
+    /// The signatures are made over the given human readable representation
+    /// of a transaction, as returned by [`Tx::signing_text`], such that
+    /// hardware wallets can display what they sign. The text must end with
+    /// the line committing to the target hash.
+    Textual(String),
+    /// The signatures are made by Ethereum wallets, such as Metamask, over
+    /// the EIP-191 personal message of the target hash in hex, such that
+    /// the keys that they hold can control implicit accounts. Only secp256k1
+    /// keys can sign in this mode.
+    Eip191,
+}
+
+/// A section representing the signatures over another section, by one or
+/// more signers.
+///
+/// Multisig accounts may co-sign a tx offline, each signer producing a
+/// detached section over the same hash, and aggregate the signatures into a
+/// single section before broadcasting the tx. A section doesn't carry the
+/// number of signatures it requires, which is instead checked against the
+/// threshold stored for the account, see [`Tx::verify_multisig`].
 #[derive(
     Clone,
     Debug,
@@ -206,10 +512,16 @@ pub struct Signature {
     salt: [u8; 8],
     /// The hash of the section being signed
     target: crate::types::hash::Hash,
-    /// The signature over the above has
-    pub signature: common::Signature,
-    /// The public key to verrify the above siggnature
-    pub_key: common::PublicKey,
+    /// The signatures over the above hash, indexed by the public keys to
+    /// verify them
+    #[serde(
+        serialize_with = "borsh_serde::<SignaturesSerde, _>",
+        deserialize_with = "serde_borsh::<SignaturesSerde, _, _>"
+    )]
+    pub signatures: BTreeMap<common::PublicKey, common::Signature>,
+    /// The payload signed over by the signatures above. Both modes are
+    /// accepted, while hardware wallets transition to textual signing.
+    pub mode: SignMode,
 }
 
 impl Signature {
@@ -218,12 +530,189 @@ impl Signature {
         target: &crate::types::hash::Hash,
         sec_key: &common::SecretKey,
     ) -> Self {
+        let mut section = Self::unsigned(target);
+        section.sign(sec_key);
+        section
+    }
+
+    /// Assemble a section from all its parts, e.g. when translating it from
+    /// another encoding
+    pub fn from_parts(
+        salt: [u8; 8],
+        target: crate::types::hash::Hash,
+        signatures: BTreeMap<common::PublicKey, common::Signature>,
+        mode: SignMode,
+    ) -> Self {
+        Self {
+            salt,
+            target,
+            signatures,
+            mode,
+        }
+    }
+
+    /// Make a section without any signatures over the given section hash
+    pub fn unsigned(target: &crate::types::hash::Hash) -> Self {
         Self {
             salt: DateTimeUtc::now().0.timestamp_millis().to_le_bytes(),
             target: *target,
-            signature: common::SigScheme::sign(sec_key, target),
-            pub_key: sec_key.ref_to(),
+            signatures: BTreeMap::new(),
+            mode: SignMode::Hash,
+        }
+    }
+
+    /// Sign the human readable representation of the given transaction
+    /// with the given key and return a section over its header
+    pub fn new_textual(tx: &Tx, sec_key: &common::SecretKey) -> Self {
+        let mut section = Self::unsigned(&tx.header_hash());
+        section.mode = SignMode::Textual(tx.signing_text());
+        section.sign(sec_key);
+        section
+    }
+
+    /// Sign the EIP-191 personal message of the given section hash with the
+    /// given secp256k1 key, as Ethereum wallets do, and return a section
+    pub fn new_eip191(
+        target: &crate::types::hash::Hash,
+        sec_key: &common::SecretKey,
+    ) -> Self {
+        let mut section = Self::unsigned(target);
+        section.mode = SignMode::Eip191;
+        section.sign(sec_key);
+        section
+    }
+
+    /// Get the hash of the section being signed
+    pub fn target(&self) -> &crate::types::hash::Hash {
+        &self.target
+    }
+
+    /// Get the payload signed over by the signatures of this section
+    fn payload(&self) -> Cow<[u8]> {
+        match &self.mode {
+            SignMode::Hash => Cow::Borrowed(&self.target.0),
+            SignMode::Textual(text) => Cow::Borrowed(text.as_bytes()),
+            SignMode::Eip191 => {
+                Cow::Owned(self.target.to_string().into_bytes())
+            }
+        }
+    }
+
+    /// Verify the given signature over the payload of this section, in the
+    /// scheme implied by its mode
+    fn verify_payload_signature(
+        &self,
+        pk: &common::PublicKey,
+        sig: &common::Signature,
+    ) -> std::result::Result<(), VerifySigError> {
+        match (&self.mode, pk, sig) {
+            (
+                SignMode::Eip191,
+                common::PublicKey::Secp256k1(pk),
+                common::Signature::Secp256k1(sig),
+            ) => secp256k1::SigScheme::verify_eip191(pk, &self.payload(), sig),
+            (SignMode::Eip191, _, _) => Err(VerifySigError::MismatchedScheme),
+            _ => common::SigScheme::verify_signature_raw(
+                pk,
+                &self.payload(),
+                sig,
+            ),
+        }
+    }
+
+    /// Check that the payload of this section commits to its target hash
+    fn verify_payload(&self) -> std::result::Result<(), VerifySigError> {
+        match &self.mode {
+            SignMode::Hash | SignMode::Eip191 => Ok(()),
+            SignMode::Textual(text)
+                if text.lines().last()
+                    == Some(&signing_text_hash_line(&self.target)) =>
+            {
+                Ok(())
+            }
+            SignMode::Textual(_) => Err(VerifySigError::SigVerifyError(
+                format!("The signed text doesn't commit to {}", self.target),
+            )),
+        }
+    }
+
+    /// Add the signature of the given key over the payload to this section,
+    /// replacing any previous signature by the same key. In EIP-191 mode, the
+    /// signatures of keys other than secp256k1 ones never verify.
+    pub fn sign(&mut self, sec_key: &common::SecretKey) -> &mut Self {
+        let signature = match (&self.mode, sec_key) {
+            (SignMode::Eip191, common::SecretKey::Secp256k1(sk)) => {
+                common::Signature::Secp256k1(secp256k1::SigScheme::sign_eip191(
+                    sk,
+                    self.payload(),
+                ))
+            }
+            _ => common::SigScheme::sign(sec_key, self.payload()),
+        };
+        self.signatures.insert(sec_key.ref_to(), signature);
+        self
+    }
+
+    /// Add the signature of the given signer over the payload to this
+    /// section, replacing any previous signature by the same key. Signers
+    /// cannot make EIP-191 signatures, as they only sign raw messages.
+    pub fn sign_with(
+        &mut self,
+        signer: &dyn Signer,
+    ) -> std::result::Result<&mut Self, SignerError> {
+        if self.mode == SignMode::Eip191 {
+            return Err(SignerError::Unsupported(
+                "EIP-191 personal message".to_string(),
+            ));
+        }
+        let signature = signer.sign(&self.payload())?;
+        self.signatures.insert(signer.public_key()?, signature);
+        Ok(self)
+    }
+
+    /// Aggregate the signatures of a detached section over the same hash
+    /// into this section
+    pub fn aggregate(
+        &mut self,
+        other: &Signature,
+    ) -> std::result::Result<&mut Self, MismatchedTargetError> {
+        if self.target != other.target || self.mode != other.mode {
+            return Err(MismatchedTargetError(self.target, other.target));
+        }
+        self.signatures.extend(
+            other
+                .signatures
+                .iter()
+                .map(|(pk, sig)| (pk.clone(), sig.clone())),
+        );
+        Ok(self)
+    }
+
+    /// Verify the signature of the given public key over the target hash,
+    /// if this section holds one
+    pub fn verify_signer(
+        &self,
+        pk: &common::PublicKey,
+    ) -> Option<std::result::Result<(), VerifySigError>> {
+        self.signatures.get(pk).map(|sig| {
+            self.verify_payload()?;
+            self.verify_payload_signature(pk, sig)
+        })
+    }
+
+    /// Check that this section holds at least one signature and that all
+    /// its signatures over the target hash are valid
+    pub fn verify_signatures(&self) -> std::result::Result<(), VerifySigError> {
+        self.verify_payload()?;
+        if self.signatures.is_empty() {
+            return Err(VerifySigError::SigVerifyError(format!(
+                "The signature section over {} holds no signatures",
+                self.target
+            )));
         }
+        self.signatures
+            .iter()
+            .try_for_each(|(pk, sig)| self.verify_payload_signature(pk, sig))
     }
 
     /// Hash this signature section
@@ -236,6 +725,12 @@ impl Signature {
     }
 }
 
+/// The last line of the human readable representation of a transaction,
+/// which commits to the hash of its header
+fn signing_text_hash_line(hash: &crate::types::hash::Hash) -> String {
+    format!("Header hash: {}", hash)
+}
+
 /// Represents a section obtained by encrypting another section
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "ferveo-tpke", serde(from = "SerializedCiphertext"))]
@@ -436,6 +931,23 @@ where
     .map_err(S::Error::custom)
 }
 
+/// A structure to facilitate Serde (de)serializations of the signatures of
+/// a signature section, whose keys cannot be represented as JSON strings
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SignaturesSerde(Vec<u8>);
+
+impl From<Vec<u8>> for SignaturesSerde {
+    fn from(sigs: Vec<u8>) -> Self {
+        Self(sigs)
+    }
+}
+
+impl From<SignaturesSerde> for Vec<u8> {
+    fn from(sigs: SignaturesSerde) -> Vec<u8> {
+        sigs.0
+    }
+}
+
 /// A structure to facilitate Serde (de)serializations of Builders
 #[derive(serde::Serialize, serde::Deserialize)]
 struct BuilderSerde(Vec<u8>);
@@ -549,6 +1061,8 @@ pub enum Section {
     /// A section providing the auxiliary inputs used to construct a MASP
     /// transaction. Only send to wallet, never send to protocol.
     MaspBuilder(MaspBuilder),
+    /// A memo attached to the transaction
+    Memo(Memo),
 }
 
 impl Section {
@@ -567,6 +1081,7 @@ impl Section {
             Self::Signature(sig) => sig.hash(hasher),
             Self::Ciphertext(ct) => ct.hash(hasher),
             Self::MaspBuilder(mb) => mb.hash(hasher),
+            Self::Memo(memo) => memo.hash(hasher),
             Self::MaspTx(tx) => {
                 hasher.update(tx.txid().as_ref());
                 hasher
@@ -574,6 +1089,13 @@ impl Section {
         }
     }
 
+    /// Get the hash of this section
+    pub fn get_hash(&self) -> crate::types::hash::Hash {
+        crate::types::hash::Hash(
+            self.hash(&mut Sha256::new()).finalize_reset().into(),
+        )
+    }
+
     /// Sign over the hash of this section and return a signature section that
     /// can be added to the container transaction
     pub fn sign(&self, sec_key: &common::SecretKey) -> Signature {
@@ -606,7 +1128,7 @@ impl Section {
     /// Extract the extra data from this section if possible
     pub fn extra_data(&self) -> Option<Vec<u8>> {
         if let Self::ExtraData(data) = self {
-            data.code.id()
+            data.decompressed_code()
         } else {
             None
         }
@@ -624,7 +1146,7 @@ impl Section {
     /// Extract the code from this section is possible
     pub fn code(&self) -> Option<Vec<u8>> {
         if let Self::Code(data) = self {
-            data.code.id()
+            data.decompressed_code()
         } else {
             None
         }
@@ -665,6 +1187,15 @@ impl Section {
             None
         }
     }
+
+    /// Extract the memo from this section if possible
+    pub fn memo(&self) -> Option<Memo> {
+        if let Self::Memo(data) = self {
+            Some(data.clone())
+        } else {
+            None
+        }
+    }
 }
 
 /// A Namada transaction header indicating where transaction subcomponents can
@@ -679,6 +1210,8 @@ impl Section {
     Deserialize,
 )]
 pub struct Header {
+    /// The version of the layout of this transaction
+    pub version: u8,
     /// The chain which this transaction is being submitted to
     pub chain_id: ChainId,
     /// The time at which this transaction expires
@@ -691,27 +1224,160 @@ pub struct Header {
     pub data_hash: crate::types::hash::Hash,
     /// The type of this transaction
     pub tx_type: TxType,
+    /// The inner transactions batched after the one designated by the code
+    /// and data hashes above, executed atomically and in order
+    pub batch: Vec<InnerTxHashes>,
+    /// The SHA-256 hash of the transaction's memo section, if any
+    pub memo_hash: Option<crate::types::hash::Hash>,
+}
+
+/// The commitment of a transaction header to the code and data sections of
+/// one of its batched inner transactions
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct InnerTxHashes {
+    /// The SHA-256 hash of the inner transaction's code section
+    pub code_hash: crate::types::hash::Hash,
+    /// The SHA-256 hash of the inner transaction's data section
+    pub data_hash: crate::types::hash::Hash,
+}
+
+/// The layout of the header of version 0 transactions, which could not
+/// batch inner transactions
+#[derive(BorshSerialize, BorshDeserialize)]
+struct HeaderV0 {
+    chain_id: ChainId,
+    expiration: Option<DateTimeUtc>,
+    timestamp: DateTimeUtc,
+    code_hash: crate::types::hash::Hash,
+    data_hash: crate::types::hash::Hash,
+    tx_type: TxType,
+}
+
+impl From<&Header> for HeaderV0 {
+    fn from(header: &Header) -> Self {
+        Self {
+            chain_id: header.chain_id.clone(),
+            expiration: header.expiration,
+            timestamp: header.timestamp,
+            code_hash: header.code_hash,
+            data_hash: header.data_hash,
+            tx_type: header.tx_type.clone(),
+        }
+    }
+}
+
+impl From<HeaderV0> for Header {
+    fn from(header: HeaderV0) -> Self {
+        Self {
+            version: 0,
+            chain_id: header.chain_id,
+            expiration: header.expiration,
+            timestamp: header.timestamp,
+            code_hash: header.code_hash,
+            data_hash: header.data_hash,
+            tx_type: header.tx_type,
+            batch: vec![],
+            memo_hash: None,
+        }
+    }
+}
+
+/// The layout of the header of version 1 transactions, which could not
+/// carry a memo
+#[derive(BorshSerialize, BorshDeserialize)]
+struct HeaderV1 {
+    chain_id: ChainId,
+    expiration: Option<DateTimeUtc>,
+    timestamp: DateTimeUtc,
+    code_hash: crate::types::hash::Hash,
+    data_hash: crate::types::hash::Hash,
+    tx_type: TxType,
+    batch: Vec<InnerTxHashes>,
+}
+
+impl From<&Header> for HeaderV1 {
+    fn from(header: &Header) -> Self {
+        Self {
+            chain_id: header.chain_id.clone(),
+            expiration: header.expiration,
+            timestamp: header.timestamp,
+            code_hash: header.code_hash,
+            data_hash: header.data_hash,
+            tx_type: header.tx_type.clone(),
+            batch: header.batch.clone(),
+        }
+    }
+}
+
+impl From<HeaderV1> for Header {
+    fn from(header: HeaderV1) -> Self {
+        Self {
+            version: 1,
+            chain_id: header.chain_id,
+            expiration: header.expiration,
+            timestamp: header.timestamp,
+            code_hash: header.code_hash,
+            data_hash: header.data_hash,
+            tx_type: header.tx_type,
+            batch: header.batch,
+            memo_hash: None,
+        }
+    }
+}
+
+/// The layout of version 0 transactions
+#[derive(BorshSerialize, BorshDeserialize)]
+struct TxV0 {
+    header: HeaderV0,
+    sections: Vec<Section>,
+}
+
+/// The layout of version 1 transactions
+#[derive(BorshSerialize, BorshDeserialize)]
+struct TxV1 {
+    header: HeaderV1,
+    sections: Vec<Section>,
 }
 
 impl Header {
     /// Make a new header of the given transaction type
     pub fn new(tx_type: TxType) -> Self {
         Self {
+            version: TX_VERSION,
             tx_type,
             chain_id: ChainId::default(),
             expiration: None,
             timestamp: DateTimeUtc::now(),
             code_hash: crate::types::hash::Hash::default(),
             data_hash: crate::types::hash::Hash::default(),
+            batch: vec![],
+            memo_hash: None,
         }
     }
 
-    /// Get the hash of this transaction header.
+    /// Get the hash of this transaction header. The headers of older
+    /// versions are hashed in their original layout, such that the
+    /// signatures over them remain valid, unless they use fields their
+    /// layout lacks.
     pub fn hash<'a>(&self, hasher: &'a mut Sha256) -> &'a mut Sha256 {
-        hasher.update(
-            self.try_to_vec()
-                .expect("unable to serialize transaction header"),
-        );
+        let bytes = match self.version {
+            0 if self.batch.is_empty() && self.memo_hash.is_none() => {
+                HeaderV0::from(self).try_to_vec()
+            }
+            1 if self.memo_hash.is_none() => HeaderV1::from(self).try_to_vec(),
+            _ => self.try_to_vec(),
+        };
+        hasher.update(bytes.expect("unable to serialize transaction header"));
         hasher
     }
 
@@ -780,9 +1446,7 @@ impl TryFrom<&[u8]> for Tx {
     type Error = Error;
 
     fn try_from(tx_bytes: &[u8]) -> Result<Self> {
-        let tx = types::Tx::decode(tx_bytes).map_err(Error::TxDecodingError)?;
-        BorshDeserialize::try_from_slice(&tx.data)
-            .map_err(Error::TxDeserializingError)
+        Tx::decode_with_limits(tx_bytes, &TxDecodeLimits::default())
     }
 }
 
@@ -795,6 +1459,78 @@ impl Tx {
         }
     }
 
+    /// Deserialize a transaction from protobufs, rejecting it as soon as it
+    /// exceeds any of the given limits. The limits are capped, see
+    /// [`TxDecodeLimits::capped`].
+    pub fn decode_with_limits(
+        tx_bytes: &[u8],
+        limits: &TxDecodeLimits,
+    ) -> Result<Self> {
+        let limits = limits.capped();
+        // The size of the tx bounds the cost of decoding it, so it is checked
+        // before anything else
+        let len = tx_bytes.len() as u64;
+        if len > limits.max_tx_len {
+            return Err(Error::TxTooLarge {
+                len,
+                max: limits.max_tx_len,
+            });
+        }
+        let tx = types::Tx::decode(tx_bytes).map_err(Error::TxDecodingError)?;
+        let tx = Tx::decode_versioned(&tx.data)?;
+        let count = tx.sections.len() as u64;
+        if count > limits.max_sections {
+            return Err(Error::TooManySections {
+                count,
+                max: limits.max_sections,
+            });
+        }
+        for (index, section) in tx.sections.iter().enumerate() {
+            let len = section
+                .try_to_vec()
+                .expect("sections should serialize")
+                .len() as u64;
+            if len > limits.max_section_len {
+                return Err(Error::SectionTooLarge {
+                    index,
+                    len,
+                    max: limits.max_section_len,
+                });
+            }
+        }
+        Ok(tx)
+    }
+
+    /// Deserialize a transaction from the given Borsh bytes, in the layout
+    /// of any of its versions up to [`TX_VERSION`]. The layouts of older
+    /// versions, which hold no version byte, are tried from the newest.
+    pub fn decode_versioned(bytes: &[u8]) -> Result<Self> {
+        match Tx::try_from_slice(bytes) {
+            Ok(tx) if tx.header.version == TX_VERSION => return Ok(tx),
+            Ok(tx) if tx.header.version > TX_VERSION => {
+                return Err(Error::UnsupportedTxVersion(tx.header.version));
+            }
+            _ => {}
+        }
+        if let Ok(TxV1 { header, sections }) = TxV1::try_from_slice(bytes) {
+            return Ok(Tx {
+                header: header.into(),
+                sections,
+            });
+        }
+        match TxV0::try_from_slice(bytes) {
+            Ok(TxV0 { header, sections }) => Ok(Tx {
+                header: header.into(),
+                sections,
+            }),
+            // A version this code doesn't know, hence cannot decode
+            Err(_) if bytes.first().map_or(false, |v| *v > TX_VERSION) => {
+                Err(Error::UnsupportedTxVersion(bytes[0]))
+            }
+            Err(err) => Err(Error::TxDeserializingError(err)),
+        }
+    }
+
     /// Get the transaction header
     pub fn header(&self) -> Header {
         self.header.clone()
@@ -807,6 +1543,16 @@ impl Tx {
         )
     }
 
+    /// Get the hash of the transaction header with the raw tx type, which is
+    /// the same for a tx whether it's wrapped, decrypted or neither
+    pub fn raw_header_hash(&self) -> crate::types::hash::Hash {
+        let mut header = self.header.clone();
+        header.tx_type = TxType::Raw;
+        crate::types::hash::Hash(
+            header.hash(&mut Sha256::new()).finalize_reset().into(),
+        )
+    }
+
     /// Update the header whilst maintaining existing cross-references
     pub fn update_header(&mut self, tx_type: TxType) -> &mut Self {
         self.header.tx_type = tx_type;
@@ -845,10 +1591,11 @@ impl Tx {
         self.header.code_hash = hash
     }
 
-    /// Get the code designated by the transaction code hash in the header
+    /// Get the code designated by the transaction code hash in the header,
+    /// decompressed if need be
     pub fn code(&self) -> Option<Vec<u8>> {
         match self.get_section(self.code_sechash()) {
-            Some(Section::Code(section)) => section.code.id(),
+            Some(Section::Code(section)) => section.decompressed_code(),
             _ => None,
         }
     }
@@ -885,14 +1632,138 @@ impl Tx {
         self.sections.last_mut().unwrap()
     }
 
-    /// Get the data designated by the transaction data hash in the header
+    /// Get the data designated by the transaction data hash in the header,
+    /// decompressed if need be
     pub fn data(&self) -> Option<Vec<u8>> {
         match self.get_section(self.data_sechash()) {
-            Some(Section::Data(data)) => Some(data.data.clone()),
+            Some(Section::Data(data)) => data.decompressed_data().ok(),
+            _ => None,
+        }
+    }
+
+    /// Decompress all the code and data sections of this transaction. The
+    /// hashes of the sections, hence the signatures over them, are left
+    /// unchanged.
+    ///
+    /// The host decompresses transactions before handing them to the VM,
+    /// which does not support compressed sections.
+    pub fn decompress(&mut self) -> Result<()> {
+        for section in &mut self.sections {
+            match section {
+                Section::Data(data)
+                    if data.compression != Compression::None =>
+                {
+                    *data = data
+                        .decompressed()
+                        .map_err(Error::SectionDecompressionError)?;
+                }
+                Section::Code(code) | Section::ExtraData(code)
+                    if code.compression != Compression::None =>
+                {
+                    *code = code
+                        .decompressed()
+                        .map_err(Error::SectionDecompressionError)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the given memo to the transaction and set its hash in the header
+    pub fn set_memo(&mut self, memo: Memo) -> &mut Section {
+        let sec = Section::Memo(memo);
+        self.header.memo_hash = Some(sec.get_hash());
+        self.sections.push(sec);
+        self.sections.last_mut().unwrap()
+    }
+
+    /// Get the memo designated by the transaction memo hash in the header
+    pub fn memo(&self) -> Option<String> {
+        match self.get_section(self.header.memo_hash.as_ref()?) {
+            Some(Section::Memo(memo)) => Some(memo.memo.clone()),
             _ => None,
         }
     }
 
+    /// Check that the memo of this transaction, if any, is present and no
+    /// longer than the given number of bytes
+    pub fn validate_memo(&self, max_memo_len: u64) -> bool {
+        match &self.header.memo_hash {
+            None => true,
+            Some(_) => self
+                .memo()
+                .map_or(false, |memo| memo.len() as u64 <= max_memo_len),
+        }
+    }
+
+    /// Batch a new inner transaction made of the given code and data after
+    /// the ones this transaction already commits to
+    pub fn add_inner_tx(&mut self, code: Code, data: Data) {
+        let code = Section::Code(code);
+        let data = Section::Data(data);
+        self.header.batch.push(InnerTxHashes {
+            code_hash: code.get_hash(),
+            data_hash: data.get_hash(),
+        });
+        self.sections.push(code);
+        self.sections.push(data);
+    }
+
+    /// Check if this transaction batches more than one inner transaction
+    pub fn is_batch(&self) -> bool {
+        !self.header.batch.is_empty()
+    }
+
+    /// Get the inner transactions committed to by this transaction, in
+    /// execution order. Each of them carries all the sections of this
+    /// transaction, but its header only designates its own code and data.
+    pub fn inner_txs(&self) -> Vec<Tx> {
+        let mut first = self.clone();
+        let batch = std::mem::take(&mut first.header.batch);
+        let mut inner_txs = Vec::with_capacity(batch.len() + 1);
+        for hashes in batch {
+            let mut inner_tx = first.clone();
+            inner_tx.set_code_sechash(hashes.code_hash);
+            inner_tx.set_data_sechash(hashes.data_hash);
+            inner_txs.push(inner_tx);
+        }
+        inner_txs.insert(0, first);
+        inner_txs
+    }
+
+    /// Get the canonical, human readable representation of this transaction
+    /// that hardware wallets display to their users when signing it. The
+    /// text ends with a line committing to the header hash, which is what
+    /// the protocol verifies against.
+    pub fn signing_text(&self) -> String {
+        let mut lines = vec![format!("Chain ID: {}", self.header.chain_id)];
+        if let Some(expiration) = &self.header.expiration {
+            lines.push(format!("Expiration: {}", expiration));
+        }
+        if let Some(wrapper) = self.header.wrapper() {
+            lines.push(format!(
+                "Fee: {} {}",
+                wrapper.fee.amount, wrapper.fee.token
+            ));
+            lines.push(format!("Fee payer: {}", wrapper.pk));
+        }
+        let transfer = self.data().and_then(|data| {
+            crate::types::token::Transfer::try_from_slice(&data).ok()
+        });
+        if let Some(transfer) = transfer {
+            lines.push(format!("Source: {}", transfer.source));
+            lines.push(format!("Target: {}", transfer.target));
+            lines.push(format!("Token: {}", transfer.token));
+            lines.push(format!("Amount: {}", transfer.amount));
+        }
+        if let Some(memo) = self.memo() {
+            lines.push(format!("Memo: {}", memo));
+        }
+        lines.push(signing_text_hash_line(&self.header_hash()));
+        lines.join("\n")
+    }
+
     /// Convert this transaction into protobufs
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = vec![];
@@ -913,18 +1784,58 @@ impl Tx {
     ) -> std::result::Result<(), VerifySigError> {
         for section in &self.sections {
             if let Section::Signature(sig_sec) = section {
-                if sig_sec.pub_key == *pk && sig_sec.target == *hash {
-                    return common::SigScheme::verify_signature_raw(
-                        pk,
-                        &hash.0,
-                        &sig_sec.signature,
-                    );
+                if sig_sec.target == *hash {
+                    if let Some(result) = sig_sec.verify_signer(pk) {
+                        return result;
+                    }
                 }
             }
         }
         Err(VerifySigError::MissingData)
     }
 
+    /// Verify that all the signature sections over the given hash hold only
+    /// valid signatures
+    pub fn verify_signature_sections(
+        &self,
+        hash: &crate::types::hash::Hash,
+    ) -> std::result::Result<(), VerifySigError> {
+        self.sections
+            .iter()
+            .filter_map(|section| match section {
+                Section::Signature(sig_sec) if sig_sec.target == *hash => {
+                    Some(sig_sec)
+                }
+                _ => None,
+            })
+            .try_for_each(Signature::verify_signatures)
+    }
+
+    /// Verify that at least `threshold` of the given public keys signed the
+    /// given hash, as required by a multisig account. The signatures may be
+    /// aggregated in a single section or spread over several ones.
+    pub fn verify_multisig(
+        &self,
+        pks: &[common::PublicKey],
+        threshold: u8,
+        hash: &crate::types::hash::Hash,
+    ) -> std::result::Result<(), VerifySigError> {
+        let valid = pks
+            .iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|pk| self.verify_signature(pk, hash).is_ok())
+            .count();
+        if valid >= usize::from(threshold) {
+            Ok(())
+        } else {
+            Err(VerifySigError::SigVerifyError(format!(
+                "Only {} out of the {} required signers signed {}",
+                valid, threshold, hash
+            )))
+        }
+    }
+
     /// Validate any and all ciphertexts stored in this transaction
     #[cfg(feature = "ferveo-tpke")]
     pub fn validate_ciphertext(&self) -> bool {
@@ -962,6 +1873,13 @@ impl Tx {
         self.data().ok_or(WrapperTxErr::DecryptedHash)?;
         self.get_section(self.code_sechash())
             .ok_or(WrapperTxErr::DecryptedHash)?;
+        // Every batched inner tx must have been decrypted too
+        for hashes in &self.header.batch {
+            self.get_section(&hashes.data_hash)
+                .ok_or(WrapperTxErr::DecryptedHash)?;
+            self.get_section(&hashes.code_hash)
+                .ok_or(WrapperTxErr::DecryptedHash)?;
+        }
         Ok(())
     }
 
@@ -970,11 +1888,20 @@ impl Tx {
     #[cfg(feature = "ferveo-tpke")]
     pub fn encrypt(&mut self, pubkey: &EncryptionKey) {
         let header_hash = self.header_hash();
+        let unshield =
+            self.header.wrapper().and_then(|wrapper| wrapper.unshield);
         let mut plaintexts = vec![];
         // Iterate backwrds to sidestep the effects of deletion on indexing
         for i in (0..self.sections.len()).rev() {
             match &self.sections[i] {
                 Section::Signature(sig) if sig.target == header_hash => {}
+                // The fee unshielding is executed before decryption, so it
+                // must remain in the clear
+                section @ Section::MaspTx(_)
+                    if unshield == Some(section.get_hash()) => {}
+                // Memos are checked and reported before decryption, so they
+                // must remain in the clear
+                Section::Memo(_) => {}
                 // Add eligible section to the list of sections to encrypt
                 _ => plaintexts.push(self.sections.remove(i)),
             }
@@ -998,6 +1925,8 @@ impl Tx {
     /// the Tx and verify it is of the appropriate form. This means
     /// 1. The wrapper tx is indeed signed
     /// 2. The signature is valid
+    /// 3. If the fee is paid by a sponsor, the sponsor signed it too
+    /// 4. Every signature section over it holds only valid signatures
     pub fn validate_header(&self) -> std::result::Result<(), TxError> {
         match &self.header.tx_type {
             // verify signature and extract signed data
@@ -1009,6 +1938,23 @@ impl Tx {
                             err
                         ))
                     })?;
+                if let Some(sponsor) = &wrapper.sponsor {
+                    self.verify_signature(sponsor, &self.header_hash())
+                        .map_err(|err| {
+                            TxError::SigError(format!(
+                                "WrapperTx sponsor signature verification \
+                                 failed: {}",
+                                err
+                            ))
+                        })?;
+                }
+                self.verify_signature_sections(&self.header_hash())
+                    .map_err(|err| {
+                        TxError::SigError(format!(
+                            "WrapperTx multisignature verification failed: {}",
+                            err
+                        ))
+                    })?;
                 Ok(())
             }
             // verify signature and extract signed data
@@ -1314,4 +2260,494 @@ mod tests {
             plaintext.try_to_vec().expect("Test failed"),
         );
     }
+
+    /// Test that the inner txs of a batch designate their own code and data,
+    /// in order
+    #[test]
+    fn test_inner_txs() {
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_code(Code::new("first code".as_bytes().to_owned()));
+        tx.set_data(Data::new("first data".as_bytes().to_owned()));
+        assert!(!tx.is_batch());
+        assert_eq!(tx.inner_txs().len(), 1);
+
+        tx.add_inner_tx(
+            Code::new("second code".as_bytes().to_owned()),
+            Data::new("second data".as_bytes().to_owned()),
+        );
+        assert!(tx.is_batch());
+        let inner_txs = tx.inner_txs();
+        assert_eq!(inner_txs.len(), 2);
+        assert!(inner_txs.iter().all(|inner_tx| !inner_tx.is_batch()));
+        assert_eq!(
+            inner_txs[0].data().expect("Test failed"),
+            "first data".as_bytes()
+        );
+        assert_eq!(
+            inner_txs[1].data().expect("Test failed"),
+            "second data".as_bytes()
+        );
+        assert_eq!(
+            inner_txs[1].code().expect("Test failed"),
+            "second code".as_bytes()
+        );
+    }
+
+    /// Test that decrypting a batch fails if the sections of one of its
+    /// inner txs are missing
+    #[cfg(feature = "ferveo-tpke")]
+    #[test]
+    fn test_decrypt_batch() {
+        // The trivial public - private keypair
+        let pubkey = EncryptionKey(<EllipticCurve as PairingEngine>::G1Affine::prime_subgroup_generator());
+        let privkey = <EllipticCurve as PairingEngine>::G2Affine::prime_subgroup_generator();
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_code(Code::new("first code".as_bytes().to_owned()));
+        tx.set_data(Data::new("first data".as_bytes().to_owned()));
+        tx.add_inner_tx(
+            Code::new("second code".as_bytes().to_owned()),
+            Data::new("second data".as_bytes().to_owned()),
+        );
+
+        let mut encrypted = tx.clone();
+        encrypted.encrypt(&pubkey);
+        encrypted.decrypt(privkey).expect("Test failed");
+        assert_eq!(encrypted.inner_txs().len(), 2);
+
+        // Drop the data of the second inner tx
+        let mut incomplete = tx;
+        incomplete.sections.pop();
+        incomplete.encrypt(&pubkey);
+        assert!(matches!(
+            incomplete.decrypt(privkey),
+            Err(WrapperTxErr::DecryptedHash)
+        ));
+    }
+
+    /// Test that compressed sections are smaller on the wire, yet are
+    /// transparently decompressed and hash to the same value as their
+    /// uncompressed counterparts
+    #[test]
+    fn test_compressed_sections() {
+        let code = vec![7u8; 4096];
+        let data = vec![42u8; 4096];
+
+        let mut plain = Tx::new(TxType::Raw);
+        plain.set_code(Code::new(code.clone()));
+        plain.set_data(Data::new(data.clone()));
+
+        let mut compressed = plain.clone();
+        let mut code_sec = Code::new_compressed(code.clone());
+        code_sec.salt = plain.sections[0].code_sec().expect("Test failed").salt;
+        let mut data_sec = Data::new_compressed(data.clone());
+        data_sec.salt = plain.sections[1].data().expect("Test failed").salt;
+        compressed.sections =
+            vec![Section::Code(code_sec), Section::Data(data_sec)];
+
+        // the header commitments of both txs match
+        assert_eq!(
+            plain.sections[0].get_hash(),
+            compressed.sections[0].get_hash()
+        );
+        assert_eq!(
+            plain.sections[1].get_hash(),
+            compressed.sections[1].get_hash()
+        );
+        assert!(compressed.to_bytes().len() < plain.to_bytes().len());
+        assert_eq!(compressed.code().expect("Test failed"), code);
+        assert_eq!(compressed.data().expect("Test failed"), data);
+
+        // decompression preserves the hashes of the sections
+        let header_hash = compressed.header_hash();
+        compressed.decompress().expect("Test failed");
+        assert_eq!(compressed.header_hash(), header_hash);
+        assert!(matches!(
+            &compressed.sections[0],
+            Section::Code(Code {
+                compression: Compression::None,
+                code: Commitment::Id(decompressed),
+                ..
+            }) if *decompressed == code
+        ));
+        assert_eq!(compressed.data().expect("Test failed"), data);
+
+        // corrupt compressed sections don't match their commitment
+        let mut corrupt = Data::new_compressed(data);
+        corrupt.data.truncate(4);
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_data(corrupt);
+        assert!(tx.data().is_none());
+        assert!(tx.decompress().is_err());
+    }
+
+    /// Test that the signatures of multiple signers over the same hash can
+    /// be aggregated into a single section, which must meet the threshold of
+    /// the account
+    #[test]
+    fn test_multisig_signature_section() {
+        use crate::types::key::testing::{keypair_1, keypair_2};
+
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_data(Data::new("data".as_bytes().to_owned()));
+        let header_hash = tx.header_hash();
+        let pks = vec![keypair_1().ref_to(), keypair_2().ref_to()];
+
+        // each co-signer signs a detached section offline
+        let mut first = Signature::unsigned(&header_hash);
+        first.sign(&keypair_1());
+        let mut second = Signature::unsigned(&header_hash);
+        second.sign(&keypair_2());
+
+        // a single signature doesn't meet the threshold
+        let mut unsigned = tx.clone();
+        unsigned.add_section(Section::Signature(first.clone()));
+        assert!(unsigned.verify_multisig(&pks, 2, &header_hash).is_err());
+
+        // signatures over other hashes can't be aggregated
+        let other =
+            Signature::new(&crate::types::hash::Hash::default(), &keypair_2());
+        assert!(first.aggregate(&other).is_err());
+
+        // a section holding an invalid signature is rejected
+        let mut forged_section = first.clone();
+        forged_section
+            .signatures
+            .extend(other.signatures.clone().into_iter());
+        let mut forged = tx.clone();
+        forged.add_section(Section::Signature(forged_section));
+        assert!(forged.verify_signature_sections(&header_hash).is_err());
+
+        // and so is a section without signatures
+        let mut empty = tx.clone();
+        empty
+            .add_section(Section::Signature(Signature::unsigned(&header_hash)));
+        assert!(empty.verify_signature_sections(&header_hash).is_err());
+
+        first.aggregate(&second).expect("Test failed");
+        tx.add_section(Section::Signature(first));
+        tx.verify_signature_sections(&header_hash)
+            .expect("Test failed");
+        tx.verify_multisig(&pks, 2, &header_hash)
+            .expect("Test failed");
+        tx.verify_signature(&keypair_1().ref_to(), &header_hash)
+            .expect("Test failed");
+        tx.verify_signature(&keypair_2().ref_to(), &header_hash)
+            .expect("Test failed");
+    }
+
+    /// Test that a multisig account's threshold is met by the distinct
+    /// signers among its keys, whichever sections hold their signatures
+    #[test]
+    fn test_verify_multisig() {
+        use crate::types::key::testing::{keypair_1, keypair_2};
+
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_data(Data::new("data".as_bytes().to_owned()));
+        let data_hash = *tx.data_sechash();
+        let pks = vec![keypair_1().ref_to(), keypair_2().ref_to()];
+
+        tx.add_section(Section::Signature(Signature::new(
+            &data_hash,
+            &keypair_1(),
+        )));
+        tx.verify_multisig(&pks, 1, &data_hash)
+            .expect("Test failed");
+        assert!(tx.verify_multisig(&pks, 2, &data_hash).is_err());
+        // the same signer is only counted once
+        let duplicated = vec![keypair_1().ref_to(), keypair_1().ref_to()];
+        assert!(tx.verify_multisig(&duplicated, 2, &data_hash).is_err());
+
+        tx.add_section(Section::Signature(Signature::new(
+            &data_hash,
+            &keypair_2(),
+        )));
+        tx.verify_multisig(&pks, 2, &data_hash)
+            .expect("Test failed");
+        // signatures over other hashes don't count
+        assert!(tx.verify_multisig(&pks, 1, &tx.header_hash()).is_err());
+    }
+
+    /// Test that signatures over the human readable representation of a
+    /// tx are verified, as long as the text commits to the header hash
+    #[test]
+    fn test_textual_signature() {
+        use crate::types::address::nam;
+        use crate::types::address::testing::{
+            established_address_1, established_address_2,
+        };
+        use crate::types::key::testing::keypair_1;
+        use crate::types::token::{Amount, Transfer};
+
+        let transfer = Transfer {
+            source: established_address_1(),
+            target: established_address_2(),
+            token: nam(),
+            sub_prefix: None,
+            amount: Amount::from(10u64),
+            key: None,
+            shielded: None,
+        };
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_data(Data::new(transfer.try_to_vec().expect("Test failed")));
+        let header_hash = tx.header_hash();
+
+        let text = tx.signing_text();
+        assert!(text.contains(&format!("Target: {}", transfer.target)));
+        assert!(text.contains(&format!("Amount: {}", transfer.amount)));
+
+        let pk = keypair_1().ref_to();
+        let mut signed = tx.clone();
+        signed.add_section(Section::Signature(Signature::new_textual(
+            &tx,
+            &keypair_1(),
+        )));
+        signed
+            .verify_signature(&pk, &header_hash)
+            .expect("Test failed");
+        signed
+            .verify_signature_sections(&header_hash)
+            .expect("Test failed");
+
+        // signed text that doesn't commit to the header is rejected
+        let mut section = Signature::unsigned(&header_hash);
+        section.mode = SignMode::Textual("Amount: 10".to_string());
+        section.sign(&keypair_1());
+        let mut forged = tx;
+        forged.add_section(Section::Signature(section));
+        assert!(forged.verify_signature(&pk, &header_hash).is_err());
+    }
+
+    /// Test that EIP-191 signatures over the header hash are verified, and
+    /// only by secp256k1 keys
+    #[test]
+    fn test_eip191_signature() {
+        use crate::types::key::testing::keypair_1;
+
+        let mut tx = Tx::new(TxType::Raw);
+        tx.header.chain_id = ChainId("test-chain".to_string());
+        let header_hash = tx.header_hash();
+
+        let keypair: common::SecretKey =
+            secp256k1::SigScheme::from_bytes([1; 32])
+                .try_to_sk()
+                .unwrap();
+        let pk = keypair.ref_to();
+        let section = Signature::new_eip191(&header_hash, &keypair);
+        assert_eq!(section.mode, SignMode::Eip191);
+        let mut signed = tx.clone();
+        signed.add_section(Section::Signature(section));
+        signed
+            .verify_signature(&pk, &header_hash)
+            .expect("Test failed");
+
+        // the signature is not valid in the other modes
+        let mut section = Signature::new_eip191(&header_hash, &keypair);
+        section.mode = SignMode::Hash;
+        let mut forged = tx.clone();
+        forged.add_section(Section::Signature(section));
+        assert!(forged.verify_signature(&pk, &header_hash).is_err());
+
+        // ed25519 keys can't sign in this mode
+        let pk = keypair_1().ref_to();
+        let mut forged = tx;
+        forged.add_section(Section::Signature(Signature::new_eip191(
+            &header_hash,
+            &keypair_1(),
+        )));
+        assert!(forged.verify_signature(&pk, &header_hash).is_err());
+    }
+
+    /// Test that signing a section through a signer is the same as signing
+    /// it with the secret key of the signer
+    #[test]
+    fn test_sign_with_signer() {
+        use crate::types::key::testing::keypair_1;
+
+        let mut tx = Tx::new(TxType::Raw);
+        tx.header.chain_id = ChainId("test-chain".to_string());
+        let header_hash = tx.header_hash();
+
+        let keypair = keypair_1();
+        let mut section = Signature::unsigned(&header_hash);
+        section.sign_with(&keypair).expect("Test failed");
+        tx.add_section(Section::Signature(section));
+        tx.verify_signature(&keypair.ref_to(), &header_hash)
+            .expect("Test failed");
+
+        // signers only sign raw messages
+        let mut section = Signature::unsigned(&header_hash);
+        section.mode = SignMode::Eip191;
+        assert!(matches!(
+            section.sign_with(&keypair),
+            Err(SignerError::Unsupported(_))
+        ));
+    }
+
+    /// Test that the memo of a tx is committed to by its header hash and
+    /// that its length is bounded
+    #[test]
+    fn test_memo_section() {
+        let mut tx = Tx::new(TxType::Raw);
+        assert!(tx.memo().is_none());
+        assert!(tx.validate_memo(0));
+        let header_hash = tx.header_hash();
+
+        tx.set_memo(Memo::new("deposit-42".to_string()));
+        assert_eq!(tx.memo(), Some("deposit-42".to_string()));
+        assert_ne!(tx.header_hash(), header_hash);
+        assert!(tx.validate_memo(DEFAULT_MAX_MEMO_LEN));
+        assert!(!tx.validate_memo(4));
+
+        // a memo committed to by the header must be present
+        tx.sections.clear();
+        assert!(tx.memo().is_none());
+        assert!(!tx.validate_memo(DEFAULT_MAX_MEMO_LEN));
+    }
+
+    /// Test that the raw header hash doesn't depend on the tx type
+    #[test]
+    fn test_raw_header_hash() {
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_data(Data::new(b"data".to_vec()));
+        let raw_header_hash = tx.raw_header_hash();
+        assert_eq!(raw_header_hash, tx.header_hash());
+
+        tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted {
+            #[cfg(not(feature = "mainnet"))]
+            has_valid_pow: false,
+        }));
+        assert_ne!(tx.header_hash(), raw_header_hash);
+        assert_eq!(tx.raw_header_hash(), raw_header_hash);
+    }
+
+    /// Make a version 2 tx committing to a data section
+    fn versioned_tx() -> Tx {
+        let mut tx = Tx::new(TxType::Raw);
+        tx.header.chain_id = ChainId("test-chain".to_string());
+        tx.set_data(Data::new(b"data".to_vec()));
+        tx
+    }
+
+    /// Get the hash of the given legacy header bytes
+    fn legacy_header_hash(bytes: Vec<u8>) -> crate::types::hash::Hash {
+        crate::types::hash::Hash(Sha256::digest(bytes).into())
+    }
+
+    /// Test that the txs of the latest version round trip
+    #[test]
+    fn test_decode_versioned_latest() {
+        let tx = versioned_tx();
+        let decoded = Tx::decode_versioned(&tx.try_to_vec().unwrap())
+            .expect("Test failed");
+        assert_eq!(decoded.header.version, TX_VERSION);
+        assert_eq!(decoded.header_hash(), tx.header_hash());
+        let decoded = Tx::try_from(tx.to_bytes().as_slice()).unwrap();
+        assert_eq!(decoded.header_hash(), tx.header_hash());
+    }
+
+    /// Test the migration of version 0 txs, whose header hash must be
+    /// preserved
+    #[test]
+    fn test_decode_versioned_v0() {
+        let tx = versioned_tx();
+        let legacy = TxV0 {
+            header: HeaderV0::from(&tx.header),
+            sections: tx.sections.clone(),
+        };
+        let header_hash =
+            legacy_header_hash(legacy.header.try_to_vec().unwrap());
+
+        let decoded = Tx::decode_versioned(&legacy.try_to_vec().unwrap())
+            .expect("Test failed");
+        assert_eq!(decoded.header.version, 0);
+        assert_eq!(decoded.header.chain_id, tx.header.chain_id);
+        assert!(decoded.header.batch.is_empty());
+        assert!(decoded.header.memo_hash.is_none());
+        assert_eq!(decoded.data(), Some(b"data".to_vec()));
+        assert_eq!(decoded.header_hash(), header_hash);
+    }
+
+    /// Test the migration of version 1 txs, whose header hash must be
+    /// preserved
+    #[test]
+    fn test_decode_versioned_v1() {
+        let mut tx = versioned_tx();
+        tx.add_inner_tx(Code::new(vec![]), Data::new(b"inner".to_vec()));
+        let legacy = TxV1 {
+            header: HeaderV1::from(&tx.header),
+            sections: tx.sections.clone(),
+        };
+        let header_hash =
+            legacy_header_hash(legacy.header.try_to_vec().unwrap());
+
+        let decoded = Tx::decode_versioned(&legacy.try_to_vec().unwrap())
+            .expect("Test failed");
+        assert_eq!(decoded.header.version, 1);
+        assert_eq!(decoded.header.batch, tx.header.batch);
+        assert!(decoded.header.memo_hash.is_none());
+        assert_eq!(decoded.inner_txs().len(), 2);
+        assert_eq!(decoded.header_hash(), header_hash);
+
+        // fields the legacy layout lacks are committed to all the same
+        let mut with_memo = decoded;
+        with_memo.set_memo(Memo::new("memo".to_string()));
+        assert_ne!(with_memo.header_hash(), header_hash);
+    }
+
+    /// Test that txs of future versions are reported as such
+    #[test]
+    fn test_decode_versioned_future() {
+        let mut tx = versioned_tx();
+        tx.header.version = TX_VERSION + 1;
+        let err = Tx::decode_versioned(&tx.try_to_vec().unwrap()).unwrap_err();
+        assert!(
+            matches!(err, Error::UnsupportedTxVersion(v) if v == TX_VERSION + 1)
+        );
+    }
+
+    /// Test that the decode limits of txs are enforced
+    #[test]
+    fn test_decode_with_limits() {
+        let mut tx = versioned_tx();
+        tx.set_data(Data::new(vec![0; 64]));
+        let bytes = tx.to_bytes();
+        let limits = TxDecodeLimits::default();
+        assert!(Tx::decode_with_limits(&bytes, &limits).is_ok());
+        assert!(Tx::try_from(bytes.as_slice()).is_ok());
+
+        let tight = TxDecodeLimits {
+            max_tx_len: bytes.len() as u64 - 1,
+            ..limits
+        };
+        assert!(matches!(
+            Tx::decode_with_limits(&bytes, &tight),
+            Err(Error::TxTooLarge { .. })
+        ));
+
+        let tight = TxDecodeLimits {
+            max_sections: 1,
+            ..limits
+        };
+        assert!(matches!(
+            Tx::decode_with_limits(&bytes, &tight),
+            Err(Error::TooManySections { count: 2, max: 1 })
+        ));
+
+        let tight = TxDecodeLimits {
+            max_section_len: 32,
+            ..limits
+        };
+        assert!(matches!(
+            Tx::decode_with_limits(&bytes, &tight),
+            Err(Error::SectionTooLarge { index: 1, .. })
+        ));
+
+        // governance can only tighten the hard caps
+        let loose = TxDecodeLimits {
+            max_sections: u64::MAX,
+            max_section_len: u64::MAX,
+            max_tx_len: u64::MAX,
+        };
+        assert_eq!(loose.capped(), limits);
+    }
 }
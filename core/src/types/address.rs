@@ -14,6 +14,7 @@ use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 use crate::ibc::signer::Signer;
+use crate::types::ethereum_events::{EthAddress, ETH_ADDRESS_LEN};
 use crate::types::key;
 use crate::types::key::PublicKeyHash;
 
@@ -64,6 +65,9 @@ pub const POS_SLASH_POOL: Address =
     Address::Internal(InternalAddress::PosSlashPool);
 /// Internal Governance address
 pub const GOV: Address = Address::Internal(InternalAddress::Governance);
+/// Internal Ethereum bridge pool address
+pub const ETH_BRIDGE_POOL: Address =
+    Address::Internal(InternalAddress::EthBridgePool);
 
 /// Raw strings used to produce internal addresses. All the strings must begin
 /// with `PREFIX_INTERNAL` and be `FIXED_LEN_STRING_BYTES` characters long.
@@ -87,8 +91,14 @@ mod internal {
         "ibc::IBC Burn Address                        ";
     pub const IBC_MINT: &str =
         "ibc::IBC Mint Address                        ";
+    pub const IBC_FORWARD: &str =
+        "ibc::IBC Forward Address                     ";
+    pub const IBC_FEE_ESCROW: &str =
+        "ibc::IBC Fee Escrow Address                  ";
     pub const ETH_BRIDGE: &str =
         "ano::ETH Bridge Address                      ";
+    pub const ETH_BRIDGE_POOL: &str =
+        "ano::ETH Bridge Pool Address                 ";
     pub const REPLAY_PROTECTION: &str =
         "ano::Replay Protection                       ";
 }
@@ -101,6 +111,10 @@ const PREFIX_IMPLICIT: &str = "imp";
 const PREFIX_INTERNAL: &str = "ano";
 /// Fixed-length address strings prefix for IBC addresses.
 const PREFIX_IBC: &str = "ibc";
+/// Fixed-length address strings prefix for wrapped ERC20 addresses.
+const PREFIX_ETH: &str = "eth";
+/// Fixed-length address strings prefix for interchain accounts.
+const PREFIX_ICA: &str = "ica";
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -115,6 +129,10 @@ pub enum DecodeError {
     UnexpectedBech32Variant(bech32::Variant),
     #[error("Invalid address encoding")]
     InvalidInnerEncoding(std::io::Error),
+    #[error("Invalid Bech32m human-readable part {0}: {1}")]
+    InvalidHrp(String, String),
+    #[error("The address is not in its canonical encoding")]
+    NonCanonicalEncoding,
 }
 
 /// Result of a function that may fail
@@ -233,12 +251,27 @@ impl Address {
                     }
                     InternalAddress::IbcBurn => internal::IBC_BURN.to_string(),
                     InternalAddress::IbcMint => internal::IBC_MINT.to_string(),
+                    InternalAddress::IbcForward => {
+                        internal::IBC_FORWARD.to_string()
+                    }
+                    InternalAddress::IbcFeeEscrow => {
+                        internal::IBC_FEE_ESCROW.to_string()
+                    }
                     InternalAddress::EthBridge => {
                         internal::ETH_BRIDGE.to_string()
                     }
+                    InternalAddress::EthBridgePool => {
+                        internal::ETH_BRIDGE_POOL.to_string()
+                    }
+                    InternalAddress::Erc20(asset) => {
+                        format!("{}::{}", PREFIX_ETH, HEXUPPER.encode(&asset.0))
+                    }
                     InternalAddress::ReplayProtection => {
                         internal::REPLAY_PROTECTION.to_string()
                     }
+                    InternalAddress::IbcAccount(hash) => {
+                        format!("{}::{}", PREFIX_ICA, hash)
+                    }
                 };
                 debug_assert_eq!(string.len(), FIXED_LEN_STRING_BYTES);
                 string
@@ -306,6 +339,9 @@ impl Address {
                 internal::ETH_BRIDGE => {
                     Ok(Address::Internal(InternalAddress::EthBridge))
                 }
+                internal::ETH_BRIDGE_POOL => {
+                    Ok(Address::Internal(InternalAddress::EthBridgePool))
+                }
                 internal::REPLAY_PROTECTION => {
                     Ok(Address::Internal(InternalAddress::ReplayProtection))
                 }
@@ -325,6 +361,12 @@ impl Address {
                 internal::IBC_MINT => {
                     Ok(Address::Internal(InternalAddress::IbcMint))
                 }
+                internal::IBC_FORWARD => {
+                    Ok(Address::Internal(InternalAddress::IbcForward))
+                }
+                internal::IBC_FEE_ESCROW => {
+                    Ok(Address::Internal(InternalAddress::IbcFeeEscrow))
+                }
                 _ if raw.len() == HASH_HEX_LEN => Ok(Address::Internal(
                     InternalAddress::IbcToken(raw.to_string()),
                 )),
@@ -333,6 +375,24 @@ impl Address {
                     "Invalid IBC internal address",
                 )),
             },
+            Some((PREFIX_ETH, raw)) => {
+                let raw = HEXUPPER.decode(raw.as_bytes()).map_err(|e| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+                })?;
+                let asset: [u8; ETH_ADDRESS_LEN] =
+                    raw.try_into().map_err(|_| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            "Wrapped ERC20 address must be 40 characters long",
+                        )
+                    })?;
+                Ok(Address::Internal(InternalAddress::Erc20(EthAddress(
+                    asset,
+                ))))
+            }
+            Some((PREFIX_ICA, raw)) if raw.len() == HASH_HEX_LEN => Ok(
+                Address::Internal(InternalAddress::IbcAccount(raw.to_string())),
+            ),
             _ => Err(Error::new(
                 ErrorKind::InvalidData,
                 "Invalid address prefix",
@@ -413,6 +473,103 @@ impl TryFrom<Signer> for Address {
     }
 }
 
+/// A compact Bech32m encoding of addresses under a configurable
+/// human-readable part (HRP), in the manner of Cosmos' ADR-028.
+///
+/// Unlike the default encoding of [`Address`], which encodes the address'
+/// fixed-length string, the data of the compact encoding is the Borsh
+/// encoding of the address, such that addresses are shorter to type and fit
+/// in smaller QR codes. Decoding is strict: the checksum must be a valid
+/// Bech32m checksum, the HRP must be the configured one, the padding must be
+/// zero and the data must be the canonical encoding of an address. As with
+/// any Bech32m string, an all upper-case encoding is accepted, e.g. from the
+/// alphanumeric mode of QR codes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactEncoding {
+    hrp: String,
+}
+
+impl Default for CompactEncoding {
+    fn default() -> Self {
+        Self {
+            hrp: ADDRESS_HRP.to_string(),
+        }
+    }
+}
+
+impl CompactEncoding {
+    /// Make a compact encoding with the given HRP, which must be lower-case
+    /// and between 1 and 83 ASCII characters in range 33-126.
+    pub fn new(hrp: impl Into<String>) -> Result<Self> {
+        let hrp = hrp.into();
+        if hrp.to_lowercase() != hrp {
+            return Err(DecodeError::InvalidHrp(
+                hrp,
+                "it must be lower-case".to_string(),
+            ));
+        }
+        // Encoding rejects invalid HRPs
+        if let Err(err) =
+            bech32::encode(&hrp, Vec::<bech32::u5>::new(), BECH32M_VARIANT)
+        {
+            return Err(DecodeError::InvalidHrp(hrp, err.to_string()));
+        }
+        Ok(Self { hrp })
+    }
+
+    /// The HRP of this encoding
+    pub fn hrp(&self) -> &str {
+        &self.hrp
+    }
+
+    /// Encode an address in the compact encoding
+    pub fn encode(&self, address: &Address) -> String {
+        let bytes = address
+            .try_to_vec()
+            .expect("Encoding an address shouldn't fail");
+        bech32::encode(&self.hrp, bytes.to_base32(), BECH32M_VARIANT)
+            .unwrap_or_else(|_| {
+                panic!(
+                    "The human-readable part {} should never cause a failure",
+                    self.hrp
+                )
+            })
+    }
+
+    /// Decode an address from the compact encoding
+    pub fn decode(&self, string: impl AsRef<str>) -> Result<Address> {
+        let string = string.as_ref();
+        let (prefix, data_base32, variant) =
+            bech32::decode(string).map_err(DecodeError::DecodeBech32)?;
+        if prefix != self.hrp {
+            return Err(DecodeError::UnexpectedBech32Prefix(
+                prefix,
+                self.hrp.clone(),
+            ));
+        }
+        match variant {
+            BECH32M_VARIANT => {}
+            _ => return Err(DecodeError::UnexpectedBech32Variant(variant)),
+        }
+        let bytes: Vec<u8> = FromBase32::from_base32(&data_base32)
+            .map_err(DecodeError::DecodeBase32)?;
+        let address = Address::try_from_slice(&bytes)
+            .map_err(DecodeError::InvalidInnerEncoding)?;
+        // Only accept addresses that also have a valid default encoding,
+        // e.g. not IBC token addresses with hashes of the wrong length
+        let canonical = Address::try_from_fixed_len_string(
+            &mut &address.to_fixed_len_string()[..],
+        )
+        .map_err(DecodeError::InvalidInnerEncoding)?;
+        if canonical != address
+            || self.encode(&address) != string.to_lowercase()
+        {
+            return Err(DecodeError::NonCanonicalEncoding);
+        }
+        Ok(address)
+    }
+}
+
 /// An established address is generated on-chain
 #[derive(
     Debug,
@@ -446,6 +603,19 @@ impl EstablishedAddressGen {
         }
     }
 
+    /// Initialize the generator of the addresses of the accounts initialized
+    /// by the tx with the given hash, which is the hash of its header with the
+    /// raw tx type. Unlike the generator of the storage, it doesn't depend on
+    /// the txs applied before, such that the addresses can be computed before
+    /// the tx is broadcast. The `n`-th account initialized by the tx gets the
+    /// `n`-th address generated with an empty source of randomness.
+    pub fn for_tx(tx_hash: &crate::types::hash::Hash) -> Self {
+        let bytes = [b"init_account".as_ref(), tx_hash.0.as_ref()].concat();
+        Self {
+            last_hash: Sha256::digest(&bytes).into(),
+        }
+    }
+
     /// Generate a new established address. Requires a source of randomness as
     /// arbitrary bytes. In the ledger, this could be some unpredictable value,
     /// such as hash of the transaction that has initialized the new address.
@@ -527,14 +697,24 @@ pub enum InternalAddress {
     IbcBurn,
     /// Mint tokens from this address with IBC token transfer
     IbcMint,
+    /// Holds the tokens of the IBC token transfers forwarded to another chain
+    IbcForward,
+    /// Escrow for the fees of the relayers of IBC packets
+    IbcFeeEscrow,
     /// Governance address
     Governance,
     /// SlashFund address for governance
     SlashFund,
     /// Bridge to Ethereum
     EthBridge,
+    /// Pool of the transfers to Ethereum
+    EthBridgePool,
+    /// ERC20 token wrapped by the bridge to Ethereum
+    Erc20(EthAddress),
     /// Replay protection contains transactions' hash
     ReplayProtection,
+    /// Interchain account opened by a controller chain over IBC
+    IbcAccount(String),
 }
 
 impl InternalAddress {
@@ -551,6 +731,20 @@ impl InternalAddress {
             format!("{:.width$x}", hasher.finalize(), width = HASH_HEX_LEN);
         InternalAddress::IbcToken(hash)
     }
+
+    /// Get the address of the interchain account that the controller port of
+    /// a counterparty chain owns over the connection of this chain
+    pub fn ibc_account_address(
+        connection_id: String,
+        controller_port_id: String,
+    ) -> Self {
+        let mut hasher = Sha256::new();
+        let s = format!("{}/{}", connection_id, controller_port_id);
+        hasher.update(&s);
+        let hash =
+            format!("{:.width$x}", hasher.finalize(), width = HASH_HEX_LEN);
+        InternalAddress::IbcAccount(hash)
+    }
 }
 
 impl Display for InternalAddress {
@@ -569,8 +763,13 @@ impl Display for InternalAddress {
                 Self::IbcEscrow => "IbcEscrow".to_string(),
                 Self::IbcBurn => "IbcBurn".to_string(),
                 Self::IbcMint => "IbcMint".to_string(),
+                Self::IbcForward => "IbcForward".to_string(),
+                Self::IbcFeeEscrow => "IbcFeeEscrow".to_string(),
                 Self::EthBridge => "EthBridge".to_string(),
+                Self::EthBridgePool => "EthBridgePool".to_string(),
+                Self::Erc20(asset) => format!("Erc20: {}", asset),
                 Self::ReplayProtection => "ReplayProtection".to_string(),
+                Self::IbcAccount(hash) => format!("IbcAccount: {}", hash),
             }
         )
     }
@@ -629,7 +828,8 @@ pub fn masp_tx_key() -> crate::types::key::common::SecretKey {
 
 /// Temporary helper for testing, a hash map of tokens addresses with their
 /// MASP XAN incentive schedules. If the reward is (a, b) then a rewarded tokens
-/// are dispensed for every b possessed tokens.
+/// are dispensed for every b possessed tokens. These are the reward rates set
+/// in the parameters at genesis, which governance can update later.
 pub fn masp_rewards() -> HashMap<Address, (u64, u64)> {
     vec![
         (nam(), (0, 100)),
@@ -702,6 +902,121 @@ pub mod tests {
             let bytes = address.try_to_vec().unwrap();
             assert_eq!(bytes.len(), ESTABLISHED_ADDRESS_BYTES_LEN);
         }
+
+        #[test]
+        /// Check that the compact encoding of addresses round trips under any
+        /// valid HRP, also when upper-cased
+        fn test_compact_encoding_roundtrip(
+            address in testing::arb_address(),
+            hrp in "[a-z0-9]{1,10}",
+        ) {
+            let encoding = CompactEncoding::new(hrp).unwrap();
+            let encoded = encoding.encode(&address);
+            assert_eq!(encoding.decode(&encoded).unwrap(), address);
+            assert_eq!(
+                encoding.decode(encoded.to_uppercase()).unwrap(),
+                address
+            );
+        }
+
+        #[test]
+        /// Check that any single character substitution in a compact encoded
+        /// address is detected
+        fn test_compact_encoding_substitution(
+            address in testing::arb_address(),
+            index in any::<prop::sample::Index>(),
+            substitute in prop::sample::select(
+                b"qpzry9x8gf2tvdw0s3jn54khce6mua7l".to_vec()
+            ),
+        ) {
+            let encoding = CompactEncoding::default();
+            let encoded = encoding.encode(&address);
+            let mut bytes = encoded.clone().into_bytes();
+            let index = index.index(bytes.len());
+            prop_assume!(bytes[index] != substitute);
+            bytes[index] = substitute;
+            let corrupted = String::from_utf8(bytes).unwrap();
+            assert!(encoding.decode(corrupted).is_err());
+        }
+
+        #[test]
+        /// Check that decoding arbitrary strings never panics and only
+        /// succeeds on canonical encodings
+        fn test_compact_encoding_fuzz(string in ".*") {
+            let encoding = CompactEncoding::default();
+            if let Ok(address) = encoding.decode(&string) {
+                assert_eq!(encoding.encode(&address), string.to_lowercase());
+            }
+        }
+
+        #[test]
+        /// Check that decoding arbitrary data under a valid checksum never
+        /// panics and only succeeds on canonical encodings
+        fn test_compact_encoding_fuzz_data(data in any::<Vec<u8>>()) {
+            let encoding = CompactEncoding::default();
+            let string = bech32::encode(
+                encoding.hrp(),
+                data.to_base32(),
+                BECH32M_VARIANT,
+            )
+            .unwrap();
+            if let Ok(address) = encoding.decode(&string) {
+                assert_eq!(address.try_to_vec().unwrap(), data);
+            }
+        }
+    }
+
+    /// Check the compact encoding against known vectors and its strictness
+    #[test]
+    fn test_compact_encoding() {
+        let encoding = CompactEncoding::default();
+        let address = testing::established_address_1();
+        let encoded = encoding.encode(&address);
+        assert!(encoded.starts_with("atest1"));
+        assert!(encoded.len() < ADDRESS_LEN);
+        assert_eq!(encoding.decode(&encoded).unwrap(), address);
+
+        // Another HRP
+        let other = CompactEncoding::new("nam").unwrap();
+        assert!(matches!(
+            other.decode(&encoded),
+            Err(DecodeError::UnexpectedBech32Prefix(_, _))
+        ));
+
+        // Mixed case
+        let mixed =
+            format!("{}{}", &encoded[..10].to_uppercase(), &encoded[10..]);
+        assert!(matches!(
+            encoding.decode(mixed),
+            Err(DecodeError::DecodeBech32(_))
+        ));
+
+        // Bech32 instead of Bech32m
+        let bytes = address.try_to_vec().unwrap();
+        let bech32 =
+            bech32::encode(ADDRESS_HRP, bytes.to_base32(), Variant::Bech32)
+                .unwrap();
+        assert!(matches!(
+            encoding.decode(bech32),
+            Err(DecodeError::UnexpectedBech32Variant(_))
+        ));
+
+        // Trailing data
+        let mut bytes = address.try_to_vec().unwrap();
+        bytes.push(0);
+        let trailing =
+            bech32::encode(ADDRESS_HRP, bytes.to_base32(), BECH32M_VARIANT)
+                .unwrap();
+        assert!(encoding.decode(trailing).is_err());
+
+        // The default encoding
+        assert!(encoding.decode(address.encode()).is_err());
+
+        // Invalid HRPs
+        assert!(CompactEncoding::new("").is_err());
+        assert!(CompactEncoding::new("Nam").is_err());
+        assert!(CompactEncoding::new("n am").is_err());
+        assert!(CompactEncoding::new("a".repeat(84)).is_err());
     }
 }
 
@@ -838,10 +1153,14 @@ pub mod testing {
             InternalAddress::IbcEscrow => {}
             InternalAddress::IbcBurn => {}
             InternalAddress::IbcMint => {}
+            InternalAddress::IbcForward => {}
+            InternalAddress::IbcFeeEscrow => {}
             InternalAddress::EthBridge => {}
-            InternalAddress::ReplayProtection => {} /* Add new addresses in
-                                                     * the
-                                                     * `prop_oneof` below. */
+            InternalAddress::EthBridgePool => {}
+            InternalAddress::Erc20(_) => {}
+            InternalAddress::ReplayProtection => {}
+            InternalAddress::IbcAccount(_) => {} /* Add new addresses in the
+                                                  * `prop_oneof` below. */
         };
         prop_oneof![
             Just(InternalAddress::PoS),
@@ -853,13 +1172,32 @@ pub mod testing {
             Just(InternalAddress::IbcEscrow),
             Just(InternalAddress::IbcBurn),
             Just(InternalAddress::IbcMint),
+            Just(InternalAddress::IbcForward),
+            Just(InternalAddress::IbcFeeEscrow),
             Just(InternalAddress::Governance),
             Just(InternalAddress::SlashFund),
             Just(InternalAddress::EthBridge),
-            Just(InternalAddress::ReplayProtection)
+            Just(InternalAddress::EthBridgePool),
+            arb_erc20(),
+            Just(InternalAddress::ReplayProtection),
+            arb_ibc_account(),
         ]
     }
 
+    fn arb_ibc_account() -> impl Strategy<Value = InternalAddress> {
+        (any::<u64>(), "[a-zA-Z0-9_]{2,64}").prop_map(|(counter, owner)| {
+            InternalAddress::ibc_account_address(
+                format!("connection-{}", counter),
+                format!("icacontroller-{}", owner),
+            )
+        })
+    }
+
+    fn arb_erc20() -> impl Strategy<Value = InternalAddress> {
+        any::<[u8; ETH_ADDRESS_LEN]>()
+            .prop_map(|asset| InternalAddress::Erc20(EthAddress(asset)))
+    }
+
     fn arb_ibc_token() -> impl Strategy<Value = InternalAddress> {
         // use sha2::{Digest, Sha256};
         ("[a-zA-Z0-9_]{2,128}", any::<u64>()).prop_map(|(id, counter)| {
@@ -0,0 +1,333 @@
+//! Address books and the resolution of aliases of addresses.
+//!
+//! Aliases may be resolved by any [`AddressResolver`], such as the wallet, an
+//! [`AddressBook`] loaded from an alias file, or [`EnvAliases`] overriding
+//! aliases with environment variables. Resolvers can be layered in tuples,
+//! such that the same aliases can be used by the CLI, tests and integration
+//! tooling alike.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::types::address::Address;
+use crate::types::chain::ChainId;
+
+/// The prefix of the environment variables overriding aliases, e.g.
+/// `NAMADA_ALIAS_ALICE` overrides the alias "alice"
+pub const ENV_VAR_ALIAS_PREFIX: &str = "NAMADA_ALIAS_";
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Unknown address or alias {0}")]
+    UnknownAlias(String),
+    #[error("Failed to read the address book {0}: {1}")]
+    ReadFile(String, std::io::Error),
+    #[error("Failed to decode the address book {0}: {1}")]
+    DecodeFile(String, toml::de::Error),
+    #[error("Failed to write the address book {0}: {1}")]
+    WriteFile(String, String),
+}
+
+/// Result of a function that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Resolves the aliases of addresses. Aliases are case-insensitive.
+pub trait AddressResolver {
+    /// Find the address with the given alias
+    fn find_address(&self, alias: &str) -> Option<Address>;
+
+    /// Resolve either a raw address (bech32m encoding) or an alias of an
+    /// address
+    fn resolve(&self, raw: &str) -> Result<Address> {
+        Address::decode(raw).or_else(|_| {
+            self.find_address(raw)
+                .ok_or_else(|| Error::UnknownAlias(raw.to_string()))
+        })
+    }
+}
+
+impl<T: AddressResolver + ?Sized> AddressResolver for &T {
+    fn find_address(&self, alias: &str) -> Option<Address> {
+        (**self).find_address(alias)
+    }
+}
+
+impl<T: AddressResolver + ?Sized> AddressResolver for Box<T> {
+    fn find_address(&self, alias: &str) -> Option<Address> {
+        (**self).find_address(alias)
+    }
+}
+
+/// Layered resolvers, the first of which takes precedence
+impl<A: AddressResolver, B: AddressResolver> AddressResolver for (A, B) {
+    fn find_address(&self, alias: &str) -> Option<Address> {
+        self.0
+            .find_address(alias)
+            .or_else(|| self.1.find_address(alias))
+    }
+}
+
+/// Aliases of addresses for any chain, and aliases specific to some chains.
+/// It's stored in TOML files of the form:
+///
+/// ```toml
+/// [aliases]
+/// alice = "atest1..."
+///
+/// [chains.my-chain-id]
+/// faucet = "atest1..."
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AddressBook {
+    /// Aliases for any chain, in lower-case
+    #[serde(default)]
+    aliases: BTreeMap<String, Address>,
+    /// Aliases specific to chains, which take precedence over the aliases for
+    /// any chain, in lower-case
+    #[serde(default)]
+    chains: BTreeMap<String, BTreeMap<String, Address>>,
+}
+
+impl AddressBook {
+    /// Load an address book from the given TOML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = fs::read_to_string(path).map_err(|err| {
+            Error::ReadFile(path.to_string_lossy().into_owned(), err)
+        })?;
+        let book: Self = toml::from_str(&file).map_err(|err| {
+            Error::DecodeFile(path.to_string_lossy().into_owned(), err)
+        })?;
+        // Normalize the aliases, which may be written in any case
+        Ok(Self {
+            aliases: normalize(book.aliases),
+            chains: book
+                .chains
+                .into_iter()
+                .map(|(chain_id, aliases)| (chain_id, normalize(aliases)))
+                .collect(),
+        })
+    }
+
+    /// Load an address book from the given TOML file, or an empty one if the
+    /// file doesn't exist
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self> {
+        if path.as_ref().exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Save the address book to the given TOML file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let path_str = path.to_string_lossy().into_owned();
+        let file = toml::to_string(self).map_err(|err| {
+            Error::WriteFile(path_str.clone(), err.to_string())
+        })?;
+        fs::write(path, file)
+            .map_err(|err| Error::WriteFile(path_str, err.to_string()))
+    }
+
+    /// Add an alias of an address for any chain
+    pub fn insert(&mut self, alias: impl AsRef<str>, address: Address) {
+        self.aliases.insert(alias.as_ref().to_lowercase(), address);
+    }
+
+    /// Add an alias of an address for the given chain
+    pub fn insert_for_chain(
+        &mut self,
+        chain_id: &ChainId,
+        alias: impl AsRef<str>,
+        address: Address,
+    ) {
+        self.chains
+            .entry(chain_id.as_str().to_string())
+            .or_default()
+            .insert(alias.as_ref().to_lowercase(), address);
+    }
+
+    /// Get the book of the given chain, resolving its specific aliases and
+    /// the aliases for any chain
+    pub fn on_chain<'a>(&'a self, chain_id: &ChainId) -> ChainAddressBook<'a> {
+        ChainAddressBook {
+            book: self,
+            chain: self.chains.get(chain_id.as_str()),
+        }
+    }
+}
+
+/// Only resolves the aliases for any chain
+impl AddressResolver for AddressBook {
+    fn find_address(&self, alias: &str) -> Option<Address> {
+        self.aliases.get(&alias.to_lowercase()).cloned()
+    }
+}
+
+/// The book of aliases of a chain, see [`AddressBook::on_chain`]
+#[derive(Clone, Debug)]
+pub struct ChainAddressBook<'a> {
+    book: &'a AddressBook,
+    chain: Option<&'a BTreeMap<String, Address>>,
+}
+
+impl<'a> AddressResolver for ChainAddressBook<'a> {
+    fn find_address(&self, alias: &str) -> Option<Address> {
+        self.chain
+            .and_then(|aliases| aliases.get(&alias.to_lowercase()))
+            .cloned()
+            .or_else(|| self.book.find_address(alias))
+    }
+}
+
+/// Aliases overridden by environment variables, named after the alias in
+/// upper-case with the characters other than alphanumeric ones replaced by
+/// `_`, see [`ENV_VAR_ALIAS_PREFIX`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnvAliases;
+
+impl EnvAliases {
+    /// The name of the environment variable overriding the given alias
+    pub fn env_var(alias: &str) -> String {
+        let name: String = alias
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{ENV_VAR_ALIAS_PREFIX}{name}")
+    }
+}
+
+impl AddressResolver for EnvAliases {
+    fn find_address(&self, alias: &str) -> Option<Address> {
+        let raw = std::env::var(Self::env_var(alias)).ok()?;
+        match Address::decode(raw.trim()) {
+            Ok(address) => Some(address),
+            Err(err) => {
+                tracing::warn!(
+                    "Ignoring the invalid address of {}: {}",
+                    Self::env_var(alias),
+                    err
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Normalize the given aliases to lower-case
+fn normalize(aliases: BTreeMap<String, Address>) -> BTreeMap<String, Address> {
+    aliases
+        .into_iter()
+        .map(|(alias, address)| (alias.to_lowercase(), address))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+
+    /// Test that chain specific aliases take precedence on their chain only
+    #[test]
+    fn test_chain_address_book() {
+        let chain_id = ChainId("my-chain".to_string());
+        let other_chain_id = ChainId("other-chain".to_string());
+        let mut book = AddressBook::default();
+        book.insert("Alice", established_address_1());
+        book.insert("faucet", established_address_2());
+        book.insert_for_chain(&chain_id, "faucet", established_address_3());
+
+        assert_eq!(book.find_address("alice"), Some(established_address_1()));
+        assert_eq!(book.find_address("faucet"), Some(established_address_2()));
+        let chain_book = book.on_chain(&chain_id);
+        assert_eq!(
+            chain_book.find_address("ALICE"),
+            Some(established_address_1())
+        );
+        assert_eq!(
+            chain_book.find_address("faucet"),
+            Some(established_address_3())
+        );
+        assert_eq!(
+            book.on_chain(&other_chain_id).find_address("faucet"),
+            Some(established_address_2())
+        );
+
+        // Raw addresses are resolved as they are
+        let raw = established_address_3().encode();
+        assert_eq!(book.resolve(&raw).unwrap(), established_address_3());
+        assert!(matches!(
+            book.resolve("bob"),
+            Err(Error::UnknownAlias(alias)) if alias == "bob"
+        ));
+    }
+
+    /// Test that address books round trip through their files
+    #[test]
+    fn test_address_book_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("address_book.toml");
+        assert_eq!(
+            AddressBook::load_or_default(&path).unwrap(),
+            AddressBook::default()
+        );
+
+        let file = format!(
+            "[aliases]\nAlice = \"{}\"\n\n[chains.my-chain]\nfaucet = \
+             \"{}\"\n",
+            established_address_1(),
+            established_address_2(),
+        );
+        fs::write(&path, file).unwrap();
+        let book = AddressBook::load(&path).unwrap();
+        assert_eq!(book.find_address("alice"), Some(established_address_1()));
+        assert_eq!(
+            book.on_chain(&ChainId("my-chain".to_string()))
+                .find_address("faucet"),
+            Some(established_address_2())
+        );
+
+        book.save(&path).unwrap();
+        assert_eq!(AddressBook::load(&path).unwrap(), book);
+    }
+
+    /// Test that environment variables override the layered resolvers
+    #[test]
+    fn test_env_aliases() {
+        assert_eq!(
+            EnvAliases::env_var("my-alias.1"),
+            "NAMADA_ALIAS_MY_ALIAS_1"
+        );
+
+        let mut book = AddressBook::default();
+        book.insert("env-test-alias", established_address_1());
+        std::env::set_var(
+            EnvAliases::env_var("env-test-alias"),
+            established_address_2().encode(),
+        );
+        let resolver = (EnvAliases, &book);
+        assert_eq!(
+            resolver.resolve("env-test-alias").unwrap(),
+            established_address_2()
+        );
+        std::env::remove_var(EnvAliases::env_var("env-test-alias"));
+        assert_eq!(
+            resolver.resolve("env-test-alias").unwrap(),
+            established_address_1()
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Types of the pool of the transfers from Namada to Ethereum.
+//!
+//! A transfer to Ethereum escrows the wrapped ERC20 tokens and the gas fee
+//! paid to its relayer in the pool, under the [`ETH_BRIDGE_POOL`] address,
+//! next to the pending transfer. Once a relayer executed the transfer on
+//! Ethereum and the validators voted on its event of the contract of the
+//! bridge, the escrowed tokens are burnt and the fee is paid to the relayer.
+
+use std::str::FromStr;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::types::address::{Address, ETH_BRIDGE_POOL};
+use crate::types::ethereum_events::EthAddress;
+use crate::types::hash::Hash;
+use crate::types::storage::{DbKeySeg, Key, KeySeg};
+use crate::types::token::{Amount, LegacyAmount};
+
+const PENDING_KEY_SEGMENT: &str = "pending";
+
+/// A transfer of wrapped ERC20 tokens from Namada to Ethereum
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct TransferToEthereum {
+    /// The address of the ERC20 contract of the transferred tokens
+    pub asset: EthAddress,
+    /// The receiver of the tokens on Ethereum
+    pub recipient: EthAddress,
+    /// The sender of the wrapped tokens on Namada
+    pub sender: Address,
+    /// The amount of transferred tokens
+    pub amount: Amount,
+}
+
+/// The gas fee paid in the native token to the relayer of a transfer
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct GasFee {
+    /// The amount of the fee
+    pub amount: Amount,
+    /// The payer of the fee
+    pub payer: Address,
+}
+
+/// A transfer to Ethereum pending in the bridge pool
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct PendingTransfer {
+    /// The transfer
+    pub transfer: TransferToEthereum,
+    /// The fee of its relayer
+    pub gas_fee: GasFee,
+}
+
+impl PendingTransfer {
+    /// The hash of the pending transfer, which identifies it in the pool
+    pub fn hash(&self) -> Hash {
+        Hash::sha256(
+            self.try_to_vec()
+                .expect("Encoding a pending transfer shouldn't fail"),
+        )
+    }
+
+    /// Decode a pending transfer from its Borsh encoding, or from its
+    /// encoding with the legacy 64-bit amounts, see [`LegacyAmount`]
+    pub fn try_from_slice_compat(bytes: &[u8]) -> std::io::Result<Self> {
+        Self::try_from_slice(bytes).or_else(|err| {
+            LegacyPendingTransfer::try_from_slice(bytes)
+                .map(Self::from)
+                .map_err(|_| err)
+        })
+    }
+}
+
+/// The Borsh layout of the pending transfers with legacy 64-bit amounts
+#[derive(BorshDeserialize)]
+struct LegacyPendingTransfer {
+    asset: EthAddress,
+    recipient: EthAddress,
+    sender: Address,
+    amount: LegacyAmount,
+    gas_amount: LegacyAmount,
+    payer: Address,
+}
+
+impl From<LegacyPendingTransfer> for PendingTransfer {
+    fn from(legacy: LegacyPendingTransfer) -> Self {
+        Self {
+            transfer: TransferToEthereum {
+                asset: legacy.asset,
+                recipient: legacy.recipient,
+                sender: legacy.sender,
+                amount: legacy.amount.into(),
+            },
+            gas_fee: GasFee {
+                amount: legacy.gas_amount.into(),
+                payer: legacy.payer,
+            },
+        }
+    }
+}
+
+/// Get the prefix of the keys of the transfers pending in the pool
+pub fn pending_transfers_prefix() -> Key {
+    Key::from(ETH_BRIDGE_POOL.to_db_key())
+        .push(&PENDING_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of a transfer pending in the pool, by its hash
+pub fn pending_transfer_key(hash: &Hash) -> Key {
+    pending_transfers_prefix()
+        .push(&hash.to_string())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Check if a key is the key of a transfer pending in the pool. Returns the
+/// hash of the transfer if so.
+pub fn is_pending_transfer_key(key: &Key) -> Option<Hash> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(hash),
+        ] if addr == &ETH_BRIDGE_POOL && prefix == PENDING_KEY_SEGMENT => {
+            Hash::from_str(hash).ok()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::established_address_1;
+
+    #[test]
+    fn test_pending_transfer_key() {
+        let pending = PendingTransfer {
+            transfer: TransferToEthereum {
+                asset: EthAddress([1; 20]),
+                recipient: EthAddress([2; 20]),
+                sender: established_address_1(),
+                amount: Amount::whole(10),
+            },
+            gas_fee: GasFee {
+                amount: Amount::whole(1),
+                payer: established_address_1(),
+            },
+        };
+        let key = pending_transfer_key(&pending.hash());
+        assert_eq!(is_pending_transfer_key(&key), Some(pending.hash()));
+        assert_eq!(is_pending_transfer_key(&pending_transfers_prefix()), None);
+    }
+
+    #[test]
+    fn test_pending_transfer_borsh_compat() {
+        let pending = PendingTransfer {
+            transfer: TransferToEthereum {
+                asset: EthAddress([1; 20]),
+                recipient: EthAddress([2; 20]),
+                sender: established_address_1(),
+                amount: Amount::whole(10),
+            },
+            gas_fee: GasFee {
+                amount: Amount::whole(1),
+                payer: established_address_1(),
+            },
+        };
+        let bytes = pending.try_to_vec().unwrap();
+        let decoded = PendingTransfer::try_from_slice_compat(&bytes).unwrap();
+        assert_eq!(decoded, pending);
+
+        // The encoding with the legacy amounts is still accepted
+        let legacy = (
+            EthAddress([1; 20]),
+            EthAddress([2; 20]),
+            established_address_1(),
+            10_000_000_u64,
+            1_000_000_u64,
+            established_address_1(),
+        )
+            .try_to_vec()
+            .unwrap();
+        assert_eq!(
+            PendingTransfer::try_from_slice_compat(&legacy).unwrap(),
+            pending
+        );
+        let truncated = &legacy[..legacy.len() - 1];
+        assert!(PendingTransfer::try_from_slice_compat(truncated).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Types of the events of the contract of the Ethereum bridge, which the
+//! validators observe on Ethereum and vote on in protocol txs.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use data_encoding::HEXLOWER_PERMISSIVE;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::types::address::{Address, InternalAddress};
+use crate::types::eth_bridge_pool::PendingTransfer;
+use crate::types::hash::Hash;
+use crate::types::token::Amount;
+
+/// The length of an Ethereum address in bytes
+pub const ETH_ADDRESS_LEN: usize = 20;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum EthAddressError {
+    #[error("Invalid hex encoding of an Ethereum address: {0}")]
+    InvalidHex(data_encoding::DecodeError),
+    #[error("An Ethereum address must be {ETH_ADDRESS_LEN} bytes long")]
+    InvalidLength,
+}
+
+/// An Ethereum address, such as the address of an ERC20 contract
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct EthAddress(pub [u8; ETH_ADDRESS_LEN]);
+
+impl EthAddress {
+    /// The address of the token wrapping the ERC20 at this address on Namada
+    pub fn wrapped_token(&self) -> Address {
+        Address::Internal(InternalAddress::Erc20(*self))
+    }
+}
+
+impl Display for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", HEXLOWER_PERMISSIVE.encode(&self.0))
+    }
+}
+
+impl FromStr for EthAddress {
+    type Err = EthAddressError;
+
+    /// Parse an Ethereum address from its hex encoding, with or without the
+    /// `0x` prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        let bytes = HEXLOWER_PERMISSIVE
+            .decode(hex.as_bytes())
+            .map_err(EthAddressError::InvalidHex)?;
+        let bytes: [u8; ETH_ADDRESS_LEN] = bytes
+            .try_into()
+            .map_err(|_| EthAddressError::InvalidLength)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for EthAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EthAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let encoded: String = Deserialize::deserialize(deserializer)?;
+        Self::from_str(&encoded).map_err(D::Error::custom)
+    }
+}
+
+/// A transfer of ERC20 tokens locked in the contract of the bridge, which
+/// mints their wrapped tokens on Namada
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct TransferToNamada {
+    /// The address of the ERC20 contract of the locked tokens
+    pub asset: EthAddress,
+    /// The amount of locked tokens
+    pub amount: Amount,
+    /// The receiver of the wrapped tokens
+    pub receiver: Address,
+}
+
+/// An event emitted by the contract of the bridge on Ethereum
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub enum EthereumEvent {
+    /// A batch of ERC20 tokens locked in the contract of the bridge, to be
+    /// minted as wrapped tokens on Namada
+    TransfersToNamada {
+        /// The nonce of the batch in the contract
+        nonce: u64,
+        /// The locked tokens
+        transfers: Vec<TransferToNamada>,
+    },
+    /// A batch of pending transfers of the bridge pool relayed to Ethereum,
+    /// whose escrowed wrapped tokens are burnt on Namada
+    TransfersToEthereum {
+        /// The nonce of the batch in the contract
+        nonce: u64,
+        /// The relayed transfers
+        transfers: Vec<PendingTransfer>,
+        /// The Namada address of the relayer, which is paid the gas fees of
+        /// the transfers
+        relayer: Address,
+    },
+}
+
+impl EthereumEvent {
+    /// The hash of the event, which identifies it in storage
+    pub fn hash(&self) -> Hash {
+        Hash::sha256(
+            self.try_to_vec()
+                .expect("Encoding an Ethereum event shouldn't fail"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eth_address_encoding() {
+        let addr = EthAddress([0xab; ETH_ADDRESS_LEN]);
+        let encoded = addr.to_string();
+        assert_eq!(encoded, format!("0x{}", "ab".repeat(ETH_ADDRESS_LEN)));
+        assert_eq!(EthAddress::from_str(&encoded).unwrap(), addr);
+        // the prefix is optional and the hex is case insensitive
+        assert_eq!(
+            EthAddress::from_str(&"AB".repeat(ETH_ADDRESS_LEN)).unwrap(),
+            addr
+        );
+        assert!(EthAddress::from_str("0xabab").is_err());
+        assert!(EthAddress::from_str(&"zz".repeat(ETH_ADDRESS_LEN)).is_err());
+    }
+}
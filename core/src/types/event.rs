@@ -0,0 +1,459 @@
+//! Typed events emitted by the ledger on finalizing a block. Their attributes
+//! have stable keys, which are indexed by Tendermint, such that the events
+//! can be queried and parsed by clients and indexers.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::types::address::Address;
+use crate::types::governance::DepositOutcome;
+use crate::types::ibc::IbcEvent;
+use crate::types::storage::BlockHeight;
+use crate::types::token::Amount;
+use crate::types::transaction::{ResultCode, TxResultDetails};
+
+/// The stable keys of the attributes of the events
+pub mod attributes {
+    /// The hash of a tx
+    pub const HASH: &str = "hash";
+    /// The height of the block of a tx
+    pub const HEIGHT: &str = "height";
+    /// The result code of a tx
+    pub const CODE: &str = "code";
+    /// The gas used by a tx
+    pub const GAS_USED: &str = "gas_used";
+    /// The info on the result of a tx
+    pub const INFO: &str = "info";
+    /// The log of a tx
+    pub const LOG: &str = "log";
+    /// The memo of a tx
+    pub const MEMO: &str = "memo";
+    /// The accounts initialized by a tx, in JSON
+    pub const INITIALIZED_ACCOUNTS: &str = "initialized_accounts";
+    /// The machine-readable details of the result of a tx, in JSON
+    pub const DETAILS: &str = "details";
+    /// The ID of a proposal
+    pub const PROPOSAL_ID: &str = "proposal_id";
+    /// The result of the tally of a proposal
+    pub const TALLY_RESULT: &str = "tally_result";
+    /// Whether a proposal has code to execute
+    pub const HAS_PROPOSAL_CODE: &str = "has_proposal_code";
+    /// Whether the code of a proposal was executed successfully
+    pub const PROPOSAL_CODE_EXIT_STATUS: &str = "proposal_code_exit_status";
+    /// What was done with the funds locked by a proposal
+    pub const DEPOSIT_OUTCOME: &str = "deposit_outcome";
+    /// The amount of the funds locked by a proposal
+    pub const DEPOSIT_AMOUNT: &str = "deposit_amount";
+    /// The payer of the fee of a tx
+    pub const PAYER: &str = "payer";
+    /// The token of the fee of a tx
+    pub const TOKEN: &str = "token";
+    /// The amount of the fee refunded to the payer of a tx
+    pub const AMOUNT: &str = "amount";
+    /// The sequence of an IBC packet
+    pub const PACKET_SEQUENCE: &str = "packet_sequence";
+    /// The source port of an IBC packet
+    pub const PACKET_SRC_PORT: &str = "packet_src_port";
+    /// The source channel of an IBC packet
+    pub const PACKET_SRC_CHANNEL: &str = "packet_src_channel";
+    /// The destination port of an IBC packet
+    pub const PACKET_DST_PORT: &str = "packet_dst_port";
+    /// The destination channel of an IBC packet
+    pub const PACKET_DST_CHANNEL: &str = "packet_dst_channel";
+}
+
+/// The types of the IBC events of the packets
+pub const IBC_PACKET_EVENT_TYPES: [&str; 5] = [
+    "send_packet",
+    "recv_packet",
+    "write_acknowledgement",
+    "acknowledge_packet",
+    "timeout_packet",
+];
+
+/// The attributes of an event, by their keys
+pub type Attributes = BTreeMap<String, String>;
+
+/// A typed event, which is converted to and parsed from the attributes of an
+/// event emitted by the ledger
+pub trait TypedEvent: Sized {
+    /// The attributes of the event
+    fn attributes(&self) -> Attributes;
+
+    /// Parse the event from the attributes of an emitted event, if they are
+    /// the ones of this event
+    fn from_attributes(attributes: &HashMap<String, String>) -> Option<Self>;
+}
+
+/// A tx applied when finalizing a block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxApplied {
+    /// The hash of the tx
+    pub hash: String,
+    /// The height of the block
+    pub height: BlockHeight,
+    /// The result code of the tx
+    pub code: ResultCode,
+    /// The gas used by the tx
+    pub gas_used: u64,
+    /// The info on the result of the tx
+    pub info: String,
+    /// The accounts initialized by the tx
+    pub initialized_accounts: Vec<Address>,
+    /// The details of the result of the tx
+    pub details: TxResultDetails,
+}
+
+impl TypedEvent for TxApplied {
+    fn attributes(&self) -> Attributes {
+        let initialized_accounts =
+            serde_json::to_string(&self.initialized_accounts)
+                .expect("Serializing addresses shouldn't fail");
+        let details = serde_json::to_string(&self.details)
+            .expect("Serializing the result details shouldn't fail");
+        Attributes::from([
+            (attributes::HASH.to_owned(), self.hash.clone()),
+            (attributes::HEIGHT.to_owned(), self.height.to_string()),
+            (attributes::CODE.to_owned(), self.code.to_string()),
+            (attributes::GAS_USED.to_owned(), self.gas_used.to_string()),
+            (attributes::INFO.to_owned(), self.info.clone()),
+            (
+                attributes::INITIALIZED_ACCOUNTS.to_owned(),
+                initialized_accounts,
+            ),
+            (attributes::DETAILS.to_owned(), details),
+        ])
+    }
+
+    fn from_attributes(attributes: &HashMap<String, String>) -> Option<Self> {
+        let get = |key: &str| attributes.get(key);
+        Some(Self {
+            hash: get(attributes::HASH)?.clone(),
+            height: get(attributes::HEIGHT)?.parse().ok()?,
+            code: ResultCode::from_u32(get(attributes::CODE)?.parse().ok()?)?,
+            gas_used: get(attributes::GAS_USED)?.parse().ok()?,
+            info: get(attributes::INFO).cloned().unwrap_or_default(),
+            initialized_accounts: match get(attributes::INITIALIZED_ACCOUNTS) {
+                Some(accounts) => serde_json::from_str(accounts).ok()?,
+                None => vec![],
+            },
+            details: match get(attributes::DETAILS) {
+                Some(details) => serde_json::from_str(details).ok()?,
+                None => TxResultDetails::default(),
+            },
+        })
+    }
+}
+
+/// The refund of the part of the fee of a wrapper tx that covered the gas
+/// left unused by its decrypted tx
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeRefund {
+    /// The hash of the decrypted tx
+    pub hash: String,
+    /// The height of the block
+    pub height: BlockHeight,
+    /// The payer of the fee
+    pub payer: Address,
+    /// The token of the fee
+    pub token: Address,
+    /// The refunded amount
+    pub amount: Amount,
+}
+
+impl TypedEvent for FeeRefund {
+    fn attributes(&self) -> Attributes {
+        Attributes::from([
+            (attributes::HASH.to_owned(), self.hash.clone()),
+            (attributes::HEIGHT.to_owned(), self.height.to_string()),
+            (attributes::PAYER.to_owned(), self.payer.encode()),
+            (attributes::TOKEN.to_owned(), self.token.encode()),
+            (attributes::AMOUNT.to_owned(), self.amount.to_string()),
+        ])
+    }
+
+    fn from_attributes(attributes: &HashMap<String, String>) -> Option<Self> {
+        let get = |key: &str| attributes.get(key);
+        Some(Self {
+            hash: get(attributes::HASH)?.clone(),
+            height: get(attributes::HEIGHT)?.parse().ok()?,
+            payer: Address::decode(get(attributes::PAYER)?).ok()?,
+            token: Address::decode(get(attributes::TOKEN)?).ok()?,
+            amount: get(attributes::AMOUNT)?.parse().ok()?,
+        })
+    }
+}
+
+/// The result of a proposal at the end of its voting period. The flags are
+/// encoded like exit codes, as `0` if they're true and `1` otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposalResult {
+    /// The ID of the proposal
+    pub proposal_id: u64,
+    /// The result of the tally
+    pub tally_result: String,
+    /// Whether the proposal has code to execute
+    pub has_proposal_code: bool,
+    /// Whether the code of the proposal was executed successfully
+    pub proposal_code_exit_status: bool,
+}
+
+/// Encode a flag like an exit code
+fn encode_flag(flag: bool) -> String {
+    (!flag as u64).to_string()
+}
+
+/// Decode a flag encoded like an exit code
+fn decode_flag(flag: &str) -> Option<bool> {
+    match flag {
+        "0" => Some(true),
+        "1" => Some(false),
+        _ => None,
+    }
+}
+
+impl TypedEvent for ProposalResult {
+    fn attributes(&self) -> Attributes {
+        Attributes::from([
+            (
+                attributes::TALLY_RESULT.to_owned(),
+                self.tally_result.clone(),
+            ),
+            (
+                attributes::PROPOSAL_ID.to_owned(),
+                self.proposal_id.to_string(),
+            ),
+            (
+                attributes::HAS_PROPOSAL_CODE.to_owned(),
+                encode_flag(self.has_proposal_code),
+            ),
+            (
+                attributes::PROPOSAL_CODE_EXIT_STATUS.to_owned(),
+                encode_flag(self.proposal_code_exit_status),
+            ),
+        ])
+    }
+
+    fn from_attributes(attributes: &HashMap<String, String>) -> Option<Self> {
+        let get = |key: &str| attributes.get(key);
+        Some(Self {
+            proposal_id: get(attributes::PROPOSAL_ID)?.parse().ok()?,
+            tally_result: get(attributes::TALLY_RESULT)?.clone(),
+            has_proposal_code: decode_flag(get(
+                attributes::HAS_PROPOSAL_CODE,
+            )?)?,
+            proposal_code_exit_status: decode_flag(get(
+                attributes::PROPOSAL_CODE_EXIT_STATUS,
+            )?)?,
+        })
+    }
+}
+
+/// The outcome of the funds locked by a proposal, once it's tallied
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposalDeposit {
+    /// The ID of the proposal
+    pub proposal_id: u64,
+    /// What was done with the funds
+    pub outcome: DepositOutcome,
+    /// The amount of the funds
+    pub amount: Amount,
+}
+
+impl TypedEvent for ProposalDeposit {
+    fn attributes(&self) -> Attributes {
+        Attributes::from([
+            (
+                attributes::PROPOSAL_ID.to_owned(),
+                self.proposal_id.to_string(),
+            ),
+            (
+                attributes::DEPOSIT_OUTCOME.to_owned(),
+                self.outcome.to_string(),
+            ),
+            (
+                attributes::DEPOSIT_AMOUNT.to_owned(),
+                self.amount.to_string(),
+            ),
+        ])
+    }
+
+    fn from_attributes(attributes: &HashMap<String, String>) -> Option<Self> {
+        let get = |key: &str| attributes.get(key);
+        Some(Self {
+            proposal_id: get(attributes::PROPOSAL_ID)?.parse().ok()?,
+            outcome: get(attributes::DEPOSIT_OUTCOME)?.parse().ok()?,
+            amount: get(attributes::DEPOSIT_AMOUNT)?.parse().ok()?,
+        })
+    }
+}
+
+/// An IBC event of a packet, e.g. of a packet sent or received by the chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IbcPacket {
+    /// The type of the IBC event, one of [`IBC_PACKET_EVENT_TYPES`]
+    pub event_type: String,
+    /// The sequence of the packet
+    pub sequence: u64,
+    /// The source port of the packet
+    pub src_port: String,
+    /// The source channel of the packet
+    pub src_channel: String,
+    /// The destination port of the packet
+    pub dst_port: String,
+    /// The destination channel of the packet
+    pub dst_channel: String,
+    /// The other attributes of the IBC event, e.g. the data of the packet
+    pub other_attributes: Attributes,
+}
+
+impl IbcPacket {
+    /// Get the event of the packet from an IBC event, if it's the event of a
+    /// packet
+    pub fn from_ibc_event(event: &IbcEvent) -> Option<Self> {
+        if !IBC_PACKET_EVENT_TYPES.contains(&event.event_type.as_str()) {
+            return None;
+        }
+        let mut packet = Self::from_attributes(&event.attributes)?;
+        packet.event_type = event.event_type.clone();
+        Some(packet)
+    }
+}
+
+impl TypedEvent for IbcPacket {
+    fn attributes(&self) -> Attributes {
+        let mut attributes = self.other_attributes.clone();
+        attributes.extend([
+            (
+                attributes::PACKET_SEQUENCE.to_owned(),
+                self.sequence.to_string(),
+            ),
+            (
+                attributes::PACKET_SRC_PORT.to_owned(),
+                self.src_port.clone(),
+            ),
+            (
+                attributes::PACKET_SRC_CHANNEL.to_owned(),
+                self.src_channel.clone(),
+            ),
+            (
+                attributes::PACKET_DST_PORT.to_owned(),
+                self.dst_port.clone(),
+            ),
+            (
+                attributes::PACKET_DST_CHANNEL.to_owned(),
+                self.dst_channel.clone(),
+            ),
+        ]);
+        attributes
+    }
+
+    /// Parse the packet from the attributes of an event. The attributes don't
+    /// include the type of the IBC event, which is left empty.
+    fn from_attributes(attributes: &HashMap<String, String>) -> Option<Self> {
+        let get = |key: &str| attributes.get(key).cloned();
+        let keys = [
+            attributes::PACKET_SEQUENCE,
+            attributes::PACKET_SRC_PORT,
+            attributes::PACKET_SRC_CHANNEL,
+            attributes::PACKET_DST_PORT,
+            attributes::PACKET_DST_CHANNEL,
+        ];
+        Some(Self {
+            event_type: String::new(),
+            sequence: get(attributes::PACKET_SEQUENCE)?.parse().ok()?,
+            src_port: get(attributes::PACKET_SRC_PORT)?,
+            src_channel: get(attributes::PACKET_SRC_CHANNEL)?,
+            dst_port: get(attributes::PACKET_DST_PORT)?,
+            dst_channel: get(attributes::PACKET_DST_CHANNEL)?,
+            other_attributes: attributes
+                .iter()
+                .filter(|(key, _)| !keys.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+
+    /// Test that the typed events are parsed back from their attributes
+    #[test]
+    fn test_typed_events_roundtrip() {
+        fn roundtrip<E: TypedEvent>(event: &E) -> Option<E> {
+            E::from_attributes(&event.attributes().into_iter().collect())
+        }
+
+        let applied = TxApplied {
+            hash: "AB".repeat(32),
+            height: BlockHeight(10),
+            code: ResultCode::Ok,
+            gas_used: 100,
+            info: "Transaction is valid.".to_owned(),
+            initialized_accounts: vec![
+                address::testing::established_address_1(),
+            ],
+            details: TxResultDetails {
+                gas_used: 100,
+                changed_keys: vec!["key".to_owned()],
+                ..Default::default()
+            },
+        };
+        assert_eq!(roundtrip(&applied), Some(applied));
+
+        let refund = FeeRefund {
+            hash: "AB".repeat(32),
+            height: BlockHeight(10),
+            payer: address::testing::established_address_1(),
+            token: address::nam(),
+            amount: Amount::whole(2),
+        };
+        assert_eq!(roundtrip(&refund), Some(refund));
+
+        let proposal = ProposalResult {
+            proposal_id: 3,
+            tally_result: "passed".to_owned(),
+            has_proposal_code: true,
+            proposal_code_exit_status: false,
+        };
+        let attributes = proposal.attributes();
+        assert_eq!(attributes[attributes::HAS_PROPOSAL_CODE], "0");
+        assert_eq!(attributes[attributes::PROPOSAL_CODE_EXIT_STATUS], "1");
+        assert_eq!(roundtrip(&proposal), Some(proposal));
+
+        let deposit = ProposalDeposit {
+            proposal_id: 3,
+            outcome: DepositOutcome::Burned,
+            amount: Amount::whole(500),
+        };
+        assert_eq!(roundtrip(&deposit), Some(deposit));
+
+        let ibc_event = IbcEvent {
+            event_type: "send_packet".to_owned(),
+            attributes: HashMap::from([
+                ("packet_sequence".to_owned(), "1".to_owned()),
+                ("packet_src_port".to_owned(), "transfer".to_owned()),
+                ("packet_src_channel".to_owned(), "channel-0".to_owned()),
+                ("packet_dst_port".to_owned(), "transfer".to_owned()),
+                ("packet_dst_channel".to_owned(), "channel-1".to_owned()),
+                ("packet_data".to_owned(), "{}".to_owned()),
+            ]),
+        };
+        let packet = IbcPacket::from_ibc_event(&ibc_event)
+            .expect("The event should be parsed");
+        assert_eq!(packet.sequence, 1);
+        assert_eq!(packet.other_attributes["packet_data"], "{}");
+        let attributes: HashMap<_, _> =
+            packet.attributes().into_iter().collect();
+        assert_eq!(attributes, ibc_event.attributes);
+
+        // Other IBC events aren't packets
+        let ibc_event = IbcEvent {
+            event_type: "create_client".to_owned(),
+            ..ibc_event
+        };
+        assert_eq!(IbcPacket::from_ibc_event(&ibc_event), None);
+    }
+}
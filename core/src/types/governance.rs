@@ -2,6 +2,7 @@
 
 use std::collections::{BTreeMap, HashSet};
 use std::fmt::{self, Display};
+use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use rust_decimal::Decimal;
@@ -116,6 +117,8 @@ pub enum Tally {
     PGFCouncil(Council),
     /// ETH Bridge proposal
     ETHBridge,
+    /// Parameter change proposal
+    ParameterChange,
 }
 
 /// The result of a proposal
@@ -134,10 +137,59 @@ pub struct ProposalResult {
     pub total_voting_power: VotePower,
     /// The total voting power from yay votes
     pub total_yay_power: VotePower,
-    /// The total voting power from nay votes (unused at the moment)
+    /// The total voting power from nay votes
     pub total_nay_power: VotePower,
 }
 
+impl ProposalResult {
+    /// Check if the yay and nay votes make up at least the given fraction of
+    /// the total voting power
+    pub fn has_quorum(&self, min_quorum: Decimal) -> bool {
+        let voted_power = Decimal::from(
+            self.total_yay_power.saturating_add(self.total_nay_power),
+        );
+        let quorum_power = Decimal::from(self.total_voting_power)
+            .checked_mul(min_quorum)
+            .unwrap_or(Decimal::MAX);
+        voted_power >= quorum_power
+    }
+}
+
+/// What is done with the funds locked by a proposal once it's tallied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositOutcome {
+    /// Refunded to the author of a passed proposal, or of a rejected
+    /// proposal that reached the quorum
+    Refunded,
+    /// Sent to the slash fund, when the code of a passed proposal failed
+    Slashed,
+    /// Burned, when a rejected proposal didn't reach the quorum
+    Burned,
+}
+
+impl Display for DepositOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepositOutcome::Refunded => write!(f, "refunded"),
+            DepositOutcome::Slashed => write!(f, "slashed"),
+            DepositOutcome::Burned => write!(f, "burned"),
+        }
+    }
+}
+
+impl FromStr for DepositOutcome {
+    type Err = ProposalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "refunded" => Ok(DepositOutcome::Refunded),
+            "slashed" => Ok(DepositOutcome::Slashed),
+            "burned" => Ok(DepositOutcome::Burned),
+            _ => Err(ProposalError::InvalidProposalData),
+        }
+    }
+}
+
 impl Display for ProposalResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let percentage = Decimal::checked_div(
@@ -161,7 +213,9 @@ impl Display for TallyResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             TallyResult::Passed(vote) => match vote {
-                Tally::Default | Tally::ETHBridge => write!(f, "passed"),
+                Tally::Default | Tally::ETHBridge | Tally::ParameterChange => {
+                    write!(f, "passed")
+                }
                 Tally::PGFCouncil((council, cap)) => write!(
                     f,
                     "passed with PGF council address: {}, spending cap: {}",
@@ -184,6 +238,91 @@ pub enum ProposalType {
     PGFCouncil,
     /// An ETH bridge proposal
     ETHBridge,
+    /// A proposal of changes of the protocol parameters, applied when it
+    /// passes
+    ParameterChange(Vec<ParameterChange>),
+}
+
+/// A change of a protocol parameter by a governance proposal, written to
+/// storage at the grace epoch of the proposal if it passes
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum ParameterChange {
+    /// The gas limit resolution of the wrapper txs
+    GasLimitResolution(u64),
+    /// The fixed fee of the wrapper txs, in the native token
+    WrapperTxFees(Amount),
+    /// The minimum gas price of the wrapper txs
+    MinGasPrice(Amount),
+    /// The minimum number of blocks and duration in seconds of an epoch
+    EpochDuration {
+        /// The minimum number of blocks of an epoch
+        min_num_of_blocks: u64,
+        /// The minimum duration of an epoch in seconds
+        min_duration: u64,
+    },
+    /// The maximum number of consensus validators
+    PosMaxValidatorSlots(u64),
+    /// The maximum staking rewards rate per annum
+    PosMaxInflationRate(Decimal),
+    /// The target ratio of staked native tokens to the total supply
+    PosTargetStakedRatio(Decimal),
+}
+
+impl ParameterChange {
+    /// Check that the new value of the parameter is valid
+    pub fn is_valid(&self) -> bool {
+        let is_ratio = |ratio: &Decimal| {
+            !ratio.is_sign_negative() && *ratio <= Decimal::ONE
+        };
+        match self {
+            Self::GasLimitResolution(resolution) => *resolution > 0,
+            Self::WrapperTxFees(_) | Self::MinGasPrice(_) => true,
+            Self::EpochDuration {
+                min_num_of_blocks, ..
+            } => *min_num_of_blocks > 0,
+            Self::PosMaxValidatorSlots(slots) => *slots > 0,
+            Self::PosMaxInflationRate(rate) => is_ratio(rate),
+            Self::PosTargetStakedRatio(ratio) => is_ratio(ratio),
+        }
+    }
+}
+
+impl Display for ParameterChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GasLimitResolution(resolution) => {
+                write!(f, "gas limit resolution: {}", resolution)
+            }
+            Self::WrapperTxFees(fees) => write!(f, "wrapper tx fees: {}", fees),
+            Self::MinGasPrice(price) => write!(f, "min gas price: {}", price),
+            Self::EpochDuration {
+                min_num_of_blocks,
+                min_duration,
+            } => write!(
+                f,
+                "epoch duration: {} blocks and {} seconds at least",
+                min_num_of_blocks, min_duration
+            ),
+            Self::PosMaxValidatorSlots(slots) => {
+                write!(f, "PoS max validator slots: {}", slots)
+            }
+            Self::PosMaxInflationRate(rate) => {
+                write!(f, "PoS max inflation rate: {}", rate)
+            }
+            Self::PosTargetStakedRatio(ratio) => {
+                write!(f, "PoS target staked ratio: {}", ratio)
+            }
+        }
+    }
 }
 
 #[derive(
@@ -321,15 +460,9 @@ impl OfflineVote {
         signing_key: &common::SecretKey,
     ) -> Self {
         let proposal_hash = proposal.compute_hash();
-        let proposal_hash_data = proposal_hash
-            .try_to_vec()
-            .expect("Conversion to bytes shouldn't fail.");
-        let proposal_vote_data = vote
-            .try_to_vec()
-            .expect("Conversion to bytes shouldn't fail.");
-        let vote_serialized =
-            &[proposal_hash_data, proposal_vote_data].concat();
-        let signature = common::SigScheme::sign(signing_key, vote_serialized);
+        // The signature is over the same hash that it's verified against
+        let vote_data_hash = Self::hash_vote(&proposal_hash, &vote);
+        let signature = common::SigScheme::sign(signing_key, vote_data_hash);
         Self {
             proposal_hash,
             vote,
@@ -340,12 +473,15 @@ impl OfflineVote {
 
     /// compute the hash of a proposal
     pub fn compute_hash(&self) -> Hash {
-        let proposal_hash_data = self
-            .proposal_hash
+        Self::hash_vote(&self.proposal_hash, &self.vote)
+    }
+
+    /// Hash the vote on the proposal with the given hash
+    fn hash_vote(proposal_hash: &Hash, vote: &ProposalVote) -> Hash {
+        let proposal_hash_data = proposal_hash
             .try_to_vec()
             .expect("Conversion to bytes shouldn't fail.");
-        let proposal_vote_data = self
-            .vote
+        let proposal_vote_data = vote
             .try_to_vec()
             .expect("Conversion to bytes shouldn't fail.");
         let vote_serialized =
@@ -354,6 +490,11 @@ impl OfflineVote {
         Hash::sha256(vote_serialized)
     }
 
+    /// Check whether this is a vote on the given proposal
+    pub fn is_for(&self, proposal: &OfflineProposal) -> bool {
+        self.proposal_hash == proposal.compute_hash()
+    }
+
     /// Check whether the signature is valid or not
     pub fn check_signature(&self, public_key: &common::PublicKey) -> bool {
         let vote_data_hash = self.compute_hash();
@@ -365,3 +506,60 @@ impl OfflineVote {
         .is_ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::established_address_1;
+    use crate::types::key::testing::{keypair_1, keypair_2};
+    use crate::types::key::RefTo;
+
+    fn offline_proposal() -> OfflineProposal {
+        let author = established_address_1();
+        let proposal = Proposal {
+            id: None,
+            content: BTreeMap::from([(
+                "title".to_owned(),
+                "Signaling poll".to_owned(),
+            )]),
+            author: author.clone(),
+            r#type: ProposalType::Default(None),
+            voting_start_epoch: Epoch(1),
+            voting_end_epoch: Epoch(4),
+            grace_epoch: Epoch(7),
+        };
+        OfflineProposal::new(proposal, author, &keypair_1())
+    }
+
+    /// Test that the signatures of an offline proposal and of an offline vote
+    /// on it are verified against the keys that made them only
+    #[test]
+    fn test_offline_signatures() {
+        let proposal = offline_proposal();
+        assert_eq!(proposal.tally_epoch, Epoch(7));
+        assert!(proposal.check_signature(&keypair_1().ref_to()));
+        assert!(!proposal.check_signature(&keypair_2().ref_to()));
+
+        let vote = OfflineVote::new(
+            &proposal,
+            ProposalVote::Yay(VoteType::Default),
+            established_address_1(),
+            &keypair_2(),
+        );
+        assert!(vote.is_for(&proposal));
+        assert!(vote.check_signature(&keypair_2().ref_to()));
+        assert!(!vote.check_signature(&keypair_1().ref_to()));
+
+        // A vote can't be changed without invalidating its signature
+        let forged = OfflineVote {
+            vote: ProposalVote::Nay,
+            ..vote.clone()
+        };
+        assert!(!forged.check_signature(&keypair_2().ref_to()));
+
+        // And it doesn't apply to another proposal
+        let mut other = offline_proposal();
+        other.tally_epoch = Epoch(8);
+        assert!(!vote.is_for(&other));
+    }
+}
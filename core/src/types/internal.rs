@@ -49,6 +49,7 @@ mod tx_queue {
     use borsh::{BorshDeserialize, BorshSerialize};
 
     use crate::proto::Tx;
+    use crate::types::token::Amount;
 
     /// A wrapper for `crate::types::transaction::WrapperTx` to conditionally
     /// add `has_valid_pow` flag for only used in testnets.
@@ -56,6 +57,9 @@ mod tx_queue {
     pub struct TxInQueue {
         /// Wrapper tx
         pub tx: Tx,
+        /// The fee charged to the payer of the wrapper in its fee token, off
+        /// which the gas left unused by the decrypted tx is refunded
+        pub fee: Amount,
         #[cfg(not(feature = "mainnet"))]
         /// A PoW solution can be used to allow zero-fee testnet
         /// transactions.
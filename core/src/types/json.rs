@@ -0,0 +1,184 @@
+//! A canonical JSON encoding of txs and core types, as an alternative to
+//! Borsh for external tooling, such as block explorers and auditors
+//! written in other languages.
+//!
+//! The canonical JSON of a value is its compact serde JSON encoding, with
+//! the fields of every object sorted by the byte order of their names,
+//! such that it can be stably reproduced regardless of the order in which
+//! fields are declared.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::proto::{Header, Section, Tx};
+use crate::types::address::Address;
+use crate::types::chain::ChainId;
+use crate::types::hash::Hash;
+use crate::types::key::common;
+use crate::types::storage::{BlockHeight, Epoch};
+use crate::types::time::DateTimeUtc;
+use crate::types::token::{Amount, Transfer};
+use crate::types::transaction::WrapperTx;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("JSON encoding error: {0}")]
+    Json(serde_json::Error),
+    #[error("The JSON is not in its canonical form")]
+    NonCanonical,
+}
+
+/// Result of a canonical JSON encoding or decoding
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Types with a canonical JSON encoding
+pub trait CanonicalJson: Serialize + DeserializeOwned {
+    /// Encode this value to its canonical JSON
+    fn to_canonical_json(&self) -> Result<String> {
+        let value = serde_json::to_value(self).map_err(Error::Json)?;
+        serde_json::to_string(&canonicalize(value)).map_err(Error::Json)
+    }
+
+    /// Decode a value from its canonical JSON. JSON that is valid but not
+    /// canonical, e.g. with unsorted fields or whitespace, is rejected.
+    fn from_canonical_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json).map_err(Error::Json)?;
+        let canonical = serde_json::to_string(&canonicalize(value.clone()))
+            .map_err(Error::Json)?;
+        if canonical != json {
+            return Err(Error::NonCanonical);
+        }
+        serde_json::from_value(value).map_err(Error::Json)
+    }
+}
+
+/// Sort the fields of all the objects nested in the given JSON value
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut fields: Vec<_> = map.into_iter().collect();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name, canonicalize(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(canonicalize).collect())
+        }
+        value => value,
+    }
+}
+
+impl CanonicalJson for Tx {}
+impl CanonicalJson for Header {}
+impl CanonicalJson for Section {}
+impl CanonicalJson for WrapperTx {}
+impl CanonicalJson for Transfer {}
+impl CanonicalJson for Address {}
+impl CanonicalJson for Amount {}
+impl CanonicalJson for Hash {}
+impl CanonicalJson for ChainId {}
+impl CanonicalJson for Epoch {}
+impl CanonicalJson for BlockHeight {}
+impl CanonicalJson for DateTimeUtc {}
+impl CanonicalJson for common::PublicKey {}
+impl CanonicalJson for common::Signature {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::Data;
+    use crate::types::address::nam;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::types::key::testing::keypair_1;
+    use crate::types::key::RefTo;
+    use crate::types::transaction::{Fee, GasLimit, TxType};
+
+    /// Test the canonical JSON of core types against known vectors
+    #[test]
+    fn test_canonical_json_vectors() {
+        let transfer = Transfer {
+            source: established_address_1(),
+            target: established_address_2(),
+            token: nam(),
+            sub_prefix: None,
+            amount: Amount::from(1_500_000u64),
+            key: None,
+            shielded: None,
+        };
+        let json = concat!(
+            r#"{"amount":"1.5","key":null,"shielded":null,"#,
+            r#""source":"atest1v4ehgw36g56ngwpk8ppnzsf4xqeyvsf3xq6nxde5gseyys3nxgenvvfex5cnyd2rx9zrzwfctgx7sp","#,
+            r#""sub_prefix":null,"#,
+            r#""target":"atest1v4ehgw36xezyzv33x56rws6zxccnwwzzgycy23p3ggur2d3ex56yxdejxerrysejx3rrxdfs44s9wu","#,
+            r#""token":"atest1v4ehgw36x3prswzxggunzv6pxqmnvdj9xvcyzvpsggeyvs3cg9qnywf589qnwvfsg5erg3fkl09rg5"}"#,
+        );
+        assert_eq!(transfer.to_canonical_json().unwrap(), json);
+        assert_eq!(Transfer::from_canonical_json(json).unwrap(), transfer);
+
+        assert_eq!(
+            Amount::from(10u64).to_canonical_json().unwrap(),
+            r#""0.00001""#
+        );
+        assert_eq!(Epoch(7).to_canonical_json().unwrap(), "7");
+        assert_eq!(BlockHeight(42).to_canonical_json().unwrap(), "42");
+        assert_eq!(
+            ChainId("test-chain".to_string())
+                .to_canonical_json()
+                .unwrap(),
+            r#""test-chain""#
+        );
+    }
+
+    /// Test that JSON that is not canonical is rejected
+    #[test]
+    fn test_non_canonical_json() {
+        let unsorted = r#"{"token":"a","amount":"1"}"#;
+        assert!(matches!(
+            Transfer::from_canonical_json(unsorted),
+            Err(Error::NonCanonical)
+        ));
+        let spaced = r#" "1.5""#;
+        assert!(matches!(
+            Amount::from_canonical_json(spaced),
+            Err(Error::NonCanonical)
+        ));
+    }
+
+    /// Test that the canonical JSON of wrapper txs round trips
+    #[test]
+    fn test_canonical_json_tx() {
+        let keypair = keypair_1();
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: Amount::from(10u64),
+                token: nam(),
+            },
+            &keypair,
+            Epoch(0),
+            GasLimit::from_multiplier(0),
+            #[cfg(not(feature = "mainnet"))]
+            None,
+        );
+        let json = wrapper.to_canonical_json().unwrap();
+        let decoded = WrapperTx::from_canonical_json(&json).unwrap();
+        assert_eq!(decoded.pk, keypair.ref_to());
+        assert_eq!(decoded.to_canonical_json().unwrap(), json);
+
+        let mut tx = Tx::new(TxType::Wrapper(Box::new(wrapper)));
+        tx.set_data(Data::new(b"data".to_vec()));
+        let json = tx.to_canonical_json().unwrap();
+        let decoded = Tx::from_canonical_json(&json).unwrap();
+        assert_eq!(decoded.header_hash(), tx.header_hash());
+        assert_eq!(decoded.data(), tx.data());
+        assert_eq!(decoded.to_canonical_json().unwrap(), json);
+    }
+}
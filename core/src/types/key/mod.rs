@@ -5,6 +5,7 @@ pub mod common;
 pub mod dkg_session_keys;
 pub mod ed25519;
 pub mod secp256k1;
+pub mod signer;
 
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
@@ -24,6 +25,8 @@ use crate::types::address;
 
 const PK_STORAGE_KEY: &str = "public_key";
 const PROTOCOL_PK_STORAGE_KEY: &str = "protocol_public_key";
+const PKS_STORAGE_KEY: &str = "public_keys";
+const THRESHOLD_STORAGE_KEY: &str = "threshold";
 
 /// Obtain a storage key for user's public key.
 pub fn pk_key(owner: &Address) -> storage::Key {
@@ -63,6 +66,47 @@ pub fn is_protocol_pk_key(key: &Key) -> Option<&Address> {
     }
 }
 
+/// Obtain a storage key for the public keys of a multisig account's signers.
+pub fn pks_key(owner: &Address) -> storage::Key {
+    Key::from(owner.to_db_key())
+        .push(&PKS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key holds the public keys of a multisig
+/// account's signers. If it is, returns the owner.
+pub fn is_pks_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == PKS_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// Obtain a storage key for the number of signatures required by a multisig
+/// account.
+pub fn threshold_key(owner: &Address) -> storage::Key {
+    Key::from(owner.to_db_key())
+        .push(&THRESHOLD_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key holds the threshold of a multisig account.
+/// If it is, returns the owner.
+pub fn is_threshold_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == THRESHOLD_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
 /// Represents an error in signature verification
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -131,6 +131,48 @@ impl FromStr for PublicKey {
     }
 }
 
+impl PublicKey {
+    /// Get the Ethereum address of this key, i.e. the last 20 bytes of the
+    /// keccak256 hash of its uncompressed encoding
+    pub fn eth_address(&self) -> EthAddress {
+        // Skip the leading tag of the uncompressed encoding
+        let hash = keccak256(&self.0.serialize()[1..]);
+        let mut address = [0; 20];
+        address.copy_from_slice(&hash[12..]);
+        EthAddress(address)
+    }
+}
+
+/// The Ethereum address of a secp256k1 public key, as shown by Ethereum
+/// wallets such as Metamask
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct EthAddress(pub [u8; 20]);
+
+impl Display for EthAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", HEXLOWER.encode(&self.0))
+    }
+}
+
+/// Get the keccak256 hash of the given bytes
+fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    use tiny_keccak::{Hasher as _, Keccak};
+    let mut hasher = Keccak::v256();
+    let mut hash = [0; 32];
+    hasher.update(bytes);
+    hasher.finalize(&mut hash);
+    hash
+}
+
+/// Get the hash signed by Ethereum wallets for the given message, i.e. the
+/// keccak256 hash of the message prefixed as specified by EIP-191
+pub fn eip191_hash(message: &[u8]) -> [u8; 32] {
+    let mut prefixed =
+        format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    keccak256(&prefixed)
+}
+
 impl From<libsecp256k1::PublicKey> for PublicKey {
     fn from(pk: libsecp256k1::PublicKey) -> Self {
         Self(pk)
@@ -408,6 +450,27 @@ impl Hash for Signature {
     }
 }
 
+impl Signature {
+    /// Encode this signature as Ethereum wallets do, i.e. as its 64 bytes
+    /// followed by its recovery id offset by 27
+    pub fn to_eth_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0; 65];
+        bytes[..64].copy_from_slice(&self.0.serialize());
+        bytes[64] = self.1.serialize() + 27;
+        bytes
+    }
+
+    /// Decode a signature made by an Ethereum wallet, whose recovery id may
+    /// or may not be offset by 27
+    pub fn from_eth_bytes(sig: &[u8; 65]) -> Result<Self, ParseSignatureError> {
+        let mut sig = *sig;
+        if sig[64] >= 27 {
+            sig[64] -= 27;
+        }
+        Self::try_from(&sig)
+    }
+}
+
 impl PartialOrd for Signature {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         self.0.serialize().partial_cmp(&other.0.serialize())
@@ -558,3 +621,106 @@ impl super::SigScheme for SigScheme {
         }
     }
 }
+
+impl SigScheme {
+    /// Sign the given message as Ethereum wallets do, over its EIP-191 hash
+    pub fn sign_eip191(
+        keypair: &SecretKey,
+        message: impl AsRef<[u8]>,
+    ) -> Signature {
+        #[cfg(not(any(test, feature = "secp256k1-sign-verify")))]
+        {
+            // to avoid `unused-variables` warn
+            let _ = (keypair, message);
+            panic!("\"secp256k1-sign-verify\" feature must be enabled");
+        }
+
+        #[cfg(any(test, feature = "secp256k1-sign-verify"))]
+        {
+            let hash = eip191_hash(message.as_ref());
+            let message = libsecp256k1::Message::parse(&hash);
+            let (sig, recovery_id) = libsecp256k1::sign(&message, &keypair.0);
+            Signature(sig, recovery_id)
+        }
+    }
+
+    /// Verify a signature made by an Ethereum wallet over the EIP-191 hash
+    /// of the given message
+    pub fn verify_eip191(
+        pk: &PublicKey,
+        message: &[u8],
+        sig: &Signature,
+    ) -> Result<(), VerifySigError> {
+        #[cfg(not(any(test, feature = "secp256k1-sign-verify")))]
+        {
+            // to avoid `unused-variables` warn
+            let _ = (pk, message, sig);
+            panic!("\"secp256k1-sign-verify\" feature must be enabled");
+        }
+
+        #[cfg(any(test, feature = "secp256k1-sign-verify"))]
+        {
+            let hash = eip191_hash(message);
+            let message = &libsecp256k1::Message::parse(&hash);
+            let is_valid = libsecp256k1::verify(message, &sig.0, &pk.0);
+            if is_valid {
+                Ok(())
+            } else {
+                Err(VerifySigError::SigVerifyError(format!(
+                    "Error verifying EIP-191 secp256k1 signature: {}",
+                    libsecp256k1::Error::InvalidSignature
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::key::SigScheme as _;
+
+    /// Test the Ethereum encodings against the vectors of web3.js
+    #[test]
+    fn test_eth_vectors() {
+        let sk = SigScheme::from_bytes(
+            HEXLOWER
+                .decode(
+                    b"4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a\
+                      3f362318",
+                )
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(
+            sk.ref_to().eth_address().to_string(),
+            "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+        assert_eq!(
+            HEXLOWER.encode(&eip191_hash(b"Some data")),
+            "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+        );
+    }
+
+    /// Test that EIP-191 signatures round trip through their Ethereum
+    /// encoding and only verify over the signed message
+    #[test]
+    fn test_eip191_signature() {
+        let sk = SigScheme::from_bytes([1; 32]);
+        let sig = SigScheme::sign_eip191(&sk, b"message");
+        let decoded = Signature::from_eth_bytes(&sig.to_eth_bytes()).unwrap();
+        assert_eq!(decoded, sig);
+        assert!(
+            SigScheme::verify_eip191(&sk.ref_to(), b"message", &sig).is_ok()
+        );
+        assert!(SigScheme::verify_eip191(&sk.ref_to(), b"other", &sig).is_err());
+        // the signature is not over the plain message
+        assert!(SigScheme::verify_signature_raw(
+            &sk.ref_to(),
+            b"message",
+            &sig
+        )
+        .is_err());
+    }
+}
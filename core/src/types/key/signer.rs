@@ -0,0 +1,61 @@
+//! Signers of messages, abstracting over where their secret keys are held.
+//!
+//! Secret keys may be held locally or by a remote signer, such as an HSM or
+//! a KMS, which never exposes them and only returns the signatures of the
+//! messages it is given.
+
+use std::fmt::Debug;
+
+use thiserror::Error;
+
+use super::{common, RefTo, SigScheme};
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum SignerError {
+    #[error("The remote signer failed: {0}")]
+    Remote(String),
+    #[error("The signer cannot produce a signature of this kind: {0}")]
+    Unsupported(String),
+}
+
+/// A holder of a secret key, which signs the messages given to it
+pub trait Signer: Debug + Send + Sync {
+    /// Get the public key of the signer
+    fn public_key(&self) -> Result<common::PublicKey, SignerError>;
+
+    /// Sign the given message, in the same way as [`SigScheme::sign`] with
+    /// the secret key of the signer
+    fn sign(&self, data: &[u8]) -> Result<common::Signature, SignerError>;
+}
+
+/// Secret keys held locally sign messages themselves
+impl Signer for common::SecretKey {
+    fn public_key(&self) -> Result<common::PublicKey, SignerError> {
+        Ok(self.ref_to())
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<common::Signature, SignerError> {
+        Ok(common::SigScheme::sign(self, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::key::testing::keypair_1;
+
+    /// Test that local keys sign messages as the signature scheme does
+    #[test]
+    fn test_local_signer() {
+        let keypair = keypair_1();
+        let signer: &dyn Signer = &keypair;
+        let pk = signer.public_key().unwrap();
+        assert_eq!(pk, keypair.ref_to());
+        let sig = signer.sign(b"message").unwrap();
+        assert!(
+            common::SigScheme::verify_signature_raw(&pk, b"message", &sig)
+                .is_ok()
+        );
+    }
+}
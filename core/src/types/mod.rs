@@ -1,11 +1,16 @@
 //! Types definitions.
 
 pub mod address;
+pub mod address_book;
 pub mod chain;
+pub mod eth_bridge_pool;
+pub mod ethereum_events;
+pub mod event;
 pub mod governance;
 pub mod hash;
 pub mod ibc;
 pub mod internal;
+pub mod json;
 pub mod key;
 pub mod masp;
 pub mod storage;
@@ -241,6 +241,12 @@ impl BlockHeight {
     pub fn next_height(&self) -> BlockHeight {
         BlockHeight(self.0 + 1)
     }
+
+    /// The number of blocks from this height until the given later height, or
+    /// zero if the given height is not later
+    pub fn blocks_until(&self, later: BlockHeight) -> u64 {
+        later.0.saturating_sub(self.0)
+    }
 }
 
 impl TryFrom<&[u8]> for BlockHash {
@@ -329,6 +335,81 @@ pub struct Key {
     pub segments: Vec<DbKeySeg>,
 }
 
+/// A builder of storage keys from typed segments, with which the storage
+/// layouts of all the subsystems are built instead of joining strings, such
+/// that they can't be malformed. Unlike [`Key::push`], the segments are
+/// checked at the construction of a layout rather than at every use site, so
+/// that invalid layouts fail in any test exercising them.
+#[derive(Clone, Debug)]
+pub struct KeyBuilder {
+    segments: Vec<DbKeySeg>,
+}
+
+impl KeyBuilder {
+    /// Add a named segment of a storage layout, which must not contain the
+    /// [`KEY_SEGMENT_SEPARATOR`], nor start with the
+    /// [`RESERVED_ADDRESS_PREFIX`] or the [`RESERVED_VP_KEY`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not valid.
+    #[must_use]
+    pub fn name(mut self, name: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        assert!(
+            !name.contains(KEY_SEGMENT_SEPARATOR)
+                && !name.starts_with(RESERVED_ADDRESS_PREFIX)
+                && !name.starts_with(RESERVED_VP_KEY),
+            "Invalid name of a storage key segment: {}",
+            name
+        );
+        self.segments.push(DbKeySeg::StringSeg(name.to_owned()));
+        self
+    }
+
+    /// Add a typed segment, such as an address, an epoch or a block height.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment's raw string contains the
+    /// [`KEY_SEGMENT_SEPARATOR`], which is only possible for strings.
+    #[must_use]
+    pub fn seg<T: KeySeg>(mut self, seg: &T) -> Self {
+        let seg = DbKeySeg::parse(seg.raw())
+            .expect("Cannot obtain a storage key segment");
+        self.segments.push(seg);
+        self
+    }
+
+    /// Add the segments of the given key, e.g. a sub-prefix
+    #[must_use]
+    pub fn join(mut self, key: &Key) -> Self {
+        self.segments.extend(key.segments.iter().cloned());
+        self
+    }
+
+    /// Build the key
+    pub fn build(self) -> Key {
+        Key {
+            segments: self.segments,
+        }
+    }
+}
+
+impl From<KeyBuilder> for Key {
+    fn from(builder: KeyBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<Key> for KeyBuilder {
+    fn from(key: Key) -> Self {
+        Self {
+            segments: key.segments,
+        }
+    }
+}
+
 /// A [`Key`] made of borrowed key segments [`DbKeySeg`].
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct KeyRef<'a> {
@@ -478,6 +559,13 @@ impl Value for TreeBytes {
 }
 
 impl Key {
+    /// Start building a key in the storage subspace of the given address
+    pub fn builder(address: &Address) -> KeyBuilder {
+        KeyBuilder {
+            segments: vec![DbKeySeg::AddressSeg(address.clone())],
+        }
+    }
+
     /// Parses string and returns a key
     pub fn parse(string: impl AsRef<str>) -> Result<Self> {
         let mut segments = Vec::new();
@@ -487,7 +575,8 @@ impl Key {
         Ok(Key { segments })
     }
 
-    /// Returns a new key with segments of `Self` and the given segment
+    /// Returns a new key with segments of `Self` and the given segment. Prefer
+    /// a [`KeyBuilder`] for the keys of storage layouts.
     pub fn push<T: KeySeg>(&self, other: &T) -> Result<Self> {
         let mut segments = self.segments.clone();
         segments.push(DbKeySeg::parse(other.raw())?);
@@ -994,6 +1083,19 @@ impl Epoch {
     pub fn sub_or_default(self, rhs: Epoch) -> Self {
         self.checked_sub(rhs).unwrap_or_default()
     }
+
+    /// Checked epoch addition. Computes self + rhs, returning None if
+    /// overflow occurred.
+    #[must_use = "this returns the result of the operation, without modifying \
+                  the original"]
+    pub fn checked_add(self, rhs: u64) -> Option<Self> {
+        self.0.checked_add(rhs).map(Self)
+    }
+
+    /// The number of epochs between `self` and `other`, in either order
+    pub fn abs_diff(self, other: Epoch) -> u64 {
+        self.0.abs_diff(other.0)
+    }
 }
 
 impl From<u64> for Epoch {
@@ -1299,6 +1401,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_key_builder() {
+        let addr = address::testing::established_address_1();
+        let other = address::testing::established_address_2();
+        let key = Key::builder(&addr)
+            .name("test")
+            .seg(&other)
+            .seg(&Epoch(1))
+            .build();
+        let target = Key::from(addr.to_db_key())
+            .push(&"test".to_owned())
+            .and_then(|key| key.push(&other))
+            .and_then(|key| key.push(&Epoch(1)))
+            .unwrap();
+        assert_eq!(key, target);
+        assert_eq!(KeyBuilder::from(key.clone()).build(), key);
+
+        let sub_prefix = Key::parse("sub/prefix").unwrap();
+        let key = Key::builder(&addr).join(&sub_prefix).name("test").build();
+        assert_eq!(key.to_string(), format!("{}/sub/prefix/test", addr.raw()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_key_builder_invalid_name() {
+        let addr = address::testing::established_address_1();
+        let _ = Key::builder(&addr).name("?").build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_key_builder_invalid_seg() {
+        let addr = address::testing::established_address_1();
+        let _ = Key::builder(&addr).seg(&"a/b".to_owned()).build();
+    }
+
     #[test]
     fn test_predecessor_epochs() {
         let mut epochs = Epochs::default();
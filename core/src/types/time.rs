@@ -55,6 +55,26 @@ impl From<DurationSecs> for std::time::Duration {
     }
 }
 
+impl DurationSecs {
+    /// Checked duration addition. Returns `None` on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    /// Checked duration multiplication. Returns `None` on overflow.
+    pub fn checked_mul(self, rhs: u64) -> Option<Self> {
+        self.0.checked_mul(rhs).map(Self)
+    }
+
+    /// The number of whole periods of the given duration needed to cover this
+    /// duration, i.e. its division rounded up. A zero `period` is treated as
+    /// one second.
+    pub fn div_ceil(self, period: Self) -> u64 {
+        let period = period.0.max(1);
+        self.0 / period + u64::from(self.0 % period != 0)
+    }
+}
+
 impl Display for DurationSecs {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -133,6 +153,13 @@ impl DateTimeUtc {
     pub fn next_second(&self) -> Self {
         *self + DurationSecs(0)
     }
+
+    /// The duration from the given earlier time until this one, in whole
+    /// seconds, or zero if the given time is not earlier
+    pub fn duration_since(&self, earlier: DateTimeUtc) -> DurationSecs {
+        let secs = (self.0 - earlier.0).num_seconds();
+        DurationSecs(u64::try_from(secs).unwrap_or_default())
+    }
 }
 
 impl FromStr for DateTimeUtc {
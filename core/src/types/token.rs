@@ -6,17 +6,21 @@ use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
 use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
-use rust_decimal::prelude::{Decimal, ToPrimitive};
+use rust_decimal::prelude::{Decimal, FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::ibc::applications::transfer::Amount as IbcAmount;
 use crate::types::address::{masp, Address, DecodeError as AddressError};
 use crate::types::hash::Hash;
-use crate::types::storage::{DbKeySeg, Key, KeySeg};
+use crate::types::storage::{DbKeySeg, Key, KeyBuilder, KeySeg};
 
 /// Amount in micro units. For different granularity another representation
 /// might be more appropriate.
+///
+/// Amounts are 128-bit, but may be at most [`MAX_AMOUNT`], such that every
+/// amount has an exact [`Change`] and [`Decimal`]. The arithmetic of amounts
+/// is checked, and the operators panic on overflow and underflow.
 #[derive(
     Clone,
     Copy,
@@ -32,7 +36,7 @@ use crate::types::storage::{DbKeySeg, Key, KeySeg};
     Hash,
 )]
 pub struct Amount {
-    micro: u64,
+    micro: u128,
 }
 
 /// Maximum decimal places in a token [`Amount`] and [`Change`].
@@ -40,8 +44,11 @@ pub const MAX_DECIMAL_PLACES: u32 = 6;
 /// Decimal scale of token [`Amount`] and [`Change`].
 pub const SCALE: u64 = 1_000_000;
 
-/// The largest value that can be represented by this integer type
-pub const MAX_AMOUNT: Amount = Amount { micro: u64::MAX };
+/// The largest amount, which is the largest integer that fits the 96 bits of
+/// a [`Decimal`]
+pub const MAX_AMOUNT: Amount = Amount {
+    micro: (1 << 96) - 1,
+};
 
 /// A change in tokens amount
 pub type Change = i128;
@@ -60,32 +67,38 @@ impl Amount {
     /// Spend a given amount.
     /// Panics when given `amount` > `self.micro` amount.
     pub fn spend(&mut self, amount: &Amount) {
-        self.micro = self.micro.checked_sub(amount.micro).unwrap();
+        *self = self.checked_sub(*amount).unwrap();
     }
 
     /// Receive a given amount.
     /// Panics on overflow.
     pub fn receive(&mut self, amount: &Amount) {
-        self.micro = self.micro.checked_add(amount.micro).unwrap();
+        *self = self.checked_add(*amount).unwrap();
     }
 
     /// Create a new amount from whole number of tokens
     pub const fn whole(amount: u64) -> Self {
         Self {
-            micro: amount * SCALE,
+            micro: amount as u128 * SCALE as u128,
         }
     }
 
+    /// Create a new amount from a raw number of micro units. Returns `None`
+    /// if it's larger than [`MAX_AMOUNT`].
+    pub fn from_micro(micro: u128) -> Option<Self> {
+        Self { micro }.checked()
+    }
+
     /// Create a new amount with the maximum value
     pub fn max() -> Self {
-        Self { micro: u64::MAX }
+        MAX_AMOUNT
     }
 
     /// Checked addition. Returns `None` on overflow.
     pub fn checked_add(&self, amount: Amount) -> Option<Self> {
         self.micro
             .checked_add(amount.micro)
-            .map(|result| Self { micro: result })
+            .and_then(Self::from_micro)
     }
 
     /// Checked subtraction. Returns `None` on underflow
@@ -95,21 +108,35 @@ impl Amount {
             .map(|result| Self { micro: result })
     }
 
+    /// Checked multiplication. Returns `None` on overflow.
+    pub fn checked_mul(&self, factor: u64) -> Option<Self> {
+        self.micro
+            .checked_mul(factor as u128)
+            .and_then(Self::from_micro)
+    }
+
+    /// Checked conversion to `u64`, for the few places that need it, such as
+    /// the MASP and voting powers. Returns `None` if the amount overflows.
+    pub fn checked_to_u64(&self) -> Option<u64> {
+        u64::try_from(self.micro).ok()
+    }
+
     /// Create amount from Change
     ///
     /// # Panics
     ///
-    /// Panics if the change is negative or overflows `u64`.
+    /// Panics if the change is negative.
     pub fn from_change(change: Change) -> Self {
         Self {
-            micro: change as u64,
+            micro: u128::try_from(change).unwrap(),
         }
     }
 
     /// Convert the amount to [`Decimal`] ignoring its scale (i.e. as an integer
     /// in micro units).
     pub fn as_dec_unscaled(&self) -> Decimal {
-        Into::<Decimal>::into(self.micro)
+        Decimal::from_u128(self.micro)
+            .expect("Token amounts are at most MAX_AMOUNT, which fits Decimal")
     }
 
     /// Convert from a [`Decimal`] that's not scaled (i.e. an integer
@@ -117,11 +144,29 @@ impl Amount {
     ///
     /// # Panics
     ///
-    /// Panics if the given decimal is not an integer that fits `u64`.
+    /// Panics if the given decimal is not a non-negative integer.
     pub fn from_dec_unscaled(micro: Decimal) -> Self {
-        let res = micro.to_u64().unwrap();
+        let res = micro.to_u128().unwrap();
         Self { micro: res }
     }
+
+    /// Decode an amount from its Borsh encoding, or from the legacy encoding
+    /// of 64-bit amounts, see [`LegacyAmount`]
+    pub fn try_from_slice_compat(bytes: &[u8]) -> std::io::Result<Self> {
+        if bytes.len() == LegacyAmount::ENCODED_LEN {
+            LegacyAmount::try_from_slice(bytes).map(Self::from)
+        } else {
+            Self::try_from_slice(bytes)
+        }
+    }
+
+    fn checked(self) -> Option<Self> {
+        if self <= MAX_AMOUNT {
+            Some(self)
+        } else {
+            None
+        }
+    }
 }
 
 impl serde::Serialize for Amount {
@@ -149,52 +194,76 @@ impl<'de> serde::Deserialize<'de> for Amount {
     }
 }
 
+/// The amounts before they were 128-bit, whose Borsh encoding is found in the
+/// storage of chains that have not been migrated yet
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize,
+)]
+pub struct LegacyAmount {
+    micro: u64,
+}
+
+impl LegacyAmount {
+    /// The length of the Borsh encoding of legacy amounts
+    pub const ENCODED_LEN: usize = 8;
+    /// The length of the storage key segments of legacy amounts
+    pub const KEY_SEG_LEN: usize = 13;
+
+    /// Parse a legacy amount from a storage key segment. Returns `None` if
+    /// it's not the key segment of a legacy amount.
+    pub fn parse_key_seg(seg: &str) -> Option<Self> {
+        if seg.len() != Self::KEY_SEG_LEN {
+            return None;
+        }
+        u64::parse(seg.to_owned()).ok().map(|micro| Self { micro })
+    }
+}
+
+impl From<LegacyAmount> for Amount {
+    fn from(amount: LegacyAmount) -> Self {
+        Self::from(amount.micro)
+    }
+}
+
 impl From<Amount> for Decimal {
     fn from(amount: Amount) -> Self {
-        Into::<Decimal>::into(amount.micro)
+        amount.as_dec_unscaled()
     }
 }
 
 impl From<Decimal> for Amount {
     fn from(micro: Decimal) -> Self {
-        let res = micro.to_u64().unwrap();
-        Self { micro: res }
+        Self::from_dec_unscaled(micro)
     }
 }
 
 impl From<u64> for Amount {
     fn from(micro: u64) -> Self {
-        Self { micro }
-    }
-}
-
-impl From<Amount> for u64 {
-    fn from(amount: Amount) -> Self {
-        amount.micro
+        Self {
+            micro: micro as u128,
+        }
     }
 }
 
 impl From<Amount> for u128 {
     fn from(amount: Amount) -> Self {
-        u128::from(amount.micro)
+        amount.micro
     }
 }
 
 impl Add for Amount {
     type Output = Amount;
 
-    fn add(mut self, rhs: Self) -> Self::Output {
-        self.micro += rhs.micro;
-        self
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).expect("Token amount overflow")
     }
 }
 
 impl Mul<u64> for Amount {
     type Output = Amount;
 
-    fn mul(mut self, rhs: u64) -> Self::Output {
-        self.micro *= rhs;
-        self
+    fn mul(self, rhs: u64) -> Self::Output {
+        self.checked_mul(rhs).expect("Token amount overflow")
     }
 }
 
@@ -204,8 +273,10 @@ impl Mul<(u64, u64)> for Amount {
     type Output = (Amount, Amount);
 
     fn mul(mut self, rhs: (u64, u64)) -> Self::Output {
-        let ant = Amount::from((self.micro / rhs.1) * rhs.0);
-        self.micro %= rhs.1;
+        let ant = Amount {
+            micro: self.micro / rhs.1 as u128,
+        } * rhs.0;
+        self.micro %= rhs.1 as u128;
         (ant, self)
     }
 }
@@ -213,30 +284,28 @@ impl Mul<(u64, u64)> for Amount {
 impl Mul<Amount> for u64 {
     type Output = Amount;
 
-    fn mul(mut self, rhs: Amount) -> Self::Output {
-        self *= rhs.micro;
-        Self::Output::from(self)
+    fn mul(self, rhs: Amount) -> Self::Output {
+        rhs * self
     }
 }
 
 impl AddAssign for Amount {
     fn add_assign(&mut self, rhs: Self) {
-        self.micro += rhs.micro
+        *self = *self + rhs
     }
 }
 
 impl Sub for Amount {
     type Output = Amount;
 
-    fn sub(mut self, rhs: Self) -> Self::Output {
-        self.micro -= rhs.micro;
-        self
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs).expect("Token amount underflow")
     }
 }
 
 impl SubAssign for Amount {
     fn sub_assign(&mut self, rhs: Self) {
-        self.micro -= rhs.micro
+        *self = *self - rhs
     }
 }
 
@@ -251,8 +320,13 @@ impl KeySeg for Amount {
     where
         Self: Sized,
     {
-        let micro = u64::parse(string)?;
-        Ok(Self { micro })
+        let micro = u128::parse(string.clone())?;
+        Self::from_micro(micro).ok_or_else(|| {
+            super::storage::Error::ParseKeySeg(format!(
+                "Failed parsing {}, the amount is larger than the maximum",
+                string
+            ))
+        })
     }
 
     fn raw(&self) -> String {
@@ -276,6 +350,15 @@ pub enum AmountParseError {
     ScaleTooLarge(u32),
     #[error("Error decoding token amount, the value is within invalid range.")]
     InvalidRange,
+    #[error("Error decoding token amount, invalid token symbol: {0}")]
+    InvalidSymbol(String),
+    #[error("Unexpected token symbol {0}, expected {1}")]
+    UnexpectedSymbol(String, String),
+    #[error(
+        "The amount has {0} decimal places, but the token only has {1} \
+         decimal places"
+    )]
+    PrecisionLoss(u8, u8),
 }
 
 impl FromStr for Amount {
@@ -288,12 +371,12 @@ impl FromStr for Amount {
                 if scale > MAX_DECIMAL_PLACES {
                     return Err(AmountParseError::ScaleTooLarge(scale));
                 }
-                let whole =
-                    decimal * rust_decimal::Decimal::new(SCALE as i64, 0);
-                let micro: u64 =
-                    rust_decimal::prelude::ToPrimitive::to_u64(&whole)
-                        .ok_or(AmountParseError::InvalidRange)?;
-                Ok(Self { micro })
+                let whole = decimal
+                    .checked_mul(rust_decimal::Decimal::new(SCALE as i64, 0))
+                    .ok_or(AmountParseError::InvalidRange)?;
+                rust_decimal::prelude::ToPrimitive::to_u128(&whole)
+                    .and_then(Self::from_micro)
+                    .ok_or(AmountParseError::InvalidRange)
             }
             Err(err) => Err(AmountParseError::InvalidDecimal(err)),
         }
@@ -302,17 +385,249 @@ impl FromStr for Amount {
 
 impl Display for Amount {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let decimal = rust_decimal::Decimal::from_i128_with_scale(
-            self.micro as i128,
-            MAX_DECIMAL_PLACES,
+        write!(
+            f,
+            "{}",
+            format_decimal(self.micro, MAX_DECIMAL_PLACES as u8)
         )
-        .normalize();
-        write!(f, "{}", decimal)
+    }
+}
+
+/// Format an integer in units of `10^-decimals` as a decimal number without
+/// trailing zeroes. Unlike [`Decimal`], it's exact for any 128-bit integer.
+fn format_decimal(value: u128, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", value, width = decimals + 1);
+    let (int, frac) = digits.split_at(digits.len() - decimals);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        int.to_owned()
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+/// An amount of tokens with the number of its decimal places, as written by
+/// users, e.g. "1.5" or "1.5 NAM". It must be converted to the raw [`Amount`]
+/// of a token with the decimal places of the token, which rejects the amounts
+/// that cannot be represented exactly.
+#[derive(Clone, Debug, Eq)]
+pub struct DenominatedAmount {
+    /// The amount in units of `10^-denom`
+    amount: Amount,
+    /// The number of decimal places of the amount
+    denom: u8,
+    /// The symbol of the token of the amount, if given
+    symbol: Option<String>,
+}
+
+impl DenominatedAmount {
+    /// An amount in units of `10^-denom`
+    pub fn new(amount: Amount, denom: u8) -> Self {
+        Self {
+            amount,
+            denom: denom.min(MAX_METADATA_DECIMALS),
+            symbol: None,
+        }
+    }
+
+    /// A raw amount of a token with the default decimal places, see
+    /// [`MAX_DECIMAL_PLACES`]
+    pub fn native(amount: Amount) -> Self {
+        Self::new(amount, MAX_DECIMAL_PLACES as u8)
+    }
+
+    /// Set the symbol of the token of the amount
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// The amount in units of `10^-denom`
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// The number of decimal places of the amount
+    pub fn denom(&self) -> u8 {
+        self.denom
+    }
+
+    /// The symbol of the token of the amount, if given
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
+
+    /// The decimal value of the amount, if it fits the 96 bits of a
+    /// [`Decimal`]
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        let mut decimal = Decimal::from_u128(self.amount.micro)?;
+        decimal.set_scale(self.denom as u32).ok()?;
+        Some(decimal)
+    }
+
+    /// Convert to the raw amount of a token with the given decimal places.
+    /// Fails if the amount cannot be represented exactly or overflows.
+    pub fn to_raw(&self, denom: u8) -> Result<Amount, AmountParseError> {
+        if denom >= self.denom {
+            10_u128
+                .checked_pow((denom - self.denom) as u32)
+                .and_then(|scale| self.amount.micro.checked_mul(scale))
+                .and_then(Amount::from_micro)
+                .ok_or(AmountParseError::InvalidRange)
+        } else {
+            // The scale always fits, as there are at most 28 decimal places
+            let scale = 10_u128.pow((self.denom - denom) as u32);
+            if self.amount.micro % scale == 0 {
+                Ok(Amount {
+                    micro: self.amount.micro / scale,
+                })
+            } else {
+                Err(AmountParseError::PrecisionLoss(self.denom, denom))
+            }
+        }
+    }
+
+    /// Convert to the raw amount of the token with the given metadata. Fails
+    /// if the amount has the symbol of another token, cannot be represented
+    /// exactly with the decimal places of the token, or overflows.
+    pub fn to_amount(
+        &self,
+        metadata: &TokenMetadata,
+    ) -> Result<Amount, AmountParseError> {
+        match self.symbol() {
+            Some(symbol) if !symbol.eq_ignore_ascii_case(&metadata.symbol) => {
+                Err(AmountParseError::UnexpectedSymbol(
+                    symbol.to_owned(),
+                    metadata.symbol.clone(),
+                ))
+            }
+            _ => self.to_raw(metadata.decimals),
+        }
+    }
+
+    /// Add two amounts of the same token, returning `None` if they have the
+    /// symbols of different tokens or on overflow
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        self.checked_op(other, Amount::checked_add)
+    }
+
+    /// Subtract two amounts of the same token, returning `None` if they have
+    /// the symbols of different tokens or on underflow
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_op(other, Amount::checked_sub)
+    }
+
+    fn checked_op(
+        &self,
+        other: &Self,
+        op: impl FnOnce(&Amount, Amount) -> Option<Amount>,
+    ) -> Option<Self> {
+        let symbol = match (&self.symbol, &other.symbol) {
+            (Some(lhs), Some(rhs)) if !lhs.eq_ignore_ascii_case(rhs) => {
+                return None;
+            }
+            (Some(symbol), _) | (_, Some(symbol)) => Some(symbol.clone()),
+            (None, None) => None,
+        };
+        // Operate on the amounts with the larger number of decimal places
+        let denom = self.denom.max(other.denom);
+        let lhs = self.to_raw(denom).ok()?;
+        let rhs = other.to_raw(denom).ok()?;
+        Some(Self {
+            amount: op(&lhs, rhs)?,
+            denom,
+            symbol,
+        })
+    }
+}
+
+/// Amounts are equal if they have the same value, whatever their number of
+/// decimal places, and the same symbol, if any
+impl PartialEq for DenominatedAmount {
+    fn eq(&self, other: &Self) -> bool {
+        let same_symbol = match (&self.symbol, &other.symbol) {
+            (Some(lhs), Some(rhs)) => lhs.eq_ignore_ascii_case(rhs),
+            (None, None) => true,
+            _ => false,
+        };
+        // Compare the amounts with the larger number of decimal places, which
+        // may only overflow for the larger amount
+        let denom = self.denom.max(other.denom);
+        same_symbol
+            && match (self.to_raw(denom), other.to_raw(denom)) {
+                (Ok(lhs), Ok(rhs)) => lhs == rhs,
+                _ => false,
+            }
+    }
+}
+
+impl FromStr for DenominatedAmount {
+    type Err = AmountParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let amount = parts.next().unwrap_or_default();
+        let symbol = parts.next();
+        if let Some(extra) = parts.next() {
+            return Err(AmountParseError::InvalidSymbol(extra.to_owned()));
+        }
+        let decimal = Decimal::from_str(amount)
+            .map_err(AmountParseError::InvalidDecimal)?
+            .normalize();
+        if decimal.is_sign_negative() && !decimal.is_zero() {
+            return Err(AmountParseError::InvalidRange);
+        }
+        let micro = Amount::from_micro(decimal.mantissa().unsigned_abs())
+            .ok_or(AmountParseError::InvalidRange)?;
+        let mut amount = Self::new(micro, decimal.scale() as u8);
+        if let Some(symbol) = symbol {
+            if !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(AmountParseError::InvalidSymbol(symbol.to_owned()));
+            }
+            amount = amount.with_symbol(symbol);
+        }
+        Ok(amount)
+    }
+}
+
+impl Display for DenominatedAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_decimal(self.amount.micro, self.denom))?;
+        if let Some(symbol) = &self.symbol {
+            write!(f, " {}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::Serialize for DenominatedAmount {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(&self.to_string(), serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DenominatedAmount {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let amount_string: String =
+            serde::Deserialize::deserialize(deserializer)?;
+        Self::from_str(&amount_string).map_err(D::Error::custom)
     }
 }
 
 impl From<Amount> for Change {
     fn from(amount: Amount) -> Self {
+        // Exact, as amounts are at most `Change::MAX`
         amount.micro as i128
     }
 }
@@ -321,10 +636,7 @@ impl TryFrom<IbcAmount> for Amount {
     type Error = AmountParseError;
 
     fn try_from(amount: IbcAmount) -> Result<Self, Self::Error> {
-        // TODO: https://github.com/anoma/namada/issues/1089
-        if amount > u64::MAX.into() {
-            return Err(AmountParseError::InvalidRange);
-        }
+        // The parsing rejects the amounts larger than `MAX_AMOUNT`
         Self::from_str(&amount.to_string())
     }
 }
@@ -339,22 +651,72 @@ pub const TX_KEY_PREFIX: &str = "tx-";
 pub const CONVERSION_KEY_PREFIX: &str = "conv";
 /// Key segment prefix for pinned shielded transactions
 pub const PIN_KEY_PREFIX: &str = "pin-";
+/// Key segment for the metadata of a token
+pub const METADATA_STORAGE_KEY: &str = "metadata";
+/// Key segment for the address of the hook of a token
+pub const HOOK_STORAGE_KEY: &str = "hook";
+/// Key segment for the mint caps of the tokens of a hook
+pub const MINT_CAP_STORAGE_KEY: &str = "mint_cap";
+/// Key segment for the minted supply of the tokens of a hook
+pub const MINTED_STORAGE_KEY: &str = "minted";
+/// Key segment for the transfer freezes of the tokens of a hook
+pub const FROZEN_STORAGE_KEY: &str = "frozen";
 const TOTAL_SUPPLY_STORAGE_KEY: &str = "total_supply";
 
+/// The maximum number of decimal places of the display of token amounts
+pub const MAX_METADATA_DECIMALS: u8 = 28;
+
+/// The metadata of a token, written at its initialization, with which clients
+/// display its amounts
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct TokenMetadata {
+    /// The symbol of the token, e.g. "NAM"
+    pub symbol: String,
+    /// The number of decimal places of the display of the raw amounts of the
+    /// token, at most [`MAX_METADATA_DECIMALS`]
+    pub decimals: u8,
+    /// The hash of the icon of the token, if any
+    pub icon_hash: Option<Hash>,
+}
+
+impl TokenMetadata {
+    /// The metadata of a token with the given symbol, whose amounts are
+    /// displayed with [`MAX_DECIMAL_PLACES`] decimal places
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            decimals: MAX_DECIMAL_PLACES as u8,
+            icon_hash: None,
+        }
+    }
+
+    /// Format the given raw amount of the token with its decimal places
+    pub fn format_amount(&self, amount: &Amount) -> String {
+        format_decimal(amount.micro, self.decimals.min(MAX_METADATA_DECIMALS))
+    }
+}
+
 /// Obtain a storage key for user's balance.
 pub fn balance_key(token_addr: &Address, owner: &Address) -> Key {
-    Key::from(token_addr.to_db_key())
-        .push(&BALANCE_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
-        .push(&owner.to_db_key())
-        .expect("Cannot obtain a storage key")
+    Key::builder(token_addr)
+        .name(BALANCE_STORAGE_KEY)
+        .seg(owner)
+        .build()
 }
 
 /// Obtain a storage key prefix for all users' balances.
 pub fn balance_prefix(token_addr: &Address) -> Key {
-    Key::from(token_addr.to_db_key())
-        .push(&BALANCE_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(token_addr).name(BALANCE_STORAGE_KEY).build()
 }
 
 /// Obtain a storage key prefix for multitoken balances.
@@ -362,16 +724,62 @@ pub fn multitoken_balance_prefix(
     token_addr: &Address,
     sub_prefix: &Key,
 ) -> Key {
-    Key::from(token_addr.to_db_key()).join(sub_prefix)
+    Key::builder(token_addr).join(sub_prefix).build()
 }
 
 /// Obtain a storage key for user's multitoken balance.
 pub fn multitoken_balance_key(prefix: &Key, owner: &Address) -> Key {
-    prefix
-        .push(&BALANCE_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
-        .push(&owner.to_db_key())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(prefix.clone())
+        .name(BALANCE_STORAGE_KEY)
+        .seg(owner)
+        .build()
+}
+
+/// Obtain a storage key for user's balance of a token, which is held either
+/// in the token's own balances or, with a sub prefix, in one of its multitoken
+/// balances.
+pub fn token_balance_key(
+    token_addr: &Address,
+    sub_prefix: Option<&Key>,
+    owner: &Address,
+) -> Key {
+    match sub_prefix {
+        Some(sub_prefix) => {
+            let prefix = multitoken_balance_prefix(token_addr, sub_prefix);
+            multitoken_balance_key(&prefix, owner)
+        }
+        None => balance_key(token_addr, owner),
+    }
+}
+
+/// Check if the given storage key is a balance key of the given token, either
+/// of its own balances or of its multitokens. If it is, returns the sub prefix
+/// of the multitoken, if any, and the owner.
+pub fn is_token_balance_key<'a>(
+    token_addr: &Address,
+    key: &'a Key,
+) -> Option<(Option<Key>, &'a Address)> {
+    is_balance_key(token_addr, key)
+        .map(|owner| (None, owner))
+        .or_else(|| {
+            is_multitoken_balance_key(token_addr, key)
+                .map(|(sub_prefix, owner)| (Some(sub_prefix), owner))
+        })
+}
+
+/// Check if the given storage key is a balance key of unspecified token,
+/// either of its own balances or of its multitokens. If it is, returns the
+/// token and the owner.
+pub fn is_any_token_or_multitoken_balance_key(
+    key: &Key,
+) -> Option<(&Address, &Address)> {
+    match key.segments.first() {
+        Some(DbKeySeg::AddressSeg(token_addr)) => {
+            is_token_balance_key(token_addr, key)
+                .map(|(_sub_prefix, owner)| (token_addr, owner))
+        }
+        _ => None,
+    }
 }
 
 /// Check if the given storage key is balance key for the given token. If it is,
@@ -413,11 +821,105 @@ pub fn is_masp_key(key: &Key) -> bool {
                     || key.starts_with(PIN_KEY_PREFIX)))
 }
 
+/// Storage key for the index of the next shielded transaction of the MASP
+pub fn masp_head_tx_key() -> Key {
+    Key::builder(&masp()).name(HEAD_TX_KEY).build()
+}
+
+/// Storage key for the shielded transaction of the MASP with the given index
+pub fn masp_tx_key(index: u64) -> Key {
+    Key::builder(&masp())
+        .name(format!("{}{}", TX_KEY_PREFIX, index))
+        .build()
+}
+
+/// Storage key for the index of the shielded transaction of the MASP pinned
+/// to the given key
+pub fn masp_pin_key(key: &str) -> Key {
+    Key::builder(&masp())
+        .name(format!("{}{}", PIN_KEY_PREFIX, key))
+        .build()
+}
+
 /// Storage key for total supply of a token
 pub fn total_supply_key(token_address: &Address) -> Key {
-    Key::from(token_address.to_db_key())
-        .push(&TOTAL_SUPPLY_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(token_address)
+        .name(TOTAL_SUPPLY_STORAGE_KEY)
+        .build()
+}
+
+/// Storage key for the metadata of a token
+pub fn metadata_key(token_address: &Address) -> Key {
+    Key::builder(token_address)
+        .name(METADATA_STORAGE_KEY)
+        .build()
+}
+
+/// Is storage key for the metadata of a specific token?
+pub fn is_metadata_key(key: &Key, token_address: &Address) -> bool {
+    matches!(&key.segments[..], [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)] if addr == token_address && key == METADATA_STORAGE_KEY)
+}
+
+/// Storage key for the address of the hook of a token, whose VP has to
+/// validate every transaction changing the token's balances
+pub fn hook_key(token_address: &Address) -> Key {
+    Key::builder(token_address).name(HOOK_STORAGE_KEY).build()
+}
+
+/// Is storage key for the hook of a specific token?
+pub fn is_hook_key(key: &Key, token_address: &Address) -> bool {
+    matches!(&key.segments[..], [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)] if addr == token_address && key == HOOK_STORAGE_KEY)
+}
+
+/// Storage key in the subspace of a hook for the cap of the minted supply of
+/// a token
+pub fn mint_cap_key(hook: &Address, token_address: &Address) -> Key {
+    hook_token_key(hook, MINT_CAP_STORAGE_KEY, token_address)
+}
+
+/// Storage key in the subspace of a hook for the supply of a token minted
+/// through IBC, which is bounded by its mint cap
+pub fn minted_key(hook: &Address, token_address: &Address) -> Key {
+    hook_token_key(hook, MINTED_STORAGE_KEY, token_address)
+}
+
+/// Storage key in the subspace of a hook for the freeze of all the transfers
+/// of a token
+pub fn frozen_key(hook: &Address, token_address: &Address) -> Key {
+    hook_token_key(hook, FROZEN_STORAGE_KEY, token_address)
+}
+
+/// Storage key in the subspace of a hook for the freeze of the transfers of a
+/// token from and to an account
+pub fn frozen_account_key(
+    hook: &Address,
+    token_address: &Address,
+    owner: &Address,
+) -> Key {
+    KeyBuilder::from(frozen_key(hook, token_address))
+        .seg(owner)
+        .build()
+}
+
+/// Check if the given storage key is a key of the minted supply of a token of
+/// the given hook. If it is, returns the token.
+pub fn is_minted_key<'a>(hook: &Address, key: &'a Key) -> Option<&'a Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(key),
+            DbKeySeg::AddressSeg(token_addr),
+        ] if addr == hook && key == MINTED_STORAGE_KEY => Some(token_addr),
+        _ => None,
+    }
+}
+
+fn hook_token_key(
+    hook: &Address,
+    segment: &str,
+    token_address: &Address,
+) -> Key {
+    Key::builder(hook).name(segment).seg(token_address).build()
 }
 
 /// Is storage key for total supply of a specific token?
@@ -544,11 +1046,122 @@ mod tests {
 
         let zero = Amount::from(0);
         assert_eq!("0", zero.to_string());
+
+        assert_eq!("79228162514264337593543.950335", MAX_AMOUNT.to_string());
+        assert_eq!(
+            MAX_AMOUNT,
+            Amount::from_str("79228162514264337593543.950335").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_token_metadata_display() {
+        let nam = TokenMetadata::new("NAM");
+        assert_eq!(nam.format_amount(&Amount::from(1_500_000)), "1.5");
+        assert_eq!(nam.format_amount(&Amount::from(0)), "0");
+
+        let eth = TokenMetadata {
+            decimals: 18,
+            ..TokenMetadata::new("ETH")
+        };
+        assert_eq!(
+            eth.format_amount(&Amount::from(u64::MAX)),
+            "18.446744073709551615"
+        );
+
+        let whole = TokenMetadata {
+            decimals: 0,
+            ..TokenMetadata::new("APFEL")
+        };
+        assert_eq!(whole.format_amount(&Amount::from(123000)), "123000");
+    }
+
+    #[test]
+    fn test_denominated_amount_parse() {
+        let amount: DenominatedAmount = "1.50 NAM".parse().unwrap();
+        assert_eq!(amount.amount(), Amount::from(15));
+        assert_eq!(amount.denom(), 1);
+        assert_eq!(amount.symbol(), Some("NAM"));
+        assert_eq!(amount.to_string(), "1.5 NAM");
+
+        let amount: DenominatedAmount = "100".parse().unwrap();
+        assert_eq!(amount, DenominatedAmount::new(Amount::from(100), 0));
+        assert_eq!(amount, DenominatedAmount::new(Amount::from(100_000), 3));
+        assert_eq!(amount.symbol(), None);
+
+        assert!(matches!(
+            "-1".parse::<DenominatedAmount>(),
+            Err(AmountParseError::InvalidRange)
+        ));
+        assert!(matches!(
+            "1.5 NAM BTC".parse::<DenominatedAmount>(),
+            Err(AmountParseError::InvalidSymbol(_))
+        ));
+        assert!(matches!(
+            "1.5 N-M".parse::<DenominatedAmount>(),
+            Err(AmountParseError::InvalidSymbol(_))
+        ));
+        assert!(matches!(
+            "NAM".parse::<DenominatedAmount>(),
+            Err(AmountParseError::InvalidDecimal(_))
+        ));
+    }
+
+    #[test]
+    fn test_denominated_amount_to_amount() {
+        let nam = TokenMetadata::new("NAM");
+        let amount: DenominatedAmount = "1.5 nam".parse().unwrap();
+        assert_eq!(amount.to_amount(&nam).unwrap(), Amount::from(1_500_000));
+        let amount: DenominatedAmount = "1.5".parse().unwrap();
+        assert_eq!(amount.to_amount(&nam).unwrap(), Amount::from(1_500_000));
+
+        let amount: DenominatedAmount = "1.5 BTC".parse().unwrap();
+        assert!(matches!(
+            amount.to_amount(&nam),
+            Err(AmountParseError::UnexpectedSymbol(found, expected))
+                if found == "BTC" && expected == "NAM"
+        ));
+        let amount: DenominatedAmount = "0.0000001".parse().unwrap();
+        assert!(matches!(
+            amount.to_amount(&nam),
+            Err(AmountParseError::PrecisionLoss(7, 6))
+        ));
+        let amount = DenominatedAmount::new(MAX_AMOUNT, 0);
+        assert!(matches!(
+            amount.to_amount(&nam),
+            Err(AmountParseError::InvalidRange)
+        ));
+        // Trailing zeroes beyond the decimal places of the token are exact
+        let amount = DenominatedAmount::new(Amount::from(1_500_000_000), 9);
+        assert_eq!(amount.to_amount(&nam).unwrap(), Amount::from(1_500_000));
+    }
+
+    #[test]
+    fn test_denominated_amount_checked_arithmetic() {
+        let lhs: DenominatedAmount = "1.5 NAM".parse().unwrap();
+        let rhs: DenominatedAmount = "0.25".parse().unwrap();
+        assert_eq!(lhs.checked_add(&rhs).unwrap().to_string(), "1.75 NAM");
+        assert_eq!(lhs.checked_sub(&rhs).unwrap().to_string(), "1.25 NAM");
+        assert_eq!(rhs.checked_sub(&lhs), None);
+
+        let btc: DenominatedAmount = "1 BTC".parse().unwrap();
+        assert_eq!(lhs.checked_add(&btc), None);
+        let max = DenominatedAmount::new(MAX_AMOUNT, 0);
+        assert_eq!(max.checked_add(&rhs), None);
+    }
+
+    #[test]
+    fn test_denominated_amount_serde() {
+        let amount: DenominatedAmount = "1.5 NAM".parse().unwrap();
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, "\"1.5 NAM\"");
+        let decoded: DenominatedAmount = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, amount);
     }
 
     #[test]
     fn test_amount_checked_sub() {
-        let max = Amount::from(u64::MAX);
+        let max = MAX_AMOUNT;
         let one = Amount::from(1);
         let zero = Amount::from(0);
 
@@ -561,9 +1174,25 @@ mod tests {
         assert_eq!(max.checked_sub(max), Some(zero));
     }
 
+    #[test]
+    #[should_panic(expected = "Token amount underflow")]
+    fn test_amount_sub_underflow() {
+        let _ = Amount::from(0) - Amount::from(1);
+    }
+
+    #[test]
+    fn test_amount_key_seg() {
+        let raw = MAX_AMOUNT.raw();
+        assert_eq!(Amount::parse(raw).unwrap(), MAX_AMOUNT);
+
+        // Keys of amounts larger than the maximum are rejected
+        let raw = (MAX_AMOUNT.micro + 1).raw();
+        assert!(Amount::parse(raw).is_err());
+    }
+
     #[test]
     fn test_amount_checked_add() {
-        let max = Amount::from(u64::MAX);
+        let max = MAX_AMOUNT;
         let one = Amount::from(1);
         let zero = Amount::from(0);
 
@@ -575,6 +1204,59 @@ mod tests {
         assert_eq!(max.checked_add(zero), Some(max));
         assert_eq!(max.checked_add(one), None);
         assert_eq!(max.checked_add(max), None);
+
+        // Amounts overflowing `u64` are fine
+        let large = Amount::from(u64::MAX);
+        assert_eq!(
+            large.checked_add(large),
+            Amount::from_micro(2 * u64::MAX as u128)
+        );
+        assert_eq!(large.checked_to_u64(), Some(u64::MAX));
+        assert_eq!((large + one).checked_to_u64(), None);
+    }
+
+    #[test]
+    fn test_amount_checked_mul() {
+        let one = Amount::from(1);
+        assert_eq!(one.checked_mul(u64::MAX), Some(Amount::from(u64::MAX)));
+        assert_eq!(MAX_AMOUNT.checked_mul(1), Some(MAX_AMOUNT));
+        assert_eq!(MAX_AMOUNT.checked_mul(2), None);
+        assert_eq!(Amount::from_micro(MAX_AMOUNT.micro + 1), None);
+        assert_eq!(MAX_AMOUNT.change(), (1 << 96) - 1);
+    }
+
+    #[test]
+    fn test_amount_max_conversions() {
+        // The largest amount converts exactly to a decimal
+        let decimal = Decimal::from(MAX_AMOUNT);
+        assert_eq!(decimal, Decimal::MAX);
+        assert_eq!(Amount::from(decimal), MAX_AMOUNT);
+
+        // The conversion to `u64` fails
+        assert_eq!(MAX_AMOUNT.checked_to_u64(), None);
+    }
+
+    #[test]
+    fn test_amount_borsh_compat() {
+        let amount = Amount::from(u64::MAX) + Amount::from(1);
+        let bytes = amount.try_to_vec().unwrap();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(Amount::try_from_slice_compat(&bytes).unwrap(), amount);
+
+        // The legacy encoding of amounts is still accepted
+        let legacy = LegacyAmount { micro: 42 }.try_to_vec().unwrap();
+        assert_eq!(legacy.len(), LegacyAmount::ENCODED_LEN);
+        assert_eq!(
+            Amount::try_from_slice_compat(&legacy).unwrap(),
+            Amount::from(42)
+        );
+
+        // So are the storage key segments of legacy amounts
+        let seg = 42_u64.raw();
+        assert_eq!(seg.len(), LegacyAmount::KEY_SEG_LEN);
+        let legacy = LegacyAmount::parse_key_seg(&seg).unwrap();
+        assert_eq!(Amount::from(legacy), Amount::from(42));
+        assert_eq!(LegacyAmount::parse_key_seg(&Amount::from(42).raw()), None);
     }
 
     #[test]
@@ -585,6 +1267,37 @@ mod tests {
         let non_zero = Amount::from(1);
         assert!(!non_zero.is_zero());
     }
+
+    #[test]
+    fn test_token_balance_keys() {
+        let token = crate::types::address::nam();
+        let owner = crate::types::address::testing::established_address_1();
+        let sub_prefix = Key::parse("ibc/denom").unwrap();
+
+        let key = token_balance_key(&token, None, &owner);
+        assert_eq!(key, balance_key(&token, &owner));
+        assert_eq!(is_token_balance_key(&token, &key), Some((None, &owner)));
+
+        let key = token_balance_key(&token, Some(&sub_prefix), &owner);
+        assert_eq!(
+            is_token_balance_key(&token, &key),
+            Some((Some(sub_prefix), &owner))
+        );
+        assert_eq!(
+            is_any_token_or_multitoken_balance_key(&key),
+            Some((&token, &owner))
+        );
+        let other_token =
+            crate::types::address::testing::established_address_2();
+        assert_eq!(is_token_balance_key(&other_token, &key), None);
+
+        let hook = crate::types::address::testing::established_address_3();
+        assert_eq!(
+            is_minted_key(&hook, &minted_key(&hook, &token)),
+            Some(&token)
+        );
+        assert_eq!(is_minted_key(&hook, &mint_cap_key(&hook, &token)), None);
+    }
 }
 
 /// Helpers for testing with addresses.
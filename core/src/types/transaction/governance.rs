@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::types::address::Address;
 use crate::types::governance::{
-    self, Proposal, ProposalError, ProposalVote, VoteType,
+    self, ParameterChange, Proposal, ProposalError, ProposalVote, VoteType,
 };
 use crate::types::storage::Epoch;
 
@@ -26,6 +26,8 @@ pub enum ProposalType {
     PGFCouncil,
     /// ETH proposal
     ETHBridge,
+    /// Parameter change proposal
+    ParameterChange(Vec<ParameterChange>),
 }
 
 impl Display for ProposalType {
@@ -34,6 +36,7 @@ impl Display for ProposalType {
             ProposalType::Default(_) => write!(f, "Default"),
             ProposalType::PGFCouncil => write!(f, "PGF Council"),
             ProposalType::ETHBridge => write!(f, "ETH Bridge"),
+            ProposalType::ParameterChange(_) => write!(f, "Parameter Change"),
         }
     }
 }
@@ -41,7 +44,7 @@ impl Display for ProposalType {
 impl PartialEq<VoteType> for ProposalType {
     fn eq(&self, other: &VoteType) -> bool {
         match self {
-            Self::Default(_) => {
+            Self::Default(_) | Self::ParameterChange(_) => {
                 matches!(other, VoteType::Default)
             }
             Self::PGFCouncil => {
@@ -71,6 +74,9 @@ impl TryFrom<governance::ProposalType> for ProposalType {
             }
             governance::ProposalType::PGFCouncil => Ok(Self::PGFCouncil),
             governance::ProposalType::ETHBridge => Ok(Self::ETHBridge),
+            governance::ProposalType::ParameterChange(changes) => {
+                Ok(Self::ParameterChange(changes))
+            }
         }
     }
 }
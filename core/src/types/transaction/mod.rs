@@ -10,6 +10,7 @@ pub mod governance;
 pub mod pos;
 /// transaction protocols made by validators
 pub mod protocol;
+pub mod result;
 /// wrapper txs with encrypted payloads
 pub mod wrapper;
 
@@ -21,6 +22,7 @@ pub use decrypted::*;
 #[cfg(feature = "ferveo-tpke")]
 pub use encrypted::EncryptionKey;
 pub use protocol::UpdateDkgSessionKey;
+pub use result::{ResultCode, TxResultDetails};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -167,6 +169,44 @@ pub struct InitAccount {
     pub public_key: common::PublicKey,
     /// The VP code hash
     pub vp_code_hash: Hash,
+    /// The public keys of the other signers of a multisig account, which
+    /// together with the public key above form its signer set
+    pub cosigners: Vec<common::PublicKey>,
+    /// The number of signatures of its signer set required by a multisig
+    /// account. The account is not a multisig if none is given.
+    pub threshold: Option<u8>,
+}
+
+impl InitAccount {
+    /// Get the signer set of the multisig account to initialize, if it is
+    /// one, along with its threshold
+    pub fn signers(&self) -> Option<(Vec<common::PublicKey>, u8)> {
+        self.threshold.map(|threshold| {
+            let mut pks = vec![self.public_key.clone()];
+            pks.extend(self.cosigners.iter().cloned());
+            (pks, threshold)
+        })
+    }
+}
+
+/// A tx data type to update the signer set of a multisig account
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateSigners {
+    /// An address of the multisig account
+    pub addr: Address,
+    /// The public keys of the new signers
+    pub public_keys: Vec<common::PublicKey>,
+    /// The number of signatures of the new signers required by the account
+    pub threshold: u8,
 }
 
 /// A tx data type to initialize a new validator account.
@@ -362,7 +402,7 @@ mod test_process_tx {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -398,7 +438,7 @@ mod test_process_tx {
             },
             &keypair,
             Epoch(0),
-            0.into(),
+            GasLimit::from_multiplier(0),
             #[cfg(not(feature = "mainnet"))]
             None,
         ))));
@@ -467,7 +507,9 @@ fn test_process_tx_decrypted_signed() {
     let ed_sig =
         ed25519::Signature::try_from_slice([0u8; 64].as_ref()).unwrap();
     let mut sig_sec = Signature::new(&decrypted.header_hash(), &gen_keypair());
-    sig_sec.signature = common::Signature::try_from_sig(&ed_sig).unwrap();
+    for signature in sig_sec.signatures.values_mut() {
+        *signature = common::Signature::try_from_sig(&ed_sig).unwrap();
+    }
     decrypted.add_section(Section::Signature(sig_sec));
     // create the tx with signed decrypted data
     let code_sec = decrypted
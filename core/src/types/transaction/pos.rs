@@ -5,6 +5,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::types::address::Address;
+use crate::types::key::common;
 use crate::types::token;
 
 /// A bond is a validator's self-bond or a delegation from non-validator to a
@@ -34,6 +35,30 @@ pub struct Bond {
 /// An unbond of a bond.
 pub type Unbond = Bond;
 
+/// A redelegation of bonded tokens from one validator to another.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct Redelegation {
+    /// Source validator address
+    pub src_validator: Address,
+    /// Destination validator address
+    pub dest_validator: Address,
+    /// Owner (delegator) of the bond
+    pub owner: Address,
+    /// The amount of tokens
+    pub amount: token::Amount,
+}
+
 /// A withdrawal of an unbond.
 #[derive(
     Debug,
@@ -55,6 +80,9 @@ pub struct Withdraw {
     pub source: Option<Address>,
 }
 
+/// A claim of the rewards of a bond.
+pub type ClaimRewards = Withdraw;
+
 /// A change to the validator commission rate.
 #[derive(
     Debug,
@@ -74,3 +102,23 @@ pub struct CommissionChange {
     /// The new commission rate
     pub new_rate: Decimal,
 }
+
+/// A rotation of the consensus key of a validator.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct ConsensusKeyChange {
+    /// Validator address
+    pub validator: Address,
+    /// The new consensus key, active from the pipeline epoch
+    pub consensus_key: common::PublicKey,
+}
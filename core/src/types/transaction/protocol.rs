@@ -1,9 +1,15 @@
 /// Types for sending and verifying txs
 /// used in Namada protocols
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use crate::types::address::Address;
+use crate::types::ethereum_events::EthereumEvent;
+use crate::types::hash::Hash;
+use crate::types::key::signer::{Signer, SignerError};
+use crate::types::key::{common, SigScheme, VerifySigError};
+use crate::types::storage::Epoch;
+use crate::types::token;
 
 /// A data type containing information used to update the DKG session key
 #[derive(
@@ -22,16 +28,141 @@ pub struct UpdateDkgSessionKey {
     pub dkg_public_key: Vec<u8>,
 }
 
+/// The signature by a consensus validator of the hash of the validator set of
+/// the next epoch, which lets the light clients of the chain, such as the
+/// contract of a bridge, verify the transitions of its validator set
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct ValidatorSetUpdate {
+    /// The address of the signing validator
+    pub validator: Address,
+    /// The epoch of the signed validator set
+    pub epoch: Epoch,
+    /// The hash of the signed validator set, see [`valset_hash`]
+    pub valset_hash: Hash,
+    /// The signature of the epoch and of the hash of the validator set
+    pub sig: common::Signature,
+}
+
+impl ValidatorSetUpdate {
+    /// Sign the hash of the validator set of an epoch with the protocol key
+    /// of a validator
+    pub fn new(
+        validator: Address,
+        epoch: Epoch,
+        valset_hash: Hash,
+        signer: &dyn Signer,
+    ) -> Result<Self, SignerError> {
+        let sig = signer.sign(&Self::signed_bytes(epoch, &valset_hash))?;
+        Ok(Self {
+            validator,
+            epoch,
+            valset_hash,
+            sig,
+        })
+    }
+
+    /// Check that the update was signed with the given protocol key
+    pub fn verify(&self, pk: &common::PublicKey) -> Result<(), VerifySigError> {
+        common::SigScheme::verify_signature_raw(
+            pk,
+            &Self::signed_bytes(self.epoch, &self.valset_hash),
+            &self.sig,
+        )
+    }
+
+    fn signed_bytes(epoch: Epoch, valset_hash: &Hash) -> Vec<u8> {
+        (epoch, valset_hash)
+            .try_to_vec()
+            .expect("Encoding a validator set update shouldn't fail")
+    }
+}
+
+/// The vote of a consensus validator on the events of the contract of the
+/// Ethereum bridge that it observed
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct EthereumEventsVote {
+    /// The address of the voting validator
+    pub validator: Address,
+    /// The observed events
+    pub events: Vec<EthereumEvent>,
+    /// The signature of the events
+    pub sig: common::Signature,
+}
+
+impl EthereumEventsVote {
+    /// Sign the observed events with the protocol key of a validator
+    pub fn new(
+        validator: Address,
+        events: Vec<EthereumEvent>,
+        signer: &dyn Signer,
+    ) -> Result<Self, SignerError> {
+        let sig = signer.sign(&Self::signed_bytes(&events))?;
+        Ok(Self {
+            validator,
+            events,
+            sig,
+        })
+    }
+
+    /// Check that the vote was signed with the given protocol key
+    pub fn verify(&self, pk: &common::PublicKey) -> Result<(), VerifySigError> {
+        common::SigScheme::verify_signature_raw(
+            pk,
+            &Self::signed_bytes(&self.events),
+            &self.sig,
+        )
+    }
+
+    fn signed_bytes(events: &[EthereumEvent]) -> Vec<u8> {
+        events
+            .try_to_vec()
+            .expect("Encoding Ethereum events shouldn't fail")
+    }
+}
+
+/// Hash a validator set, given as the protocol keys and the bonded stakes of
+/// its validators. The validators are sorted by their keys, such that the
+/// hash doesn't depend on their order.
+pub fn valset_hash(
+    validators: impl IntoIterator<Item = (common::PublicKey, token::Amount)>,
+) -> Hash {
+    let mut validators: Vec<_> = validators.into_iter().collect();
+    validators.sort();
+    Hash::sha256(
+        validators
+            .try_to_vec()
+            .expect("Encoding a validator set shouldn't fail"),
+    )
+}
+
 #[cfg(feature = "ferveo-tpke")]
 mod protocol_txs {
     use std::io::{ErrorKind, Write};
     use std::path::Path;
 
-    use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
     use ferveo::dkg::pv::Message;
     use serde_json;
 
     use super::*;
+    use crate::ledger::oracle::SignedOraclePrices;
     use crate::proto::{Code, Data, Section, Signature, Tx, TxError};
     use crate::types::chain::ChainId;
     use crate::types::key::*;
@@ -104,17 +235,44 @@ mod protocol_txs {
         /// Aggregation of Ethereum state changes
         /// voted on by validators in last block
         EthereumStateUpdate,
+        /// Aggregation of the prices of the fee tokens reported by the
+        /// validators in the vote extensions of the last block
+        OraclePrices(Vec<SignedOraclePrices>),
+        /// Signature by a validator of the validator set of the next epoch
+        ValidatorSetUpdate(ValidatorSetUpdate),
+        /// Vote of a validator on the events of the Ethereum bridge
+        EthereumEvents(EthereumEventsVote),
     }
 
     impl ProtocolTxType {
-        /// Create a new tx requesting a new DKG session keypair
+        /// Create a new protocol tx, signed by the given signer of the
+        /// protocol key
+        pub fn sign(
+            self,
+            signer: &dyn Signer,
+            chain_id: ChainId,
+        ) -> Result<Tx, SignerError> {
+            let mut outer_tx =
+                Tx::new(TxType::Protocol(Box::new(ProtocolTx {
+                    pk: signer.public_key()?,
+                    tx: self,
+                })));
+            outer_tx.header.chain_id = chain_id;
+            let mut signature = Signature::unsigned(&outer_tx.header_hash());
+            signature.sign_with(signer)?;
+            outer_tx.add_section(Section::Signature(signature));
+            Ok(outer_tx)
+        }
+
+        /// Create a new tx requesting a new DKG session keypair, signed by
+        /// the given signer of the protocol key
         pub fn request_new_dkg_keypair<'a, F>(
             data: UpdateDkgSessionKey,
-            signing_key: &common::SecretKey,
+            signer: &dyn Signer,
             wasm_dir: &'a Path,
             wasm_loader: F,
             chain_id: ChainId,
-        ) -> Tx
+        ) -> Result<Tx, SignerError>
         where
             F: FnOnce(&'a str, &'static str) -> Vec<u8>,
         {
@@ -126,7 +284,7 @@ mod protocol_txs {
             );
             let mut outer_tx =
                 Tx::new(TxType::Protocol(Box::new(ProtocolTx {
-                    pk: signing_key.ref_to(),
+                    pk: signer.public_key()?,
                     tx: Self::NewDkgKeypair,
                 })));
             outer_tx.header.chain_id = chain_id;
@@ -135,11 +293,10 @@ mod protocol_txs {
                 data.try_to_vec()
                     .expect("Serializing request should not fail"),
             ));
-            outer_tx.add_section(Section::Signature(Signature::new(
-                &outer_tx.header_hash(),
-                signing_key,
-            )));
-            outer_tx
+            let mut signature = Signature::unsigned(&outer_tx.header_hash());
+            signature.sign_with(signer)?;
+            outer_tx.add_section(Section::Signature(signature));
+            Ok(outer_tx)
         }
     }
 
@@ -0,0 +1,201 @@
+//! The stable codes and the machine-readable details of the results of the
+//! txs, which are sent back to the clients in the tx events.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::TxResult;
+use crate::types::address::Address;
+
+/// The codes of the results of the txs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResultCode {
+    /// The tx was applied successfully
+    Ok = 0,
+    /// The decrypted tx was signed for another chain
+    InvalidDecryptedChainId = 1,
+    /// The decrypted tx expired
+    ExpiredDecryptedTx = 2,
+    /// The tx failed to execute or ran out of gas
+    WasmRuntimeError = 3,
+    /// The tx was rejected
+    InvalidTx = 4,
+    /// The signature of the tx is invalid
+    InvalidSig = 5,
+    /// The tx is out of order in the block
+    InvalidOrder = 6,
+    /// The block contains more decrypted txs than wrappers
+    ExtraTxs = 7,
+    /// The tx couldn't be decrypted
+    Undecryptable = 8,
+    /// The tx doesn't fit in the space allocated to its kind in the block
+    AllocationError = 9,
+    /// The tx was already applied
+    ReplayTx = 10,
+    /// The tx was signed for another chain
+    InvalidChainId = 11,
+    /// The tx expired
+    ExpiredTx = 12,
+}
+
+impl ResultCode {
+    /// All the codes, in order
+    pub const ALL: [ResultCode; 13] = [
+        ResultCode::Ok,
+        ResultCode::InvalidDecryptedChainId,
+        ResultCode::ExpiredDecryptedTx,
+        ResultCode::WasmRuntimeError,
+        ResultCode::InvalidTx,
+        ResultCode::InvalidSig,
+        ResultCode::InvalidOrder,
+        ResultCode::ExtraTxs,
+        ResultCode::Undecryptable,
+        ResultCode::AllocationError,
+        ResultCode::ReplayTx,
+        ResultCode::InvalidChainId,
+        ResultCode::ExpiredTx,
+    ];
+
+    /// Checks if the given [`ResultCode`] value is a protocol level error,
+    /// that can be recovered from at the finalize block stage.
+    pub const fn is_recoverable(&self) -> bool {
+        use ResultCode::*;
+        // NOTE: pattern match on all `ResultCode` variants, in order
+        // to catch potential bugs when adding new codes
+        match self {
+            Ok
+            | InvalidDecryptedChainId
+            | ExpiredDecryptedTx
+            | WasmRuntimeError => true,
+            InvalidTx | InvalidSig | InvalidOrder | ExtraTxs
+            | Undecryptable | AllocationError | ReplayTx | InvalidChainId
+            | ExpiredTx => false,
+        }
+    }
+
+    /// Get the code from its numeric value, if it's a known code
+    pub fn from_u32(code: u32) -> Option<Self> {
+        Self::ALL.get(code as usize).copied()
+    }
+
+    /// The numeric value of the code
+    pub const fn to_u32(self) -> u32 {
+        self as u32
+    }
+}
+
+impl fmt::Display for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            ResultCode::Ok => "The transaction was applied",
+            ResultCode::InvalidDecryptedChainId => {
+                "The decrypted transaction was signed for another chain"
+            }
+            ResultCode::ExpiredDecryptedTx => {
+                "The decrypted transaction expired"
+            }
+            ResultCode::WasmRuntimeError => {
+                "The transaction failed to execute or ran out of gas"
+            }
+            ResultCode::InvalidTx => "The transaction was rejected",
+            ResultCode::InvalidSig => {
+                "The signature of the transaction is invalid"
+            }
+            ResultCode::InvalidOrder => {
+                "The transaction is out of order in the block"
+            }
+            ResultCode::ExtraTxs => {
+                "The block contains more decrypted transactions than wrappers"
+            }
+            ResultCode::Undecryptable => {
+                "The transaction could not be decrypted"
+            }
+            ResultCode::AllocationError => {
+                "The transaction doesn't fit in the block space allocated to \
+                 its kind"
+            }
+            ResultCode::ReplayTx => "The transaction was already applied",
+            ResultCode::InvalidChainId => {
+                "The transaction was signed for another chain"
+            }
+            ResultCode::ExpiredTx => "The transaction expired",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+impl From<ResultCode> for u32 {
+    fn from(code: ResultCode) -> u32 {
+        code.to_u32()
+    }
+}
+
+impl From<ResultCode> for String {
+    fn from(code: ResultCode) -> String {
+        u32::from(code).to_string()
+    }
+}
+
+/// The machine-readable details of the result of a tx
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxResultDetails {
+    /// The gas used by the tx
+    pub gas_used: u64,
+    /// The storage keys changed by the tx
+    pub changed_keys: Vec<String>,
+    /// The addresses whose VPs rejected the tx
+    pub rejected_vps: Vec<Address>,
+    /// The errors of the VPs, by their addresses
+    pub vp_errors: Vec<(Address, String)>,
+    /// The error that prevented the application of the tx, if any
+    pub error: Option<String>,
+}
+
+impl TxResultDetails {
+    /// The details of a tx that failed with the given error
+    pub fn error(gas_used: u64, error: impl ToString) -> Self {
+        Self {
+            gas_used,
+            error: Some(error.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&TxResult> for TxResultDetails {
+    fn from(result: &TxResult) -> Self {
+        Self {
+            gas_used: result.gas_used,
+            changed_keys: result
+                .changed_keys
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            rejected_vps: result
+                .vps_result
+                .rejected_vps
+                .iter()
+                .cloned()
+                .collect(),
+            vp_errors: result.vps_result.errors.clone(),
+            error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the numeric values of the codes are stable
+    #[test]
+    fn test_result_codes_roundtrip() {
+        for (value, code) in ResultCode::ALL.into_iter().enumerate() {
+            assert_eq!(code.to_u32(), value as u32);
+            assert_eq!(ResultCode::from_u32(value as u32), Some(code));
+        }
+        assert_eq!(ResultCode::from_u32(ResultCode::ALL.len() as u32), None);
+        assert_eq!(String::from(ResultCode::ExpiredTx), "12");
+    }
+}
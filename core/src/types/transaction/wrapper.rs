@@ -11,14 +11,31 @@ pub mod wrapper_tx {
     use thiserror::Error;
 
     use crate::types::address::Address;
+    use crate::types::hash::Hash;
     use crate::types::key::*;
-    use crate::types::storage::Epoch;
+    use crate::types::storage::{BlockHeight, Epoch};
     use crate::types::token::Amount;
 
-    /// Minimum fee amount in micro NAMs
+    /// Fixed fee amount in micro NAMs of wrapper txs in testnets, also the
+    /// floor of discounted bundle fees. The minimum fee of a wrapper tx
+    /// otherwise depends on its gas limit, see
+    /// [`WrapperTx::validate_min_gas_price`].
     pub const MIN_FEE: u64 = 100;
-    /// TODO: Determine a sane number for this
-    const GAS_LIMIT_RESOLUTION: u64 = 1_000_000;
+    /// Maximum discount, in basis points, applied to the fees of bundled txs
+    pub const MAX_BUNDLE_DISCOUNT_BPS: u64 = 5_000;
+    /// The number of basis points in a whole
+    const BPS_SCALE: u64 = 10_000;
+    /// The default resolution of gas limits, used at genesis when the
+    /// chain parameters don't specify one
+    pub const DEFAULT_GAS_LIMIT_RESOLUTION: u64 = 1_000_000;
+    /// The current wire version of serialized wrapper txs
+    pub const WRAPPER_WIRE_VERSION: u16 = 0;
+    /// The length in bytes of the wire version prefix of a serialized
+    /// wrapper tx
+    const WIRE_VERSION_LEN: usize = 2;
+    /// The minimum percentage by which a wrapper must raise the gas price of
+    /// a pending wrapper of the same inner tx to replace it in the mempool
+    pub const MIN_GAS_PRICE_BUMP_PCT: u64 = 10;
 
     /// Errors relating to decrypting a wrapper tx and its
     /// encrypted payload from a Tx type
@@ -38,6 +55,41 @@ pub mod wrapper_tx {
              differs from that in the WrapperTx"
         )]
         InvalidKeyPair,
+        #[error("The fee token {0} is not registered")]
+        UnknownFeeToken(Address),
+        #[error("The fee {fee} is lower than the minimum fee {floor}")]
+        FeeTooLow { fee: Amount, floor: Amount },
+        #[error(
+            "Found wrapper with wire version {found}, expected wire version \
+             {expected}"
+        )]
+        MixedWireVersions { expected: u16, found: u16 },
+        #[error(
+            "The gas limit of {multiplier} times the resolution \
+             {resolution} overflows"
+        )]
+        GasLimitOverflow { multiplier: u64, resolution: u64 },
+        #[error("The gas limit {limit} exceeds the gas ceiling {ceiling}")]
+        GasLimitTooHigh { limit: u64, ceiling: u64 },
+        #[error(
+            "The wrapper epoch {epoch} is too far from the current epoch \
+             {current}"
+        )]
+        EpochOutOfWindow { epoch: Epoch, current: Epoch },
+        #[error(
+            "The ciphertext size of {size} bytes exceeds the maximum of {max} \
+             bytes"
+        )]
+        CiphertextTooLarge { size: usize, max: usize },
+        #[error("The PoW solution of the wrapper is invalid")]
+        InvalidPowSolution,
+        #[error("The wrapper cannot replace the pending wrapper: {0}")]
+        InvalidReplacement(String),
+        #[error(
+            "The gas price {gas_price} is lower than the minimum {min} to \
+             replace the pending wrapper"
+        )]
+        ReplacementUnderpriced { gas_price: Amount, min: Amount },
     }
 
     /// A fee is an amount of a specified token
@@ -59,13 +111,68 @@ pub mod wrapper_tx {
         pub token: Address,
     }
 
-    /// Gas limits must be multiples of GAS_LIMIT_RESOLUTION
-    /// This is done to minimize the amount of information leak from
-    /// a wrapper tx. The larger the GAS_LIMIT_RESOLUTION, the
-    /// less info leaked.
+    impl Fee {
+        /// Compute the per-tx fee of a bundle of `count` txs sharing a fee
+        /// payer. Each tx in the bundle beyond the first discounts the
+        /// `base` fee by `discount_bps_per_tx` basis points, up to a total
+        /// discount of [`MAX_BUNDLE_DISCOUNT_BPS`]. The resulting fee is
+        /// never lower than [`MIN_FEE`].
+        pub fn bundle_discount(
+            base: Amount,
+            count: usize,
+            discount_bps_per_tx: u16,
+            token: Address,
+        ) -> Fee {
+            let extra_txs = count.saturating_sub(1) as u64;
+            let discount_bps = extra_txs
+                .saturating_mul(u64::from(discount_bps_per_tx))
+                .min(MAX_BUNDLE_DISCOUNT_BPS);
+            let discounted = u128::from(base)
+                * u128::from(BPS_SCALE - discount_bps)
+                / u128::from(BPS_SCALE);
+            // the discounted fee is never larger than the base fee,
+            // therefore it is a valid amount
+            let amount = Amount::from_micro(discounted)
+                .expect("The discounted fee must be a valid amount")
+                .max(Amount::from(MIN_FEE));
+            Fee { amount, token }
+        }
+
+        /// Estimate the fee to pay in `token` for a tx that consumed
+        /// `gas_used` gas, given the `base` fee already converted into
+        /// `token`. The gas used is rounded up to a multiple of the gas
+        /// limit `resolution`, and the tip for the resulting gas limit at
+        /// the given `gas_price` is added on top of the base fee.
+        ///
+        /// Returns the fee with the gas limit to set in the wrapper, or
+        /// `None` if the computation overflows.
+        pub fn estimate(
+            base: Amount,
+            gas_used: u64,
+            resolution: u64,
+            gas_price: Option<Amount>,
+            token: Address,
+        ) -> Option<(Fee, GasLimit)> {
+            let gas_limit = GasLimit::from_raw(gas_used, resolution);
+            let tip = match gas_price {
+                Some(gas_price) => {
+                    gas_price.checked_mul(gas_limit.checked_raw(resolution)?)?
+                }
+                None => Amount::default(),
+            };
+            let amount = base.checked_add(tip)?;
+            Some((Fee { amount, token }, gas_limit))
+        }
+    }
+
+    /// Gas limits must be multiples of the gas limit resolution, a chain
+    /// parameter. This is done to minimize the amount of information leak
+    /// from a wrapper tx. The larger the resolution, the less info leaked.
     ///
-    /// This struct only stores the multiple of GAS_LIMIT_RESOLUTION,
-    /// not the raw amount
+    /// This struct only stores the multiple of the resolution, not the raw
+    /// amount, which is also how it's serialized. Use [`GasLimit::from_raw`]
+    /// and [`GasLimit::checked_raw`] to convert from and into raw numbers
+    /// with the resolution of a given chain.
     #[derive(
         Debug,
         Clone,
@@ -77,74 +184,225 @@ pub mod wrapper_tx {
         BorshSchema,
         Eq,
     )]
-    #[serde(from = "u64")]
-    #[serde(into = "u64")]
+    #[serde(transparent)]
     pub struct GasLimit {
         multiplier: u64,
     }
 
     impl GasLimit {
-        /// We refund unused gas up to GAS_LIMIT_RESOLUTION
-        pub fn refund_amount(&self, used_gas: u64) -> Amount {
-            if used_gas < (u64::from(self) - GAS_LIMIT_RESOLUTION) {
-                // we refund only up to GAS_LIMIT_RESOLUTION
-                GAS_LIMIT_RESOLUTION
-            } else if used_gas >= u64::from(self) {
+        /// A gas limit of the given multiple of the gas limit resolution
+        pub const fn from_multiplier(multiplier: u64) -> GasLimit {
+            GasLimit { multiplier }
+        }
+
+        /// The multiple of the gas limit resolution of this gas limit
+        pub fn multiplier(&self) -> u64 {
+            self.multiplier
+        }
+
+        /// Round the raw gas `amount` up to the next highest multiple of
+        /// the given gas limit `resolution`, which must be non-zero
+        pub fn from_raw(amount: u64, resolution: u64) -> GasLimit {
+            // we could use the ceiling function but this way avoids casts to
+            // floats
+            let multiplier = amount / resolution;
+            if resolution * multiplier < amount {
+                GasLimit {
+                    multiplier: multiplier + 1,
+                }
+            } else {
+                GasLimit { multiplier }
+            }
+        }
+
+        /// Get back the gas limit as a raw number, given the gas limit
+        /// `resolution`. Returns `None` if the raw number overflows.
+        pub fn checked_raw(&self, resolution: u64) -> Option<u64> {
+            self.multiplier.checked_mul(resolution)
+        }
+
+        /// Get back the gas limit as a raw number, given the gas limit
+        /// `resolution`, saturating at the max `u64` on overflow
+        fn saturating_raw(&self, resolution: u64) -> u64 {
+            self.multiplier.saturating_mul(resolution)
+        }
+
+        /// We refund unused gas up to the gas limit `resolution`
+        pub fn refund_amount(&self, used_gas: u64, resolution: u64) -> Amount {
+            let raw = self.saturating_raw(resolution);
+            if used_gas < self.full_refund_threshold(resolution) {
+                // we refund only up to the resolution
+                resolution
+            } else if used_gas >= raw {
                 // Gas limit was under estimated, no refund
                 0
             } else {
                 // compute refund
-                u64::from(self) - used_gas
+                raw - used_gas
             }
             .into()
         }
-    }
 
-    /// Round the input number up to the next highest multiple
-    /// of GAS_LIMIT_RESOLUTION
-    impl From<u64> for GasLimit {
-        fn from(amount: u64) -> GasLimit {
-            // we could use the ceiling function but this way avoids casts to
-            // floats
-            if GAS_LIMIT_RESOLUTION * (amount / GAS_LIMIT_RESOLUTION) < amount {
-                GasLimit {
-                    multiplier: (amount / GAS_LIMIT_RESOLUTION) + 1,
-                }
-            } else {
-                GasLimit {
-                    multiplier: (amount / GAS_LIMIT_RESOLUTION),
-                }
+        /// The largest refund that may be issued for this gas limit, given
+        /// the gas limit `resolution`
+        pub fn max_refund(&self, resolution: u64) -> Amount {
+            self.saturating_raw(resolution).min(resolution).into()
+        }
+
+        /// The gas usage at or below which the refund is capped at the gas
+        /// limit `resolution`, i.e. the knee of the refund curve
+        pub fn full_refund_threshold(&self, resolution: u64) -> u64 {
+            self.saturating_raw(resolution).saturating_sub(resolution)
+        }
+
+        /// Round `base` up to the next multiple of the given gas limit
+        /// `resolution`, which must be non-zero, then add a number of
+        /// resolution units, at most `max_extra_units`, pseudo-randomly
+        /// derived from `seed`.
+        ///
+        /// Jittering gas limits makes wrapper txs with similar gas
+        /// requirements harder to tell apart. The same seed always
+        /// yields the same gas limit.
+        pub fn with_jitter(
+            base: u64,
+            resolution: u64,
+            seed: &[u8; 32],
+            max_extra_units: u64,
+        ) -> GasLimit {
+            let digest = Sha256::digest(seed);
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&digest[..8]);
+            let random = u64::from_le_bytes(bytes);
+            let extra_units = match max_extra_units.checked_add(1) {
+                Some(bound) => random % bound,
+                None => random,
+            };
+            let GasLimit { multiplier } = GasLimit::from_raw(base, resolution);
+            GasLimit {
+                multiplier: multiplier.saturating_add(extra_units),
             }
         }
-    }
 
-    /// Round the input number up to the next highest multiple
-    /// of GAS_LIMIT_RESOLUTION
-    impl From<Amount> for GasLimit {
-        fn from(amount: Amount) -> GasLimit {
-            GasLimit::from(u64::from(amount))
+        /// Check if this gas limit is within `tolerance_pct` percent
+        /// (inclusive) of the `recommended` gas limit, in either direction
+        pub fn within_tolerance(
+            &self,
+            recommended: &GasLimit,
+            tolerance_pct: u8,
+        ) -> bool {
+            let raw = u128::from(self.multiplier);
+            let recommended = u128::from(recommended.multiplier);
+            let deviation = raw.abs_diff(recommended);
+            deviation * 100 <= recommended * u128::from(tolerance_pct)
         }
     }
 
-    /// Get back the gas limit as a raw number
-    impl From<&GasLimit> for u64 {
-        fn from(limit: &GasLimit) -> u64 {
-            limit.multiplier * GAS_LIMIT_RESOLUTION
-        }
+    /// Compute the largest total refund that may have to be issued for
+    /// the given gas limits, e.g. those of all the wrappers in a block,
+    /// given the gas limit `resolution`.
+    ///
+    /// The total saturates at the max [`Amount`], instead of overflowing.
+    pub fn max_refund_liability(
+        limits: &[GasLimit],
+        resolution: u64,
+    ) -> Amount {
+        limits.iter().fold(Amount::default(), |total, limit| {
+            total
+                .checked_add(limit.max_refund(resolution))
+                .unwrap_or_else(Amount::max)
+        })
     }
 
-    /// Get back the gas limit as a raw number
-    impl From<GasLimit> for u64 {
-        fn from(limit: GasLimit) -> u64 {
-            limit.multiplier * GAS_LIMIT_RESOLUTION
-        }
+    /// Read the wire version of a serialized wrapper tx, without decoding
+    /// the rest of it. The wire version is encoded in the first two bytes of
+    /// a wrapper, in little endian order.
+    pub fn peek_wire_version(tx: &[u8]) -> Result<u16, WrapperTxErr> {
+        let prefix = tx
+            .get(..WIRE_VERSION_LEN)
+            .ok_or(WrapperTxErr::InvalidWrapperTx)?;
+        Ok(u16::from_le_bytes([prefix[0], prefix[1]]))
     }
 
-    /// Get back the gas limit as a raw number, viewed as an Amount
-    impl From<GasLimit> for Amount {
-        fn from(limit: GasLimit) -> Amount {
-            Amount::from(limit.multiplier * GAS_LIMIT_RESOLUTION)
+    /// Check that all the given serialized wrapper txs share the same wire
+    /// version, and return it. This is a cheap check to perform before fully
+    /// decoding the wrappers in a block.
+    ///
+    /// An empty list of wrappers is trivially uniform, on the current
+    /// [`WRAPPER_WIRE_VERSION`].
+    pub fn validate_uniform_wire_version(
+        txs: &[&[u8]],
+    ) -> Result<u16, WrapperTxErr> {
+        let mut versions = txs.iter().map(|tx| peek_wire_version(tx));
+        let expected = match versions.next() {
+            Some(version) => version?,
+            None => return Ok(WRAPPER_WIRE_VERSION),
+        };
+        for found in versions {
+            let found = found?;
+            if found != expected {
+                return Err(WrapperTxErr::MixedWireVersions {
+                    expected,
+                    found,
+                });
+            }
         }
+        Ok(expected)
+    }
+
+    /// The validation rules a wrapper tx is checked against by
+    /// [`WrapperTx::enforce`]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct WrapperPolicy {
+        /// The minimum fee a wrapper must pay
+        pub fee_floor: Amount,
+        /// The max number of epochs the epoch of a wrapper may be away
+        /// from the current epoch
+        pub epoch_window: u64,
+        /// The max size in bytes of the encrypted payload of a wrapper
+        pub max_ciphertext_size: usize,
+        #[cfg(not(feature = "mainnet"))]
+        /// Whether a valid PoW solution exempts a wrapper from paying the
+        /// minimum fee
+        pub pow_exempts_fee: bool,
+    }
+
+    /// The state a wrapper tx is checked against, together with a
+    /// [`WrapperPolicy`], by [`WrapperTx::enforce`]
+    pub struct PolicyContext<'a> {
+        /// The current epoch
+        pub current_epoch: Epoch,
+        /// The max gas limit a wrapper may declare
+        pub gas_ceiling: u64,
+        /// The gas limit resolution of the chain
+        pub gas_limit_resolution: u64,
+        /// Lookup of the tokens fees may be paid in
+        pub registry: &'a dyn Fn(&Address) -> bool,
+        /// The size in bytes of the encrypted payload of the wrapper
+        pub ciphertext_size: usize,
+    }
+
+    /// A compact record of the metadata of a wrapper tx that was
+    /// included in a block, for wallets to keep track of
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        BorshSerialize,
+        BorshDeserialize,
+        BorshSchema,
+        Serialize,
+        Deserialize,
+    )]
+    pub struct InclusionReceipt {
+        /// The hash of the included wrapper tx
+        pub wrapper_hash: Hash,
+        /// The epoch the wrapper tx was submitted in
+        pub epoch: Epoch,
+        /// The fee paid for including the wrapper tx
+        pub fee: Fee,
+        /// The gas limit of the wrapper tx
+        pub gas_limit: GasLimit,
     }
 
     /// A transaction with an encrypted payload as well
@@ -160,6 +418,10 @@ pub mod wrapper_tx {
         Deserialize,
     )]
     pub struct WrapperTx {
+        /// The wire version of the serialization of the wrapper. It's
+        /// serialized first, such that [`peek_wire_version`] can read it
+        /// without decoding the rest of the wrapper.
+        pub wire_version: u16,
         /// The fee to be payed for including the tx
         pub fee: Fee,
         /// Used to determine an implicit account of the fee payer
@@ -169,6 +431,30 @@ pub mod wrapper_tx {
         pub epoch: Epoch,
         /// Max amount of gas that can be used when executing the inner tx
         pub gas_limit: GasLimit,
+        /// The hash of an optional MASP transaction section of the outer tx,
+        /// unshielding the funds to pay the fee with. This section is left
+        /// unencrypted, as it's executed before the fee is charged.
+        pub unshield: Option<Hash>,
+        /// An optional tip offered per unit of gas on top of the fee, in the
+        /// fee token. Wrappers offering a higher gas price are prioritized
+        /// when building a block.
+        pub gas_price: Option<Amount>,
+        /// The optional last block height at which the wrapper may be
+        /// included in a block
+        pub expiration_height: Option<BlockHeight>,
+        /// The optional sequence number of this wrapper among the ones paid
+        /// by the fee payer. If present, it must match the next nonce of the
+        /// fee payer stored in the ledger.
+        pub nonce: Option<u64>,
+        /// The public key of an optional sponsor paying the fee of this
+        /// wrapper in place of its signer. The sponsor must sign the header
+        /// of the outer tx as well.
+        pub sponsor: Option<common::PublicKey>,
+        /// The address of an optional established account paying the fee of
+        /// this wrapper, e.g. a multisig. The outer tx must then carry a
+        /// signature over its header made with the public key of the
+        /// account, the one its VP checks. Takes precedence over the sponsor.
+        pub fee_payer_address: Option<Address>,
         #[cfg(not(feature = "mainnet"))]
         /// A PoW solution can be used to allow zero-fee testnet transactions
         pub pow_solution: Option<crate::ledger::testnet_pow::Solution>,
@@ -189,19 +475,181 @@ pub mod wrapper_tx {
             >,
         ) -> WrapperTx {
             Self {
+                wire_version: WRAPPER_WIRE_VERSION,
                 fee,
                 pk: keypair.ref_to(),
                 epoch,
                 gas_limit,
+                unshield: None,
+                gas_price: None,
+                expiration_height: None,
+                nonce: None,
+                sponsor: None,
+                fee_payer_address: None,
                 #[cfg(not(feature = "mainnet"))]
                 pow_solution,
             }
         }
 
-        /// Get the address of the implicit account associated
-        /// with the public key
+        /// Pay the fee of this wrapper with the funds unshielded by the MASP
+        /// transaction section with the given hash, which must be attached
+        /// to the outer tx
+        pub fn with_unshield(mut self, unshield: Hash) -> Self {
+            self.unshield = Some(unshield);
+            self
+        }
+
+        /// Offer the given tip per unit of gas to prioritize this wrapper
+        pub fn with_gas_price(mut self, gas_price: Amount) -> Self {
+            self.gas_price = Some(gas_price);
+            self
+        }
+
+        /// Forbid the inclusion of this wrapper in any block above the given
+        /// height
+        pub fn with_expiration_height(mut self, height: BlockHeight) -> Self {
+            self.expiration_height = Some(height);
+            self
+        }
+
+        /// Sequence this wrapper with the given nonce of its fee payer
+        pub fn with_nonce(mut self, nonce: u64) -> Self {
+            self.nonce = Some(nonce);
+            self
+        }
+
+        /// Have the fee of this wrapper paid by the implicit account of the
+        /// given sponsor public key
+        pub fn with_sponsor(mut self, sponsor: common::PublicKey) -> Self {
+            self.sponsor = Some(sponsor);
+            self
+        }
+
+        /// Make the established account with the given address pay the fee
+        /// of this wrapper
+        pub fn with_fee_payer_address(mut self, address: Address) -> Self {
+            self.fee_payer_address = Some(address);
+            self
+        }
+
+        /// Check if this wrapper may no longer be included in a block at the
+        /// given height
+        pub fn is_expired_at(&self, height: BlockHeight) -> bool {
+            matches!(self.expiration_height, Some(exp) if height > exp)
+        }
+
+        /// The gas price offered by this wrapper, which is zero if it
+        /// offers no tip
+        pub fn effective_gas_price(&self) -> Amount {
+            self.gas_price.unwrap_or_default()
+        }
+
+        /// The minimum gas price of a wrapper replacing this one in the
+        /// mempool, which is [`MIN_GAS_PRICE_BUMP_PCT`] percent above its
+        /// gas price, rounded up, and at least one unit above it.
+        ///
+        /// Returns `None` if the computation overflows.
+        pub fn min_replacement_gas_price(&self) -> Option<Amount> {
+            let gas_price = u128::from(self.effective_gas_price());
+            let bumped = gas_price
+                .checked_mul(u128::from(100 + MIN_GAS_PRICE_BUMP_PCT))?
+                .checked_add(99)?
+                / 100;
+            Amount::from_micro(bumped.max(gas_price.checked_add(1)?))
+        }
+
+        /// Check that this wrapper may replace the given `pending` wrapper of
+        /// the same inner tx in the mempool. The replacement must be paid by
+        /// the same fee payer in the same token, must not lower the gas limit
+        /// and must raise the gas price by at least
+        /// [`MIN_GAS_PRICE_BUMP_PCT`] percent.
+        pub fn validate_replacement(
+            &self,
+            pending: &WrapperTx,
+        ) -> Result<(), WrapperTxErr> {
+            if self.fee_payer() != pending.fee_payer() {
+                return Err(WrapperTxErr::InvalidReplacement(
+                    "the fee payer differs".to_string(),
+                ));
+            }
+            if self.fee.token != pending.fee.token {
+                return Err(WrapperTxErr::InvalidReplacement(
+                    "the fee token differs".to_string(),
+                ));
+            }
+            if self.gas_limit.multiplier < pending.gas_limit.multiplier {
+                return Err(WrapperTxErr::InvalidReplacement(
+                    "the gas limit is lower".to_string(),
+                ));
+            }
+            let min = pending.min_replacement_gas_price().ok_or_else(|| {
+                WrapperTxErr::InvalidReplacement(
+                    "the gas price of the pending wrapper can't be raised"
+                        .to_string(),
+                )
+            })?;
+            let gas_price = self.effective_gas_price();
+            if gas_price < min {
+                return Err(WrapperTxErr::ReplacementUnderpriced {
+                    gas_price,
+                    min,
+                });
+            }
+            Ok(())
+        }
+
+        /// The total tip offered by this wrapper, i.e. its gas price for its
+        /// entire gas limit, given the gas limit `resolution`.
+        ///
+        /// Returns `None` if the computation overflows.
+        pub fn tip(&self, resolution: u64) -> Option<Amount> {
+            match self.gas_price {
+                Some(gas_price) => gas_price
+                    .checked_mul(self.gas_limit.checked_raw(resolution)?),
+                None => Some(Amount::default()),
+            }
+        }
+
+        /// Create the skeleton of a new wrapper tx from the public key of the
+        /// fee payer, rather than from its secret key. This supports offline
+        /// signing workflows, where a coordinator builds the wrapper and a
+        /// separate signer produces its signature.
+        ///
+        /// The returned wrapper carries no signature: the caller must attach
+        /// the signature over the header of the enclosing tx separately.
+        pub fn unsigned_skeleton(
+            fee: Fee,
+            pk: common::PublicKey,
+            epoch: Epoch,
+            gas_limit: GasLimit,
+        ) -> WrapperTx {
+            Self {
+                wire_version: WRAPPER_WIRE_VERSION,
+                fee,
+                pk,
+                epoch,
+                gas_limit,
+                unshield: None,
+                gas_price: None,
+                expiration_height: None,
+                nonce: None,
+                sponsor: None,
+                fee_payer_address: None,
+                #[cfg(not(feature = "mainnet"))]
+                pow_solution: None,
+            }
+        }
+
+        /// Get the address of the account paying the fee, which is the
+        /// given fee payer address if any, or else the implicit address of
+        /// the sponsor if any, or else the one of the signer
         pub fn fee_payer(&self) -> Address {
-            Address::from(&self.pk)
+            match &self.fee_payer_address {
+                Some(address) => address.clone(),
+                None => {
+                    Address::from(self.sponsor.as_ref().unwrap_or(&self.pk))
+                }
+            }
         }
 
         /// Produce a SHA-256 hash of this section
@@ -211,6 +659,152 @@ pub mod wrapper_tx {
             );
             hasher
         }
+
+        /// Produce a receipt of the inclusion of this wrapper, bundling its
+        /// hash together with its epoch, fee and gas limit
+        pub fn inclusion_receipt(&self) -> InclusionReceipt {
+            InclusionReceipt {
+                wrapper_hash: Hash(
+                    self.hash(&mut Sha256::new()).finalize_reset().into(),
+                ),
+                epoch: self.epoch,
+                fee: self.fee.clone(),
+                gas_limit: self.gas_limit.clone(),
+            }
+        }
+
+        /// Check that the fee token of this wrapper is known to the given
+        /// token registry lookup
+        pub fn verify_registered_token(
+            &self,
+            registry: &dyn Fn(&Address) -> bool,
+        ) -> Result<(), WrapperTxErr> {
+            if registry(&self.fee.token) {
+                Ok(())
+            } else {
+                Err(WrapperTxErr::UnknownFeeToken(self.fee.token.clone()))
+            }
+        }
+
+        /// Check that the fee of this wrapper is at least the given `floor`.
+        ///
+        /// Unlike [`MIN_FEE`], the floor may be adjusted dynamically, e.g.
+        /// to account for network congestion.
+        pub fn validate_dynamic_fee(
+            &self,
+            floor: Amount,
+        ) -> Result<(), WrapperTxErr> {
+            if self.fee.amount >= floor {
+                Ok(())
+            } else {
+                Err(WrapperTxErr::FeeTooLow {
+                    fee: self.fee.amount,
+                    floor,
+                })
+            }
+        }
+
+        /// Check that the fee of this wrapper is at least its gas limit,
+        /// given the gas limit `resolution`, times the `min_gas_price` of
+        /// the chain
+        pub fn validate_min_gas_price(
+            &self,
+            resolution: u64,
+            min_gas_price: Amount,
+        ) -> Result<(), WrapperTxErr> {
+            let floor = self
+                .gas_limit
+                .checked_raw(resolution)
+                .and_then(|raw| min_gas_price.checked_mul(raw))
+                .ok_or(WrapperTxErr::GasLimitOverflow {
+                    multiplier: self.gas_limit.multiplier,
+                    resolution,
+                })?;
+            self.validate_dynamic_fee(floor)
+        }
+
+        /// Check this wrapper against all the rules of the given `policy`,
+        /// returning the first violation found
+        pub fn enforce(
+            &self,
+            policy: &WrapperPolicy,
+            ctx: &PolicyContext,
+        ) -> Result<(), WrapperTxErr> {
+            self.verify_registered_token(ctx.registry)?;
+            self.enforce_fee_floor(policy)?;
+
+            let limit = self.gas_limit.saturating_raw(ctx.gas_limit_resolution);
+            if limit > ctx.gas_ceiling {
+                return Err(WrapperTxErr::GasLimitTooHigh {
+                    limit,
+                    ceiling: ctx.gas_ceiling,
+                });
+            }
+
+            if self.epoch.0.abs_diff(ctx.current_epoch.0) > policy.epoch_window
+            {
+                return Err(WrapperTxErr::EpochOutOfWindow {
+                    epoch: self.epoch,
+                    current: ctx.current_epoch,
+                });
+            }
+
+            if ctx.ciphertext_size > policy.max_ciphertext_size {
+                return Err(WrapperTxErr::CiphertextTooLarge {
+                    size: ctx.ciphertext_size,
+                    max: policy.max_ciphertext_size,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Check the fee of this wrapper against the floor of the given
+        /// `policy`, taking PoW fee exemptions into account
+        fn enforce_fee_floor(
+            &self,
+            policy: &WrapperPolicy,
+        ) -> Result<(), WrapperTxErr> {
+            #[cfg(not(feature = "mainnet"))]
+            if policy.pow_exempts_fee && self.fee.amount < policy.fee_floor {
+                if let Some(solution) = &self.pow_solution {
+                    return if solution.verify_solution(self.fee_payer()) {
+                        Ok(())
+                    } else {
+                        Err(WrapperTxErr::InvalidPowSolution)
+                    };
+                }
+            }
+            self.validate_dynamic_fee(policy.fee_floor)
+        }
+
+        /// The worst-case cost of this wrapper, i.e. its fee plus the
+        /// cost of consuming its entire gas limit at the given gas price,
+        /// given the gas limit `resolution`.
+        ///
+        /// Returns `None` if the computation overflows.
+        pub fn max_cost(
+            &self,
+            gas_price: Amount,
+            resolution: u64,
+        ) -> Option<Amount> {
+            let max_gas_cost = gas_price
+                .checked_mul(self.gas_limit.checked_raw(resolution)?)?;
+            self.fee.amount.checked_add(max_gas_cost)
+        }
+
+        /// Check if the worst-case cost of this wrapper at the given gas
+        /// price, given the gas limit `resolution`, does not exceed `budget`
+        pub fn fits_budget(
+            &self,
+            gas_price: Amount,
+            resolution: u64,
+            budget: Amount,
+        ) -> bool {
+            self.max_cost(gas_price, resolution)
+                .map(|cost| cost <= budget)
+                .unwrap_or(false)
+        }
     }
 
     #[cfg(test)]
@@ -218,13 +812,13 @@ pub mod wrapper_tx {
         use super::*;
 
         /// Test serializing and deserializing again gives back original object
-        /// Test that serializing converts GasLimit to u64 correctly
+        /// Test that serializing converts GasLimit to its multiplier
         #[test]
         fn test_gas_limit_roundtrip() {
             let limit = GasLimit { multiplier: 1 };
             // Test serde roundtrip
             let js = serde_json::to_string(&limit).expect("Test failed");
-            assert_eq!(js, format!("{}", GAS_LIMIT_RESOLUTION));
+            assert_eq!(js, "1");
             let new_limit: GasLimit =
                 serde_json::from_str(&js).expect("Test failed");
             assert_eq!(new_limit, limit);
@@ -238,115 +832,562 @@ pub mod wrapper_tx {
             );
         }
 
-        /// Test that when we deserialize a u64 that is not a multiple of
-        /// GAS_LIMIT_RESOLUTION to a GasLimit, it rounds up to the next
-        /// multiple
+        /// Test that when we convert a raw number that is not a multiple of
+        /// the resolution to a GasLimit, it rounds up to the next multiple
         #[test]
-        fn test_deserialize_not_multiple_of_resolution() {
-            let js = serde_json::to_string(&(GAS_LIMIT_RESOLUTION + 1))
-                .expect("Test failed");
-            let limit: GasLimit =
-                serde_json::from_str(&js).expect("Test failed");
+        fn test_from_raw_not_multiple_of_resolution() {
+            let limit = GasLimit::from_raw(
+                DEFAULT_GAS_LIMIT_RESOLUTION + 1,
+                DEFAULT_GAS_LIMIT_RESOLUTION,
+            );
             assert_eq!(limit, GasLimit { multiplier: 2 });
         }
 
+        /// Test that converting a gas limit whose raw number overflows
+        /// fails, instead of wrapping around, while its serialization is
+        /// exact
+        #[test]
+        fn test_gas_limit_overflow() {
+            let limit = GasLimit {
+                multiplier: u64::MAX / DEFAULT_GAS_LIMIT_RESOLUTION + 1,
+            };
+            assert_eq!(limit.checked_raw(DEFAULT_GAS_LIMIT_RESOLUTION), None);
+            let js = serde_json::to_string(&limit).expect("Test failed");
+            let new_limit: GasLimit =
+                serde_json::from_str(&js).expect("Test failed");
+            assert_eq!(new_limit, limit);
+
+            let limit = GasLimit {
+                multiplier: u64::MAX / DEFAULT_GAS_LIMIT_RESOLUTION,
+            };
+            assert_eq!(
+                limit.checked_raw(DEFAULT_GAS_LIMIT_RESOLUTION),
+                Some(limit.multiplier * DEFAULT_GAS_LIMIT_RESOLUTION)
+            );
+        }
+
         /// Test that refund is calculated correctly
         #[test]
         fn test_gas_limit_refund() {
             let limit = GasLimit { multiplier: 1 };
-            let refund = limit.refund_amount(GAS_LIMIT_RESOLUTION - 1);
+            let refund = limit.refund_amount(
+                DEFAULT_GAS_LIMIT_RESOLUTION - 1,
+                DEFAULT_GAS_LIMIT_RESOLUTION,
+            );
             assert_eq!(refund, Amount::from(1u64));
         }
 
-        /// Test that we don't refund more than GAS_LIMIT_RESOLUTION
+        /// Test that we don't refund more than DEFAULT_GAS_LIMIT_RESOLUTION
         #[test]
         fn test_gas_limit_too_high_no_refund() {
             let limit = GasLimit { multiplier: 2 };
-            let refund = limit.refund_amount(GAS_LIMIT_RESOLUTION - 1);
-            assert_eq!(refund, Amount::from(GAS_LIMIT_RESOLUTION));
+            let refund = limit.refund_amount(
+                DEFAULT_GAS_LIMIT_RESOLUTION - 1,
+                DEFAULT_GAS_LIMIT_RESOLUTION,
+            );
+            assert_eq!(refund, Amount::from(DEFAULT_GAS_LIMIT_RESOLUTION));
         }
 
         /// Test that if gas usage was underestimated, we issue no refund
         #[test]
         fn test_gas_limit_too_low_no_refund() {
             let limit = GasLimit { multiplier: 1 };
-            let refund = limit.refund_amount(GAS_LIMIT_RESOLUTION + 1);
+            let refund = limit.refund_amount(
+                DEFAULT_GAS_LIMIT_RESOLUTION + 1,
+                DEFAULT_GAS_LIMIT_RESOLUTION,
+            );
             assert_eq!(refund, Amount::from(0u64));
         }
+
+        /// Test that gas limits are checked against a tolerance band around
+        /// a recommended gas limit
+        #[test]
+        fn test_gas_limit_within_tolerance() {
+            let recommended = GasLimit { multiplier: 10 };
+            // the edges of a 20% band are 8 and 12 multiples
+            for inside in [8, 10, 12] {
+                let limit = GasLimit { multiplier: inside };
+                assert!(limit.within_tolerance(&recommended, 20));
+            }
+            for outside in [7, 13] {
+                let limit = GasLimit {
+                    multiplier: outside,
+                };
+                assert!(!limit.within_tolerance(&recommended, 20));
+            }
+        }
+
+        /// Test that jittered gas limits are deterministic in their seed and
+        /// bounded by the max number of extra resolution units
+        #[test]
+        fn test_gas_limit_with_jitter() {
+            let resolution = 1_000;
+            let base = resolution + 1;
+            let seed = [7u8; 32];
+            let limit = GasLimit::with_jitter(base, resolution, &seed, 10);
+            assert_eq!(
+                limit,
+                GasLimit::with_jitter(base, resolution, &seed, 10)
+            );
+            assert!((2..=12).contains(&limit.multiplier));
+
+            // without extra units, the limit is just rounded up to the
+            // given resolution
+            assert_eq!(
+                GasLimit::with_jitter(base, resolution, &seed, 0),
+                GasLimit::from_raw(base, resolution)
+            );
+        }
+
+        /// Test that the max refund liability of several gas limits is the
+        /// sum of their max refunds
+        #[test]
+        fn test_max_refund_liability() {
+            let limits = [
+                GasLimit { multiplier: 0 },
+                GasLimit { multiplier: 1 },
+                GasLimit { multiplier: 5 },
+            ];
+            let expected: Amount = limits
+                .iter()
+                .map(|limit| limit.max_refund(DEFAULT_GAS_LIMIT_RESOLUTION))
+                .sum();
+            assert_eq!(
+                expected,
+                Amount::from(2 * DEFAULT_GAS_LIMIT_RESOLUTION)
+            );
+            assert_eq!(
+                max_refund_liability(&limits, DEFAULT_GAS_LIMIT_RESOLUTION),
+                expected
+            );
+            assert_eq!(
+                max_refund_liability(&[], DEFAULT_GAS_LIMIT_RESOLUTION),
+                Amount::default()
+            );
+        }
+
+        /// Test that the full refund threshold is the point past which
+        /// the refund starts decreasing
+        #[test]
+        fn test_full_refund_threshold() {
+            let limit = GasLimit { multiplier: 3 };
+            let threshold =
+                limit.full_refund_threshold(DEFAULT_GAS_LIMIT_RESOLUTION);
+            assert_eq!(threshold, 2 * DEFAULT_GAS_LIMIT_RESOLUTION);
+            assert_eq!(
+                limit
+                    .refund_amount(threshold - 1, DEFAULT_GAS_LIMIT_RESOLUTION),
+                Amount::from(DEFAULT_GAS_LIMIT_RESOLUTION)
+            );
+            assert_eq!(
+                limit.refund_amount(threshold, DEFAULT_GAS_LIMIT_RESOLUTION),
+                Amount::from(DEFAULT_GAS_LIMIT_RESOLUTION)
+            );
+            assert_eq!(
+                limit
+                    .refund_amount(threshold + 1, DEFAULT_GAS_LIMIT_RESOLUTION),
+                Amount::from(DEFAULT_GAS_LIMIT_RESOLUTION - 1)
+            );
+        }
+
+        /// Test that gas limits can be converted with a resolution other
+        /// than the default one
+        #[test]
+        fn test_gas_limit_custom_resolution() {
+            let resolution = 10;
+            let limit = GasLimit::from_raw(21, resolution);
+            assert_eq!(limit, GasLimit { multiplier: 3 });
+            assert_eq!(limit.checked_raw(resolution), Some(30));
+            assert_eq!(limit.max_refund(resolution), Amount::from(10u64));
+            assert_eq!(limit.refund_amount(25, resolution), Amount::from(5u64));
+            assert_eq!(
+                GasLimit::from_raw(20, resolution),
+                GasLimit { multiplier: 2 }
+            );
+            let huge = GasLimit {
+                multiplier: u64::MAX,
+            };
+            assert_eq!(huge.checked_raw(resolution), None);
+        }
     }
 
     #[cfg(test)]
-    mod test_wrapper_tx {
+    mod test_fees {
         use super::*;
-        use crate::proto::{Code, Data, Section, Signature, Tx, TxError};
         use crate::types::address::nam;
-        use crate::types::transaction::{Hash, TxType};
 
-        fn gen_keypair() -> common::SecretKey {
-            use rand::prelude::ThreadRng;
-            use rand::thread_rng;
+        /// Test that a single tx gets no bundle discount
+        #[test]
+        fn test_bundle_discount_single_tx() {
+            let base = Amount::from(10 * MIN_FEE);
+            let fee = Fee::bundle_discount(base, 1, 100, nam());
+            assert_eq!(fee.amount, base);
+            assert_eq!(fee.token, nam());
+        }
 
-            let mut rng: ThreadRng = thread_rng();
-            ed25519::SigScheme::generate(&mut rng).try_to_sk().unwrap()
+        /// Test that the discount grows with the bundle size, up to a cap
+        #[test]
+        fn test_bundle_discount_is_capped() {
+            let base = Amount::from(10 * MIN_FEE);
+            // 4 extra txs at 1% each
+            let fee = Fee::bundle_discount(base, 5, 100, nam());
+            assert_eq!(fee.amount, Amount::from(96 * MIN_FEE / 10));
+            // a large bundle hits the max discount
+            let fee = Fee::bundle_discount(base, 1_000, 100, nam());
+            assert_eq!(fee.amount, Amount::from(5 * MIN_FEE));
         }
 
-        /// We test that when we feed in a Tx and then decrypt it again
-        /// that we get what we started with.
+        /// Test that a discounted fee is never lower than the minimum fee
         #[test]
-        fn test_encryption_round_trip() {
-            let keypair = gen_keypair();
-            let mut wrapper =
-                Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
-                    Fee {
-                        amount: 10.into(),
-                        token: nam(),
-                    },
-                    &keypair,
-                    Epoch(0),
-                    0.into(),
-                    #[cfg(not(feature = "mainnet"))]
-                    None,
-                ))));
-            wrapper.set_code(Code::new("wasm code".as_bytes().to_owned()));
-            wrapper
-                .set_data(Data::new("transaction data".as_bytes().to_owned()));
-            wrapper.add_section(Section::Signature(Signature::new(
-                &wrapper.header_hash(),
-                &keypair,
-            )));
-            let mut encrypted_tx = wrapper.clone();
-            encrypted_tx.encrypt(&Default::default());
-            assert!(encrypted_tx.validate_ciphertext());
-            let privkey = <EllipticCurve as PairingEngine>::G2Affine::prime_subgroup_generator();
-            encrypted_tx.decrypt(privkey).expect("Test failed");
-            assert_eq!(wrapper.data(), encrypted_tx.data());
-            assert_eq!(wrapper.code(), encrypted_tx.code());
+        fn test_bundle_discount_clamped_to_min_fee() {
+            let base = Amount::from(MIN_FEE + 1);
+            let fee = Fee::bundle_discount(base, 1_000, 100, nam());
+            assert_eq!(fee.amount, Amount::from(MIN_FEE));
         }
 
-        /// We test that when we try to decrypt a tx and it
-        /// does not match the commitment, an error is returned
+        /// Test that the discount of a base fee larger than `u64::MAX`
+        /// doesn't truncate it
         #[test]
-        fn test_decryption_invalid_hash() {
-            let keypair = gen_keypair();
-            let mut wrapper =
-                Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
-                    Fee {
-                        amount: 10.into(),
-                        token: nam(),
-                    },
-                    &keypair,
-                    Epoch(0),
-                    0.into(),
-                    #[cfg(not(feature = "mainnet"))]
-                    None,
-                ))));
-            wrapper.set_code(Code::new("wasm code".as_bytes().to_owned()));
-            wrapper
-                .set_data(Data::new("transaction data".as_bytes().to_owned()));
-            // give a incorrect commitment to the decrypted contents of the tx
-            wrapper.set_code_sechash(Hash([0u8; 32]));
-            wrapper.set_data_sechash(Hash([0u8; 32]));
-            wrapper.add_section(Section::Signature(Signature::new(
+        fn test_bundle_discount_large_base() {
+            let base = Amount::from(u64::MAX) * 4;
+            let fee = Fee::bundle_discount(base, 1_000, 100, nam());
+            assert_eq!(fee.amount, Amount::from(u64::MAX) * 2);
+        }
+
+        /// Test that the estimated fee pads the gas used up to the gas
+        /// limit resolution and includes the tip for the padded limit
+        #[test]
+        fn test_estimate_fee() {
+            let base = Amount::from(MIN_FEE);
+            let (fee, gas_limit) =
+                Fee::estimate(base, 1_500, 1_000, Some(2.into()), nam())
+                    .expect("Test failed");
+            assert_eq!(gas_limit.checked_raw(1_000), Some(2_000));
+            assert_eq!(fee.amount, Amount::from(MIN_FEE + 4_000));
+            assert_eq!(fee.token, nam());
+            // without a gas price, only the base fee is due
+            let (fee, gas_limit) =
+                Fee::estimate(base, 2_000, 1_000, None, nam())
+                    .expect("Test failed");
+            assert_eq!(gas_limit.checked_raw(1_000), Some(2_000));
+            assert_eq!(fee.amount, base);
+            // an overflowing tip yields no estimate
+            assert!(
+                Fee::estimate(base, u64::MAX, 1, Some(2.into()), nam())
+                    .is_none()
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod test_policy {
+        use super::test_wrapper_tx::gen_keypair;
+        use super::*;
+        use crate::types::address::{btc, nam};
+
+        /// A wrapper that satisfies [`policy`] within [`context`]
+        fn valid_wrapper() -> WrapperTx {
+            WrapperTx::new(
+                Fee {
+                    amount: MIN_FEE.into(),
+                    token: nam(),
+                },
+                &gen_keypair(),
+                Epoch(5),
+                GasLimit::from_multiplier(1),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+        }
+
+        fn policy() -> WrapperPolicy {
+            WrapperPolicy {
+                fee_floor: MIN_FEE.into(),
+                epoch_window: 1,
+                max_ciphertext_size: 1024,
+                #[cfg(not(feature = "mainnet"))]
+                pow_exempts_fee: false,
+            }
+        }
+
+        fn context(registry: &dyn Fn(&Address) -> bool) -> PolicyContext<'_> {
+            PolicyContext {
+                current_epoch: Epoch(5),
+                gas_ceiling: 10 * DEFAULT_GAS_LIMIT_RESOLUTION,
+                gas_limit_resolution: DEFAULT_GAS_LIMIT_RESOLUTION,
+                registry,
+                ciphertext_size: 512,
+            }
+        }
+
+        fn registry(token: &Address) -> bool {
+            *token == nam()
+        }
+
+        /// Test that a wrapper satisfying all policy rules is accepted
+        #[test]
+        fn test_enforce_valid_wrapper() {
+            let wrapper = valid_wrapper();
+            assert!(wrapper.enforce(&policy(), &context(&registry)).is_ok());
+        }
+
+        /// Test that unregistered fee tokens are rejected
+        #[test]
+        fn test_enforce_unknown_token() {
+            let mut wrapper = valid_wrapper();
+            wrapper.fee.token = btc();
+            let err = wrapper
+                .enforce(&policy(), &context(&registry))
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::UnknownFeeToken(_));
+        }
+
+        /// Test that fees below the floor are rejected
+        #[test]
+        fn test_enforce_fee_floor() {
+            let mut wrapper = valid_wrapper();
+            wrapper.fee.amount = (MIN_FEE - 1).into();
+            let err = wrapper
+                .enforce(&policy(), &context(&registry))
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::FeeTooLow { .. });
+        }
+
+        /// Test that gas limits above the ceiling are rejected
+        #[test]
+        fn test_enforce_gas_ceiling() {
+            let mut wrapper = valid_wrapper();
+            wrapper.gas_limit = GasLimit::from_multiplier(11);
+            let err = wrapper
+                .enforce(&policy(), &context(&registry))
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::GasLimitTooHigh { .. });
+        }
+
+        /// Test that wrappers outside of the epoch window are rejected
+        #[test]
+        fn test_enforce_epoch_window() {
+            let mut wrapper = valid_wrapper();
+            wrapper.epoch = Epoch(4);
+            assert!(wrapper.enforce(&policy(), &context(&registry)).is_ok());
+            wrapper.epoch = Epoch(7);
+            let err = wrapper
+                .enforce(&policy(), &context(&registry))
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::EpochOutOfWindow { .. });
+        }
+
+        /// Test that oversized ciphertexts are rejected
+        #[test]
+        fn test_enforce_ciphertext_size() {
+            let wrapper = valid_wrapper();
+            let mut ctx = context(&registry);
+            ctx.ciphertext_size = 1025;
+            let err =
+                wrapper.enforce(&policy(), &ctx).expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::CiphertextTooLarge { .. });
+        }
+
+        /// Test that PoW solutions exempt wrappers from the fee floor only
+        /// if they are valid
+        #[cfg(not(feature = "mainnet"))]
+        #[test]
+        fn test_enforce_pow() {
+            use crate::ledger::testnet_pow::{
+                ChallengeParams, Difficulty, Solution,
+            };
+
+            let mut policy = policy();
+            policy.pow_exempts_fee = true;
+            let mut wrapper = valid_wrapper();
+            wrapper.fee.amount = 0.into();
+
+            // any solution is valid at the lowest difficulty
+            wrapper.pow_solution = Some(Solution {
+                params: ChallengeParams {
+                    difficulty: Difficulty::try_new(0).unwrap(),
+                    counter: 0,
+                },
+                value: 0,
+            });
+            assert!(wrapper.enforce(&policy, &context(&registry)).is_ok());
+
+            // an arbitrary value is not a solution at the highest difficulty
+            wrapper.pow_solution = Some(Solution {
+                params: ChallengeParams {
+                    difficulty: Difficulty::try_new(9).unwrap(),
+                    counter: 0,
+                },
+                value: 0,
+            });
+            let err = wrapper
+                .enforce(&policy, &context(&registry))
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::InvalidPowSolution);
+        }
+    }
+
+    #[cfg(test)]
+    mod test_wire_versions {
+        use super::*;
+        use crate::types::address::nam;
+
+        fn wrapper(wire_version: u16) -> Vec<u8> {
+            let keypair: common::SecretKey =
+                ed25519::SigScheme::generate(&mut rand::thread_rng())
+                    .try_to_sk()
+                    .unwrap();
+            let mut wrapper = WrapperTx::new(
+                Fee {
+                    amount: MIN_FEE.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            wrapper.wire_version = wire_version;
+            wrapper.try_to_vec().expect("Test failed")
+        }
+
+        /// Test that the wire version of a serialized wrapper is read back
+        /// from its prefix
+        #[test]
+        fn test_peek_wire_version() {
+            let bytes = wrapper(WRAPPER_WIRE_VERSION);
+            assert_eq!(
+                peek_wire_version(&bytes).expect("Test failed"),
+                WRAPPER_WIRE_VERSION
+            );
+            let decoded =
+                WrapperTx::try_from_slice(&bytes).expect("Test failed");
+            assert_eq!(decoded.wire_version, WRAPPER_WIRE_VERSION);
+
+            // A wrapper on another wire version can't share a block with it
+            let bumped = wrapper(WRAPPER_WIRE_VERSION + 1);
+            assert_eq!(
+                peek_wire_version(&bumped).expect("Test failed"),
+                WRAPPER_WIRE_VERSION + 1
+            );
+            let err = validate_uniform_wire_version(&[&bytes[..], &bumped[..]])
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::MixedWireVersions { .. });
+        }
+
+        /// Test that wrappers sharing a wire version are accepted
+        #[test]
+        fn test_uniform_wire_versions() {
+            let first = [1u8, 0, 0xaa, 0xbb];
+            let second = [1u8, 0, 0xcc];
+            let version =
+                validate_uniform_wire_version(&[&first[..], &second[..]])
+                    .expect("Test failed");
+            assert_eq!(version, 1);
+            assert_eq!(
+                validate_uniform_wire_version(&[]).expect("Test failed"),
+                WRAPPER_WIRE_VERSION
+            );
+        }
+
+        /// Test that wrappers with differing wire versions are rejected
+        #[test]
+        fn test_mixed_wire_versions() {
+            let first = [1u8, 0, 0xaa, 0xbb];
+            let second = [2u8, 0, 0xcc];
+            let err = validate_uniform_wire_version(&[&first[..], &second[..]])
+                .expect_err("Test failed");
+            assert_matches!(
+                err,
+                WrapperTxErr::MixedWireVersions {
+                    expected: 1,
+                    found: 2
+                }
+            );
+        }
+
+        /// Test that wrappers too short to hold a wire version are rejected
+        #[test]
+        fn test_truncated_wire_version() {
+            let err = validate_uniform_wire_version(&[&[1u8][..]])
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::InvalidWrapperTx);
+        }
+    }
+
+    #[cfg(test)]
+    mod test_wrapper_tx {
+        use super::*;
+        use crate::proto::{Code, Data, Section, Signature, Tx, TxError};
+        use crate::types::address::{btc, nam};
+        use crate::types::transaction::{Hash, TxType};
+
+        pub(super) fn gen_keypair() -> common::SecretKey {
+            use rand::prelude::ThreadRng;
+            use rand::thread_rng;
+
+            let mut rng: ThreadRng = thread_rng();
+            ed25519::SigScheme::generate(&mut rng).try_to_sk().unwrap()
+        }
+
+        /// We test that when we feed in a Tx and then decrypt it again
+        /// that we get what we started with.
+        #[test]
+        fn test_encryption_round_trip() {
+            let keypair = gen_keypair();
+            let mut wrapper =
+                Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+                    Fee {
+                        amount: 10.into(),
+                        token: nam(),
+                    },
+                    &keypair,
+                    Epoch(0),
+                    GasLimit::from_multiplier(0),
+                    #[cfg(not(feature = "mainnet"))]
+                    None,
+                ))));
+            wrapper.set_code(Code::new("wasm code".as_bytes().to_owned()));
+            wrapper
+                .set_data(Data::new("transaction data".as_bytes().to_owned()));
+            wrapper.add_section(Section::Signature(Signature::new(
+                &wrapper.header_hash(),
+                &keypair,
+            )));
+            let mut encrypted_tx = wrapper.clone();
+            encrypted_tx.encrypt(&Default::default());
+            assert!(encrypted_tx.validate_ciphertext());
+            let privkey = <EllipticCurve as PairingEngine>::G2Affine::prime_subgroup_generator();
+            encrypted_tx.decrypt(privkey).expect("Test failed");
+            assert_eq!(wrapper.data(), encrypted_tx.data());
+            assert_eq!(wrapper.code(), encrypted_tx.code());
+        }
+
+        /// We test that when we try to decrypt a tx and it
+        /// does not match the commitment, an error is returned
+        #[test]
+        fn test_decryption_invalid_hash() {
+            let keypair = gen_keypair();
+            let mut wrapper =
+                Tx::new(TxType::Wrapper(Box::new(WrapperTx::new(
+                    Fee {
+                        amount: 10.into(),
+                        token: nam(),
+                    },
+                    &keypair,
+                    Epoch(0),
+                    GasLimit::from_multiplier(0),
+                    #[cfg(not(feature = "mainnet"))]
+                    None,
+                ))));
+            wrapper.set_code(Code::new("wasm code".as_bytes().to_owned()));
+            wrapper
+                .set_data(Data::new("transaction data".as_bytes().to_owned()));
+            // give a incorrect commitment to the decrypted contents of the tx
+            wrapper.set_code_sechash(Hash([0u8; 32]));
+            wrapper.set_data_sechash(Hash([0u8; 32]));
+            wrapper.add_section(Section::Signature(Signature::new(
                 &wrapper.header_hash(),
                 &keypair,
             )));
@@ -371,7 +1412,7 @@ pub mod wrapper_tx {
                 },
                 &keypair,
                 Epoch(0),
-                0.into(),
+                GasLimit::from_multiplier(0),
                 #[cfg(not(feature = "mainnet"))]
                 None,
             ))));
@@ -407,6 +1448,427 @@ pub mod wrapper_tx {
             let err = tx.validate_header().expect_err("Test failed");
             assert_matches!(err, TxError::SigError(_));
         }
+
+        /// Test that the fee of a sponsored wrapper is paid by the sponsor,
+        /// which must sign the header of the outer tx too
+        #[test]
+        fn test_sponsored_wrapper() {
+            let keypair = gen_keypair();
+            let sponsor = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_sponsor(sponsor.ref_to());
+            assert_eq!(wrapper.fee_payer(), Address::from(&sponsor.ref_to()));
+
+            let mut tx = Tx::new(TxType::Wrapper(Box::new(wrapper)));
+            tx.set_code(Code::new("wasm code".as_bytes().to_owned()));
+            tx.set_data(Data::new("transaction data".as_bytes().to_owned()));
+            tx.add_section(Section::Signature(Signature::new(
+                &tx.header_hash(),
+                &keypair,
+            )));
+            // the sponsor has not signed yet
+            let err = tx.validate_header().expect_err("Test failed");
+            assert_matches!(err, TxError::SigError(_));
+
+            tx.add_section(Section::Signature(Signature::new(
+                &tx.header_hash(),
+                &sponsor,
+            )));
+            tx.validate_header().expect("Test failed");
+        }
+
+        /// Test that the fee of a wrapper with a fee payer address is paid by
+        /// that account, even if the wrapper is sponsored
+        #[test]
+        fn test_fee_payer_address() {
+            let keypair = gen_keypair();
+            let sponsor = gen_keypair();
+            let account =
+                crate::types::address::testing::established_address_1();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            )
+            .with_sponsor(sponsor.ref_to())
+            .with_fee_payer_address(account.clone());
+            assert_eq!(wrapper.fee_payer(), account);
+        }
+
+        /// Test that the fee payer of a wrapper skeleton is derived from the
+        /// provided public key
+        #[test]
+        fn test_unsigned_skeleton_fee_payer() {
+            let pk = gen_keypair().ref_to();
+            let wrapper = WrapperTx::unsigned_skeleton(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                pk.clone(),
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+            );
+            assert_eq!(wrapper.pk, pk);
+            assert_eq!(wrapper.fee_payer(), Address::from(&pk));
+        }
+
+        /// Test that the fee unshielding of a wrapper is committed to by its
+        /// hash
+        #[test]
+        fn test_with_unshield() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            assert!(wrapper.unshield.is_none());
+            let unshield = Hash([1; 32]);
+            let unshielding = wrapper.clone().with_unshield(unshield);
+            assert_eq!(unshielding.unshield, Some(unshield));
+            assert_ne!(
+                wrapper.inclusion_receipt().wrapper_hash,
+                unshielding.inclusion_receipt().wrapper_hash
+            );
+        }
+
+        /// Test that the tip of a wrapper covers its entire gas limit at its
+        /// gas price
+        #[test]
+        fn test_gas_price_tip() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_raw(3, 1),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            assert_eq!(wrapper.effective_gas_price(), Amount::default());
+            assert_eq!(wrapper.tip(1), Some(Amount::default()));
+
+            let tipping = wrapper.with_gas_price(2.into());
+            assert_eq!(tipping.effective_gas_price(), Amount::from(2));
+            assert_eq!(tipping.tip(1), Some(Amount::from(6)));
+            assert_eq!(tipping.tip(10), Some(Amount::from(60)));
+            assert_eq!(tipping.tip(u64::MAX), None);
+        }
+
+        /// Test that a replacement wrapper must raise the gas price of the
+        /// pending one by the minimum bump, without changing its fee payer
+        /// or token nor lowering its gas limit
+        #[test]
+        fn test_validate_replacement() {
+            let keypair = gen_keypair();
+            let pending = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(1),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            // without a tip, any tip is a valid bump
+            assert_eq!(pending.min_replacement_gas_price(), Some(1.into()));
+            let pending = pending.with_gas_price(15.into());
+            assert_eq!(pending.min_replacement_gas_price(), Some(17.into()));
+
+            let underpriced = pending.clone().with_gas_price(16.into());
+            assert_matches!(
+                underpriced.validate_replacement(&pending),
+                Err(WrapperTxErr::ReplacementUnderpriced { .. })
+            );
+            let bumped = pending.clone().with_gas_price(17.into());
+            assert!(bumped.validate_replacement(&pending).is_ok());
+            // a wrapper can't replace itself
+            assert!(pending.validate_replacement(&pending).is_err());
+
+            let mut other_token = bumped.clone();
+            other_token.fee.token = btc();
+            assert_matches!(
+                other_token.validate_replacement(&pending),
+                Err(WrapperTxErr::InvalidReplacement(_))
+            );
+            let mut lower_gas = bumped.clone();
+            lower_gas.gas_limit = GasLimit::from_multiplier(0);
+            assert_matches!(
+                lower_gas.validate_replacement(&pending),
+                Err(WrapperTxErr::InvalidReplacement(_))
+            );
+            let other_payer = bumped.with_fee_payer_address(
+                crate::types::address::testing::established_address_1(),
+            );
+            assert_matches!(
+                other_payer.validate_replacement(&pending),
+                Err(WrapperTxErr::InvalidReplacement(_))
+            );
+
+            // an overflowing bump can't be replaced
+            let maxed = pending.with_gas_price(Amount::max());
+            assert_eq!(maxed.min_replacement_gas_price(), None);
+            assert!(maxed.clone().validate_replacement(&maxed).is_err());
+        }
+
+        /// Test that a wrapper expires above its expiration height
+        #[test]
+        fn test_expiration_height() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            assert!(!wrapper.is_expired_at(BlockHeight(u64::MAX)));
+
+            let expiring = wrapper.with_expiration_height(BlockHeight(10));
+            assert!(!expiring.is_expired_at(BlockHeight(9)));
+            assert!(!expiring.is_expired_at(BlockHeight(10)));
+            assert!(expiring.is_expired_at(BlockHeight(11)));
+        }
+
+        /// Test that the nonce of a wrapper is only set on request
+        #[test]
+        fn test_with_nonce() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            assert_eq!(wrapper.nonce, None);
+            assert_eq!(wrapper.with_nonce(3).nonce, Some(3));
+        }
+
+        /// Test that a wrapper paying fees in a registered token passes the
+        /// registry check
+        #[test]
+        fn test_registered_fee_token() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            let registry = |token: &Address| *token == nam();
+            assert!(wrapper.verify_registered_token(&registry).is_ok());
+        }
+
+        /// Test that a wrapper paying fees in an unregistered token is
+        /// rejected
+        #[test]
+        fn test_unregistered_fee_token() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: btc(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(0),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            let registry = |token: &Address| *token == nam();
+            let err = wrapper
+                .verify_registered_token(&registry)
+                .expect_err("Test failed");
+            assert_matches!(
+                err,
+                WrapperTxErr::UnknownFeeToken(token) if token == btc()
+            );
+        }
+
+        /// Test that the inclusion receipt of a wrapper commits to its hash
+        /// and metadata
+        #[test]
+        fn test_inclusion_receipt() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(3),
+                GasLimit::from_multiplier(1),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            let receipt = wrapper.inclusion_receipt();
+            let expected_hash =
+                Hash(wrapper.hash(&mut Sha256::new()).finalize_reset().into());
+            assert_eq!(receipt.wrapper_hash, expected_hash);
+            assert_eq!(receipt.epoch, wrapper.epoch);
+            assert_eq!(receipt.fee, wrapper.fee);
+            assert_eq!(receipt.gas_limit, wrapper.gas_limit);
+
+            // the receipt should survive a serde roundtrip
+            let js = serde_json::to_string(&receipt).expect("Test failed");
+            let decoded: InclusionReceipt =
+                serde_json::from_str(&js).expect("Test failed");
+            assert_eq!(decoded, receipt);
+        }
+
+        /// Test that wrapper fees are validated against a dynamic floor
+        /// higher than [`MIN_FEE`]
+        #[test]
+        fn test_validate_dynamic_fee() {
+            let keypair = gen_keypair();
+            let floor = Amount::from(10 * MIN_FEE);
+            let wrapper_with_fee = |amount: u64| {
+                WrapperTx::new(
+                    Fee {
+                        amount: amount.into(),
+                        token: nam(),
+                    },
+                    &keypair,
+                    Epoch(0),
+                    GasLimit::from_multiplier(0),
+                    #[cfg(not(feature = "mainnet"))]
+                    None,
+                )
+            };
+
+            let at_floor = wrapper_with_fee(10 * MIN_FEE);
+            assert!(at_floor.validate_dynamic_fee(floor).is_ok());
+
+            let above_floor = wrapper_with_fee(10 * MIN_FEE + 1);
+            assert!(above_floor.validate_dynamic_fee(floor).is_ok());
+
+            // this fee would pass the static minimum, but not the floor
+            let below_floor = wrapper_with_fee(10 * MIN_FEE - 1);
+            let err = below_floor
+                .validate_dynamic_fee(floor)
+                .expect_err("Test failed");
+            assert_matches!(
+                err,
+                WrapperTxErr::FeeTooLow { fee, floor: f }
+                    if fee == Amount::from(10 * MIN_FEE - 1) && f == floor
+            );
+        }
+
+        /// Test that the fee of a wrapper must cover its gas limit at the
+        /// minimum gas price
+        #[test]
+        fn test_validate_min_gas_price() {
+            let keypair = gen_keypair();
+            let wrapper_with_fee = |amount: u64| {
+                WrapperTx::new(
+                    Fee {
+                        amount: amount.into(),
+                        token: nam(),
+                    },
+                    &keypair,
+                    Epoch(0),
+                    GasLimit::from_raw(2_000, 1_000),
+                    #[cfg(not(feature = "mainnet"))]
+                    None,
+                )
+            };
+            let min_gas_price = Amount::from(3u64);
+
+            let at_floor = wrapper_with_fee(6_000);
+            assert!(
+                at_floor
+                    .validate_min_gas_price(1_000, min_gas_price)
+                    .is_ok()
+            );
+
+            let below_floor = wrapper_with_fee(5_999);
+            let err = below_floor
+                .validate_min_gas_price(1_000, min_gas_price)
+                .expect_err("Test failed");
+            assert_matches!(
+                err,
+                WrapperTxErr::FeeTooLow { fee, floor }
+                    if fee == Amount::from(5_999u64)
+                        && floor == Amount::from(6_000u64)
+            );
+
+            // an overflowing floor is rejected
+            let err = at_floor
+                .validate_min_gas_price(u64::MAX, min_gas_price)
+                .expect_err("Test failed");
+            assert_matches!(err, WrapperTxErr::GasLimitOverflow { .. });
+        }
+
+        /// Test that a wrapper whose worst-case cost is within the budget
+        /// fits it, and one whose cost exceeds it does not
+        #[test]
+        fn test_fits_budget() {
+            let keypair = gen_keypair();
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: 10.into(),
+                    token: nam(),
+                },
+                &keypair,
+                Epoch(0),
+                GasLimit::from_multiplier(1),
+                #[cfg(not(feature = "mainnet"))]
+                None,
+            );
+            let gas_price = Amount::from(2u64);
+            let resolution = 1_000;
+            let max_cost = Amount::from(10 + 2 * resolution);
+            let cost = wrapper.max_cost(gas_price, resolution);
+            assert_eq!(cost, Some(max_cost));
+            assert!(wrapper.fits_budget(gas_price, resolution, max_cost));
+            let budget = max_cost - Amount::from(1u64);
+            assert!(!wrapper.fits_budget(gas_price, resolution, budget));
+            // an overflowing cost never fits a budget
+            let max = Amount::max();
+            assert!(!wrapper.fits_budget(max, resolution, max));
+        }
     }
 }
 
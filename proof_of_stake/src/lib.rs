@@ -14,6 +14,7 @@
 
 pub mod btree_set;
 pub mod epoched;
+pub mod migrations;
 pub mod parameters;
 pub mod pos_queries;
 pub mod rewards;
@@ -53,22 +54,24 @@ use storage::{
     bonds_for_source_prefix, bonds_prefix, consensus_keys_key,
     decimal_mult_amount, get_validator_address_from_bond, into_tm_voting_power,
     is_bond_key, is_unbond_key, is_validator_slashes_key,
-    last_block_proposer_key, params_key, slashes_prefix,
-    unbonds_for_source_prefix, unbonds_prefix, validator_address_raw_hash_key,
-    validator_last_slash_key, validator_max_commission_rate_change_key,
-    BondDetails, BondsAndUnbondsDetail, BondsAndUnbondsDetails,
-    ReverseOrdTokenAmount, RewardsAccumulator, SlashedAmount, UnbondDetails,
-    ValidatorUnbondRecords,
+    last_block_proposer_key, params_key, rewards_claimed_until_key,
+    slashes_prefix, unbonds_for_source_prefix, unbonds_prefix,
+    validator_address_raw_hash_key, validator_last_slash_key,
+    validator_max_commission_rate_change_key, BondDetails,
+    BondsAndUnbondsDetail, BondsAndUnbondsDetails, ReverseOrdTokenAmount,
+    RewardsAccumulator, SlashedAmount, UnbondDetails, ValidatorUnbondRecords,
 };
 use thiserror::Error;
 use types::{
     decimal_mult_i128, BelowCapacityValidatorSet, BelowCapacityValidatorSets,
     BondId, Bonds, CommissionRates, ConsensusValidator, ConsensusValidatorSet,
-    ConsensusValidatorSets, EpochedSlashes, GenesisValidator, Position,
-    RewardsProducts, Slash, SlashType, Slashes, TotalDeltas, Unbonds,
-    ValidatorAddresses, ValidatorConsensusKeys, ValidatorDeltas,
-    ValidatorPositionAddresses, ValidatorSetPositions, ValidatorSetUpdate,
-    ValidatorState, ValidatorStates, VoteInfo, WeightedValidator,
+    ConsensusValidatorSets, EpochedSlashes, GenesisValidator,
+    IncomingRedelegations, OutgoingRedelegations, PendingMaxValidatorSlots,
+    Position, Redelegation, RewardsProducts, Slash, SlashType, Slashes,
+    TotalDeltas, Unbonds, ValidatorAddresses, ValidatorConsensusKeys,
+    ValidatorDeltas, ValidatorPositionAddresses, ValidatorSetPositions,
+    ValidatorSetUpdate, ValidatorState, ValidatorStates, VoteInfo,
+    WeightedValidator,
 };
 
 /// Address of the PoS account implemented as a native VP
@@ -141,6 +144,25 @@ pub enum UnbondError {
     ValidatorIsFrozen(Address),
 }
 
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum RedelegationError {
+    #[error("The source and destination validators are both {0}")]
+    RedelegationSrcEqDest(Address),
+    #[error(
+        "The given delegator address {0} is a validator address. Self-bonds \
+         may not be redelegated."
+    )]
+    DelegatorIsValidator(Address),
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error(
+        "The tokens of the delegator {0} were redelegated to the source \
+         validator {1} too recently to be redelegated again"
+    )]
+    IsChainedRedelegation(Address, Address),
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum WithdrawError {
@@ -150,6 +172,13 @@ pub enum WithdrawError {
     NoWithdrawableUnbond(BondId),
 }
 
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ClaimRewardsError {
+    #[error("No bond could be found for {0}")]
+    NoBondFound(BondId),
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum SlashError {
@@ -182,6 +211,15 @@ pub enum CommissionRateChangeError {
     CannotRead(Address),
 }
 
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ConsensusKeyChangeError {
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error("The consensus key {0} is already used by a validator")]
+    KeyInUse(common::PublicKey),
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum UnjailValidatorError {
@@ -214,12 +252,24 @@ impl From<UnbondError> for storage_api::Error {
     }
 }
 
+impl From<RedelegationError> for storage_api::Error {
+    fn from(err: RedelegationError) -> Self {
+        Self::new(err)
+    }
+}
+
 impl From<WithdrawError> for storage_api::Error {
     fn from(err: WithdrawError) -> Self {
         Self::new(err)
     }
 }
 
+impl From<ClaimRewardsError> for storage_api::Error {
+    fn from(err: ClaimRewardsError) -> Self {
+        Self::new(err)
+    }
+}
+
 impl From<CommissionRateChangeError> for storage_api::Error {
     fn from(err: CommissionRateChangeError) -> Self {
         Self::new(err)
@@ -232,6 +282,12 @@ impl From<InflationError> for storage_api::Error {
     }
 }
 
+impl From<ConsensusKeyChangeError> for storage_api::Error {
+    fn from(err: ConsensusKeyChangeError) -> Self {
+        Self::new(err)
+    }
+}
+
 impl From<UnjailValidatorError> for storage_api::Error {
     fn from(err: UnjailValidatorError) -> Self {
         Self::new(err)
@@ -323,6 +379,23 @@ pub fn unbond_records_handle(validator: &Address) -> ValidatorUnbondRecords {
     ValidatorUnbondRecords::open(key)
 }
 
+/// Get the storage handle to the redelegations out of a validator
+pub fn validator_outgoing_redelegations_handle(
+    validator: &Address,
+) -> OutgoingRedelegations {
+    let key = storage::validator_outgoing_redelegations_key(validator);
+    OutgoingRedelegations::open(key)
+}
+
+/// Get the storage handle to the latest redelegations of delegators to a
+/// validator
+pub fn validator_incoming_redelegations_handle(
+    validator: &Address,
+) -> IncomingRedelegations {
+    let key = storage::validator_incoming_redelegations_key(validator);
+    IncomingRedelegations::open(key)
+}
+
 /// Get the storage handle to a PoS validator's deltas
 pub fn validator_set_positions_handle() -> ValidatorSetPositions {
     let key = storage::validator_set_positions_key();
@@ -342,6 +415,13 @@ pub fn enqueued_slashes_handle() -> EpochedSlashes {
     EpochedSlashes::open(key)
 }
 
+/// Get the storage handle to the maximum numbers of consensus validators
+/// scheduled by governance
+pub fn pending_max_validator_slots_handle() -> PendingMaxValidatorSlots {
+    let key = storage::pending_max_validator_slots_key();
+    PendingMaxValidatorSlots::open(key)
+}
+
 /// Get the storage handle to the rewards accumulator for the consensus
 /// validators in a given epoch
 pub fn rewards_accumulator_handle() -> RewardsAccumulator {
@@ -498,6 +578,45 @@ where
     storage.write(&key, params)
 }
 
+/// Schedule a new maximum number of consensus validators, which takes effect
+/// at the pipeline epoch, like the changes to the validator sets
+pub fn schedule_max_validator_slots<S>(
+    storage: &mut S,
+    slots: u64,
+    current_epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let params = read_pos_params(storage)?;
+    let pipeline_epoch = current_epoch + params.pipeline_len;
+    pending_max_validator_slots_handle().insert(
+        storage,
+        pipeline_epoch,
+        slots,
+    )?;
+    Ok(())
+}
+
+/// Write the maximum number of consensus validators scheduled for the given
+/// epoch, if any, to the PoS parameters. Must be called at the beginning of
+/// every epoch.
+pub fn apply_pending_max_validator_slots<S>(
+    storage: &mut S,
+    current_epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let pending = pending_max_validator_slots_handle();
+    if let Some(slots) = pending.remove(storage, &current_epoch)? {
+        let mut params = read_pos_params(storage)?;
+        params.max_validator_slots = slots;
+        write_pos_params(storage, params)?;
+    }
+    Ok(())
+}
+
 /// Get the validator address given the raw hash of the Tendermint consensus key
 pub fn find_validator_by_raw_hash<S>(
     storage: &S,
@@ -573,6 +692,31 @@ where
     storage.write(&key, epoch)
 }
 
+/// Read the epoch until which the rewards of the given bond have been claimed
+pub fn read_rewards_claimed_until<S>(
+    storage: &S,
+    bond_id: &BondId,
+) -> storage_api::Result<Option<Epoch>>
+where
+    S: StorageRead,
+{
+    let key = rewards_claimed_until_key(bond_id);
+    storage.read(&key)
+}
+
+/// Write the epoch until which the rewards of the given bond have been claimed
+pub fn write_rewards_claimed_until<S>(
+    storage: &mut S,
+    bond_id: &BondId,
+    epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let key = rewards_claimed_until_key(bond_id);
+    storage.write(&key, epoch)
+}
+
 /// Read last block proposer address.
 pub fn read_last_block_proposer_address<S>(
     storage: &S,
@@ -810,7 +954,7 @@ pub fn is_validator<S>(
     address: &Address,
 ) -> storage_api::Result<bool>
 where
-    S: StorageRead + StorageWrite,
+    S: StorageRead,
 {
     let rate = read_validator_max_commission_rate_change(storage, address)?;
     Ok(rate.is_some())
@@ -1793,6 +1937,222 @@ where
     Ok(())
 }
 
+/// Redelegate tokens that a delegator bonded to a source validator to a
+/// destination validator. From the pipeline epoch, the tokens contribute to the
+/// stake of the destination validator instead of the source validator without
+/// being unbonded. The source validator can still slash them for the
+/// infractions committed while they contributed to its stake, so they can't be
+/// redelegated again until these slashes are processed.
+pub fn redelegate_tokens<S>(
+    storage: &mut S,
+    delegator: &Address,
+    src_validator: &Address,
+    dest_validator: &Address,
+    amount: token::Amount,
+    current_epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let amount = amount.change();
+    tracing::debug!(
+        "Redelegating token amount {amount} from {src_validator} to \
+         {dest_validator} at epoch {current_epoch}"
+    );
+    let params = read_pos_params(storage)?;
+    let pipeline_epoch = current_epoch + params.pipeline_len;
+
+    if src_validator == dest_validator {
+        return Err(RedelegationError::RedelegationSrcEqDest(
+            src_validator.clone(),
+        )
+        .into());
+    }
+    if is_validator(storage, delegator)? {
+        return Err(
+            RedelegationError::DelegatorIsValidator(delegator.clone()).into()
+        );
+    }
+    for validator in [src_validator, dest_validator] {
+        if !is_validator(storage, validator)? {
+            return Err(
+                RedelegationError::NotAValidator(validator.clone()).into()
+            );
+        }
+    }
+    // Make sure the source validator is not currently frozen
+    if is_validator_frozen(storage, src_validator, current_epoch, &params)? {
+        return Err(
+            UnbondError::ValidatorIsFrozen(src_validator.clone()).into()
+        );
+    }
+    // Check that the destination validator is not inactive at anywhere
+    // between the current epoch and pipeline offset
+    for epoch in current_epoch.iter_range(params.pipeline_len) {
+        if let Some(ValidatorState::Inactive) =
+            validator_state_handle(dest_validator)
+                .get(storage, epoch, &params)?
+        {
+            return Err(
+                BondError::InactiveValidator(dest_validator.clone()).into()
+            );
+        }
+    }
+    // Tokens redelegated to the source validator can't be redelegated again
+    // before the slashes of the validator they came from are processed
+    if let Some(redelegation_epoch) =
+        validator_incoming_redelegations_handle(src_validator)
+            .get(storage, delegator)?
+    {
+        if redelegation_epoch + params.slash_processing_epoch_offset()
+            > current_epoch
+        {
+            return Err(RedelegationError::IsChainedRedelegation(
+                delegator.clone(),
+                src_validator.clone(),
+            )
+            .into());
+        }
+    }
+
+    // Make sure there are enough tokens left in the bond at the pipeline offset
+    let src_bonds = bond_handle(delegator, src_validator);
+    let remaining_at_pipeline = src_bonds
+        .get_sum(storage, pipeline_epoch, &params)?
+        .unwrap_or_default();
+    if amount > remaining_at_pipeline {
+        return Err(UnbondError::UnbondAmountGreaterThanBond(
+            token::Amount::from_change(amount),
+            token::Amount::from_change(remaining_at_pipeline),
+        )
+        .into());
+    }
+
+    // Take the tokens out of the bonds to the source validator, starting from
+    // the future-most. The bonds are updated at their start epochs through the
+    // data handler, so that the epoch of their last update stays current.
+    #[allow(clippy::needless_collect)]
+    let bonds: Vec<Result<_, _>> =
+        src_bonds.get_data_handler().iter(storage)?.collect();
+    let mut remaining = amount;
+    let mut amount_after_slashing = token::Change::default();
+    for bond in bonds.into_iter().rev() {
+        if remaining <= token::Change::default() {
+            break;
+        }
+        let (bond_epoch, bond_amount) = bond?;
+        if bond_amount <= token::Change::default() {
+            continue;
+        }
+        let to_redelegate = cmp::min(bond_amount, remaining);
+        src_bonds.get_data_handler().insert(
+            storage,
+            bond_epoch,
+            bond_amount - to_redelegate,
+        )?;
+
+        let slashes_for_this_bond =
+            find_slashes_in_range(storage, bond_epoch, None, src_validator)?;
+        let redelegated = get_slashed_amount(
+            &params,
+            token::Amount::from_change(to_redelegate),
+            &slashes_for_this_bond,
+        )?;
+        amount_after_slashing += redelegated;
+
+        // The tokens leave the stake of the source validator like unbonded
+        // tokens, which its slashes that are still to be processed don't
+        // apply to
+        let cur_amnt = unbond_records_handle(src_validator)
+            .at(&pipeline_epoch)
+            .get(storage, &bond_epoch)?
+            .unwrap_or_default();
+        unbond_records_handle(src_validator)
+            .at(&pipeline_epoch)
+            .insert(
+                storage,
+                bond_epoch,
+                cur_amnt + token::Amount::from_change(to_redelegate),
+            )?;
+        validator_outgoing_redelegations_handle(src_validator).push(
+            storage,
+            Redelegation {
+                delegator: delegator.clone(),
+                dest_validator: dest_validator.clone(),
+                bond_start: bond_epoch,
+                redelegation_epoch: pipeline_epoch,
+                amount: token::Amount::from_change(redelegated),
+            },
+        )?;
+
+        remaining -= to_redelegate;
+    }
+    tracing::debug!(
+        "Token change including slashes on redelegation = \
+         {amount_after_slashing}"
+    );
+
+    // Bond the tokens to the destination validator at the pipeline offset
+    let dest_bonds = bond_handle(delegator, dest_validator);
+    let cur_remain = dest_bonds
+        .get_delta_val(storage, pipeline_epoch, &params)?
+        .unwrap_or_default();
+    dest_bonds.set(
+        storage,
+        cur_remain + amount_after_slashing,
+        current_epoch,
+        params.pipeline_len,
+    )?;
+    let dest_total_bonded = total_bonded_handle(dest_validator);
+    let cur_remain_global = dest_total_bonded
+        .get_delta_val(storage, pipeline_epoch, &params)?
+        .unwrap_or_default();
+    dest_total_bonded.set(
+        storage,
+        cur_remain_global + amount_after_slashing,
+        current_epoch,
+        params.pipeline_len,
+    )?;
+    validator_incoming_redelegations_handle(dest_validator).insert(
+        storage,
+        delegator.clone(),
+        pipeline_epoch,
+    )?;
+
+    // Move the stake between the validators at the pipeline offset. The total
+    // stake is unchanged and the tokens stay in the PoS account.
+    for (validator, change) in [
+        (src_validator, -amount_after_slashing),
+        (dest_validator, amount_after_slashing),
+    ] {
+        let is_jailed_at_pipeline = matches!(
+            validator_state_handle(validator)
+                .get(storage, pipeline_epoch, &params)?
+                .unwrap(),
+            ValidatorState::Jailed
+        );
+        if !is_jailed_at_pipeline {
+            update_validator_set(
+                storage,
+                &params,
+                validator,
+                change,
+                current_epoch,
+            )?;
+        }
+        update_validator_deltas(
+            storage,
+            &params,
+            validator,
+            change,
+            current_epoch,
+            params.pipeline_len,
+        )?;
+    }
+
+    Ok(())
+}
+
 /// Initialize data for a new validator.
 pub fn become_validator<S>(
     storage: &mut S,
@@ -1960,6 +2320,101 @@ where
     Ok(withdrawable_amount)
 }
 
+/// Compute the rewards of a bond which haven't been claimed yet, i.e. those
+/// of the epochs before the current one since the last claim.
+///
+/// The rewards of an epoch are given by the rewards products of the validator
+/// in that epoch. A delegation earns the rewards net of the validator's
+/// commission, while a self-bond earns the full rewards and the commission on
+/// the rewards of the delegations to the validator.
+pub fn compute_bond_rewards<S>(
+    storage: &S,
+    source: Option<&Address>,
+    validator: &Address,
+    current_epoch: Epoch,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead,
+{
+    let params = read_pos_params(storage)?;
+    let source = source.unwrap_or(validator);
+    let bond_id = BondId {
+        source: source.clone(),
+        validator: validator.clone(),
+    };
+    let claimed_until =
+        read_rewards_claimed_until(storage, &bond_id)?.unwrap_or_default();
+    let is_self_bond = source == validator;
+    let delegation_products = delegator_rewards_products_handle(validator);
+
+    let mut rewards = token::Amount::default();
+    for next in validator_rewards_products_handle(validator).iter(storage)? {
+        let (epoch, product) = next?;
+        if epoch < claimed_until || epoch >= current_epoch {
+            continue;
+        }
+        let (_, bond) = bond_amount(storage, &bond_id, epoch)?;
+        if bond == token::Amount::default() {
+            continue;
+        }
+        let delegation_product = delegation_products
+            .get(storage, &epoch)?
+            .unwrap_or(Decimal::ONE);
+        if is_self_bond {
+            rewards += decimal_mult_amount(product - Decimal::ONE, bond);
+            let stake =
+                read_validator_stake(storage, &params, validator, epoch)?
+                    .unwrap_or_default();
+            let delegated = stake.checked_sub(bond).unwrap_or_default();
+            rewards +=
+                decimal_mult_amount(product - delegation_product, delegated);
+        } else {
+            rewards +=
+                decimal_mult_amount(delegation_product - Decimal::ONE, bond);
+        }
+    }
+    tracing::debug!(
+        "Rewards of {source} from validator {validator} since epoch \
+         {claimed_until}: {rewards}"
+    );
+    Ok(rewards)
+}
+
+/// Claim the rewards of a bond which haven't been claimed yet and transfer
+/// them from the PoS address to the source. Because the rewards are computed
+/// from the bond amounts at the time of the claim, the rewards of the epochs
+/// before an unbond should be claimed before unbonding.
+pub fn claim_reward_tokens<S>(
+    storage: &mut S,
+    source: Option<&Address>,
+    validator: &Address,
+    current_epoch: Epoch,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead + StorageWrite,
+{
+    tracing::debug!("Claiming rewards in epoch {current_epoch}");
+    let source = source.unwrap_or(validator);
+    let bond_id = BondId {
+        source: source.clone(),
+        validator: validator.clone(),
+    };
+    if bond_handle(source, validator)
+        .get_data_handler()
+        .is_empty(storage)?
+    {
+        return Err(ClaimRewardsError::NoBondFound(bond_id).into());
+    }
+
+    let rewards =
+        compute_bond_rewards(storage, Some(source), validator, current_epoch)?;
+    let staking_token = staking_token_address(storage);
+    transfer_tokens(storage, &staking_token, rewards, &ADDRESS, source)?;
+    write_rewards_claimed_until(storage, &bond_id, current_epoch)?;
+
+    Ok(rewards)
+}
+
 /// Change the commission rate of a validator
 pub fn change_validator_commission_rate<S>(
     storage: &mut S,
@@ -2012,6 +2467,43 @@ where
     commission_handle.set(storage, new_rate, current_epoch, params.pipeline_len)
 }
 
+/// Rotate the consensus key of a validator, e.g. to replace a compromised
+/// key without unbonding. The new key is used to sign blocks from the
+/// pipeline epoch onwards, until which the current key remains active.
+pub fn change_consensus_key<S>(
+    storage: &mut S,
+    validator: &Address,
+    consensus_key: &common::PublicKey,
+    current_epoch: Epoch,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    if !is_validator(storage, validator)? {
+        return Err(
+            ConsensusKeyChangeError::NotAValidator(validator.clone()).into()
+        );
+    }
+    // Keys may never be reused, not even the past keys of the same validator
+    if is_consensus_key_used(storage, consensus_key)? {
+        return Err(
+            ConsensusKeyChangeError::KeyInUse(consensus_key.clone()).into()
+        );
+    }
+    try_insert_consensus_key(storage, consensus_key)?;
+
+    let params = read_pos_params(storage)?;
+    // The raw hash of the previous key keeps pointing to the validator, such
+    // that the evidence of its misbehaviour can still be processed
+    write_validator_address_raw_hash(storage, validator, consensus_key)?;
+    validator_consensus_key_handle(validator).set(
+        storage,
+        consensus_key.clone(),
+        current_epoch,
+        params.pipeline_len,
+    )
+}
+
 /// Transfer tokens between accounts
 /// TODO: may want to move this into core crate
 pub fn transfer_tokens<S>(
@@ -2162,7 +2654,7 @@ where
 
     let consensus_validators = cur_consensus_validators
         .iter(storage)?
-        .filter_map(|validator| {
+        .flat_map(|validator| {
             let (
                 NestedSubKey::Data {
                     key: cur_stake,
@@ -2175,6 +2667,18 @@ where
                 "Consensus validator address {address}, stake {cur_stake}"
             );
 
+            let consensus_key = validator_consensus_key_handle(&address)
+                .get(storage, next_epoch, params)
+                .unwrap()
+                .unwrap();
+            // The key Tendermint knows this validator by, if it rotated its
+            // consensus key in the next epoch
+            let prev_consensus_key = validator_consensus_key_handle(&address)
+                .get(storage, current_epoch, params)
+                .unwrap()
+                .filter(|prev_key| prev_key != &consensus_key);
+            let mut updates = Vec::with_capacity(2);
+
             // Check if the validator was consensus in the previous epoch with
             // the same stake
             // Look up previous state and prev and current voting powers
@@ -2199,15 +2703,16 @@ where
                 });
 
                 // If it was in `Consensus` before and voting power has not
-                // changed, skip the update
+                // changed, skip the update, unless its consensus key changed
                 if matches!(prev_state, Some(ValidatorState::Consensus))
                     && *prev_tm_voting_power == *cur_tm_voting_power
+                    && prev_consensus_key.is_none()
                 {
                     tracing::debug!(
                         "skipping validator update, {address} is in consensus \
                          set but voting power hasn't changed"
                     );
-                    return None;
+                    return updates;
                 }
 
                 // If both previous and current voting powers are 0, skip
@@ -2217,21 +2722,29 @@ where
                         "skipping validator update, {address} is in consensus \
                          set but without voting power"
                     );
-                    return None;
+                    return updates;
+                }
+
+                // The rotated key must be removed from the Tendermint set
+                if let (Some(ValidatorState::Consensus), Some(prev_key)) =
+                    (prev_state, prev_consensus_key)
+                {
+                    tracing::debug!(
+                        "{address} rotated consensus key {}",
+                        prev_key.tm_raw_hash()
+                    );
+                    updates.push(ValidatorSetUpdate::Deactivated(prev_key));
                 }
             }
-            let consensus_key = validator_consensus_key_handle(&address)
-                .get(storage, next_epoch, params)
-                .unwrap()
-                .unwrap();
             tracing::debug!(
                 "{address} consensus key {}",
                 consensus_key.tm_raw_hash()
             );
-            Some(ValidatorSetUpdate::Consensus(ConsensusValidator {
+            updates.push(ValidatorSetUpdate::Consensus(ConsensusValidator {
                 consensus_key,
                 bonded_stake: cur_stake.into(),
-            }))
+            }));
+            updates
         });
     let cur_below_capacity_validators =
         below_capacity_validator_set_handle().at(&next_epoch);
@@ -2293,8 +2806,10 @@ where
                 }
             }
 
+            // Tendermint knows the validator by its key in the current epoch,
+            // which differs from the next one if it rotated its key
             let consensus_key = validator_consensus_key_handle(&address)
-                .get(storage, next_epoch, params)
+                .get(storage, current_epoch, params)
                 .unwrap()
                 .unwrap();
             tracing::debug!(
@@ -2848,8 +3363,8 @@ where
     let rewards_calculator = PosRewardsCalculator {
         proposer_reward: params.block_proposer_reward,
         signer_reward: params.block_vote_reward,
-        signing_stake: u64::from(total_signing_stake),
-        total_stake: u64::from(total_consensus_stake),
+        signing_stake: total_signing_stake,
+        total_stake: total_consensus_stake,
     };
     let coeffs = rewards_calculator
         .get_reward_coeffs()
@@ -3197,6 +3712,11 @@ where
 
     let mut deltas_for_update: HashMap<Address, Vec<(u64, token::Change)>> =
         HashMap::new();
+    // The slashed amounts of the bonds redelegated out of the slashed
+    // validators, with the delegator, the destination validator and the
+    // redelegation epoch
+    let mut redelegation_slashes =
+        Vec::<(Address, Address, Epoch, token::Change)>::new();
 
     // Store the final processed slashes to their corresponding validators, then
     // update the deltas
@@ -3227,6 +3747,31 @@ where
         }
         total_rate = cmp::min(Decimal::ONE, total_rate);
 
+        // The tokens redelegated out of the validator after the infraction
+        // are slashed at the validator they were redelegated to
+        for redelegation in
+            validator_outgoing_redelegations_handle(&validator).iter(storage)?
+        {
+            let Redelegation {
+                delegator,
+                dest_validator,
+                bond_start,
+                redelegation_epoch,
+                amount,
+            } = redelegation?;
+            if bond_start <= infraction_epoch
+                && infraction_epoch < redelegation_epoch
+            {
+                let slashed = decimal_mult_amount(total_rate, amount).change();
+                redelegation_slashes.push((
+                    delegator,
+                    dest_validator,
+                    redelegation_epoch,
+                    slashed,
+                ));
+            }
+        }
+
         // Find the total amount deducted from the deltas due to unbonds that
         // became active after the infraction epoch, accounting for slashes
         let mut total_unbonded = token::Amount::default();
@@ -3421,6 +3966,68 @@ where
         }
     }
 
+    // Slash the redelegated bonds at the pipeline offset. The part of a bond
+    // that was unbonded from the destination validator in the meantime isn't
+    // slashed here.
+    for (delegator, validator, redelegation_epoch, slashed) in
+        redelegation_slashes
+    {
+        let bonds = bond_handle(&delegator, &validator);
+        let bond_amount = bonds
+            .get_delta_val(storage, redelegation_epoch, &params)?
+            .unwrap_or_default();
+        let slashed = cmp::min(slashed, bond_amount);
+        if slashed <= token::Change::default() {
+            continue;
+        }
+        tracing::debug!(
+            "Slashing {} of the bond of {} redelegated to validator {} at \
+             epoch {}",
+            slashed,
+            &delegator,
+            &validator,
+            redelegation_epoch
+        );
+        bonds.get_data_handler().insert(
+            storage,
+            redelegation_epoch,
+            bond_amount - slashed,
+        )?;
+
+        let is_jailed_at_pipeline = matches!(
+            validator_state_handle(&validator).get(
+                storage,
+                current_epoch + params.pipeline_len,
+                &params
+            )?,
+            Some(ValidatorState::Jailed)
+        );
+        if !is_jailed_at_pipeline {
+            update_validator_set(
+                storage,
+                &params,
+                &validator,
+                -slashed,
+                current_epoch,
+            )?;
+        }
+        update_validator_deltas(
+            storage,
+            &params,
+            &validator,
+            -slashed,
+            current_epoch,
+            params.pipeline_len,
+        )?;
+        update_total_deltas(
+            storage,
+            &params,
+            -slashed,
+            current_epoch,
+            params.pipeline_len,
+        )?;
+    }
+
     // debug_assert!(total_slashed >= token::Change::default());
 
     // TODO: Transfer all slashed tokens from PoS account to Slash Pool address
@@ -0,0 +1,89 @@
+//! Migrations of the PoS storage layout, which are registered with the other
+//! steps of the ledger, see [`namada_core::ledger::migrations`].
+
+use namada_core::ledger::storage_api::token::migrate_legacy_amount;
+use namada_core::ledger::storage_api::{self, StorageRead, StorageWrite};
+use namada_core::types::storage::{DbKeySeg, Key, KeySeg};
+use namada_core::types::token::{self, LegacyAmount};
+
+use crate::storage::{
+    is_below_capacity_validator_set_key, is_consensus_validator_set_key,
+    is_unbond_key, is_validator_total_unbonded_key, ReverseOrdTokenAmount,
+};
+use crate::ADDRESS;
+
+/// The index of the segment of the bonded stake in the keys of the validator
+/// sets
+const VALIDATOR_SET_STAKE_SEGMENT: usize = 7;
+
+/// Migrate the amounts stored by the PoS from the legacy encoding of 64-bit
+/// amounts: the unbonds, the validators' unbond records and the keys of the
+/// validator sets, which are sorted by the bonded stakes. The bonds and the
+/// deltas are changes, whose encoding is unchanged. It's idempotent, and
+/// returns the number of migrated values.
+pub fn migrate_legacy_amounts<S>(storage: &mut S) -> storage_api::Result<u64>
+where
+    S: StorageRead + StorageWrite,
+{
+    let prefix = Key::from(ADDRESS.to_db_key());
+    let mut amount_keys = vec![];
+    let mut validator_set_keys = vec![];
+    for entry in storage_api::iter_prefix_bytes(storage, &prefix)? {
+        let (key, _value) = entry?;
+        if is_unbond_key(&key).is_some()
+            || is_validator_total_unbonded_key(&key).is_some()
+        {
+            amount_keys.push(key);
+        } else if is_consensus_validator_set_key(&key)
+            || is_below_capacity_validator_set_key(&key)
+        {
+            validator_set_keys.push(key);
+        }
+    }
+    let mut migrated = 0;
+    for key in amount_keys {
+        if migrate_legacy_amount(storage, &key)? {
+            migrated += 1;
+        }
+    }
+    for key in validator_set_keys {
+        if migrate_legacy_validator_set_key(storage, key)? {
+            migrated += 1;
+        }
+    }
+    Ok(migrated)
+}
+
+/// Move the validator at the given key of a validator set to the key of its
+/// bonded stake in the encoding of 128-bit amounts, if it has the key segment
+/// of a legacy amount. Returns whether it was moved.
+fn migrate_legacy_validator_set_key<S>(
+    storage: &mut S,
+    key: Key,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead + StorageWrite,
+{
+    let legacy = match &key.segments[VALIDATOR_SET_STAKE_SEGMENT] {
+        DbKeySeg::StringSeg(seg) => LegacyAmount::parse_key_seg(seg),
+        _ => None,
+    };
+    let legacy = match legacy {
+        Some(legacy) => token::Amount::from(legacy),
+        None => return Ok(false),
+    };
+    let stake_seg = if is_below_capacity_validator_set_key(&key) {
+        // The legacy amounts were inverted from the largest 64-bit amount
+        let stake = token::Amount::from(u64::MAX) - legacy;
+        ReverseOrdTokenAmount(stake).to_db_key()
+    } else {
+        legacy.to_db_key()
+    };
+    let mut new_key = key.clone();
+    new_key.segments[VALIDATOR_SET_STAKE_SEGMENT] = stake_seg;
+    if let Some(value) = storage.read_bytes(&key)? {
+        storage.delete(&key)?;
+        storage.write_bytes(&new_key, value)?;
+    }
+    Ok(true)
+}
@@ -1,5 +1,6 @@
 //! PoS rewards distribution.
 
+use namada_core::types::token;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use thiserror::Error;
@@ -16,8 +17,8 @@ pub enum RewardsError {
          least 2/3 of the total bonded stake)."
     )]
     InsufficientVotes {
-        votes_needed: u64,
-        signing_stake: u64,
+        votes_needed: token::Amount,
+        signing_stake: token::Amount,
     },
     /// rewards coefficients are not set
     #[error("Rewards coefficients are not properly set.")]
@@ -42,9 +43,9 @@ pub struct PosRewardsCalculator {
     /// Rewards fraction that goes to the block signers
     pub signer_reward: Decimal,
     /// Total stake of validators who signed the block
-    pub signing_stake: u64,
+    pub signing_stake: token::Amount,
     /// Total stake of the whole consensus set
-    pub total_stake: u64,
+    pub total_stake: token::Amount,
 }
 
 impl PosRewardsCalculator {
@@ -52,7 +53,6 @@ impl PosRewardsCalculator {
     /// the validator's signing behavior and stake to determine the fraction of
     /// the block rewards earned.
     pub fn get_reward_coeffs(&self) -> Result<PosRewards, RewardsError> {
-        // TODO: think about possibility of u64 overflow
         let votes_needed = self.get_min_required_votes();
 
         let Self {
@@ -87,7 +87,10 @@ impl PosRewardsCalculator {
     }
 
     /// Implement as ceiling of (2/3) * validator set stake
-    fn get_min_required_votes(&self) -> u64 {
-        ((2 * self.total_stake) + 3 - 1) / 3
+    fn get_min_required_votes(&self) -> token::Amount {
+        // Amounts are at most 96-bit, so this can't overflow `u128`
+        let total_stake = u128::from(self.total_stake);
+        token::Amount::from_micro(((2 * total_stake) + 3 - 1) / 3)
+            .expect("Two thirds of an amount must be a valid amount")
     }
 }
@@ -2,7 +2,7 @@
 
 use namada_core::ledger::storage_api::collections::{lazy_map, lazy_vec};
 use namada_core::types::address::Address;
-use namada_core::types::storage::{DbKeySeg, Epoch, Key, KeySeg};
+use namada_core::types::storage::{DbKeySeg, Epoch, Key, KeyBuilder, KeySeg};
 
 use super::ADDRESS;
 use crate::epoched::LAZY_MAP_SUB_KEY;
@@ -29,8 +29,11 @@ const ENQUEUED_SLASHES_KEY: &str = "enqueued_slashes";
 const VALIDATOR_LAST_SLASH_EPOCH: &str = "last_slash_epoch";
 const BOND_STORAGE_KEY: &str = "bond";
 const UNBOND_STORAGE_KEY: &str = "unbond";
+const REWARDS_CLAIMED_UNTIL_STORAGE_KEY: &str = "rewards_claimed_until";
 const VALIDATOR_TOTAL_BONDED_STORAGE_KEY: &str = "total_bonded";
 const VALIDATOR_TOTAL_UNBONDED_STORAGE_KEY: &str = "total_unbonded";
+const VALIDATOR_OUTGOING_REDELEGATIONS_KEY: &str = "outgoing_redelegations";
+const VALIDATOR_INCOMING_REDELEGATIONS_KEY: &str = "incoming_redelegations";
 const VALIDATOR_SETS_STORAGE_PREFIX: &str = "validator_sets";
 const CONSENSUS_VALIDATOR_SET_STORAGE_KEY: &str = "consensus";
 const BELOW_CAPACITY_VALIDATOR_SET_STORAGE_KEY: &str = "below_capacity";
@@ -40,6 +43,7 @@ const CONSENSUS_KEYS: &str = "consensus_keys";
 const LAST_BLOCK_PROPOSER_STORAGE_KEY: &str = "last_block_proposer";
 const CONSENSUS_VALIDATOR_SET_ACCUMULATOR_STORAGE_KEY: &str =
     "validator_rewards_accumulator";
+const PENDING_MAX_VALIDATOR_SLOTS_KEY: &str = "pending_max_validator_slots";
 
 /// Is the given key a PoS storage key?
 pub fn is_pos_key(key: &Key) -> bool {
@@ -51,9 +55,7 @@ pub fn is_pos_key(key: &Key) -> bool {
 
 /// Storage key for PoS parameters.
 pub fn params_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&PARAMS_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(PARAMS_STORAGE_KEY).build()
 }
 
 /// Is storage key for PoS parameters?
@@ -63,22 +65,20 @@ pub fn is_params_key(key: &Key) -> bool {
 
 /// Storage key prefix for validator data.
 fn validator_prefix(validator: &Address) -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&VALIDATOR_STORAGE_PREFIX.to_owned())
-        .expect("Cannot obtain a storage key")
-        .push(&validator.to_db_key())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(VALIDATOR_STORAGE_PREFIX)
+        .seg(validator)
+        .build()
 }
 
 /// Storage key for validator's address raw hash for look-up from raw hash of an
 /// address to address.
 pub fn validator_address_raw_hash_key(raw_hash: impl AsRef<str>) -> Key {
     let raw_hash = raw_hash.as_ref().to_owned();
-    Key::from(ADDRESS.to_db_key())
-        .push(&VALIDATOR_ADDRESS_RAW_HASH.to_owned())
-        .expect("Cannot obtain a storage key")
-        .push(&raw_hash)
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(VALIDATOR_ADDRESS_RAW_HASH)
+        .seg(&raw_hash)
+        .build()
 }
 
 /// Is storage key for validator's address raw hash?
@@ -97,9 +97,9 @@ pub fn is_validator_address_raw_hash_key(key: &Key) -> Option<&str> {
 
 /// Storage key for validator's consensus key.
 pub fn validator_consensus_key_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_CONSENSUS_KEY_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_CONSENSUS_KEY_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for validator's consensus key?
@@ -122,9 +122,9 @@ pub fn is_validator_consensus_key_key(key: &Key) -> Option<&Address> {
 
 /// Storage key for validator's commission rate.
 pub fn validator_commission_rate_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_COMMISSION_RATE_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_COMMISSION_RATE_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for validator's commissionr ate?
@@ -147,9 +147,9 @@ pub fn is_validator_commission_rate_key(key: &Key) -> Option<&Address> {
 
 /// Storage key for validator's maximum commission rate change per epoch.
 pub fn validator_max_commission_rate_change_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_MAX_COMMISSION_CHANGE_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_MAX_COMMISSION_CHANGE_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for validator's maximum commission rate change per epoch?
@@ -174,9 +174,9 @@ pub fn is_validator_max_commission_rate_change_key(
 
 /// Storage key for validator's self rewards products.
 pub fn validator_self_rewards_product_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_SELF_REWARDS_PRODUCT_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_SELF_REWARDS_PRODUCT_KEY)
+        .build()
 }
 
 /// Is storage key for validator's self rewards products?
@@ -199,9 +199,9 @@ pub fn is_validator_self_rewards_product_key(key: &Key) -> Option<&Address> {
 
 /// Storage key for validator's delegation rewards products.
 pub fn validator_delegation_rewards_product_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_DELEGATION_REWARDS_PRODUCT_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_DELEGATION_REWARDS_PRODUCT_KEY)
+        .build()
 }
 
 /// Is storage key for validator's delegation rewards products?
@@ -226,9 +226,9 @@ pub fn is_validator_delegation_rewards_product_key(
 
 /// Storage key for validator's last known rewards product epoch.
 pub fn validator_last_known_product_epoch_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_LAST_KNOWN_PRODUCT_EPOCH_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_LAST_KNOWN_PRODUCT_EPOCH_KEY)
+        .build()
 }
 
 /// Is storage key for validator's last known rewards product epoch?
@@ -253,9 +253,9 @@ pub fn is_validator_last_known_product_epoch_key(
 
 /// Storage key for validator's consensus key.
 pub fn validator_state_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_STATE_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_STATE_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for validator's state?
@@ -285,9 +285,9 @@ pub fn is_validator_state_key(key: &Key) -> Option<(&Address, Epoch)> {
 
 /// Storage key for validator's deltas.
 pub fn validator_deltas_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_DELTAS_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_DELTAS_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for validator's total deltas?
@@ -315,31 +315,23 @@ pub fn is_validator_deltas_key(key: &Key) -> Option<&Address> {
 
 /// Storage prefix for all active validators (consensus, below-capacity, jailed)
 pub fn validator_addresses_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&VALIDATOR_ADDRESSES_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(VALIDATOR_ADDRESSES_KEY).build()
 }
 
 /// Storage prefix for slashes.
 pub fn slashes_prefix() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&SLASHES_PREFIX.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(SLASHES_PREFIX).build()
 }
 
 /// Storage key for all slashes.
 pub fn enqueued_slashes_key() -> Key {
     // slashes_prefix()
-    Key::from(ADDRESS.to_db_key())
-        .push(&ENQUEUED_SLASHES_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(ENQUEUED_SLASHES_KEY).build()
 }
 
 /// Storage key for validator's slashes.
 pub fn validator_slashes_key(validator: &Address) -> Key {
-    slashes_prefix()
-        .push(&validator.to_db_key())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(slashes_prefix()).seg(validator).build()
 }
 
 /// Is storage key for a validator's slashes
@@ -368,30 +360,36 @@ pub fn is_validator_slashes_key(key: &Key) -> Option<Address> {
 /// Storage key for the last (most recent) epoch in which a slashable offense
 /// was detected for a given validator
 pub fn validator_last_slash_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_LAST_SLASH_EPOCH.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_LAST_SLASH_EPOCH)
+        .build()
 }
 
 /// Storage key prefix for all bonds.
 pub fn bonds_prefix() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&BOND_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(BOND_STORAGE_KEY).build()
 }
 
 /// Storage key prefix for all bonds of the given source address.
 pub fn bonds_for_source_prefix(source: &Address) -> Key {
-    bonds_prefix()
-        .push(&source.to_db_key())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(bonds_prefix()).seg(source).build()
 }
 
 /// Storage key for a bond with the given ID (source and validator).
 pub fn bond_key(bond_id: &BondId) -> Key {
-    bonds_for_source_prefix(&bond_id.source)
-        .push(&bond_id.validator.to_db_key())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(bonds_for_source_prefix(&bond_id.source))
+        .seg(&bond_id.validator)
+        .build()
+}
+
+/// Storage key for the epoch until which the rewards of a bond with the given
+/// ID (source and validator) have been claimed.
+pub fn rewards_claimed_until_key(bond_id: &BondId) -> Key {
+    Key::builder(&ADDRESS)
+        .name(REWARDS_CLAIMED_UNTIL_STORAGE_KEY)
+        .seg(&bond_id.source)
+        .seg(&bond_id.validator)
+        .build()
 }
 
 /// Is storage key for a bond? Returns the bond ID and bond start epoch if so.
@@ -429,32 +427,27 @@ pub fn is_bond_key(key: &Key) -> Option<(BondId, Epoch)> {
 
 /// Storage key for the total bonds for a given validator.
 pub fn validator_total_bonded_key(validator: &Address) -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&VALIDATOR_TOTAL_BONDED_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
-        .push(&validator.to_db_key())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(VALIDATOR_TOTAL_BONDED_STORAGE_KEY)
+        .seg(validator)
+        .build()
 }
 
 /// Storage key prefix for all unbonds.
 pub fn unbonds_prefix() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&UNBOND_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(UNBOND_STORAGE_KEY).build()
 }
 
 /// Storage key prefix for all unbonds of the given source address.
 pub fn unbonds_for_source_prefix(source: &Address) -> Key {
-    unbonds_prefix()
-        .push(&source.to_db_key())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(unbonds_prefix()).seg(source).build()
 }
 
 /// Storage key for an unbond with the given ID (source and validator).
 pub fn unbond_key(bond_id: &BondId) -> Key {
-    unbonds_for_source_prefix(&bond_id.source)
-        .push(&bond_id.validator.to_db_key())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(unbonds_for_source_prefix(&bond_id.source))
+        .seg(&bond_id.validator)
+        .build()
 }
 
 /// Is storage key for an unbond? Returns the bond ID and unbond start and
@@ -496,30 +489,68 @@ pub fn is_unbond_key(key: &Key) -> Option<(BondId, Epoch, Epoch)> {
 
 /// Storage key for validator's total-unbonded amount to track for slashing
 pub fn validator_total_unbonded_key(validator: &Address) -> Key {
-    validator_prefix(validator)
-        .push(&VALIDATOR_TOTAL_UNBONDED_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_TOTAL_UNBONDED_STORAGE_KEY)
+        .build()
+}
+
+/// Is storage key for validator's total-unbonded amount?
+pub fn is_validator_total_unbonded_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(validator),
+            DbKeySeg::StringSeg(key),
+            DbKeySeg::StringSeg(data_1),
+            DbKeySeg::StringSeg(_epoch),
+            DbKeySeg::StringSeg(data_2),
+            DbKeySeg::StringSeg(_bond_epoch),
+        ] if addr == &ADDRESS
+            && prefix == VALIDATOR_STORAGE_PREFIX
+            && key == VALIDATOR_TOTAL_UNBONDED_STORAGE_KEY
+            && data_1 == lazy_map::DATA_SUBKEY
+            && data_2 == lazy_map::DATA_SUBKEY =>
+        {
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
+/// Storage key for the redelegations out of a validator
+pub fn validator_outgoing_redelegations_key(validator: &Address) -> Key {
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_OUTGOING_REDELEGATIONS_KEY)
+        .build()
+}
+
+/// Storage key for the latest redelegations of delegators to a validator
+pub fn validator_incoming_redelegations_key(validator: &Address) -> Key {
+    KeyBuilder::from(validator_prefix(validator))
+        .name(VALIDATOR_INCOMING_REDELEGATIONS_KEY)
+        .build()
 }
 
 /// Storage prefix for validator sets.
 pub fn validator_sets_prefix() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&VALIDATOR_SETS_STORAGE_PREFIX.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(VALIDATOR_SETS_STORAGE_PREFIX)
+        .build()
 }
 
 /// Storage key for consensus validator set
 pub fn consensus_validator_set_key() -> Key {
-    validator_sets_prefix()
-        .push(&CONSENSUS_VALIDATOR_SET_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_sets_prefix())
+        .name(CONSENSUS_VALIDATOR_SET_STORAGE_KEY)
+        .build()
 }
 
 /// Storage key for below-capacity validator set
 pub fn below_capacity_validator_set_key() -> Key {
-    validator_sets_prefix()
-        .push(&BELOW_CAPACITY_VALIDATOR_SET_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    KeyBuilder::from(validator_sets_prefix())
+        .name(BELOW_CAPACITY_VALIDATOR_SET_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for the consensus validator set?
@@ -534,9 +565,9 @@ pub fn is_below_capacity_validator_set_key(key: &Key) -> bool {
 
 /// Storage key for total deltas of all validators.
 pub fn total_deltas_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&TOTAL_DELTAS_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(TOTAL_DELTAS_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for total deltas of all validators?
@@ -561,9 +592,9 @@ pub fn is_total_deltas_key(key: &Key) -> Option<&String> {
 
 /// Storage key for block proposer address of the previous block.
 pub fn last_block_proposer_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&LAST_BLOCK_PROPOSER_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(LAST_BLOCK_PROPOSER_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for block proposer address of the previous block?
@@ -573,9 +604,9 @@ pub fn is_last_block_proposer_key(key: &Key) -> bool {
 
 /// Storage key for the consensus validator set rewards accumulator.
 pub fn consensus_validator_rewards_accumulator_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&CONSENSUS_VALIDATOR_SET_ACCUMULATOR_STORAGE_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(CONSENSUS_VALIDATOR_SET_ACCUMULATOR_STORAGE_KEY)
+        .build()
 }
 
 /// Is storage key for the consensus validator set?
@@ -587,6 +618,14 @@ pub fn is_consensus_validator_set_accumulator_key(key: &Key) -> bool {
             && key == CONSENSUS_VALIDATOR_SET_ACCUMULATOR_STORAGE_KEY)
 }
 
+/// Storage key for the maximum numbers of consensus validators scheduled by
+/// governance.
+pub fn pending_max_validator_slots_key() -> Key {
+    Key::builder(&ADDRESS)
+        .name(PENDING_MAX_VALIDATOR_SLOTS_KEY)
+        .build()
+}
+
 /// Get validator address from bond key
 pub fn get_validator_address_from_bond(key: &Key) -> Option<Address> {
     match key.get_at(3) {
@@ -600,16 +639,14 @@ pub fn get_validator_address_from_bond(key: &Key) -> Option<Address> {
 
 /// Storage key for validator set positions
 pub fn validator_set_positions_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&VALIDATOR_SET_POSITIONS_KEY.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS)
+        .name(VALIDATOR_SET_POSITIONS_KEY)
+        .build()
 }
 
 /// Storage key for consensus keys set.
 pub fn consensus_keys_key() -> Key {
-    Key::from(ADDRESS.to_db_key())
-        .push(&CONSENSUS_KEYS.to_owned())
-        .expect("Cannot obtain a storage key")
+    Key::builder(&ADDRESS).name(CONSENSUS_KEYS).build()
 }
 
 /// Is storage key for consensus keys set?
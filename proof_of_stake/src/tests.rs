@@ -37,18 +37,19 @@ use crate::types::{
 };
 use crate::{
     become_validator, below_capacity_validator_set_handle, bond_handle,
-    bond_tokens, bonds_and_unbonds, consensus_validator_set_handle,
-    copy_validator_sets_and_positions, find_validator_by_raw_hash,
-    get_num_consensus_validators, init_genesis,
+    bond_tokens, bonds_and_unbonds, change_consensus_key, claim_reward_tokens,
+    compute_bond_rewards, consensus_validator_set_handle,
+    copy_validator_sets_and_positions, delegator_rewards_products_handle,
+    find_validator_by_raw_hash, get_num_consensus_validators, init_genesis,
     insert_validator_into_validator_set, is_validator, process_slashes,
     read_below_capacity_validator_set_addresses_with_stake,
     read_consensus_validator_set_addresses_with_stake, read_total_stake,
-    read_validator_delta_value, read_validator_stake, slash,
+    read_validator_delta_value, read_validator_stake, redelegate_tokens, slash,
     staking_token_address, total_deltas_handle, unbond_handle, unbond_tokens,
     unjail_validator, update_validator_deltas, update_validator_set,
-    validator_consensus_key_handle, validator_set_update_tendermint,
-    validator_slashes_handle, validator_state_handle, withdraw_tokens,
-    write_validator_address_raw_hash,
+    validator_consensus_key_handle, validator_rewards_products_handle,
+    validator_set_update_tendermint, validator_slashes_handle,
+    validator_state_handle, withdraw_tokens, write_validator_address_raw_hash,
 };
 
 proptest! {
@@ -483,8 +484,8 @@ fn test_bonds_aux(params: PosParams, validators: Vec<GenesisValidator>) {
 
     // Unbond the self-bond with an amount that will remove all of the self-bond
     // executed after genesis and some of the genesis bond
-    let amount_self_unbond: token::Amount =
-        amount_self_bond + (u64::from(validator.tokens) / 2).into();
+    let amount_self_unbond: token::Amount = amount_self_bond
+        + token::Amount::from_micro(u128::from(validator.tokens) / 2).unwrap();
     // When the difference is 0, only the non-genesis self-bond is unbonded
     let unbonded_genesis_self_bond =
         amount_self_unbond - amount_self_bond != token::Amount::default();
@@ -1898,6 +1899,299 @@ fn test_validator_sets_swap() {
     );
 }
 
+/// Test that the rotation of a validator's consensus key takes effect at the
+/// pipeline epoch, when Tendermint is given the new key in place of the old
+#[test]
+fn test_change_consensus_key() {
+    let mut s = TestWlStorage::default();
+    let params = PosParams {
+        // Set 0.1 votes per token
+        tm_votes_per_token: dec!(0.1),
+        ..Default::default()
+    };
+    let validator = address::testing::established_address_1();
+    let pk1 = key::testing::common_sk_from_simple_seed(0).to_public();
+    let pk2 = key::testing::common_sk_from_simple_seed(1).to_public();
+    let stake = token::Amount::whole(10);
+
+    let epoch = Epoch::default();
+    init_genesis(
+        &mut s,
+        &params,
+        [GenesisValidator {
+            address: validator.clone(),
+            tokens: stake,
+            consensus_key: pk1.clone(),
+            commission_rate: Decimal::new(1, 1),
+            max_commission_rate_change: Decimal::new(1, 1),
+        }]
+        .into_iter(),
+        epoch,
+    )
+    .unwrap();
+
+    // Only validators may change their key, and keys can't be reused
+    let non_validator = address::testing::established_address_2();
+    assert!(change_consensus_key(&mut s, &non_validator, &pk2, epoch).is_err());
+    assert!(change_consensus_key(&mut s, &validator, &pk1, epoch).is_err());
+
+    change_consensus_key(&mut s, &validator, &pk2, epoch).unwrap();
+    let key_at = |s: &TestWlStorage, epoch: Epoch| {
+        validator_consensus_key_handle(&validator)
+            .get(s, epoch, &params)
+            .unwrap()
+    };
+    let pipeline_epoch = epoch + params.pipeline_len;
+    assert_eq!(key_at(&s, pipeline_epoch.prev()), Some(pk1.clone()));
+    assert_eq!(key_at(&s, pipeline_epoch), Some(pk2.clone()));
+    let raw_hash = key::tm_consensus_key_raw_hash(&pk2);
+    assert_eq!(
+        find_validator_by_raw_hash(&s, raw_hash).unwrap(),
+        Some(validator)
+    );
+
+    // Tendermint keeps the old key until the pipeline epoch
+    let mut epoch = epoch;
+    while epoch < pipeline_epoch.prev() {
+        epoch = advance_epoch(&mut s, &params);
+        assert!(get_tendermint_set_updates(&s, &params, epoch).is_empty());
+    }
+    let epoch = advance_epoch(&mut s, &params);
+    assert_eq!(epoch, pipeline_epoch);
+    assert_eq!(
+        get_tendermint_set_updates(&s, &params, epoch),
+        vec![
+            ValidatorSetUpdate::Deactivated(pk1),
+            ValidatorSetUpdate::Consensus(ConsensusValidator {
+                consensus_key: pk2,
+                bonded_stake: stake.into(),
+            }),
+        ]
+    );
+}
+
+/// Test that redelegated tokens move from the stake of the source validator to
+/// the destination validator at the pipeline epoch, can't be redelegated again
+/// right away, and are slashed for an infraction of the source validator
+/// committed before the redelegation
+#[test]
+fn test_redelegation() {
+    let mut s = TestWlStorage::default();
+    let params = PosParams {
+        unbonding_len: 4,
+        ..Default::default()
+    };
+    let validators: Vec<_> = [10, 100, 100]
+        .into_iter()
+        .enumerate()
+        .map(|(seed, tokens)| GenesisValidator {
+            address: address_from_simple_seed(seed as u64),
+            tokens: token::Amount::whole(tokens),
+            consensus_key: common_sk_from_simple_seed(seed as u64).to_public(),
+            commission_rate: Decimal::new(5, 2),
+            max_commission_rate_change: Decimal::new(1, 2),
+        })
+        .collect();
+    let src = validators[0].address.clone();
+    let dest = validators[1].address.clone();
+    let delegator = address::testing::established_address_1();
+
+    let mut current_epoch = s.storage.block.epoch;
+    init_genesis(&mut s, &params, validators.into_iter(), current_epoch)
+        .unwrap();
+    s.commit_block().unwrap();
+
+    let bond_amount = token::Amount::whole(5);
+    let token = staking_token_address(&s);
+    credit_tokens(&mut s, &token, &delegator, bond_amount).unwrap();
+    bond_tokens(&mut s, Some(&delegator), &src, bond_amount, current_epoch)
+        .unwrap();
+    for _ in 0..params.pipeline_len {
+        current_epoch = advance_epoch(&mut s, &params);
+    }
+
+    // Self-bonds, redelegations to the same validator and amounts greater
+    // than the bond can't be redelegated
+    let redelegate = |s: &mut TestWlStorage,
+                      delegator: &Address,
+                      src: &Address,
+                      dest: &Address,
+                      amount: token::Amount| {
+        redelegate_tokens(s, delegator, src, dest, amount, current_epoch)
+    };
+    assert!(redelegate(&mut s, &src, &src, &dest, bond_amount).is_err());
+    assert!(redelegate(&mut s, &delegator, &src, &src, bond_amount).is_err());
+    let too_much = bond_amount + token::Amount::whole(1);
+    assert!(redelegate(&mut s, &delegator, &src, &dest, too_much).is_err());
+
+    let total_stake = read_total_stake(&s, &params, current_epoch).unwrap();
+    redelegate(&mut s, &delegator, &src, &dest, bond_amount).unwrap();
+    let pipeline_epoch = current_epoch + params.pipeline_len;
+    let bond_at = |s: &TestWlStorage, validator: &Address| {
+        bond_handle(&delegator, validator)
+            .get_sum(s, pipeline_epoch, &params)
+            .unwrap()
+            .unwrap_or_default()
+    };
+    let stake_at = |s: &TestWlStorage, validator: &Address, epoch: Epoch| {
+        read_validator_stake(s, &params, validator, epoch)
+            .unwrap()
+            .unwrap_or_default()
+    };
+    assert_eq!(bond_at(&s, &src), token::Change::default());
+    assert_eq!(bond_at(&s, &dest), bond_amount.change());
+    assert_eq!(
+        stake_at(&s, &src, pipeline_epoch.prev()),
+        token::Amount::whole(15)
+    );
+    assert_eq!(stake_at(&s, &src, pipeline_epoch), token::Amount::whole(10));
+    assert_eq!(
+        stake_at(&s, &dest, pipeline_epoch),
+        token::Amount::whole(105)
+    );
+    assert_eq!(
+        read_total_stake(&s, &params, pipeline_epoch).unwrap(),
+        total_stake
+    );
+
+    // The redelegated tokens can't be redelegated again before the slashes of
+    // the source validator are processed
+    let other = address_from_simple_seed(2);
+    assert!(
+        redelegate(&mut s, &delegator, &dest, &other, bond_amount).is_err()
+    );
+
+    // The source validator misbehaved before the redelegation
+    let infraction_epoch = current_epoch;
+    slash(
+        &mut s,
+        &params,
+        current_epoch,
+        infraction_epoch,
+        BlockHeight(0),
+        SlashType::DuplicateVote,
+        &src,
+    )
+    .unwrap();
+    let processing_epoch =
+        infraction_epoch + params.slash_processing_epoch_offset();
+    while current_epoch < processing_epoch {
+        current_epoch = advance_epoch(&mut s, &params);
+        process_slashes(&mut s, current_epoch).unwrap();
+    }
+
+    let slash_rate = validator_slashes_handle(&src)
+        .get(&s, 0)
+        .unwrap()
+        .unwrap()
+        .rate;
+    let slashed = decimal_mult_amount(slash_rate, bond_amount);
+    assert!(slashed > token::Amount::default());
+    let dest_bond = bond_handle(&delegator, &dest)
+        .get_sum(&s, current_epoch, &params)
+        .unwrap()
+        .unwrap_or_default();
+    assert_eq!(dest_bond, (bond_amount - slashed).change());
+    let pipeline_epoch = current_epoch + params.pipeline_len;
+    assert_eq!(
+        stake_at(&s, &dest, pipeline_epoch),
+        token::Amount::whole(105) - slashed
+    );
+}
+
+/// Test that the rewards of an epoch are split between a delegator and the
+/// validator by the commission rate, and can only be claimed once
+#[test]
+fn test_claim_rewards() {
+    let mut s = TestWlStorage::default();
+    let params = PosParams::default();
+    let validator = address_from_simple_seed(0);
+    let genesis_validator = GenesisValidator {
+        address: validator.clone(),
+        tokens: token::Amount::whole(90),
+        consensus_key: common_sk_from_simple_seed(0).to_public(),
+        commission_rate: Decimal::new(1, 1),
+        max_commission_rate_change: Decimal::new(1, 2),
+    };
+    let delegator = address::testing::established_address_1();
+
+    let mut current_epoch = s.storage.block.epoch;
+    init_genesis(
+        &mut s,
+        &params,
+        [genesis_validator].into_iter(),
+        current_epoch,
+    )
+    .unwrap();
+    s.commit_block().unwrap();
+
+    let token = staking_token_address(&s);
+    let bond_amount = token::Amount::whole(10);
+    credit_tokens(&mut s, &token, &delegator, bond_amount).unwrap();
+    bond_tokens(
+        &mut s,
+        Some(&delegator),
+        &validator,
+        bond_amount,
+        current_epoch,
+    )
+    .unwrap();
+    for _ in 0..params.pipeline_len {
+        current_epoch = advance_epoch(&mut s, &params);
+    }
+
+    // The validator with a stake of 100 tokens earns 10 tokens of rewards in
+    // the epoch in which the delegation becomes active
+    let rewards_epoch = current_epoch;
+    credit_tokens(&mut s, &token, &super::ADDRESS, token::Amount::whole(10))
+        .unwrap();
+    validator_rewards_products_handle(&validator)
+        .insert(&mut s, rewards_epoch, dec!(1.1))
+        .unwrap();
+    delegator_rewards_products_handle(&validator)
+        .insert(&mut s, rewards_epoch, dec!(1.09))
+        .unwrap();
+
+    // The rewards can't be claimed until the end of the epoch
+    let rewards =
+        compute_bond_rewards(&s, Some(&delegator), &validator, current_epoch)
+            .unwrap();
+    assert_eq!(rewards, token::Amount::default());
+    current_epoch = advance_epoch(&mut s, &params);
+
+    let rewards =
+        compute_bond_rewards(&s, None, &validator, current_epoch).unwrap();
+    assert_eq!(rewards, token::Amount::from(9_100_000));
+    let rewards = claim_reward_tokens(
+        &mut s,
+        Some(&delegator),
+        &validator,
+        current_epoch,
+    )
+    .unwrap();
+    assert_eq!(rewards, token::Amount::from(900_000));
+    assert_eq!(read_balance(&s, &token, &delegator).unwrap(), rewards);
+
+    let rewards = claim_reward_tokens(
+        &mut s,
+        Some(&delegator),
+        &validator,
+        current_epoch,
+    )
+    .unwrap();
+    assert_eq!(rewards, token::Amount::default());
+
+    // There are no rewards without a bond
+    let other = address::testing::established_address_2();
+    assert!(claim_reward_tokens(
+        &mut s,
+        Some(&other),
+        &validator,
+        current_epoch
+    )
+    .is_err());
+}
+
 fn get_tendermint_set_updates(
     s: &TestWlStorage,
     params: &PosParams,
@@ -934,7 +934,7 @@ impl ConcretePosState {
                 tracing::debug!(
                     "Consensus val {}, stake: {} ({})",
                     &validator,
-                    u64::from(bonded_stake),
+                    bonded_stake,
                     deltas_stake
                 );
                 assert!(deltas_stake >= 0);
@@ -987,7 +987,7 @@ impl ConcretePosState {
                 tracing::debug!(
                     "Below-cap val {}, stake: {} ({})",
                     &validator,
-                    u64::from(bonded_stake),
+                    bonded_stake,
                     deltas_stake
                 );
                 assert_eq!(
@@ -2669,7 +2669,7 @@ impl AbstractPosState {
                     tracing::debug!(
                         "Consensus val {}, stake {} ({}) - ({:?})",
                         val,
-                        u64::from(*amount),
+                        amount,
                         deltas_stake,
                         val_state
                     );
@@ -2703,7 +2703,7 @@ impl AbstractPosState {
                     tracing::debug!(
                         "Below-cap val {}, stake {} ({}) - ({:?})",
                         val,
-                        u64::from(token::Amount::from(*amount)),
+                        token::Amount::from(*amount),
                         deltas_stake,
                         val_state
                     );
@@ -120,6 +120,10 @@ pub type TotalDeltas = crate::epoched::EpochedDelta<
     23,
 >;
 
+/// The maximum numbers of consensus validators scheduled by governance, by the
+/// epochs from which they apply
+pub type PendingMaxValidatorSlots = LazyMap<Epoch, u64>;
+
 /// Epoched validator commission rate
 pub type CommissionRates =
     crate::epoched::Epoched<Decimal, crate::epoched::OffsetPipelineLen>;
@@ -399,6 +403,35 @@ pub struct Slash {
 /// their staked tokens at and before the epoch of the slash.
 pub type Slashes = LazyVec<Slash>;
 
+/// A redelegation of a delegator's bond out of a validator. It's kept by the
+/// source validator for its slashes for the infractions committed while the
+/// redelegated tokens contributed to its stake to be applied to the bond at
+/// the destination validator.
+#[derive(
+    Debug, Clone, BorshDeserialize, BorshSerialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct Redelegation {
+    /// The delegator of the bond
+    pub delegator: Address,
+    /// The validator the bond is redelegated to
+    pub dest_validator: Address,
+    /// The epoch from which the tokens contributed to the stake of the
+    /// source validator
+    pub bond_start: Epoch,
+    /// The epoch from which the tokens contribute to the stake of the
+    /// destination validator, i.e. the pipeline epoch of the redelegation
+    pub redelegation_epoch: Epoch,
+    /// The redelegated amount
+    pub amount: token::Amount,
+}
+
+/// Redelegations out of a validator
+pub type OutgoingRedelegations = LazyVec<Redelegation>;
+
+/// The epochs from which the latest redelegations of delegators to a validator
+/// contribute to its stake, keyed by the delegator
+pub type IncomingRedelegations = LazyMap<Address, Epoch>;
+
 /// A type of slashable event.
 #[derive(
     Debug,
@@ -532,7 +565,9 @@ pub fn mult_change_to_amount(
 ) -> token::Amount {
     let prod = dec * Decimal::from(change);
     // truncate the number to the floor
-    token::Amount::from(prod.to_u64().expect("Product is out of bounds"))
+    prod.to_u128()
+        .and_then(token::Amount::from_micro)
+        .expect("Product is out of bounds")
 }
 
 /// Multiply a value of type Decimal with one of type Amount and then return the
@@ -543,17 +578,19 @@ pub fn decimal_mult_amount(
 ) -> token::Amount {
     let prod = dec * Decimal::from(amount);
     // truncate the number to the floor
-    token::Amount::from(prod.to_u64().expect("Product is out of bounds"))
+    prod.to_u128()
+        .and_then(token::Amount::from_micro)
+        .expect("Product is out of bounds")
 }
 
 /// Calculate voting power in the tendermint context (which is stored as i64)
 /// from the number of tokens
 pub fn into_tm_voting_power(
     votes_per_token: Decimal,
-    tokens: impl Into<u64>,
+    tokens: token::Amount,
 ) -> i64 {
-    let prod = decimal_mult_u64(votes_per_token, tokens.into());
-    i64::try_from(prod).expect("Invalid voting power")
+    let prod = decimal_mult_amount(votes_per_token, tokens);
+    i64::try_from(u128::from(prod)).expect("Invalid voting power")
 }
 
 #[cfg(test)]
@@ -0,0 +1,153 @@
+//! A log to replay the block space allocation decisions taken by the ledger.
+//!
+//! Both the proposer of a block, in `PrepareProposal`, and its verifiers,
+//! in `ProcessProposal`, allocate block space for each of its txs. When
+//! enabled, this log records every such decision, such that operators can
+//! diagnose why a proposal was rejected, by comparing the decisions of
+//! the proposer with their own.
+//!
+//! The log can only hold `N` decisions at a time, where `N` is a
+//! configurable parameter. If the log is holding `N` decisions, and a
+//! new decision is logged, old decisions are pruned.
+
+use std::sync::Mutex;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use circular_queue::CircularQueue;
+
+use crate::types::hash::Hash;
+use crate::types::storage::BlockHeight;
+
+/// The role of the node that took some allocation decision.
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+pub enum AllocRole {
+    /// The node was proposing the block.
+    Proposer,
+    /// The node was verifying the block.
+    Verifier,
+}
+
+/// The block space bin some tx was allocated in.
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+pub enum AllocBin {
+    /// The whole block.
+    Block,
+    /// The bin of DKG encrypted txs.
+    Encrypted,
+    /// The priority lane of DKG encrypted txs.
+    Priority,
+    /// The bin of DKG decrypted txs.
+    Decrypted,
+    /// The bin of protocol txs.
+    Protocol,
+}
+
+/// A block space allocation decision.
+#[derive(Debug, Clone, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct AllocDecision {
+    /// The height of the block being allocated.
+    pub height: BlockHeight,
+    /// The role of the node in the allocation.
+    pub role: AllocRole,
+    /// The hash of the tx bytes.
+    pub tx_hash: Hash,
+    /// The bin the tx was allocated in.
+    pub bin: AllocBin,
+    /// The reason why the tx was rejected, if it was.
+    pub rejection: Option<String>,
+    /// The space left after the decision, measured in bytes.
+    pub space_left: u64,
+}
+
+/// Represents a log of [`AllocDecision`] instances, which is only
+/// populated when enabled.
+#[derive(Debug, Default)]
+pub struct AllocReplayLog {
+    queue: Option<Mutex<CircularQueue<AllocDecision>>>,
+}
+
+impl AllocReplayLog {
+    /// Return a new, enabled log holding at most `max_decisions`.
+    pub fn new(max_decisions: usize) -> Self {
+        Self {
+            queue: Some(Mutex::new(CircularQueue::with_capacity(
+                max_decisions,
+            ))),
+        }
+    }
+
+    /// Check if this log records allocation decisions.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.queue.is_some()
+    }
+
+    /// Log a new allocation decision, if this log is enabled.
+    pub fn record(&self, decision: AllocDecision) {
+        if let Some(queue) = &self.queue {
+            queue
+                .lock()
+                .expect("The allocator replay log should not be poisoned")
+                .push(decision);
+        }
+    }
+
+    /// Return the decisions that were logged at the given block
+    /// height, in the order they were taken.
+    pub fn decisions_at(&self, height: BlockHeight) -> Vec<AllocDecision> {
+        let queue = match &self.queue {
+            Some(queue) => queue
+                .lock()
+                .expect("The allocator replay log should not be poisoned"),
+            None => return vec![],
+        };
+        queue
+            .asc_iter()
+            .filter(|decision| decision.height == height)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return a mock decision at the given height.
+    fn decision(height: u64, space_left: u64) -> AllocDecision {
+        AllocDecision {
+            height: BlockHeight(height),
+            role: AllocRole::Proposer,
+            tx_hash: Hash::sha256(space_left.to_le_bytes()),
+            bin: AllocBin::Encrypted,
+            rejection: None,
+            space_left,
+        }
+    }
+
+    /// Test that decisions are only logged when the log is enabled,
+    /// and that they are read back in order, by height.
+    #[test]
+    fn test_log_decisions() {
+        let log = AllocReplayLog::default();
+        log.record(decision(1, 0));
+        assert!(log.decisions_at(BlockHeight(1)).is_empty());
+
+        let log = AllocReplayLog::new(3);
+        log.record(decision(1, 3));
+        log.record(decision(2, 2));
+        log.record(decision(2, 1));
+        assert_eq!(
+            log.decisions_at(BlockHeight(2)),
+            vec![decision(2, 2), decision(2, 1)]
+        );
+
+        // old decisions are pruned
+        log.record(decision(3, 0));
+        assert!(log.decisions_at(BlockHeight(1)).is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 //! Structures encapsulating SDK arguments
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use namada_core::types::chain::ChainId;
 use namada_core::types::time::DateTimeUtc;
@@ -8,10 +9,11 @@ use zeroize::Zeroizing;
 
 use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::types::address::Address;
+use crate::types::ethereum_events::EthAddress;
+use crate::types::hash::Hash;
 use crate::types::key::{common, SchemeType};
 use crate::types::masp::MaspValue;
-use crate::types::storage::Epoch;
-use crate::types::transaction::GasLimit;
+use crate::types::storage::{BlockHeight, Epoch};
 use crate::types::{storage, token};
 
 /// Abstraction of types being used in Namada
@@ -95,12 +97,49 @@ pub struct TxTransfer<C: NamadaTypes = SdkTypes> {
     pub token: C::Address,
     /// Transferred token address
     pub sub_prefix: Option<String>,
-    /// Transferred token amount
-    pub amount: token::Amount,
+    /// Transferred token amount, converted to the raw amount of the token
+    /// with its decimal places
+    pub amount: token::DenominatedAmount,
     /// Native token address
     pub native_token: C::NativeAddress,
     /// Path to the TX WASM code file
     pub tx_code_path: PathBuf,
+    /// The funds paying the fee of the wrapper of a transfer from a spending
+    /// key
+    pub fee_source: FeeSource,
+}
+
+/// The funds paying the fee of the wrapper tx of a transfer from the shielded
+/// pool, when the fee is paid by a transparent account rather than by the
+/// shielded inputs of the transfer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeSource {
+    /// The transparent balance of the fee payer
+    Transparent,
+    /// Funds unshielded from the spending key of the transfer to the fee
+    /// payer before the fee is charged
+    Shielded,
+    /// The transparent balance of the fee payer if it covers the fee, or
+    /// else funds unshielded from the spending key of the transfer
+    #[default]
+    Auto,
+}
+
+impl FromStr for FeeSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transparent" => Ok(Self::Transparent),
+            "shielded" => Ok(Self::Shielded),
+            "auto" => Ok(Self::Auto),
+            _ => Err(format!(
+                "Unknown fee source {}, expected one of transparent, \
+                 shielded or auto",
+                s
+            )),
+        }
+    }
 }
 
 /// IBC transfer transaction arguments
@@ -126,6 +165,39 @@ pub struct TxIbcTransfer<C: NamadaTypes = SdkTypes> {
     pub timeout_height: Option<u64>,
     /// Timeout timestamp offset
     pub timeout_sec_offset: Option<u64>,
+    /// The fee for the relayer of the packet to the destination chain
+    pub recv_fee: token::Amount,
+    /// The fee for the relayer of the acknowledgement of the packet
+    pub ack_fee: token::Amount,
+    /// The fee for the relayer of the timeout of the packet
+    pub timeout_fee: token::Amount,
+    /// The token of the relayer fees, the native token if not given
+    pub relayer_fee_token: Option<C::Address>,
+    /// Native token address
+    pub native_token: C::NativeAddress,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+/// Transfer to Ethereum through the bridge pool arguments
+#[derive(Clone, Debug)]
+pub struct EthereumBridgePool<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// The address of the ERC20 contract of the transferred tokens
+    pub asset: EthAddress,
+    /// The receiver of the tokens on Ethereum
+    pub recipient: EthAddress,
+    /// The sender of the wrapped tokens on Namada
+    pub sender: C::Address,
+    /// The amount of transferred tokens
+    pub amount: token::Amount,
+    /// The gas fee paid in the native token to the relayer of the transfer
+    pub gas_amount: token::Amount,
+    /// The payer of the gas fee
+    pub gas_payer: C::Address,
+    /// Native token address
+    pub native_token: C::NativeAddress,
     /// Path to the TX WASM code file
     pub tx_code_path: PathBuf,
 }
@@ -143,6 +215,12 @@ pub struct TxInitAccount<C: NamadaTypes = SdkTypes> {
     pub tx_code_path: PathBuf,
     /// Public key for the new account
     pub public_key: C::PublicKey,
+    /// Public keys of the other signers of a multisig account
+    pub cosigners: Vec<C::PublicKey>,
+    /// Number of signatures required by a multisig account
+    pub threshold: Option<u8>,
+    /// Prefix that the encoded address of the new account must start with
+    pub vanity_prefix: Option<String>,
 }
 
 /// Transaction to initialize a new account
@@ -185,6 +263,21 @@ pub struct TxUpdateVp<C: NamadaTypes = SdkTypes> {
     pub addr: C::Address,
 }
 
+/// Transaction to update the signers of a multisig account arguments
+#[derive(Clone, Debug)]
+pub struct TxUpdateSigners<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+    /// Address of the multisig account whose signers are to be updated
+    pub addr: C::Address,
+    /// The new public keys of the signers
+    pub public_keys: Vec<C::PublicKey>,
+    /// The new number of signatures required
+    pub threshold: u8,
+}
+
 /// Bond arguments
 #[derive(Clone, Debug)]
 pub struct Bond<C: NamadaTypes = SdkTypes> {
@@ -219,6 +312,23 @@ pub struct Unbond<C: NamadaTypes = SdkTypes> {
     pub tx_code_path: PathBuf,
 }
 
+/// Redelegation arguments
+#[derive(Clone, Debug)]
+pub struct Redelegate<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Source validator address
+    pub src_validator: C::Address,
+    /// Destination validator address
+    pub dest_validator: C::Address,
+    /// Owner of the bond
+    pub owner: C::Address,
+    /// Amount of tokens to redelegate
+    pub amount: token::Amount,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
 /// Reveal public key
 #[derive(Clone, Debug)]
 pub struct RevealPk<C: NamadaTypes = SdkTypes> {
@@ -258,6 +368,31 @@ pub struct Withdraw<C: NamadaTypes = SdkTypes> {
     pub tx_code_path: PathBuf,
 }
 
+/// Claim rewards arguments
+#[derive(Clone, Debug)]
+pub struct ClaimRewards<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Validator address
+    pub validator: C::Address,
+    /// Source address for claiming the rewards of delegations. For claiming
+    /// the rewards of self-bonds, the validator is also the source
+    pub source: Option<C::Address>,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+/// Query rewards arguments
+#[derive(Clone, Debug)]
+pub struct QueryRewards<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// Validator address
+    pub validator: C::Address,
+    /// Source address of the bond, the validator for self-bonds
+    pub source: Option<C::Address>,
+}
+
 /// Query asset conversions
 #[derive(Clone, Debug)]
 pub struct QueryConversions<C: NamadaTypes = SdkTypes> {
@@ -295,6 +430,24 @@ pub struct QueryTransfers<C: NamadaTypes = SdkTypes> {
     pub token: Option<C::Address>,
 }
 
+/// Query the transfers pending in the bridge pool
+#[derive(Clone, Debug)]
+pub struct QueryBridgePool<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+}
+
+/// Query the balance of a wrapped ERC20 token
+#[derive(Clone, Debug)]
+pub struct QueryErc20<C: NamadaTypes = SdkTypes> {
+    /// Common query args
+    pub query: Query<C>,
+    /// The address of the ERC20 contract of the wrapped token
+    pub asset: EthAddress,
+    /// Address of an owner
+    pub owner: C::Address,
+}
+
 /// Query PoS bond(s)
 #[derive(Clone, Debug)]
 pub struct QueryBonds<C: NamadaTypes = SdkTypes> {
@@ -330,6 +483,19 @@ pub struct TxCommissionRateChange<C: NamadaTypes = SdkTypes> {
     pub tx_code_path: PathBuf,
 }
 
+#[derive(Clone, Debug)]
+/// Consensus key change args
+pub struct TxChangeConsensusKey<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Validator address (should be self)
+    pub validator: C::Address,
+    /// The new consensus key, active from the pipeline epoch
+    pub consensus_key: C::PublicKey,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
 #[derive(Clone, Debug)]
 /// Re-activate a jailed validator args
 pub struct TxUnjailValidator<C: NamadaTypes = SdkTypes> {
@@ -407,20 +573,45 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     /// Whether to force overwrite the above alias, if it is provided, in the
     /// wallet.
     pub wallet_alias_force: bool,
-    /// The amount being payed to include the transaction
-    pub fee_amount: token::Amount,
+    /// The amount being payed to include the transaction, converted to the
+    /// raw amount of the fee token with its decimal places
+    pub fee_amount: token::DenominatedAmount,
     /// The token in which the fee is being paid
     pub fee_token: C::Address,
-    /// The max amount of gas used to process tx
-    pub gas_limit: GasLimit,
+    /// The max amount of gas used to process tx, which is rounded up to a
+    /// multiple of the gas limit resolution of the chain
+    pub gas_limit: u64,
+    /// The optional tip offered per unit of gas to prioritize the tx
+    pub gas_price: Option<token::Amount>,
     /// The optional expiration of the transaction
     pub expiration: Option<DateTimeUtc>,
+    /// The optional last block height at which the transaction may be
+    /// included in a block
+    pub expiration_height: Option<BlockHeight>,
+    /// The optional nonce of the transaction among the ones paid by the fee
+    /// payer
+    pub nonce: Option<u64>,
+    /// The optional memo attached to the transaction
+    pub memo: Option<String>,
+    /// Sign the transaction over the EIP-191 personal messages of its
+    /// hashes, as Ethereum wallets do
+    pub eip191: bool,
     /// The chain id for which the transaction is intended
     pub chain_id: Option<ChainId>,
     /// Sign the tx with the key for the given alias from your wallet
     pub signing_key: Option<C::Keypair>,
     /// Sign the tx with the keypair of the public key of the given address
     pub signer: Option<C::Address>,
+    /// Have the fee paid by the implicit account of the given key, which
+    /// must sign the wrapper too
+    pub fee_sponsor: Option<C::Keypair>,
+    /// The hash of the MASP transaction section of the tx which unshields the
+    /// funds to pay the fee with. It is set when building a transfer whose
+    /// fee is paid from the shielded pool.
+    pub fee_unshield: Option<Hash>,
+    /// Have the fee paid by the given established account, whose keypair
+    /// must be in the wallet to authorize it
+    pub fee_payer: Option<C::Address>,
     /// Path to the TX WASM code file to reveal PK
     pub tx_reveal_code_path: PathBuf,
     /// Password to decrypt key
@@ -462,6 +653,26 @@ pub struct MaspPayAddrGen<C: NamadaTypes = SdkTypes> {
     pub viewing_key: C::ViewingKey,
     /// Pin
     pub pin: bool,
+    /// The number of diversified payment addresses to generate
+    pub count: u32,
+}
+
+/// MASP viewing key export arguments
+#[derive(Clone, Debug)]
+pub struct MaspViewingKeyExport {
+    /// The alias of the viewing key or of the spending key
+    pub alias: String,
+}
+
+/// MASP viewing key import arguments
+#[derive(Clone, Debug)]
+pub struct MaspViewingKeyImport {
+    /// Key alias
+    pub alias: String,
+    /// Whether to force overwrite the alias
+    pub alias_force: bool,
+    /// The file of the exported viewing key
+    pub file_path: PathBuf,
 }
 
 /// Wallet generate key and implicit address arguments
@@ -492,6 +703,9 @@ pub struct KeyAndAddressRestore {
     pub unsafe_dont_encrypt: bool,
     /// BIP44 derivation path
     pub derivation_path: Option<String>,
+    /// The number of keys to restore, at consecutive address indexes from
+    /// the derivation path
+    pub count: u32,
 }
 
 /// Wallet key lookup arguments
@@ -541,6 +755,13 @@ pub struct KeyExport {
     pub alias: String,
 }
 
+/// Wallet key migration arguments
+#[derive(Clone, Debug)]
+pub struct KeyMigrate {
+    /// Don't encrypt the keys that are stored unencrypted
+    pub unsafe_dont_encrypt: bool,
+}
+
 /// Wallet address lookup arguments
 #[derive(Clone, Debug)]
 pub struct AddressOrAliasFind {
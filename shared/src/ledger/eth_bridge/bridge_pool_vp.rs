@@ -0,0 +1,117 @@
+//! Validity predicate for the pool of the transfers to Ethereum
+//!
+//! A tx may only add new transfers to the pool, as long as it escrows their
+//! wrapped tokens and their gas fees in the pool's balances. The pending
+//! transfers and the escrowed tokens are only removed by the protocol, once
+//! the transfers are relayed to Ethereum.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use thiserror::Error;
+
+use crate::ledger::native_vp::{self, Ctx, NativeVp, VpEnv};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::ledger::storage_api::StorageRead;
+use crate::proto::Tx;
+use crate::types::address::{Address, InternalAddress, ETH_BRIDGE_POOL};
+use crate::types::eth_bridge_pool::{is_pending_transfer_key, PendingTransfer};
+use crate::types::storage::{DbKeySeg, Key};
+use crate::types::token;
+use crate::vm::WasmCacheAccess;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
+}
+
+/// Bridge pool functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Validity predicate for the bridge pool
+pub struct BridgePoolVp<'a, DB, H, CA>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H, CA>,
+}
+
+impl<'a, DB, H, CA> NativeVp for BridgePoolVp<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    const ADDR: InternalAddress = InternalAddress::EthBridgePool;
+
+    fn validate_tx(
+        &self,
+        _tx_data: &Tx,
+        keys_changed: &BTreeSet<Key>,
+        _verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let native_token = self.ctx.pre().get_native_token()?;
+        // The amounts that the new pending transfers must escrow, by token,
+        // minus the amounts that are escrowed
+        let mut unescrowed: BTreeMap<Address, token::Change> = BTreeMap::new();
+        for key in keys_changed {
+            if let Some(hash) = is_pending_transfer_key(key) {
+                if self.ctx.has_key_pre(key)? {
+                    tracing::debug!(
+                        "Rejected a change of the pending transfer {}",
+                        hash
+                    );
+                    return Ok(false);
+                }
+                let pending: PendingTransfer = match self.ctx.read_post(key)? {
+                    Some(pending) => pending,
+                    None => return Ok(false),
+                };
+                if pending.hash() != hash {
+                    tracing::debug!(
+                        "Rejected a pending transfer stored under the hash {}",
+                        hash
+                    );
+                    return Ok(false);
+                }
+                *unescrowed
+                    .entry(pending.transfer.asset.wrapped_token())
+                    .or_default() += pending.transfer.amount.change();
+                *unescrowed.entry(native_token.clone()).or_default() +=
+                    pending.gas_fee.amount.change();
+            } else if matches!(
+                key.segments.first(),
+                Some(DbKeySeg::AddressSeg(addr)) if addr == &ETH_BRIDGE_POOL
+            ) {
+                tracing::debug!("Rejected a change of the pool key {}", key);
+                return Ok(false);
+            } else if let Some((token, owner)) =
+                token::is_any_token_or_multitoken_balance_key(key)
+            {
+                if owner != &ETH_BRIDGE_POOL {
+                    continue;
+                }
+                if token::is_balance_key(token, key).is_none() {
+                    tracing::debug!(
+                        "Rejected a change of the multitoken balance {}",
+                        key
+                    );
+                    return Ok(false);
+                }
+                let pre: token::Amount =
+                    self.ctx.read_pre(key)?.unwrap_or_default();
+                let post: token::Amount =
+                    self.ctx.read_post(key)?.unwrap_or_default();
+                *unescrowed.entry(token.clone()).or_default() -=
+                    post.change() - pre.change();
+            }
+        }
+        Ok(unescrowed.values().all(|change| *change == 0))
+    }
+}
@@ -0,0 +1,84 @@
+//! Validity predicate for the ERC20 tokens wrapped by the bridge to Ethereum
+//!
+//! The wrapped tokens are only minted and burnt by the protocol, when acting
+//! on the events of the contract of the bridge, so a tx may only transfer
+//! them between accounts. The debits of the accounts are authorized by their
+//! own VPs.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use crate::ledger::native_vp::{self, Ctx, NativeVp, VpEnv};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::proto::Tx;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::ethereum_events::{EthAddress, ETH_ADDRESS_LEN};
+use crate::types::storage::{DbKeySeg, Key};
+use crate::types::token;
+use crate::vm::WasmCacheAccess;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
+}
+
+/// Wrapped ERC20 functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Validity predicate shared by all the wrapped ERC20 tokens
+pub struct Erc20Vp<'a, DB, H, CA>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H, CA>,
+}
+
+impl<'a, DB, H, CA> NativeVp for Erc20Vp<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    const ADDR: InternalAddress =
+        InternalAddress::Erc20(EthAddress([0; ETH_ADDRESS_LEN]));
+
+    fn validate_tx(
+        &self,
+        _tx_data: &Tx,
+        keys_changed: &BTreeSet<Key>,
+        _verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let token = self.ctx.address;
+        let mut change: token::Change = 0;
+        for key in keys_changed {
+            if !matches!(
+                key.segments.first(),
+                Some(DbKeySeg::AddressSeg(addr)) if addr == token
+            ) {
+                continue;
+            }
+            if token::is_balance_key(token, key).is_none() {
+                tracing::debug!(
+                    "Rejected a change of the wrapped ERC20 key {}",
+                    key
+                );
+                return Ok(false);
+            }
+            let pre: token::Amount =
+                self.ctx.read_pre(key)?.unwrap_or_default();
+            let post: token::Amount =
+                self.ctx.read_post(key)?.unwrap_or_default();
+            change += post.change() - pre.change();
+        }
+        // the total supply is unchanged
+        Ok(change == 0)
+    }
+}
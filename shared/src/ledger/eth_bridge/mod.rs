@@ -1,3 +1,6 @@
 //! Bridge from Ethereum
 
+pub mod bridge_pool_vp;
+pub mod erc20_vp;
+pub mod storage;
 pub mod vp;
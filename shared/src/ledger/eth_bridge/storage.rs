@@ -0,0 +1,402 @@
+//! Storage of the bridge to Ethereum:
+//!
+//! - the signatures of the validator set updates, which the contract of the
+//!   bridge verifies to follow the transitions of the validator set
+//! - the tallies of the votes of the validators on the events of the contract
+//!   of the bridge, which are acted on once seen by more than 2/3 of the stake
+//!
+//! The keys are only written by the protocol, as the VP of the bridge rejects
+//! every tx.
+
+use std::collections::BTreeMap;
+
+use super::vp::ADDRESS;
+use crate::ledger::oracle::is_quorum;
+use crate::ledger::storage_api::{
+    self, token as token_api, StorageRead, StorageWrite,
+};
+use crate::types::address::{Address, ETH_BRIDGE_POOL};
+use crate::types::eth_bridge_pool::pending_transfer_key;
+use crate::types::ethereum_events::EthereumEvent;
+use crate::types::hash::Hash;
+use crate::types::key::common;
+use crate::types::storage::{DbKeySeg, Epoch, Key, KeySeg};
+use crate::types::token;
+use crate::types::transaction::protocol::ValidatorSetUpdate;
+
+const VALSET_KEY_SEGMENT: &str = "valset";
+const HASH_KEY_SEGMENT: &str = "hash";
+const SIGS_KEY_SEGMENT: &str = "sigs";
+const SIGNED_STAKE_KEY_SEGMENT: &str = "signed_stake";
+const EVENTS_KEY_SEGMENT: &str = "eth_msgs";
+const BODY_KEY_SEGMENT: &str = "body";
+const SEEN_BY_KEY_SEGMENT: &str = "seen_by";
+const VOTING_POWER_KEY_SEGMENT: &str = "voting_power";
+const SEEN_KEY_SEGMENT: &str = "seen";
+
+/// Get the prefix of the keys of the update to the validator set of an epoch
+pub fn valset_prefix(epoch: Epoch) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&VALSET_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+        .push(&epoch)
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of the hash of the validator set of an epoch
+pub fn valset_hash_key(epoch: Epoch) -> Key {
+    valset_prefix(epoch)
+        .push(&HASH_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the prefix of the keys of the signatures of the validator set of an
+/// epoch
+pub fn valset_sigs_prefix(epoch: Epoch) -> Key {
+    valset_prefix(epoch)
+        .push(&SIGS_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of the signature by a validator of the validator set of an
+/// epoch
+pub fn valset_sig_key(epoch: Epoch, validator: &Address) -> Key {
+    valset_sigs_prefix(epoch)
+        .push(validator)
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of the stake of the validators that signed the validator set
+/// of an epoch
+pub fn valset_signed_stake_key(epoch: Epoch) -> Key {
+    valset_prefix(epoch)
+        .push(&SIGNED_STAKE_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Check if a key is the key of the signature of a validator set. Returns the
+/// epoch of the validator set and the address of the validator if so.
+pub fn is_valset_sig_key(key: &Key) -> Option<(Epoch, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(epoch),
+            DbKeySeg::StringSeg(sigs),
+            DbKeySeg::AddressSeg(validator),
+        ] if addr == &ADDRESS
+            && prefix == VALSET_KEY_SEGMENT
+            && sigs == SIGS_KEY_SEGMENT =>
+        {
+            Epoch::parse(epoch.clone())
+                .ok()
+                .map(|epoch| (epoch, validator))
+        }
+        _ => None,
+    }
+}
+
+/// Record the signature of a validator set update by a validator with the
+/// given stake
+pub fn write_valset_update<S>(
+    storage: &mut S,
+    update: &ValidatorSetUpdate,
+    stake: token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    storage.write(&valset_hash_key(update.epoch), update.valset_hash)?;
+    storage.write(
+        &valset_sig_key(update.epoch, &update.validator),
+        &update.sig,
+    )?;
+    let signed_stake = read_valset_signed_stake(storage, update.epoch)?;
+    storage.write(&valset_signed_stake_key(update.epoch), signed_stake + stake)
+}
+
+/// Read the hash of the validator set of an epoch, if any validator signed it
+pub fn read_valset_hash<S>(
+    storage: &S,
+    epoch: Epoch,
+) -> storage_api::Result<Option<Hash>>
+where
+    S: StorageRead,
+{
+    storage.read(&valset_hash_key(epoch))
+}
+
+/// Read the signatures of the validator set of an epoch, by the addresses of
+/// their validators
+pub fn read_valset_sigs<S>(
+    storage: &S,
+    epoch: Epoch,
+) -> storage_api::Result<BTreeMap<Address, common::Signature>>
+where
+    S: StorageRead,
+{
+    storage_api::iter_prefix(storage, &valset_sigs_prefix(epoch))?
+        .filter_map(|entry| match entry {
+            Ok((key, sig)) => is_valset_sig_key(&key)
+                .map(|(_, validator)| Ok((validator.clone(), sig))),
+            Err(err) => Some(Err(err)),
+        })
+        .collect()
+}
+
+/// Read the stake of the validators that signed the validator set of an
+/// epoch
+pub fn read_valset_signed_stake<S>(
+    storage: &S,
+    epoch: Epoch,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead,
+{
+    Ok(storage
+        .read(&valset_signed_stake_key(epoch))?
+        .unwrap_or_default())
+}
+
+/// Get the prefix of the keys of the tally of the votes on an Ethereum event,
+/// by the hash of the event
+pub fn eth_msg_prefix(hash: &Hash) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&EVENTS_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+        .push(&hash.to_string())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of an Ethereum event
+pub fn eth_msg_body_key(hash: &Hash) -> Key {
+    eth_msg_prefix(hash)
+        .push(&BODY_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of the vote of a validator on an Ethereum event
+pub fn eth_msg_seen_by_key(hash: &Hash, validator: &Address) -> Key {
+    eth_msg_prefix(hash)
+        .push(&SEEN_BY_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+        .push(validator)
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of the stake of the validators that voted on an Ethereum
+/// event
+pub fn eth_msg_voting_power_key(hash: &Hash) -> Key {
+    eth_msg_prefix(hash)
+        .push(&VOTING_POWER_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key of the flag set once an Ethereum event is seen by more than
+/// 2/3 of the stake
+pub fn eth_msg_seen_key(hash: &Hash) -> Key {
+    eth_msg_prefix(hash)
+        .push(&SEEN_KEY_SEGMENT.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Check if an Ethereum event was seen by more than 2/3 of the stake
+pub fn is_eth_msg_seen<S>(storage: &S, hash: &Hash) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    Ok(storage.read(&eth_msg_seen_key(hash))?.unwrap_or_default())
+}
+
+/// Check if a validator already voted on an Ethereum event
+pub fn has_voted_eth_msg<S>(
+    storage: &S,
+    hash: &Hash,
+    validator: &Address,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    storage.has_key(&eth_msg_seen_by_key(hash, validator))
+}
+
+/// Read the stake of the validators that voted on an Ethereum event
+pub fn read_eth_msg_voting_power<S>(
+    storage: &S,
+    hash: &Hash,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead,
+{
+    Ok(storage
+        .read(&eth_msg_voting_power_key(hash))?
+        .unwrap_or_default())
+}
+
+/// Record the vote of a validator with the given stake on an Ethereum event.
+/// Returns `true` if the event is seen by more than 2/3 of the total stake
+/// with this vote, i.e. if it must be acted on.
+pub fn vote_eth_msg<S>(
+    storage: &mut S,
+    event: &EthereumEvent,
+    validator: &Address,
+    stake: token::Amount,
+    total_stake: token::Amount,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead + StorageWrite,
+{
+    let hash = event.hash();
+    let body_key = eth_msg_body_key(&hash);
+    if !storage.has_key(&body_key)? {
+        storage.write(&body_key, event)?;
+    }
+    storage.write(&eth_msg_seen_by_key(&hash, validator), stake)?;
+    let voting_power = read_eth_msg_voting_power(storage, &hash)? + stake;
+    storage.write(&eth_msg_voting_power_key(&hash), voting_power)?;
+    if is_eth_msg_seen(storage, &hash)? || !is_quorum(voting_power, total_stake)
+    {
+        return Ok(false);
+    }
+    storage.write(&eth_msg_seen_key(&hash), true)?;
+    Ok(true)
+}
+
+/// Act on an Ethereum event seen by more than 2/3 of the stake: mint the
+/// wrapped tokens of the ERC20 tokens locked on Ethereum, or burn the escrowed
+/// tokens of the transfers of the bridge pool relayed to Ethereum and pay
+/// their gas fees to the relayer. Relayed transfers that aren't pending in the
+/// pool are ignored.
+pub fn act_on_eth_msg<S>(
+    storage: &mut S,
+    event: &EthereumEvent,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    match event {
+        EthereumEvent::TransfersToNamada { transfers, .. } => {
+            for transfer in transfers {
+                token_api::credit_tokens(
+                    storage,
+                    &transfer.asset.wrapped_token(),
+                    &transfer.receiver,
+                    transfer.amount,
+                )?;
+            }
+        }
+        EthereumEvent::TransfersToEthereum {
+            transfers, relayer, ..
+        } => {
+            let native_token = storage.get_native_token()?;
+            for pending in transfers {
+                let key = pending_transfer_key(&pending.hash());
+                if !storage.has_key(&key)? {
+                    tracing::warn!(
+                        "Ignoring a relayed transfer that isn't pending in \
+                         the bridge pool: {:?}",
+                        pending
+                    );
+                    continue;
+                }
+                storage.delete(&key)?;
+                token_api::burn_tokens(
+                    storage,
+                    &pending.transfer.asset.wrapped_token(),
+                    &ETH_BRIDGE_POOL,
+                    pending.transfer.amount,
+                )?;
+                token_api::transfer(
+                    storage,
+                    &native_token,
+                    &ETH_BRIDGE_POOL,
+                    relayer,
+                    pending.gas_fee.amount,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+    use crate::types::ethereum_events::{EthAddress, TransferToNamada};
+
+    #[test]
+    fn test_valset_sig_key() {
+        let validator = established_address_1();
+        let key = valset_sig_key(Epoch(3), &validator);
+        assert_eq!(is_valset_sig_key(&key), Some((Epoch(3), &validator)));
+        assert_eq!(is_valset_sig_key(&valset_hash_key(Epoch(3))), None);
+        assert_eq!(is_valset_sig_key(&valset_signed_stake_key(Epoch(3))), None);
+    }
+
+    /// Test that the wrapped tokens of a transfer to Namada are minted once
+    /// it is seen by more than 2/3 of the stake
+    #[test]
+    fn test_vote_eth_msg() {
+        let mut storage = TestWlStorage::default();
+        let receiver = established_address_1();
+        let asset = EthAddress([1; 20]);
+        let event = EthereumEvent::TransfersToNamada {
+            nonce: 0,
+            transfers: vec![TransferToNamada {
+                asset,
+                amount: token::Amount::whole(100),
+                receiver: receiver.clone(),
+            }],
+        };
+        let hash = event.hash();
+        let wrapped = asset.wrapped_token();
+        let stake = token::Amount::whole(30);
+        let total_stake = token::Amount::whole(90);
+
+        // 2/3 of the stake is not a quorum
+        for validator in [established_address_2(), established_address_3()] {
+            assert!(!vote_eth_msg(
+                &mut storage,
+                &event,
+                &validator,
+                stake,
+                total_stake
+            )
+            .unwrap());
+            assert!(has_voted_eth_msg(&storage, &hash, &validator).unwrap());
+        }
+        assert!(!is_eth_msg_seen(&storage, &hash).unwrap());
+        assert_eq!(
+            read_eth_msg_voting_power(&storage, &hash).unwrap(),
+            token::Amount::whole(60)
+        );
+
+        // the event is acted on once, with the vote reaching the quorum
+        assert!(vote_eth_msg(
+            &mut storage,
+            &event,
+            &receiver,
+            stake,
+            total_stake
+        )
+        .unwrap());
+        assert!(is_eth_msg_seen(&storage, &hash).unwrap());
+        act_on_eth_msg(&mut storage, &event).unwrap();
+        assert_eq!(
+            token_api::read_balance(&storage, &wrapped, &receiver).unwrap(),
+            token::Amount::whole(100)
+        );
+        assert!(!vote_eth_msg(
+            &mut storage,
+            &event,
+            &receiver,
+            stake,
+            total_stake
+        )
+        .unwrap());
+    }
+}
@@ -1,5 +1,7 @@
 //! Logic to do with events emitted by the ledger.
 pub mod log;
+#[cfg(feature = "websocket-client")]
+pub mod subscription;
 
 use std::collections::HashMap;
 use std::convert::TryFrom;
@@ -11,6 +13,9 @@ use thiserror::Error;
 
 use crate::ledger::native_vp::governance::utils::ProposalEvent;
 use crate::tendermint_proto::abci::EventAttribute;
+use crate::types::event::{
+    attributes, FeeRefund, IbcPacket, ProposalDeposit, TypedEvent,
+};
 use crate::types::ibc::IbcEvent;
 #[cfg(feature = "ferveo-tpke")]
 use crate::types::transaction::TxType;
@@ -49,6 +54,10 @@ pub enum EventType {
     Ibc(String),
     /// The proposal that has been executed
     Proposal,
+    /// The unused fee of a decrypted transaction was refunded
+    FeeRefund,
+    /// The funds locked by a proposal were refunded, slashed or burned
+    ProposalDeposit,
 }
 
 impl Display for EventType {
@@ -58,6 +67,8 @@ impl Display for EventType {
             EventType::Applied => write!(f, "applied"),
             EventType::Ibc(t) => write!(f, "{}", t),
             EventType::Proposal => write!(f, "proposal"),
+            EventType::FeeRefund => write!(f, "fee_refund"),
+            EventType::ProposalDeposit => write!(f, "proposal_deposit"),
         }?;
         Ok(())
     }
@@ -75,7 +86,7 @@ impl Event {
                     level: EventLevel::Tx,
                     attributes: HashMap::new(),
                 };
-                event["hash"] = tx.header_hash().to_string();
+                event[attributes::HASH] = tx.header_hash().to_string();
                 event
             }
             TxType::Decrypted(_) => {
@@ -84,7 +95,7 @@ impl Event {
                     level: EventLevel::Tx,
                     attributes: HashMap::new(),
                 };
-                event["hash"] = tx
+                event[attributes::HASH] = tx
                     .clone()
                     .update_header(TxType::Raw)
                     .header_hash()
@@ -97,16 +108,30 @@ impl Event {
                     level: EventLevel::Tx,
                     attributes: HashMap::new(),
                 };
-                event["hash"] = tx.header_hash().to_string();
+                event[attributes::HASH] = tx.header_hash().to_string();
                 event
             }
             _ => unreachable!(),
         };
-        event["height"] = height.to_string();
-        event["log"] = "".to_string();
+        event[attributes::HEIGHT] = height.to_string();
+        event[attributes::LOG] = "".to_string();
+        if let Some(memo) = tx.memo() {
+            event[attributes::MEMO] = memo;
+        }
         event
     }
 
+    /// Set the attributes of a typed event, overriding the attributes with
+    /// the same keys
+    pub fn extend(&mut self, event: &impl TypedEvent) {
+        self.attributes.extend(event.attributes());
+    }
+
+    /// Parse a typed event from the attributes of the event
+    pub fn parse<E: TypedEvent>(&self) -> Option<E> {
+        E::from_attributes(&self.attributes)
+    }
+
     /// Check if the events keys contains a given string
     pub fn contains_key(&self, key: &str) -> bool {
         self.attributes.contains_key(key)
@@ -146,6 +171,16 @@ impl From<IbcEvent> for Event {
     }
 }
 
+impl From<IbcPacket> for Event {
+    fn from(packet: IbcPacket) -> Self {
+        Self {
+            attributes: packet.attributes().into_iter().collect(),
+            event_type: EventType::Ibc(packet.event_type),
+            level: EventLevel::Tx,
+        }
+    }
+}
+
 impl From<ProposalEvent> for Event {
     fn from(proposal_event: ProposalEvent) -> Self {
         Self {
@@ -156,13 +191,36 @@ impl From<ProposalEvent> for Event {
     }
 }
 
-/// Convert our custom event into the necessary tendermint proto type
+impl From<FeeRefund> for Event {
+    fn from(refund: FeeRefund) -> Self {
+        Self {
+            event_type: EventType::FeeRefund,
+            level: EventLevel::Tx,
+            attributes: refund.attributes().into_iter().collect(),
+        }
+    }
+}
+
+impl From<ProposalDeposit> for Event {
+    fn from(deposit: ProposalDeposit) -> Self {
+        Self {
+            event_type: EventType::ProposalDeposit,
+            level: EventLevel::Block,
+            attributes: deposit.attributes().into_iter().collect(),
+        }
+    }
+}
+
+/// Convert our custom event into the necessary tendermint proto type. The
+/// attributes are sorted by their keys, such that the events are
+/// deterministic, and they are all indexed by Tendermint.
 impl From<Event> for crate::tendermint_proto::abci::Event {
     fn from(event: Event) -> Self {
+        let mut attributes: Vec<_> = event.attributes.into_iter().collect();
+        attributes.sort();
         Self {
             r#type: event.event_type.to_string(),
-            attributes: event
-                .attributes
+            attributes: attributes
                 .into_iter()
                 .map(|(key, value)| EventAttribute {
                     key,
@@ -0,0 +1,348 @@
+//! A client of the events emitted by the ledger, which subscribes to the new
+//! blocks over a Tendermint websocket.
+//!
+//! The subscription reconnects to the node when the connection is lost and
+//! backfills the events of the blocks committed in the meantime from their
+//! block results, such that no event is missed nor received twice.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use futures::StreamExt;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+
+use crate::ledger::events::{Event, EventLevel, EventType};
+use crate::tendermint::abci;
+use crate::tendermint_rpc::error::Error as RpcError;
+use crate::tendermint_rpc::event::EventData;
+use crate::tendermint_rpc::query::{EventType as TmEventType, Query};
+use crate::tendermint_rpc::{
+    Client, Subscription, SubscriptionClient, WebSocketClient,
+    WebSocketClientUrl,
+};
+use crate::types::event::TypedEvent;
+use crate::types::storage::BlockHeight;
+
+/// The maximum delay between the attempts to reconnect to the node
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Errors of the events subscriptions
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Error of the connection to the node
+    #[error("Tendermint RPC error: {0}")]
+    Rpc(#[from] RpcError),
+}
+
+/// Result of a function that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The events of a given type, optionally with the given values of some of
+/// their attributes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventFilter {
+    event_type: EventType,
+    attributes: BTreeMap<String, String>,
+}
+
+impl EventFilter {
+    /// Filter the events of the given type
+    pub fn new(event_type: EventType) -> Self {
+        Self {
+            event_type,
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Only keep the events with the given value of an attribute
+    pub fn with_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Check if an event passes the filter
+    pub fn matches(&self, event: &Event) -> bool {
+        event.event_type == self.event_type
+            && self
+                .attributes
+                .iter()
+                .all(|(key, value)| event.get(key) == Some(value))
+    }
+
+    /// The query of the new blocks with the events of the filter
+    fn query(&self) -> Query {
+        self.attributes.iter().fold(
+            Query::from(TmEventType::NewBlock),
+            |query, (key, value)| {
+                query.and_eq(
+                    format!("{}.{}", self.event_type, key),
+                    value.clone(),
+                )
+            },
+        )
+    }
+}
+
+/// An event received from a subscription
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubscribedEvent {
+    /// The height of the block that emitted the event
+    pub height: BlockHeight,
+    /// The event
+    pub event: Event,
+}
+
+impl SubscribedEvent {
+    /// Parse a typed event from the attributes of the event
+    pub fn parse<E: TypedEvent>(&self) -> Option<E> {
+        self.event.parse()
+    }
+}
+
+/// A client of the events emitted by the ledger
+#[derive(Clone, Debug)]
+pub struct EventClient {
+    url: WebSocketClientUrl,
+}
+
+impl EventClient {
+    /// A client of the events of the node with the given websocket URL, e.g.
+    /// `ws://127.0.0.1:26657/websocket`
+    pub fn new(url: WebSocketClientUrl) -> Self {
+        Self { url }
+    }
+
+    /// Subscribe to the events passing the given filter, from the next
+    /// committed block
+    pub async fn subscribe_events(
+        &self,
+        filter: EventFilter,
+    ) -> Result<EventSubscription> {
+        let connection = Connection::open(&self.url, &filter).await?;
+        let last_height = connection.latest_height().await?;
+        Ok(EventSubscription {
+            url: self.url.clone(),
+            filter,
+            connection: Some(connection),
+            last_height,
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+/// A subscription to the events passing a filter
+pub struct EventSubscription {
+    url: WebSocketClientUrl,
+    filter: EventFilter,
+    connection: Option<Connection>,
+    /// The height of the last block whose events were received
+    last_height: BlockHeight,
+    /// The received events that weren't yet returned
+    pending: VecDeque<SubscribedEvent>,
+}
+
+impl EventSubscription {
+    /// Wait for the next event. If the connection to the node is lost, this
+    /// reconnects to it and backfills the events of the missed blocks.
+    pub async fn next(&mut self) -> SubscribedEvent {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return event;
+            }
+            let connection = match self.connection.as_mut() {
+                Some(connection) => connection,
+                None => {
+                    self.reconnect().await;
+                    continue;
+                }
+            };
+            let received = connection.subscription.next().await;
+            match received {
+                Some(Ok(event)) => {
+                    if let EventData::NewBlock {
+                        block: Some(block),
+                        result_end_block,
+                        ..
+                    } = event.data
+                    {
+                        let events = result_end_block
+                            .map(|result| result.events)
+                            .unwrap_or_default();
+                        self.receive_block(
+                            BlockHeight(block.header.height.value()),
+                            &events,
+                        );
+                    }
+                }
+                Some(Err(err)) => {
+                    tracing::info!("Lost the events subscription: {}", err);
+                    self.connection = None;
+                }
+                None => {
+                    tracing::info!("The events subscription was closed");
+                    self.connection = None;
+                }
+            }
+        }
+    }
+
+    /// Keep the events of a block that pass the filter, unless the block was
+    /// already received
+    fn receive_block(&mut self, height: BlockHeight, events: &[abci::Event]) {
+        if height <= self.last_height {
+            return;
+        }
+        self.last_height = height;
+        self.pending.extend(
+            events
+                .iter()
+                .map(decode_event)
+                .filter(|event| self.filter.matches(event))
+                .map(|event| SubscribedEvent { height, event }),
+        );
+    }
+
+    /// Reconnect to the node, with an exponential backoff, and backfill the
+    /// events of the blocks committed since the last received block
+    async fn reconnect(&mut self) {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match self.try_reconnect().await {
+                Ok(()) => return,
+                Err(err) => {
+                    tracing::info!(
+                        "Failed to reconnect the events subscription, \
+                         retrying in {:?}: {}",
+                        backoff,
+                        err
+                    );
+                    async_std::task::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                }
+            }
+        }
+    }
+
+    async fn try_reconnect(&mut self) -> Result<()> {
+        // Subscribe before backfilling, such that no block is missed in
+        // between
+        let connection = Connection::open(&self.url, &self.filter).await?;
+        let latest_height = connection.latest_height().await?;
+        let first_height = self.last_height.next_height();
+        for height in first_height.0..=latest_height.0 {
+            let results = connection
+                .client
+                .block_results(
+                    crate::tendermint::block::Height::try_from(height)
+                        .expect("The height should be valid"),
+                )
+                .await?;
+            self.receive_block(
+                BlockHeight(height),
+                &results.end_block_events.unwrap_or_default(),
+            );
+        }
+        tracing::info!(
+            "Reconnected the events subscription, backfilled the blocks from \
+             height {} to {}",
+            first_height,
+            latest_height
+        );
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+/// A websocket connection to the node with a subscription to the new blocks
+struct Connection {
+    client: WebSocketClient,
+    subscription: Subscription,
+    driver: JoinHandle<std::result::Result<(), RpcError>>,
+}
+
+impl Connection {
+    async fn open(
+        url: &WebSocketClientUrl,
+        filter: &EventFilter,
+    ) -> Result<Self> {
+        let (client, driver) = WebSocketClient::new(url.clone()).await?;
+        let driver = tokio::spawn(driver.run());
+        let subscription = client.subscribe(filter.query()).await?;
+        Ok(Self {
+            client,
+            subscription,
+            driver,
+        })
+    }
+
+    /// The height of the last committed block
+    async fn latest_height(&self) -> Result<BlockHeight> {
+        let block = self.client.latest_block().await?;
+        Ok(BlockHeight(block.block.header.height.value()))
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.driver.abort();
+    }
+}
+
+/// Decode an event from its Tendermint encoding
+fn decode_event(event: &abci::Event) -> Event {
+    let event_type = match event.type_str.as_str() {
+        "accepted" => EventType::Accepted,
+        "applied" => EventType::Applied,
+        "proposal" => EventType::Proposal,
+        "fee_refund" => EventType::FeeRefund,
+        "proposal_deposit" => EventType::ProposalDeposit,
+        ibc => EventType::Ibc(ibc.to_owned()),
+    };
+    let level = match event_type {
+        EventType::Proposal | EventType::ProposalDeposit => EventLevel::Block,
+        _ => EventLevel::Tx,
+    };
+    Event {
+        event_type,
+        level,
+        attributes: event
+            .attributes
+            .iter()
+            .map(|tag| (tag.key.to_string(), tag.value.to_string()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::event::attributes;
+
+    /// Test that the events are filtered by their type and attributes
+    #[test]
+    fn test_event_filter() {
+        let event = Event {
+            event_type: EventType::Applied,
+            level: EventLevel::Tx,
+            attributes: HashMap::from([
+                (attributes::HASH.to_owned(), "AB".to_owned()),
+                (attributes::CODE.to_owned(), "0".to_owned()),
+            ]),
+        };
+        assert!(EventFilter::new(EventType::Applied).matches(&event));
+        assert!(EventFilter::new(EventType::Applied)
+            .with_attribute(attributes::HASH, "AB")
+            .matches(&event));
+        assert!(!EventFilter::new(EventType::Applied)
+            .with_attribute(attributes::HASH, "CD")
+            .matches(&event));
+        assert!(!EventFilter::new(EventType::Accepted).matches(&event));
+    }
+}
@@ -0,0 +1,102 @@
+//! Validity predicate of the accounts operated by IBC as a native validity
+//! predicate
+//!
+//! These are the interchain accounts (ICS-27), the account of the forwarded
+//! token transfers and the escrow of the relayer fees (ICS-29). They have no
+//! keys on this chain, so their tokens can only be spent in an IBC tx, e.g. by
+//! the txs that the controller chain of an interchain account sends over IBC,
+//! which the IBC VP validates by executing it again.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use crate::ledger::ibc::storage::is_ibc_key;
+use crate::ledger::native_vp::{self, Ctx, NativeVp, VpEnv};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::proto::Tx;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::Key;
+use crate::types::token;
+use crate::vm::WasmCacheAccess;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
+}
+
+/// Result for the VP of the accounts operated by IBC
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Validity predicate of the accounts operated by IBC
+pub struct IbcAccount<'a, DB, H, CA>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H, CA>,
+}
+
+impl<'a, DB, H, CA> NativeVp for IbcAccount<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    // The interchain account is identified by its hash, which isn't used here
+    const ADDR: InternalAddress = InternalAddress::IbcAccount(String::new());
+
+    fn validate_tx(
+        &self,
+        _tx_data: &Tx,
+        keys_changed: &BTreeSet<Key>,
+        _verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        // the IBC VP checks the txs that change the IBC state
+        let is_ibc_tx = keys_changed.iter().any(is_ibc_key);
+        for key in keys_changed {
+            let is_account_key =
+                key.find_addresses().iter().any(is_ibc_account);
+            if !is_account_key || is_ibc_tx {
+                continue;
+            }
+            // anyone can credit the account
+            if let Some((_, owner)) =
+                token::is_any_token_or_multitoken_balance_key(key)
+            {
+                if is_ibc_account(owner) {
+                    let pre: token::Amount =
+                        self.ctx.read_pre(key)?.unwrap_or_default();
+                    let post: token::Amount =
+                        self.ctx.read_post(key)?.unwrap_or_default();
+                    if post >= pre {
+                        continue;
+                    }
+                }
+            }
+            tracing::debug!(
+                "Rejected a change of the IBC account key {} out of an IBC tx",
+                key
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+fn is_ibc_account(addr: &Address) -> bool {
+    matches!(
+        addr,
+        Address::Internal(
+            InternalAddress::IbcAccount(_)
+                | InternalAddress::IbcForward
+                | InternalAddress::IbcFeeEscrow
+        )
+    )
+}
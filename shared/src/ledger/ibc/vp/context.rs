@@ -3,7 +3,9 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use namada_core::ledger::ibc::storage::is_ibc_key;
+use namada_core::ledger::ibc::storage::{
+    is_ibc_account_balance_key, is_ibc_key,
+};
 use namada_core::ledger::ibc::{IbcCommonContext, IbcStorageContext};
 use namada_core::ledger::storage::write_log::StorageModification;
 use namada_core::ledger::storage::{self as ledger_storage, StorageHasher};
@@ -11,7 +13,9 @@ use namada_core::ledger::storage_api::StorageRead;
 use namada_core::types::address::{Address, InternalAddress};
 use namada_core::types::ibc::IbcEvent;
 use namada_core::types::storage::{BlockHeight, Header, Key};
-use namada_core::types::token::{is_any_token_balance_key, Amount};
+use namada_core::types::token::{self, is_any_token_balance_key, Amount};
+
+use namada_proof_of_stake::is_validator;
 
 use super::Error;
 use crate::ledger::native_vp::CtxPreStorageRead;
@@ -47,7 +51,10 @@ where
     }
 
     pub fn get_changed_keys(&self) -> HashSet<&Key> {
-        self.store.keys().filter(|k| is_ibc_key(k)).collect()
+        self.store
+            .keys()
+            .filter(|k| is_ibc_key(k) || is_ibc_account_balance_key(k))
+            .collect()
     }
 
     pub fn get_changed_value(&self, key: &Key) -> Option<&StorageModification> {
@@ -159,6 +166,31 @@ where
         Ok(())
     }
 
+    fn get_native_token(&self) -> Result<Address, Self::Error> {
+        self.ctx.get_native_token().map_err(Error::NativeVpError)
+    }
+
+    fn is_validator(&self, address: &Address) -> Result<bool, Self::Error> {
+        is_validator(&self.ctx, address).map_err(Error::NativeVpError)
+    }
+
+    fn bond_tokens(
+        &mut self,
+        source: &Address,
+        _validator: &Address,
+        amount: Amount,
+    ) -> Result<(), Self::Error> {
+        // NOTE: Only the balance of the source, which is checked by the VP,
+        // is updated. The bond itself is validated by the PoS VP.
+        let native_token = self.get_native_token()?;
+        let src = token::balance_key(&native_token, source);
+        let dest = token::balance_key(
+            &native_token,
+            &Address::Internal(InternalAddress::PoS),
+        );
+        self.transfer_token(&src, &dest, amount)
+    }
+
     /// Get the current height of this chain
     fn get_height(&self) -> Result<BlockHeight, Self::Error> {
         self.ctx.get_block_height().map_err(Error::NativeVpError)
@@ -260,6 +292,23 @@ where
         unimplemented!("Validation doesn't transfer")
     }
 
+    fn get_native_token(&self) -> Result<Address, Self::Error> {
+        self.ctx.get_native_token().map_err(Error::NativeVpError)
+    }
+
+    fn is_validator(&self, address: &Address) -> Result<bool, Self::Error> {
+        is_validator(&self.ctx, address).map_err(Error::NativeVpError)
+    }
+
+    fn bond_tokens(
+        &mut self,
+        _source: &Address,
+        _validator: &Address,
+        _amount: Amount,
+    ) -> Result<(), Self::Error> {
+        unimplemented!("Validation doesn't bond")
+    }
+
     fn get_height(&self) -> Result<BlockHeight, Self::Error> {
         self.ctx.get_block_height().map_err(Error::NativeVpError)
     }
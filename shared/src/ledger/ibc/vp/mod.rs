@@ -1,5 +1,6 @@
 //! IBC integration as a native validity predicate
 
+mod account;
 mod context;
 mod denom;
 mod token;
@@ -9,10 +10,14 @@ use std::collections::{BTreeSet, HashSet};
 use std::rc::Rc;
 use std::time::Duration;
 
+pub use account::{Error as IbcAccountError, IbcAccount};
 use context::{PseudoExecutionContext, VpValidationContext};
-use namada_core::ledger::ibc::storage::{is_ibc_denom_key, is_ibc_key};
+use namada_core::ledger::ibc::storage::{
+    is_ibc_account_balance_key, is_ibc_denom_key, is_ibc_key,
+};
 use namada_core::ledger::ibc::{
-    Error as ActionError, IbcActions, TransferModule, ValidationParams,
+    Error as ActionError, IbcActions, IcaHostModule, TransferModule,
+    ValidationParams,
 };
 use namada_core::ledger::storage::write_log::StorageModification;
 use namada_core::ledger::storage::{self as ledger_storage, StorageHasher};
@@ -111,10 +116,14 @@ where
         let mut actions = IbcActions::new(ctx.clone());
         let module = TransferModule::new(ctx.clone());
         actions.add_transfer_route(module.module_id(), module);
+        let module = IcaHostModule::new(ctx.clone());
+        actions.add_ica_host_route(module.module_id(), module);
         actions.execute(tx_data)?;
 
-        let changed_ibc_keys: HashSet<&Key> =
-            keys_changed.iter().filter(|k| is_ibc_key(k)).collect();
+        let changed_ibc_keys: HashSet<&Key> = keys_changed
+            .iter()
+            .filter(|k| is_ibc_key(k) || is_ibc_account_balance_key(k))
+            .collect();
         if changed_ibc_keys.len() != ctx.borrow().get_changed_keys().len() {
             return Err(Error::StateChange(format!(
                 "The changed keys mismatched: Actual {:?}, Expected {:?}",
@@ -151,8 +160,10 @@ where
         let mut actions = IbcActions::new(ctx.clone());
         actions.set_validation_params(self.validation_params()?);
 
-        let module = TransferModule::new(ctx);
+        let module = TransferModule::new(ctx.clone());
         actions.add_transfer_route(module.module_id(), module);
+        let module = IcaHostModule::new(ctx);
+        actions.add_ica_host_route(module.module_id(), module);
         actions.validate(tx_data).map_err(Error::IbcAction)
     }
 
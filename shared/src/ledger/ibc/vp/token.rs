@@ -1,12 +1,21 @@
 //! IBC token transfer validation as a native validity predicate
 
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
 
 use borsh::BorshDeserialize;
+use namada_core::ledger::ibc::context::fee::{
+    MsgTransferWithFee, MSG_TRANSFER_WITH_FEE_TYPE_URL,
+};
+use namada_core::ledger::ibc::context::forward::{
+    forward_metadata, received_denom, ForwardedTransfer,
+};
 use prost::Message;
 use thiserror::Error;
 
+use crate::ibc::applications::transfer::acknowledgement::TokenTransferAcknowledgement;
 use crate::ibc::applications::transfer::coin::PrefixedCoin;
+use crate::ibc::applications::transfer::denom::PrefixedDenom;
 use crate::ibc::applications::transfer::error::TokenTransferError;
 use crate::ibc::applications::transfer::msgs::transfer::{
     MsgTransfer, TYPE_URL as MSG_TRANSFER_TYPE_URL,
@@ -17,6 +26,7 @@ use crate::ibc::applications::transfer::{
 };
 use crate::ibc::core::ics04_channel::msgs::PacketMsg;
 use crate::ibc::core::ics04_channel::packet::Packet;
+use crate::ibc::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::ibc::core::ics26_routing::error::RouterError;
 use crate::ibc::core::ics26_routing::msgs::MsgEnvelope;
 use crate::ibc_proto::google::protobuf::Any;
@@ -134,7 +144,14 @@ where
                         .to_owned(),
                 ));
             }
-        } else if ibc_keys_changed.len() > 1 {
+        }
+        // a forwarded transfer can update 2 special IBC accounts
+        if let Some(changes) = self.forwarding_changes(&tx_data)? {
+            if self.validate_changes(&ibc_keys_changed, &changes)? {
+                return Ok(true);
+            }
+        }
+        if ibc_keys_changed.len() > 1 {
             // a transaction can update at most 1 special IBC account for now
             return Err(Error::TokenTransfer(
                 "Invalid transfer for multiple non-owner balances".to_owned(),
@@ -150,6 +167,15 @@ where
                     .map_err(Error::MsgTransfer)?;
                 self.validate_sending_token(&msg)
             }
+            MSG_TRANSFER_WITH_FEE_TYPE_URL => {
+                let msg = MsgTransferWithFee::try_from_slice(&ibc_msg.value)
+                    .map_err(Error::Decoding)?;
+                let transfer = Any::decode(&msg.transfer[..])
+                    .map_err(Error::DecodingIbcData)?;
+                let msg = MsgTransfer::try_from(transfer)
+                    .map_err(Error::MsgTransfer)?;
+                self.validate_sending_token(&msg)
+            }
             _ => {
                 let envelope: MsgEnvelope =
                     ibc_msg.try_into().map_err(Error::IbcMessage)?;
@@ -305,6 +331,154 @@ where
         }
     }
 
+    /// Get the expected changes of the balances of the special IBC accounts
+    /// when the tx receives a token transfer to be forwarded, or refunds a
+    /// forwarded transfer and sends the tokens back to the original sender
+    fn forwarding_changes(
+        &self,
+        tx_data: &[u8],
+    ) -> Result<Option<HashMap<Key, token::Change>>> {
+        let ibc_msg = Any::decode(tx_data).map_err(Error::DecodingIbcData)?;
+        if ibc_msg.type_url == MSG_TRANSFER_TYPE_URL {
+            return Ok(None);
+        }
+        let envelope: MsgEnvelope = match ibc_msg.try_into() {
+            Ok(envelope) => envelope,
+            Err(_) => return Ok(None),
+        };
+        let mut changes = HashMap::new();
+        let packet = match envelope {
+            MsgEnvelope::Packet(PacketMsg::Recv(msg)) => {
+                let forward = match forward_metadata(&msg.packet.data) {
+                    Some(forward) => forward,
+                    None => return Ok(None),
+                };
+                let data =
+                    serde_json::from_slice::<PacketData>(&msg.packet.data)
+                        .map_err(Error::DecodingPacketData)?;
+                let amount = Amount::try_from(data.token.amount)
+                    .map_err(Error::Amount)?;
+                let (key, change) = receiving_change(
+                    &msg.packet.port_id_on_a,
+                    &msg.packet.chan_id_on_a,
+                    &data.token.denom,
+                    amount,
+                )?;
+                *changes.entry(key).or_default() += change;
+                let (port_id, channel_id) = match (
+                    PortId::from_str(&forward.port),
+                    ChannelId::from_str(&forward.channel),
+                ) {
+                    (Ok(port_id), Ok(channel_id)) => (port_id, channel_id),
+                    _ => return Ok(None),
+                };
+                let (key, change) = sending_change(
+                    &port_id,
+                    &channel_id,
+                    &received_denom(&msg.packet, &data.token.denom),
+                    amount,
+                )?;
+                *changes.entry(key).or_default() += change;
+                return Ok(Some(changes));
+            }
+            MsgEnvelope::Packet(PacketMsg::Ack(msg)) => {
+                let is_success = matches!(
+                    serde_json::from_slice::<TokenTransferAcknowledgement>(
+                        msg.acknowledgement.as_ref()
+                    ),
+                    Ok(TokenTransferAcknowledgement::Success(_))
+                );
+                if is_success {
+                    return Ok(None);
+                }
+                msg.packet
+            }
+            MsgEnvelope::Packet(PacketMsg::Timeout(msg)) => msg.packet,
+            MsgEnvelope::Packet(PacketMsg::TimeoutOnClose(msg)) => msg.packet,
+            _ => return Ok(None),
+        };
+
+        let forward_key = ibc_storage::forward_key(
+            &packet.port_id_on_a,
+            &packet.chan_id_on_a,
+            packet.sequence,
+        );
+        let forwarded: ForwardedTransfer =
+            match self.ctx.read_pre(&forward_key)? {
+                Some(forwarded) => forwarded,
+                None => return Ok(None),
+            };
+        let (port_id, channel_id) = match (
+            PortId::from_str(&forwarded.port_id),
+            ChannelId::from_str(&forwarded.channel_id),
+        ) {
+            (Ok(port_id), Ok(channel_id)) => (port_id, channel_id),
+            _ => return Ok(None),
+        };
+        let data = serde_json::from_slice::<PacketData>(&packet.data)
+            .map_err(Error::DecodingPacketData)?;
+        let amount =
+            Amount::try_from(data.token.amount).map_err(Error::Amount)?;
+        let (key, change) = refunding_change(
+            &packet.port_id_on_a,
+            &packet.chan_id_on_a,
+            &data.token.denom,
+            amount,
+        )?;
+        *changes.entry(key).or_default() += change;
+        let (key, change) =
+            sending_change(&port_id, &channel_id, &data.token.denom, amount)?;
+        *changes.entry(key).or_default() += change;
+        Ok(Some(changes))
+    }
+
+    /// Check that the balances of the special IBC accounts changed as
+    /// expected, and no others
+    fn validate_changes(
+        &self,
+        keys_changed: &HashSet<Key>,
+        changes: &HashMap<Key, token::Change>,
+    ) -> Result<bool> {
+        if keys_changed.iter().any(|key| !changes.contains_key(key)) {
+            return Ok(false);
+        }
+        for (key, change) in changes {
+            let actual = match token::is_any_token_balance_key(key) {
+                Some(Address::Internal(InternalAddress::IbcEscrow)) => {
+                    let pre =
+                        try_decode_token_amount(self.ctx.read_bytes_pre(key)?)?
+                            .unwrap_or_default();
+                    let post = try_decode_token_amount(
+                        self.ctx.read_bytes_post(key)?,
+                    )?
+                    .unwrap_or_default();
+                    post.change() - pre.change()
+                }
+                // the previous balance of the mint address should be the
+                // maximum
+                Some(Address::Internal(InternalAddress::IbcMint)) => {
+                    match try_decode_token_amount(
+                        self.ctx.read_bytes_temp(key)?,
+                    )? {
+                        Some(post) => post.change() - Amount::max().change(),
+                        None => 0,
+                    }
+                }
+                // the previous balance of the burn address should be zero
+                Some(Address::Internal(InternalAddress::IbcBurn)) => {
+                    try_decode_token_amount(self.ctx.read_bytes_temp(key)?)?
+                        .map(|post| post.change())
+                        .unwrap_or_default()
+                }
+                _ => return Ok(false),
+            };
+            if actual != *change {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
     fn validate_refunding_token(&self, packet: &Packet) -> Result<bool> {
         let data = serde_json::from_slice::<PacketData>(&packet.data)
             .map_err(Error::DecodingPacketData)?;
@@ -357,6 +531,69 @@ where
     }
 }
 
+/// The change of the balance of the special IBC account by receiving the
+/// token over the channel
+fn receiving_change(
+    port_id_on_a: &PortId,
+    chan_id_on_a: &ChannelId,
+    denom: &PrefixedDenom,
+    amount: Amount,
+) -> Result<(Key, token::Change)> {
+    let token = ibc_storage::token(denom.to_string())
+        .map_err(|e| Error::Denom(e.to_string()))?;
+    let owner = if is_receiver_chain_source(
+        port_id_on_a.clone(),
+        chan_id_on_a.clone(),
+        denom,
+    ) {
+        InternalAddress::IbcEscrow
+    } else {
+        InternalAddress::IbcMint
+    };
+    let key = token::balance_key(&token, &Address::Internal(owner));
+    Ok((key, -amount.change()))
+}
+
+/// The change of the balance of the special IBC account by sending the token
+/// over the channel
+fn sending_change(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    denom: &PrefixedDenom,
+    amount: Amount,
+) -> Result<(Key, token::Change)> {
+    let token = ibc_storage::token(denom.to_string())
+        .map_err(|e| Error::Denom(e.to_string()))?;
+    let owner =
+        if is_sender_chain_source(port_id.clone(), channel_id.clone(), denom) {
+            InternalAddress::IbcEscrow
+        } else {
+            InternalAddress::IbcBurn
+        };
+    let key = token::balance_key(&token, &Address::Internal(owner));
+    Ok((key, amount.change()))
+}
+
+/// The change of the balance of the special IBC account by refunding the
+/// token sent over the channel
+fn refunding_change(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    denom: &PrefixedDenom,
+    amount: Amount,
+) -> Result<(Key, token::Change)> {
+    let token = ibc_storage::token(denom.to_string())
+        .map_err(|e| Error::Denom(e.to_string()))?;
+    let owner =
+        if is_sender_chain_source(port_id.clone(), channel_id.clone(), denom) {
+            InternalAddress::IbcEscrow
+        } else {
+            InternalAddress::IbcMint
+        };
+    let key = token::balance_key(&token, &Address::Internal(owner));
+    Ok((key, -amount.change()))
+}
+
 impl From<native_vp::Error> for Error {
     fn from(err: native_vp::Error) -> Self {
         Self::NativeVpError(err)
@@ -63,8 +63,8 @@ impl RewardsController {
             epochs_per_year,
         } = self;
 
-        let locked: Decimal = u64::from(locked_tokens).into();
-        let total: Decimal = u64::from(total_tokens).into();
+        let locked = Decimal::from(locked_tokens);
+        let total = Decimal::from(total_tokens);
         let epochs_py: Decimal = (epochs_per_year).into();
 
         let locked_ratio = locked / total;
@@ -65,10 +65,10 @@ use crate::tendermint_rpc::query::Query;
 use crate::tendermint_rpc::Order;
 use crate::types::address::{masp, Address};
 use crate::types::masp::{BalanceOwner, ExtendedViewingKey, PaymentAddress};
-use crate::types::storage::{BlockHeight, Epoch, Key, KeySeg, TxIndex};
+use crate::types::storage::{BlockHeight, Epoch, TxIndex};
 use crate::types::token;
 use crate::types::token::{
-    Transfer, HEAD_TX_KEY, PIN_KEY_PREFIX, TX_KEY_PREFIX,
+    masp_head_tx_key, masp_pin_key, masp_tx_key, Transfer,
 };
 use crate::types::transaction::{EllipticCurve, PairingEngine, WrapperTx};
 
@@ -454,6 +454,68 @@ pub type TransferDelta = HashMap<Address, Amount<Address>>;
 /// Represents the changes that were made to a list of shielded accounts
 pub type TransactionDelta = HashMap<ViewingKey, Amount>;
 
+/// An output of a shielded transaction, i.e. a note to be decrypted
+pub type MaspOutput = OutputDescription<
+    <<Authorized as Authorization>::SaplingAuth as masp_primitives::transaction::components::sapling::Authorization>::Proof,
+>;
+
+/// The number of compact shielded transfers fetched with each query
+const COMPACT_TXS_PER_PAGE: u64 = 500;
+
+/// The compact form of an accepted shielded transfer, with only what the
+/// scanning of the notes uses: the outputs to be decrypted, the nullifiers of
+/// the spent notes and the transparent part of the transfer. The proofs and
+/// the signatures, which make up most of a shielded transaction, are left
+/// out.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct CompactMaspTx {
+    /// The height of the block of the transfer
+    pub height: BlockHeight,
+    /// The index of the transfer in its block
+    pub index: TxIndex,
+    /// The epoch of the transfer
+    pub epoch: Epoch,
+    /// The source of the transparent part of the transfer
+    pub source: Address,
+    /// The target of the transparent part of the transfer
+    pub target: Address,
+    /// The token of the transparent part of the transfer
+    pub token: Address,
+    /// The amount of the transparent part of the transfer
+    pub amount: token::Amount,
+    /// The shielded outputs, in the order of the commitment tree
+    pub outputs: Vec<MaspOutput>,
+    /// The nullifiers of the spent notes
+    pub nullifiers: Vec<Nullifier>,
+}
+
+impl CompactMaspTx {
+    /// Make the compact form of the accepted shielded transfer
+    pub fn new(
+        height: BlockHeight,
+        index: TxIndex,
+        epoch: Epoch,
+        tx: &Transfer,
+        shielded: &Transaction,
+    ) -> Self {
+        let bundle = shielded.sapling_bundle();
+        Self {
+            height,
+            index,
+            epoch,
+            source: tx.source.clone(),
+            target: tx.target.clone(),
+            token: tx.token.clone(),
+            amount: tx.amount,
+            outputs: bundle
+                .map_or_else(Vec::new, |x| x.shielded_outputs.clone()),
+            nullifiers: bundle.map_or_else(Vec::new, |x| {
+                x.shielded_spends.iter().map(|ss| ss.nullifier).collect()
+            }),
+        }
+    }
+}
+
 /// Represents the current state of the shielded pool from the perspective of
 /// the chosen viewing keys.
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -555,6 +617,22 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         }
     }
 
+    /// Bring the saved shielded context up to date for the given keys. The
+    /// last saved context is loaded, the shielded transfers accepted since
+    /// then are scanned, and the updated context is saved so that future
+    /// syncs only have to scan the newer transfers. Failing to load or save
+    /// the context only costs rescanning, so it's not an error.
+    pub async fn sync(
+        &mut self,
+        client: &U::C,
+        sks: &[ExtendedSpendingKey],
+        fvks: &[ViewingKey],
+    ) {
+        let _ = self.load().await;
+        self.fetch(client, sks, fvks).await;
+        let _ = self.save().await;
+    }
+
     /// Fetch the current state of the multi-asset shielded pool into a
     /// ShieldedContext
     pub async fn fetch(
@@ -584,7 +662,7 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         let (txs, mut tx_iter);
         if !unknown_keys.is_empty() {
             // Load all transactions accepted until this point
-            txs = Self::fetch_shielded_transfers(client, 0).await;
+            txs = Self::fetch_compact_shielded_transfers(client, 0).await;
             tx_iter = txs.iter();
             // Do this by constructing a shielding context only for unknown keys
             let mut tx_ctx = Self {
@@ -596,9 +674,8 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
             }
             // Update this unknown shielded context until it is level with self
             while tx_ctx.last_txidx != self.last_txidx {
-                if let Some(((height, idx), (epoch, tx, stx))) = tx_iter.next()
-                {
-                    tx_ctx.scan_tx(*height, *idx, *epoch, tx, stx);
+                if let Some(tx) = tx_iter.next() {
+                    tx_ctx.scan_compact_tx(tx);
                 } else {
                     break;
                 }
@@ -608,13 +685,40 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
             self.merge(tx_ctx);
         } else {
             // Load only transactions accepted from last_txid until this point
-            txs = Self::fetch_shielded_transfers(client, self.last_txidx).await;
+            txs =
+                Self::fetch_compact_shielded_transfers(client, self.last_txidx)
+                    .await;
             tx_iter = txs.iter();
         }
         // Now that we possess the unspent notes corresponding to both old and
         // new keys up until tx_pos, proceed to scan the new transactions.
-        for ((height, idx), (epoch, tx, stx)) in &mut tx_iter {
-            self.scan_tx(*height, *idx, *epoch, tx, stx);
+        for tx in &mut tx_iter {
+            self.scan_compact_tx(tx);
+        }
+    }
+
+    /// Obtain the compact forms of the shielded transactions accepted from
+    /// the given index onwards, in the order in which they were accepted. They
+    /// are fetched in pages of [`COMPACT_TXS_PER_PAGE`] transactions, until a
+    /// page isn't full.
+    pub async fn fetch_compact_shielded_transfers(
+        client: &U::C,
+        last_txidx: u64,
+    ) -> Vec<CompactMaspTx> {
+        let mut txs = Vec::new();
+        loop {
+            let start = last_txidx + txs.len() as u64;
+            let page = rpc::query_masp_compact_txs(
+                client,
+                start,
+                COMPACT_TXS_PER_PAGE,
+            )
+            .await;
+            let is_last_page = (page.len() as u64) < COMPACT_TXS_PER_PAGE;
+            txs.extend(page);
+            if is_last_page {
+                return txs;
+            }
         }
     }
 
@@ -627,12 +731,8 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         client: &U::C,
         last_txidx: u64,
     ) -> BTreeMap<(BlockHeight, TxIndex), (Epoch, Transfer, Transaction)> {
-        // The address of the MASP account
-        let masp_addr = masp();
         // Construct the key where last transaction pointer is stored
-        let head_tx_key = Key::from(masp_addr.to_db_key())
-            .push(&HEAD_TX_KEY.to_owned())
-            .expect("Cannot obtain a storage key");
+        let head_tx_key = masp_head_tx_key();
         // Query for the index of the last accepted transaction
         let head_txidx = query_storage_value::<U::C, u64>(client, &head_tx_key)
             .await
@@ -641,9 +741,7 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         // Fetch all the transactions we do not have yet
         for i in last_txidx..head_txidx {
             // Construct the key for where the current transaction is stored
-            let current_tx_key = Key::from(masp_addr.to_db_key())
-                .push(&(TX_KEY_PREFIX.to_owned() + &i.to_string()))
-                .expect("Cannot obtain a storage key");
+            let current_tx_key = masp_tx_key(i);
             // Obtain the current transaction
             let (tx_epoch, tx_height, tx_index, current_tx, current_stx) =
                 query_storage_value::<
@@ -677,13 +775,18 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         tx: &Transfer,
         shielded: &Transaction,
     ) {
+        self.scan_compact_tx(&CompactMaspTx::new(
+            height, index, epoch, tx, shielded,
+        ))
+    }
+
+    /// Applies the compact form of a transaction to the supplied context, in
+    /// the same way as [`Self::scan_tx`] applies the full transaction.
+    pub fn scan_compact_tx(&mut self, tx: &CompactMaspTx) {
         // For tracking the account changes caused by this Transaction
         let mut transaction_delta = TransactionDelta::new();
         // Listen for notes sent to our viewing keys
-        for so in shielded
-            .sapling_bundle()
-            .map_or(&vec![], |x| &x.shielded_outputs)
-        {
+        for so in &tx.outputs {
             // Create merkle tree leaf node from note commitment
             let node = Node::new(so.cmu.to_repr());
             // Update each merkle tree in the witness map with the latest
@@ -702,7 +805,7 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
             // Let's try to see if any of our viewing keys can decrypt latest
             // note
             for (vk, notes) in self.pos_map.iter_mut() {
-                let decres = try_sapling_note_decryption::<_, OutputDescription<<<Authorized as Authorization>::SaplingAuth as masp_primitives::transaction::components::sapling::Authorization>::Proof>>(
+                let decres = try_sapling_note_decryption::<_, MaspOutput>(
                     &NETWORK,
                     1.into(),
                     &PreparedIncomingViewingKey::new(&vk.ivk()),
@@ -736,13 +839,10 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
             }
         }
         // Cancel out those of our notes that have been spent
-        for ss in shielded
-            .sapling_bundle()
-            .map_or(&vec![], |x| &x.shielded_spends)
-        {
+        for nullifier in &tx.nullifiers {
             // If the shielded spend's nullifier is in our map, then target note
             // is rendered unusable
-            if let Some(note_pos) = self.nf_map.get(&ss.nullifier) {
+            if let Some(note_pos) = self.nf_map.get(nullifier) {
                 self.spents.insert(*note_pos);
                 // Note the account changes
                 let balance = transaction_delta
@@ -756,15 +856,15 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         }
         // Record the changes to the transparent accounts
         let transparent_delta =
-            Amount::from_nonnegative(tx.token.clone(), u64::from(tx.amount))
+            Amount::from_nonnegative(tx.token.clone(), u128::from(tx.amount))
                 .expect("invalid value for amount");
         let mut transfer_delta = TransferDelta::new();
         transfer_delta
             .insert(tx.source.clone(), Amount::zero() - &transparent_delta);
         transfer_delta.insert(tx.target.clone(), transparent_delta);
         self.delta_map.insert(
-            (height, index),
-            (epoch, transfer_delta, transaction_delta),
+            (tx.height, tx.index),
+            (tx.epoch, transfer_delta, transaction_delta),
         );
         self.last_txidx += 1;
     }
@@ -1084,20 +1184,14 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
             Some(counter_owner) if counter_owner == owner.into() => {}
             _ => return Err(PinnedBalanceError::InvalidViewingKey),
         }
-        // The address of the MASP account
-        let masp_addr = masp();
         // Construct the key for where the transaction ID would be stored
-        let pin_key = Key::from(masp_addr.to_db_key())
-            .push(&(PIN_KEY_PREFIX.to_owned() + &owner.hash()))
-            .expect("Cannot obtain a storage key");
+        let pin_key = masp_pin_key(&owner.hash());
         // Obtain the transaction pointer at the key
         let txidx = rpc::query_storage_value::<U::C, u64>(client, &pin_key)
             .await
             .ok_or(PinnedBalanceError::NoTransactionPinned)?;
         // Construct the key for where the pinned transaction is stored
-        let tx_key = Key::from(masp_addr.to_db_key())
-            .push(&(TX_KEY_PREFIX.to_owned() + &txidx.to_string()))
-            .expect("Cannot obtain a storage key");
+        let tx_key = masp_tx_key(txidx);
         // Obtain the pointed to transaction
         let (tx_epoch, _tx_height, _tx_index, _tx, shielded) =
             rpc::query_storage_value::<
@@ -1208,11 +1302,57 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
     /// UTXOs are sometimes used to make transactions balanced, but it is
     /// understood that transparent account changes are effected only by the
     /// amounts and signatures specified by the containing Transfer object.
+    /// The transferred and fee amounts are the raw amounts of the arguments,
+    /// converted with the decimal places of their tokens.
     #[cfg(feature = "masp-tx-gen")]
     pub async fn gen_shielded_transfer(
         &mut self,
         client: &U::C,
         args: args::TxTransfer,
+        transfer_amount: token::Amount,
+        fee_amount: token::Amount,
+        shielded_gas: bool,
+    ) -> Result<
+        Option<(
+            Builder<(), (), ExtendedFullViewingKey, ()>,
+            Transaction,
+            SaplingMetadata,
+            Epoch,
+        )>,
+        builder::Error<std::convert::Infallible>,
+    > {
+        let spending_key = args.source.spending_key();
+        // No shielded components are needed when neither source nor
+        // destination are shielded
+        if spending_key.is_none() && args.target.payment_address().is_none() {
+            return Ok(None);
+        }
+        let spending_keys: Vec<_> =
+            spending_key.into_iter().map(|x| x.into()).collect();
+        // Update the shielded context given the spending key we possess
+        self.sync(client, &spending_keys, &[]).await;
+        self.build_shielded_transfer(
+            client,
+            args,
+            transfer_amount,
+            fee_amount,
+            shielded_gas,
+        )
+        .await
+    }
+
+    /// Make the shielded components of a Transfer object like
+    /// [`ShieldedContext::gen_shielded_transfer`], from this context as is,
+    /// without syncing it first. This allows building a transfer with the
+    /// notes left unspent by another one that was just built, after marking
+    /// them with [`ShieldedContext::mark_spent`].
+    #[cfg(feature = "masp-tx-gen")]
+    pub async fn build_shielded_transfer(
+        &mut self,
+        client: &U::C,
+        args: args::TxTransfer,
+        transfer_amount: token::Amount,
+        fee_amount: token::Amount,
         shielded_gas: bool,
     ) -> Result<
         Option<(
@@ -1223,8 +1363,6 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         )>,
         builder::Error<std::convert::Infallible>,
     > {
-        // No shielded components are needed when neither source nor destination
-        // are shielded
         let spending_key = args.source.spending_key();
         let payment_address = args.target.payment_address();
         // No shielded components are needed when neither source nor
@@ -1234,26 +1372,20 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         }
         // We want to fund our transaction solely from supplied spending key
         let spending_key = spending_key.map(|x| x.into());
-        let spending_keys: Vec<_> = spending_key.into_iter().collect();
-        // Load the current shielded context given the spending key we
-        // possess
-        let _ = self.load();
-        self.fetch(client, &spending_keys, &[]).await;
-        // Save the update state so that future fetches can be
-        // short-circuited
-        let _ = self.save();
         // Determine epoch in which to submit potential shielded transaction
         let epoch = rpc::query_epoch(client).await;
         // Context required for storing which notes are in the source's
         // possesion
-        let amt: u64 = args.amount.into();
+        let amt = transfer_amount
+            .checked_to_u64()
+            .expect("supplied amount too large");
         let memo = MemoBytes::empty();
 
         // Now we build up the transaction within this object
         let mut builder = Builder::<_, OsRng>::new(NETWORK, 1.into());
         // Convert transaction amount into MASP types
         let (asset_type, amount) =
-            convert_amount(epoch, &args.token, args.amount);
+            convert_amount(epoch, &args.token, transfer_amount);
         // The fee to be paid for the transaction
         let tx_fee;
 
@@ -1262,7 +1394,7 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
             // Transaction fees need to match the amount in the wrapper Transfer
             // when MASP source is used
             let (_, fee) =
-                convert_amount(epoch, &args.tx.fee_token, args.tx.fee_amount);
+                convert_amount(epoch, &args.tx.fee_token, fee_amount);
             tx_fee = fee.clone();
             // If the gas is coming from the shielded pool, then our shielded
             // inputs must also cover the gas fee
@@ -1399,6 +1531,20 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
             })
     }
 
+    /// Mark the notes spent by the given transaction as spent in this context,
+    /// so that they aren't spent again by a transaction built before this one
+    /// is applied. This is only kept in memory until the context is loaded
+    /// again.
+    pub fn mark_spent(&mut self, tx: &Transaction) {
+        if let Some(bundle) = tx.sapling_bundle() {
+            for spend in &bundle.shielded_spends {
+                if let Some(note_pos) = self.nf_map.get(&spend.nullifier) {
+                    self.spents.insert(*note_pos);
+                }
+            }
+        }
+    }
+
     /// Obtain the known effects of all accepted shielded and transparent
     /// transactions. If an owner is specified, then restrict the set to only
     /// transactions crediting/debiting the given owner. If token is specified,
@@ -1414,15 +1560,12 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
         (Epoch, TransferDelta, TransactionDelta),
     > {
         const TXS_PER_PAGE: u8 = 100;
-        let _ = self.load();
         let vks = viewing_keys;
         let fvks: Vec<_> = vks
             .values()
             .map(|fvk| ExtendedFullViewingKey::from(*fvk).fvk.vk)
             .collect();
-        self.fetch(client, &[], &fvks).await;
-        // Save the update state so that future fetches can be short-circuited
-        let _ = self.save();
+        self.sync(client, &[], &fvks).await;
         // Required for filtering out rejected transactions from Tendermint
         // responses
         let block_results = rpc::query_results(client).await;
@@ -1492,7 +1635,7 @@ impl<U: ShieldedUtils> ShieldedContext<U> {
                             let mut delta = TransferDelta::default();
                             let tfer_delta = Amount::from_nonnegative(
                                 transfer.token.clone(),
-                                u64::from(transfer.amount),
+                                u128::from(transfer.amount),
                             )
                             .expect("invalid value for amount");
                             delta.insert(
@@ -1552,7 +1695,7 @@ fn convert_amount(
 ) -> (AssetType, Amount) {
     let asset_type = make_asset_type(epoch, token);
     // Combine the value and unit into one amount
-    let amount = Amount::from_nonnegative(asset_type, u64::from(val))
+    let amount = Amount::from_nonnegative(asset_type, u128::from(val))
         .expect("invalid value for amount");
     (asset_type, amount)
 }
@@ -0,0 +1,175 @@
+//! The view of a node of the wrapper txs pending in its mempool.
+//!
+//! When enabled, the node records the wrapper txs that pass its mempool
+//! validation, until they are included in a block or fail to be revalidated
+//! after a block was committed. This view can be queried by explorers and
+//! users, to find out whether a tx is still queued and whether its fee must be
+//! bumped for it to be included.
+//!
+//! Exposing the mempool is unsafe for a public node, so this view is disabled
+//! by default. It can only hold `N` wrappers at a time, where `N` is a
+//! configurable parameter. If the view is holding `N` wrappers, and a new
+//! wrapper is recorded, the oldest wrapper is pruned.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::types::address::Address;
+use crate::types::hash::Hash;
+use crate::types::storage::BlockHeight;
+use crate::types::transaction::{Fee, GasLimit};
+
+/// A wrapper tx pending in the mempool of the node.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct PendingWrapper {
+    /// The hash of the wrapper tx header.
+    pub hash: Hash,
+    /// The fee offered by the wrapper.
+    pub fee: Fee,
+    /// The gas limit of the wrapper.
+    pub gas_limit: GasLimit,
+    /// The address paying the fee of the wrapper.
+    pub fee_payer: Address,
+    /// The height of the last committed block when the wrapper was last
+    /// validated by the node.
+    pub validated_at: BlockHeight,
+}
+
+/// Represents the [`PendingWrapper`] instances in the mempool of the node,
+/// which are only recorded when enabled.
+#[derive(Debug, Default)]
+pub struct MempoolView {
+    wrappers: Option<Mutex<VecDeque<PendingWrapper>>>,
+    capacity: usize,
+}
+
+impl MempoolView {
+    /// Return a new, enabled view holding at most `max_wrappers`.
+    pub fn new(max_wrappers: usize) -> Self {
+        Self {
+            wrappers: Some(Mutex::new(VecDeque::with_capacity(max_wrappers))),
+            capacity: max_wrappers,
+        }
+    }
+
+    /// Check if this view records the pending wrappers.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.wrappers.is_some()
+    }
+
+    /// Record a wrapper that passed the mempool validation, if this view is
+    /// enabled. A wrapper that was already recorded is updated.
+    pub fn insert(&self, wrapper: PendingWrapper) {
+        if let Some(wrappers) = &self.wrappers {
+            let mut wrappers = wrappers
+                .lock()
+                .expect("The mempool view should not be poisoned");
+            wrappers.retain(|pending| pending.hash != wrapper.hash);
+            if self.capacity == 0 {
+                return;
+            }
+            if wrappers.len() == self.capacity {
+                wrappers.pop_front();
+            }
+            wrappers.push_back(wrapper);
+        }
+    }
+
+    /// Remove the wrapper with the given hash, e.g. when it was included in a
+    /// block or when it failed to be revalidated.
+    pub fn remove(&self, hash: &Hash) {
+        if let Some(wrappers) = &self.wrappers {
+            wrappers
+                .lock()
+                .expect("The mempool view should not be poisoned")
+                .retain(|pending| &pending.hash != hash);
+        }
+    }
+
+    /// Return the pending wrappers whose fee is paid by the given address,
+    /// or all the pending wrappers if none is given, from the oldest one.
+    /// Returns `None` if this view is disabled.
+    pub fn pending(
+        &self,
+        fee_payer: Option<&Address>,
+    ) -> Option<Vec<PendingWrapper>> {
+        let wrappers = self
+            .wrappers
+            .as_ref()?
+            .lock()
+            .expect("The mempool view should not be poisoned");
+        Some(
+            wrappers
+                .iter()
+                .filter(|pending| {
+                    fee_payer.map_or(true, |payer| &pending.fee_payer == payer)
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::nam;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::types::token::Amount;
+
+    /// Return a mock wrapper with the given fee payer.
+    fn wrapper(id: u64, fee_payer: Address) -> PendingWrapper {
+        PendingWrapper {
+            hash: Hash::sha256(id.to_le_bytes()),
+            fee: Fee {
+                amount: Amount::from(id),
+                token: nam(),
+            },
+            gas_limit: GasLimit::from_multiplier(id),
+            fee_payer,
+            validated_at: BlockHeight(1),
+        }
+    }
+
+    /// Test that wrappers are only recorded when the view is enabled, that
+    /// they can be filtered by fee payer and that they are removed or pruned.
+    #[test]
+    fn test_pending_wrappers() {
+        let view = MempoolView::default();
+        view.insert(wrapper(1, established_address_1()));
+        assert_eq!(view.pending(None), None);
+
+        let view = MempoolView::new(3);
+        view.insert(wrapper(1, established_address_1()));
+        view.insert(wrapper(2, established_address_2()));
+        view.insert(wrapper(3, established_address_1()));
+        assert_eq!(
+            view.pending(Some(&established_address_1())),
+            Some(vec![
+                wrapper(1, established_address_1()),
+                wrapper(3, established_address_1())
+            ])
+        );
+
+        // revalidated wrappers are not duplicated
+        view.insert(wrapper(3, established_address_1()));
+        assert_eq!(view.pending(None).unwrap().len(), 3);
+
+        // included wrappers are removed
+        view.remove(&wrapper(2, established_address_2()).hash);
+        assert_eq!(view.pending(Some(&established_address_2())), Some(vec![]));
+
+        // the oldest wrappers are pruned
+        view.insert(wrapper(4, established_address_2()));
+        view.insert(wrapper(5, established_address_2()));
+        assert_eq!(
+            view.pending(Some(&established_address_1())),
+            Some(vec![wrapper(3, established_address_1())])
+        );
+    }
+}
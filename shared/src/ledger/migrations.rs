@@ -0,0 +1,106 @@
+//! The migrations of the storage layout of the ledger, which are registered
+//! here such that they can migrate the layouts of all the subsystems, see
+//! [`namada_core::ledger::migrations`].
+
+pub use namada_core::ledger::migrations::*;
+
+use crate::ledger::pos::namada_proof_of_stake;
+use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
+use crate::types::storage::BlockHeight;
+
+/// The height of the block in which the token amounts are migrated from the
+/// legacy encoding of 64-bit amounts. Existing chains upgrade at the halt
+/// height of the protocol upgrade, so it's applied in the first block that
+/// the upgraded nodes finalize.
+const LEGACY_AMOUNTS_HEIGHT: BlockHeight = BlockHeight(1);
+
+/// The migrations of the storage layout, ordered by their versions. A new
+/// step must be appended with the next version.
+pub fn all<S>() -> Vec<Migration<S>>
+where
+    S: StorageRead + StorageWrite,
+{
+    vec![Migration {
+        version: 1,
+        height: LEGACY_AMOUNTS_HEIGHT,
+        description: "re-encode the legacy 64-bit token amounts",
+        apply: migrate_all_legacy_amounts,
+    }]
+}
+
+/// Migrate the amounts of the core of the protocol and of the PoS from the
+/// legacy encoding of 64-bit amounts
+fn migrate_all_legacy_amounts<S>(storage: &mut S) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let migrated = migrate_legacy_amounts(storage)?;
+    tracing::info!("Migrated {} legacy amounts", migrated);
+    let migrated =
+        namada_proof_of_stake::migrations::migrate_legacy_amounts(storage)?;
+    tracing::info!("Migrated {} legacy amounts of the PoS", migrated);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use namada_core::ledger::storage::testing::TestWlStorage;
+    use namada_core::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+
+    use super::*;
+    use crate::ledger::pos::namada_proof_of_stake::types::Position;
+    use crate::ledger::pos::namada_proof_of_stake::{
+        bond_handle, consensus_validator_set_handle, unbond_handle,
+    };
+    use crate::types::address;
+    use crate::types::storage::{DbKeySeg, Epoch, KeySeg};
+    use crate::types::token::{self, Amount};
+
+    /// Test that the registered migrations re-encode the legacy amounts of a
+    /// pre-upgrade storage, with a PoS bond and a non-native balance
+    #[test]
+    fn test_migrate_legacy_amounts() {
+        let mut storage = TestWlStorage::default();
+        let delegator = established_address_1();
+        let validator = established_address_2();
+        let legacy = 42_u64.try_to_vec().unwrap();
+
+        let balance_key = token::balance_key(&address::btc(), &delegator);
+        storage.write_bytes(&balance_key, &legacy).unwrap();
+
+        // The bonds are changes, whose encoding is unchanged
+        let bond = bond_handle(&delegator, &validator).get_data_handler();
+        bond.insert(&mut storage, Epoch(0), 42).unwrap();
+        let unbond = unbond_handle(&delegator, &validator).at(&Epoch(2));
+        let unbond_key = unbond.get_data_key(&Epoch(0));
+        storage.write_bytes(&unbond_key, &legacy).unwrap();
+
+        // The consensus validator set keyed by the legacy stake
+        let set = consensus_validator_set_handle().at(&Epoch(0));
+        let stake = Amount::from(42);
+        let mut legacy_set_key = set.at(&stake).get_data_key(&Position(0));
+        legacy_set_key.segments[7] = DbKeySeg::StringSeg(42_u64.raw());
+        storage
+            .write_bytes(&legacy_set_key, validator.try_to_vec().unwrap())
+            .unwrap();
+
+        let migrations = all();
+        let applied =
+            run(&mut storage, &migrations, LEGACY_AMOUNTS_HEIGHT).unwrap();
+        assert_eq!(applied, vec![1]);
+
+        let bytes = storage.read_bytes(&balance_key).unwrap().unwrap();
+        assert_eq!(Amount::try_from_slice(&bytes).unwrap(), stake);
+        assert_eq!(bond.get(&storage, &Epoch(0)).unwrap(), Some(42));
+        assert_eq!(unbond.get(&storage, &Epoch(0)).unwrap(), Some(stake));
+        assert!(!storage.has_key(&legacy_set_key).unwrap());
+        let validators = set.at(&stake);
+        assert_eq!(
+            validators.get(&storage, &Position(0)).unwrap(),
+            Some(validator)
+        );
+    }
+}
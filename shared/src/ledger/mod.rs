@@ -1,11 +1,14 @@
 //! The ledger modules
 
+pub mod alloc_log;
 pub mod args;
 pub mod eth_bridge;
 pub mod events;
 pub mod ibc;
 pub mod inflation;
 pub mod masp;
+pub mod mempool_view;
+pub mod migrations;
 pub mod native_vp;
 pub mod pos;
 #[cfg(all(feature = "wasm-runtime", feature = "ferveo-tpke"))]
@@ -19,5 +22,6 @@ pub mod vp_host_fns;
 pub mod wallet;
 
 pub use namada_core::ledger::{
-    gas, governance, parameters, replay_protection, storage_api, tx_env, vp_env,
+    gas, governance, oracle, parameters, replay_protection, storage_api,
+    tx_env, vp_env,
 };
@@ -7,7 +7,7 @@ use std::collections::BTreeSet;
 use namada_core::ledger::governance::storage as gov_storage;
 use namada_core::ledger::storage;
 use namada_core::ledger::vp_env::VpEnv;
-use namada_core::types::governance::{ProposalVote, VoteType};
+use namada_core::types::governance::{ParameterChange, ProposalVote, VoteType};
 use namada_core::types::transaction::governance::ProposalType;
 use thiserror::Error;
 use utils::is_valid_validator_voting_period;
@@ -250,7 +250,9 @@ where
                 }
 
                 match proposal_type {
-                    ProposalType::Default(_) | ProposalType::PGFCouncil => {
+                    ProposalType::Default(_)
+                    | ProposalType::PGFCouncil
+                    | ProposalType::ParameterChange(_) => {
                         if self
                             .is_validator(
                                 pre_voting_start_epoch,
@@ -317,10 +319,18 @@ where
     /// Validate the proposal type
     pub fn is_valid_proposal_type(&self, proposal_id: u64) -> Result<bool> {
         let proposal_type_key = gov_storage::get_proposal_type_key(proposal_id);
-        Ok(self
-            .ctx
-            .read_post::<ProposalType>(&proposal_type_key)?
-            .is_some())
+        let proposal_type: Option<ProposalType> =
+            self.ctx.read_post(&proposal_type_key)?;
+
+        match proposal_type {
+            // A parameter change proposal must change at least a parameter,
+            // each to a valid value
+            Some(ProposalType::ParameterChange(changes)) => Ok(!changes
+                .is_empty()
+                && changes.iter().all(ParameterChange::is_valid)),
+            Some(_) => Ok(true),
+            None => Ok(false),
+        }
     }
 
     /// Validate a proposal code
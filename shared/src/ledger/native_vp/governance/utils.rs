@@ -15,6 +15,7 @@ use crate::ledger::governance::storage as gov_storage;
 use crate::ledger::pos::BondId;
 use crate::ledger::storage_api;
 use crate::types::address::Address;
+use crate::types::event::{self, TypedEvent};
 use crate::types::governance::{
     ProposalVote, Tally, TallyResult, VotePower, VoteType,
 };
@@ -26,7 +27,10 @@ use crate::types::token;
 pub struct Votes {
     /// Map from validators who votes yay to their total stake amount
     pub yay_validators: HashMap<Address, (VotePower, ProposalVote)>,
-    /// Map from delegation votes to their bond amount
+    /// Map from validators who votes nay to their total stake amount
+    pub nay_validators: HashMap<Address, VotePower>,
+    /// Map from delegators to their votes, each with the amount bonded to
+    /// the validator whose vote it overrides
     pub delegators:
         HashMap<Address, HashMap<Address, (VotePower, ProposalVote)>>,
 }
@@ -62,21 +66,15 @@ impl ProposalEvent {
         has_proposal_code: bool,
         proposal_code_exit_status: bool,
     ) -> Self {
-        let attributes = HashMap::from([
-            ("tally_result".to_string(), tally.to_string()),
-            ("proposal_id".to_string(), id.to_string()),
-            (
-                "has_proposal_code".to_string(),
-                (!has_proposal_code as u64).to_string(),
-            ),
-            (
-                "proposal_code_exit_status".to_string(),
-                (!proposal_code_exit_status as u64).to_string(),
-            ),
-        ]);
+        let result = event::ProposalResult {
+            proposal_id: id,
+            tally_result: tally.to_string(),
+            has_proposal_code,
+            proposal_code_exit_status,
+        };
         Self {
             event_type,
-            attributes,
+            attributes: result.attributes().into_iter().collect(),
         }
     }
 }
@@ -89,12 +87,17 @@ pub fn compute_tally(
 ) -> Result<ProposalResult, Error> {
     let Votes {
         yay_validators,
+        nay_validators,
         delegators,
     } = votes;
 
     match proposal_type {
-        ProposalType::Default(_) | ProposalType::ETHBridge => {
+        ProposalType::Default(_)
+        | ProposalType::ETHBridge
+        | ProposalType::ParameterChange(_) => {
             let mut total_yay_staked_tokens = VotePower::default();
+            let mut total_nay_staked_tokens: VotePower =
+                nay_validators.values().sum();
 
             for (_, (amount, validator_vote)) in yay_validators.iter() {
                 if let ProposalVote::Yay(vote_type) = validator_vote {
@@ -132,6 +135,11 @@ pub fn compute_tally(
                                 // didn't vote / voted nay
                                 total_yay_staked_tokens += vote_power;
                             }
+                            if nay_validators.contains_key(validator_address) {
+                                // The delegator amount no longer counts
+                                // towards the nay of its validator
+                                total_nay_staked_tokens -= vote_power;
+                            }
                         }
                         ProposalVote::Nay => {
                             // NAY: Remove delegator amount whose validator
@@ -140,6 +148,11 @@ pub fn compute_tally(
                             if yay_validators.contains_key(validator_address) {
                                 total_yay_staked_tokens -= vote_power;
                             }
+                            if !nay_validators.contains_key(validator_address) {
+                                // Add delegator amount whose validator didn't
+                                // vote / voted yay
+                                total_nay_staked_tokens += vote_power;
+                            }
                         }
 
                         _ => {
@@ -164,6 +177,9 @@ pub fn compute_tally(
                     ProposalType::ETHBridge => {
                         TallyResult::Passed(Tally::ETHBridge)
                     }
+                    ProposalType::ParameterChange(_) => {
+                        TallyResult::Passed(Tally::ParameterChange)
+                    }
                     _ => {
                         return Err(Error::Tally(format!(
                             "Unexpected proposal type: {}",
@@ -176,14 +192,14 @@ pub fn compute_tally(
                     result: tally_result,
                     total_voting_power: total_stake,
                     total_yay_power: total_yay_staked_tokens,
-                    total_nay_power: 0,
+                    total_nay_power: total_nay_staked_tokens,
                 })
             } else {
                 Ok(ProposalResult {
                     result: TallyResult::Rejected,
                     total_voting_power: total_stake,
                     total_yay_power: total_yay_staked_tokens,
-                    total_nay_power: 0,
+                    total_nay_power: total_nay_staked_tokens,
                 })
             }
         }
@@ -278,43 +294,37 @@ pub fn compute_tally(
                             }
                         }
                         ProposalVote::Nay => {
-                            for (
-                                validator_address,
-                                (vote_power, _delegator_vote),
-                            ) in vote_map.iter()
+                            // NAY: Remove delegator amount from the councils
+                            // its validator voted for
+                            if let Some((_, validator_vote)) =
+                                yay_validators.get(validator_address)
                             {
-                                if let Some((_, validator_vote)) =
-                                    yay_validators.get(validator_address)
+                                if let ProposalVote::Yay(
+                                    VoteType::PGFCouncil(votes),
+                                ) = validator_vote
                                 {
-                                    if let ProposalVote::Yay(
-                                        VoteType::PGFCouncil(votes),
-                                    ) = validator_vote
-                                    {
-                                        for vote in votes {
-                                            if let Some(power) =
-                                                total_yay_staked_tokens
-                                                    .get_mut(vote)
-                                            {
-                                                *power -= vote_power;
-                                            } else {
-                                                return Err(Error::Tally(
-                                                    format!(
-                                                        "Expected PGF vote \
-                                                         {:?} was not in tally",
-                                                        vote
-                                                    ),
-                                                ));
-                                            }
+                                    for vote in votes {
+                                        if let Some(power) =
+                                            total_yay_staked_tokens
+                                                .get_mut(vote)
+                                        {
+                                            *power -= vote_power;
+                                        } else {
+                                            return Err(Error::Tally(format!(
+                                                "Expected PGF vote {:?} was \
+                                                 not in tally",
+                                                vote
+                                            )));
                                         }
-                                    } else {
-                                        // Log the error and continue
-                                        tracing::error!(
-                                            "Unexpected vote type. Expected: \
-                                             PGFCouncil, Found: {}",
-                                            validator_vote
-                                        );
-                                        continue;
                                     }
+                                } else {
+                                    // Log the error and continue
+                                    tracing::error!(
+                                        "Unexpected vote type. Expected: \
+                                         PGFCouncil, Found: {}",
+                                        validator_vote
+                                    );
+                                    continue;
                                 }
                             }
                         }
@@ -386,6 +396,7 @@ where
         storage_api::iter_prefix::<ProposalVote>(storage, &vote_prefix_key)?;
 
     let mut yay_validators = HashMap::new();
+    let mut nay_validators = HashMap::new();
     let mut delegators: HashMap<
         Address,
         HashMap<Address, (VotePower, ProposalVote)>,
@@ -396,7 +407,7 @@ where
         let voter_address = gov_storage::get_voter_address(&vote_key);
         match voter_address {
             Some(voter_address) => {
-                if vote.is_yay() && validators.contains(voter_address) {
+                if validators.contains(voter_address) {
                     let amount: VotePower = read_validator_stake(
                         storage,
                         &params,
@@ -406,8 +417,12 @@ where
                     .unwrap_or_default()
                     .into();
 
-                    yay_validators
-                        .insert(voter_address.clone(), (amount, vote));
+                    if vote.is_yay() {
+                        yay_validators
+                            .insert(voter_address.clone(), (amount, vote));
+                    } else {
+                        nay_validators.insert(voter_address.clone(), amount);
+                    }
                 } else if !validators.contains(voter_address) {
                     let validator_address =
                         gov_storage::get_vote_delegation_address(&vote_key);
@@ -440,6 +455,7 @@ where
 
     Ok(Votes {
         yay_validators,
+        nay_validators,
         delegators,
     })
 }
@@ -473,3 +489,132 @@ where
         None => Ok(false),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+        established_address_4,
+    };
+
+    const TOTAL_STAKE: VotePower = 1_000;
+
+    /// The votes of three validators with a stake of 400, 300 and 300, of
+    /// which the first and second vote as given and the third doesn't vote
+    fn validators_votes(first_yay: bool, second_yay: bool) -> Votes {
+        let mut yay_validators = HashMap::new();
+        let mut nay_validators = HashMap::new();
+        for (validator, stake, is_yay) in [
+            (established_address_1(), 400, first_yay),
+            (established_address_2(), 300, second_yay),
+        ] {
+            if is_yay {
+                yay_validators.insert(
+                    validator,
+                    (stake, ProposalVote::Yay(VoteType::Default)),
+                );
+            } else {
+                nay_validators.insert(validator, stake);
+            }
+        }
+        Votes {
+            yay_validators,
+            nay_validators,
+            delegators: HashMap::new(),
+        }
+    }
+
+    /// Add the vote of the delegator of the bonded amount to the validator
+    fn add_delegator_vote(
+        votes: &mut Votes,
+        validator: Address,
+        amount: VotePower,
+        vote: ProposalVote,
+    ) {
+        votes
+            .delegators
+            .entry(established_address_4())
+            .or_default()
+            .insert(validator, (amount, vote));
+    }
+
+    fn tally(votes: Votes) -> ProposalResult {
+        compute_tally(votes, TOTAL_STAKE, &ProposalType::Default(None)).unwrap()
+    }
+
+    #[test]
+    fn test_tally_validators_only() {
+        let result = tally(validators_votes(true, true));
+        assert!(matches!(result.result, TallyResult::Passed(Tally::Default)));
+        assert_eq!(result.total_yay_power, 700);
+        assert_eq!(result.total_nay_power, 0);
+
+        let result = tally(validators_votes(true, false));
+        assert!(matches!(result.result, TallyResult::Rejected));
+        assert_eq!(result.total_yay_power, 400);
+        assert_eq!(result.total_nay_power, 300);
+    }
+
+    /// A delegator voting nay withdraws its bond from the yay of its
+    /// validator, which can make the proposal fail
+    #[test]
+    fn test_tally_delegator_overrides_yay() {
+        let mut votes = validators_votes(true, true);
+        add_delegator_vote(
+            &mut votes,
+            established_address_1(),
+            100,
+            ProposalVote::Nay,
+        );
+        let result = tally(votes);
+        assert!(matches!(result.result, TallyResult::Rejected));
+        assert_eq!(result.total_yay_power, 600);
+        assert_eq!(result.total_nay_power, 100);
+    }
+
+    /// A delegator voting yay withdraws its bond from the nay of its
+    /// validator, which can make the proposal pass
+    #[test]
+    fn test_tally_delegator_overrides_nay() {
+        let mut votes = validators_votes(true, false);
+        add_delegator_vote(
+            &mut votes,
+            established_address_2(),
+            300,
+            ProposalVote::Yay(VoteType::Default),
+        );
+        let result = tally(votes);
+        assert!(matches!(result.result, TallyResult::Passed(Tally::Default)));
+        assert_eq!(result.total_yay_power, 700);
+        assert_eq!(result.total_nay_power, 0);
+    }
+
+    /// A delegator of a validator who didn't vote counts on its own, whether
+    /// it agrees with the others or not
+    #[test]
+    fn test_tally_delegator_of_absent_validator() {
+        let mut votes = validators_votes(true, true);
+        add_delegator_vote(
+            &mut votes,
+            established_address_3(),
+            50,
+            ProposalVote::Nay,
+        );
+        let result = tally(votes);
+        assert_eq!(result.total_yay_power, 700);
+        assert_eq!(result.total_nay_power, 50);
+
+        let mut votes = validators_votes(true, false);
+        add_delegator_vote(
+            &mut votes,
+            established_address_3(),
+            300,
+            ProposalVote::Yay(VoteType::Default),
+        );
+        let result = tally(votes);
+        assert!(matches!(result.result, TallyResult::Passed(Tally::Default)));
+        assert_eq!(result.total_yay_power, 700);
+        assert_eq!(result.total_nay_power, 300);
+    }
+}
@@ -28,10 +28,11 @@ pub const SLASH_POOL_ADDRESS: Address =
 /// from the number of tokens
 pub fn into_tm_voting_power(
     votes_per_token: Decimal,
-    tokens: impl Into<u64>,
+    tokens: token::Amount,
 ) -> i64 {
-    let prod = decimal_mult_u64(votes_per_token, tokens.into());
-    i64::try_from(prod).expect("Invalid validator voting power (i64)")
+    let prod = decimal_mult_amount(votes_per_token, tokens);
+    i64::try_from(u128::from(prod))
+        .expect("Invalid validator voting power (i64)")
 }
 
 /// Initialize storage in the genesis block.
@@ -5,9 +5,11 @@ use std::panic;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use thiserror::Error;
 
+use crate::ledger::eth_bridge::bridge_pool_vp::BridgePoolVp;
+use crate::ledger::eth_bridge::erc20_vp::Erc20Vp;
 use crate::ledger::eth_bridge::vp::EthBridge;
-use crate::ledger::gas::{self, BlockGasMeter, VpGasMeter};
-use crate::ledger::ibc::vp::{Ibc, IbcToken};
+use crate::ledger::gas::{self, BlockGasMeter, GasCostTable, VpGasMeter};
+use crate::ledger::ibc::vp::{Ibc, IbcAccount, IbcToken};
 use crate::ledger::native_vp::governance::GovernanceVp;
 use crate::ledger::native_vp::parameters::{self, ParametersVp};
 use crate::ledger::native_vp::replay_protection::ReplayProtectionVp;
@@ -54,12 +56,18 @@ pub enum Error {
     ParametersNativeVpError(parameters::Error),
     #[error("IBC Token native VP: {0}")]
     IbcTokenNativeVpError(crate::ledger::ibc::vp::IbcTokenError),
+    #[error("IBC account native VP: {0}")]
+    IbcAccountNativeVpError(crate::ledger::ibc::vp::IbcAccountError),
     #[error("Governance native VP error: {0}")]
     GovernanceNativeVpError(crate::ledger::native_vp::governance::Error),
     #[error("SlashFund native VP error: {0}")]
     SlashFundNativeVpError(crate::ledger::native_vp::slash_fund::Error),
     #[error("Ethereum bridge native VP error: {0}")]
     EthBridgeNativeVpError(crate::ledger::eth_bridge::vp::Error),
+    #[error("Ethereum bridge pool native VP error: {0}")]
+    BridgePoolNativeVpError(crate::ledger::eth_bridge::bridge_pool_vp::Error),
+    #[error("Wrapped ERC20 native VP error: {0}")]
+    Erc20NativeVpError(crate::ledger::eth_bridge::erc20_vp::Error),
     #[error("Replay protection native VP error: {0}")]
     ReplayProtectionNativeVpError(
         crate::ledger::native_vp::replay_protection::Error,
@@ -80,7 +88,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// but no further validations.
 #[allow(clippy::too_many_arguments)]
 pub fn apply_tx<D, H, CA>(
-    tx: Tx,
+    mut tx: Tx,
     tx_length: usize,
     tx_index: TxIndex,
     block_gas_meter: &mut BlockGasMeter,
@@ -104,6 +112,33 @@ where
             #[cfg(not(feature = "mainnet"))]
             has_valid_pow,
         }) => {
+            let signatures = tx
+                .sections
+                .iter()
+                .filter(|section| {
+                    matches!(section, proto::Section::Signature(_))
+                })
+                .count();
+            block_gas_meter
+                .add_signature_verification_fee(signatures)
+                .map_err(Error::GasError)?;
+            // The VM only supports decompressed sections
+            tx.decompress().map_err(Error::TxDecodingError)?;
+
+            if tx.is_batch() {
+                return apply_batch(
+                    &tx,
+                    &tx_index,
+                    storage,
+                    block_gas_meter,
+                    write_log,
+                    vp_wasm_cache,
+                    tx_wasm_cache,
+                    #[cfg(not(feature = "mainnet"))]
+                    has_valid_pow,
+                );
+            }
+
             let verifiers = execute_tx(
                 &tx,
                 &tx_index,
@@ -153,6 +188,87 @@ where
     }
 }
 
+/// Apply the inner txs of a batch in order. The batch is atomic: its storage
+/// modifications are only kept if all of its inner txs are accepted by the
+/// VPs, each of which only validates the modifications of its own inner tx.
+#[allow(clippy::too_many_arguments)]
+fn apply_batch<D, H, CA>(
+    tx: &Tx,
+    tx_index: &TxIndex,
+    storage: &Storage<D, H>,
+    block_gas_meter: &mut BlockGasMeter,
+    write_log: &mut WriteLog,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+    #[cfg(not(feature = "mainnet"))]
+    // This is true when the wrapper of this tx contained a valid
+    // `testnet_pow::Solution`
+    has_valid_pow: bool,
+) -> Result<TxResult>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    // Restored if any of the inner txs fails
+    let snapshot = write_log.clone();
+    let mut result = TxResult::default();
+    for inner_tx in tx.inner_txs() {
+        let vps_result = execute_tx(
+            &inner_tx,
+            tx_index,
+            storage,
+            block_gas_meter,
+            write_log,
+            vp_wasm_cache,
+            tx_wasm_cache,
+        )
+        .and_then(|verifiers| {
+            check_vps(
+                &inner_tx,
+                tx_index,
+                storage,
+                block_gas_meter,
+                write_log,
+                &verifiers,
+                vp_wasm_cache,
+                #[cfg(not(feature = "mainnet"))]
+                has_valid_pow,
+            )
+        });
+        let vps_result = match vps_result {
+            Ok(vps_result) => vps_result,
+            Err(err) => {
+                *write_log = snapshot;
+                return Err(err);
+            }
+        };
+        result
+            .vps_result
+            .accepted_vps
+            .extend(vps_result.accepted_vps);
+        result.vps_result.errors.extend(vps_result.errors);
+        if !vps_result.rejected_vps.is_empty() {
+            result.vps_result.rejected_vps = vps_result.rejected_vps;
+            *write_log = snapshot;
+            break;
+        }
+        result
+            .initialized_accounts
+            .extend(write_log.get_initialized_accounts());
+        result.changed_keys.extend(write_log.get_keys());
+        result.ibc_events.extend(write_log.take_ibc_events());
+        // The VPs triggered by the next inner tx must not validate the
+        // modifications of this one
+        write_log.commit_tx();
+    }
+
+    result.gas_used = block_gas_meter
+        .finalize_transaction()
+        .map_err(Error::GasError)?;
+    Ok(result)
+}
+
 /// Execute a transaction code. Returns verifiers requested by the transaction.
 fn execute_tx<D, H, CA>(
     tx: &Tx,
@@ -213,6 +329,7 @@ where
         storage,
         write_log,
         initial_gas,
+        gas_meter.gas_costs(),
         vp_wasm_cache,
         #[cfg(not(feature = "mainnet"))]
         has_valid_pow,
@@ -236,6 +353,7 @@ fn execute_vps<D, H, CA>(
     storage: &Storage<D, H>,
     write_log: &WriteLog,
     initial_gas: u64,
+    gas_costs: &GasCostTable,
     vp_wasm_cache: &mut VpCache<CA>,
     #[cfg(not(feature = "mainnet"))]
     // This is true when the wrapper of this tx contained a valid
@@ -250,7 +368,8 @@ where
     verifiers
         .par_iter()
         .try_fold(VpsResult::default, |mut result, addr| {
-            let mut gas_meter = VpGasMeter::new(initial_gas);
+            let mut gas_meter =
+                VpGasMeter::new(initial_gas).with_gas_costs(*gas_costs);
             let accept = match &addr {
                 Address::Implicit(_) | Address::Established(_) => {
                     let (vp_hash, gas) = storage
@@ -379,6 +498,16 @@ where
                             gas_meter = ibc_token.ctx.gas_meter.into_inner();
                             result
                         }
+                        InternalAddress::IbcAccount(_)
+                        | InternalAddress::IbcForward
+                        | InternalAddress::IbcFeeEscrow => {
+                            let ibc_account = IbcAccount { ctx };
+                            let result = ibc_account
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::IbcAccountNativeVpError);
+                            gas_meter = ibc_account.ctx.gas_meter.into_inner();
+                            result
+                        }
                         InternalAddress::EthBridge => {
                             let bridge = EthBridge { ctx };
                             let result = bridge
@@ -387,6 +516,22 @@ where
                             gas_meter = bridge.ctx.gas_meter.into_inner();
                             result
                         }
+                        InternalAddress::EthBridgePool => {
+                            let bridge_pool = BridgePoolVp { ctx };
+                            let result = bridge_pool
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::BridgePoolNativeVpError);
+                            gas_meter = bridge_pool.ctx.gas_meter.into_inner();
+                            result
+                        }
+                        InternalAddress::Erc20(_) => {
+                            let erc20 = Erc20Vp { ctx };
+                            let result = erc20
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::Erc20NativeVpError);
+                            gas_meter = erc20.ctx.gas_meter.into_inner();
+                            result
+                        }
                         InternalAddress::ReplayProtection => {
                             let replay_protection_vp =
                                 ReplayProtectionVp { ctx };
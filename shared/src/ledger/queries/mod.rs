@@ -2,8 +2,8 @@
 //! defined via `router!` macro.
 
 // Re-export to show in rustdoc!
-pub use shell::Shell;
 use shell::SHELL;
+pub use shell::{DryRunEffects, Shell};
 #[cfg(any(test, feature = "async-client"))]
 pub use types::Client;
 pub use types::{
@@ -97,7 +97,9 @@ mod testing {
     use tendermint_rpc::Response;
 
     use super::*;
+    use crate::ledger::alloc_log::AllocReplayLog;
     use crate::ledger::events::log::EventLog;
+    use crate::ledger::mempool_view::MempoolView;
     use crate::ledger::storage::testing::TestWlStorage;
     use crate::types::storage::BlockHeight;
     use crate::vm::wasm::{self, TxCache, VpCache};
@@ -114,6 +116,10 @@ mod testing {
         pub wl_storage: TestWlStorage,
         /// event log
         pub event_log: EventLog,
+        /// allocator replay log
+        pub alloc_replay_log: AllocReplayLog,
+        /// mempool view
+        pub mempool_view: MempoolView,
         /// VP wasm compilation cache
         pub vp_wasm_cache: VpCache<WasmCacheRoAccess>,
         /// tx wasm compilation cache
@@ -134,6 +140,8 @@ mod testing {
             // Initialize the `TestClient`
             let wl_storage = TestWlStorage::default();
             let event_log = EventLog::default();
+            let alloc_replay_log = AllocReplayLog::default();
+            let mempool_view = MempoolView::default();
             let (vp_wasm_cache, vp_cache_dir) =
                 wasm::compilation_cache::common::testing::cache();
             let (tx_wasm_cache, tx_cache_dir) =
@@ -142,6 +150,8 @@ mod testing {
                 rpc,
                 wl_storage,
                 event_log,
+                alloc_replay_log,
+                mempool_view,
                 vp_wasm_cache: vp_wasm_cache.read_only(),
                 tx_wasm_cache: tx_wasm_cache.read_only(),
                 vp_cache_dir,
@@ -177,9 +187,12 @@ mod testing {
             let ctx = RequestCtx {
                 wl_storage: &self.wl_storage,
                 event_log: &self.event_log,
+                alloc_replay_log: &self.alloc_replay_log,
+                mempool_view: &self.mempool_view,
                 vp_wasm_cache: self.vp_wasm_cache.clone(),
                 tx_wasm_cache: self.tx_wasm_cache.clone(),
                 storage_read_past_height_limit: None,
+                mempool_min_gas_price: Default::default(),
             };
             let response = self.rpc.handle(ctx, &request).unwrap();
             Ok(response)
@@ -1007,10 +1007,13 @@ mod test {
         };
         let ctx = RequestCtx {
             event_log: &client.event_log,
+            alloc_replay_log: &client.alloc_replay_log,
+            mempool_view: &client.mempool_view,
             wl_storage: &client.wl_storage,
             vp_wasm_cache: client.vp_wasm_cache.clone(),
             tx_wasm_cache: client.tx_wasm_cache.clone(),
             storage_read_past_height_limit: None,
+            mempool_min_gas_price: Default::default(),
         };
         let result = TEST_RPC.handle(ctx, &request);
         assert!(result.is_err());
@@ -2,23 +2,36 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::sapling::Node;
+use masp_primitives::transaction::Transaction;
+use namada_core::ledger::epoch_clock::EpochClock;
 use namada_core::ledger::storage::LastBlock;
 use namada_core::types::address::Address;
 use namada_core::types::hash::Hash;
 use namada_core::types::storage::{BlockResults, KeySeg};
 
+use crate::ledger::alloc_log::AllocDecision;
 use crate::ledger::events::log::dumb_queries;
 use crate::ledger::events::Event;
+use crate::ledger::masp::CompactMaspTx;
+use crate::ledger::mempool_view::PendingWrapper;
+use crate::ledger::parameters;
 use crate::ledger::queries::types::{RequestCtx, RequestQuery};
 use crate::ledger::queries::{require_latest_height, EncodedResponseQuery};
+use crate::ledger::replay_protection;
 use crate::ledger::storage::traits::StorageHasher;
+#[cfg(all(feature = "wasm-runtime", feature = "ferveo-tpke"))]
+use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{DBIter, DB};
-use crate::ledger::storage_api::{self, ResultExt, StorageRead};
+use crate::ledger::storage_api::{self, OptionExt, ResultExt, StorageRead};
 use crate::tendermint::merkle::proof::Proof;
 use crate::types::storage::{self, Epoch, PrefixValue};
-#[cfg(any(test, feature = "async-client"))]
+use crate::types::time::{DateTimeUtc, DurationSecs};
+use crate::types::token;
 use crate::types::transaction::TxResult;
 
+/// The maximum number of compact shielded transfers returned by a query
+pub const MAX_COMPACT_MASP_TXS: u64 = 1000;
+
 type Conversion = (
     Address,
     Epoch,
@@ -26,6 +39,21 @@ type Conversion = (
     MerklePath<Node>,
 );
 
+/// The effects that a tx would have if it was applied on top of the last
+/// committed block
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct DryRunEffects {
+    /// The height of the committed block on top of which the tx was run
+    pub height: storage::BlockHeight,
+    /// The result of the tx, with the gas it used
+    pub result: TxResult,
+    /// The storage changes of the tx, with the new values or `None` for the
+    /// deleted keys
+    pub changes: Vec<(storage::Key, Option<Vec<u8>>)>,
+    /// The events that the tx would emit
+    pub events: Vec<Event>,
+}
+
 router! {SHELL,
     // Epoch of the last committed block
     ( "epoch" ) -> Epoch = epoch,
@@ -33,13 +61,44 @@ router! {SHELL,
     // Query the last committed block
     ( "last_block" ) -> Option<LastBlock> = last_block,
 
+    // The state of the epochs at the last committed block, from which the
+    // upcoming epochs are estimated
+    ( "epoch_clock" ) -> Option<EpochClock> = epoch_clock,
+
+    // The estimated number of seconds from now until the start of the given
+    // epoch, which is zero if it's not an upcoming epoch
+    ( "seconds_until_epoch" / [epoch: Epoch] )
+        -> Option<u64> = seconds_until_epoch,
+
+    // The minimum gas price accepted by the node's mempool
+    ( "mempool_min_gas_price" ) -> token::Amount = mempool_min_gas_price,
+
+    // The minimum gas price of the chain, below which wrapper txs are invalid
+    ( "min_gas_price" ) -> token::Amount = min_gas_price,
+
+    // The nonce that the next wrapper paid by the given owner must carry
+    ( "nonce" / [owner: Address] ) -> u64 = next_nonce,
+
+    // The metadata of the given token, if any
+    ( "token_metadata" / [token: Address] )
+        -> Option<token::TokenMetadata> = token_metadata,
+
     // Raw storage access - read value
     ( "value" / [storage_key: storage::Key] )
         -> Vec<u8> = (with_options storage_value),
 
+    // Raw storage access - read value at a committed height, if any
+    ( "value_at_height" / [height: storage::BlockHeight]
+        / [storage_key: storage::Key] )
+        -> Option<Vec<u8>> = storage_value_at_height,
+
     // Dry run a transaction
     ( "dry_run_tx" ) -> TxResult = (with_options dry_run_tx),
 
+    // Dry run a transaction and get all of its effects
+    ( "dry_run_tx_effects" ) -> DryRunEffects
+        = (with_options dry_run_tx_effects),
+
     // Raw storage access - prefix iterator
     ( "prefix" / [storage_key: storage::Key] )
         -> Vec<PrefixValue> = (with_options storage_prefix),
@@ -54,12 +113,29 @@ router! {SHELL,
     // Conversion state access - read conversion
     ( "conv" / [asset_type: AssetType] ) -> Conversion = read_conversion,
 
+    // The compact forms of the accepted shielded transfers from the given
+    // index, at most the given limit of them, for the scanning of the notes
+    ( "masp_compact_txs" / [start: u64] / [limit: u64] )
+        -> Vec<CompactMaspTx> = masp_compact_txs,
+
     // was the transaction accepted?
     ( "accepted" / [tx_hash: Hash] ) -> Option<Event> = accepted,
 
     // was the transaction applied?
     ( "applied" / [tx_hash: Hash] ) -> Option<Event> = applied,
 
+    // Block space allocation decisions taken by the node at some height
+    ( "alloc_replay_log" / [height: storage::BlockHeight] )
+        -> Vec<AllocDecision> = alloc_replay_log,
+
+    // The wrapper txs pending in the mempool of the node, if it exposes them
+    ( "pending_wrappers" ) -> Option<Vec<PendingWrapper>> = pending_wrappers,
+
+    // The wrapper txs pending in the mempool of the node whose fee is paid by
+    // the given address, if it exposes them
+    ( "pending_wrappers_of" / [fee_payer: Address] )
+        -> Option<Vec<PendingWrapper>> = pending_wrappers_of,
+
 }
 
 // Handlers:
@@ -69,30 +145,139 @@ fn dry_run_tx<D, H>(
     mut ctx: RequestCtx<'_, D, H>,
     request: &RequestQuery,
 ) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let (data, _write_log) = apply_dry_run_tx(&mut ctx, &request.data)?;
+    let data = data.try_to_vec().into_storage_result()?;
+    Ok(EncodedResponseQuery {
+        data,
+        proof: None,
+        info: Default::default(),
+    })
+}
+
+/// Dry run a tx on top of the last committed block. The tx can only be run
+/// at the last committed height, as the VM reads the latest state of the
+/// storage.
+#[cfg(all(feature = "wasm-runtime", feature = "ferveo-tpke"))]
+fn dry_run_tx_effects<D, H>(
+    mut ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    use crate::ledger::events::{EventLevel, EventType};
+    use crate::ledger::storage::write_log::StorageModification;
+    use crate::proto::Tx;
+    use crate::types::event::{IbcPacket, TxApplied};
+    use crate::types::transaction::{ResultCode, TxResultDetails, TxType};
+
+    require_latest_height(&ctx, request)?;
+    let hash = Tx::try_from(&request.data[..])
+        .into_storage_result()?
+        .update_header(TxType::Raw)
+        .header_hash()
+        .to_string();
+    let (result, write_log) = apply_dry_run_tx(&mut ctx, &request.data)?;
+    let height = ctx.wl_storage.storage.get_last_block_height();
+
+    let changes = write_log
+        .get_keys()
+        .into_iter()
+        .filter_map(|key| {
+            let value = match write_log.read(&key).0? {
+                StorageModification::Write { value } => Some(value.clone()),
+                StorageModification::Delete => None,
+                StorageModification::InitAccount { vp_code_hash } => {
+                    Some(vp_code_hash.0.to_vec())
+                }
+                // Temporary values are never written to the storage
+                StorageModification::Temp { .. } => return None,
+            };
+            Some((key, value))
+        })
+        .collect();
+
+    let code = if result.is_accepted() {
+        ResultCode::Ok
+    } else {
+        ResultCode::InvalidTx
+    };
+    let mut tx_event = Event {
+        event_type: EventType::Applied,
+        level: EventLevel::Tx,
+        attributes: Default::default(),
+    };
+    tx_event.extend(&TxApplied {
+        hash,
+        height: height.next_height(),
+        code,
+        gas_used: result.gas_used,
+        info: result.to_string(),
+        initialized_accounts: result.initialized_accounts.clone(),
+        details: TxResultDetails::from(&result),
+    });
+    let mut events = vec![tx_event];
+    if result.is_accepted() {
+        events.extend(result.ibc_events.iter().map(|ibc_event| {
+            match IbcPacket::from_ibc_event(ibc_event) {
+                Some(packet) => Event::from(packet),
+                None => Event::from(ibc_event.clone()),
+            }
+        }));
+    }
+
+    let data = DryRunEffects {
+        height,
+        result,
+        changes,
+        events,
+    }
+    .try_to_vec()
+    .into_storage_result()?;
+    Ok(EncodedResponseQuery {
+        data,
+        proof: None,
+        info: Default::default(),
+    })
+}
+
+/// Apply a tx with a new write log, as if it was decrypted
+#[cfg(all(feature = "wasm-runtime", feature = "ferveo-tpke"))]
+fn apply_dry_run_tx<D, H>(
+    ctx: &mut RequestCtx<'_, D, H>,
+    tx_bytes: &[u8],
+) -> storage_api::Result<(TxResult, WriteLog)>
 where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
     use crate::ledger::gas::BlockGasMeter;
     use crate::ledger::protocol;
-    use crate::ledger::storage::write_log::WriteLog;
     use crate::proto::Tx;
     use crate::types::storage::TxIndex;
     use crate::types::transaction::decrypted::DecryptedTx;
     use crate::types::transaction::TxType;
 
     let mut gas_meter = BlockGasMeter::default();
+    gas_meter.set_gas_costs(parameters::read_gas_cost_table_parameter(
+        ctx.wl_storage,
+    )?);
     let mut write_log = WriteLog::default();
-    let mut tx = Tx::try_from(&request.data[..]).into_storage_result()?;
+    let mut tx = Tx::try_from(tx_bytes).into_storage_result()?;
     tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted {
         #[cfg(not(feature = "mainnet"))]
         // To be able to dry-run testnet faucet withdrawal, pretend
         // that we got a valid PoW
         has_valid_pow: true,
     }));
-    let data = protocol::apply_tx(
+    let result = protocol::apply_tx(
         tx,
-        request.data.len(),
+        tx_bytes.len(),
         TxIndex(0),
         &mut gas_meter,
         &mut write_log,
@@ -101,12 +286,7 @@ where
         &mut ctx.tx_wasm_cache,
     )
     .into_storage_result()?;
-    let data = data.try_to_vec().into_storage_result()?;
-    Ok(EncodedResponseQuery {
-        data,
-        proof: None,
-        info: Default::default(),
-    })
+    Ok((result, write_log))
 }
 
 /// Query to read block results from storage
@@ -167,6 +347,44 @@ where
     }
 }
 
+/// Get the compact forms of the accepted shielded transfers from the start
+/// index, in the order they were accepted. At most the limit of the transfers,
+/// capped by [`MAX_COMPACT_MASP_TXS`], is returned, so a shorter result means
+/// that there are no more transfers.
+fn masp_compact_txs<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    start: u64,
+    limit: u64,
+) -> storage_api::Result<Vec<CompactMaspTx>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let head_txidx: u64 = ctx
+        .wl_storage
+        .read(&token::masp_head_tx_key())?
+        .unwrap_or_default();
+    let end =
+        head_txidx.min(start.saturating_add(limit.min(MAX_COMPACT_MASP_TXS)));
+    (start..end)
+        .map(|txidx| {
+            let (epoch, height, index, transfer, shielded): (
+                Epoch,
+                storage::BlockHeight,
+                storage::TxIndex,
+                token::Transfer,
+                Transaction,
+            ) = ctx
+                .wl_storage
+                .read(&token::masp_tx_key(txidx))?
+                .ok_or_err_msg("The accepted shielded transfer is missing")?;
+            Ok(CompactMaspTx::new(
+                height, index, epoch, &transfer, &shielded,
+            ))
+        })
+        .collect()
+}
+
 #[cfg(not(all(feature = "wasm-runtime", feature = "ferveo-tpke")))]
 fn dry_run_tx<D, H>(
     _ctx: RequestCtx<'_, D, H>,
@@ -179,6 +397,18 @@ where
     unimplemented!("Dry running tx requires \"wasm-runtime\" feature.")
 }
 
+#[cfg(not(all(feature = "wasm-runtime", feature = "ferveo-tpke")))]
+fn dry_run_tx_effects<D, H>(
+    _ctx: RequestCtx<'_, D, H>,
+    _request: &RequestQuery,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    unimplemented!("Dry running tx requires \"wasm-runtime\" feature.")
+}
+
 fn epoch<D, H>(ctx: RequestCtx<'_, D, H>) -> storage_api::Result<Epoch>
 where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
@@ -198,6 +428,91 @@ where
     Ok(ctx.wl_storage.storage.last_block.clone())
 }
 
+fn epoch_clock<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+) -> storage_api::Result<Option<EpochClock>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let storage = &ctx.wl_storage.storage;
+    let last_block = match storage.last_block.as_ref() {
+        Some(last_block) => last_block,
+        None => return Ok(None),
+    };
+    let epoch_duration =
+        parameters::read_epoch_duration_parameter(ctx.wl_storage)?;
+    // The blocks are expected to take at most this time
+    let block_time: DurationSecs = ctx
+        .wl_storage
+        .read(&parameters::storage::get_max_expected_time_per_block_key())?
+        .unwrap_or(DurationSecs(0));
+    Ok(Some(EpochClock {
+        epoch: storage.last_epoch,
+        height: last_block.height,
+        time: last_block.time,
+        next_epoch_min_start_height: storage.next_epoch_min_start_height,
+        next_epoch_min_start_time: storage.next_epoch_min_start_time,
+        epoch_duration,
+        block_time,
+    }))
+}
+
+fn seconds_until_epoch<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    epoch: Epoch,
+) -> storage_api::Result<Option<u64>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let now = DateTimeUtc::now();
+    let clock = epoch_clock(ctx)?;
+    Ok(clock.map(|clock| clock.duration_until_epoch(epoch, now).0))
+}
+
+fn mempool_min_gas_price<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+) -> storage_api::Result<token::Amount>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    Ok(ctx.mempool_min_gas_price)
+}
+
+fn min_gas_price<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+) -> storage_api::Result<token::Amount>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    parameters::read_min_gas_price_parameter(ctx.wl_storage)
+}
+
+fn next_nonce<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    owner: Address,
+) -> storage_api::Result<u64>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    replay_protection::read_next_nonce(ctx.wl_storage, &owner)
+}
+
+fn token_metadata<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    token: Address,
+) -> storage_api::Result<Option<token::TokenMetadata>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_api::token::read_metadata(ctx.wl_storage, &token)
+}
+
 /// Returns data with `vec![]` when the storage key is not found. For all
 /// borsh-encoded types, it is safe to check `data.is_empty()` to see if the
 /// value was found, except for unit - see `fn query_storage_value` in
@@ -211,20 +526,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
-        if request.height.0 + past_height_limit
-            < ctx.wl_storage.storage.get_last_block_height().0
-        {
-            return Err(storage_api::Error::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Cannot query more than {past_height_limit} blocks in the \
-                     past (configured via \
-                     `shell.storage_read_past_height_limit`)."
-                ),
-            )));
-        }
-    }
+    check_past_height_limit(&ctx, request.height)?;
 
     match ctx
         .wl_storage
@@ -269,6 +571,64 @@ where
     }
 }
 
+/// Check that the given height isn't further in the past than the storage can
+/// be queried
+pub(super) fn check_past_height_limit<D, H>(
+    ctx: &RequestCtx<'_, D, H>,
+    height: storage::BlockHeight,
+) -> storage_api::Result<()>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
+        if height.0 + past_height_limit
+            < ctx.wl_storage.storage.get_last_block_height().0
+        {
+            return Err(storage_api::Error::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot query more than {past_height_limit} blocks in the \
+                     past (configured via \
+                     `shell.storage_read_past_height_limit`)."
+                ),
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the value of the storage key at the given committed height, if
+/// any, which is read from the diffs of the storage. The height must not be
+/// older than the history retained by the node.
+fn storage_value_at_height<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    height: storage::BlockHeight,
+    storage_key: storage::Key,
+) -> storage_api::Result<Option<Vec<u8>>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let last_height = ctx.wl_storage.storage.get_last_block_height();
+    if height > last_height {
+        return Err(storage_api::Error::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "The height {height} isn't committed yet, the last committed \
+                 height is {last_height}"
+            ),
+        )));
+    }
+    check_past_height_limit(&ctx, height)?;
+    let (value, _gas) = ctx
+        .wl_storage
+        .storage
+        .read_with_height(&storage_key, height)
+        .into_storage_result()?;
+    Ok(value)
+}
+
 fn storage_prefix<D, H>(
     ctx: RequestCtx<'_, D, H>,
     request: &RequestQuery,
@@ -358,19 +718,53 @@ where
         .cloned())
 }
 
+fn alloc_replay_log<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    height: storage::BlockHeight,
+) -> storage_api::Result<Vec<AllocDecision>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    Ok(ctx.alloc_replay_log.decisions_at(height))
+}
+
+fn pending_wrappers<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+) -> storage_api::Result<Option<Vec<PendingWrapper>>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    Ok(ctx.mempool_view.pending(None))
+}
+
+fn pending_wrappers_of<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    fee_payer: Address,
+) -> storage_api::Result<Option<Vec<PendingWrapper>>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    Ok(ctx.mempool_view.pending(Some(&fee_payer)))
+}
+
 #[cfg(test)]
 mod test {
     use borsh::BorshDeserialize;
     use namada_test_utils::TestWasms;
 
+    use crate::ledger::events::EventType;
+    use crate::ledger::mempool_view::{MempoolView, PendingWrapper};
     use crate::ledger::queries::testing::TestClient;
     use crate::ledger::queries::RPC;
     use crate::ledger::storage_api::{self, StorageWrite};
     use crate::proto::{Code, Data, Tx};
     use crate::types::hash::Hash;
-    use crate::types::storage::Key;
+    use crate::types::storage::{BlockHeight, Key};
     use crate::types::transaction::decrypted::DecryptedTx;
-    use crate::types::transaction::TxType;
+    use crate::types::transaction::{Fee, GasLimit, TxType};
     use crate::types::{address, token};
 
     #[test]
@@ -387,11 +781,78 @@ mod test {
         let path = RPC.shell().dry_run_tx_path();
         assert_eq!("/shell/dry_run_tx", path);
 
+        let path = RPC.shell().dry_run_tx_effects_path();
+        assert_eq!("/shell/dry_run_tx_effects", path);
+
         let path = RPC.shell().storage_prefix_path(&key);
         assert_eq!(format!("/shell/prefix/{}", key), path);
 
         let path = RPC.shell().storage_has_key_path(&key);
         assert_eq!(format!("/shell/has_key/{}", key), path);
+
+        let height = BlockHeight(3);
+        let path = RPC.shell().storage_value_at_height_path(&height, &key);
+        assert_eq!(format!("/shell/value_at_height/{}/{}", height, key), path);
+
+        let path = RPC.shell().pending_wrappers_of_path(&owner);
+        assert_eq!(format!("/shell/pending_wrappers_of/{}", owner), path);
+
+        let path = RPC.shell().masp_compact_txs_path(&10, &100);
+        assert_eq!("/shell/masp_compact_txs/10/100", path);
+    }
+
+    /// Test that no compact shielded transfers are returned before any
+    /// shielded transfer is accepted
+    #[tokio::test]
+    async fn test_masp_compact_txs_without_transfers() {
+        let client = TestClient::new(RPC);
+        let txs = RPC
+            .shell()
+            .masp_compact_txs(&client, &0, &100)
+            .await
+            .unwrap();
+        assert!(txs.is_empty());
+    }
+
+    /// Test that the pending wrappers are only served when the node exposes
+    /// its mempool
+    #[tokio::test]
+    async fn test_pending_wrappers() {
+        let mut client = TestClient::new(RPC);
+        let fee_payer = address::testing::established_address_1();
+        assert_eq!(RPC.shell().pending_wrappers(&client).await.unwrap(), None);
+
+        client.mempool_view = MempoolView::new(10);
+        let wrapper = PendingWrapper {
+            hash: Hash::sha256(b"wrapper"),
+            fee: Fee {
+                amount: token::Amount::from(10),
+                token: address::nam(),
+            },
+            gas_limit: GasLimit::from_multiplier(1),
+            fee_payer: fee_payer.clone(),
+            validated_at: BlockHeight(1),
+        };
+        client.mempool_view.insert(wrapper.clone());
+        assert_eq!(
+            RPC.shell().pending_wrappers(&client).await.unwrap(),
+            Some(vec![wrapper.clone()])
+        );
+        assert_eq!(
+            RPC.shell()
+                .pending_wrappers_of(&client, &fee_payer)
+                .await
+                .unwrap(),
+            Some(vec![wrapper])
+        );
+        let other = address::testing::established_address_2();
+        assert_eq!(
+            RPC.shell()
+                .pending_wrappers_of(&client, &other)
+                .await
+                .unwrap(),
+            Some(vec![])
+        );
     }
 
     #[tokio::test]
@@ -423,11 +884,37 @@ mod test {
         let tx_bytes = outer_tx.to_bytes();
         let result = RPC
             .shell()
-            .dry_run_tx(&client, Some(tx_bytes), None, false)
+            .dry_run_tx(&client, Some(tx_bytes.clone()), None, false)
             .await
             .unwrap();
         assert!(result.data.is_accepted());
 
+        // Request the effects of the dry run tx
+        let effects = RPC
+            .shell()
+            .dry_run_tx_effects(&client, Some(tx_bytes.clone()), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert!(effects.result.is_accepted());
+        assert_eq!(
+            effects.height,
+            client.wl_storage.storage.get_last_block_height()
+        );
+        assert_eq!(effects.events.len(), 1);
+        assert_eq!(effects.events[0].event_type, EventType::Applied);
+        // It can only be run at the last committed height
+        assert!(RPC
+            .shell()
+            .dry_run_tx_effects(
+                &client,
+                Some(tx_bytes),
+                Some(BlockHeight(100)),
+                false
+            )
+            .await
+            .is_err());
+
         // Request storage value for a balance key ...
         let token_addr = address::testing::established_address_1();
         let owner = address::testing::established_address_2();
@@ -474,6 +961,29 @@ mod test {
             token::Amount::try_from_slice(&read_balance.data).unwrap()
         );
 
+        // Request storage value at the committed height ...
+        let last_height = client.wl_storage.storage.get_last_block_height();
+        let read_balance = RPC
+            .shell()
+            .storage_value_at_height(&client, &last_height, &balance_key)
+            .await
+            .unwrap()
+            .expect("The balance should be found");
+        assert_eq!(
+            balance,
+            token::Amount::try_from_slice(&read_balance).unwrap()
+        );
+        // ... but not at a height that isn't committed yet
+        assert!(RPC
+            .shell()
+            .storage_value_at_height(
+                &client,
+                &last_height.next_height(),
+                &balance_key
+            )
+            .await
+            .is_err());
+
         // Request storage prefix iterator
         let balance_prefix = token::balance_prefix(&token_addr);
         let read_balances = RPC
@@ -8,12 +8,15 @@ use tendermint_rpc::query::Query;
 use tendermint_rpc::Order;
 use thiserror::Error;
 
+use crate::ledger::alloc_log::AllocReplayLog;
 use crate::ledger::events::log::EventLog;
+use crate::ledger::mempool_view::MempoolView;
 use crate::ledger::storage::{DBIter, StorageHasher, DB};
 use crate::ledger::storage_api;
 use crate::tendermint::merkle::proof::Proof;
 use crate::tendermint_rpc::error::Error as RpcError;
 use crate::types::storage::BlockHeight;
+use crate::types::token;
 #[cfg(feature = "wasm-runtime")]
 use crate::vm::wasm::{TxCache, VpCache};
 #[cfg(feature = "wasm-runtime")]
@@ -30,6 +33,10 @@ where
     pub wl_storage: &'shell WlStorage<D, H>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
     pub event_log: &'shell EventLog,
+    /// Log of block space allocation decisions, if enabled.
+    pub alloc_replay_log: &'shell AllocReplayLog,
+    /// View of the wrapper txs pending in the mempool, if enabled.
+    pub mempool_view: &'shell MempoolView,
     /// Cache of VP wasm compiled artifacts.
     #[cfg(feature = "wasm-runtime")]
     pub vp_wasm_cache: VpCache<WasmCacheRoAccess>,
@@ -40,6 +47,9 @@ where
     /// limit the how many block heights in the past can the storage be
     /// queried for reading values.
     pub storage_read_past_height_limit: Option<u64>,
+    /// Taken from config `mempool_min_gas_price`. The minimum gas price that
+    /// wrappers must offer to be accepted into the node's mempool.
+    pub mempool_min_gas_price: token::Amount,
 }
 
 /// A `Router` handles parsing read-only query requests and dispatching them to
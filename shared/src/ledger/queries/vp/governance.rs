@@ -0,0 +1,262 @@
+//! Queries router and handlers for the governance validity predicate
+//!
+//! The live tally of a proposal is computed from its votes in the same way as
+//! at the end of its voting period, with the stake of the voters at the
+//! current epoch until the voting period ends, and at its end epoch after.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::ledger::governance::storage as gov_storage;
+use namada_core::types::governance::{ProposalVote, TallyResult, VotePower};
+use namada_core::types::transaction::governance::ProposalType;
+use namada_proof_of_stake::{read_pos_params, read_total_stake};
+use rust_decimal::Decimal;
+
+use crate::ledger::native_vp::governance::utils::{
+    compute_tally, get_proposal_votes, Votes,
+};
+use crate::ledger::queries::types::RequestCtx;
+use crate::ledger::storage::{DBIter, StorageHasher, DB};
+use crate::ledger::storage_api::{self, ResultExt, StorageRead};
+use crate::types::address::Address;
+use crate::types::storage::Epoch;
+
+// Governance validity predicate queries
+router! {GOV,
+    ( "tally" / [id: u64] ) -> Option<ProposalTally> = proposal_tally,
+
+    ( "voters" / [id: u64] ) -> Option<Vec<ProposalVoter>> = proposal_voters,
+}
+
+/// The tally of the votes on a proposal at the epoch of the query
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProposalTally {
+    /// The epoch of the stake which the votes are weighted with
+    pub epoch: Epoch,
+    /// The total voting power at the epoch
+    pub total_voting_power: VotePower,
+    /// The voting power of the yay votes
+    pub total_yay_power: VotePower,
+    /// The voting power of the nay votes
+    pub total_nay_power: VotePower,
+    /// Whether the proposal passes with the votes so far
+    pub passed: bool,
+    /// The fraction of the total voting power which voted
+    pub turnout: Decimal,
+    /// The fraction of the total voting power which must vote for the deposit
+    /// of a rejected proposal to be refunded
+    pub min_quorum: Decimal,
+    /// The last epoch of the voting period
+    pub voting_end_epoch: Epoch,
+    /// The number of epochs left until the end of the voting period, or zero
+    /// if it's over
+    pub remaining_epochs: u64,
+}
+
+/// A vote on a proposal with the voting power it's counted with
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProposalVoter {
+    /// The address of the voter
+    pub voter: Address,
+    /// The validator whose stake is voted with, i.e. the voter itself for a
+    /// validator or the validator of the bond for a delegator
+    pub validator: Address,
+    /// The vote
+    pub vote: ProposalVote,
+    /// The voting power of the vote
+    pub power: VotePower,
+}
+
+/// Get the live tally of the proposal, if it exists
+fn proposal_tally<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Option<ProposalTally>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let (proposal_type, voting_end_epoch, epoch, votes) =
+        match read_proposal_votes(&ctx, id)? {
+            Some(proposal_votes) => proposal_votes,
+            None => return Ok(None),
+        };
+    let params = read_pos_params(ctx.wl_storage)?;
+    let total_stake = read_total_stake(ctx.wl_storage, &params, epoch)?;
+    let total_voting_power = VotePower::from(total_stake);
+    let result = compute_tally(votes, total_voting_power, &proposal_type)
+        .into_storage_result()?;
+    let min_quorum = ctx
+        .wl_storage
+        .read::<Decimal>(&gov_storage::get_min_proposal_quorum_key())?
+        .unwrap_or_default();
+
+    let voted_power = result
+        .total_yay_power
+        .saturating_add(result.total_nay_power);
+    let turnout = Decimal::from(voted_power)
+        .checked_div(Decimal::from(total_voting_power))
+        .unwrap_or_default();
+    let current_epoch = ctx.wl_storage.storage.last_epoch;
+    let remaining_epochs = voting_end_epoch.0.saturating_sub(current_epoch.0);
+
+    Ok(Some(ProposalTally {
+        epoch,
+        total_voting_power,
+        total_yay_power: result.total_yay_power,
+        total_nay_power: result.total_nay_power,
+        passed: matches!(result.result, TallyResult::Passed(_)),
+        turnout,
+        min_quorum,
+        voting_end_epoch,
+        remaining_epochs,
+    }))
+}
+
+/// Get the votes on the proposal, if it exists, sorted by voter and
+/// validator
+fn proposal_voters<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Option<Vec<ProposalVoter>>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let votes = match read_proposal_votes(&ctx, id)? {
+        Some((_, _, _, votes)) => votes,
+        None => return Ok(None),
+    };
+    let Votes {
+        yay_validators,
+        nay_validators,
+        delegators,
+    } = votes;
+
+    let mut voters: Vec<ProposalVoter> = yay_validators
+        .into_iter()
+        .map(|(validator, (power, vote))| ProposalVoter {
+            voter: validator.clone(),
+            validator,
+            vote,
+            power,
+        })
+        .chain(nay_validators.into_iter().map(|(validator, power)| {
+            ProposalVoter {
+                voter: validator.clone(),
+                validator,
+                vote: ProposalVote::Nay,
+                power,
+            }
+        }))
+        .chain(delegators.into_iter().flat_map(|(delegator, votes)| {
+            votes.into_iter().map(move |(validator, (power, vote))| {
+                ProposalVoter {
+                    voter: delegator.clone(),
+                    validator,
+                    vote,
+                    power,
+                }
+            })
+        }))
+        .collect();
+    voters.sort_by(|a, b| {
+        (&a.voter, &a.validator).cmp(&(&b.voter, &b.validator))
+    });
+    Ok(Some(voters))
+}
+
+/// Read the type, the voting end epoch and the votes of the proposal, if it
+/// exists, together with the epoch of the stake of the votes
+fn read_proposal_votes<D, H>(
+    ctx: &RequestCtx<'_, D, H>,
+    id: u64,
+) -> storage_api::Result<Option<(ProposalType, Epoch, Epoch, Votes)>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let proposal_type: ProposalType = match ctx
+        .wl_storage
+        .read(&gov_storage::get_proposal_type_key(id))?
+    {
+        Some(proposal_type) => proposal_type,
+        None => return Ok(None),
+    };
+    let voting_end_epoch: Epoch = ctx
+        .wl_storage
+        .read(&gov_storage::get_voting_end_epoch_key(id))?
+        .unwrap_or_default();
+    let epoch =
+        std::cmp::min(ctx.wl_storage.storage.last_epoch, voting_end_epoch);
+    let votes = get_proposal_votes(ctx.wl_storage, epoch, id)?;
+    Ok(Some((proposal_type, voting_end_epoch, epoch, votes)))
+}
+
+#[cfg(test)]
+mod test {
+    use namada_proof_of_stake::parameters::PosParams;
+    use namada_proof_of_stake::write_pos_params;
+
+    use super::*;
+    use crate::ledger::queries::testing::TestClient;
+    use crate::ledger::queries::RPC;
+    use crate::ledger::storage_api::StorageWrite;
+
+    #[test]
+    fn test_governance_queries_router_paths() {
+        let path = RPC.vp().gov().proposal_tally_path(&3);
+        assert_eq!("/vp/governance/tally/3", path);
+
+        let path = RPC.vp().gov().proposal_voters_path(&3);
+        assert_eq!("/vp/governance/voters/3", path);
+    }
+
+    /// Test the tally of a proposal without votes before the end of its
+    /// voting period, and that there's no tally of a missing proposal
+    #[tokio::test]
+    async fn test_proposal_tally_without_votes() {
+        let mut client = TestClient::new(RPC);
+        write_pos_params(&mut client.wl_storage, &PosParams::default())
+            .unwrap();
+        client
+            .wl_storage
+            .write(
+                &gov_storage::get_proposal_type_key(0),
+                ProposalType::Default(None),
+            )
+            .unwrap();
+        client
+            .wl_storage
+            .write(&gov_storage::get_voting_end_epoch_key(0), Epoch(3))
+            .unwrap();
+        client
+            .wl_storage
+            .write(
+                &gov_storage::get_min_proposal_quorum_key(),
+                Decimal::new(33, 2),
+            )
+            .unwrap();
+        client.wl_storage.commit_tx();
+
+        let tally = RPC
+            .vp()
+            .gov()
+            .proposal_tally(&client, &0)
+            .await
+            .unwrap()
+            .expect("The proposal should be tallied");
+        assert_eq!(tally.epoch, Epoch(0));
+        assert_eq!(tally.total_yay_power, 0);
+        assert_eq!(tally.total_nay_power, 0);
+        assert_eq!(tally.turnout, Decimal::ZERO);
+        assert_eq!(tally.min_quorum, Decimal::new(33, 2));
+        assert_eq!(tally.voting_end_epoch, Epoch(3));
+        assert_eq!(tally.remaining_epochs, 3);
+
+        let voters = RPC.vp().gov().proposal_voters(&client, &0).await.unwrap();
+        assert_eq!(voters, Some(vec![]));
+
+        let tally = RPC.vp().gov().proposal_tally(&client, &1).await.unwrap();
+        assert!(tally.is_none());
+    }
+}
@@ -0,0 +1,214 @@
+//! Queries router and handlers for the IBC validity predicate
+//!
+//! The client states, consensus states, connections and channels are returned
+//! in their protobuf encoding as stored. With the `prove` flag of the request,
+//! they come with an ICS-23 proof of their existence, or of their
+//! non-existence if not found, at the height of the request.
+
+use borsh::BorshSerialize;
+
+use crate::ibc::core::ics02_client::height::Height;
+use crate::ibc::core::ics24_host::identifier::{
+    ChannelId, ClientId, ConnectionId, PortChannelId, PortId,
+};
+use crate::ledger::ibc::storage as ibc_storage;
+use crate::ledger::queries::shell::check_past_height_limit;
+use crate::ledger::queries::types::{RequestCtx, RequestQuery};
+use crate::ledger::queries::{require_no_data, EncodedResponseQuery};
+use crate::ledger::storage::{DBIter, StorageHasher, DB};
+use crate::ledger::storage_api::{self, ResultExt};
+use crate::types::storage;
+
+// IBC validity predicate queries
+router! {IBC,
+    ( "client_state" / [client_id: ClientId] )
+        -> Option<Vec<u8>> = (with_options client_state),
+
+    ( "consensus_state" / [client_id: ClientId] / [height: Height] )
+        -> Option<Vec<u8>> = (with_options consensus_state),
+
+    ( "connection" / [conn_id: ConnectionId] )
+        -> Option<Vec<u8>> = (with_options connection),
+
+    ( "channel" / [port_id: PortId] / [channel_id: ChannelId] )
+        -> Option<Vec<u8>> = (with_options channel),
+}
+
+/// Get the state of the client
+fn client_state<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    client_id: ClientId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let key = ibc_storage::client_state_key(&client_id);
+    read_with_proof(ctx, request, key)
+}
+
+/// Get the consensus state of the client at the given height of its
+/// counterparty chain
+fn consensus_state<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    client_id: ClientId,
+    height: Height,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let key = ibc_storage::consensus_state_key(&client_id, height);
+    read_with_proof(ctx, request, key)
+}
+
+/// Get the end of the connection on this chain
+fn connection<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    conn_id: ConnectionId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let key = ibc_storage::connection_key(&conn_id);
+    read_with_proof(ctx, request, key)
+}
+
+/// Get the end of the channel on this chain
+fn channel<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    port_id: PortId,
+    channel_id: ChannelId,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let key = ibc_storage::channel_key(&PortChannelId {
+        port_id,
+        channel_id,
+    });
+    read_with_proof(ctx, request, key)
+}
+
+/// Read the value of the IBC key at the height of the request, with a proof
+/// of its existence or non-existence if requested
+fn read_with_proof<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    key: storage::Key,
+) -> storage_api::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    require_no_data(request)?;
+    check_past_height_limit(&ctx, request.height)?;
+
+    let (value, _gas) = ctx
+        .wl_storage
+        .storage
+        .read_with_height(&key, request.height)
+        .into_storage_result()?;
+    let proof = if request.prove {
+        let proof = match &value {
+            Some(value) => ctx.wl_storage.storage.get_existence_proof(
+                &key,
+                value,
+                request.height,
+            ),
+            None => ctx
+                .wl_storage
+                .storage
+                .get_non_existence_proof(&key, request.height),
+        }
+        .into_storage_result()?;
+        Some(proof)
+    } else {
+        None
+    };
+    let data = value.try_to_vec().into_storage_result()?;
+    Ok(EncodedResponseQuery {
+        data,
+        proof,
+        info: Default::default(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::ledger::queries::testing::TestClient;
+    use crate::ledger::queries::RPC;
+    use crate::ledger::storage_api::StorageWrite;
+
+    #[test]
+    fn test_ibc_queries_router_paths() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let path = RPC.vp().ibc().client_state_path(&client_id);
+        assert_eq!(format!("/vp/ibc/client_state/{}", client_id), path);
+
+        let height = Height::new(0, 10).unwrap();
+        let path = RPC.vp().ibc().consensus_state_path(&client_id, &height);
+        assert_eq!(
+            format!("/vp/ibc/consensus_state/{}/{}", client_id, height),
+            path
+        );
+
+        let port_id = PortId::transfer();
+        let channel_id = ChannelId::new(0);
+        let path = RPC.vp().ibc().channel_path(&port_id, &channel_id);
+        assert_eq!(format!("/vp/ibc/channel/{}/{}", port_id, channel_id), path);
+    }
+
+    /// Test that the client state is returned with a proof of its existence,
+    /// and a missing client state with a proof of its non-existence
+    #[tokio::test]
+    async fn test_client_state_with_proof() {
+        let mut client = TestClient::new(RPC);
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let state = b"client state".to_vec();
+        client
+            .wl_storage
+            .write_bytes(&ibc_storage::client_state_key(&client_id), &state)
+            .unwrap();
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+        let height = client.wl_storage.storage.get_last_block_height();
+
+        let response = RPC
+            .vp()
+            .ibc()
+            .client_state(&client, None, Some(height), true, &client_id)
+            .await
+            .unwrap();
+        assert_eq!(response.data, Some(state));
+        assert!(response.proof.is_some());
+
+        let missing = ClientId::from_str("07-tendermint-1").unwrap();
+        let response = RPC
+            .vp()
+            .ibc()
+            .client_state(&client, None, Some(height), true, &missing)
+            .await
+            .unwrap();
+        assert_eq!(response.data, None);
+        assert!(response.proof.is_some());
+
+        // the proof is only attached on request
+        let response = RPC
+            .vp()
+            .ibc()
+            .client_state(&client, None, Some(height), false, &client_id)
+            .await
+            .unwrap();
+        assert!(response.proof.is_none());
+    }
+}
@@ -1,13 +1,22 @@
 //! Queries router and handlers for validity predicates
 
 // Re-export to show in rustdoc!
+// The `self` prefix disambiguates the module from the `ibc` crate
+pub use self::ibc::Ibc;
+use self::ibc::IBC;
+pub use governance::Gov;
+use governance::GOV;
 pub use pos::Pos;
 use pos::POS;
+pub mod governance;
+pub mod ibc;
 pub mod pos;
 
 // Validity predicate queries
 router! {VP,
     ( "pos" ) = (sub POS),
+    ( "ibc" ) = (sub IBC),
+    ( "governance" ) = (sub GOV),
 }
 
 /// Client-only methods for the router type are composed from router functions.
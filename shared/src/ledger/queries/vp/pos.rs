@@ -11,7 +11,7 @@ use namada_proof_of_stake::types::{
 };
 use namada_proof_of_stake::{
     self, below_capacity_validator_set_handle, bond_amount, bond_handle,
-    consensus_validator_set_handle, find_all_slashes,
+    compute_bond_rewards, consensus_validator_set_handle, find_all_slashes,
     find_delegation_validators, find_delegations, read_all_validator_addresses,
     read_pos_params, read_total_stake,
     read_validator_max_commission_rate_change, read_validator_stake,
@@ -79,6 +79,9 @@ router! {POS,
     ( "withdrawable_tokens" / [source: Address] / [validator: Address] / [epoch: opt Epoch] )
         -> token::Amount = withdrawable_tokens,
 
+    ( "rewards" / [validator: Address] / [source: opt Address] )
+        -> token::Amount = rewards,
+
     ( "bonds_and_unbonds" / [source: opt Address] / [validator: opt Address] )
         -> BondsAndUnbondsDetails = bonds_and_unbonds,
 
@@ -435,6 +438,26 @@ where
     Ok(total)
 }
 
+/// Get the rewards of the bond which can be claimed, i.e. of the epochs before
+/// the current one which haven't been claimed yet
+fn rewards<D, H>(
+    ctx: RequestCtx<'_, D, H>,
+    validator: Address,
+    source: Option<Address>,
+) -> storage_api::Result<token::Amount>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let current_epoch = ctx.wl_storage.storage.last_epoch;
+    compute_bond_rewards(
+        ctx.wl_storage,
+        source.as_ref(),
+        &validator,
+        current_epoch,
+    )
+}
+
 fn bonds_and_unbonds<D, H>(
     ctx: RequestCtx<'_, D, H>,
     source: Option<Address>,
@@ -1,35 +1,47 @@
 //! SDK RPC queries
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use borsh::BorshDeserialize;
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::sapling::Node;
-use namada_core::ledger::storage::LastBlock;
+use namada_core::ledger::epoch_clock::EpochClock;
+use namada_core::ledger::storage::{merkle_tree, LastBlock, MerkleRoot};
 use namada_core::ledger::testnet_pow;
 use namada_core::types::address::Address;
 use namada_core::types::storage::Key;
 use namada_core::types::token::Amount;
 use namada_proof_of_stake::types::{BondsAndUnbondsDetails, CommissionPair};
+use rust_decimal::Decimal;
 use serde::Serialize;
 use tokio::time::Duration;
 
+use crate::ledger::alloc_log::AllocDecision;
 use crate::ledger::events::Event;
 use crate::ledger::governance::parameters::GovParams;
 use crate::ledger::governance::storage as gov_storage;
+use crate::ledger::ibc::storage as ibc_storage;
+use crate::ledger::masp::CompactMaspTx;
+use crate::ledger::mempool_view::PendingWrapper;
 use crate::ledger::native_vp::governance::utils::Votes;
+use crate::ledger::parameters::convert_fee_amount;
+use crate::ledger::parameters::storage as parameter_storage;
 use crate::ledger::queries::vp::pos::EnrichedBondsAndUnbondsDetails;
-use crate::ledger::queries::RPC;
+use crate::ledger::queries::{DryRunEffects, RPC};
 use crate::proto::Tx;
 use crate::tendermint::merkle::proof::Proof;
 use crate::tendermint_rpc::error::Error as TError;
 use crate::tendermint_rpc::query::Query;
 use crate::tendermint_rpc::Order;
+use crate::types::event::attributes;
 use crate::types::governance::{ProposalVote, VotePower};
 use crate::types::hash::Hash;
 use crate::types::key::*;
 use crate::types::storage::{BlockHeight, BlockResults, Epoch, PrefixValue};
 use crate::types::token::balance_key;
+use crate::types::transaction::{
+    Fee, GasLimit, ResultCode, TxResultDetails, MIN_FEE,
+};
 use crate::types::{storage, token};
 
 /// Query the status of a given transaction.
@@ -87,6 +99,109 @@ pub async fn query_epoch<C: crate::ledger::queries::Client + Sync>(
     epoch
 }
 
+/// Query the minimum gas price accepted by the node's mempool, from which
+/// the tip needed to get a wrapper included can be estimated.
+pub async fn query_mempool_min_gas_price<
+    C: crate::ledger::queries::Client + Sync,
+>(
+    client: &C,
+) -> token::Amount {
+    unwrap_client_response::<C, _>(
+        RPC.shell().mempool_min_gas_price(client).await,
+    )
+}
+
+/// Query the block space allocation decisions taken by the node at the
+/// given height. Empty unless the node enabled its allocator replay log.
+pub async fn query_alloc_replay_log<
+    C: crate::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    height: BlockHeight,
+) -> Vec<AllocDecision> {
+    unwrap_client_response::<C, _>(
+        RPC.shell().alloc_replay_log(client, &height).await,
+    )
+}
+
+/// Query the wrapper txs pending in the mempool of the node, optionally only
+/// the ones whose fee is paid by the given address. Returns `None` unless the
+/// node exposes its mempool.
+pub async fn query_pending_wrappers<
+    C: crate::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    fee_payer: Option<&Address>,
+) -> Option<Vec<PendingWrapper>> {
+    let pending = match fee_payer {
+        Some(fee_payer) => {
+            RPC.shell().pending_wrappers_of(client, fee_payer).await
+        }
+        None => RPC.shell().pending_wrappers(client).await,
+    };
+    unwrap_client_response::<C, _>(pending)
+}
+
+/// Query the minimum gas price of the chain. The fee of a wrapper must be at
+/// least its gas limit times this price.
+pub async fn query_min_gas_price<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+) -> token::Amount {
+    unwrap_client_response::<C, _>(RPC.shell().min_gas_price(client).await)
+}
+
+/// Query the resolution of the gas limits of the chain, which gas limits are
+/// multiples of.
+pub async fn query_gas_limit_resolution<
+    C: crate::ledger::queries::Client + Sync,
+>(
+    client: &C,
+) -> u64 {
+    let key = parameter_storage::get_gas_limit_resolution_key();
+    query_storage_value(client, &key)
+        .await
+        .expect("Parameter should be definied.")
+}
+
+/// Query the nonce that the next wrapper paid by the given owner must carry.
+pub async fn query_next_nonce<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    owner: &Address,
+) -> u64 {
+    unwrap_client_response::<C, _>(RPC.shell().nonce(client, owner).await)
+}
+
+/// Query the metadata of the given token, if any.
+pub async fn query_token_metadata<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    token: &Address,
+) -> Option<token::TokenMetadata> {
+    unwrap_client_response::<C, _>(
+        RPC.shell().token_metadata(client, token).await,
+    )
+}
+
+/// Query the state of the epochs at the last committed block, from which the
+/// upcoming epochs are estimated, if any block was committed.
+pub async fn query_epoch_clock<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+) -> Option<EpochClock> {
+    unwrap_client_response::<C, _>(RPC.shell().epoch_clock(client).await)
+}
+
+/// Query the estimated number of seconds from now until the start of the
+/// given epoch, if any block was committed.
+pub async fn query_seconds_until_epoch<
+    C: crate::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    epoch: Epoch,
+) -> Option<u64> {
+    unwrap_client_response::<C, _>(
+        RPC.shell().seconds_until_epoch(client, &epoch).await,
+    )
+}
+
 /// Query the last committed block, if any.
 pub async fn query_block<C: crate::ledger::queries::Client + Sync>(
     client: &C,
@@ -230,6 +345,20 @@ pub async fn query_conversion<C: crate::ledger::queries::Client + Sync>(
     ))
 }
 
+/// Query the compact forms of the accepted shielded transfers from the start
+/// index, at most the limit of them
+pub async fn query_masp_compact_txs<
+    C: crate::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    start: u64,
+    limit: u64,
+) -> Vec<CompactMaspTx> {
+    unwrap_client_response::<C, _>(
+        RPC.shell().masp_compact_txs(client, &start, &limit).await,
+    )
+}
+
 /// Query a wasm code hash
 pub async fn query_wasm_code_hash<C: crate::ledger::queries::Client + Sync>(
     client: &C,
@@ -292,6 +421,31 @@ where
         })
 }
 
+/// Query a storage value at the given committed height and decode it with
+/// [`BorshDeserialize`], e.g. to find the balance of an account at some past
+/// block. The value is read from the history of the storage retained by the
+/// node. Returns `None` if the key had no value at that height.
+pub async fn query_storage_value_at_height<C, T>(
+    client: &C,
+    key: &storage::Key,
+    height: BlockHeight,
+) -> Option<T>
+where
+    T: BorshDeserialize,
+    C: crate::ledger::queries::Client + Sync,
+{
+    let value = unwrap_client_response::<C, _>(
+        RPC.shell()
+            .storage_value_at_height(client, &height, key)
+            .await,
+    )?;
+    T::try_from_slice(&value[..])
+        .map(Some)
+        .unwrap_or_else(|err| {
+            panic!("Error decoding the value: {}", err);
+        })
+}
+
 /// Query a storage value and the proof without decoding.
 pub async fn query_storage_value_bytes<
     C: crate::ledger::queries::Client + Sync,
@@ -314,6 +468,40 @@ pub async fn query_storage_value_bytes<
     }
 }
 
+/// Query a storage value at the given height without decoding, with a proof
+/// of its membership in the merkle tree or of its non-membership if it's not
+/// found. The proof can be verified against the merkle root of the height,
+/// see [`query_merkle_root`].
+pub async fn query_storage_value_with_proof<
+    C: crate::ledger::queries::Client + Sync,
+>(
+    client: &C,
+    key: &storage::Key,
+    height: BlockHeight,
+) -> (Option<Vec<u8>>, merkle_tree::Proof) {
+    let (value, proof) =
+        query_storage_value_bytes(client, key, Some(height), true).await;
+    let proof = proof.expect("The proof should be returned when requested");
+    let proof = merkle_tree::Proof::try_from(proof).unwrap_or_else(|err| {
+        panic!("Error decoding the proof: {}", err);
+    });
+    (value, proof)
+}
+
+/// Query the merkle root of the storage at the given height, which is the app
+/// hash of the header of the next block. Returns `None` if the next block
+/// isn't available yet.
+pub async fn query_merkle_root<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    height: BlockHeight,
+) -> Option<MerkleRoot> {
+    let next_height =
+        crate::tendermint::block::Height::try_from(height.next_height().0)
+            .ok()?;
+    let response = client.block(next_height).await.ok()?;
+    Some(MerkleRoot(response.block.header.app_hash.value()))
+}
+
 /// Query a range of storage values with a matching prefix and decode them with
 /// [`BorshDeserialize`]. Returns an iterator of the storage keys paired with
 /// their associated values.
@@ -349,6 +537,18 @@ where
     }
 }
 
+/// Query the original denom of a token received over IBC, by the hash of its
+/// denom trace in its IBC token address
+pub async fn query_ibc_denom<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    token_hash: impl AsRef<str>,
+) -> Option<String> {
+    let denom_key = ibc_storage::ibc_denom_key(token_hash);
+    let (denom, _proof) =
+        query_storage_value_bytes(client, &denom_key, None, false).await;
+    denom.and_then(|denom| String::from_utf8(denom).ok())
+}
+
 /// Query to check if the given storage key exists.
 pub async fn query_has_storage_key<C: crate::ledger::queries::Client + Sync>(
     client: &C,
@@ -437,6 +637,123 @@ pub async fn dry_run_tx<C: crate::ledger::queries::Client + Sync>(
     result
 }
 
+/// Dry run a transaction and get all of its effects: the storage changes,
+/// the gas used and the events that it would have, if it was applied on top
+/// of the last committed block
+pub async fn dry_run_tx_effects<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    tx_bytes: Vec<u8>,
+) -> DryRunEffects {
+    let (data, height, prove) = (Some(tx_bytes), None, false);
+    unwrap_client_response::<C, _>(
+        RPC.shell()
+            .dry_run_tx_effects(client, data, height, prove)
+            .await,
+    )
+    .data
+}
+
+/// Estimate the fee to pay in `fee_token` for the given inner `tx`, by
+/// dry-running it against the latest state. The gas it consumes is rounded
+/// up to the gas limit resolution of the chain and the fee includes the
+/// tip offered at `gas_price` for the resulting gas limit.
+///
+/// The fee is never lower than the resulting gas limit times the minimum gas
+/// price of the chain, converted from the `native_token` into `fee_token`.
+///
+/// Returns the fee with the gas limit to set in the wrapper, or `None` if
+/// fees cannot be paid in `fee_token` or the computation overflows.
+pub async fn estimate_fee<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    tx: &Tx,
+    native_token: &Address,
+    fee_token: &Address,
+    gas_price: Option<Amount>,
+) -> Option<(Fee, GasLimit)> {
+    let (data, height, prove) = (Some(tx.to_bytes()), None, false);
+    let gas_used = unwrap_client_response::<C, _>(
+        RPC.shell().dry_run_tx(client, data, height, prove).await,
+    )
+    .data
+    .gas_used;
+    let resolution = query_gas_limit_resolution(client).await;
+    let fee_tokens: BTreeMap<Address, Decimal> =
+        query_storage_value(client, &parameter_storage::get_fee_tokens_key())
+            .await
+            .expect("Parameter should be definied.");
+    let wrapper_tx_fees: Amount = query_storage_value(
+        client,
+        &parameter_storage::get_wrapper_tx_fees_key(),
+    )
+    .await
+    .unwrap_or_else(|| Amount::whole(MIN_FEE));
+    let base = convert_fee_amount(
+        &fee_tokens,
+        native_token,
+        fee_token,
+        wrapper_tx_fees,
+    )?;
+    let (mut fee, gas_limit) = Fee::estimate(
+        base,
+        gas_used,
+        resolution,
+        gas_price,
+        fee_token.clone(),
+    )?;
+    // The fee must also cover the gas limit at the minimum gas price
+    let min_gas_price = query_min_gas_price(client).await;
+    let min_gas_price = convert_fee_amount(
+        &fee_tokens,
+        native_token,
+        fee_token,
+        min_gas_price,
+    )?;
+    let raw_gas_limit = gas_limit.checked_raw(resolution)?;
+    let floor = min_gas_price.checked_mul(raw_gas_limit)?;
+    fee.amount = fee.amount.max(floor);
+    Some((fee, gas_limit))
+}
+
+/// Query the fees charged in `fee_token` for a wrapper tx with the given raw
+/// gas limit and gas price, i.e. the fixed wrapper tx fee converted into the
+/// fee token with the tip offered at `gas_price` for the gas limit, rounded
+/// up to the gas limit resolution of the chain.
+///
+/// Returns `None` if fees cannot be paid in `fee_token` or the computation
+/// overflows.
+pub async fn query_wrapper_fees<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    native_token: &Address,
+    fee_token: &Address,
+    gas_limit: u64,
+    gas_price: Option<Amount>,
+) -> Option<Amount> {
+    let resolution = query_gas_limit_resolution(client).await;
+    let fee_tokens: BTreeMap<Address, Decimal> =
+        query_storage_value(client, &parameter_storage::get_fee_tokens_key())
+            .await
+            .expect("Parameter should be definied.");
+    let wrapper_tx_fees: Amount = query_storage_value(
+        client,
+        &parameter_storage::get_wrapper_tx_fees_key(),
+    )
+    .await
+    .unwrap_or_else(|| Amount::whole(MIN_FEE));
+    let base = convert_fee_amount(
+        &fee_tokens,
+        native_token,
+        fee_token,
+        wrapper_tx_fees,
+    )?;
+    let gas_limit =
+        GasLimit::from_raw(gas_limit, resolution).checked_raw(resolution)?;
+    let tip = match gas_price {
+        Some(gas_price) => gas_price.checked_mul(gas_limit)?,
+        None => Amount::default(),
+    };
+    base.checked_add(tip)
+}
+
 /// Data needed for broadcasting a tx and
 /// monitoring its progress on chain
 ///
@@ -475,6 +792,10 @@ pub struct TxResponse {
     pub gas_used: String,
     /// Initialized accounts
     pub initialized_accounts: Vec<Address>,
+    /// The memo attached to the transaction, if any
+    pub memo: Option<String>,
+    /// The machine-readable details of the result, if any
+    pub details: Option<TxResultDetails>,
 }
 
 impl TryFrom<Event> for TxResponse {
@@ -518,6 +839,14 @@ impl TryFrom<Event> for TxResponse {
                 serde_json::from_str(initialized_accounts)
                     .map_err(|err| format!("JSON decode error: {err}"))
             })?;
+        let memo = event.get("memo").cloned();
+        let details = event
+            .get(attributes::DETAILS)
+            .map(|details| {
+                serde_json::from_str(details)
+                    .map_err(|err| format!("JSON decode error: {err}"))
+            })
+            .transpose()?;
 
         Ok(TxResponse {
             hash,
@@ -527,6 +856,8 @@ impl TryFrom<Event> for TxResponse {
             code,
             gas_used,
             initialized_accounts,
+            memo,
+            details,
         })
     }
 }
@@ -538,6 +869,40 @@ impl TxResponse {
             panic!("Error fetching TxResponse: {err}");
         })
     }
+
+    /// The code of the result, if it's a known code
+    pub fn result_code(&self) -> Option<ResultCode> {
+        self.code.parse().ok().and_then(ResultCode::from_u32)
+    }
+
+    /// A description of the error of the tx, for the users, if it failed
+    pub fn error_description(&self) -> Option<String> {
+        let code = self.result_code()?;
+        if code == ResultCode::Ok {
+            return None;
+        }
+        let mut description = code.to_string();
+        if let Some(details) = &self.details {
+            if let Some(error) = &details.error {
+                description.push_str(&format!(": {}", error));
+            }
+            if !details.rejected_vps.is_empty() {
+                let rejected_vps: Vec<_> = details
+                    .rejected_vps
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                description.push_str(&format!(
+                    ". Rejected by the validity predicates of {}",
+                    rejected_vps.join(", ")
+                ));
+            }
+            for (address, error) in &details.vp_errors {
+                description.push_str(&format!(". Error in {address}: {error}"));
+            }
+        }
+        Some(description)
+    }
 }
 
 /// Lookup the full response accompanying the specified transaction event
@@ -607,6 +972,10 @@ pub async fn query_tx_response<C: crate::ledger::queries::Client + Sync>(
             event_map["initialized_accounts"],
         )
         .unwrap_or_default(),
+        memo: event_map.get("memo").map(|memo| memo.to_string()),
+        details: event_map
+            .get(attributes::DETAILS)
+            .and_then(|details| serde_json::from_str(details).ok()),
     };
     Ok(result)
 }
@@ -626,6 +995,7 @@ pub async fn get_proposal_votes<C: crate::ledger::queries::Client + Sync>(
 
     let mut yay_validators: HashMap<Address, (VotePower, ProposalVote)> =
         HashMap::new();
+    let mut nay_validators: HashMap<Address, VotePower> = HashMap::new();
     let mut delegators: HashMap<
         Address,
         HashMap<Address, (VotePower, ProposalVote)>,
@@ -636,12 +1006,16 @@ pub async fn get_proposal_votes<C: crate::ledger::queries::Client + Sync>(
             let voter_address = gov_storage::get_voter_address(&key)
                 .expect("Vote key should contain the voting address.")
                 .clone();
-            if vote.is_yay() && validators.contains(&voter_address) {
+            if validators.contains(&voter_address) {
                 let amount: VotePower =
                     get_validator_stake(client, epoch, &voter_address)
                         .await
                         .into();
-                yay_validators.insert(voter_address, (amount, vote));
+                if vote.is_yay() {
+                    yay_validators.insert(voter_address, (amount, vote));
+                } else {
+                    nay_validators.insert(voter_address, amount);
+                }
             } else if !validators.contains(&voter_address) {
                 let validator_address =
                     gov_storage::get_vote_delegation_address(&key)
@@ -669,6 +1043,7 @@ pub async fn get_proposal_votes<C: crate::ledger::queries::Client + Sync>(
 
     Votes {
         yay_validators,
+        nay_validators,
         delegators,
     }
 }
@@ -805,6 +1180,17 @@ pub async fn query_withdrawable_tokens<
     )
 }
 
+/// Query the rewards of a bond which can be claimed in the current epoch
+pub async fn query_rewards<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    source: &Option<Address>,
+    validator: &Address,
+) -> token::Amount {
+    unwrap_client_response::<C, token::Amount>(
+        RPC.vp().pos().rewards(client, validator, source).await,
+    )
+}
+
 /// Query all unbonds for a validator, applying slashes
 pub async fn query_unbond_with_slashing<
     C: crate::ledger::queries::Client + Sync,
@@ -832,6 +1218,11 @@ pub async fn get_governance_parameters<
         .await
         .expect("Parameter should be definied.");
 
+    let key = gov_storage::get_max_proposal_code_gas_key();
+    let max_proposal_code_gas = query_storage_value::<C, u64>(client, &key)
+        .await
+        .expect("Parameter should be definied.");
+
     let key = gov_storage::get_max_proposal_content_key();
     let max_proposal_content_size = query_storage_value::<C, u64>(client, &key)
         .await
@@ -847,6 +1238,11 @@ pub async fn get_governance_parameters<
         .await
         .expect("Parameter should be definied.");
 
+    let key = gov_storage::get_min_proposal_quorum_key();
+    let min_proposal_quorum = query_storage_value::<C, Decimal>(client, &key)
+        .await
+        .expect("Parameter should be definied.");
+
     let key = gov_storage::get_min_proposal_period_key();
     let min_proposal_period = query_storage_value::<C, u64>(client, &key)
         .await
@@ -858,12 +1254,16 @@ pub async fn get_governance_parameters<
         .expect("Parameter should be definied.");
 
     GovParams {
-        min_proposal_fund: u64::from(min_proposal_fund),
+        min_proposal_fund: min_proposal_fund
+            .checked_to_u64()
+            .expect("The min proposal fund should fit in a u64"),
         max_proposal_code_size,
+        max_proposal_code_gas,
         min_proposal_period,
         max_proposal_period,
         max_proposal_content_size,
         min_proposal_grace_epochs,
+        min_proposal_quorum,
     }
 }
 
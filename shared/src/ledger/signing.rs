@@ -38,7 +38,7 @@ use crate::ledger::tx::{
 pub use crate::ledger::wallet::store::AddressVpType;
 use crate::ledger::wallet::{Wallet, WalletUtils};
 use crate::ledger::{args, rpc};
-use crate::proto::{Section, Signature, Tx};
+use crate::proto::{Memo, Section, Signature, Tx};
 use crate::types::key::*;
 use crate::types::masp::{ExtendedViewingKey, PaymentAddress};
 use crate::types::storage::Epoch;
@@ -48,7 +48,7 @@ use crate::types::transaction::governance::{
     InitProposalData, VoteProposalData,
 };
 use crate::types::transaction::{
-    Fee, InitAccount, InitValidator, TxType, UpdateVp, WrapperTx,
+    Fee, GasLimit, InitAccount, InitValidator, TxType, UpdateVp, WrapperTx,
 };
 
 #[cfg(feature = "std")]
@@ -175,6 +175,23 @@ pub async fn tx_signer<
     }
 }
 
+/// Sign the given section hash with the given key and return a section, in
+/// the signing mode requested by the tx args. Only secp256k1 keys can sign
+/// the EIP-191 personal messages of hashes, e.g. when held by Metamask.
+pub fn signature_section(
+    args: &args::Tx,
+    target: &crate::types::hash::Hash,
+    keypair: &common::SecretKey,
+) -> Result<Signature, Error> {
+    match keypair {
+        _ if !args.eip191 => Ok(Signature::new(target, keypair)),
+        common::SecretKey::Secp256k1(_) => {
+            Ok(Signature::new_eip191(target, keypair))
+        }
+        _ => Err(Error::Eip191Key(keypair.ref_to())),
+    }
+}
+
 /// Sign a transaction with a given signing key or public key of a given signer.
 /// If no explicit signer given, use the `default`. If no `default` is given,
 /// Error.
@@ -196,15 +213,17 @@ pub async fn sign_tx<
 ) -> Result<TxBroadcastData, Error> {
     let keypair = tx_signer::<C, U>(client, wallet, args, default).await?;
     // Sign over the transacttion data
-    tx.add_section(Section::Signature(Signature::new(
+    tx.add_section(Section::Signature(signature_section(
+        args,
         tx.data_sechash(),
         &keypair,
-    )));
+    )?));
     // Sign over the transaction code
-    tx.add_section(Section::Signature(Signature::new(
+    tx.add_section(Section::Signature(signature_section(
+        args,
         tx.code_sechash(),
         &keypair,
-    )));
+    )?));
 
     let epoch = rpc::query_epoch(client).await;
 
@@ -260,13 +279,22 @@ pub async fn sign_wrapper<
         .unwrap_or_default()
     };
     let fee_token = &args.fee_token;
-    let source = Address::from(&keypair.ref_to());
+    // The fee is paid by the established fee payer account if any, or else
+    // by the sponsor, if any
+    let source = match &args.fee_payer {
+        Some(fee_payer) => fee_payer.clone(),
+        None => Address::from(
+            &args.fee_sponsor.as_ref().unwrap_or(keypair).ref_to(),
+        ),
+    };
     let balance_key = token::balance_key(fee_token, &source);
     let balance =
         rpc::query_storage_value::<C, token::Amount>(client, &balance_key)
             .await
             .unwrap_or_default();
-    let is_bal_sufficient = fee_amount <= balance;
+    // The funds unshielded before charging the fee must cover it
+    let is_bal_sufficient =
+        args.fee_unshield.is_some() || fee_amount <= balance;
     if !is_bal_sufficient {
         eprintln!(
             "The wrapper transaction source doesn't have enough balance to \
@@ -301,25 +329,66 @@ pub async fn sign_wrapper<
         }
     };
 
+    let resolution = rpc::query_gas_limit_resolution(client).await;
     // This object governs how the payload will be processed
-    tx.update_header(TxType::Wrapper(Box::new(WrapperTx::new(
+    let mut wrapper = WrapperTx::new(
         Fee {
             amount: fee_amount,
             token: fee_token.clone(),
         },
         keypair,
         epoch,
-        args.gas_limit.clone(),
+        GasLimit::from_raw(args.gas_limit, resolution),
         #[cfg(not(feature = "mainnet"))]
         pow_solution,
-    ))));
+    );
+    if let Some(gas_price) = args.gas_price {
+        wrapper = wrapper.with_gas_price(gas_price);
+    }
+    if let Some(height) = args.expiration_height {
+        wrapper = wrapper.with_expiration_height(height);
+    }
+    if let Some(nonce) = args.nonce {
+        wrapper = wrapper.with_nonce(nonce);
+    }
+    if let Some(sponsor) = &args.fee_sponsor {
+        wrapper = wrapper.with_sponsor(sponsor.ref_to());
+    }
+    if let Some(fee_payer) = &args.fee_payer {
+        wrapper = wrapper.with_fee_payer_address(fee_payer.clone());
+    }
+    if let Some(unshield) = args.fee_unshield {
+        wrapper = wrapper.with_unshield(unshield);
+    }
+    tx.update_header(TxType::Wrapper(Box::new(wrapper)));
     tx.header.chain_id = args.chain_id.clone().unwrap();
     tx.header.expiration = args.expiration;
+    if let Some(memo) = &args.memo {
+        tx.set_memo(Memo::new(memo.clone()));
+    }
     // Then sign over the bound wrapper
-    tx.add_section(Section::Signature(Signature::new(
-        &tx.header_hash(),
-        keypair,
-    )));
+    tx.add_section(Section::Signature(
+        signature_section(args, &tx.header_hash(), keypair)
+            .expect("The signing key should have been checked"),
+    ));
+    // The sponsor must consent to paying the fee
+    if let Some(sponsor) = &args.fee_sponsor {
+        tx.add_section(Section::Signature(Signature::new(
+            &tx.header_hash(),
+            sponsor,
+        )));
+    }
+    // The established fee payer account must authorize paying the fee
+    if let Some(fee_payer) = &args.fee_payer {
+        let fee_payer_keypair =
+            find_keypair(client, wallet, fee_payer, args.password.clone())
+                .await
+                .expect("Unable to load the keypair of the fee payer");
+        tx.add_section(Section::Signature(Signature::new(
+            &tx.header_hash(),
+            &fee_payer_keypair,
+        )));
+    }
 
     #[cfg(feature = "std")]
     // Attempt to decode the construction
@@ -377,6 +446,108 @@ pub async fn sign_wrapper<
     }
 }
 
+/// Bump the fee of the pending wrapper `tx` in the mempool, whose hash is the
+/// one returned when it was signed, by wrapping its inner tx again with the
+/// given gas price. The inner tx is reused as is, still encrypted, and the new
+/// wrapper is signed with the keys of the signer, sponsor and fee payer of the
+/// pending wrapper, found in the wallet.
+///
+/// The new gas price must be at least the
+/// [`WrapperTx::min_replacement_gas_price`] of the pending wrapper, for the
+/// new wrapper to replace it in the mempool.
+pub async fn bump_fee<
+    C: crate::ledger::queries::Client + Sync,
+    U: WalletUtils,
+>(
+    client: &C,
+    wallet: &mut Wallet<U>,
+    args: &args::Tx,
+    mut tx: Tx,
+    new_gas_price: Amount,
+) -> Result<TxBroadcastData, Error> {
+    let pending = tx.header().wrapper().ok_or_else(|| {
+        Error::Other("Only the fee of a wrapper tx can be bumped".to_string())
+    })?;
+    let min_gas_price =
+        pending.min_replacement_gas_price().ok_or_else(|| {
+            Error::Other(
+                "The gas price of the pending wrapper can't be raised"
+                    .to_string(),
+            )
+        })?;
+    if new_gas_price < min_gas_price {
+        return other_err(format!(
+            "The gas price {} is lower than the minimum {} to replace the \
+             pending wrapper",
+            new_gas_price, min_gas_price
+        ));
+    }
+
+    // Drop the signatures over the header of the pending wrapper
+    let pending_hash = tx.header_hash();
+    tx.sections.retain(|section| match section {
+        Section::Signature(sig) => *sig.target() != pending_hash,
+        _ => true,
+    });
+    let pk = pending.pk.clone();
+    let sponsor = pending.sponsor.clone();
+    let fee_payer = pending.fee_payer_address.clone();
+    tx.update_header(TxType::Wrapper(Box::new(
+        pending.with_gas_price(new_gas_price),
+    )));
+
+    // Sign over the bound wrapper again
+    let keypair =
+        wallet
+            .find_key_by_pk(&pk, args.password.clone())
+            .map_err(|err| {
+                Error::Other(format!(
+                    "Unable to load the keypair of the wrapper signer {}: {}",
+                    pk, err
+                ))
+            })?;
+    tx.add_section(Section::Signature(signature_section(
+        args,
+        &tx.header_hash(),
+        &keypair,
+    )?));
+    if let Some(sponsor) = sponsor {
+        let sponsor = wallet
+            .find_key_by_pk(&sponsor, args.password.clone())
+            .map_err(|err| {
+                Error::Other(format!(
+                    "Unable to load the keypair of the fee sponsor {}: {}",
+                    sponsor, err
+                ))
+            })?;
+        tx.add_section(Section::Signature(Signature::new(
+            &tx.header_hash(),
+            &sponsor,
+        )));
+    }
+    if let Some(fee_payer) = fee_payer {
+        let fee_payer_keypair =
+            find_keypair(client, wallet, &fee_payer, args.password.clone())
+                .await?;
+        tx.add_section(Section::Signature(Signature::new(
+            &tx.header_hash(),
+            &fee_payer_keypair,
+        )));
+    }
+
+    let wrapper_hash = tx.header_hash().to_string();
+    let decrypted_hash = tx
+        .clone()
+        .update_header(TxType::Raw)
+        .header_hash()
+        .to_string();
+    Ok(TxBroadcastData::Wrapper {
+        tx,
+        wrapper_hash,
+        decrypted_hash,
+    })
+}
+
 fn other_err<T>(string: String) -> Result<T, Error> {
     Err(Error::Other(string))
 }
@@ -1035,11 +1206,16 @@ pub async fn to_ledger_vector<
     }
 
     if let Some(wrapper) = tx.header.wrapper() {
+        let resolution = rpc::query_gas_limit_resolution(client).await;
+        let gas_limit = wrapper
+            .gas_limit
+            .checked_raw(resolution)
+            .ok_or_else(|| std::io::Error::from(ErrorKind::InvalidData))?;
         tv.output_expert.extend(vec![
             format!("Timestamp : {}", tx.header.timestamp.0),
             format!("PK : {}", wrapper.pk),
             format!("Epoch : {}", wrapper.epoch),
-            format!("Gas limit : {}", Amount::from(wrapper.gas_limit)),
+            format!("Gas limit : {}", gas_limit),
             format!("Fee token : {}", wrapper.fee.token),
         ]);
         if let Some(token) = tokens.get(&wrapper.fee.token) {
@@ -1049,6 +1225,19 @@ pub async fn to_ledger_vector<
             tv.output_expert
                 .push(format!("Fee amount : {}", wrapper.fee.amount));
         }
+        if let Some(gas_price) = wrapper.gas_price {
+            tv.output_expert.push(format!("Gas price : {}", gas_price));
+        }
+        if let Some(height) = wrapper.expiration_height {
+            tv.output_expert
+                .push(format!("Expiration height : {}", height));
+        }
+        if let Some(sponsor) = &wrapper.sponsor {
+            tv.output_expert.push(format!("Fee sponsor : {}", sponsor));
+        }
+        if let Some(fee_payer) = &wrapper.fee_payer_address {
+            tv.output_expert.push(format!("Fee payer : {}", fee_payer));
+        }
     }
 
     // Finally, index each line and break those that are too long
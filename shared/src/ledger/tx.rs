@@ -15,7 +15,13 @@ use masp_primitives::transaction::components::transparent::fees::{
     InputView as TransparentInputView, OutputView as TransparentOutputView,
 };
 use masp_primitives::transaction::components::Amount;
-use namada_core::types::address::{masp, masp_tx_key, Address};
+use masp_primitives::transaction::Transaction;
+use namada_core::ledger::ibc::context::fee::{
+    MsgTransferWithFee, PacketFee, MSG_TRANSFER_WITH_FEE_TYPE_URL,
+};
+use namada_core::types::address::{
+    masp, masp_tx_key, Address, EstablishedAddressGen, InternalAddress,
+};
 use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::types::CommissionPair;
 use prost::EncodeError;
@@ -32,21 +38,28 @@ use crate::ibc::timestamp::Timestamp as IbcTimestamp;
 use crate::ibc::tx_msg::Msg;
 use crate::ibc::Height as IbcHeight;
 use crate::ibc_proto::cosmos::base::v1beta1::Coin;
-use crate::ledger::args;
+use crate::ibc_proto::google::protobuf::Any;
+use crate::ledger::args::{self, FeeSource};
 use crate::ledger::governance::storage as gov_storage;
+use crate::ledger::ibc::storage as ibc_storage;
 use crate::ledger::masp::{ShieldedContext, ShieldedUtils};
 use crate::ledger::rpc::{self, TxBroadcastData, TxResponse};
 use crate::ledger::signing::{find_keypair, sign_tx, tx_signer, TxSigningKey};
 use crate::ledger::wallet::{Wallet, WalletUtils};
-use crate::proto::{Code, Data, MaspBuilder, Section, Signature, Tx};
+use crate::proto::{Code, Data, MaspBuilder, Section, Tx};
 use crate::tendermint_rpc::endpoint::broadcast::tx_sync::Response;
 use crate::tendermint_rpc::error::Error as RpcError;
+use crate::types::eth_bridge_pool::{
+    GasFee, PendingTransfer, TransferToEthereum,
+};
 use crate::types::hash::Hash;
 use crate::types::key::*;
 use crate::types::masp::TransferTarget;
 use crate::types::storage::{Epoch, RESERVED_ADDRESS_PREFIX};
 use crate::types::time::DateTimeUtc;
-use crate::types::transaction::{pos, InitAccount, TxType, UpdateVp};
+use crate::types::transaction::{
+    pos, InitAccount, TxType, UpdateSigners, UpdateVp,
+};
 use crate::types::{storage, token};
 use crate::vm;
 use crate::vm::WasmValidationError;
@@ -63,18 +76,30 @@ pub const TX_VOTE_PROPOSAL: &str = "tx_vote_proposal.wasm";
 pub const TX_REVEAL_PK: &str = "tx_reveal_pk.wasm";
 /// Update validity predicate WASM path
 pub const TX_UPDATE_VP_WASM: &str = "tx_update_vp.wasm";
+/// Update multisig signers transaction WASM path
+pub const TX_UPDATE_SIGNERS_WASM: &str = "tx_update_signers.wasm";
 /// Transfer transaction WASM path
 pub const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
 /// IBC transaction WASM path
 pub const TX_IBC_WASM: &str = "tx_ibc.wasm";
 /// User validity predicate WASM path
 pub const VP_USER_WASM: &str = "vp_user.wasm";
+/// Multisig validity predicate WASM path
+pub const VP_MULTISIG_WASM: &str = "vp_multisig.wasm";
 /// Bond WASM path
 pub const TX_BOND_WASM: &str = "tx_bond.wasm";
 /// Unbond WASM path
 pub const TX_UNBOND_WASM: &str = "tx_unbond.wasm";
 /// Withdraw WASM path
 pub const TX_WITHDRAW_WASM: &str = "tx_withdraw.wasm";
+/// Redelegate WASM path
+pub const TX_REDELEGATE_WASM: &str = "tx_redelegate.wasm";
+/// Claim rewards WASM path
+pub const TX_CLAIM_REWARDS_WASM: &str = "tx_claim_rewards.wasm";
+/// Add a transfer to the bridge pool WASM path
+pub const TX_BRIDGE_POOL_WASM: &str = "tx_bridge_pool.wasm";
+/// Change consensus key WASM path
+pub const TX_CHANGE_CONSENSUS_KEY_WASM: &str = "tx_change_consensus_key.wasm";
 /// Change commission WASM path
 pub const TX_CHANGE_COMMISSION_WASM: &str =
     "tx_change_validator_commission.wasm";
@@ -125,12 +150,29 @@ pub enum Error {
     /// No bonds found
     #[error("No bonds found")]
     NoBondFound,
+    /// No rewards to claim
+    #[error("There are no rewards to claim in the current epoch {0}.")]
+    NoRewardsToClaim(Epoch),
     /// Lower bond amount than the unbond
     #[error(
         "The total bonds of the source {0} is lower than the amount to be \
          unbonded. Amount to unbond is {1} and the total bonds is {2}."
     )]
     LowerBondThanUnbond(Address, token::Amount, token::Amount),
+    /// Lower bond amount than the redelegation
+    #[error(
+        "The bond of the owner {0} is lower than the amount to be \
+         redelegated. Amount to redelegate is {1} and the bond is {2}."
+    )]
+    LowerBondThanRedelegation(Address, token::Amount, token::Amount),
+    /// Redelegation of a self-bond
+    #[error(
+        "The owner {0} is a validator. Self-bonds may not be redelegated."
+    )]
+    RedelegatorIsValidator(Address),
+    /// Redelegation to the same validator
+    #[error("The source and destination validators are both {0}.")]
+    RedelegationSrcEqDest(Address),
     /// Balance is too low
     #[error(
         "The balance of the source {0} of token {1} is lower than the amount \
@@ -165,6 +207,9 @@ pub enum Error {
         token::Amount,
         Address,
     ),
+    /// The fee token isn't whitelisted
+    #[error("Fees cannot be paid in the token {0}")]
+    FeeTokenNotWhitelisted(Address),
     /// No Balance found for token
     #[error("{0}")]
     MaspError(builder::Error<std::convert::Infallible>),
@@ -200,6 +245,24 @@ pub enum Error {
     /// Epoch not in storage
     #[error("Proposal end epoch is not in the storage.")]
     EpochNotInStorage,
+    /// Only secp256k1 keys can sign EIP-191 personal messages
+    #[error("EIP-191 signatures require a secp256k1 key, found {0}")]
+    Eip191Key(common::PublicKey),
+    /// Only established accounts can have a set of signers
+    #[error("The address {0} cannot have a set of signers")]
+    NoSigners(Address),
+    /// The threshold of a multisig account is out of range
+    #[error("The threshold {0} is invalid for {1} signers")]
+    InvalidThreshold(u8, usize),
+    /// No address with the vanity prefix was found
+    #[error(
+        "No address starting with {0} was found in {1} attempts, try a \
+         shorter prefix"
+    )]
+    VanityPrefixNotFound(String, u64),
+    /// An amount cannot be converted to the raw amount of its token
+    #[error("Invalid amount {0} of the token {1}: {2}")]
+    InvalidAmount(token::DenominatedAmount, Address, token::AmountParseError),
     /// Other Errors that may show up when using the interface
     #[error("{0}")]
     Other(String),
@@ -366,14 +429,16 @@ pub async fn submit_reveal_pk_aux<
     } else {
         find_keypair(client, wallet, &addr, args.password.clone()).await
     }?;
-    tx.add_section(Section::Signature(Signature::new(
+    tx.add_section(Section::Signature(super::signing::signature_section(
+        args,
         tx.data_sechash(),
         &keypair,
-    )));
-    tx.add_section(Section::Signature(Signature::new(
+    )?));
+    tx.add_section(Section::Signature(super::signing::signature_section(
+        args,
         tx.code_sechash(),
         &keypair,
-    )));
+    )?));
     let epoch = rpc::query_epoch(client).await;
     let to_broadcast = if args.dry_run {
         TxBroadcastData::DryRun(tx)
@@ -499,6 +564,9 @@ pub async fn submit_tx<C: crate::ledger::queries::Client + Sync>(
             "Transaction accepted with result: {}",
             serde_json::to_string_pretty(&parsed).unwrap()
         );
+        if let Some(error) = parsed.error_description() {
+            eprintln!("The transaction was not accepted. {}", error);
+        }
         // The transaction is now on chain. We wait for it to be decrypted
         // and applied
         if parsed.code == 0.to_string() {
@@ -513,6 +581,9 @@ pub async fn submit_tx<C: crate::ledger::queries::Client + Sync>(
                 "Transaction applied with result: {}",
                 serde_json::to_string_pretty(&parsed).unwrap()
             );
+            if let Some(error) = parsed.error_description() {
+                eprintln!("The transaction failed. {}", error);
+            }
             Ok(parsed)
         } else {
             Ok(parsed)
@@ -675,6 +746,218 @@ pub async fn submit_validator_commission_change<
     Ok(())
 }
 
+/// Submit transaction to redelegate bonded tokens from one validator to another
+pub async fn submit_redelegate<
+    C: crate::ledger::queries::Client + Sync,
+    U: WalletUtils,
+>(
+    client: &C,
+    wallet: &mut Wallet<U>,
+    args: args::Redelegate,
+) -> Result<(), Error> {
+    let owner = args.owner.clone();
+    if !args.tx.force {
+        if args.src_validator == args.dest_validator {
+            return Err(Error::RedelegationSrcEqDest(args.src_validator));
+        }
+        known_validator_or_err(
+            args.src_validator.clone(),
+            args.tx.force,
+            client,
+        )
+        .await?;
+        known_validator_or_err(
+            args.dest_validator.clone(),
+            args.tx.force,
+            client,
+        )
+        .await?;
+        if rpc::is_validator(client, &owner).await {
+            return Err(Error::RedelegatorIsValidator(owner));
+        }
+
+        let bond_amount =
+            rpc::query_bond(client, &owner, &args.src_validator, None).await;
+        println!(
+            "Bond amount available for redelegation: {} NAM",
+            bond_amount
+        );
+        if args.amount > bond_amount {
+            return Err(Error::LowerBondThanRedelegation(
+                owner,
+                args.amount,
+                bond_amount,
+            ));
+        }
+    }
+
+    let tx_code_hash =
+        query_wasm_code_hash(client, args.tx_code_path.to_str().unwrap())
+            .await
+            .unwrap();
+
+    let data = pos::Redelegation {
+        src_validator: args.src_validator.clone(),
+        dest_validator: args.dest_validator.clone(),
+        owner: owner.clone(),
+        amount: args.amount,
+    };
+    let data = data.try_to_vec().map_err(Error::EncodeTxFailure)?;
+
+    let mut tx = Tx::new(TxType::Raw);
+    tx.header.chain_id = args.tx.chain_id.clone().unwrap();
+    tx.header.expiration = args.tx.expiration;
+    tx.set_data(Data::new(data));
+    tx.set_code(Code::from_hash(tx_code_hash));
+
+    process_tx::<C, U>(
+        client,
+        wallet,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(owner),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Submit transaction to rotate the consensus key of a validator
+pub async fn submit_change_consensus_key<
+    C: crate::ledger::queries::Client + Sync,
+    U: WalletUtils,
+>(
+    client: &C,
+    wallet: &mut Wallet<U>,
+    args: args::TxChangeConsensusKey,
+) -> Result<(), Error> {
+    let validator = args.validator.clone();
+    if !rpc::is_validator(client, &validator).await {
+        eprintln!("The given address {validator} is not a validator.");
+        if !args.tx.force {
+            return Err(Error::InvalidValidatorAddress(validator));
+        }
+    }
+
+    let tx_code_hash =
+        query_wasm_code_hash(client, args.tx_code_path.to_str().unwrap())
+            .await
+            .unwrap();
+
+    let data = pos::ConsensusKeyChange {
+        validator: args.validator.clone(),
+        consensus_key: args.consensus_key.clone(),
+    };
+    let data = data.try_to_vec().map_err(Error::EncodeTxFailure)?;
+
+    let mut tx = Tx::new(TxType::Raw);
+    tx.header.chain_id = args.tx.chain_id.clone().unwrap();
+    tx.header.expiration = args.tx.expiration;
+    tx.set_data(Data::new(data));
+    tx.set_code(Code::from_hash(tx_code_hash));
+
+    let default_signer = args.validator.clone();
+    process_tx::<C, U>(
+        client,
+        wallet,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(default_signer),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await?;
+    println!(
+        "The consensus key {} must be given to the validator's node before \
+         the pipeline epoch, from which it is used.",
+        args.consensus_key
+    );
+    Ok(())
+}
+
+/// Submit transaction to add a transfer to Ethereum to the bridge pool
+pub async fn submit_bridge_pool_tx<
+    C: crate::ledger::queries::Client + Sync,
+    U: WalletUtils,
+>(
+    client: &C,
+    wallet: &mut Wallet<U>,
+    args: args::EthereumBridgePool,
+) -> Result<(), Error> {
+    let sender =
+        source_exists_or_err(args.sender.clone(), args.tx.force, client)
+            .await?;
+    let gas_payer =
+        source_exists_or_err(args.gas_payer.clone(), args.tx.force, client)
+            .await?;
+
+    // Check that the escrowed tokens are available
+    let wrapped_token = args.asset.wrapped_token();
+    let balance_key = token::balance_key(&wrapped_token, &sender);
+    check_balance_too_low_err(
+        &wrapped_token,
+        &sender,
+        args.amount,
+        balance_key,
+        args.tx.force,
+        client,
+    )
+    .await?;
+    let balance_key = token::balance_key(&args.native_token, &gas_payer);
+    check_balance_too_low_err(
+        &args.native_token,
+        &gas_payer,
+        args.gas_amount,
+        balance_key,
+        args.tx.force,
+        client,
+    )
+    .await?;
+
+    let tx_code_hash =
+        query_wasm_code_hash(client, args.tx_code_path.to_str().unwrap())
+            .await
+            .unwrap();
+
+    let transfer = PendingTransfer {
+        transfer: TransferToEthereum {
+            asset: args.asset,
+            recipient: args.recipient,
+            sender: sender.clone(),
+            amount: args.amount,
+        },
+        gas_fee: GasFee {
+            amount: args.gas_amount,
+            payer: gas_payer,
+        },
+    };
+    let data = transfer.try_to_vec().map_err(Error::EncodeTxFailure)?;
+
+    let mut tx = Tx::new(TxType::Raw);
+    tx.header.chain_id = args.tx.chain_id.clone().unwrap();
+    tx.header.expiration = args.tx.expiration;
+    tx.set_data(Data::new(data));
+    tx.set_code(Code::from_hash(tx_code_hash));
+
+    process_tx::<C, U>(
+        client,
+        wallet,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(sender),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await?;
+    println!(
+        "The transfer {} is pending in the bridge pool until it is relayed \
+         to Ethereum.",
+        transfer.hash()
+    );
+    Ok(())
+}
+
 /// Submit transaction to unjail a jailed validator
 pub async fn submit_unjail_validator<
     C: crate::ledger::queries::Client + Sync,
@@ -790,6 +1073,66 @@ pub async fn submit_withdraw<
     Ok(())
 }
 
+/// Submit a transaction to claim the rewards of a bond
+pub async fn submit_claim_rewards<
+    C: crate::ledger::queries::Client + Sync,
+    U: WalletUtils,
+>(
+    client: &C,
+    wallet: &mut Wallet<U>,
+    args: args::ClaimRewards,
+) -> Result<(), Error> {
+    let epoch = rpc::query_epoch(client).await;
+
+    let validator =
+        known_validator_or_err(args.validator.clone(), args.tx.force, client)
+            .await?;
+
+    let source = args.source.clone();
+
+    let tx_code_hash =
+        query_wasm_code_hash(client, args.tx_code_path.to_str().unwrap())
+            .await
+            .unwrap();
+
+    // Check the rewards of the bond which can be claimed
+    let rewards = rpc::query_rewards(client, &source, &validator).await;
+    if rewards == 0.into() {
+        eprintln!(
+            "There are no rewards to claim in the current epoch {}.",
+            epoch
+        );
+        if !args.tx.force {
+            return Err(Error::NoRewardsToClaim(epoch));
+        }
+    } else {
+        println!("Found {rewards} tokens of rewards that can be claimed.");
+        println!("Submitting transaction to claim them...");
+    }
+
+    let data = pos::ClaimRewards { validator, source };
+    let data = data.try_to_vec().map_err(Error::EncodeTxFailure)?;
+
+    let mut tx = Tx::new(TxType::Raw);
+    tx.header.chain_id = args.tx.chain_id.clone().unwrap();
+    tx.header.expiration = args.tx.expiration;
+    tx.set_data(Data::new(data));
+    tx.set_code(Code::from_hash(tx_code_hash));
+
+    let default_signer = args.source.unwrap_or(args.validator);
+    process_tx::<C, U>(
+        client,
+        wallet,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(default_signer),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await?;
+    Ok(())
+}
+
 /// Submit a transaction to unbond
 pub async fn submit_unbond<
     C: crate::ledger::queries::Client + Sync,
@@ -1035,8 +1378,32 @@ pub async fn submit_ibc_transfer<
 
     let token = token_exists_or_err(args.token, args.tx.force, client).await?;
 
+    // A token received over IBC can be given by its IBC token address, which
+    // is sent from the sub prefix of its original denom
+    let (token, sub_prefix) = match (&token, args.sub_prefix) {
+        (Address::Internal(InternalAddress::IbcToken(hash)), None) => {
+            match rpc::query_ibc_denom(client, hash).await {
+                Some(denom) => {
+                    let sub_prefix = format!(
+                        "{}/{}{}",
+                        ibc_storage::MULTITOKEN_STORAGE_KEY,
+                        RESERVED_ADDRESS_PREFIX,
+                        token
+                    );
+                    let base_token = ibc_storage::token(&denom)
+                        .map_err(|e| Error::Other(e.to_string()))?;
+                    (base_token, Some(sub_prefix))
+                }
+                // a token originating from another chain, which was never
+                // received, has no balance to send
+                None => (token, None),
+            }
+        }
+        (_, sub_prefix) => (token, sub_prefix),
+    };
+
     // Check source balance
-    let (sub_prefix, balance_key) = match args.sub_prefix {
+    let (sub_prefix, balance_key) = match sub_prefix {
         Some(sub_prefix) => {
             let sub_prefix = storage::Key::parse(sub_prefix).unwrap();
             let prefix = token::multitoken_balance_prefix(&token, &sub_prefix);
@@ -1107,6 +1474,37 @@ pub async fn submit_ibc_transfer<
     prost::Message::encode(&any_msg, &mut data)
         .map_err(Error::EncodeFailure)?;
 
+    // attach the fees of the relayers, which are paid by the source
+    let fee = PacketFee {
+        token: args.relayer_fee_token.unwrap_or(args.native_token),
+        recv_fee: args.recv_fee,
+        ack_fee: args.ack_fee,
+        timeout_fee: args.timeout_fee,
+    };
+    if fee.total() != token::Amount::default() {
+        check_balance_too_low_err(
+            &fee.token,
+            &source,
+            fee.total(),
+            token::balance_key(&fee.token, &source),
+            args.tx.force,
+            client,
+        )
+        .await?;
+        tracing::debug!("IBC relayer fee {:?}", fee);
+        let msg = MsgTransferWithFee {
+            transfer: data,
+            fee,
+        };
+        let any_msg = Any {
+            type_url: MSG_TRANSFER_WITH_FEE_TYPE_URL.to_string(),
+            value: msg.try_to_vec().map_err(Error::EncodeTxFailure)?,
+        };
+        data = vec![];
+        prost::Message::encode(&any_msg, &mut data)
+            .map_err(Error::EncodeFailure)?;
+    }
+
     let mut tx = Tx::new(TxType::Raw);
     tx.header.chain_id = args.tx.chain_id.clone().unwrap();
     tx.header.expiration = args.tx.expiration;
@@ -1199,6 +1597,102 @@ async fn used_asset_types<
     Ok(asset_types)
 }
 
+/// Convert a denominated amount of the given token to its raw amount, with the
+/// decimal places of the token's metadata, or with the default decimal places
+/// of amounts if the token has no metadata.
+pub async fn denominate_amount<C: crate::ledger::queries::Client + Sync>(
+    client: &C,
+    token: &Address,
+    amount: &token::DenominatedAmount,
+) -> Result<token::Amount, Error> {
+    let raw_amount = match rpc::query_token_metadata(client, token).await {
+        Some(metadata) => amount.to_amount(&metadata),
+        None => amount.to_raw(token::MAX_DECIMAL_PLACES as u8),
+    };
+    raw_amount
+        .map_err(|err| Error::InvalidAmount(amount.clone(), token.clone(), err))
+}
+
+/// Make the MASP transaction unshielding the fee of the wrapper of a transfer
+/// from its spending key to the transparent fee payer, if required by the fee
+/// source of the transfer. The notes spent by the shielded part of the
+/// transfer, `spent`, are left to it.
+async fn gen_fee_unshielding<
+    C: crate::ledger::queries::Client + Sync,
+    U: ShieldedUtils<C = C>,
+>(
+    client: &C,
+    shielded: &mut ShieldedContext<U>,
+    args: &args::TxTransfer,
+    signer: &common::PublicKey,
+    spent: &Transaction,
+) -> Result<Option<Transaction>, Error> {
+    if args.fee_source == FeeSource::Transparent {
+        return Ok(None);
+    }
+    // The fee is paid by the established fee payer account if any, or else
+    // by the sponsor or the signer of the wrapper
+    let fee_payer = match &args.tx.fee_payer {
+        Some(fee_payer) => fee_payer.clone(),
+        None => Address::from(
+            &args
+                .tx
+                .fee_sponsor
+                .as_ref()
+                .map_or_else(|| signer.clone(), |sponsor| sponsor.ref_to()),
+        ),
+    };
+    let fee_token = &args.tx.fee_token;
+    let fees = rpc::query_wrapper_fees(
+        client,
+        &args.native_token,
+        fee_token,
+        args.tx.gas_limit,
+        args.tx.gas_price,
+    )
+    .await
+    .ok_or_else(|| Error::FeeTokenNotWhitelisted(fee_token.clone()))?;
+    if args.fee_source == FeeSource::Auto {
+        let balance_key = token::balance_key(fee_token, &fee_payer);
+        let balance =
+            rpc::query_storage_value::<C, token::Amount>(client, &balance_key)
+                .await
+                .unwrap_or_default();
+        if fees <= balance {
+            return Ok(None);
+        }
+    }
+
+    let unshield_args = args::TxTransfer {
+        target: TransferTarget::Address(fee_payer),
+        token: fee_token.clone(),
+        sub_prefix: None,
+        ..args.clone()
+    };
+    // Build the unshielding with the notes that the transfer doesn't spend
+    shielded.mark_spent(spent);
+    let unshielding = shielded
+        .build_shielded_transfer(client, unshield_args, fees, 0.into(), false)
+        .await;
+    // The transfer may not be applied, so forget about its spent notes
+    let _ = shielded.load().await;
+    match unshielding {
+        Ok(unshielding) => {
+            Ok(unshielding.map(|(_builder, unshielding, _, _)| unshielding))
+        }
+        Err(builder::Error::InsufficientFunds(_)) => {
+            Err(Error::NegativeBalanceAfterTransfer(
+                masp(),
+                0.into(),
+                fee_token.clone(),
+                fees,
+                fee_token.clone(),
+            ))
+        }
+        Err(err) => Err(Error::MaspError(err)),
+    }
+}
+
 /// Submit an ordinary transfer
 pub async fn submit_transfer<
     C: crate::ledger::queries::Client + Sync,
@@ -1220,6 +1714,11 @@ pub async fn submit_transfer<
     target_exists_or_err(target.clone(), args.tx.force, client).await?;
     // Check that the token address exists on chain
     token_exists_or_err(token.clone(), args.tx.force, client).await?;
+    let transfer_amount =
+        denominate_amount(client, &token, &args.amount).await?;
+    let fee_amount =
+        denominate_amount(client, &args.tx.fee_token, &args.tx.fee_amount)
+            .await?;
     // Check source balance
     let (sub_prefix, balance_key) = match &args.sub_prefix {
         Some(sub_prefix) => {
@@ -1235,7 +1734,7 @@ pub async fn submit_transfer<
     check_balance_too_low_err::<C>(
         &token,
         &source,
-        args.amount,
+        transfer_amount,
         balance_key,
         args.tx.force,
         client,
@@ -1258,13 +1757,13 @@ pub async fn submit_transfer<
         } else if source == masp_addr {
             (
                 TxSigningKey::SecretKey(masp_tx_key()),
-                args.amount,
+                transfer_amount,
                 token.clone(),
             )
         } else {
             (
                 TxSigningKey::WalletAddress(args.source.effective_address()),
-                args.amount,
+                transfer_amount,
                 token,
             )
         };
@@ -1293,7 +1792,13 @@ pub async fn submit_transfer<
     for _ in 0..2 {
         // Construct the shielded part of the transaction, if any
         let stx_result = shielded
-            .gen_shielded_transfer(client, args.clone(), shielded_gas)
+            .gen_shielded_transfer(
+                client,
+                args.clone(),
+                transfer_amount,
+                fee_amount,
+                shielded_gas,
+            )
             .await;
 
         let shielded_parts = match stx_result {
@@ -1301,18 +1806,43 @@ pub async fn submit_transfer<
             Err(builder::Error::InsufficientFunds(_)) => {
                 Err(Error::NegativeBalanceAfterTransfer(
                     source.clone(),
-                    args.amount,
+                    transfer_amount,
                     token.clone(),
-                    args.tx.fee_amount,
+                    fee_amount,
                     args.tx.fee_token.clone(),
                 ))
             }
             Err(err) => Err(Error::MaspError(err)),
         }?;
 
+        // A transparent fee payer of a transfer from a spending key may have
+        // the fee unshielded from the spending key
+        let fee_unshielding = match &shielded_parts {
+            Some(shielded_parts) if source == masp_addr && !shielded_gas => {
+                gen_fee_unshielding(
+                    client,
+                    shielded,
+                    &args,
+                    &chosen_signer,
+                    &shielded_parts.1,
+                )
+                .await?
+            }
+            _ => None,
+        };
+
         let mut tx = Tx::new(TxType::Raw);
         tx.header.chain_id = args.tx.chain_id.clone().unwrap();
         tx.header.expiration = args.tx.expiration;
+        let mut tx_args = args.tx.clone();
+        // Add the MASP Transaction unshielding the fee, to be executed before
+        // the fee is charged
+        if let Some(fee_unshielding) = fee_unshielding {
+            let section = tx.add_section(Section::MaspTx(fee_unshielding));
+            tx_args.fee_unshield = Some(Hash(
+                section.hash(&mut Sha256::new()).finalize_reset().into(),
+            ));
+        }
         // Add the MASP Transaction and its Builder to facilitate validation
         let (masp_hash, shielded_tx_epoch) = if let Some(shielded_parts) =
             shielded_parts
@@ -1367,7 +1897,7 @@ pub async fn submit_transfer<
         let result = process_tx::<C, V>(
             client,
             wallet,
-            &args.tx,
+            &tx_args,
             tx,
             default_signer.clone(),
             #[cfg(not(feature = "mainnet"))]
@@ -1433,10 +1963,19 @@ pub async fn submit_init_account<
     let data = InitAccount {
         public_key,
         vp_code_hash: extra_hash,
+        cosigners: args.cosigners,
+        threshold: args.threshold,
     };
     let data = data.try_to_vec().map_err(Error::EncodeTxFailure)?;
-    tx.set_data(Data::new(data));
     tx.set_code(Code::from_hash(tx_code_hash));
+    let address = match &args.vanity_prefix {
+        Some(prefix) => set_data_with_vanity_address(&mut tx, data, prefix)?,
+        None => {
+            tx.set_data(Data::new(data));
+            init_account_address(&tx)
+        }
+    };
+    println!("The account will be initialized with the address {address}.");
 
     // TODO Move unwrap to an either
     let initialized_accounts = process_tx::<C, U>(
@@ -1456,6 +1995,43 @@ pub async fn submit_init_account<
     Ok(())
 }
 
+/// The maximum number of txs tried to find an account address with a vanity
+/// prefix
+pub const VANITY_MAX_ATTEMPTS: u64 = 10_000_000;
+
+/// Pre-compute the address of the account initialized by the given tx, such as
+/// an `init-account` tx, before it's broadcast. The address only depends on
+/// the header of the tx, so it doesn't change when the tx is signed or
+/// wrapped.
+pub fn init_account_address(tx: &Tx) -> Address {
+    EstablishedAddressGen::for_tx(&tx.raw_header_hash()).generate_address(b"")
+}
+
+/// Set the data of the given tx with salts tried until the address of the
+/// account initialized by the tx starts with the given prefix. Returns the
+/// address.
+fn set_data_with_vanity_address(
+    tx: &mut Tx,
+    data: Vec<u8>,
+    prefix: &str,
+) -> Result<Address, Error> {
+    for _ in 0..VANITY_MAX_ATTEMPTS {
+        let section = Section::Data(Data::new(data.clone()));
+        tx.set_data_sechash(Hash(
+            section.hash(&mut Sha256::new()).finalize_reset().into(),
+        ));
+        let address = init_account_address(tx);
+        if address.encode().starts_with(prefix) {
+            tx.add_section(section);
+            return Ok(address);
+        }
+    }
+    Err(Error::VanityPrefixNotFound(
+        prefix.to_string(),
+        VANITY_MAX_ATTEMPTS,
+    ))
+}
+
 /// Submit a transaction to update a VP
 pub async fn submit_update_vp<
     C: crate::ledger::queries::Client + Sync,
@@ -1545,6 +2121,86 @@ pub async fn submit_update_vp<
     Ok(())
 }
 
+/// Submit a transaction to update the signers of a multisig account
+pub async fn submit_update_signers<
+    C: crate::ledger::queries::Client + Sync,
+    U: WalletUtils,
+>(
+    client: &C,
+    wallet: &mut Wallet<U>,
+    args: args::TxUpdateSigners,
+) -> Result<(), Error> {
+    let addr = args.addr.clone();
+
+    // Check that the address is established and exists on chain
+    match &addr {
+        Address::Established(_) => {
+            let exists = rpc::known_address::<C>(client, &addr).await;
+            if !exists {
+                if args.tx.force {
+                    eprintln!("The address {} doesn't exist on chain.", addr);
+                    Ok(())
+                } else {
+                    Err(Error::LocationDoesNotExist(addr.clone()))
+                }
+            } else {
+                Ok(())
+            }
+        }
+        Address::Implicit(_) | Address::Internal(_) => {
+            if args.tx.force {
+                eprintln!("The address {} cannot have a set of signers.", addr);
+                Ok(())
+            } else {
+                Err(Error::NoSigners(addr.clone()))
+            }
+        }
+    }?;
+    if args.threshold == 0 || args.threshold as usize > args.public_keys.len() {
+        if args.tx.force {
+            eprintln!(
+                "The threshold {} is invalid for {} signers.",
+                args.threshold,
+                args.public_keys.len()
+            );
+        } else {
+            return Err(Error::InvalidThreshold(
+                args.threshold,
+                args.public_keys.len(),
+            ));
+        }
+    }
+
+    let tx_code_hash =
+        query_wasm_code_hash(client, args.tx_code_path.to_str().unwrap())
+            .await
+            .unwrap();
+
+    let mut tx = Tx::new(TxType::Raw);
+    tx.header.chain_id = args.tx.chain_id.clone().unwrap();
+    tx.header.expiration = args.tx.expiration;
+    let data = UpdateSigners {
+        addr,
+        public_keys: args.public_keys,
+        threshold: args.threshold,
+    };
+    let data = data.try_to_vec().map_err(Error::EncodeTxFailure)?;
+    tx.set_data(Data::new(data));
+    tx.set_code(Code::from_hash(tx_code_hash));
+
+    process_tx::<C, U>(
+        client,
+        wallet,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(args.addr),
+        #[cfg(not(feature = "mainnet"))]
+        false,
+    )
+    .await?;
+    Ok(())
+}
+
 /// Submit a custom transaction
 pub async fn submit_custom<
     C: crate::ledger::queries::Client + Sync,
@@ -52,6 +52,15 @@ pub fn add_gas(gas_meter: &mut VpGasMeter, used_gas: u64) -> EnvResult<()> {
     result
 }
 
+/// Add the gas cost of a storage read, given the gas of the read in bytes
+pub fn add_storage_read_gas(
+    gas_meter: &mut VpGasMeter,
+    used_gas: u64,
+) -> EnvResult<()> {
+    let gas = gas_meter.gas_costs().storage_read(used_gas);
+    add_gas(gas_meter, gas)
+}
+
 /// Storage read prior state (before tx execution). It will try to read from the
 /// storage.
 pub fn read_pre<DB, H>(
@@ -65,7 +74,7 @@ where
     H: StorageHasher,
 {
     let (log_val, gas) = write_log.read_pre(key);
-    add_gas(gas_meter, gas)?;
+    add_storage_read_gas(gas_meter, gas)?;
     match log_val {
         Some(write_log::StorageModification::Write { ref value }) => {
             Ok(Some(value.clone()))
@@ -87,7 +96,7 @@ where
             // When not found in write log, try to read from the storage
             let (value, gas) =
                 storage.read(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas)?;
+            add_storage_read_gas(gas_meter, gas)?;
             Ok(value)
         }
     }
@@ -107,7 +116,7 @@ where
 {
     // Try to read from the write log first
     let (log_val, gas) = write_log.read(key);
-    add_gas(gas_meter, gas)?;
+    add_storage_read_gas(gas_meter, gas)?;
     match log_val {
         Some(write_log::StorageModification::Write { ref value }) => {
             Ok(Some(value.clone()))
@@ -129,7 +138,7 @@ where
             // When not found in write log, try to read from the storage
             let (value, gas) =
                 storage.read(key).map_err(RuntimeError::StorageError)?;
-            add_gas(gas_meter, gas)?;
+            add_storage_read_gas(gas_meter, gas)?;
             Ok(value)
         }
     }
@@ -290,7 +299,7 @@ pub fn get_tx_code_hash(
     let hash = tx
         .get_section(tx.code_sechash())
         .and_then(Section::code_sec)
-        .map(|x| x.code.hash());
+        .map(|x| x.code_hash());
     add_gas(gas_meter, MIN_STORAGE_GAS)?;
     Ok(hash)
 }
@@ -95,6 +95,26 @@ impl DerivationPath {
         Ok(Self(inner).hardened(scheme))
     }
 
+    /// Get the path `n` indexes after this one, at the same depth, e.g. the
+    /// path of the `n`th next address of a BIP44 account. Returns `None` if
+    /// the last index overflows.
+    pub fn nth_sibling(&self, n: u32) -> Option<Self> {
+        let mut indexes = self.0.path().to_vec();
+        if let Some(last) = indexes.last_mut() {
+            *last = match *last {
+                ChildIndex::Hardened(idx) => {
+                    ChildIndex::hardened(idx.checked_add(n)?).ok()?
+                }
+                ChildIndex::Normal(idx) => {
+                    ChildIndex::normal(idx.checked_add(n)?).ok()?
+                }
+            };
+        } else if n > 0 {
+            return None;
+        }
+        Some(Self::new(indexes))
+    }
+
     pub fn path(&self) -> &[ChildIndex] {
         self.0.path()
     }
@@ -156,4 +176,29 @@ mod tests {
         assert!(!path_nam.is_compatible(SchemeType::Secp256k1));
         assert!(path_nam.is_compatible(SchemeType::Common));
     }
+
+    #[test]
+    fn path_nth_sibling() {
+        let path = DerivationPath::default_for_scheme(SchemeType::Secp256k1);
+        assert_eq!(
+            path.nth_sibling(2).unwrap().to_string(),
+            "m/44'/60'/0'/0/2"
+        );
+        let path = DerivationPath::default_for_scheme(SchemeType::Ed25519);
+        assert_eq!(
+            path.nth_sibling(3).unwrap().to_string(),
+            "m/44'/877'/0'/0'/3'"
+        );
+        assert_eq!(path.nth_sibling(0).unwrap().to_string(), path.to_string());
+
+        let path = DerivationPath::from_path_str(SchemeType::Secp256k1, "m")
+            .expect("Path construction cannot fail.");
+        assert!(path.nth_sibling(1).is_none());
+        let path = DerivationPath::from_path_str(
+            SchemeType::Secp256k1,
+            "m/44'/60'/0'/0/2147483647",
+        )
+        .expect("Path construction cannot fail.");
+        assert!(path.nth_sibling(1).is_none());
+    }
 }
@@ -4,6 +4,7 @@ use std::fmt::Display;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
+use argon2::{Algorithm, Argon2, Params, Version};
 use borsh::{BorshDeserialize, BorshSerialize};
 use data_encoding::HEXLOWER;
 use orion::{aead, kdf};
@@ -11,10 +12,24 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use zeroize::Zeroizing;
 
+use crate::ledger::wallet::alias::Alias;
 use crate::ledger::wallet::WalletUtils;
 
 const ENCRYPTED_KEY_PREFIX: &str = "encrypted:";
 const UNENCRYPTED_KEY_PREFIX: &str = "unencrypted:";
+/// The prefix of the encoding of keypairs encrypted with Argon2id derived
+/// keys, which is followed by the Argon2id parameters and the hex encoded
+/// salt and ciphertext, e.g. `argon2id$m=19456,t=2,p=1$<hex>`. Keypairs
+/// encrypted in the legacy format are only hex encoded.
+const ARGON2ID_PREFIX: &str = "argon2id$";
+/// The maximum Argon2id memory size in KiB (4 GiB) accepted from a wallet,
+/// such that a tampered wallet cannot make the key derivation exhaust the
+/// memory
+const ARGON2ID_MAX_M_COST: u32 = 4 * 1024 * 1024;
+/// The maximum number of Argon2id iterations accepted from a wallet
+const ARGON2ID_MAX_T_COST: u32 = 64;
+/// The maximum degree of Argon2id parallelism accepted from a wallet
+const ARGON2ID_MAX_P_COST: u32 = 64;
 
 /// A keypair stored in a wallet
 #[derive(Debug)]
@@ -111,25 +126,124 @@ pub enum DeserializeStoredKeypairError {
     MissingPrefix,
 }
 
-/// An encrypted keypair stored in a wallet
+/// The function deriving the encryption key of a keypair from its password
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyDerivation {
+    /// Argon2i with fixed parameters, used by wallets before the encryption
+    /// of keys with Argon2id. Only supported for decryption.
+    Legacy,
+    /// Argon2id with the given parameters
+    Argon2id(Argon2idParams),
+}
+
+/// The parameters of Argon2id key derivations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2idParams {
+    /// Memory size in KiB
+    pub m_cost: u32,
+    /// Number of iterations
+    pub t_cost: u32,
+    /// Degree of parallelism
+    pub p_cost: u32,
+}
+
+impl Default for Argon2idParams {
+    /// The parameters recommended by OWASP for Argon2id
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+/// An encrypted keypair stored in a wallet, made of the salt of the
+/// encryption key followed by the encrypted keypair
 #[derive(Debug)]
-pub struct EncryptedKeypair<T: BorshSerialize + BorshDeserialize>(
-    Vec<u8>,
-    PhantomData<T>,
-);
+pub struct EncryptedKeypair<T: BorshSerialize + BorshDeserialize> {
+    kdf: KeyDerivation,
+    data: Vec<u8>,
+    phantom: PhantomData<T>,
+}
 
 impl<T: BorshSerialize + BorshDeserialize> Display for EncryptedKeypair<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", HEXLOWER.encode(self.0.as_ref()))
+        if let KeyDerivation::Argon2id(Argon2idParams {
+            m_cost,
+            t_cost,
+            p_cost,
+        }) = self.kdf
+        {
+            write!(f, "{ARGON2ID_PREFIX}m={m_cost},t={t_cost},p={p_cost}$")?;
+        }
+        write!(f, "{}", HEXLOWER.encode(self.data.as_ref()))
     }
 }
 
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ParseEncryptedKeypairError {
+    #[error("Invalid hex encoding: {0}")]
+    Hex(data_encoding::DecodeError),
+    #[error("Invalid Argon2id parameters: {0}")]
+    Params(String),
+}
+
 impl<T: BorshSerialize + BorshDeserialize> FromStr for EncryptedKeypair<T> {
-    type Err = data_encoding::DecodeError;
+    type Err = ParseEncryptedKeypairError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        HEXLOWER.decode(s.as_ref()).map(|x| Self(x, PhantomData))
+        let (kdf, data) = match s.strip_prefix(ARGON2ID_PREFIX) {
+            Some(rest) => {
+                let (params, data) = rest.split_once('$').ok_or_else(|| {
+                    ParseEncryptedKeypairError::Params(rest.to_string())
+                })?;
+                (
+                    KeyDerivation::Argon2id(parse_argon2id_params(params)?),
+                    data,
+                )
+            }
+            None => (KeyDerivation::Legacy, s),
+        };
+        let data = HEXLOWER
+            .decode(data.as_ref())
+            .map_err(ParseEncryptedKeypairError::Hex)?;
+        Ok(Self {
+            kdf,
+            data,
+            phantom: PhantomData,
+        })
+    }
+}
+
+/// Parse Argon2id parameters in the `m=<m_cost>,t=<t_cost>,p=<p_cost>` form
+fn parse_argon2id_params(
+    params: &str,
+) -> Result<Argon2idParams, ParseEncryptedKeypairError> {
+    let invalid = || ParseEncryptedKeypairError::Params(params.to_string());
+    let mut costs = params.split(',').map(|param| param.split_once('='));
+    let mut cost = |name: &str| -> Result<u32, ParseEncryptedKeypairError> {
+        match costs.next() {
+            Some(Some((param, value))) if param == name => {
+                value.parse().map_err(|_| invalid())
+            }
+            _ => Err(invalid()),
+        }
+    };
+    let params = Argon2idParams {
+        m_cost: cost("m")?,
+        t_cost: cost("t")?,
+        p_cost: cost("p")?,
+    };
+    if costs.next().is_some()
+        || params.m_cost > ARGON2ID_MAX_M_COST
+        || params.t_cost > ARGON2ID_MAX_T_COST
+        || params.p_cost > ARGON2ID_MAX_P_COST
+    {
+        return Err(invalid());
     }
+    Ok(params)
 }
 
 #[allow(missing_docs)]
@@ -137,6 +251,8 @@ impl<T: BorshSerialize + BorshDeserialize> FromStr for EncryptedKeypair<T> {
 pub enum DecryptionError {
     #[error("Unexpected encryption salt")]
     BadSalt,
+    #[error("Unsupported key derivation parameters")]
+    BadParams,
     #[error("Unable to decrypt the keypair. Is the password correct?")]
     DecryptionError,
     #[error("Unable to deserialize the keypair")]
@@ -193,13 +309,66 @@ where
             StoredKeypair::Raw(_) => false,
         }
     }
+
+    /// Indicates whether this key is encrypted with a legacy key derivation
+    /// and should be migrated
+    pub fn is_legacy(&self) -> bool {
+        match self {
+            StoredKeypair::Encrypted(encrypted) => {
+                encrypted.kdf == KeyDerivation::Legacy
+            }
+            StoredKeypair::Raw(_) => false,
+        }
+    }
+
+    /// Migrate this key, stored with the given alias, to the current format.
+    /// Keys encrypted with a legacy key derivation are re-encrypted with the
+    /// same password, which is prompted for. If `encrypt_raw` is set, raw
+    /// keys are encrypted with a newly prompted password. Returns the
+    /// migrated key, if any migration happened.
+    pub fn migrate<U: WalletUtils>(
+        &self,
+        alias: &Alias,
+        encrypt_raw: bool,
+    ) -> Result<Option<Self>, DecryptionError> {
+        match self {
+            StoredKeypair::Encrypted(encrypted)
+                if encrypted.kdf == KeyDerivation::Legacy =>
+            {
+                let password = U::read_key_decryption_password(alias);
+                let keypair = encrypted.decrypt(password.clone())?;
+                Ok(Some(Self::Encrypted(EncryptedKeypair::new(
+                    &keypair, password,
+                ))))
+            }
+            StoredKeypair::Raw(keypair) if encrypt_raw => {
+                let password = U::read_key_encryption_password(alias);
+                Ok(Some(Self::Encrypted(EncryptedKeypair::new(
+                    keypair, password,
+                ))))
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
-    /// Encrypt a keypair and store it with its salt.
+    /// Encrypt a keypair with an Argon2id derived key and store it with its
+    /// salt.
     pub fn new(keypair: &T, password: Zeroizing<String>) -> Self {
+        Self::new_with_params(keypair, password, Argon2idParams::default())
+    }
+
+    /// Encrypt a keypair with a key derived by Argon2id with the given
+    /// parameters and store it with its salt.
+    pub fn new_with_params(
+        keypair: &T,
+        password: Zeroizing<String>,
+        params: Argon2idParams,
+    ) -> Self {
         let salt = encryption_salt();
-        let encryption_key = encryption_key(&salt, &password);
+        let encryption_key = argon2id_encryption_key(&salt, &password, params)
+            .expect("Generation of encryption secret key shouldn't fail");
 
         let data = keypair
             .try_to_vec()
@@ -210,7 +379,11 @@ impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
 
         let encrypted_data = [salt.as_ref(), &encrypted_keypair].concat();
 
-        Self(encrypted_data, PhantomData)
+        Self {
+            kdf: KeyDerivation::Argon2id(params),
+            data: encrypted_data,
+            phantom: PhantomData,
+        }
     }
 
     /// Decrypt an encrypted keypair
@@ -219,12 +392,21 @@ impl<T: BorshSerialize + BorshDeserialize> EncryptedKeypair<T> {
         password: Zeroizing<String>,
     ) -> Result<T, DecryptionError> {
         let salt_len = encryption_salt().len();
-        let (raw_salt, cipher) = self.0.split_at(salt_len);
+        if self.data.len() < salt_len {
+            return Err(DecryptionError::BadSalt);
+        }
+        let (raw_salt, cipher) = self.data.split_at(salt_len);
 
         let salt = kdf::Salt::from_slice(raw_salt)
             .map_err(|_| DecryptionError::BadSalt)?;
 
-        let encryption_key = encryption_key(&salt, &password);
+        let encryption_key = match self.kdf {
+            KeyDerivation::Legacy => legacy_encryption_key(&salt, &password),
+            KeyDerivation::Argon2id(params) => {
+                argon2id_encryption_key(&salt, &password, params)
+                    .map_err(|_| DecryptionError::BadParams)?
+            }
+        };
 
         let decrypted_data = aead::open(&encryption_key, cipher)
             .map_err(|_| DecryptionError::DecryptionError)?;
@@ -239,9 +421,102 @@ fn encryption_salt() -> kdf::Salt {
     kdf::Salt::default()
 }
 
-/// Make encryption secret key from a password.
-fn encryption_key(salt: &kdf::Salt, password: &str) -> kdf::SecretKey {
+/// Make encryption secret key from a password with the Argon2i derivation of
+/// legacy wallets.
+fn legacy_encryption_key(salt: &kdf::Salt, password: &str) -> kdf::SecretKey {
     kdf::Password::from_slice(password.as_bytes())
         .and_then(|password| kdf::derive_key(&password, salt, 3, 1 << 17, 32))
         .expect("Generation of encryption secret key shouldn't fail")
 }
+
+/// Make encryption secret key from a password with Argon2id.
+fn argon2id_encryption_key(
+    salt: &kdf::Salt,
+    password: &str,
+    params: Argon2idParams,
+) -> Result<aead::SecretKey, argon2::Error> {
+    let params =
+        Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt.as_ref(), &mut *key)?;
+    Ok(aead::SecretKey::from_slice(&*key)
+        .expect("The size of the encryption secret key is valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::key::testing::keypair_1;
+    use crate::types::key::{common, RefTo};
+
+    /// Cheap parameters to keep the tests fast
+    const TEST_PARAMS: Argon2idParams = Argon2idParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    /// Test that keypairs encrypted with Argon2id round trip through their
+    /// encoding and only decrypt with their password
+    #[test]
+    fn test_argon2id_encrypted_keypair() {
+        let keypair: common::SecretKey = keypair_1();
+        let encrypted = EncryptedKeypair::new_with_params(
+            &keypair,
+            Zeroizing::new("password".to_string()),
+            TEST_PARAMS,
+        );
+        let encoded = encrypted.to_string();
+        assert!(encoded.starts_with("argon2id$m=64,t=1,p=1$"));
+
+        let decoded: EncryptedKeypair<common::SecretKey> =
+            encoded.parse().unwrap();
+        assert_eq!(decoded.kdf, KeyDerivation::Argon2id(TEST_PARAMS));
+        let decrypted = decoded
+            .decrypt(Zeroizing::new("password".to_string()))
+            .unwrap();
+        assert_eq!(decrypted.ref_to(), keypair.ref_to());
+        assert!(matches!(
+            decoded.decrypt(Zeroizing::new("wrong".to_string())),
+            Err(DecryptionError::DecryptionError)
+        ));
+    }
+
+    /// Test that keypairs encrypted in the legacy format are still decrypted
+    #[test]
+    fn test_legacy_encrypted_keypair() {
+        let keypair: common::SecretKey = keypair_1();
+        let salt = encryption_salt();
+        let encryption_key = legacy_encryption_key(&salt, "password");
+        let cipher =
+            aead::seal(&encryption_key, &keypair.try_to_vec().unwrap())
+                .unwrap();
+        let encoded = HEXLOWER.encode(&[salt.as_ref(), &cipher].concat());
+
+        let decoded: EncryptedKeypair<common::SecretKey> =
+            encoded.parse().unwrap();
+        assert_eq!(decoded.kdf, KeyDerivation::Legacy);
+        let decrypted = decoded
+            .decrypt(Zeroizing::new("password".to_string()))
+            .unwrap();
+        assert_eq!(decrypted.ref_to(), keypair.ref_to());
+        assert!(StoredKeypair::Encrypted(decoded).is_legacy());
+    }
+
+    /// Test that malformed Argon2id parameters are rejected
+    #[test]
+    fn test_invalid_argon2id_params() {
+        for params in [
+            "m=1,t=1",
+            "m=1,t=1,p=1,x=1",
+            "t=1,m=1,p=1",
+            "m=a,t=1,p=1",
+            "m=4294967295,t=1,p=1",
+            "m=64,t=4294967295,p=1",
+            "m=64,t=1,p=4294967295",
+        ] {
+            assert!(parse_argon2id_params(params).is_err(), "{params}");
+        }
+    }
+}
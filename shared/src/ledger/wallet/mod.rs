@@ -22,7 +22,8 @@ use zeroize::Zeroizing;
 use self::derivation_path::{DerivationPath, DerivationPathError};
 pub use self::keys::{DecryptionError, StoredKeypair};
 pub use self::store::{ConfirmationResponse, ValidatorData, ValidatorKeys};
-use crate::types::address::Address;
+use crate::types::address::{Address, DecodeError};
+use crate::types::address_book::AddressResolver;
 use crate::types::key::*;
 use crate::types::masp::{
     ExtendedSpendingKey, ExtendedViewingKey, PaymentAddress,
@@ -69,11 +70,27 @@ pub trait WalletUtils {
     /// Read the password for decryption from the file/env/stdin.
     fn read_decryption_password() -> Zeroizing<String>;
 
+    /// Read the password for decrypting the key with the given alias from
+    /// the file/env/stdin. Every key may be encrypted with its own password,
+    /// so implementations should say which key the password is for.
+    fn read_key_decryption_password(alias: &Alias) -> Zeroizing<String> {
+        let _ = alias;
+        Self::read_decryption_password()
+    }
+
     /// Read the password for encryption from the file/env/stdin.
     /// If the password is read from stdin, the implementation is expected
     /// to ask for a confirmation.
     fn read_encryption_password() -> Zeroizing<String>;
 
+    /// Read the password for encrypting the key with the given alias from
+    /// the file/env/stdin. Implementations should say which key the
+    /// password is for.
+    fn read_key_encryption_password(alias: &Alias) -> Zeroizing<String> {
+        let _ = alias;
+        Self::read_encryption_password()
+    }
+
     /// Read an alias from the file/env/stdin.
     fn read_alias(prompt_msg: &str) -> String;
 
@@ -163,6 +180,36 @@ impl<U: WalletUtils> Wallet<U> {
         derivation_path: Option<String>,
         password: Option<Zeroizing<String>>,
     ) -> Result<Option<(String, common::SecretKey)>, GenRestoreKeyError> {
+        self.derive_keys_from_user_mnemonic_code(
+            scheme,
+            alias,
+            alias_force,
+            derivation_path,
+            1,
+            password,
+        )
+        .map(|mut keys| keys.pop())
+    }
+
+    /// Restore `count` keypairs from the user mnemonic code (read from stdin)
+    /// at consecutive BIP44 address indexes, starting from the given
+    /// derivation path, such that a whole wallet can be restored from a
+    /// single mnemonic. The first key takes the provided alias and the next
+    /// ones take it suffixed by their index, e.g. `alias-1`. The keys whose
+    /// alias the user chose not to overwrite are skipped.
+    /// The keys are encrypted with the provided password. If no password
+    /// provided, will prompt for password from stdin.
+    /// Stores the keys in decrypted key cache and returns their aliases and
+    /// the keys.
+    pub fn derive_keys_from_user_mnemonic_code(
+        &mut self,
+        scheme: SchemeType,
+        alias: Option<String>,
+        alias_force: bool,
+        derivation_path: Option<String>,
+        count: u32,
+        password: Option<Zeroizing<String>>,
+    ) -> Result<Vec<(String, common::SecretKey)>, GenRestoreKeyError> {
         let parsed_derivation_path = derivation_path
             .map(|p| {
                 let is_default = p.eq_ignore_ascii_case("DEFAULT");
@@ -181,18 +228,37 @@ impl<U: WalletUtils> Wallet<U> {
                  with the chosen cryptography scheme."
             )
         }
-        println!("Using HD derivation path {}", parsed_derivation_path);
         let mnemonic = U::read_mnemonic_code()?;
         let passphrase = U::read_mnemonic_passphrase(false);
         let seed = Seed::new(&mnemonic, &passphrase);
 
-        Ok(self.gen_and_store_key(
-            scheme,
-            alias,
-            alias_force,
-            Some((seed, parsed_derivation_path)),
-            password,
-        ))
+        let mut keys = vec![];
+        for index in 0..count {
+            let path =
+                parsed_derivation_path.nth_sibling(index).ok_or_else(|| {
+                    GenRestoreKeyError::DerivationPathError(
+                        DerivationPathError::InvalidDerivationPath(format!(
+                            "the index of {} overflows after {} keys",
+                            parsed_derivation_path, index
+                        )),
+                    )
+                })?;
+            println!("Using HD derivation path {}", path);
+            let alias = match &alias {
+                Some(alias) if index > 0 => {
+                    Some(format!("{}-{}", alias, index))
+                }
+                alias => alias.clone(),
+            };
+            keys.extend(self.gen_and_store_key(
+                scheme,
+                alias,
+                alias_force,
+                Some((seed.clone(), path)),
+                password.clone(),
+            ));
+        }
+        Ok(keys)
     }
 
     /// Generate a new keypair and derive an implicit address from its public
@@ -280,6 +346,62 @@ impl<U: WalletUtils> Wallet<U> {
         (alias.into(), key)
     }
 
+    /// Generate a payment address with a random diversifier from the given
+    /// viewing key and store it under the given alias in the wallet. Returns
+    /// `None` if the alias wasn't confirmed.
+    #[cfg(feature = "masp-tx-gen")]
+    pub fn gen_payment_addr(
+        &mut self,
+        alias: String,
+        viewing_key: &ExtendedViewingKey,
+        pin: bool,
+        force_alias: bool,
+    ) -> Option<(String, PaymentAddress)> {
+        let viewing_key = ExtendedFullViewingKey::from(*viewing_key).fvk.vk;
+        let (div, _g_d) =
+            crate::ledger::masp::find_valid_diversifier(&mut rand::rngs::OsRng);
+        let payment_addr: PaymentAddress = viewing_key
+            .to_payment_address(div)
+            .expect("a valid diversifier has a payment address")
+            .into();
+        let payment_addr = payment_addr.pinned(pin);
+        self.insert_payment_addr(alias, payment_addr, force_alias)
+            .map(|alias| (alias, payment_addr))
+    }
+
+    /// Generate the given number of payment addresses with distinct
+    /// diversifiers from the given viewing key, e.g. an address for every
+    /// customer or invoice, which can't be linked to one another without the
+    /// viewing key. They are stored under the given alias suffixed with their
+    /// index, or under the alias itself if only one is generated. The
+    /// addresses whose alias wasn't confirmed are skipped.
+    #[cfg(feature = "masp-tx-gen")]
+    pub fn gen_payment_addrs(
+        &mut self,
+        alias: String,
+        viewing_key: &ExtendedViewingKey,
+        count: u32,
+        pin: bool,
+        force_alias: bool,
+    ) -> Vec<(String, PaymentAddress)> {
+        if count == 1 {
+            return self
+                .gen_payment_addr(alias, viewing_key, pin, force_alias)
+                .into_iter()
+                .collect();
+        }
+        (0..count)
+            .filter_map(|index| {
+                self.gen_payment_addr(
+                    format!("{}-{}", alias, index),
+                    viewing_key,
+                    pin,
+                    force_alias,
+                )
+            })
+            .collect()
+    }
+
     /// Add validator data to the store
     pub fn add_validator_data(
         &mut self,
@@ -367,6 +489,30 @@ impl<U: WalletUtils> Wallet<U> {
             .ok_or(FindKeyError::KeyNotFound)
     }
 
+    /// Export the viewing key with the given alias, or the viewing key of the
+    /// spending key with the given alias, in its encoding that can be shared
+    /// with an auditor to let them see the shielded transfers of the key
+    /// without being able to spend from it
+    pub fn export_viewing_key(
+        &mut self,
+        alias: impl AsRef<str>,
+    ) -> Result<String, FindKeyError> {
+        self.find_viewing_key(alias).map(ToString::to_string)
+    }
+
+    /// Import a viewing key from its encoding, as exported by
+    /// [`Wallet::export_viewing_key`], and store it under the given alias in
+    /// the wallet. Returns `Ok(None)` if the alias wasn't confirmed.
+    pub fn import_viewing_key(
+        &mut self,
+        alias: String,
+        encoded: &str,
+        force_alias: bool,
+    ) -> Result<Option<String>, DecodeError> {
+        let view_key = ExtendedViewingKey::from_str(encoded.trim())?;
+        Ok(self.insert_viewing_key(alias, view_key, force_alias))
+    }
+
     /// Find the payment address with the given alias in the wallet and return
     /// it
     pub fn find_payment_addr(
@@ -456,8 +602,8 @@ impl<U: WalletUtils> Wallet<U> {
     {
         match stored_key {
             StoredKeypair::Encrypted(encrypted) => {
-                let password =
-                    password.unwrap_or_else(U::read_decryption_password);
+                let password = password
+                    .unwrap_or_else(|| U::read_key_decryption_password(&alias));
                 let key = encrypted
                     .decrypt(password)
                     .map_err(FindKeyError::KeyDecryptionError)?;
@@ -656,6 +802,20 @@ impl<U: WalletUtils> Wallet<U> {
         self.store.add_vp_type_to_address(vp_type, address)
     }
 
+    /// Migrate the keys of the wallet to the current encryption format,
+    /// prompting for the password of every key encrypted with a legacy key
+    /// derivation. If `encrypt_raw` is set, the keys stored raw are encrypted
+    /// with newly prompted passwords. Returns the aliases of the migrated
+    /// keys.
+    pub fn migrate_keys(
+        &mut self,
+        encrypt_raw: bool,
+    ) -> Result<Vec<Alias>, FindKeyError> {
+        self.store
+            .migrate_keys::<U>(encrypt_raw)
+            .map_err(FindKeyError::KeyDecryptionError)
+    }
+
     /// Provide immutable access to the backing store
     pub fn store(&self) -> &Store {
         &self.store
@@ -671,3 +831,10 @@ impl<U: WalletUtils> Wallet<U> {
         &self.store_dir
     }
 }
+
+/// The wallet resolves the aliases of the addresses it stores
+impl<U: WalletUtils> AddressResolver for Wallet<U> {
+    fn find_address(&self, alias: &str) -> Option<Address> {
+        Wallet::find_address(self, alias).cloned()
+    }
+}
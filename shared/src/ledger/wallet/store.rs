@@ -17,7 +17,7 @@ use zeroize::Zeroizing;
 use super::alias::{self, Alias};
 use super::derivation_path::DerivationPath;
 use super::pre_genesis;
-use crate::ledger::wallet::{StoredKeypair, WalletUtils};
+use crate::ledger::wallet::{DecryptionError, StoredKeypair, WalletUtils};
 use crate::types::address::{Address, ImplicitAddress};
 use crate::types::key::dkg_session_keys::DkgKeypair;
 use crate::types::key::*;
@@ -217,6 +217,39 @@ impl Store {
         &self.spend_keys
     }
 
+    /// Migrate the keys and spending keys of the store to the current
+    /// encryption format, prompting for their passwords. If `encrypt_raw` is
+    /// set, the keys stored raw are encrypted too. Returns the aliases of the
+    /// migrated keys. The store is left unchanged on an error.
+    pub fn migrate_keys<U: WalletUtils>(
+        &mut self,
+        encrypt_raw: bool,
+    ) -> Result<Vec<Alias>, DecryptionError> {
+        let mut keys = Vec::new();
+        for (alias, key) in self.keys.iter().sorted_by_key(|(alias, _)| *alias)
+        {
+            if let Some(key) = key.migrate::<U>(alias, encrypt_raw)? {
+                keys.push((alias.clone(), key));
+            }
+        }
+        let mut spend_keys = Vec::new();
+        for (alias, key) in
+            self.spend_keys.iter().sorted_by_key(|(alias, _)| *alias)
+        {
+            if let Some(key) = key.migrate::<U>(alias, encrypt_raw)? {
+                spend_keys.push((alias.clone(), key));
+            }
+        }
+        let migrated = keys
+            .iter()
+            .map(|(alias, _)| alias.clone())
+            .chain(spend_keys.iter().map(|(alias, _)| alias.clone()))
+            .collect();
+        self.keys.extend(keys);
+        self.spend_keys.extend(spend_keys);
+        Ok(migrated)
+    }
+
     #[cfg(feature = "masp-tx-gen")]
     fn generate_spending_key() -> ExtendedSpendingKey {
         use rand::rngs::OsRng;
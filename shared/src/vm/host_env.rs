@@ -472,12 +472,11 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
-    tx_add_gas(
-        env,
-        used_gas
-            .try_into()
-            .map_err(TxRuntimeError::NumConversionError)?,
-    )
+    let used_gas = used_gas
+        .try_into()
+        .map_err(TxRuntimeError::NumConversionError)?;
+    let gas_costs = *unsafe { env.ctx.gas_meter.get() }.gas_costs();
+    tx_add_gas(env, gas_costs.wasm_opcodes(used_gas))
 }
 
 /// Add a gas cost incured in a transaction
@@ -503,6 +502,22 @@ where
     result
 }
 
+/// Add the gas cost of a storage read in a transaction, given the gas of the
+/// read in bytes
+pub fn tx_add_storage_read_gas<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    used_gas: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let gas_costs = *unsafe { env.ctx.gas_meter.get() }.gas_costs();
+    tx_add_gas(env, gas_costs.storage_read(used_gas))
+}
+
 /// Called from VP wasm to request to use the given gas amount
 pub fn vp_charge_gas<MEM, DB, H, EVAL, CA>(
     env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
@@ -515,13 +530,12 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    let used_gas = used_gas
+        .try_into()
+        .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    vp_host_fns::add_gas(
-        gas_meter,
-        used_gas
-            .try_into()
-            .map_err(vp_host_fns::RuntimeError::NumConversionError)?,
-    )
+    let gas = gas_meter.gas_costs().wasm_opcodes(used_gas);
+    vp_host_fns::add_gas(gas_meter, gas)
 }
 
 /// Storage `has_key` function exposed to the wasm VM Tx environment. It will
@@ -606,7 +620,7 @@ where
     // try to read from the write log first
     let write_log = unsafe { env.ctx.write_log.get() };
     let (log_val, gas) = write_log.read(&key);
-    tx_add_gas(env, gas)?;
+    tx_add_storage_read_gas(env, gas)?;
     Ok(match log_val {
         Some(write_log::StorageModification::Write { ref value }) => {
             let len: i64 = value
@@ -647,7 +661,7 @@ where
             let storage = unsafe { env.ctx.storage.get() };
             let (value, gas) =
                 storage.read(&key).map_err(TxRuntimeError::StorageError)?;
-            tx_add_gas(env, gas)?;
+            tx_add_storage_read_gas(env, gas)?;
             match value {
                 Some(value) => {
                     let len: i64 = value
@@ -847,7 +861,8 @@ where
     let (gas, _size_diff) = write_log
         .write(&key, value)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_add_gas(env, gas)
+    let gas_costs = *unsafe { env.ctx.gas_meter.get() }.gas_costs();
+    tx_add_gas(env, gas_costs.storage_write(gas))
     // TODO: charge the size diff
 }
 
@@ -1431,11 +1446,11 @@ where
 
     tracing::debug!("tx_init_account");
 
-    let storage = unsafe { env.ctx.storage.get() };
+    let tx = unsafe { env.ctx.tx.get() };
     let write_log = unsafe { env.ctx.write_log.get() };
     let code_hash = Hash::try_from(&code_hash[..])
         .map_err(|e| TxRuntimeError::InvalidVpCodeHash(e.to_string()))?;
-    let (addr, gas) = write_log.init_account(&storage.address_gen, code_hash);
+    let (addr, gas) = write_log.init_account(&tx.raw_header_hash(), code_hash);
     let addr_bytes =
         addr.try_to_vec().map_err(TxRuntimeError::EncodingError)?;
     tx_add_gas(env, gas)?;
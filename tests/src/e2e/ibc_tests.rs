@@ -770,7 +770,7 @@ fn transfer_received_token(
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -987,7 +987,7 @@ fn submit_ibc_tx(
             "--gas-amount",
             "0",
             "--gas-limit",
-            "0",
+            "1000",
             "--gas-token",
             NAM,
             "--node",
@@ -118,7 +118,7 @@ fn test_node_connectivity_and_consensus() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -392,7 +392,7 @@ fn ledger_txs_and_queries() -> Result<()> {
             "--gas-amount",
             "0",
             "--gas-limit",
-            "0",
+            "1000",
             "--gas-token",
             NAM,
             "--node",
@@ -412,7 +412,7 @@ fn ledger_txs_and_queries() -> Result<()> {
             "--gas-amount",
             "0",
             "--gas-limit",
-            "0",
+            "1000",
             "--gas-token",
             NAM,
             "--node",
@@ -429,7 +429,7 @@ fn ledger_txs_and_queries() -> Result<()> {
              "--gas-amount",
              "0",
              "--gas-limit",
-             "0",
+             "1000",
              "--gas-token",
              NAM,
             "--node",
@@ -447,7 +447,7 @@ fn ledger_txs_and_queries() -> Result<()> {
             "--gas-amount",
             "0",
             "--gas-limit",
-            "0",
+            "1000",
             "--gas-token",
             NAM,
             "--node",
@@ -468,7 +468,7 @@ fn ledger_txs_and_queries() -> Result<()> {
             "--gas-amount",
             "0",
             "--gas-limit",
-            "0",
+            "1000",
             "--gas-token",
             NAM,
             "--node",
@@ -1757,7 +1757,7 @@ fn invalid_transactions() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -1812,7 +1812,7 @@ fn invalid_transactions() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         // Force to ignore client check that fails on the balance check of the
@@ -1889,7 +1889,7 @@ fn pos_bonds() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -1912,7 +1912,7 @@ fn pos_bonds() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -1932,7 +1932,7 @@ fn pos_bonds() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -1955,7 +1955,7 @@ fn pos_bonds() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -1999,7 +1999,7 @@ fn pos_bonds() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -2020,7 +2020,7 @@ fn pos_bonds() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -2097,7 +2097,7 @@ fn pos_rewards() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -2132,7 +2132,7 @@ fn pos_rewards() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -2153,7 +2153,7 @@ fn pos_rewards() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -2243,7 +2243,7 @@ fn test_bond_queries() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -2278,7 +2278,7 @@ fn test_bond_queries() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -2300,7 +2300,7 @@ fn test_bond_queries() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -2413,7 +2413,7 @@ fn pos_init_validator() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--commission-rate",
@@ -2442,7 +2442,7 @@ fn pos_init_validator() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -2465,7 +2465,7 @@ fn pos_init_validator() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -2490,7 +2490,7 @@ fn pos_init_validator() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -2510,7 +2510,7 @@ fn pos_init_validator() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -2610,7 +2610,7 @@ fn ledger_many_txs_in_a_block() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -2721,7 +2721,7 @@ fn proposal_submission() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -3075,7 +3075,7 @@ fn eth_governance_proposal() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -3301,7 +3301,7 @@ fn pgf_governance_proposal() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -3576,7 +3576,7 @@ fn proposal_offline() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -4033,7 +4033,7 @@ fn test_genesis_validators() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
@@ -4212,7 +4212,7 @@ fn double_signing_gets_slashed() -> Result<()> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--node",
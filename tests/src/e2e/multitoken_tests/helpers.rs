@@ -49,7 +49,7 @@ pub fn init_multitoken_vp(test: &Test, rpc_addr: &str) -> Result<String> {
         "--gas-amount",
         "0",
         "--gas-limit",
-        "0",
+        "1000",
         "--gas-token",
         NAM,
         "--ledger-address",
@@ -502,8 +502,8 @@ mod tests {
             validator: &Address,
             amount: token::Amount,
         ) -> bool {
-            let raw_amount: u64 = amount.into();
-            let mut total_bonds: u64 = 0;
+            let raw_amount: u128 = amount.into();
+            let mut total_bonds: u128 = 0;
             for action in self.all_valid_actions().into_iter() {
                 match action {
                     ValidPosAction::Bond {
@@ -513,7 +513,7 @@ mod tests {
                     } => {
                         if owner == &bond_owner && validator == &bond_validator
                         {
-                            let raw_amount: u64 = amount.into();
+                            let raw_amount: u128 = amount.into();
                             total_bonds += raw_amount;
                         }
                     }
@@ -524,7 +524,7 @@ mod tests {
                     } => {
                         if owner == &bond_owner && validator == &bond_validator
                         {
-                            let raw_amount: u64 = amount.into();
+                            let raw_amount: u128 = amount.into();
                             total_bonds -= raw_amount;
                         }
                     }
@@ -780,7 +780,7 @@ pub mod testing {
                 let arb_unbond = arb_current_bond.prop_flat_map(
                     |(bond_id, current_bond_amount)| {
                         let current_bond_amount: u64 =
-                            current_bond_amount.into();
+                            current_bond_amount.checked_to_u64().unwrap();
                         // Unbond an arbitrary amount up to what's available
                         (0..current_bond_amount).prop_map(move |amount| {
                             ValidPosAction::Unbond {
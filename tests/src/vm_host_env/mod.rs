@@ -1396,6 +1396,90 @@ mod tests {
         assert_eq!(balance, Some(Amount::whole(100)));
     }
 
+    #[test]
+    fn test_ibc_receive_foreign_token() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Set the initial state before starting transactions
+        let (_token, receiver) = ibc::init_storage();
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel(&conn_id, false);
+        writes.extend(channel_writes);
+
+        writes.into_iter().for_each(|(key, val)| {
+            tx_host_env::with(|env| {
+                env.wl_storage
+                    .storage
+                    .write(&key, &val)
+                    .expect("write error");
+            });
+        });
+
+        // packet of a token originating from the counterparty chain, whose
+        // base denom isn't a Namada address
+        let base_denom = "uatom";
+        let packet = ibc::received_packet(
+            port_id.clone(),
+            channel_id.clone(),
+            ibc::Sequence::from(1),
+            base_denom.to_string(),
+            &receiver,
+        );
+
+        // Start a transaction to receive a packet
+        let msg = ibc::msg_packet_recv(packet);
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+
+        let mut tx = Tx::new(TxType::Raw);
+        tx.set_code(Code::new(vec![]));
+        tx.set_data(Data::new(tx_data.clone()));
+        tx.add_section(Section::Signature(Signature::new(
+            tx.code_sechash(),
+            &key::testing::keypair_1(),
+        )));
+        tx.add_section(Section::Signature(Signature::new(
+            tx.data_sechash(),
+            &key::testing::keypair_1(),
+        )));
+        // receive a packet with the message
+        tx_host_env::ibc::ibc_actions(tx::ctx())
+            .execute(&tx_data)
+            .expect("receiving the token failed");
+
+        // Check
+        let env = tx_host_env::take();
+        let result = ibc::validate_ibc_vp_from_tx(&env, &tx);
+        assert!(result.expect("validation failed unexpectedly"));
+        // Check if the token was minted under the token address of the hash
+        // of the base denom
+        let token = ibc_storage::token(base_denom).expect("invalid denom");
+        assert_eq!(
+            token,
+            address::Address::Internal(address::InternalAddress::IbcToken(
+                ibc_storage::calc_hash(base_denom)
+            ))
+        );
+        let mint = token::balance_key(
+            &token,
+            &address::Address::Internal(address::InternalAddress::IbcMint),
+        );
+        let result = ibc::validate_token_vp_from_tx(&env, &tx, &mint);
+        assert!(result.expect("token validation failed unexpectedly"));
+        // Check the balance
+        tx_host_env::set(env);
+        let denom = format!("{}/{}/{}", port_id, channel_id, base_denom);
+        let key = ibc::balance_key_with_ibc_prefix(denom, &receiver);
+        let balance: Option<Amount> = tx_host_env::with(|env| {
+            env.wl_storage.read(&key).expect("read error")
+        });
+        assert_eq!(balance, Some(Amount::whole(100)));
+    }
+
     #[test]
     fn test_ibc_unescrow_token() {
         // The environment must be initialized first
@@ -207,6 +207,29 @@ impl TestTxEnv {
             .unwrap();
     }
 
+    /// Set the signer set of the multisig account at the address.
+    pub fn write_signers(
+        &mut self,
+        address: &Address,
+        public_keys: &[key::common::PublicKey],
+        threshold: u8,
+    ) {
+        self.wl_storage
+            .storage
+            .write(
+                &key::pks_key(address),
+                public_keys.to_vec().try_to_vec().unwrap(),
+            )
+            .unwrap();
+        self.wl_storage
+            .storage
+            .write(
+                &key::threshold_key(address),
+                threshold.try_to_vec().unwrap(),
+            )
+            .unwrap();
+    }
+
     /// Apply the tx changes to the write log.
     pub fn execute_tx(&mut self) -> Result<(), Error> {
         wasm::run::tx(
@@ -0,0 +1,38 @@
+//! Transfers from Namada to Ethereum through the bridge pool
+
+use namada_core::types::address::ETH_BRIDGE_POOL;
+pub use namada_core::types::eth_bridge_pool::*;
+
+use super::*;
+
+/// Escrow the wrapped tokens and the gas fee of a transfer to Ethereum in the
+/// bridge pool, and add the transfer to the pending transfers of the pool.
+pub fn add_transfer_to_pool(
+    ctx: &mut Ctx,
+    pending: &PendingTransfer,
+) -> TxResult {
+    let native_token = ctx.get_native_token()?;
+    token::transfer(
+        ctx,
+        &pending.gas_fee.payer,
+        &ETH_BRIDGE_POOL,
+        &native_token,
+        None,
+        pending.gas_fee.amount,
+        &None,
+        &None,
+        &None,
+    )?;
+    token::transfer(
+        ctx,
+        &pending.transfer.sender,
+        &ETH_BRIDGE_POOL,
+        &pending.transfer.asset.wrapped_token(),
+        None,
+        pending.transfer.amount,
+        &None,
+        &None,
+        &None,
+    )?;
+    ctx.write(&pending_transfer_key(&pending.hash()), pending)
+}
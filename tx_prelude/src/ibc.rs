@@ -4,14 +4,16 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 pub use namada_core::ledger::ibc::{
-    Error, IbcActions, IbcCommonContext, IbcStorageContext, ProofSpec,
-    TransferModule,
+    Error, IbcActions, IbcCommonContext, IbcStorageContext, IcaHostModule,
+    ProofSpec, TransferModule,
 };
 use namada_core::ledger::storage_api::{StorageRead, StorageWrite};
 use namada_core::ledger::tx_env::TxEnv;
+pub use namada_core::types::address::Address;
 pub use namada_core::types::ibc::IbcEvent;
 use namada_core::types::storage::{BlockHeight, Header, Key};
 use namada_core::types::token::Amount;
+use namada_proof_of_stake::{bond_tokens, is_validator};
 
 use crate::token::transfer_with_keys;
 use crate::{Ctx, KeyValIterator};
@@ -20,8 +22,10 @@ use crate::{Ctx, KeyValIterator};
 pub fn ibc_actions(ctx: &mut Ctx) -> IbcActions<Ctx> {
     let ctx = Rc::new(RefCell::new(ctx.clone()));
     let mut actions = IbcActions::new(ctx.clone());
-    let module = TransferModule::new(ctx);
+    let module = TransferModule::new(ctx.clone());
     actions.add_transfer_route(module.module_id(), module);
+    let module = IcaHostModule::new(ctx);
+    actions.add_ica_host_route(module.module_id(), module);
     actions
 }
 
@@ -79,6 +83,27 @@ impl IbcStorageContext for Ctx {
         transfer_with_keys(self, src, dest, amount)
     }
 
+    fn get_native_token(&self) -> std::result::Result<Address, Self::Error> {
+        StorageRead::get_native_token(self)
+    }
+
+    fn is_validator(
+        &self,
+        address: &Address,
+    ) -> std::result::Result<bool, Self::Error> {
+        is_validator(self, address)
+    }
+
+    fn bond_tokens(
+        &mut self,
+        source: &Address,
+        validator: &Address,
+        amount: Amount,
+    ) -> std::result::Result<(), Self::Error> {
+        let current_epoch = self.get_block_epoch()?;
+        bond_tokens(self, Some(source), validator, amount, current_epoch)
+    }
+
     fn get_height(&self) -> std::result::Result<BlockHeight, Self::Error> {
         self.get_block_height()
     }
@@ -1,5 +1,6 @@
 //! Cryptographic signature keys
 
+use namada_core::types::address::Address;
 pub use namada_core::types::key::*;
 
 use super::*;
@@ -9,3 +10,14 @@ use super::*;
 pub fn reveal_pk(ctx: &mut Ctx, pk: &common::PublicKey) -> EnvResult<()> {
     storage_api::key::reveal_pk(ctx, pk)
 }
+
+/// Set the public keys of the signers of a multisig account and the number
+/// of their signatures it requires.
+pub fn write_signers(
+    ctx: &mut Ctx,
+    owner: &Address,
+    pks: &[common::PublicKey],
+    threshold: u8,
+) -> EnvResult<()> {
+    storage_api::key::write_signers(ctx, owner, pks, threshold)
+}
@@ -5,8 +5,9 @@ use namada_core::types::transaction::InitValidator;
 use namada_core::types::{key, token};
 pub use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::{
-    become_validator, bond_tokens, change_validator_commission_rate,
-    read_pos_params, unbond_tokens, unjail_validator, withdraw_tokens,
+    become_validator, bond_tokens, change_consensus_key,
+    change_validator_commission_rate, claim_reward_tokens, read_pos_params,
+    redelegate_tokens, unbond_tokens, unjail_validator, withdraw_tokens,
 };
 pub use namada_proof_of_stake::{parameters, types};
 use rust_decimal::Decimal;
@@ -40,6 +41,26 @@ impl Ctx {
         unbond_tokens(self, source, validator, amount, current_epoch)
     }
 
+    /// Redelegate bonded tokens of the `owner` from the `src_validator` to the
+    /// `dest_validator`, without unbonding them.
+    pub fn redelegate_tokens(
+        &mut self,
+        owner: &Address,
+        src_validator: &Address,
+        dest_validator: &Address,
+        amount: token::Amount,
+    ) -> TxResult {
+        let current_epoch = self.get_block_epoch()?;
+        redelegate_tokens(
+            self,
+            owner,
+            src_validator,
+            dest_validator,
+            amount,
+            current_epoch,
+        )
+    }
+
     /// NEW: Withdraw unbonded tokens from a self-bond to a validator when
     /// `source` is `None` or equal to the `validator` address, or withdraw
     /// unbonded tokens delegated to the `validator` to the `source`.
@@ -52,6 +73,18 @@ impl Ctx {
         withdraw_tokens(self, source, validator, current_epoch)
     }
 
+    /// Claim the rewards of a self-bond to a validator when `source` is `None`
+    /// or equal to the `validator` address, or of the tokens delegated to the
+    /// `validator` by the `source`.
+    pub fn claim_reward_tokens(
+        &mut self,
+        source: Option<&Address>,
+        validator: &Address,
+    ) -> EnvResult<token::Amount> {
+        let current_epoch = self.get_block_epoch()?;
+        claim_reward_tokens(self, source, validator, current_epoch)
+    }
+
     /// NEW: Change validator commission rate.
     pub fn change_validator_commission_rate(
         &mut self,
@@ -62,6 +95,16 @@ impl Ctx {
         change_validator_commission_rate(self, validator, *rate, current_epoch)
     }
 
+    /// Rotate the consensus key of a validator from the pipeline epoch.
+    pub fn change_consensus_key(
+        &mut self,
+        validator: &Address,
+        consensus_key: &key::common::PublicKey,
+    ) -> TxResult {
+        let current_epoch = self.get_block_epoch()?;
+        change_consensus_key(self, validator, consensus_key, current_epoch)
+    }
+
     /// Unjail a jailed validator and re-enter the validator sets.
     pub fn unjail_validator(&mut self, validator: &Address) -> TxResult {
         let current_epoch = self.get_block_epoch()?;
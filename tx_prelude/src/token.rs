@@ -1,7 +1,7 @@
 use masp_primitives::transaction::Transaction;
 use namada_core::types::address::{Address, InternalAddress};
 use namada_core::types::hash::Hash;
-use namada_core::types::storage::KeySeg;
+use namada_core::types::storage::DbKeySeg;
 use namada_core::types::token;
 pub use namada_core::types::token::*;
 
@@ -21,22 +21,9 @@ pub fn transfer(
     shielded: &Option<Transaction>,
 ) -> TxResult {
     if amount != Amount::default() {
-        let src_key = match &sub_prefix {
-            Some(sub_prefix) => {
-                let prefix =
-                    token::multitoken_balance_prefix(token, sub_prefix);
-                token::multitoken_balance_key(&prefix, src)
-            }
-            None => token::balance_key(token, src),
-        };
-        let dest_key = match &sub_prefix {
-            Some(sub_prefix) => {
-                let prefix =
-                    token::multitoken_balance_prefix(token, sub_prefix);
-                token::multitoken_balance_key(&prefix, dest)
-            }
-            None => token::balance_key(token, dest),
-        };
+        let src_key = token::token_balance_key(token, sub_prefix.as_ref(), src);
+        let dest_key =
+            token::token_balance_key(token, sub_prefix.as_ref(), dest);
         let src_bal: Option<Amount> = match src {
             Address::Internal(InternalAddress::IbcMint) => Some(Amount::max()),
             Address::Internal(InternalAddress::IbcBurn) => {
@@ -83,6 +70,7 @@ pub fn transfer(
                     ctx.write(&dest_key, dest_bal)?;
                 }
             }
+            apply_hook(ctx, token, src, dest, amount)?;
         }
     }
 
@@ -91,14 +79,10 @@ pub fn transfer(
     if let Some(shielded) = shielded {
         let masp_addr = address::masp();
         ctx.insert_verifier(&masp_addr)?;
-        let head_tx_key = storage::Key::from(masp_addr.to_db_key())
-            .push(&HEAD_TX_KEY.to_owned())
-            .expect("Cannot obtain a storage key");
+        let head_tx_key = token::masp_head_tx_key();
         let current_tx_idx: u64 =
             ctx.read(&head_tx_key).unwrap_or(None).unwrap_or(0);
-        let current_tx_key = storage::Key::from(masp_addr.to_db_key())
-            .push(&(TX_KEY_PREFIX.to_owned() + &current_tx_idx.to_string()))
-            .expect("Cannot obtain a storage key");
+        let current_tx_key = token::masp_tx_key(current_tx_idx);
         // Save the Transfer object and its location within the blockchain
         // so that clients do not have to separately look these
         // up
@@ -123,9 +107,7 @@ pub fn transfer(
         ctx.write(&head_tx_key, current_tx_idx + 1)?;
         // If storage key has been supplied, then pin this transaction to it
         if let Some(key) = key {
-            let pin_key = storage::Key::from(masp_addr.to_db_key())
-                .push(&(PIN_KEY_PREFIX.to_owned() + key))
-                .expect("Cannot obtain a storage key");
+            let pin_key = token::masp_pin_key(key);
             ctx.write(&pin_key, current_tx_idx)?;
         }
     }
@@ -176,5 +158,44 @@ pub fn transfer_with_keys(
         }
         _ => ctx.write(dest_key, dest_bal)?,
     }
+    if let (Some(DbKeySeg::AddressSeg(token)), Some(src), Some(dest)) =
+        (src_key.segments.first(), src_owner, dest_owner)
+    {
+        apply_hook(ctx, token, src, dest, amount)?;
+    }
     Ok(())
 }
+
+/// If the token has a hook, add it to the verifiers of the transaction, and
+/// track the supply minted and burned through IBC that it bounds.
+fn apply_hook(
+    ctx: &mut Ctx,
+    token: &Address,
+    src: &Address,
+    dest: &Address,
+    amount: Amount,
+) -> TxResult {
+    let hook: Option<Address> = ctx.read(&token::hook_key(token))?;
+    let hook = match hook {
+        Some(hook) => hook,
+        None => return Ok(()),
+    };
+    ctx.insert_verifier(&hook)?;
+    let minted_key = token::minted_key(&hook, token);
+    let minted: Amount = ctx.read(&minted_key)?.unwrap_or_default();
+    let new_minted = match (src, dest) {
+        (Address::Internal(InternalAddress::IbcMint), _) => {
+            minted.checked_add(amount).unwrap_or_else(|| {
+                log_string("the minted supply overflows");
+                unreachable!()
+            })
+        }
+        // Burning tokens that weren't minted through IBC doesn't free up
+        // the mint cap
+        (_, Address::Internal(InternalAddress::IbcBurn)) => {
+            minted.checked_sub(amount).unwrap_or_default()
+        }
+        _ => return Ok(()),
+    };
+    ctx.write(&minted_key, new_minted)
+}
@@ -10,3 +10,13 @@ use super::*;
 pub fn get(ctx: &Ctx, owner: &Address) -> EnvResult<Option<common::PublicKey>> {
     storage_api::key::get(&ctx.pre(), owner)
 }
+
+/// Get the public keys of the signers of a multisig account and the number of
+/// their signatures it requires, from the state prior to tx execution.
+/// Returns `Ok(None)` if the account is not a multisig.
+pub fn get_signers(
+    ctx: &Ctx,
+    owner: &Address,
+) -> EnvResult<Option<(Vec<common::PublicKey>, u8)>> {
+    storage_api::key::get_signers(&ctx.pre(), owner)
+}
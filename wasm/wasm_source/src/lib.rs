@@ -1,7 +1,13 @@
 #[cfg(feature = "tx_bond")]
 pub mod tx_bond;
+#[cfg(feature = "tx_bridge_pool")]
+pub mod tx_bridge_pool;
+#[cfg(feature = "tx_change_consensus_key")]
+pub mod tx_change_consensus_key;
 #[cfg(feature = "tx_change_validator_commission")]
 pub mod tx_change_validator_commission;
+#[cfg(feature = "tx_claim_rewards")]
+pub mod tx_claim_rewards;
 #[cfg(feature = "tx_ibc")]
 pub mod tx_ibc;
 #[cfg(feature = "tx_init_account")]
@@ -10,6 +16,8 @@ pub mod tx_init_account;
 pub mod tx_init_proposal;
 #[cfg(feature = "tx_init_validator")]
 pub mod tx_init_validator;
+#[cfg(feature = "tx_redelegate")]
+pub mod tx_redelegate;
 #[cfg(feature = "tx_reveal_pk")]
 pub mod tx_reveal_pk;
 #[cfg(feature = "tx_transfer")]
@@ -18,6 +26,8 @@ pub mod tx_transfer;
 pub mod tx_unbond;
 #[cfg(feature = "tx_unjail_validator")]
 pub mod tx_unjail_validator;
+#[cfg(feature = "tx_update_signers")]
+pub mod tx_update_signers;
 #[cfg(feature = "tx_update_vp")]
 pub mod tx_update_vp;
 #[cfg(feature = "tx_vote_proposal")]
@@ -29,10 +39,14 @@ pub mod tx_withdraw;
 pub mod vp_implicit;
 #[cfg(feature = "vp_masp")]
 pub mod vp_masp;
+#[cfg(feature = "vp_multisig")]
+pub mod vp_multisig;
 #[cfg(feature = "vp_testnet_faucet")]
 pub mod vp_testnet_faucet;
 #[cfg(feature = "vp_token")]
 pub mod vp_token;
+#[cfg(feature = "vp_token_hook")]
+pub mod vp_token_hook;
 #[cfg(feature = "vp_user")]
 pub mod vp_user;
 #[cfg(feature = "vp_validator")]
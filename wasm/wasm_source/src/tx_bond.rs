@@ -349,7 +349,7 @@ mod tests {
             // Generate initial stake
             (initial_stake in token::testing::arb_amount_ceiled((i64::MAX/8) as u64))
             // Use the initial stake to limit the bond amount
-            (bond in arb_bond(((i64::MAX/8) as u64) - u64::from(initial_stake)),
+            (bond in arb_bond(((i64::MAX/8) as u64) - initial_stake.checked_to_u64().unwrap()),
             // Use the generated initial stake too
             initial_stake in Just(initial_stake),
         ) -> (token::Amount, transaction::pos::Bond) {
@@ -0,0 +1,18 @@
+//! A tx adding a transfer to Ethereum to the bridge pool, which escrows its
+//! wrapped tokens and the gas fee of its relayer.
+
+use namada_tx_prelude::eth_bridge_pool::PendingTransfer;
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data")?;
+    let pending = PendingTransfer::try_from_slice(&data[..])
+        .wrap_err("failed to decode a PendingTransfer")?;
+    debug_log!(
+        "apply_tx called with a transfer to Ethereum: {:#?}",
+        pending
+    );
+    eth_bridge_pool::add_transfer_to_pool(ctx, &pending)
+}
@@ -0,0 +1,17 @@
+//! A tx for a validator to rotate their consensus key, which takes effect at
+//! the pipeline epoch.
+
+use namada_tx_prelude::transaction::pos::ConsensusKeyChange;
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data")?;
+    let ConsensusKeyChange {
+        validator,
+        consensus_key,
+    } = transaction::pos::ConsensusKeyChange::try_from_slice(&data[..])
+        .wrap_err("failed to decode a ConsensusKeyChange")?;
+    ctx.change_consensus_key(&validator, &consensus_key)
+}
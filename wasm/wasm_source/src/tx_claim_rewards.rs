@@ -0,0 +1,16 @@
+//! A tx for a PoS claim of the rewards of a self-bond or a delegation.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data")?;
+    let claim = transaction::pos::ClaimRewards::try_from_slice(&data[..])
+        .wrap_err("failed to decode ClaimRewards")?;
+
+    let rewards =
+        ctx.claim_reward_tokens(claim.source.as_ref(), &claim.validator)?;
+    debug_log!("Claimed rewards {}", rewards);
+    Ok(())
+}
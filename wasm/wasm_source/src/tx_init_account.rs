@@ -1,5 +1,6 @@
 //! A tx to initialize a new established address with a given public key and
-//! a validity predicate.
+//! a validity predicate. Multisig accounts are also given the signer set and
+//! threshold that their VP checks signatures against.
 
 use namada_tx_prelude::*;
 
@@ -21,5 +22,13 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
     let address = ctx.init_account(vp_code)?;
     let pk_key = key::pk_key(&address);
     ctx.write(&pk_key, &tx_data.public_key)?;
+    if let Some((pks, threshold)) = tx_data.signers() {
+        if threshold == 0 || usize::from(threshold) > pks.len() {
+            return Err(Error::new_const(
+                "the threshold must be between 1 and the number of signers",
+            ));
+        }
+        key::write_signers(ctx, &address, &pks, threshold)?;
+    }
     Ok(())
 }
@@ -0,0 +1,19 @@
+//! A tx for a delegator to redelegate bonded tokens from one validator to
+//! another without unbonding them.
+
+use namada_tx_prelude::transaction::pos::Redelegation;
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data")?;
+    let Redelegation {
+        src_validator,
+        dest_validator,
+        owner,
+        amount,
+    } = transaction::pos::Redelegation::try_from_slice(&data[..])
+        .wrap_err("failed to decode a Redelegation")?;
+    ctx.redelegate_tokens(&owner, &src_validator, &dest_validator, amount)
+}
@@ -418,7 +418,8 @@ mod tests {
         token::testing::arb_amount_ceiled((i64::MAX / 8) as u64).prop_flat_map(
             |initial_stake| {
                 // Use the initial stake to limit the bond amount
-                let unbond = arb_unbond(u64::from(initial_stake));
+                let max_amount = initial_stake.checked_to_u64().unwrap();
+                let unbond = arb_unbond(max_amount);
                 // Use the generated initial stake too too
                 (Just(initial_stake), unbond)
             },
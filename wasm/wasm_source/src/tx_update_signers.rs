@@ -0,0 +1,28 @@
+//! A tx for updating the signer set of a multisig account. The account's VP
+//! must accept the update, which it does when it's signed by enough of its
+//! current signers.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Tx) -> TxResult {
+    let signed = tx_data;
+    let data = signed.data().ok_or_err_msg("Missing data")?;
+    let update = transaction::UpdateSigners::try_from_slice(&data[..])
+        .wrap_err("failed to decode UpdateSigners")?;
+
+    debug_log!("update signers for: {:#?}", update.addr);
+    if update.threshold == 0
+        || usize::from(update.threshold) > update.public_keys.len()
+    {
+        return Err(Error::new_const(
+            "the threshold must be between 1 and the number of signers",
+        ));
+    }
+    key::write_signers(
+        ctx,
+        &update.addr,
+        &update.public_keys,
+        update.threshold,
+    )
+}
@@ -236,7 +236,7 @@ mod tests {
                 // stake
                 let unbonded_amount =
                     token::testing::arb_amount_non_zero_ceiled(
-                        initial_stake.into(),
+                        initial_stake.checked_to_u64().unwrap(),
                     );
                 // Use the generated initial stake too too
                 (Just(initial_stake), unbonded_amount)
@@ -39,8 +39,8 @@ fn valid_asset_type(
 /// Checks if the reported transparent amount and the unshielded
 /// values agree, if not adds to the debug log
 fn valid_transfer_amount(
-    reporeted_transparent_value: u64,
-    unshielded_transfer_value: u64,
+    reporeted_transparent_value: u128,
+    unshielded_transfer_value: u128,
 ) -> bool {
     let res = reporeted_transparent_value == unshielded_transfer_value;
     if !res {
@@ -62,7 +62,7 @@ fn convert_amount(
 ) -> (AssetType, Amount) {
     let asset_type = asset_type_from_epoched_address(epoch, token);
     // Combine the value and unit into one amount
-    let amount = Amount::from_nonnegative(asset_type, u64::from(val))
+    let amount = Amount::from_nonnegative(asset_type, u128::from(val))
         .expect("invalid value or asset type for amount");
     (asset_type, amount)
 }
@@ -169,8 +169,8 @@ fn validate_tx(
             // Satisfies 2. and 3.
             if !(valid_asset_type(&expected_asset_type, &out.asset_type)
                 && valid_transfer_amount(
-                    out.value as u64,
-                    u64::from(transfer.amount),
+                    u128::from(out.value as u64),
+                    u128::from(transfer.amount),
                 ))
             {
                 return reject();
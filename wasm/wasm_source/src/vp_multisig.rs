@@ -0,0 +1,358 @@
+//! A multisig account VP.
+//!
+//! This VP behaves like the basic user VP, except that a valid signature
+//! consists of the signatures of at least `threshold` of the account's
+//! signers, whose public keys are stored in the account's storage.
+//!
+//! Updates of the signer set must keep its threshold between 1 and the
+//! number of signers.
+
+use namada_vp_prelude::address::masp;
+use namada_vp_prelude::storage::KeySeg;
+use namada_vp_prelude::*;
+use once_cell::unsync::Lazy;
+
+enum KeyType<'a> {
+    Token(&'a Address),
+    PoS,
+    Vp(&'a Address),
+    Masp,
+    GovernanceVote(&'a Address),
+    Signers(&'a Address),
+    Unknown,
+}
+
+impl<'a> From<&'a storage::Key> for KeyType<'a> {
+    fn from(key: &'a storage::Key) -> KeyType<'a> {
+        if let Some(address) = token::is_any_token_balance_key(key) {
+            Self::Token(address)
+        } else if let Some((_, address)) =
+            token::is_any_multitoken_balance_key(key)
+        {
+            Self::Token(address)
+        } else if proof_of_stake::is_pos_key(key) {
+            Self::PoS
+        } else if gov_storage::is_vote_key(key) {
+            let voter_address = gov_storage::get_voter_address(key);
+            if let Some(address) = voter_address {
+                Self::GovernanceVote(address)
+            } else {
+                Self::Unknown
+            }
+        } else if let Some(address) = key.is_validity_predicate() {
+            Self::Vp(address)
+        } else if token::is_masp_key(key) {
+            Self::Masp
+        } else if let Some(address) =
+            key::is_pks_key(key).or_else(|| key::is_threshold_key(key))
+        {
+            Self::Signers(address)
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[validity_predicate]
+fn validate_tx(
+    ctx: &Ctx,
+    tx_data: Tx,
+    addr: Address,
+    keys_changed: BTreeSet<storage::Key>,
+    verifiers: BTreeSet<Address>,
+) -> VpResult {
+    debug_log!(
+        "vp_multisig called with user addr: {}, key_changed: {:?}, verifiers: \
+         {:?}",
+        addr,
+        keys_changed,
+        verifiers
+    );
+
+    let valid_sig = Lazy::new(|| match key::get_signers(ctx, &addr) {
+        Ok(Some((pks, threshold))) => tx_data
+            .verify_multisig(&pks, threshold, tx_data.data_sechash())
+            .is_ok(),
+        _ => false,
+    });
+
+    if !is_valid_tx(ctx, &tx_data)? {
+        return reject();
+    }
+
+    for key in keys_changed.iter() {
+        let key_type: KeyType = key.into();
+        let is_valid = match key_type {
+            KeyType::Token(owner) => {
+                if owner == &addr {
+                    let pre: token::Amount =
+                        ctx.read_pre(key)?.unwrap_or_default();
+                    let post: token::Amount =
+                        ctx.read_post(key)?.unwrap_or_default();
+                    let change = post.change() - pre.change();
+                    // debit has to signed, credit doesn't
+                    let valid = change >= 0 || addr == masp() || *valid_sig;
+                    debug_log!(
+                        "token key: {}, change: {}, valid_sig: {}, valid \
+                         modification: {}",
+                        key,
+                        change,
+                        *valid_sig,
+                        valid
+                    );
+                    valid
+                } else {
+                    debug_log!(
+                        "This address ({}) is not of owner ({}) of token key: \
+                         {}",
+                        addr,
+                        owner,
+                        key
+                    );
+                    // If this is not the owner, allow any change
+                    true
+                }
+            }
+            KeyType::PoS => {
+                // Allow the account to be used in PoS
+                let bond_id = proof_of_stake::is_bond_key(key)
+                    .map(|(bond_id, _)| bond_id)
+                    .or_else(|| {
+                        proof_of_stake::is_unbond_key(key)
+                            .map(|(bond_id, _, _)| bond_id)
+                    });
+                let valid = match bond_id {
+                    Some(bond_id) => {
+                        // Bonds and unbonds changes for this address
+                        // must be signed
+                        bond_id.source != addr || *valid_sig
+                    }
+                    None => {
+                        // Any other PoS changes are allowed without signature
+                        true
+                    }
+                };
+                debug_log!(
+                    "PoS key {} {}",
+                    key,
+                    if valid { "accepted" } else { "rejected" }
+                );
+                valid
+            }
+            KeyType::GovernanceVote(voter) => {
+                if voter == &addr {
+                    *valid_sig
+                } else {
+                    true
+                }
+            }
+            KeyType::Vp(owner) => {
+                let has_post: bool = ctx.has_key_post(key)?;
+                if owner == &addr {
+                    if has_post {
+                        let vp_hash: Vec<u8> =
+                            ctx.read_bytes_post(key)?.unwrap();
+                        *valid_sig && is_vp_whitelisted(ctx, &vp_hash)?
+                    } else {
+                        false
+                    }
+                } else {
+                    let vp_hash: Vec<u8> = ctx.read_bytes_post(key)?.unwrap();
+                    is_vp_whitelisted(ctx, &vp_hash)?
+                }
+            }
+            KeyType::Signers(owner) => {
+                if owner == &addr {
+                    *valid_sig && has_valid_signers_post(ctx, &addr)?
+                } else {
+                    true
+                }
+            }
+            KeyType::Masp => true,
+            KeyType::Unknown => {
+                if key.segments.get(0) == Some(&addr.to_db_key()) {
+                    // Unknown changes to this address space require a valid
+                    // signature
+                    *valid_sig
+                } else {
+                    // Unknown changes anywhere else are permitted
+                    true
+                }
+            }
+        };
+        if !is_valid {
+            debug_log!("key {} modification failed vp", key);
+            return reject();
+        }
+    }
+
+    accept()
+}
+
+/// Check that the signer set of the account after the tx is applied requires
+/// between 1 and all of its signers to sign
+fn has_valid_signers_post(ctx: &Ctx, addr: &Address) -> EnvResult<bool> {
+    let pks: Vec<key::common::PublicKey> =
+        ctx.read_post(&key::pks_key(addr))?.unwrap_or_default();
+    let threshold: u8 = ctx
+        .read_post(&key::threshold_key(addr))?
+        .unwrap_or_default();
+    Ok(threshold > 0 && usize::from(threshold) <= pks.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::proto::{Data, Signature};
+    use namada::types::transaction::TxType;
+    // Use this as `#[test]` annotation to enable logging
+    use namada_tests::log::test;
+    use namada_tests::tx::{self, tx_host_env, TestTxEnv};
+    use namada_tests::vp::*;
+    use namada_vp_prelude::key::RefTo;
+
+    use super::*;
+
+    /// Run a debit transfer from a 2-of-2 multisig account signed by the
+    /// given keys and return whether the VP accepts it
+    fn validate_debit_signed_by(keypairs: &[key::common::SecretKey]) -> bool {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let pks = vec![
+            key::testing::keypair_1().ref_to(),
+            key::testing::keypair_2().ref_to(),
+        ];
+        let target = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::from(10_098_123);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+        tx_env.credit_tokens(&vp_owner, &token, None, amount);
+        tx_env.write_signers(&vp_owner, &pks, 2);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &target,
+                &token,
+                None,
+                amount,
+                &None,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        for keypair in keypairs {
+            tx.add_section(Section::Signature(Signature::new(
+                tx.data_sechash(),
+                keypair,
+            )));
+        }
+        let signed_tx = tx.clone();
+        vp_env.tx = signed_tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that no-op transaction (i.e. no storage modifications) accepted.
+    #[test]
+    fn test_no_op_transaction() {
+        let mut tx_data = Tx::new(TxType::Raw);
+        tx_data.set_data(Data::new(vec![]));
+        let addr: Address = address::testing::established_address_1();
+        let keys_changed: BTreeSet<storage::Key> = BTreeSet::default();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+
+        // The VP env must be initialized before calling `validate_tx`
+        vp_host_env::init();
+
+        assert!(
+            validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap()
+        );
+    }
+
+    /// Test that a debit transfer is only accepted with the signatures of
+    /// enough signers.
+    #[test]
+    fn test_debit_transfer_threshold() {
+        assert!(!validate_debit_signed_by(&[]));
+        assert!(!validate_debit_signed_by(&[key::testing::keypair_1()]));
+        // a signer can't sign twice
+        assert!(!validate_debit_signed_by(&[
+            key::testing::keypair_1(),
+            key::testing::keypair_1(),
+        ]));
+        assert!(validate_debit_signed_by(&[
+            key::testing::keypair_1(),
+            key::testing::keypair_2(),
+        ]));
+    }
+
+    /// Test that an update of the signer set is only accepted when signed
+    /// by enough signers and when it keeps a valid threshold.
+    #[test]
+    fn test_signers_update() {
+        let run = |threshold: u8, signed: bool| -> bool {
+            // Initialize a tx environment
+            let mut tx_env = TestTxEnv::default();
+
+            let vp_owner = address::testing::established_address_1();
+            let keypair = key::testing::keypair_1();
+            let pks = vec![keypair.ref_to()];
+
+            // Spawn the accounts to be able to modify their storage
+            tx_env.spawn_accounts([&vp_owner]);
+            tx_env.write_signers(&vp_owner, &pks, 1);
+
+            // Initialize VP environment from a transaction
+            vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+                let pks = vec![
+                    key::testing::keypair_1().ref_to(),
+                    key::testing::keypair_2().ref_to(),
+                ];
+                tx_host_env::key::write_signers(
+                    tx::ctx(),
+                    address,
+                    &pks,
+                    threshold,
+                )
+                .unwrap();
+            });
+
+            let mut vp_env = vp_host_env::take();
+            let mut tx = vp_env.tx.clone();
+            tx.set_data(Data::new(vec![]));
+            if signed {
+                tx.add_section(Section::Signature(Signature::new(
+                    tx.data_sechash(),
+                    &keypair,
+                )));
+            }
+            let signed_tx = tx.clone();
+            vp_env.tx = signed_tx.clone();
+            let keys_changed: BTreeSet<storage::Key> =
+                vp_env.all_touched_storage_keys();
+            let verifiers: BTreeSet<Address> = BTreeSet::default();
+            vp_host_env::set(vp_env);
+            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        };
+        assert!(!run(2, false));
+        assert!(run(2, true));
+        assert!(!run(0, true));
+        assert!(!run(3, true));
+    }
+}
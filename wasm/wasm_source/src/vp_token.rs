@@ -1,5 +1,7 @@
 //! A VP for a fungible token. Enforces that the total supply is unchanged in a
-//! transaction that moves balance(s).
+//! transaction that moves balance(s), either of the token's own balances or of
+//! its multitokens, and that the token's hook, if any, validates the
+//! transaction.
 
 use std::collections::BTreeSet;
 
@@ -45,6 +47,8 @@ fn validate_tx(
 /// - The value associated with the `total_supply` storage key may not change.
 /// - For any balance changes, the total of outputs must be equal to the total
 ///   of inputs.
+/// - For any balance changes, the hook of the token, if any, must be a verifier
+///   of the transaction, such that its VP validates the transaction.
 fn token_checks(
     ctx: &Ctx,
     token: &Address,
@@ -52,11 +56,10 @@ fn token_checks(
     verifiers: &BTreeSet<Address>,
 ) -> VpResult {
     let mut change: token::Change = 0;
+    let mut balances_changed = false;
     for key in keys_touched.iter() {
-        let owner: Option<&Address> = token::is_balance_key(token, key)
-            .or_else(|| {
-                token::is_multitoken_balance_key(token, key).map(|a| a.1)
-            });
+        let owner: Option<&Address> =
+            token::is_token_balance_key(token, key).map(|(_, owner)| owner);
 
         match owner {
             None => {
@@ -97,6 +100,7 @@ fn token_checks(
                 };
                 let this_change = post.change() - pre.change();
                 change += this_change;
+                balances_changed |= this_change != 0;
                 // make sure that the spender approved the transaction
                 if this_change < 0
                     && !(verifiers.contains(owner) || *owner == address::masp())
@@ -106,6 +110,16 @@ fn token_checks(
             }
         }
     }
+    if balances_changed {
+        // The hook is set at the token's initialization only
+        let hook: Option<Address> = ctx.read_pre(&token::hook_key(token))?;
+        if let Some(hook) = hook {
+            if !verifiers.contains(&hook) {
+                debug_log!("The token's hook {} is not a verifier", hook);
+                return reject();
+            }
+        }
+    }
     Ok(change == 0)
 }
 
@@ -116,7 +130,7 @@ mod tests {
     use namada::proto::Data;
     use namada::types::transaction::TxType;
     use namada_tests::log::test;
-    use namada_tests::tx::{self, TestTxEnv};
+    use namada_tests::tx::{self, tx_host_env, TestTxEnv};
     use namada_tests::vp::*;
     use namada_vp_prelude::storage_api::StorageWrite;
 
@@ -272,4 +286,108 @@ mod tests {
             "Change of a `total_supply` value should be rejected"
         );
     }
+
+    #[test]
+    fn test_transfer_without_hook_verifier_is_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+        let token = address::nam();
+        let hook = address::testing::established_address_3();
+        let src = address::testing::established_address_1();
+        let dest = address::testing::established_address_2();
+        let total_supply = token::Amount::from(10_098_123);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&token, &hook, &src, &dest]);
+        token::credit_tokens(
+            &mut tx_env.wl_storage,
+            &token,
+            &src,
+            total_supply,
+        )
+        .unwrap();
+        token::write_hook(&mut tx_env.wl_storage, &token, &hook).unwrap();
+        // Commit the initial state
+        tx_env.commit_tx_and_block();
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(token.clone(), tx_env, |_address| {
+            // Apply a transfer without the hook as a verifier
+
+            let amount = token::Amount::from(100);
+            token::transfer(tx::ctx(), &token, &src, &dest, amount).unwrap();
+        });
+
+        let vp_env = vp_host_env::take();
+        let mut tx_data = Tx::new(TxType::Raw);
+        tx_data.set_data(Data::new(vec![]));
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers = vp_env.get_verifiers();
+        vp_host_env::set(vp_env);
+
+        assert!(
+            !validate_tx(&CTX, tx_data, token, keys_changed, verifiers)
+                .unwrap(),
+            "A transfer without the token's hook as a verifier should be \
+             rejected"
+        );
+    }
+
+    #[test]
+    fn test_transfer_with_hook_verifier_is_accepted() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+        let token = address::nam();
+        let hook = address::testing::established_address_3();
+        let src = address::testing::established_address_1();
+        let dest = address::testing::established_address_2();
+        let total_supply = token::Amount::from(10_098_123);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&token, &hook, &src, &dest]);
+        token::credit_tokens(
+            &mut tx_env.wl_storage,
+            &token,
+            &src,
+            total_supply,
+        )
+        .unwrap();
+        token::write_hook(&mut tx_env.wl_storage, &token, &hook).unwrap();
+        // Commit the initial state
+        tx_env.commit_tx_and_block();
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(token.clone(), tx_env, |_address| {
+            // Apply a transfer, which adds the hook as a verifier
+
+            let amount = token::Amount::from(100);
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                &src,
+                &dest,
+                &token,
+                None,
+                amount,
+                &None,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        let vp_env = vp_host_env::take();
+        let mut tx_data = Tx::new(TxType::Raw);
+        tx_data.set_data(Data::new(vec![]));
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers = vp_env.get_verifiers();
+        vp_host_env::set(vp_env);
+
+        assert!(verifiers.contains(&hook));
+        assert!(
+            validate_tx(&CTX, tx_data, token, keys_changed, verifiers).unwrap(),
+            "A transfer with the token's hook as a verifier should be accepted"
+        );
+    }
 }
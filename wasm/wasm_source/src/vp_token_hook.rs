@@ -0,0 +1,369 @@
+//! A VP for the hook of tokens, which the VPs of the tokens that it's set for
+//! require to validate any of their balance changes.
+//!
+//! This VP enforces for these tokens that:
+//!
+//! - The balances of the accounts whose transfers of a token are frozen (or
+//!   all the balances of a token, if all of its transfers are frozen) may not
+//!   change.
+//! - The supply of a token minted through IBC is tracked in the hook's
+//!   storage and may not exceed the token's mint cap, if any.
+//!
+//! Any other storage key changes, such as of the mint caps and the freezes,
+//! are allowed only with a valid signature.
+
+use std::collections::BTreeMap;
+
+use namada_vp_prelude::address::InternalAddress;
+use namada_vp_prelude::storage_api::token as token_api;
+use namada_vp_prelude::*;
+use once_cell::unsync::Lazy;
+
+#[validity_predicate]
+fn validate_tx(
+    ctx: &Ctx,
+    tx_data: Tx,
+    addr: Address,
+    keys_changed: BTreeSet<storage::Key>,
+    verifiers: BTreeSet<Address>,
+) -> VpResult {
+    debug_log!(
+        "vp_token_hook called with hook addr: {}, key_changed: {:?}, \
+         verifiers: {:?}",
+        addr,
+        keys_changed,
+        verifiers
+    );
+
+    let valid_sig = Lazy::new(|| {
+        let pk = key::get(ctx, &addr);
+        match pk {
+            Ok(Some(pk)) => tx_data
+                .verify_signature(&pk, tx_data.data_sechash())
+                .is_ok(),
+            _ => false,
+        }
+    });
+
+    if !is_valid_tx(ctx, &tx_data)? {
+        return reject();
+    }
+
+    // The changes of the supply of the hooked tokens minted through IBC
+    let mut minted: BTreeMap<Address, token::Change> = BTreeMap::new();
+    for key in keys_changed.iter() {
+        let is_valid = if let Some((token, owner)) =
+            token::is_any_token_or_multitoken_balance_key(key)
+        {
+            let pre: token::Amount = match owner {
+                Address::Internal(InternalAddress::IbcMint) => {
+                    token::Amount::max()
+                }
+                Address::Internal(InternalAddress::IbcBurn) => {
+                    token::Amount::default()
+                }
+                _ => ctx.read_pre(key)?.unwrap_or_default(),
+            };
+            let post: token::Amount = match owner {
+                Address::Internal(InternalAddress::IbcMint) => {
+                    ctx.read_temp(key)?.unwrap_or_else(token::Amount::max)
+                }
+                Address::Internal(InternalAddress::IbcBurn) => {
+                    ctx.read_temp(key)?.unwrap_or_default()
+                }
+                _ => ctx.read_post(key)?.unwrap_or_default(),
+            };
+            let change = post.change() - pre.change();
+            let hook = token_api::read_hook(&ctx.pre(), token)?;
+            if change != 0 && hook.as_ref() == Some(&addr) {
+                if token_api::is_frozen(&ctx.pre(), &addr, token, owner)? {
+                    debug_log!(
+                        "The transfers of token {} of {} are frozen",
+                        token,
+                        owner
+                    );
+                    return reject();
+                }
+                // Minting decreases the balance of the mint address, and
+                // burning increases the balance of the burn address
+                if let Address::Internal(
+                    InternalAddress::IbcMint | InternalAddress::IbcBurn,
+                ) = owner
+                {
+                    *minted.entry(token.clone()).or_default() -= change;
+                }
+            }
+            // Debit of the hook's own balances has to be signed
+            owner != &addr || change >= 0 || *valid_sig
+        } else if let Some(token) = token::is_minted_key(&addr, key) {
+            // The minted supply is validated below
+            minted.entry(token.clone()).or_default();
+            true
+        } else if let Some(owner) = key.is_validity_predicate() {
+            let has_post: bool = ctx.has_key_post(key)?;
+            if owner == &addr {
+                if has_post {
+                    let vp_hash: Vec<u8> = ctx.read_bytes_post(key)?.unwrap();
+                    return Ok(*valid_sig && is_vp_whitelisted(ctx, &vp_hash)?);
+                } else {
+                    return reject();
+                }
+            } else {
+                let vp_hash: Vec<u8> = ctx.read_bytes_post(key)?.unwrap();
+                return is_vp_whitelisted(ctx, &vp_hash);
+            }
+        } else {
+            // Allow any other key change if authorized by a signature
+            *valid_sig
+        };
+
+        if !is_valid {
+            debug_log!("key {} modification failed vp", key);
+            return reject();
+        }
+    }
+
+    for (token, change) in minted {
+        if !minted_checks(ctx, &addr, &token, change)? {
+            debug_log!("The minted supply of token {} is invalid", token);
+            return reject();
+        }
+    }
+
+    accept()
+}
+
+/// Check that the minted supply of the given token tracked by the hook was
+/// updated with its change in the transaction, in which burning the tokens
+/// that weren't minted through IBC is ignored, and that it doesn't exceed the
+/// token's mint cap, if any.
+fn minted_checks(
+    ctx: &Ctx,
+    hook: &Address,
+    token: &Address,
+    change: token::Change,
+) -> VpResult {
+    let minted_key = token::minted_key(hook, token);
+    let pre: token::Amount = ctx.read_pre(&minted_key)?.unwrap_or_default();
+    let post: token::Amount = ctx.read_post(&minted_key)?.unwrap_or_default();
+    let expected = pre.change() + change;
+    if expected < 0 && post.is_zero() {
+        return accept();
+    }
+    if post.change() != expected {
+        return reject();
+    }
+    match token_api::read_mint_cap(&ctx.pre(), hook, token)? {
+        Some(mint_cap) if change > 0 => Ok(post <= mint_cap),
+        _ => accept(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::proto::{Data, Signature};
+    use namada::types::transaction::TxType;
+    // Use this as `#[test]` annotation to enable logging
+    use namada_tests::log::test;
+    use namada_tests::tx::{self, tx_host_env, TestTxEnv};
+    use namada_tests::vp::*;
+    use namada_tx_prelude::StorageWrite;
+    use namada_vp_prelude::key::RefTo;
+
+    use super::*;
+
+    /// Initialize a tx environment with a token hooked by the given hook
+    fn init_hooked_token(
+        hook: &Address,
+        src: &Address,
+        dest: &Address,
+    ) -> (TestTxEnv, Address) {
+        let mut tx_env = TestTxEnv::default();
+        let token = address::nam();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&token, hook, src, dest]);
+        token_api::credit_tokens(
+            &mut tx_env.wl_storage,
+            &token,
+            src,
+            token::Amount::from(10_098_123),
+        )
+        .unwrap();
+        token_api::write_hook(&mut tx_env.wl_storage, &token, hook).unwrap();
+        (tx_env, token)
+    }
+
+    /// Validate a transfer of the given token with the hook's VP
+    fn validate_transfer(
+        tx_env: TestTxEnv,
+        hook: &Address,
+        token: &Address,
+        src: &Address,
+        dest: &Address,
+    ) -> bool {
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(hook.clone(), tx_env, |_address| {
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                src,
+                dest,
+                token,
+                None,
+                token::Amount::from(100),
+                &None,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        let vp_env = vp_host_env::take();
+        let mut tx_data = Tx::new(TxType::Raw);
+        tx_data.set_data(Data::new(vec![]));
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers = vp_env.get_verifiers();
+        vp_host_env::set(vp_env);
+
+        validate_tx(&CTX, tx_data, hook.clone(), keys_changed, verifiers)
+            .unwrap()
+    }
+
+    /// Test that a transfer that isn't frozen is accepted
+    #[test]
+    fn test_transfer_accepted() {
+        let hook = address::testing::established_address_3();
+        let src = address::testing::established_address_1();
+        let dest = address::testing::established_address_2();
+        let (mut tx_env, token) = init_hooked_token(&hook, &src, &dest);
+        // Freeze the transfers of another account only
+        tx_env
+            .wl_storage
+            .write(&token::frozen_account_key(&hook, &token, &hook), true)
+            .unwrap();
+        tx_env.commit_tx_and_block();
+
+        assert!(validate_transfer(tx_env, &hook, &token, &src, &dest));
+    }
+
+    /// Test that a transfer from a frozen account is rejected
+    #[test]
+    fn test_frozen_account_transfer_rejected() {
+        let hook = address::testing::established_address_3();
+        let src = address::testing::established_address_1();
+        let dest = address::testing::established_address_2();
+        let (mut tx_env, token) = init_hooked_token(&hook, &src, &dest);
+        tx_env
+            .wl_storage
+            .write(&token::frozen_account_key(&hook, &token, &src), true)
+            .unwrap();
+        tx_env.commit_tx_and_block();
+
+        assert!(!validate_transfer(tx_env, &hook, &token, &src, &dest));
+    }
+
+    /// Test that a transfer of a frozen token is rejected
+    #[test]
+    fn test_frozen_token_transfer_rejected() {
+        let hook = address::testing::established_address_3();
+        let src = address::testing::established_address_1();
+        let dest = address::testing::established_address_2();
+        let (mut tx_env, token) = init_hooked_token(&hook, &src, &dest);
+        tx_env
+            .wl_storage
+            .write(&token::frozen_key(&hook, &token), true)
+            .unwrap();
+        tx_env.commit_tx_and_block();
+
+        assert!(!validate_transfer(tx_env, &hook, &token, &src, &dest));
+    }
+
+    /// Test that minting over the mint cap is rejected
+    #[test]
+    fn test_mint_over_cap_rejected() {
+        let hook = address::testing::established_address_3();
+        let mint = address::Address::Internal(InternalAddress::IbcMint);
+        let dest = address::testing::established_address_2();
+        let (mut tx_env, token) = init_hooked_token(&hook, &dest, &dest);
+        tx_env
+            .wl_storage
+            .write(&token::mint_cap_key(&hook, &token), token::Amount::from(99))
+            .unwrap();
+        tx_env.commit_tx_and_block();
+
+        assert!(!validate_transfer(tx_env, &hook, &token, &mint, &dest));
+    }
+
+    /// Test that minting under the mint cap is accepted
+    #[test]
+    fn test_mint_under_cap_accepted() {
+        let hook = address::testing::established_address_3();
+        let mint = address::Address::Internal(InternalAddress::IbcMint);
+        let dest = address::testing::established_address_2();
+        let (mut tx_env, token) = init_hooked_token(&hook, &dest, &dest);
+        tx_env
+            .wl_storage
+            .write(
+                &token::mint_cap_key(&hook, &token),
+                token::Amount::from(100),
+            )
+            .unwrap();
+        tx_env.commit_tx_and_block();
+
+        assert!(validate_transfer(tx_env, &hook, &token, &mint, &dest));
+    }
+
+    /// Write a mint cap in a transaction and validate it with the hook's VP,
+    /// with a signature of the hook's key if `signed`
+    fn validate_mint_cap_change(signed: bool) -> bool {
+        let mut tx_env = TestTxEnv::default();
+        let hook = address::testing::established_address_3();
+        let token = address::nam();
+        let keypair = key::testing::keypair_1();
+        let public_key = keypair.ref_to();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&hook, &token]);
+        tx_env.write_public_key(&hook, &public_key);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(hook.clone(), tx_env, |_address| {
+            tx::ctx()
+                .write(
+                    &token::mint_cap_key(&hook, &token),
+                    token::Amount::from(100),
+                )
+                .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        tx.set_data(Data::new(vec![]));
+        if signed {
+            tx.add_section(Section::Signature(Signature::new(
+                tx.data_sechash(),
+                &keypair,
+            )));
+        }
+        vp_env.tx = tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers = vp_env.get_verifiers();
+        vp_host_env::set(vp_env);
+
+        validate_tx(&CTX, tx, hook, keys_changed, verifiers).unwrap()
+    }
+
+    /// Test that the unsigned change of a mint cap is rejected
+    #[test]
+    fn test_unsigned_mint_cap_change_rejected() {
+        assert!(!validate_mint_cap_change(false));
+    }
+
+    /// Test that the signed change of a mint cap is accepted
+    #[test]
+    fn test_signed_mint_cap_change_accepted() {
+        assert!(validate_mint_cap_change(true));
+    }
+}
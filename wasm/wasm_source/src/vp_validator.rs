@@ -137,8 +137,16 @@ fn validate_tx(
                     Some(source) => *source != addr || *valid_sig,
                     None => true,
                 };
-                let valid =
-                    valid_bond_or_unbond_change && valid_commission_rate_change;
+                // Validator's consensus key change must be signed
+                let valid_consensus_key_change = key
+                    .split_prefix(&proof_of_stake::validator_consensus_key_key(
+                        &addr,
+                    ))
+                    .is_none()
+                    || *valid_sig;
+                let valid = valid_bond_or_unbond_change
+                    && valid_commission_rate_change
+                    && valid_consensus_key_change;
                 debug_log!(
                     "PoS key {} {}",
                     key,